@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use bevy_cobweb_ui::prelude::cob::*;
+
+use crate::files::{file_span, read_file};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Parses files with error recovery and prints every error found.
+///
+/// Returns `true` if all files parsed without errors.
+pub(crate) fn run(files: &[PathBuf]) -> bool
+{
+    let mut num_errors = 0;
+    let mut num_failed_files = 0;
+
+    for path in files.iter() {
+        let Some(content) = read_file(path) else {
+            num_failed_files += 1;
+            continue;
+        };
+        let file = path.to_string_lossy();
+
+        let diagnostics = match Cob::parse_recoverable(file_span(&file, &content)) {
+            Ok(recovery) => recovery.diagnostics,
+            Err(err) => CobDiagnostic::from_span_error(&err).into_iter().collect(),
+        };
+        if diagnostics.is_empty() {
+            continue;
+        }
+
+        for diagnostic in diagnostics.iter() {
+            eprintln!("{}\n", diagnostic);
+        }
+        num_errors += diagnostics.len();
+        num_failed_files += 1;
+    }
+
+    if num_failed_files == 0 {
+        println!("checked {} file(s), no errors found", files.len());
+        return true;
+    }
+
+    eprintln!("checked {} file(s), found {} error(s) in {} file(s)", files.len(), num_errors, num_failed_files);
+    false
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,198 @@
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use bevy_cobweb_ui::prelude::cob::*;
+
+use crate::files::{parse_file, read_file};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+const INDENT: &str = "    ";
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn push_line(out: &mut String, depth: usize, line: impl std::fmt::Display)
+{
+    for _ in 0..depth {
+        out.push_str(INDENT);
+    }
+    let _ = writeln!(out, "{}", line);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn dump_layer_entries(out: &mut String, depth: usize, entries: &[CobSceneLayerEntry])
+{
+    for entry in entries.iter() {
+        match entry {
+            CobSceneLayerEntry::Loadable(loadable) => push_line(out, depth, loadable.id.to_canonical(None)),
+            CobSceneLayerEntry::LoadableMacroCall(call) => push_line(out, depth, format!("{}!", call.path)),
+            CobSceneLayerEntry::SceneMacroCall(call) => {
+                push_line(out, depth, format!("+{}", call.path));
+                for param in call.param_defs.iter() {
+                    push_line(out, depth + 1, format!("'{}'", param.name));
+                    dump_layer_entries(out, depth + 2, &param.entries);
+                }
+            }
+            CobSceneLayerEntry::Layer(layer) => dump_layer(out, depth, layer),
+            CobSceneLayerEntry::SceneMacroParam(param) => match &param.name {
+                Some(name) => push_line(out, depth, format!("..'{}'", name)),
+                None => push_line(out, depth, "..*"),
+            },
+            CobSceneLayerEntry::LoadableRemoval(removal) => {
+                push_line(out, depth, format!("-{}", removal.id.to_canonical(None)))
+            }
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn dump_layer(out: &mut String, depth: usize, layer: &CobSceneLayer)
+{
+    match &layer.base {
+        Some(CobSceneBase { file: Some(file), name, .. }) => push_line(
+            out,
+            depth,
+            format!("\"{}\" : {}::\"{}\"", layer.name.as_str(), file.as_str(), name.as_str()),
+        ),
+        Some(CobSceneBase { file: None, name, .. }) => push_line(
+            out,
+            depth,
+            format!("\"{}\" : \"{}\"", layer.name.as_str(), name.as_str()),
+        ),
+        None => push_line(out, depth, format!("\"{}\"", layer.name.as_str())),
+    }
+    dump_layer_entries(out, depth + 1, &layer.entries);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn condition_string(condition: &CobCondition) -> String
+{
+    let flag = if condition.negated {
+        format!("!{}", condition.flag)
+    } else {
+        condition.flag.to_string()
+    };
+    match &condition.comparison {
+        Some(comparison) => format!("{} {} \"{}\"", flag, comparison.op.as_str(), comparison.value.as_str()),
+        None => flag,
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn dump_section(out: &mut String, depth: usize, section: &CobSection)
+{
+    match section {
+        CobSection::Manifest(manifest) => {
+            push_line(out, depth, "#manifest");
+            for entry in manifest.entries.iter() {
+                let file = match &entry.file {
+                    CobManifestFile::SelfRef => String::from("self"),
+                    CobManifestFile::File(file) => format!("\"{}\"", file.as_str()),
+                };
+                push_line(out, depth + 1, format!("{} as {}", file, entry.key.as_str()));
+            }
+        }
+        CobSection::Import(import) => {
+            push_line(out, depth, "#import");
+            for entry in import.entries.iter() {
+                let alias = match &entry.alias {
+                    CobImportAlias::None => "_",
+                    CobImportAlias::Alias(alias) => alias.as_str(),
+                };
+                push_line(out, depth + 1, format!("{} as {}", entry.key.as_str(), alias));
+            }
+        }
+        CobSection::Defs(defs) => {
+            push_line(out, depth, "#defs");
+            for entry in defs.entries.iter() {
+                match entry {
+                    CobDefEntry::Constant(def) => push_line(out, depth + 1, format!("${}", def.name.as_str())),
+                    CobDefEntry::DataMacro(def) => push_line(out, depth + 1, format!("{}!", def.name)),
+                    CobDefEntry::LoadableMacro(def) => push_line(out, depth + 1, format!("{}!", def.name)),
+                    CobDefEntry::SceneMacro(def) => {
+                        push_line(out, depth + 1, format!("+{}", def.name));
+                        dump_layer_entries(out, depth + 2, &def.entries);
+                    }
+                }
+            }
+        }
+        CobSection::Theme(theme) => {
+            push_line(out, depth, format!("#theme({})", theme.name));
+            for def in theme.entries.iter() {
+                push_line(out, depth + 1, format!("${}", def.name.as_str()));
+            }
+        }
+        CobSection::Commands(commands) => {
+            push_line(out, depth, "#commands");
+            for entry in commands.entries.iter() {
+                match entry {
+                    CobCommandEntry::Loadable(loadable) => {
+                        push_line(out, depth + 1, loadable.id.to_canonical(None))
+                    }
+                    CobCommandEntry::LoadableMacroCall(call) => {
+                        push_line(out, depth + 1, format!("{}!", call.path))
+                    }
+                }
+            }
+        }
+        CobSection::Scenes(scenes) => {
+            push_line(out, depth, "#scenes");
+            for layer in scenes.scenes.iter() {
+                dump_layer(out, depth + 1, layer);
+            }
+        }
+        CobSection::Conditional(conditional) => {
+            push_line(out, depth, format!("#if({})", condition_string(&conditional.condition)));
+            for section in conditional.sections.iter() {
+                dump_section(out, depth + 1, section);
+            }
+            if let Some(branch) = &conditional.else_branch {
+                push_line(out, depth, "#else");
+                for section in branch.sections.iter() {
+                    dump_section(out, depth + 1, section);
+                }
+            }
+            push_line(out, depth, "#endif");
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Prints the section and scene tree of each file.
+///
+/// Returns `false` if any file failed to parse.
+pub(crate) fn run(files: &[PathBuf]) -> bool
+{
+    let mut success = true;
+
+    for (idx, path) in files.iter().enumerate() {
+        let Some(content) = read_file(path) else {
+            success = false;
+            continue;
+        };
+        let file = path.to_string_lossy();
+        let Some(cob) = parse_file(&file, &content) else {
+            success = false;
+            continue;
+        };
+
+        let mut out = String::default();
+        if idx > 0 {
+            out.push('\n');
+        }
+        push_line(&mut out, 0, format!("// {}", file));
+        for section in cob.sections.iter() {
+            dump_section(&mut out, 0, section);
+        }
+        print!("{}", out);
+    }
+
+    success
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,97 @@
+use std::path::{Path, PathBuf};
+
+use bevy_cobweb_ui::prelude::cob::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn is_cob_file(path: &Path) -> bool
+{
+    path.extension().is_some_and(|ext| ext == "cob")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn collect_dir(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String>
+{
+    let entries = std::fs::read_dir(dir).map_err(|err| format!("failed reading {}: {}", dir.display(), err))?;
+    let mut paths = entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("failed reading {}: {}", dir.display(), err))?;
+
+    // Sort so output is stable across platforms.
+    paths.sort();
+
+    for path in paths {
+        if path.is_dir() {
+            collect_dir(&path, files)?;
+        } else if is_cob_file(&path) {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Collects `.cob` files from a list of file and directory paths.
+pub(crate) fn collect_cob_files(paths: &[String]) -> Result<Vec<PathBuf>, String>
+{
+    let mut files = vec![];
+
+    for path in paths.iter().map(PathBuf::from) {
+        if path.is_dir() {
+            collect_dir(&path, &mut files)?;
+        } else if !path.exists() {
+            return Err(format!("{} does not exist", path.display()));
+        } else if !is_cob_file(&path) {
+            return Err(format!("{} is not a .cob file", path.display()));
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Reads a file to a string, printing an error on failure.
+pub(crate) fn read_file(path: &Path) -> Option<String>
+{
+    match std::fs::read_to_string(path) {
+        Ok(content) => Some(content),
+        Err(err) => {
+            eprintln!("error: failed reading {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Makes a span for parsing a file's content.
+pub(crate) fn file_span<'a>(file: &'a str, content: &'a str) -> Span<'a>
+{
+    Span::new_extra(content, CobLocationMetadata { file })
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Parses a file, printing the error on failure.
+pub(crate) fn parse_file(file: &str, content: &str) -> Option<Cob>
+{
+    match Cob::parse(file_span(file, content)) {
+        Ok(cob) => Some(cob),
+        Err(err) => {
+            match CobDiagnostic::from_span_error(&err) {
+                Some(diagnostic) => eprintln!("{}\n", diagnostic),
+                None => eprintln!("error: failed parsing {}: {:?}\n", file, err),
+            }
+            None
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+use bevy_cobweb_ui::prelude::cob::*;
+
+use crate::files::{parse_file, read_file};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Rewrites files in the canonical layout produced by [`Cob::format`].
+///
+/// If `check_only` is set then files are not modified, and files that would change are listed instead.
+///
+/// Returns `false` if any file failed to parse or, in check mode, if any file would change.
+pub(crate) fn run(files: &[PathBuf], check_only: bool, style: &CobFormatStyle) -> bool
+{
+    let mut success = true;
+    let mut num_changed = 0;
+
+    for path in files.iter() {
+        let Some(content) = read_file(path) else {
+            success = false;
+            continue;
+        };
+        let file = path.to_string_lossy();
+
+        // Broken files are never rewritten.
+        let Some(mut cob) = parse_file(&file, &content) else {
+            success = false;
+            continue;
+        };
+        cob.format(style);
+
+        let mut formatted = Vec::with_capacity(content.len());
+        if let Err(err) = cob.write_to(&mut DefaultRawSerializer::new(&mut formatted)) {
+            eprintln!("error: failed formatting {}: {}", file, err);
+            success = false;
+            continue;
+        }
+        if formatted == content.as_bytes() {
+            continue;
+        }
+
+        num_changed += 1;
+        if check_only {
+            println!("would reformat {}", file);
+            continue;
+        }
+        if let Err(err) = std::fs::write(path, &formatted) {
+            eprintln!("error: failed writing {}: {}", file, err);
+            success = false;
+            continue;
+        }
+        println!("reformatted {}", file);
+    }
+
+    if check_only && num_changed > 0 {
+        eprintln!("{} of {} file(s) would be reformatted", num_changed, files.len());
+        return false;
+    }
+
+    success
+}
+
+//-------------------------------------------------------------------------------------------------------------------
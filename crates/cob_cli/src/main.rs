@@ -0,0 +1,117 @@
+//! Headless tool for working with COB files.
+//!
+//! ```text
+//! cob check <paths>...          Parse files and report all errors.
+//! cob fmt [options] <paths>...  Rewrite files in the canonical COB layout.
+//! cob dump <paths>...           Print the section and scene tree of files.
+//! ```
+//!
+//! Directories are searched recursively for `.cob` files. The exit code is `1` if any problems were found, and
+//! `2` if the arguments are invalid.
+
+mod check;
+mod dump;
+mod files;
+mod fmt;
+
+use std::process::ExitCode;
+
+use bevy_cobweb_ui::prelude::cob::CobFormatStyle;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+const USAGE: &str = "usage:
+    cob check <paths>...          Parse files and report all errors.
+    cob fmt [options] <paths>...  Rewrite files in the canonical COB layout.
+    cob dump <paths>...           Print the section and scene tree of files.
+
+fmt options:
+    --check            Only report files that would change.
+    --max-width <n>    Wrap maps that extend past this column (default 115).
+    --indent <n>       Spaces per indentation level (default 4).
+    --sort-imports     Sort #import entries. Manifest entries are never sorted.
+
+Directories are searched recursively for .cob files.";
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn usage_error(message: &str) -> ExitCode
+{
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    ExitCode::from(2)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Parses the value of a numeric option.
+fn option_value(option: &str, value: Option<String>) -> Result<usize, ExitCode>
+{
+    let Some(value) = value else { return Err(usage_error(&format!("missing value for '{}'", option))) };
+    match value.parse::<usize>() {
+        Ok(value) if value > 0 => Ok(value),
+        _ => Err(usage_error(&format!("invalid value '{}' for '{}'", value, option))),
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn main() -> ExitCode
+{
+    let mut args = std::env::args().skip(1);
+    let Some(command) = args.next() else { return usage_error("missing command") };
+
+    let mut check_only = false;
+    let mut style = CobFormatStyle::default();
+    let mut paths = vec![];
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" if command == "fmt" => check_only = true,
+            "--sort-imports" if command == "fmt" => style.sort_imports = true,
+            "--max-width" if command == "fmt" => match option_value(&arg, args.next()) {
+                Ok(value) => style.max_width = value,
+                Err(code) => return code,
+            },
+            "--indent" if command == "fmt" => match option_value(&arg, args.next()) {
+                Ok(value) => style.indent_width = value,
+                Err(code) => return code,
+            },
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            _ if arg.starts_with('-') => return usage_error(&format!("unknown option '{}'", arg)),
+            _ => paths.push(arg),
+        }
+    }
+
+    if matches!(command.as_str(), "-h" | "--help" | "help") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+    if paths.is_empty() {
+        return usage_error("no files specified");
+    }
+
+    let files = match files::collect_cob_files(&paths) {
+        Ok(files) => files,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let success = match command.as_str() {
+        "check" => check::run(&files),
+        "fmt" => fmt::run(&files, check_only, &style),
+        "dump" => dump::run(&files),
+        _ => return usage_error(&format!("unknown command '{}'", command)),
+    };
+
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,201 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+const FORMATTED_COB: &str = "#manifest
+self as main
+
+#scenes
+\"a\"
+    Node{width:10}
+
+    \"b\"
+        Text{text:\"hi\"}
+";
+
+const UNFORMATTED_COB: &str = "#scenes
+\"a\"
+  Node{width:10   height:5}
+";
+
+const BROKEN_COB: &str = "// header
+?
+";
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Makes an empty directory for one test.
+fn make_dir(test: &str) -> PathBuf
+{
+    let dir = std::env::temp_dir().join(format!("cob_cli_{}_{}", test, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write_file(dir: &Path, name: &str, content: &str) -> String
+{
+    let path = dir.join(name);
+    std::fs::write(&path, content).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+fn run_cob(args: &[&str]) -> Output
+{
+    Command::new(env!("CARGO_BIN_EXE_cob"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String
+{
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String
+{
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+/// Checks that `stderr` contains a diagnostic for the `?` in [`BROKEN_COB`].
+fn assert_broken_diagnostic(stderr: &str, file: &str)
+{
+    let expected = format!(
+        "
+ --> {}:2:1
+  |
+2 | ?
+  | ^",
+        file
+    );
+    assert!(stderr.contains("error: expected a section"), "{}", stderr);
+    assert!(stderr.contains(&expected), "{}", stderr);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn check()
+{
+    let dir = make_dir("check");
+    let good = write_file(&dir, "good.cob", FORMATTED_COB);
+
+    let output = run_cob(&["check", &good]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "checked 1 file(s), no errors found\n");
+
+    // Errors are reported with their location, and directories are searched.
+    let broken = write_file(&dir, "broken.cob", BROKEN_COB);
+    let output = run_cob(&["check", dir.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    let err = stderr(&output);
+    assert_broken_diagnostic(&err, &broken);
+    assert!(err.ends_with("checked 2 file(s), found 1 error(s) in 1 file(s)\n"), "{}", err);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn fmt_check()
+{
+    let dir = make_dir("fmt_check");
+    let good = write_file(&dir, "good.cob", FORMATTED_COB);
+    let messy = write_file(&dir, "messy.cob", UNFORMATTED_COB);
+
+    let output = run_cob(&["fmt", "--check", &good]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "");
+
+    let output = run_cob(&["fmt", "--check", &good, &messy]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), format!("would reformat {}\n", messy));
+    assert_eq!(stderr(&output), "1 of 2 file(s) would be reformatted\n");
+
+    // Check mode never rewrites files.
+    assert_eq!(std::fs::read_to_string(&messy).unwrap(), UNFORMATTED_COB);
+
+    // Broken files are reported and never rewritten.
+    let broken = write_file(&dir, "broken.cob", BROKEN_COB);
+    let output = run_cob(&["fmt", "--check", &broken]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_broken_diagnostic(&stderr(&output), &broken);
+    assert_eq!(std::fs::read_to_string(&broken).unwrap(), BROKEN_COB);
+
+    // Without --check, files are rewritten.
+    let output = run_cob(&["fmt", &messy]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(stdout(&output), format!("reformatted {}\n", messy));
+    assert_eq!(std::fs::read_to_string(&messy).unwrap(), "#scenes\n\"a\"\n    Node{width:10 height:5}\n");
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn dump()
+{
+    let dir = make_dir("dump");
+    let good = write_file(&dir, "good.cob", FORMATTED_COB);
+
+    let output = run_cob(&["dump", &good]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "// {}
+#manifest
+    self as main
+#scenes
+    \"a\"
+        Node
+        \"b\"
+            Text
+",
+            good
+        )
+    );
+
+    let broken = write_file(&dir, "broken.cob", BROKEN_COB);
+    let output = run_cob(&["dump", &broken]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_broken_diagnostic(&stderr(&output), &broken);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn invalid_arguments()
+{
+    let dir = make_dir("invalid_arguments");
+    let good = write_file(&dir, "good.cob", FORMATTED_COB);
+    let text = write_file(&dir, "notes.txt", "");
+
+    // Usage errors.
+    assert_eq!(run_cob(&[]).status.code(), Some(2));
+    assert_eq!(run_cob(&["check"]).status.code(), Some(2));
+    assert_eq!(run_cob(&["check", "--bogus", &good]).status.code(), Some(2));
+    assert_eq!(run_cob(&["check", "--check", &good]).status.code(), Some(2));
+    assert_eq!(run_cob(&["lint", &good]).status.code(), Some(2));
+    assert_eq!(run_cob(&["fmt", "--indent", "0", &good]).status.code(), Some(2));
+    assert_eq!(run_cob(&["fmt", "--max-width"]).status.code(), Some(2));
+
+    // Bad paths.
+    let missing = dir.join("missing.cob");
+    let output = run_cob(&["check", missing.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("does not exist"));
+    let output = run_cob(&["check", &text]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("is not a .cob file"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
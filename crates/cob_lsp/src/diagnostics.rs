@@ -0,0 +1,210 @@
+use bevy_cobweb_ui::prelude::cob::*;
+use lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+
+use crate::text::{byte_range, find_word, is_ident_char, offset_to_position, token_at};
+use crate::workspace::CobFileIndex;
+use crate::CobSchema;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn make_diagnostic(range: Range, severity: DiagnosticSeverity, message: &str, hint: Option<&str>) -> Diagnostic
+{
+    let message = match hint {
+        Some(hint) => format!("{}\nhint: {}", message, hint),
+        None => String::from(message),
+    };
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        source: Some(String::from("cob")),
+        message,
+        ..Default::default()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Converts a parse error to an LSP diagnostic covering the token at the error location.
+fn convert_parse_diagnostic(text: &str, diagnostic: &CobDiagnostic) -> Diagnostic
+{
+    let range = match &diagnostic.location {
+        Some(location) => {
+            let start = location.offset.min(text.len());
+            let end = token_at(text, start, is_ident_char)
+                .map(|(_, end)| end)
+                .unwrap_or(start);
+            byte_range(text, start, end)
+        }
+        None => Range::new(Position::new(0, 0), Position::new(0, 0)),
+    };
+    make_diagnostic(
+        range,
+        DiagnosticSeverity::ERROR,
+        &diagnostic.message,
+        diagnostic.hint.as_deref(),
+    )
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Checks loadable names and struct fields against the schema.
+///
+/// The parsed file has no source locations, so loadables are located by searching forward through the text in
+/// the same order they appear in the file.
+struct SchemaChecker<'a>
+{
+    text: &'a str,
+    schema: &'a CobSchema,
+    cursor: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> SchemaChecker<'a>
+{
+    /// Finds the next occurrence of `word` and moves the cursor past it.
+    fn advance(&mut self, word: &str) -> Range
+    {
+        match find_word(self.text, word, self.cursor) {
+            Some(start) => {
+                self.cursor = start + word.len();
+                byte_range(self.text, start, self.cursor)
+            }
+            None => {
+                let position = offset_to_position(self.text, self.cursor);
+                Range::new(position, position)
+            }
+        }
+    }
+
+    fn warn(&mut self, range: Range, message: String, hint: Option<String>)
+    {
+        self.diagnostics.push(make_diagnostic(
+            range,
+            DiagnosticSeverity::WARNING,
+            &message,
+            hint.as_deref(),
+        ));
+    }
+
+    fn check_loadable(&mut self, loadable: &CobLoadable)
+    {
+        let range = self.advance(loadable.id.name.as_str());
+        let name = loadable.id.to_canonical(None);
+        let Some(schema) = self.schema.get(&name) else {
+            let hint = find_similar_name(&name, self.schema.loadables.keys().map(|key| key.as_str()))
+                .map(|similar| format!("did you mean `{}`?", similar));
+            self.warn(
+                range,
+                format!("no loadable named `{}` was registered in the app", name),
+                hint,
+            );
+            return;
+        };
+
+        // Only structs with known fields can be checked.
+        let CobLoadableVariant::Map(map) = &loadable.variant else { return };
+        if schema.fields.is_empty() {
+            return;
+        }
+        for entry in map.entries.iter() {
+            let CobMapEntry::KeyValue(CobMapKeyValue { key: CobMapKey::FieldName { name: field, .. }, .. }) =
+                entry
+            else {
+                continue;
+            };
+            let range = self.advance(field.as_str());
+            if schema.fields.iter().any(|f| f.name == field.as_str()) {
+                continue;
+            }
+            let hint = find_similar_name(field, schema.fields.iter().map(|f| f.name.as_str()))
+                .map(|similar| format!("did you mean `{}`?", similar));
+            self.warn(range, format!("`{}` has no field named `{}`", name, field), hint);
+        }
+    }
+
+    fn check_layer_entries(&mut self, entries: &[CobSceneLayerEntry])
+    {
+        for entry in entries.iter() {
+            match entry {
+                CobSceneLayerEntry::Loadable(loadable) => self.check_loadable(loadable),
+                CobSceneLayerEntry::SceneMacroCall(call) => {
+                    for param in call.param_defs.iter() {
+                        self.check_layer_entries(&param.entries);
+                    }
+                }
+                CobSceneLayerEntry::Layer(layer) => self.check_layer_entries(&layer.entries),
+                CobSceneLayerEntry::LoadableMacroCall(_)
+                | CobSceneLayerEntry::SceneMacroParam(_)
+                | CobSceneLayerEntry::LoadableRemoval(_) => (),
+            }
+        }
+    }
+
+    fn check_defs(&mut self, defs: &CobDefs)
+    {
+        for entry in defs.entries.iter() {
+            match entry {
+                CobDefEntry::LoadableMacro(def) => match &def.value {
+                    CobLoadableMacroValue::Loadable(loadable) => self.check_loadable(loadable),
+                    CobLoadableMacroValue::Group(group) => {
+                        for entry in group.entries.iter() {
+                            if let CobLoadableGroupEntry::Loadable(loadable) = entry {
+                                self.check_loadable(loadable);
+                            }
+                        }
+                    }
+                    CobLoadableMacroValue::LoadableMacroCall(_) => (),
+                },
+                CobDefEntry::SceneMacro(def) => self.check_layer_entries(&def.entries),
+                CobDefEntry::Constant(_) | CobDefEntry::DataMacro(_) => (),
+            }
+        }
+    }
+
+    fn check(&mut self, cob: &Cob)
+    {
+        for section in cob.all_sections() {
+            match section {
+                CobSection::Manifest(_)
+                | CobSection::Import(_)
+                | CobSection::Theme(_)
+                | CobSection::Conditional(_) => (),
+                CobSection::Defs(defs) => self.check_defs(defs),
+                CobSection::Commands(commands) => {
+                    for entry in commands.entries.iter() {
+                        if let CobCommandEntry::Loadable(loadable) = entry {
+                            self.check_loadable(loadable);
+                        }
+                    }
+                }
+                CobSection::Scenes(scenes) => {
+                    for layer in scenes.scenes.iter() {
+                        self.check_layer_entries(&layer.entries);
+                    }
+                }
+            }
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Collects parse errors for a file, and schema warnings if a schema is available.
+pub(crate) fn file_diagnostics(index: &CobFileIndex, schema: &CobSchema) -> Vec<Diagnostic>
+{
+    let mut diagnostics: Vec<_> = index
+        .diagnostics
+        .iter()
+        .map(|diagnostic| convert_parse_diagnostic(&index.text, diagnostic))
+        .collect();
+
+    if let (Some(cob), false) = (&index.cob, schema.is_empty()) {
+        let mut checker = SchemaChecker { text: &index.text, schema, cursor: 0, diagnostics: vec![] };
+        checker.check(cob);
+        diagnostics.extend(checker.diagnostics);
+    }
+
+    diagnostics
+}
+
+//-------------------------------------------------------------------------------------------------------------------
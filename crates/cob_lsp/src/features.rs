@@ -0,0 +1,221 @@
+use bevy_cobweb_ui::prelude::CobDefKind;
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionTextEdit, Documentation, Hover, HoverContents, Location,
+    MarkupContent, MarkupKind, Position, Range, TextEdit, Url,
+};
+
+use crate::text::*;
+use crate::workspace::Workspace;
+use crate::CobSchema;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn file_location(workspace: &Workspace, file: &str, range: Range) -> Option<Location>
+{
+    let uri = Url::from_file_path(workspace.path_of(file)).ok()?;
+    Some(Location::new(uri, range))
+}
+
+fn def_location(workspace: &Workspace, file: &str, kind: CobDefKind, path: &str) -> Option<Location>
+{
+    let (def_file, name) = workspace.resolve_def(file, kind, path)?;
+    let def_index = workspace.get(&def_file)?;
+    let (def_start, def_end) = def_index.find_def(kind, &name)?;
+    file_location(workspace, &def_file, byte_range(&def_index.text, def_start, def_end))
+}
+
+fn def_completions(workspace: &Workspace, file: &str, kind: CobDefKind) -> impl Iterator<Item = CompletionItem>
+{
+    workspace
+        .visible_defs(file, kind)
+        .into_iter()
+        .map(move |path| {
+            let (label, insert_text, detail) = match kind {
+                CobDefKind::Constant => (format!("${}", path), format!("${}", path), "constant"),
+                CobDefKind::DataMacro => (format!("{}!", path), format!("{}!()", path), "data macro"),
+                CobDefKind::LoadableMacro => (format!("{}!", path), format!("{}!()", path), "loadable macro"),
+                CobDefKind::SceneMacro => (format!("+{}", path), format!("+{}()", path), "scene macro"),
+            };
+            CompletionItem {
+                label,
+                insert_text: Some(insert_text),
+                kind: Some(CompletionItemKind::FUNCTION),
+                detail: Some(String::from(detail)),
+                ..Default::default()
+            }
+        })
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Finds the definition of the `$constant`, `macro!`, `+scene_macro` or manifest key at `offset`.
+pub(crate) fn definition(workspace: &Workspace, file: &str, offset: usize) -> Option<Location>
+{
+    let text = &workspace.get(file)?.text;
+
+    // Constants.
+    if let Some((start, end)) = constant_at(text, offset) {
+        return def_location(workspace, file, CobDefKind::Constant, &text[start + 1..end]);
+    }
+
+    // Macros. Data and loadable macros are called the same way, so the first kind with a matching def is used.
+    if let Some((start, end)) = macro_at(text, offset) {
+        let path = &text[start..end];
+        return [CobDefKind::DataMacro, CobDefKind::LoadableMacro]
+            .into_iter()
+            .find_map(|kind| def_location(workspace, file, kind, path));
+    }
+    if let Some((start, end)) = scene_macro_at(text, offset) {
+        return def_location(workspace, file, CobDefKind::SceneMacro, &text[start..end]);
+    }
+
+    // Manifest keys in `#import` sections, and file paths in `#manifest` sections.
+    let start_of_file = Range::new(Position::new(0, 0), Position::new(0, 0));
+    let context = cursor_context(text, offset);
+    let (line_start, line) = line_at(text, offset);
+    match context.section {
+        Some("#import") => {
+            let key = line.split_whitespace().next()?;
+            let key_start = line_start + line.find(key)?;
+            if offset < key_start || offset > key_start + key.len() {
+                return None;
+            }
+            let target = workspace.manifest_file(key)?;
+            file_location(workspace, &target, start_of_file)
+        }
+        Some("#manifest") => {
+            let first = line.split_whitespace().next()?;
+            let target = match first {
+                "self" => String::from(file),
+                _ => String::from(first.strip_prefix('"')?.strip_suffix('"')?),
+            };
+            file_location(workspace, &target, start_of_file)
+        }
+        _ => None,
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Suggests constants, macros, struct fields, or loadable names depending on where the cursor is.
+pub(crate) fn completion(
+    workspace: &Workspace,
+    schema: &CobSchema,
+    file: &str,
+    offset: usize,
+) -> Vec<CompletionItem>
+{
+    let Some(index) = workspace.get(file) else { return vec![] };
+    let text = &index.text;
+    let context = cursor_context(text, offset);
+    if context.in_comment_or_string {
+        return vec![];
+    }
+
+    // Constants replace the whole `$path` token, since paths contain `::`.
+    if let Some((start, end)) = constant_at(text, offset) {
+        let range = byte_range(text, start, end);
+        return workspace
+            .visible_defs(file, CobDefKind::Constant)
+            .into_iter()
+            .map(|name| {
+                let label = format!("${}", name);
+                CompletionItem {
+                    kind: Some(CompletionItemKind::CONSTANT),
+                    text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(range, label.clone()))),
+                    label,
+                    ..Default::default()
+                }
+            })
+            .collect();
+    }
+
+    match context.open_bracket {
+        // Fields of the loadable that owns the map.
+        Some(('{', bracket)) => {
+            let Some(loadable) = ident_before_bracket(text, bracket).and_then(|name| schema.get(name)) else {
+                return def_completions(workspace, file, CobDefKind::DataMacro).collect();
+            };
+            loadable
+                .fields
+                .iter()
+                .map(|field| CompletionItem {
+                    label: field.name.clone(),
+                    kind: Some(CompletionItemKind::FIELD),
+                    detail: Some(field.type_path.clone()),
+                    documentation: field.docs.clone().map(Documentation::String),
+                    insert_text: Some(format!("{}:", field.name)),
+                    ..Default::default()
+                })
+                .chain(def_completions(workspace, file, CobDefKind::DataMacro))
+                .collect()
+        }
+        // Values inside arrays, tuples and enum variants can be data macro calls.
+        Some(_) => def_completions(workspace, file, CobDefKind::DataMacro).collect(),
+        // Loadables and loadable macros can be written anywhere outside of brackets in sections that contain them.
+        None => {
+            let loadables = schema
+                .loadables
+                .iter()
+                .map(|(name, loadable)| CompletionItem {
+                    label: name.clone(),
+                    kind: Some(CompletionItemKind::STRUCT),
+                    detail: Some(loadable.kind.clone()),
+                    documentation: loadable.docs.clone().map(Documentation::String),
+                    ..Default::default()
+                });
+            match context.section {
+                Some("#defs" | "#commands") => loadables
+                    .chain(def_completions(workspace, file, CobDefKind::LoadableMacro))
+                    .collect(),
+                Some("#scenes") => loadables
+                    .chain(def_completions(workspace, file, CobDefKind::LoadableMacro))
+                    .chain(def_completions(workspace, file, CobDefKind::SceneMacro))
+                    .collect(),
+                _ => vec![],
+            }
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Shows the schema of the loadable at `offset`.
+pub(crate) fn hover(workspace: &Workspace, schema: &CobSchema, file: &str, offset: usize) -> Option<Hover>
+{
+    let text = &workspace.get(file)?.text;
+    let (start, end) = token_at(text, offset, is_ident_char)?;
+    let loadable = schema.get(&text[start..end])?;
+
+    let mut value = format!("```rust\n{}\n```\n", loadable.type_path);
+    if !loadable.kind.is_empty() {
+        value.push_str(&format!("*{}*\n", loadable.kind));
+    }
+    if let Some(docs) = &loadable.docs {
+        value.push_str(&format!("\n{}\n", docs));
+    }
+    if !loadable.fields.is_empty() {
+        value.push_str("\n**Fields**\n");
+        for field in loadable.fields.iter() {
+            match &field.default {
+                Some(default) => {
+                    value.push_str(&format!("- `{}`: `{}` = `{}`\n", field.name, field.type_path, default))
+                }
+                None => value.push_str(&format!("- `{}`: `{}`\n", field.name, field.type_path)),
+            }
+        }
+    }
+    if !loadable.variants.is_empty() {
+        value.push_str("\n**Variants**\n");
+        for variant in loadable.variants.iter() {
+            value.push_str(&format!("- `{}`\n", variant.name));
+        }
+    }
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value }),
+        range: Some(byte_range(text, start, end)),
+    })
+}
+
+//-------------------------------------------------------------------------------------------------------------------
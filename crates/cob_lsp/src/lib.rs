@@ -0,0 +1,256 @@
+//! Language server for COB files.
+//!
+//! Provides:
+//! - Parse errors as diagnostics, and warnings for unknown loadables and struct fields if a schema is available.
+//! - Go-to-definition for `$constants`, macros, and manifest keys.
+//! - Completion of constants, macros, loadable names, and struct fields.
+//! - Hover docs for loadables.
+//!
+//! Files are parsed with [`Cob::parse_recoverable`](bevy_cobweb_ui::prelude::Cob::parse_recoverable), so a
+//! broken file still gets completion and hovers for the parts that parsed. Constants and macros are resolved with
+//! [`CobResolver`](bevy_cobweb_ui::prelude::CobResolver) across all `.cob` files in the assets directory and the
+//! builtin files, the same way they are when an app loads them. `#if` blocks are evaluated against the default
+//! [`CobFlags`](bevy_cobweb_ui::prelude::CobFlags) plus the `flags` initialization option.
+//!
+//! The loadable schema is a JSON file exported by the app (see [`CobSchema`]). It can be passed to
+//! [`run_server`] directly or set with the `schema` initialization option.
+
+mod diagnostics;
+mod features;
+mod schema;
+mod text;
+mod workspace;
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::path::PathBuf;
+
+use bevy_cobweb_ui::prelude::CobFlags;
+use lsp_server::{Connection, Message, Notification, Request, Response};
+use lsp_types::{
+    CompletionOptions, HoverProviderCapability, InitializeParams, OneOf, PublishDiagnosticsParams,
+    ServerCapabilities, TextDocumentPositionParams, TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+pub use schema::*;
+use serde::Deserialize;
+
+use crate::text::position_to_offset;
+use crate::workspace::Workspace;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Custom settings sent by the client in `initializationOptions`.
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct InitOptions
+{
+    /// Directory that COB file paths are relative to. Defaults to `assets` in the workspace root.
+    assets_dir: Option<PathBuf>,
+    /// Path to a loadable schema JSON file.
+    schema: Option<PathBuf>,
+    /// Flags set on top of the default flags when evaluating `#if` blocks, e.g. `{ "device": "steam_deck" }`.
+    flags: HashMap<String, String>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn capabilities() -> ServerCapabilities
+{
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![String::from("$"), String::from("{")]),
+            ..Default::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        ..Default::default()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn workspace_root(params: &InitializeParams) -> PathBuf
+{
+    params
+        .workspace_folders
+        .as_ref()
+        .and_then(|folders| folders.first())
+        .map(|folder| &folder.uri)
+        .or(params.root_uri.as_ref())
+        .and_then(|uri| uri.to_file_path().ok())
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+struct Server
+{
+    connection: Connection,
+    workspace: Workspace,
+    schema: CobSchema,
+    /// Files with content owned by the client.
+    open_files: HashSet<String>,
+}
+
+impl Server
+{
+    fn publish_diagnostics(&self, uri: Url, file: &str) -> Result<(), Box<dyn Error + Sync + Send>>
+    {
+        let diagnostics = match self.workspace.get(file) {
+            Some(index) => diagnostics::file_diagnostics(index, &self.schema),
+            None => vec![],
+        };
+        let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+        let notification = Notification::new(String::from("textDocument/publishDiagnostics"), params);
+        self.connection
+            .sender
+            .send(Message::Notification(notification))?;
+        Ok(())
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<(), Box<dyn Error + Sync + Send>>
+    {
+        let (uri, file) = match notification.method.as_str() {
+            "textDocument/didOpen" => {
+                let params: lsp_types::DidOpenTextDocumentParams = serde_json::from_value(notification.params)?;
+                let Ok(path) = params.text_document.uri.to_file_path() else { return Ok(()) };
+                let file = self.workspace.update(&path, params.text_document.text);
+                self.open_files.insert(file.clone());
+                (params.text_document.uri, file)
+            }
+            "textDocument/didChange" => {
+                let params: lsp_types::DidChangeTextDocumentParams = serde_json::from_value(notification.params)?;
+                let Ok(path) = params.text_document.uri.to_file_path() else { return Ok(()) };
+                // Only full document syncs are requested.
+                let Some(change) = params.content_changes.into_iter().last() else { return Ok(()) };
+                let file = self.workspace.update(&path, change.text);
+                (params.text_document.uri, file)
+            }
+            "textDocument/didClose" => {
+                let params: lsp_types::DidCloseTextDocumentParams = serde_json::from_value(notification.params)?;
+                let Ok(path) = params.text_document.uri.to_file_path() else { return Ok(()) };
+                let file = self.workspace.reload(&path);
+                self.open_files.remove(&file);
+                (params.text_document.uri, file)
+            }
+            "workspace/didChangeWatchedFiles" => {
+                let params: lsp_types::DidChangeWatchedFilesParams = serde_json::from_value(notification.params)?;
+                for change in params.changes {
+                    let Ok(path) = change.uri.to_file_path() else { continue };
+                    if !self.open_files.contains(&self.workspace.file_name(&path)) {
+                        self.workspace.reload(&path);
+                    }
+                }
+                return Ok(());
+            }
+            _ => return Ok(()),
+        };
+
+        self.publish_diagnostics(uri, &file)
+    }
+
+    /// Gets the file name and byte offset of a cursor position.
+    fn locate(&self, params: &TextDocumentPositionParams) -> Option<(String, usize)>
+    {
+        let path = params.text_document.uri.to_file_path().ok()?;
+        let file = self.workspace.file_name(&path);
+        let offset = position_to_offset(&self.workspace.get(&file)?.text, params.position);
+        Some((file, offset))
+    }
+
+    fn handle_request(&mut self, request: Request) -> Result<(), Box<dyn Error + Sync + Send>>
+    {
+        let result = match request.method.as_str() {
+            "textDocument/definition" => {
+                let params: lsp_types::GotoDefinitionParams = serde_json::from_value(request.params)?;
+                let location = self
+                    .locate(&params.text_document_position_params)
+                    .and_then(|(file, offset)| features::definition(&self.workspace, &file, offset));
+                serde_json::to_value(location)?
+            }
+            "textDocument/completion" => {
+                let params: lsp_types::CompletionParams = serde_json::from_value(request.params)?;
+                let items = match self.locate(&params.text_document_position) {
+                    Some((file, offset)) => features::completion(&self.workspace, &self.schema, &file, offset),
+                    None => vec![],
+                };
+                serde_json::to_value(items)?
+            }
+            "textDocument/hover" => {
+                let params: lsp_types::HoverParams = serde_json::from_value(request.params)?;
+                let hover = self
+                    .locate(&params.text_document_position_params)
+                    .and_then(|(file, offset)| features::hover(&self.workspace, &self.schema, &file, offset));
+                serde_json::to_value(hover)?
+            }
+            _ => {
+                let message = format!("unsupported request {}", request.method);
+                let response =
+                    Response::new_err(request.id, lsp_server::ErrorCode::MethodNotFound as i32, message);
+                self.connection.sender.send(Message::Response(response))?;
+                return Ok(());
+            }
+        };
+
+        let response = Response::new_ok(request.id, result);
+        self.connection.sender.send(Message::Response(response))?;
+        Ok(())
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Runs the language server until the client shuts it down.
+///
+/// Use [`Connection::stdio`] for editors, or [`Connection::memory`] to drive the server from a test.
+pub fn run_server(connection: Connection, schema: CobSchema) -> Result<(), Box<dyn Error + Sync + Send>>
+{
+    let capabilities = serde_json::to_value(capabilities())?;
+    let params: InitializeParams = serde_json::from_value(connection.initialize(capabilities)?)?;
+    let options: InitOptions = match params.initialization_options.clone() {
+        Some(options) => serde_json::from_value(options)?,
+        None => InitOptions::default(),
+    };
+
+    let root = workspace_root(&params);
+    let assets_dir = match options.assets_dir {
+        Some(dir) => root.join(dir),
+        None if root.join("assets").is_dir() => root.join("assets"),
+        None => root.clone(),
+    };
+    let schema = match (schema.is_empty(), options.schema) {
+        (true, Some(path)) => CobSchema::load(root.join(path))?,
+        _ => schema,
+    };
+
+    let mut flags = CobFlags::default();
+    for (flag, value) in options.flags {
+        flags.set(flag, value);
+    }
+
+    let mut server = Server {
+        connection,
+        workspace: Workspace::new(assets_dir, flags),
+        schema,
+        open_files: HashSet::default(),
+    };
+
+    while let Ok(message) = server.connection.receiver.recv() {
+        match message {
+            Message::Request(request) => {
+                if server.connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                server.handle_request(request)?;
+            }
+            Message::Notification(notification) => server.handle_notification(notification)?,
+            Message::Response(_) => (),
+        }
+    }
+
+    Ok(())
+}
+
+//-------------------------------------------------------------------------------------------------------------------
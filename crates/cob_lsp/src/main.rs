@@ -0,0 +1,75 @@
+//! COB language server over stdio.
+//!
+//! ```text
+//! cob-lsp [--schema <file>]
+//! ```
+//!
+//! The schema is a JSON file describing the app's loadables. Without it, loadable names and struct fields can't
+//! be checked, completed, or hovered.
+
+use std::process::ExitCode;
+
+use cob_lsp::{run_server, CobSchema};
+use lsp_server::Connection;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+const USAGE: &str = "usage:
+    cob-lsp [--schema <file>]
+
+Runs the COB language server over stdio.
+
+options:
+    --schema <file>    JSON file describing the app's loadables.";
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn usage_error(message: &str) -> ExitCode
+{
+    eprintln!("error: {}\n\n{}", message, USAGE);
+    ExitCode::from(2)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn main() -> ExitCode
+{
+    let mut args = std::env::args().skip(1);
+    let mut schema = CobSchema::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--schema" => {
+                let Some(path) = args.next() else { return usage_error("missing value for '--schema'") };
+                match CobSchema::load(&path) {
+                    Ok(loaded) => schema = loaded,
+                    Err(err) => {
+                        eprintln!("error: {}", err);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            _ => return usage_error(&format!("unknown argument '{}'", arg)),
+        }
+    }
+
+    let (connection, io_threads) = Connection::stdio();
+    let result = run_server(connection, schema);
+    let joined = io_threads.join();
+
+    match result
+        .map_err(|err| err.to_string())
+        .and(joined.map_err(|err| err.to_string()))
+    {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
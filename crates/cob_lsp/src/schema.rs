@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A field of a loadable struct or enum variant.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct FieldSchema
+{
+    /// Field name. Tuple fields are named by index.
+    pub name: String,
+    /// Type path of the field's type.
+    #[serde(rename = "type")]
+    pub type_path: String,
+    pub docs: Option<String>,
+    /// The field's default value, written as COB.
+    pub default: Option<String>,
+}
+
+/// A variant of a loadable enum.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct VariantSchema
+{
+    pub name: String,
+    pub fields: Vec<FieldSchema>,
+}
+
+/// Description of one registered loadable.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct LoadableSchema
+{
+    /// How the loadable was registered, e.g. `component` or `instruction`.
+    pub kind: String,
+    /// Full type path of the loadable.
+    pub type_path: String,
+    pub docs: Option<String>,
+    pub fields: Vec<FieldSchema>,
+    pub variants: Vec<VariantSchema>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// The loadables registered in an app, keyed by the name used in COB files.
+///
+/// Read from a JSON file exported by the app with
+/// [`export_loadable_schema`](bevy_cobweb_ui::prelude::export_loadable_schema). Without a schema, loadable names
+/// can't be checked or completed.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct CobSchema
+{
+    pub loadables: BTreeMap<String, LoadableSchema>,
+}
+
+impl CobSchema
+{
+    /// Reads a schema from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String>
+    {
+        let path = path.as_ref();
+        let content =
+            std::fs::read_to_string(path).map_err(|err| format!("failed reading {}: {}", path.display(), err))?;
+        serde_json::from_str(&content).map_err(|err| format!("failed parsing schema {}: {}", path.display(), err))
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.loadables.is_empty()
+    }
+
+    /// Gets a loadable by name. Generic loadables can be looked up by their base name.
+    pub fn get(&self, name: &str) -> Option<&LoadableSchema>
+    {
+        self.loadables.get(name).or_else(|| {
+            self.loadables
+                .iter()
+                .find(|(key, _)| key.split('<').next() == Some(name))
+                .map(|(_, loadable)| loadable)
+        })
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,274 @@
+use lsp_types::{Position, Range};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+const SECTIONS: [&str; 6] = ["#manifest", "#import", "#defs", "#theme", "#commands", "#scenes"];
+
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) fn is_ident_char(c: char) -> bool
+{
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Characters in a constant path like `$alias::name`.
+pub(crate) fn is_constant_char(c: char) -> bool
+{
+    is_ident_char(c) || c == '$' || c == ':'
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Converts a byte offset to an LSP position. Columns are counted in UTF-16 code units.
+pub(crate) fn offset_to_position(text: &str, offset: usize) -> Position
+{
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &text[..offset];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+    let character = before[line_start..].encode_utf16().count();
+    Position::new(line as u32, character as u32)
+}
+
+/// Converts an LSP position to a byte offset. Positions past the end of a line are clamped to the line end.
+pub(crate) fn position_to_offset(text: &str, position: Position) -> usize
+{
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match text[line_start..].find('\n') {
+            Some(idx) => line_start += idx + 1,
+            None => return text.len(),
+        }
+    }
+    let line_end = text[line_start..]
+        .find('\n')
+        .map(|idx| line_start + idx)
+        .unwrap_or(text.len());
+
+    let mut units = 0;
+    for (idx, c) in text[line_start..line_end].char_indices() {
+        if units >= position.character as usize {
+            return line_start + idx;
+        }
+        units += c.len_utf16();
+    }
+    line_end
+}
+
+pub(crate) fn byte_range(text: &str, start: usize, end: usize) -> Range
+{
+    Range::new(offset_to_position(text, start), offset_to_position(text, end))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Gets the byte range of the token touching `offset`.
+pub(crate) fn token_at(text: &str, offset: usize, is_token_char: impl Fn(char) -> bool) -> Option<(usize, usize)>
+{
+    let offset = offset.min(text.len());
+    let start = text[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_token_char(*c))
+        .last()
+        .map(|(idx, _)| idx)
+        .unwrap_or(offset);
+    let end = text[offset..]
+        .char_indices()
+        .find(|(_, c)| !is_token_char(*c))
+        .map(|(idx, _)| offset + idx)
+        .unwrap_or(text.len());
+    (start < end).then_some((start, end))
+}
+
+/// Gets the byte range of the `$constant` path touching `offset`, including the `$`.
+///
+/// Field names are excluded, e.g. only `$color` is returned in `bar:$color`.
+pub(crate) fn constant_at(text: &str, offset: usize) -> Option<(usize, usize)>
+{
+    let (start, end) = token_at(text, offset, is_constant_char)?;
+    let dollar = start + text[start..(offset + 1).min(end)].rfind('$')?;
+    Some((dollar, end))
+}
+
+/// Gets the byte range of the macro path touching `offset` in a call like `alias::name!(..)`, excluding the `!`.
+///
+/// Field names are excluded, e.g. only `my_macro` is returned in `bar:my_macro!()`.
+pub(crate) fn macro_at(text: &str, offset: usize) -> Option<(usize, usize)>
+{
+    let (start, end) = token_at(text, offset, |c| is_ident_char(c) || c == ':')?;
+    if !text[end..].starts_with('!') {
+        return None;
+    }
+    let bytes = text.as_bytes();
+    let start = (start..end)
+        .filter(|idx| {
+            bytes[*idx] == b':' && (*idx == 0 || bytes[idx - 1] != b':') && bytes.get(idx + 1) != Some(&b':')
+        })
+        .last()
+        .map(|colon| colon + 1)
+        .unwrap_or(start);
+    (start < end).then_some((start, end))
+}
+
+/// Gets the byte range of the scene macro path touching `offset` in a call like `+alias::name(..)`, excluding the
+/// `+`.
+pub(crate) fn scene_macro_at(text: &str, offset: usize) -> Option<(usize, usize)>
+{
+    let (start, end) = token_at(text, offset, |c| is_ident_char(c) || c == ':')?;
+    (text[..start].ends_with('+') && text[end..].starts_with('(')).then_some((start, end))
+}
+
+/// Finds `word` at or after `from`, where the word is not part of a longer identifier.
+pub(crate) fn find_word(text: &str, word: &str, from: usize) -> Option<usize>
+{
+    let mut from = from;
+    while let Some(found) = text.get(from..)?.find(word) {
+        let start = from + found;
+        let end = start + word.len();
+        let ident_before = matches!(text[..start].chars().next_back(), Some(c) if is_ident_char(c));
+        let ident_after = matches!(text[end..].chars().next(), Some(c) if is_ident_char(c));
+        if !ident_before && !ident_after {
+            return Some(start);
+        }
+        from = end;
+    }
+    None
+}
+
+/// Gets the line containing `offset`.
+pub(crate) fn line_at(text: &str, offset: usize) -> (usize, &str)
+{
+    let start = text[..offset].rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+    let end = text[offset..]
+        .find('\n')
+        .map(|idx| offset + idx)
+        .unwrap_or(text.len());
+    (start, &text[start..end])
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Where the cursor is within the structure of a COB file.
+#[derive(Debug, Default)]
+pub(crate) struct CursorContext
+{
+    /// The section containing the cursor, e.g. `#scenes`.
+    pub(crate) section: Option<&'static str>,
+    /// The innermost bracket that is open at the cursor, and its offset.
+    pub(crate) open_bracket: Option<(char, usize)>,
+    /// `true` if the cursor is inside a comment or string.
+    pub(crate) in_comment_or_string: bool,
+}
+
+/// Scans `text` up to `offset` to find the section and open brackets at the cursor.
+pub(crate) fn cursor_context(text: &str, offset: usize) -> CursorContext
+{
+    let bytes = text.as_bytes();
+    let offset = offset.min(text.len());
+    let mut context = CursorContext::default();
+    let mut brackets = vec![];
+    let mut line_start = true;
+    let mut idx = 0;
+
+    while idx < offset {
+        let byte = bytes[idx];
+        if line_start && byte == b'#' {
+            if let Some(section) = SECTIONS
+                .iter()
+                .find(|section| text[idx..].starts_with(*section))
+            {
+                context.section = Some(*section);
+                brackets.clear();
+            }
+        }
+        line_start = byte == b'\n';
+
+        // Skip comments and strings.
+        let skip_to = match byte {
+            b'/' if bytes.get(idx + 1) == Some(&b'/') => Some(text[idx..].find('\n').map(|end| idx + end)),
+            b'/' if bytes.get(idx + 1) == Some(&b'*') => Some(text[idx + 2..].find("*/").map(|end| idx + end + 4)),
+            b'"' => {
+                let mut end = None;
+                let mut escaped = false;
+                for (pos, byte) in bytes.iter().enumerate().skip(idx + 1) {
+                    match *byte {
+                        _ if escaped => escaped = false,
+                        b'\\' => escaped = true,
+                        b'"' => {
+                            end = Some(pos + 1);
+                            break;
+                        }
+                        _ => (),
+                    }
+                }
+                Some(end)
+            }
+            _ => None,
+        };
+        if let Some(skip_to) = skip_to {
+            match skip_to {
+                Some(skip_to) if skip_to <= offset => {
+                    idx = skip_to;
+                    continue;
+                }
+                _ => {
+                    context.in_comment_or_string = true;
+                    break;
+                }
+            }
+        }
+
+        match byte {
+            b'{' | b'(' | b'[' => brackets.push((byte as char, idx)),
+            b'}' | b')' | b']' => {
+                brackets.pop();
+            }
+            _ => (),
+        }
+        idx += 1;
+    }
+
+    context.open_bracket = brackets.last().copied();
+    context
+}
+
+/// Gets the identifier directly before an opening bracket, e.g. `FlexNode` in `FlexNode{`.
+///
+/// Generics are skipped. Returns `None` for enum variants and map values (`a::B{`, `field:{`).
+pub(crate) fn ident_before_bracket(text: &str, bracket: usize) -> Option<&str>
+{
+    let mut end = text[..bracket].trim_end().len();
+
+    // Skip generics.
+    if text[..end].ends_with('>') {
+        let mut depth = 0;
+        for (idx, c) in text[..end].char_indices().rev() {
+            match c {
+                '>' => depth += 1,
+                '<' => depth -= 1,
+                _ => (),
+            }
+            if depth == 0 {
+                end = idx;
+                break;
+            }
+        }
+    }
+
+    let start = text[..end]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_ident_char(*c))
+        .last()
+        .map(|(idx, _)| idx)?;
+    if text[..start].ends_with("::") {
+        return None;
+    }
+    Some(&text[start..end])
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,223 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use bevy_cobweb_ui::prelude::cob::*;
+use bevy_cobweb_ui::prelude::{CobDefKind, CobFlags, CobResolver};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn collect_dir(dir: &Path, files: &mut Vec<PathBuf>)
+{
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for path in entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        if path.is_dir() {
+            collect_dir(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "cob") {
+            files.push(path);
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A parsed COB file.
+pub(crate) struct CobFileIndex
+{
+    pub(crate) path: PathBuf,
+    pub(crate) text: String,
+    /// `None` if the file couldn't be parsed at all.
+    pub(crate) cob: Option<Cob>,
+    pub(crate) diagnostics: Vec<CobDiagnostic>,
+}
+
+impl CobFileIndex
+{
+    fn new(file: &str, path: PathBuf, text: String) -> Self
+    {
+        let span = Span::new_extra(&text, CobLocationMetadata { file });
+        let (cob, diagnostics) = match Cob::parse_recoverable(span) {
+            Ok(recovery) => (Some(recovery.cob), recovery.diagnostics),
+            Err(err) => (None, CobDiagnostic::from_span_error(&err).into_iter().collect()),
+        };
+        Self { path, text, cob, diagnostics }
+    }
+
+    /// Finds the byte range of the `$name`, `name!` or `+name` in a constant or macro definition.
+    ///
+    /// The parsed file has no source locations, so this searches for a line starting with `$name =`, `name!(` or
+    /// `+name(`.
+    pub(crate) fn find_def(&self, kind: CobDefKind, name: &str) -> Option<(usize, usize)>
+    {
+        let mut offset = 0;
+        for line in self.text.split_inclusive('\n') {
+            let trimmed = line.trim_start();
+            let start = offset + (line.len() - trimmed.len());
+            match kind {
+                CobDefKind::Constant => {
+                    let rest = trimmed
+                        .strip_prefix('$')
+                        .and_then(|rest| rest.strip_prefix(name));
+                    if rest.is_some_and(|rest| rest.trim_start().starts_with('=')) {
+                        return Some((start, start + 1 + name.len()));
+                    }
+                }
+                CobDefKind::DataMacro | CobDefKind::LoadableMacro => {
+                    let rest = trimmed
+                        .strip_prefix(name)
+                        .and_then(|rest| rest.strip_prefix('!'));
+                    if rest.is_some_and(|rest| rest.starts_with('(')) {
+                        return Some((start, start + name.len() + 1));
+                    }
+                }
+                CobDefKind::SceneMacro => {
+                    let rest = trimmed
+                        .strip_prefix('+')
+                        .and_then(|rest| rest.strip_prefix(name));
+                    if rest.is_some_and(|rest| rest.starts_with('(')) {
+                        return Some((start, start + 1 + name.len()));
+                    }
+                }
+            }
+            offset += line.len();
+        }
+        None
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// All COB files in the assets directory.
+///
+/// Open documents replace the file contents on disk until they are closed.
+pub(crate) struct Workspace
+{
+    assets_dir: PathBuf,
+    /// [ file name relative to the assets directory : file ]
+    files: HashMap<String, CobFileIndex>,
+    /// Resolves defs the same way apps do. Resolved files are cached, so it's mutated while handling requests.
+    resolver: RefCell<CobResolver>,
+}
+
+impl Workspace
+{
+    /// Parses all COB files in `assets_dir`.
+    ///
+    /// `#if` blocks are evaluated against `flags` when resolving defs.
+    pub(crate) fn new(assets_dir: PathBuf, flags: CobFlags) -> Self
+    {
+        let mut workspace = Self {
+            assets_dir,
+            files: HashMap::default(),
+            resolver: RefCell::new(CobResolver::new(flags)),
+        };
+
+        let mut paths = vec![];
+        collect_dir(&workspace.assets_dir, &mut paths);
+        for path in paths {
+            workspace.reload(&path);
+        }
+
+        workspace
+    }
+
+    /// Gets the name of a file relative to the assets directory, which is how files are referenced in COB.
+    ///
+    /// Files outside the assets directory are named by their file name.
+    pub(crate) fn file_name(&self, path: &Path) -> String
+    {
+        let relative = match path.strip_prefix(&self.assets_dir) {
+            Ok(relative) => relative,
+            Err(_) => Path::new(path.file_name().unwrap_or_default()),
+        };
+        let segments: Vec<_> = relative
+            .iter()
+            .map(|segment| segment.to_string_lossy())
+            .collect();
+        segments.join("/")
+    }
+
+    pub(crate) fn get(&self, file: &str) -> Option<&CobFileIndex>
+    {
+        self.files.get(file)
+    }
+
+    /// Re-parses a file with new content. Returns the file's name.
+    pub(crate) fn update(&mut self, path: &Path, text: String) -> String
+    {
+        let file = self.file_name(path);
+        let index = CobFileIndex::new(&file, path.into(), text);
+        if let Some(cob_file) = CobFile::try_new(&file) {
+            let mut resolver = self.resolver.borrow_mut();
+            match &index.cob {
+                Some(cob) => resolver.insert(cob.clone()),
+                None => resolver.remove(&cob_file),
+            }
+        }
+        self.files.insert(file.clone(), index);
+        file
+    }
+
+    /// Re-reads a file from disk, or removes it if it no longer exists. Returns the file's name.
+    pub(crate) fn reload(&mut self, path: &Path) -> String
+    {
+        match std::fs::read_to_string(path) {
+            Ok(text) => self.update(path, text),
+            Err(_) => {
+                let file = self.file_name(path);
+                self.files.remove(&file);
+                if let Some(cob_file) = CobFile::try_new(&file) {
+                    self.resolver.borrow_mut().remove(&cob_file);
+                }
+                file
+            }
+        }
+    }
+
+    //---------------------------------------------------------------------------------------------------------------
+
+    /// Gets the file a manifest key points to.
+    pub(crate) fn manifest_file(&self, key: &str) -> Option<String>
+    {
+        let resolver = self.resolver.borrow();
+        let file = resolver.manifest_file(&ManifestKey::new(key))?;
+        Some(String::from(file.as_str()))
+    }
+
+    /// Finds the file and name where a constant or macro used in `file` is defined.
+    pub(crate) fn resolve_def(&self, file: &str, kind: CobDefKind, path: &str) -> Option<(String, String)>
+    {
+        let file = CobFile::try_new(file)?;
+        let mut resolver = self.resolver.borrow_mut();
+        let (def_file, name) = resolver.resolve(&file).ok()?.locate(kind, path)?;
+        Some((String::from(def_file.as_str()), String::from(name)))
+    }
+
+    /// Gets all constants or macros of a kind that can be used in `file`, including imported defs with their
+    /// aliases.
+    pub(crate) fn visible_defs(&self, file: &str, kind: CobDefKind) -> Vec<String>
+    {
+        let Some(file) = CobFile::try_new(file) else { return vec![] };
+        let mut resolver = self.resolver.borrow_mut();
+        let Ok(constants) = resolver.resolve(&file) else { return vec![] };
+        constants
+            .paths(kind)
+            .into_iter()
+            .map(|path| String::from(path.as_str()))
+            .collect()
+    }
+
+    /// Gets the path of a file in the assets directory.
+    pub(crate) fn path_of(&self, file: &str) -> PathBuf
+    {
+        match self.files.get(file) {
+            Some(index) => index.path.clone(),
+            None => self.assets_dir.join(file),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
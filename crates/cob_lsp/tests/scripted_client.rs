@@ -0,0 +1,225 @@
+use std::path::PathBuf;
+
+use cob_lsp::{run_server, CobSchema};
+use lsp_server::{Connection, Message, Notification, Request, RequestId};
+use lsp_types::{DiagnosticSeverity, PublishDiagnosticsParams, Url};
+use serde_json::{json, Value};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+const LIB_COB: &str = "#defs
+$color = 10
+value!(@v) = @v
+
+#if(lsp_test)
+#defs
+$flagged = 1
+#else
+#defs
+$unflagged = 1
+#endif
+";
+
+const MAIN_COB: &str = "#manifest
+self as main
+\"lib.cob\" as lib
+
+#import
+lib as lib
+
+#commands
+Foo{bar:$lib::color}
+Foo{bax:1}
+Foo{bar:lib::value!(1)}
+";
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Sends messages to the server and waits for its replies.
+struct Client
+{
+    connection: Connection,
+    next_id: i32,
+}
+
+impl Client
+{
+    fn request(&mut self, method: &str, params: Value) -> Value
+    {
+        self.next_id += 1;
+        let id = RequestId::from(self.next_id);
+        let request = Request::new(id.clone(), String::from(method), params);
+        self.connection
+            .sender
+            .send(Message::Request(request))
+            .unwrap();
+
+        loop {
+            match self.connection.receiver.recv().unwrap() {
+                Message::Response(response) if response.id == id => {
+                    assert!(response.error.is_none(), "{:?}", response.error);
+                    return response.result.unwrap_or_default();
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    fn notify(&self, method: &str, params: Value)
+    {
+        let notification = Notification::new(String::from(method), params);
+        self.connection
+            .sender
+            .send(Message::Notification(notification))
+            .unwrap();
+    }
+
+    fn diagnostics(&self) -> PublishDiagnosticsParams
+    {
+        loop {
+            match self.connection.receiver.recv().unwrap() {
+                Message::Notification(notification)
+                    if notification.method == "textDocument/publishDiagnostics" =>
+                {
+                    return serde_json::from_value(notification.params).unwrap();
+                }
+                _ => continue,
+            }
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn position(uri: &Url, line: u32, character: u32) -> Value
+{
+    json!({ "textDocument": { "uri": uri }, "position": { "line": line, "character": character } })
+}
+
+fn test_schema() -> CobSchema
+{
+    serde_json::from_value(json!({
+        "loadables": {
+            "Foo": {
+                "kind": "component",
+                "type_path": "my_app::Foo",
+                "docs": "A test component.",
+                "fields": [{ "name": "bar", "type": "u32" }]
+            }
+        }
+    }))
+    .unwrap()
+}
+
+fn make_workspace() -> PathBuf
+{
+    let dir = std::env::temp_dir().join(format!("cob_lsp_scripted_client_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("lib.cob"), LIB_COB).unwrap();
+    std::fs::write(dir.join("main.cob"), MAIN_COB).unwrap();
+    dir
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn scripted_client()
+{
+    let dir = make_workspace();
+    let root_uri = Url::from_file_path(&dir).unwrap();
+    let main_uri = Url::from_file_path(dir.join("main.cob")).unwrap();
+    let lib_uri = Url::from_file_path(dir.join("lib.cob")).unwrap();
+
+    let (server_connection, client_connection) = Connection::memory();
+    let server = std::thread::spawn(move || run_server(server_connection, test_schema()).unwrap());
+    let mut client = Client { connection: client_connection, next_id: 0 };
+
+    // Initialize.
+    let result = client.request(
+        "initialize",
+        json!({
+            "capabilities": {},
+            "workspaceFolders": [{ "uri": root_uri, "name": "test" }],
+            "initializationOptions": { "flags": { "lsp_test": "" } }
+        }),
+    );
+    assert!(result["capabilities"]["completionProvider"].is_object());
+    client.notify("initialized", json!({}));
+
+    // Parse errors are reported for broken documents.
+    let broken = json!({ "uri": main_uri, "languageId": "cob", "version": 1, "text": "#commands\nFoo{bar:\n" });
+    client.notify("textDocument/didOpen", json!({ "textDocument": broken }));
+    let diagnostics = client.diagnostics();
+    assert_eq!(diagnostics.uri, main_uri);
+    assert!(!diagnostics.diagnostics.is_empty());
+    assert_eq!(diagnostics.diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+
+    // Unknown fields are reported when the document is fixed.
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": main_uri, "version": 2 },
+            "contentChanges": [{ "text": MAIN_COB }]
+        }),
+    );
+    let diagnostics = client.diagnostics();
+    assert_eq!(diagnostics.diagnostics.len(), 1);
+    let diagnostic = &diagnostics.diagnostics[0];
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+    assert_eq!(diagnostic.message, "`Foo` has no field named `bax`\nhint: did you mean `bar`?");
+    assert_eq!((diagnostic.range.start.line, diagnostic.range.start.character), (9, 4));
+
+    // Go to an imported constant.
+    let result = client.request("textDocument/definition", position(&main_uri, 8, 11));
+    assert_eq!(result["uri"], json!(lib_uri));
+    assert_eq!(result["range"]["start"], json!({ "line": 1, "character": 0 }));
+
+    // Go to an imported macro.
+    let result = client.request("textDocument/definition", position(&main_uri, 10, 15));
+    assert_eq!(result["uri"], json!(lib_uri));
+    assert_eq!(result["range"]["start"], json!({ "line": 2, "character": 0 }));
+
+    // Go to a manifest key's file.
+    let result = client.request("textDocument/definition", position(&main_uri, 5, 1));
+    assert_eq!(result["uri"], json!(lib_uri));
+
+    // Complete constants.
+    let result = client.request("textDocument/completion", position(&main_uri, 8, 9));
+    let labels: Vec<_> = result
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| &item["label"])
+        .collect();
+    // Constants in `#if` blocks are only visible if the flags match.
+    assert_eq!(labels, [&json!("$lib::color"), &json!("$lib::flagged")]);
+
+    // Complete struct fields.
+    let result = client.request("textDocument/completion", position(&main_uri, 8, 4));
+    assert_eq!(result[0]["label"], "bar");
+    assert_eq!(result[0]["insertText"], "bar:");
+
+    // Complete data macros.
+    assert_eq!(result[1]["label"], "lib::value!");
+    assert_eq!(result[1]["insertText"], "lib::value!()");
+
+    // Complete loadable names.
+    let result = client.request("textDocument/completion", position(&main_uri, 11, 0));
+    assert_eq!(result[0]["label"], "Foo");
+
+    // Hover over a loadable.
+    let result = client.request("textDocument/hover", position(&main_uri, 8, 1));
+    let hover = result["contents"]["value"].as_str().unwrap();
+    assert!(hover.contains("my_app::Foo"));
+    assert!(hover.contains("A test component."));
+    assert!(hover.contains("`bar`: `u32`"));
+
+    // Shut down.
+    client.request("shutdown", Value::Null);
+    client.notify("exit", Value::Null);
+    server.join().unwrap();
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
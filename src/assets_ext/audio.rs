@@ -231,6 +231,12 @@ impl AudioMap
         let _ = self.pending.remove(id);
     }
 
+    /// Checks if the map holds a handle for the audio source at `path`.
+    pub(crate) fn contains_path(&self, path: &str) -> bool
+    {
+        self.cached_audios.contains_key(path) || self.localized_audios.contains_key(path)
+    }
+
     /// Adds an audio that should be cached.
     ///
     /// Note that if this is called in state [`LoadState::Loading`], then [`LoadState::Done`] will wait
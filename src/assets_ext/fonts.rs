@@ -403,6 +403,12 @@ impl FontMap
         self.insert_localized(to_insert, asset_server, manifest, c);
     }
 
+    /// Checks if the map holds a handle for the font at `path`.
+    pub(crate) fn contains_path(&self, path: &str) -> bool
+    {
+        self.cached_fonts.contains_key(path) || self.localization_fonts.contains_key(path)
+    }
+
     /// Adds a font family that should be loaded.
     ///
     /// Returns `false` if there are no eligible fonts. See [`RegisterFontFamilies`]. The requested font
@@ -236,6 +236,12 @@ impl ImageMap
         let _ = self.pending.remove(id);
     }
 
+    /// Checks if the map holds a handle for the image at `path`.
+    pub(crate) fn contains_path(&self, path: &str) -> bool
+    {
+        self.cached_images.contains_key(path) || self.localized_images.contains_key(path)
+    }
+
     /// Adds an image that should be cached.
     ///
     /// Note that if this is called in state [`LoadState::Loading`], then [`LoadState::Done`] will wait
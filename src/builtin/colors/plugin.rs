@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::*;
+use crate::builtin::*;
 
 //-------------------------------------------------------------------------------------------------------------------
 
@@ -10,10 +10,10 @@ impl Plugin for BuiltinColorsPlugin
 {
     fn build(&self, app: &mut App)
     {
-        load_embedded_scene_file!(app, "bevy_cobweb_ui", "src/builtin/colors", "basic.cob");
-        load_embedded_scene_file!(app, "bevy_cobweb_ui", "src/builtin/colors", "colors.cob");
-        load_embedded_scene_file!(app, "bevy_cobweb_ui", "src/builtin/colors", "css.cob");
-        load_embedded_scene_file!(app, "bevy_cobweb_ui", "src/builtin/colors", "tailwind.cob");
+        BASIC_COLORS_COB.load(app);
+        COLORS_COB.load(app);
+        CSS_COLORS_COB.load(app);
+        TAILWIND_COLORS_COB.load(app);
     }
 }
 
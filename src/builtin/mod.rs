@@ -8,8 +8,91 @@ pub mod widgets;
 
 mod plugin;
 
+use std::path::{Path, PathBuf};
+
+use bevy::asset::io::embedded::EmbeddedAssetRegistry;
+use bevy::prelude::*;
 pub(crate) use plugin::*;
 
+use crate::prelude::LoadedCobAssetFilesAppExt;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A COB file embedded in this crate.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct BuiltinCobFile
+{
+    /// The directory containing the file, relative to the crate root.
+    pub(crate) dir: &'static str,
+    /// The file's name. The file is loaded from `embedded://bevy_cobweb_ui/{name}`.
+    pub(crate) name: &'static str,
+    pub(crate) content: &'static str,
+}
+
+impl BuiltinCobFile
+{
+    /// Gets the asset path the file is loaded from.
+    pub(crate) fn asset_path(&self) -> String
+    {
+        format!("embedded://bevy_cobweb_ui/{}", self.name)
+    }
+
+    /// Embeds the file in the app and loads it.
+    ///
+    /// Equivalent to [`load_embedded_scene_file`](crate::load_embedded_scene_file).
+    pub(crate) fn load(&self, app: &mut App)
+    {
+        app.world_mut()
+            .resource_mut::<EmbeddedAssetRegistry>()
+            .insert_asset(
+                PathBuf::from(self.dir).join(self.name),
+                &Path::new("bevy_cobweb_ui").join(self.name),
+                self.content.as_bytes(),
+            );
+        app.load(self.asset_path());
+    }
+}
+
+/// Defines a constant for each builtin COB file, and [`BUILTIN_COB_FILES`] with all of them.
+macro_rules! builtin_cob_files {
+    ($($(#[$attr:meta])* $const_name:ident: $dir:literal, $name:literal;)*) => {
+        $(
+            $(#[$attr])*
+            pub(crate) const $const_name: BuiltinCobFile = BuiltinCobFile {
+                dir: $dir,
+                name: $name,
+                content: include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $dir, "/", $name)),
+            };
+        )*
+
+        /// All COB files embedded in this crate.
+        ///
+        /// Used by the [`CobCompiler`](crate::prelude::CobCompiler) and
+        /// [`CobResolver`](crate::prelude::CobResolver), so files can import builtin files without loading them
+        /// in an app.
+        pub(crate) const BUILTIN_COB_FILES: &[BuiltinCobFile] = &[$($(#[$attr])* $const_name,)*];
+    };
+}
+
+builtin_cob_files! {
+    #[cfg(feature = "colors")]
+    BASIC_COLORS_COB: "src/builtin/colors", "basic.cob";
+    #[cfg(feature = "colors")]
+    COLORS_COB: "src/builtin/colors", "colors.cob";
+    #[cfg(feature = "colors")]
+    CSS_COLORS_COB: "src/builtin/colors", "css.cob";
+    #[cfg(feature = "colors")]
+    TAILWIND_COLORS_COB: "src/builtin/colors", "tailwind.cob";
+    #[cfg(feature = "widgets")]
+    RADIO_BUTTON_COB: "src/builtin/widgets/radio_button", "radio_button.cob";
+    #[cfg(feature = "widgets")]
+    SLIDER_COB: "src/builtin/widgets/slider", "slider.cob";
+    #[cfg(feature = "widgets")]
+    TOOLTIP_COB: "src/builtin/widgets/tooltip", "tooltip.cob";
+    #[cfg(feature = "editor")]
+    EDITOR_FRAME_COB: "src/editor/template", "frame.cob";
+}
+
 //-------------------------------------------------------------------------------------------------------------------
 
 /// Loads an embedded widget.
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
 
 use crate::builtin::widgets::*;
+use crate::builtin::TOOLTIP_COB;
 
 //-------------------------------------------------------------------------------------------------------------------
 
@@ -11,9 +12,10 @@ impl Plugin for BuiltinWidgetsPlugin
     fn build(&self, app: &mut App)
     {
         app.add_plugins(radio_button::CobwebRadioButtonPlugin)
-            .add_plugins(slider::CobwebSliderPlugin)
-            //.add_plugins(slider::CobwebTooltipPlugin)
-            ;
+            .add_plugins(slider::CobwebSliderPlugin);
+
+        // The tooltip instruction is not implemented yet, but the tooltip scene macros can be used on their own.
+        TOOLTIP_COB.load(app);
     }
 }
 
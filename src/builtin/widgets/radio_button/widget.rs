@@ -1,7 +1,7 @@
 use bevy::prelude::*;
 use bevy_cobweb::prelude::*;
 
-//use crate::load_embedded_scene_file;
+use crate::builtin::RADIO_BUTTON_COB;
 use crate::prelude::*;
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -183,9 +183,7 @@ impl Plugin for CobwebRadioButtonPlugin
 {
     fn build(&self, app: &mut App)
     {
-        // TODO: re-enable once COB scene macros are implemented
-        //load_embedded_scene_file!(app, "bevy_cobweb_ui", "src/builtin/widgets/radio_button",
-        // "radio_button.cob");
+        RADIO_BUTTON_COB.load(app);
         app.register_instruction_type::<RadioGroup>()
             .register_instruction_type::<RadioButton>();
     }
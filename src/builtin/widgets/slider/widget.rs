@@ -5,6 +5,7 @@ use bevy_cobweb::prelude::*;
 use smallvec::SmallVec;
 use smol_str::SmolStr;
 
+use crate::builtin::SLIDER_COB;
 use crate::prelude::*;
 use crate::sickle::*;
 
@@ -906,8 +907,7 @@ impl Plugin for CobwebSliderPlugin
 {
     fn build(&self, app: &mut App)
     {
-        // TODO: re-enable once COB scene macros are implemented
-        //load_embedded_scene_file!(app, "bevy_cobweb_ui", "src/builtin/widgets/slider", "slider.cob");
+        SLIDER_COB.load(app);
         app.register_instruction_type::<Slider>()
             .register_component_type::<SliderHandle>()
             .init_resource::<ChildrenIterScratch>()
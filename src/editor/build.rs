@@ -1,5 +1,7 @@
 use std::sync::Arc;
 
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::ButtonState;
 use bevy::prelude::*;
 use bevy::reflect::TypeRegistry;
 use bevy::render::camera::RenderTarget;
@@ -23,44 +25,21 @@ fn build_widgets<'a>(
     widgets: &CobWidgetRegistry,
     file_hash: CobFileHash,
     scene_ref: SceneRef,
-    longname: &'static str,
     shortname: &'static str,
     loadable: Box<dyn PartialReflect + 'static>,
     death_signal: DeathSignal,
 )
 {
-    // Check for loadable widget
-    if let Some(spawn_fn) = widgets.get(longname) {
-        let content_entity = l.id();
-        let editor_ref = CobEditorRef {
-            file_hash,
-            scene_ref,
-            loadable_name: shortname,
-            structure_path: ReflectStructurePath { path: Arc::from([]) },
-            death_signal,
-        };
-        let (loader, builder) = l.inner();
-        if !(spawn_fn)(
-            builder.commands(),
-            loader,
-            content_entity,
-            &editor_ref,
-            loadable.as_ref(),
-        ) {
-            l.load_scene(("editor.frame", "destructure_unsupported"));
-        }
-
-        return;
-    }
-
-    // Fallback
-    l.load_scene(("editor.frame", "destructure_unsupported"));
+    let editor_ref = CobEditorRef {
+        file_hash,
+        scene_ref,
+        loadable_name: shortname,
+        structure_path: ReflectStructurePath { path: Arc::from([]) },
+        death_signal,
+    };
 
-    // TODO: Destructure and look for widgets for internal values
-    // - TODO: If no widget found for an enum, provide a drop-down.
-    // - TODO: If no widget found for a primitive type, serialize it to COB and display it directly.
-    // - TODO: If no widget found for a set, display "<cannot destructure sets>" (you can only add/remove entries,
-    //   TODO)
+    // Uses registered widgets if possible, and otherwise builds widgets from the reflected structure.
+    build_reflected_widget(l, widgets, editor_ref, loadable.as_ref());
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -77,7 +56,7 @@ fn build_loadable<'a>(
 {
     // Look up loadable type
     let name = loadable.id.to_canonical(None);
-    let Some((deserializer, _, longname, shortname)) = get_deserializer(registry, name.as_str(), loadables) else {
+    let Some((deserializer, _, _, shortname)) = get_deserializer(registry, name.as_str(), loadables) else {
         l.load_scene(("editor.frame", "unsupported"));
         return;
     };
@@ -99,7 +78,7 @@ fn build_loadable<'a>(
                     let (signaler, signal) = DeathSignaler::new();
                     l.insert(signaler);
 
-                    build_widgets(l, widgets, file_hash, scene_ref, longname, shortname, reflected, signal);
+                    build_widgets(l, widgets, file_hash, scene_ref, shortname, reflected, signal);
                 });
             }
             Err(_) => {
@@ -127,6 +106,19 @@ fn build_scene_layer<'a>(
 
     // Build view
     l.load_scene_and_edit(("editor.frame", "scene_node"), |l| {
+        // Highlight the node if it was picked in inspect mode.
+        let node_entity = l.id();
+        let node_ref = scene_ref.clone();
+        l.update_on(
+            broadcast::<EditorNodeInspected>(),
+            move |_: UpdateId, mut c: Commands, p: PseudoStateParam, inspection: Res<EditorInspection>| {
+                match inspection.inspected() == Some(&node_ref) {
+                    true => p.try_select(node_entity, &mut c),
+                    false => p.try_deselect(node_entity, &mut c),
+                };
+            },
+        );
+
         // Set node name.
         let ref_path = scene_ref.path.clone();
         l.get("name")
@@ -202,6 +194,13 @@ fn build_file_view(In((base_entity, file)): In<(Entity, CobFile)>, mut c: Comman
             // Look up file in editor to get file data.
             let Some(file_data) = editor.get_file(&file) else { return };
 
+            // Handle files that failed to parse.
+            // - The file's view will be rebuilt when the file is fixed.
+            if file_data.has_errors() {
+                c.ui_builder(base_entity).load_scene(("editor.frame", "file_has_errors"), &mut s);
+                return;
+            }
+
             // Handle non-editable files.
             // Note: these are filtered out by the dropdown but we handle it just in case.
             if !file_data.is_editable() {
@@ -209,6 +208,23 @@ fn build_file_view(In((base_entity, file)): In<(Entity, CobFile)>, mut c: Comman
                 return;
             }
 
+            // Handle files that changed on disk while they had unsaved changes.
+            if editor.has_conflict(&file) {
+                c.ui_builder(base_entity).load_scene_and_edit(("editor.frame", "file_conflict"), &mut s, |l| {
+                    for (button, resolution) in [
+                        ("buttons::keep_mine", ConflictResolution::KeepMine),
+                        ("buttons::take_theirs", ConflictResolution::TakeTheirs),
+                        ("buttons::merge", ConflictResolution::Merge),
+                    ] {
+                        let file = file.clone();
+                        l.get(button).on_pressed(move |mut c: Commands| {
+                            c.queue(ResolveFileConflict { file: file.clone(), resolution });
+                        });
+                    }
+                });
+                return;
+            }
+
             // Save tracked hash, used to coordinate rebuilds on save.
             *tracked_hash = Some(file_data.last_save_hash);
 
@@ -450,6 +466,45 @@ fn build_editor_view(mut c: Commands, mut s: ResMut<SceneLoader>, camera: Query<
         );
         l.react().entity_event(unsaved, Disable);
 
+        // Build inspect button.
+        // - Picking a node in inspect mode selects the node's file.
+        let inspect = l.get("footer::inspect").id();
+        l.get("footer::inspect").on_pressed(
+            move |mut c: Commands, p: PseudoStateParam, mut inspection: ResMut<EditorInspection>| {
+                inspection.toggle();
+                match inspection.is_enabled() {
+                    true => p.try_select(inspect, &mut c),
+                    false => p.try_deselect(inspect, &mut c),
+                };
+            },
+        );
+        let dropdown_entity = l.get("dropdown").id();
+        l.react().on(
+            broadcast::<EditorNodeInspected>(),
+            move |//
+                event: BroadcastEvent<EditorNodeInspected>,
+                mut c: Commands,
+                p: PseudoStateParam,
+                editor: Res<CobEditor>//
+            | {
+                let Some(inspected) = event.try_read() else { return };
+                p.try_deselect(inspect, &mut c);
+
+                let SceneFile::File(file) = &inspected.node.file else { return };
+                if !editor.get_file(file).is_some_and(|file_data| file_data.is_editable()) {
+                    tracing::warn!("failed inspecting {:?}; the file is not editable", inspected.node);
+                    return;
+                }
+
+                // Select the file, then refresh the dropdown so it displays the selection.
+                c.react().entity_event(dropdown_entity, Some(file.clone()));
+                if !p.entity_has(dropdown_entity, PseudoState::Open) {
+                    c.react().entity_event(dropdown_entity, Open);
+                }
+                c.react().entity_event(dropdown_entity, Close);
+            },
+        );
+
         // Build save button.
         // TODO: use CMD-S instead?
         l.get("footer::save")
@@ -459,6 +514,45 @@ fn build_editor_view(mut c: Commands, mut s: ResMut<SceneLoader>, camera: Query<
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Undoes edits to the selected file with `Ctrl+Z`, and redoes them with `Ctrl+Shift+Z`.
+///
+/// `Cmd` can be used instead of `Ctrl`. Only keys pressed while the editor window is focused are used.
+fn handle_undo_keys(
+    mut c: Commands,
+    mut keys: EventReader<KeyboardInput>,
+    pressed: Res<ButtonInput<KeyCode>>,
+    selection: Res<EditorFileSelection>,
+    editor_window: Query<Entity, With<EditorWindow>>,
+)
+{
+    let editor_window = editor_window.get_single().ok();
+    let control = pressed.any_pressed([
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::SuperLeft,
+        KeyCode::SuperRight,
+    ]);
+    let shift = pressed.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    for key in keys.read() {
+        if key.state != ButtonState::Pressed || key.key_code != KeyCode::KeyZ || Some(key.window) != editor_window
+        {
+            continue;
+        }
+        if !control {
+            continue;
+        }
+        let Some(file) = selection.0.clone() else { continue };
+
+        match shift {
+            true => c.queue(RedoEdit { file }),
+            false => c.queue(UndoEdit { file }),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 // TODO: try to make auto-moving the window smoother
 // TODO: the editor's position does not sync with the window on startup until you move the window
 // - maybe infer it from window starting size + monitor dimensions?
@@ -560,6 +654,7 @@ impl Plugin for CobEditorBuildPlugin
         app.add_plugins(CobEditorTemplatePlugin)
             .init_resource::<EditorFileSelection>()
             .add_systems(First, refresh_editor_window)
+            .add_systems(Update, handle_undo_keys)
             .add_systems(OnEnter(LoadState::Done), build_editor_view);
     }
 }
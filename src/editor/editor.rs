@@ -1,8 +1,11 @@
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::time::Duration;
 
 use bevy::asset::io::file::FileAssetReader;
 use bevy::prelude::*;
+use bevy::tasks::futures_lite::future;
+use bevy::tasks::{block_on, Task};
 use bevy_cobweb::prelude::*;
 
 use super::*;
@@ -15,16 +18,67 @@ pub(super) struct CobFileData
     pub(super) last_save_hash: CobFileHash,
     /// Data for the file. Defs in this data are *not* resolved.
     pub(super) data: Cob,
+    /// Data for the file as of the last save. Used as the base when merging external changes into unsaved data.
+    saved_data: Cob,
+    /// Whether the file had parse errors when it was loaded.
+    ///
+    /// The invalid parts of the file are missing from `data`, so saving it would discard them.
+    has_errors: bool,
 }
 
 impl CobFileData
 {
     pub(super) fn is_editable(&self) -> bool
     {
+        // Files with errors can't be edited until the errors are fixed on disk.
+        if self.has_errors {
+            return false;
+        }
+
         // Temp hack to qualify files with non-default asset source as uneditable, since we don't have a good
         // way to save them yet.
         self.data.file.as_str().find("://").is_none()
     }
+
+    pub(super) fn has_errors(&self) -> bool
+    {
+        self.has_errors
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// New file data that was loaded while the editor had unsaved changes to the file.
+struct CobFileConflict
+{
+    theirs: Cob,
+    hash: CobFileHash,
+    has_errors: bool,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Writes file data to a scratch file then replaces the target file with it.
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+fn write_file(scratch: &std::path::Path, target: &std::path::Path, buff: &[u8]) -> Result<(), String>
+{
+    use std::io::Write;
+
+    // Write to scratch file.
+    {
+        let mut file =
+            std::fs::File::create(scratch).map_err(|err| format!("failed opening scratch file: {err:?}"))?;
+        // Set length to zero in case a previous use of the scratch file failed halfway.
+        file.set_len(0)
+            .map_err(|err| format!("failed truncating scratch file to zero length: {err:?}"))?;
+        file.write_all(buff)
+            .map_err(|err| format!("failed writing the buffer to scratch: {err:?}"))?;
+        file.sync_all()
+            .map_err(|err| format!("failed syncing scratch: {err:?}"))?;
+    }
+
+    // Safely replace the target file with the scratch.
+    std::fs::rename(scratch, target).map_err(|err| format!("failed renaming scratch: {err:?}"))
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -39,6 +93,21 @@ pub(crate) struct CobEditor
     /// Files waiting to be saved.
     unsaved: HashSet<CobFile>,
 
+    /// Files sent to the backend for re-processing after structural edits.
+    reprocessing: HashSet<CobFile>,
+
+    /// Undo/redo history of edits.
+    history: EditorHistory,
+
+    /// Files that changed on disk while they had unsaved changes.
+    conflicts: HashMap<CobFile, CobFileConflict>,
+
+    /// Files being written to disk.
+    saving: HashMap<CobFile, Task<Result<(), String>>>,
+
+    /// Files that failed to be written. They will be written on the next save even if they are unchanged.
+    failed_saves: HashSet<CobFile>,
+
     /// Asset directory location.
     ///
     /// If there is no path then files cannot be saved.
@@ -62,6 +131,11 @@ impl CobEditor
         Self {
             files: HashMap::default(),
             unsaved: HashSet::default(),
+            reprocessing: HashSet::default(),
+            history: EditorHistory::default(),
+            conflicts: HashMap::default(),
+            saving: HashMap::default(),
+            failed_saves: HashSet::default(),
             asset_dir,
         }
     }
@@ -86,13 +160,28 @@ impl CobEditor
         self.files.get_mut(file)
     }
 
+    /// Returns `true` if the file changed on disk while it had unsaved changes, and the conflict is unresolved.
+    ///
+    /// Files with conflicts can't be edited or saved.
+    pub(super) fn has_conflict(&self, file: &CobFile) -> bool
+    {
+        self.conflicts.contains_key(file)
+    }
+
     /// Adds a file that was just processed by the CobwebAssetCache.
-    pub(crate) fn add_processed(&mut self, c: &mut Commands, hash: CobFileHash, data: &Cob)
+    ///
+    /// Files with parse errors are tracked but can't be edited.
+    pub(crate) fn add_processed(&mut self, c: &mut Commands, hash: CobFileHash, has_errors: bool, data: &Cob)
     {
         let Some(existing) = self.files.get_mut(&data.file) else {
             self.files.insert(
                 data.file.clone(),
-                CobFileData { last_save_hash: hash, data: data.clone() },
+                CobFileData {
+                    last_save_hash: hash,
+                    data: data.clone(),
+                    saved_data: data.clone(),
+                    has_errors,
+                },
             );
             c.react()
                 .broadcast(EditorNewFile { file: data.file.clone() });
@@ -103,24 +192,104 @@ impl CobEditor
         c.react()
             .broadcast(EditorFileExternalChange { file: data.file.clone() });
 
-        // Remove from unsaved.
-        let removed = self.unsaved.remove(&data.file);
+        // Files re-processed after structural edits already have the edited data, and other files with the same
+        // hash are unchanged.
+        self.reprocessing.remove(&data.file);
+        if existing.last_save_hash == hash {
+            return;
+        }
 
-        // Avoid cloning the data if it's the same data unless we have unsaved changes that need to be discarded.
-        if !removed && existing.last_save_hash == hash {
+        // Don't discard unsaved changes. The conflict must be resolved with `ResolveFileConflict`.
+        if self.unsaved.contains(&data.file) && existing.data != *data {
+            tracing::warn!("file {:?} was changed while it had unsaved changes in the editor; resolve the \
+                conflict in the editor to keep your changes, take the new file data, or merge them", data.file);
+            self.conflicts.insert(
+                data.file.clone(),
+                CobFileConflict { theirs: data.clone(), hash, has_errors },
+            );
+            c.react()
+                .broadcast(EditorFileConflict { file: data.file.clone() });
             return;
         }
 
-        if removed {
-            tracing::warn!("discarding unsaved changes for file {:?}; changes were overwritten by new \
-                file data that was hot-reloaded", data.file);
+        // Save new data.
+        self.accept_external(c, hash, has_errors, data.clone());
+    }
+
+    /// Replaces a file's data with new data from disk, discarding unsaved changes.
+    fn accept_external(&mut self, c: &mut Commands, hash: CobFileHash, has_errors: bool, data: Cob)
+    {
+        let Some(existing) = self.files.get_mut(&data.file) else { return };
+
+        self.conflicts.remove(&data.file);
+        if self.unsaved.remove(&data.file) {
             c.react()
                 .broadcast(EditorFileSaved { file: data.file.clone(), hash });
         }
 
-        // Save new data.
+        // Undo history no longer applies to the file.
+        self.history.invalidate(&data.file);
         existing.last_save_hash = hash;
-        existing.data = data.clone();
+        existing.saved_data = data.clone();
+        existing.data = data;
+        existing.has_errors = has_errors;
+    }
+
+    /// Resolves a conflict between unsaved changes and new file data from disk.
+    ///
+    /// Returns the resolved data if it should be propagated to the backend, which currently has the new data.
+    pub(super) fn resolve_conflict(
+        &mut self,
+        c: &mut Commands,
+        file: &CobFile,
+        resolution: ConflictResolution,
+    ) -> Option<Cob>
+    {
+        let conflict = self.conflicts.remove(file)?;
+        let file_data = self.files.get_mut(file)?;
+
+        let resolved = match resolution {
+            // The editor's data is unchanged, so its undo history still applies.
+            ConflictResolution::KeepMine => file_data.data.clone(),
+            ConflictResolution::TakeTheirs => {
+                c.react()
+                    .broadcast(EditorFileExternalChange { file: file.clone() });
+                self.accept_external(c, conflict.hash, conflict.has_errors, conflict.theirs);
+                return None;
+            }
+            ConflictResolution::Merge if conflict.has_errors => {
+                tracing::warn!("failed merging {:?}; the new file data has errors; fix the errors, or keep your \
+                    changes instead", file);
+                self.conflicts.insert(file.clone(), conflict);
+                return None;
+            }
+            ConflictResolution::Merge => {
+                match merge_cob(&file_data.saved_data, &file_data.data, &conflict.theirs) {
+                    Ok((merged, conflicting)) => {
+                        if !conflicting.is_empty() {
+                            tracing::warn!("merged {:?} with conflicts; kept the editor's version of {:?}",
+                                file, conflicting);
+                        }
+
+                        // Undo history doesn't apply to the merged data.
+                        self.history.invalidate(file);
+                        merged
+                    }
+                    Err(err) => {
+                        tracing::warn!("failed merging {:?}; {err}; keep your changes or take the new file data \
+                            instead", file);
+                        self.conflicts.insert(file.clone(), conflict);
+                        return None;
+                    }
+                }
+            }
+        };
+
+        // The new data is on disk now, so saving the resolved data will overwrite it.
+        file_data.last_save_hash = conflict.hash;
+        file_data.saved_data = conflict.theirs;
+        file_data.data = resolved.clone();
+        Some(resolved)
     }
 
     pub(super) fn mark_unsaved(&mut self, c: &mut Commands, file: CobFile)
@@ -130,9 +299,67 @@ impl CobEditor
         self.unsaved.insert(file);
     }
 
+    /// Records that a file was sent to the backend for re-processing after a structural edit.
+    ///
+    /// The file will stay unsaved when the re-processed data comes back through [`Self::add_processed`].
+    pub(super) fn mark_reprocessing(&mut self, file: CobFile)
+    {
+        self.reprocessing.insert(file);
+    }
+
+    /// Records an edit to a file in the undo history.
+    ///
+    /// Patches to the same value made in quick succession are merged into one undo step if they have the same
+    /// `group`.
+    pub(super) fn record_edit(&mut self, file: CobFile, edit: CobEdit, group: Option<EditGroup>, now: Duration)
+    {
+        self.history.record(file, edit, group, now);
+    }
+
+    /// Undoes the last edit to a file.
+    ///
+    /// Returns the restored file data, which should be propagated to the backend.
+    pub(super) fn undo(&mut self, file: &CobFile) -> Option<Cob>
+    {
+        if self.has_conflict(file) {
+            tracing::warn!("ignoring undo in {:?}; the file has an unresolved conflict", file);
+            return None;
+        }
+        let file_data = self.files.get_mut(file)?;
+        match self.history.undo(file, &mut file_data.data) {
+            Ok(true) => Some(file_data.data.clone()),
+            Ok(false) => None,
+            Err(err) => {
+                tracing::warn!("failed undoing edit in {:?}; {err}; undo history was cleared", file);
+                None
+            }
+        }
+    }
+
+    /// Redoes the last undone edit to a file.
+    ///
+    /// Returns the restored file data, which should be propagated to the backend.
+    pub(super) fn redo(&mut self, file: &CobFile) -> Option<Cob>
+    {
+        if self.has_conflict(file) {
+            tracing::warn!("ignoring redo in {:?}; the file has an unresolved conflict", file);
+            return None;
+        }
+        let file_data = self.files.get_mut(file)?;
+        match self.history.redo(file, &mut file_data.data) {
+            Ok(true) => Some(file_data.data.clone()),
+            Ok(false) => None,
+            Err(err) => {
+                tracing::warn!("failed redoing edit in {:?}; {err}; undo history was cleared", file);
+                None
+            }
+        }
+    }
+
     /// Saves currently-unsaved files.
-    // TODO: currently blocks the main loop, maybe pass this off to the CPU thread pool? problem is how to
-    // correctly synchronize with the editor; also need to be careful about not contesting the scratch file name
+    ///
+    /// Files are written to disk on the IO task pool. Files with unresolved conflicts and files that aren't
+    /// editable are not saved.
     pub(super) fn save(&mut self, c: &mut Commands, cob_cache: &mut CobAssetCache, registry: &CobHashRegistry)
     {
         let Some(asset_dir) = &self.asset_dir else {
@@ -144,7 +371,25 @@ impl CobEditor
             return;
         };
 
-        for unsaved in self.unsaved.drain() {
+        let mut to_save = Vec::with_capacity(self.unsaved.len());
+        for unsaved in self.unsaved.iter() {
+            if self.conflicts.contains_key(unsaved) {
+                tracing::warn!("not saving {unsaved:?}; the file has an unresolved conflict");
+                continue;
+            }
+            if !self
+                .files
+                .get(unsaved)
+                .is_some_and(|file_data| file_data.is_editable())
+            {
+                tracing::warn!("not saving {unsaved:?}; the file is not editable");
+                continue;
+            }
+            to_save.push(unsaved.clone());
+        }
+
+        for unsaved in to_save {
+            self.unsaved.remove(&unsaved);
             let Some(file_data) = self.files.get_mut(&unsaved) else {
                 tracing::error!("file {:?} is missing on save (this is a bug)", unsaved);
                 continue;
@@ -163,7 +408,9 @@ impl CobEditor
                 .broadcast(EditorFileSaved { file: unsaved.clone(), hash });
 
             // If hash didn't change, no need to save the file since the 'unsaved' status is spurious.
-            if hash == file_data.last_save_hash {
+            // - Files are rewritten if their last write failed.
+            let retry = self.failed_saves.remove(&unsaved);
+            if hash == file_data.last_save_hash && !retry {
                 continue;
             }
 
@@ -179,53 +426,74 @@ impl CobEditor
             }
 
             // Update the hash registry.
+            // - This happens before the file is written so the asset loader can recognize the saved file.
             registry.set_file_for_save(unsaved.as_str(), file_data.last_save_hash, hash);
 
             // Save the file.
             file_data.last_save_hash = hash;
+            file_data.saved_data = file_data.data.clone();
 
             #[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
             {
-                use std::io::Write;
-
-                // Write to scratch file.
-                let scratch = asset_dir.join(".__cob_editor_scratch");
-                {
-                    let mut file = match std::fs::File::create(&scratch) {
-                        Ok(file) => file,
-                        Err(err) => {
-                            tracing::warn!("saving {unsaved:?} failed unexpectedly while opening scratch file: {err:?}");
-                            continue;
-                        }
-                    };
-                    // Set length to zero in case a previous use of the scratch file failed halfway.
-                    if let Err(err) = file.set_len(0) {
-                        tracing::warn!("saving {unsaved:?} failed unexpectedly while truncating scratch file to zero \
-                            length: {err:?}");
-                        continue;
-                    }
-                    if let Err(err) = file.write_all(&buff) {
-                        tracing::warn!("saving {unsaved:?} failed unexpectedly while writing the buffer to \
-                            scratch: {err:?}");
-                        continue;
+                use bevy::tasks::IoTaskPool;
+
+                // Each file gets its own scratch file next to it, so concurrent saves don't contest it.
+                let target = asset_dir.join(unsaved.as_str());
+                let Some(file_name) = target.file_name() else {
+                    tracing::warn!("saving {unsaved:?} failed unexpectedly; file path has no file name");
+                    continue;
+                };
+                let scratch =
+                    target.with_file_name(format!(".{}.__cob_editor_scratch", file_name.to_string_lossy()));
+
+                // Saves of the same file are written in order.
+                // - A failed write is superseded by the newer write.
+                let prev = self.saving.remove(&unsaved);
+                let task = IoTaskPool::get().spawn(async move {
+                    if let Some(prev) = prev {
+                        let _ = prev.await;
                     }
-                    if let Err(err) = file.sync_all() {
-                        tracing::warn!("saving {unsaved:?} failed unexpectedly while syncing: {err:?}");
-                        continue;
-                    }
-                }
+                    write_file(&scratch, &target, &buff)
+                });
+                self.saving.insert(unsaved, task);
+            }
+        }
+    }
 
-                // Safely replace the target file with the scratch.
-                if let Err(err) = std::fs::rename(&scratch, asset_dir.join(unsaved.as_str())) {
-                    tracing::warn!("saving {unsaved:?} failed unexpectedly while renaming scratch: {err:?}");
-                }
+    /// Checks for finished file writes.
+    ///
+    /// Files that failed to be written are marked unsaved again.
+    fn poll_saves(&mut self, c: &mut Commands)
+    {
+        let mut failed = vec![];
+        self.saving.retain(|file, task| {
+            let Some(result) = block_on(future::poll_once(task)) else { return true };
+            if let Err(err) = result {
+                tracing::warn!("saving {file:?} failed unexpectedly; {err}");
+                failed.push(file.clone());
             }
+            false
+        });
+
+        for file in failed {
+            self.failed_saves.insert(file.clone());
+            self.mark_unsaved(c, file);
         }
     }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
+fn poll_editor_saves(mut c: Commands, mut editor: ResMut<CobEditor>)
+{
+    if editor.saving.is_empty() {
+        return;
+    }
+    editor.poll_saves(&mut c);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 pub(super) struct CobEditorImplPlugin;
 
 impl Plugin for CobEditorImplPlugin
@@ -236,7 +504,8 @@ impl Plugin for CobEditorImplPlugin
         let asset_plugin = added
             .get(0)
             .expect("AssetPlugin should be added before CobwebUiPlugin");
-        app.insert_resource(CobEditor::new(asset_plugin.file_path.clone()));
+        app.insert_resource(CobEditor::new(asset_plugin.file_path.clone()))
+            .add_systems(Update, poll_editor_saves);
     }
 }
 
@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
 use bevy::prelude::*;
+use bevy::reflect::{List, Map, ReflectRef};
+use bevy_cobweb::prelude::*;
 use serde::de::DeserializeSeed;
 
 use super::*;
@@ -30,6 +32,98 @@ impl Command for SaveEditor
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Command for undoing the last edit to a file in the editor.
+///
+/// Edits are undone in the order they were made. A file's history is cleared if it is reloaded with external
+/// changes or merged with them. The editor view is rebuilt when the backend has re-processed the file.
+#[derive(Debug)]
+pub struct UndoEdit
+{
+    pub file: CobFile,
+}
+
+impl Command for UndoEdit
+{
+    fn apply(self, world: &mut World)
+    {
+        world.resource_scope::<CobEditor, ()>(|world: &mut World, mut editor: Mut<CobEditor>| {
+            let Some(restored) = editor.undo(&self.file) else { return };
+            propagate_file_edit(world, &mut editor, &self.file, restored, "undo");
+        });
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command for redoing the last undone edit to a file in the editor.
+///
+/// Redo steps are cleared when a new edit is made to the file.
+#[derive(Debug)]
+pub struct RedoEdit
+{
+    pub file: CobFile,
+}
+
+impl Command for RedoEdit
+{
+    fn apply(self, world: &mut World)
+    {
+        world.resource_scope::<CobEditor, ()>(|world: &mut World, mut editor: Mut<CobEditor>| {
+            let Some(restored) = editor.redo(&self.file) else { return };
+            propagate_file_edit(world, &mut editor, &self.file, restored, "redo");
+        });
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// How to resolve a conflict between unsaved changes in the editor and new file data from disk.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ConflictResolution
+{
+    /// Keep the editor's data. It will overwrite the file on disk when saved.
+    KeepMine,
+    /// Discard unsaved changes and use the file data from disk.
+    TakeTheirs,
+    /// Merge unsaved changes with the file data from disk, one scene node at a time.
+    ///
+    /// Sections other than `#scenes` are merged as a whole. If a scene node or section was changed on both sides,
+    /// then the editor's version is kept. Fails if sections were added or removed on either side.
+    Merge,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command for resolving a conflict reported by [`EditorFileConflict`].
+///
+/// Files with unresolved conflicts can't be edited or saved.
+#[derive(Debug)]
+pub struct ResolveFileConflict
+{
+    pub file: CobFile,
+    pub resolution: ConflictResolution,
+}
+
+impl Command for ResolveFileConflict
+{
+    fn apply(self, world: &mut World)
+    {
+        world.resource_scope::<CobEditor, ()>(|world: &mut World, mut editor: Mut<CobEditor>| {
+            let resolved = editor.resolve_conflict(&mut world.commands(), &self.file, self.resolution);
+            let Some(resolved) = resolved else { return };
+
+            // Replace the conflict prompt in the editor view.
+            world
+                .commands()
+                .react()
+                .broadcast(EditorFileExternalChange { file: self.file.clone() });
+            propagate_file_edit(world, &mut editor, &self.file, resolved, "conflict resolution");
+        });
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Command for patching a value in the editor (a command or scene node loadable).
 #[derive(Debug)]
 pub struct SubmitPatch
@@ -60,6 +154,13 @@ impl Command for SubmitPatch
                 return;
             };
 
+            // Exit if the file has a conflict.
+            if editor.has_conflict(&file) {
+                tracing::warn!("ignoring editor patch for {} in {:?}; file has an unresolved conflict",
+                    editor_ref.loadable_name, editor_ref.scene_ref);
+                return;
+            }
+
             // Look up the targeted file.
             let Some(file_data) = editor.get_file_mut(&file) else {
                 tracing::warn!("ignoring editor patch for {} in {:?}; file is unknown",
@@ -67,6 +168,13 @@ impl Command for SubmitPatch
                 return;
             };
 
+            // Exit if the file can't be edited.
+            if !file_data.is_editable() {
+                tracing::warn!("ignoring editor patch for {} in {:?}; file is not editable",
+                    editor_ref.loadable_name, editor_ref.scene_ref);
+                return;
+            }
+
             // Exit if file hash doesn't match.
             if file_data.last_save_hash != editor_ref.file_hash {
                 tracing::warn!("ignoring editor patch for {} in {:?}; widget has a stale editor reference",
@@ -81,6 +189,9 @@ impl Command for SubmitPatch
                 return;
             };
 
+            // Save the original value for the undo history.
+            let before = targeted.clone();
+
             // Prep deserializer for targeted loadable.
             let loadables = world.resource::<LoadableRegistry>();
             let type_registry = world.resource::<AppTypeRegistry>().read();
@@ -136,6 +247,11 @@ impl Command for SubmitPatch
             // Replace the old value.
             *targeted = new_loadable.clone();
 
+            // Record the patch in the undo history.
+            let now = world.resource::<Time<Real>>().elapsed();
+            let edit = CobEdit::loadable(&editor_ref, before, new_loadable.clone());
+            editor.record_edit(file.clone(), edit, Some(EditGroup::new(&editor_ref)), now);
+
             // Mark the file as unsaved in the editor.
             let mut commands = world.commands();
             editor.mark_unsaved(&mut commands, file.clone());
@@ -204,34 +320,672 @@ impl Command for SubmitPatch
 
 //-------------------------------------------------------------------------------------------------------------------
 
-// TODO: add/remove struct/enum-struct field
-// - requires re-spawning widgets
-// - requires patching the CobLoadable directly, since fields of reflected values cannot be inserted/removed easily
+/// Applies a structural edit to a file in the editor.
+///
+/// The edit is applied to a copy of the file's data, so nothing changes if it fails. Edited files are marked
+/// unsaved and re-processed by the backend like hot-reloaded files, which updates the app and respawns the
+/// editor's widgets for the file.
+fn apply_structural_edit(
+    world: &mut World,
+    file: &CobFile,
+    file_hash: Option<CobFileHash>,
+    edit_name: &str,
+    edit: impl FnOnce(&World, &mut Cob) -> Result<(), String>,
+)
+{
+    world.resource_scope::<CobEditor, ()>(|world: &mut World, mut editor: Mut<CobEditor>| {
+        // Exit if the file has a conflict.
+        if editor.has_conflict(file) {
+            tracing::warn!("ignoring {edit_name} in {:?}; file has an unresolved conflict", file);
+            return;
+        }
+
+        // Look up the targeted file.
+        let Some(file_data) = editor.get_file_mut(file) else {
+            tracing::warn!("ignoring {edit_name} in {:?}; file is unknown", file);
+            return;
+        };
+
+        // Exit if the file can't be edited.
+        if !file_data.is_editable() {
+            tracing::warn!("ignoring {edit_name} in {:?}; file is not editable", file);
+            return;
+        }
+
+        // Exit if file hash doesn't match.
+        if file_hash.is_some_and(|hash| hash != file_data.last_save_hash) {
+            tracing::warn!("ignoring {edit_name} in {:?}; widget has a stale editor reference", file);
+            return;
+        }
+
+        // Edit a copy of the data.
+        let mut data = file_data.data.clone();
+        if let Err(err) = (edit)(world, &mut data) {
+            tracing::warn!("ignoring {edit_name} in {:?}; {err}", file);
+            return;
+        }
+        let before = std::mem::replace(&mut file_data.data, data.clone());
+
+        // Record the edit in the undo history.
+        if let Some(edit) = CobEdit::diff(&before, &data) {
+            let now = world.resource::<Time<Real>>().elapsed();
+            editor.record_edit(file.clone(), edit, None, now);
+        }
+
+        // Rebuild the editor view right away so widgets with stale references are removed.
+        world
+            .commands()
+            .react()
+            .broadcast(EditorFileExternalChange { file: file.clone() });
+        propagate_file_edit(world, &mut editor, file, data, edit_name);
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Propagates new data for a file that was replaced in the editor.
+///
+/// The file is marked unsaved and re-processed in the backend. The editor view is rebuilt when the re-processed
+/// file is added back to the editor.
+fn propagate_file_edit(world: &mut World, editor: &mut CobEditor, file: &CobFile, data: Cob, edit_name: &str)
+{
+    let Some(hash) = editor
+        .get_file(file)
+        .map(|file_data| file_data.last_save_hash)
+    else {
+        return;
+    };
+
+    editor.mark_unsaved(&mut world.commands(), file.clone());
+
+    world.resource_scope::<CobAssetCache, ()>(|world: &mut World, mut cob_cache: Mut<CobAssetCache>| {
+        let mut commands_buffer = world.resource_mut::<CommandsBuffer>();
+        if !cob_cache.reprocess_edited_file(&mut commands_buffer, data, hash) {
+            tracing::warn!("failed propagating {edit_name} in {:?} to backend; target file is currently being \
+                re-processed, likely due to a hot-reloaded change; the current editor view of the file will \
+                likely be overwritten soon", file);
+            return;
+        }
+        editor.mark_reprocessing(file.clone());
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Applies a structural edit to the value targeted by an editor reference.
+///
+/// `edit` receives the targeted value and returns its replacement.
+fn apply_value_edit(
+    world: &mut World,
+    editor_ref: &CobEditorRef,
+    edit_name: &str,
+    edit: impl FnOnce(&(dyn PartialReflect + 'static)) -> Result<Box<dyn PartialReflect>, String>,
+)
+{
+    // Exit if source widget is dead.
+    if editor_ref.death_signal.is_dead() {
+        tracing::warn!("ignoring {edit_name} for {} in {:?}; originating widget is marked 'dead'",
+            editor_ref.loadable_name, editor_ref.scene_ref);
+        return;
+    }
+
+    let Some(file) = get_scene_file(&editor_ref.scene_ref, edit_name) else { return };
+
+    apply_structural_edit(world, &file, Some(editor_ref.file_hash), edit_name, |world, data| {
+        // Look up the targeted loadable.
+        let targeted = get_targeted(data, editor_ref)
+            .ok_or_else(|| format!("targeted loadable {} not found", editor_ref.loadable_name))?;
+
+        // Get a PartialReflect for the targeted loadable.
+        let loadables = world.resource::<LoadableRegistry>();
+        let type_registry = world.resource::<AppTypeRegistry>().read();
+        let Some((deserializer, ..)) = get_deserializer(&type_registry, editor_ref.loadable_name, loadables)
+        else {
+            return Err(format!("failed looking up loadable {} in type registry", editor_ref.loadable_name));
+        };
+        let reflected = deserializer
+            .deserialize(&*targeted)
+            .map_err(|err| format!("original value failed to deserialize: {err:?}"))?;
+
+        // Rebuild the loadable with the edited value.
+        let rebuilt = editor_ref
+            .structure_path
+            .try_rebuild_value(reflected.as_ref(), edit)?;
+        let mut new_loadable = CobLoadable::extract_partial_reflect(rebuilt.as_ref(), &type_registry)
+            .map_err(|err| format!("failed extracting edited value: {err:?}"))?;
+
+        // Recover fill from the previous value.
+        new_loadable.recover_fill(targeted);
+        *targeted = new_loadable;
+        Ok(())
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Gets the file of a scene ref that should point to a file.
+fn get_scene_file(scene_ref: &SceneRef, edit_name: &str) -> Option<CobFile>
+{
+    let SceneFile::File(file) = scene_ref.file.clone() else {
+        tracing::error!("ignoring {edit_name} in {:?}; scene ref unexpectedly has a manifest key instead of file",
+            scene_ref);
+        return None;
+    };
+    Some(file)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Returns `true` if the scene ref points to the `#commands` section of a file.
+fn is_commands_ref(scene_ref: &SceneRef) -> bool
+{
+    scene_ref.path.iter().next() == Some("#commands")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command for adding a field to a struct or struct-like enum variant in the editor.
+///
+/// The `editor_ref` should point to the struct. Fails if the field is already present.
+#[derive(Debug)]
+pub struct InsertStructField
+{
+    pub editor_ref: CobEditorRef,
+    pub field: String,
+    pub value: Box<dyn PartialReflect + 'static>,
+}
+
+impl Command for InsertStructField
+{
+    fn apply(self, world: &mut World)
+    {
+        let Self { editor_ref, field, value } = self;
+        apply_value_edit(world, &editor_ref, "struct field insertion", move |target| {
+            edit_struct_fields(target, move |fields| {
+                if fields.iter().any(|(name, _)| *name == field) {
+                    return Err(format!("field {field} already exists"));
+                }
+                fields.push((field, value));
+                Ok(())
+            })
+        });
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command for removing a field from a struct or struct-like enum variant in the editor.
+///
+/// The `editor_ref` should point to the struct. Removed fields will use their default values if the type allows
+/// it.
+#[derive(Debug)]
+pub struct RemoveStructField
+{
+    pub editor_ref: CobEditorRef,
+    pub field: String,
+}
+
+impl Command for RemoveStructField
+{
+    fn apply(self, world: &mut World)
+    {
+        let Self { editor_ref, field } = self;
+        apply_value_edit(world, &editor_ref, "struct field removal", move |target| {
+            edit_struct_fields(target, move |fields| {
+                let Some(pos) = fields.iter().position(|(name, _)| *name == field) else {
+                    return Err(format!("field {field} not found"));
+                };
+                fields.remove(pos);
+                Ok(())
+            })
+        });
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Replaces an enum with a value of the same type.
+fn set_enum_variant(
+    target: &(dyn PartialReflect + 'static),
+    value: Box<dyn PartialReflect + 'static>,
+) -> Result<Box<dyn PartialReflect>, String>
+{
+    let ReflectRef::Enum(_) = target.reflect_ref() else { return Err(String::from("expected an enum")) };
+    let ReflectRef::Enum(_) = value.reflect_ref() else {
+        return Err(String::from("expected an enum value"));
+    };
+    let type_id = |value: &dyn PartialReflect| value.get_represented_type_info().map(|info| info.type_id());
+    if type_id(target) != type_id(value.as_ref()) {
+        return Err(String::from("enum value has a different type"));
+    }
+    Ok(value)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command for changing the variant of an enum in the editor.
+///
+/// The `editor_ref` should point to the enum, and `value` should be an enum of the same type with the new variant.
+/// This includes `Option`s, which are written as `none` or the flattened `Some` value.
+#[derive(Debug)]
+pub struct SetEnumVariant
+{
+    pub editor_ref: CobEditorRef,
+    pub value: Box<dyn PartialReflect + 'static>,
+}
+
+impl Command for SetEnumVariant
+{
+    fn apply(self, world: &mut World)
+    {
+        let Self { editor_ref, value } = self;
+        apply_value_edit(world, &editor_ref, "enum variant change", move |target| {
+            set_enum_variant(target, value)
+        });
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command for inserting an entry in a list in the editor.
+///
+/// The `editor_ref` should point to the list. Entries at and after `index` are shifted back.
+#[derive(Debug)]
+pub struct InsertListEntry
+{
+    pub editor_ref: CobEditorRef,
+    pub index: usize,
+    pub value: Box<dyn PartialReflect + 'static>,
+}
+
+impl Command for InsertListEntry
+{
+    fn apply(self, world: &mut World)
+    {
+        let Self { editor_ref, index, value } = self;
+        apply_value_edit(world, &editor_ref, "list entry insertion", move |target| {
+            let ReflectRef::List(dyn_list) = target.reflect_ref() else {
+                return Err(String::from("expected a list"));
+            };
+            if index > dyn_list.len() {
+                return Err(format!("index {index} is out of bounds for list of length {}", dyn_list.len()));
+            }
+            let mut rebuilt = dyn_list.clone_dynamic();
+            rebuilt.insert(index, value);
+            Ok(Box::new(rebuilt))
+        });
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command for removing an entry from a list in the editor.
+///
+/// The `editor_ref` should point to the list.
+#[derive(Debug)]
+pub struct RemoveListEntry
+{
+    pub editor_ref: CobEditorRef,
+    pub index: usize,
+}
+
+impl Command for RemoveListEntry
+{
+    fn apply(self, world: &mut World)
+    {
+        let Self { editor_ref, index } = self;
+        apply_value_edit(world, &editor_ref, "list entry removal", move |target| {
+            let ReflectRef::List(dyn_list) = target.reflect_ref() else {
+                return Err(String::from("expected a list"));
+            };
+            if index >= dyn_list.len() {
+                return Err(format!("index {index} is out of bounds for list of length {}", dyn_list.len()));
+            }
+            let mut rebuilt = dyn_list.clone_dynamic();
+            rebuilt.remove(index);
+            Ok(Box::new(rebuilt))
+        });
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command for inserting an entry in a map in the editor.
+///
+/// The `editor_ref` should point to the map. Fails if the key is already present.
+#[derive(Debug)]
+pub struct InsertMapEntry
+{
+    pub editor_ref: CobEditorRef,
+    pub key: Box<dyn PartialReflect + 'static>,
+    pub value: Box<dyn PartialReflect + 'static>,
+}
+
+impl Command for InsertMapEntry
+{
+    fn apply(self, world: &mut World)
+    {
+        let Self { editor_ref, key, value } = self;
+        apply_value_edit(world, &editor_ref, "map entry insertion", move |target| {
+            let ReflectRef::Map(dyn_map) = target.reflect_ref() else {
+                return Err(String::from("expected a map"));
+            };
+            if dyn_map.get(key.as_ref()).is_some() {
+                return Err(format!("map key {:?} already exists", key));
+            }
+            let mut rebuilt = dyn_map.clone_dynamic();
+            rebuilt.insert_boxed(key, value);
+            Ok(Box::new(rebuilt))
+        });
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command for removing an entry from a map in the editor.
+///
+/// The `editor_ref` should point to the map.
+#[derive(Debug)]
+pub struct RemoveMapEntry
+{
+    pub editor_ref: CobEditorRef,
+    pub key: Box<dyn PartialReflect + 'static>,
+}
+
+impl Command for RemoveMapEntry
+{
+    fn apply(self, world: &mut World)
+    {
+        let Self { editor_ref, key } = self;
+        apply_value_edit(world, &editor_ref, "map entry removal", move |target| {
+            let ReflectRef::Map(dyn_map) = target.reflect_ref() else {
+                return Err(String::from("expected a map"));
+            };
+            let mut rebuilt = dyn_map.clone_dynamic();
+            if rebuilt.remove(key.as_ref()).is_none() {
+                return Err(format!("map key {:?} not found", key));
+            }
+            Ok(Box::new(rebuilt))
+        });
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command for adding an empty scene node in the editor.
+///
+/// The node is inserted before the `index`-th node among its siblings, or after the last sibling if `index` is
+/// `None`. See [`Cob::insert_scene_node`].
+#[derive(Debug)]
+pub struct AddSceneNode
+{
+    pub node: SceneRef,
+    pub index: Option<usize>,
+}
+
+impl Command for AddSceneNode
+{
+    fn apply(self, world: &mut World)
+    {
+        let Some(file) = get_scene_file(&self.node, "scene node insertion") else { return };
+        apply_structural_edit(world, &file, None, "scene node insertion", |_, data| {
+            data.insert_scene_node(&self.node.path, self.index)
+        });
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command for removing a scene node and all its children in the editor.
+#[derive(Debug)]
+pub struct RemoveSceneNode
+{
+    pub node: SceneRef,
+}
+
+impl Command for RemoveSceneNode
+{
+    fn apply(self, world: &mut World)
+    {
+        let Some(file) = get_scene_file(&self.node, "scene node removal") else { return };
+        apply_structural_edit(world, &file, None, "scene node removal", |_, data| {
+            data.remove_scene_node(&self.node.path)
+        });
+    }
+}
 
 //-------------------------------------------------------------------------------------------------------------------
 
-// TODO: set enum variant
-// - requires re-spawning widgets
-// - might require special handling for Option<T>
+/// Command for moving a scene node among its siblings in the editor.
+///
+/// See [`Cob::move_scene_node`].
+#[derive(Debug)]
+pub struct MoveSceneNode
+{
+    pub node: SceneRef,
+    pub index: usize,
+}
+
+impl Command for MoveSceneNode
+{
+    fn apply(self, world: &mut World)
+    {
+        let Some(file) = get_scene_file(&self.node, "scene node move") else { return };
+        apply_structural_edit(world, &file, None, "scene node move", |_, data| {
+            data.move_scene_node(&self.node.path, self.index)
+        });
+    }
+}
 
 //-------------------------------------------------------------------------------------------------------------------
 
-// TODO: add/remove list entry
-// - requires re-spawning widgets
+/// Command for adding a loadable to a scene node in the editor.
+///
+/// If `SceneRef::path` equals `"#commands"` then the loadable is added to the file's `#commands` section.
+///
+/// The loadable is inserted before the `index`-th loadable in the node, or after the last loadable if `index` is
+/// `None`. Fails if the node already has a loadable of the same type.
+#[derive(Debug)]
+pub struct AddLoadable
+{
+    pub node: SceneRef,
+    pub value: Box<dyn PartialReflect + 'static>,
+    pub index: Option<usize>,
+}
+
+impl Command for AddLoadable
+{
+    fn apply(self, world: &mut World)
+    {
+        let Some(file) = get_scene_file(&self.node, "loadable insertion") else { return };
+        apply_structural_edit(world, &file, None, "loadable insertion", |world, data| {
+            let loadables = world.resource::<LoadableRegistry>();
+            let type_registry = world.resource::<AppTypeRegistry>().read();
+            let loadable = CobLoadable::extract_partial_reflect(self.value.as_ref(), &type_registry)
+                .map_err(|err| format!("failed extracting loadable: {err:?}"))?;
+            let name = loadable.id.to_canonical(None);
+            if get_registration(&type_registry, &name, loadables).is_none() {
+                return Err(format!("{name} is not a registered loadable"));
+            }
+
+            match is_commands_ref(&self.node) {
+                true => data.insert_command_loadable(loadable, self.index),
+                false => data.insert_scene_loadable(&self.node.path, loadable, self.index),
+            }
+        });
+    }
+}
 
 //-------------------------------------------------------------------------------------------------------------------
 
-// TODO: add/remove map entry
-// - requires re-spawning widgets
+/// Command for removing a loadable from a scene node in the editor.
+///
+/// If `SceneRef::path` equals `"#commands"` then the loadable is removed from the file's `#commands` section.
+#[derive(Debug)]
+pub struct RemoveLoadable
+{
+    pub node: SceneRef,
+    /// Shortname of the loadable.
+    pub loadable_name: String,
+}
+
+impl Command for RemoveLoadable
+{
+    fn apply(self, world: &mut World)
+    {
+        let Some(file) = get_scene_file(&self.node, "loadable removal") else { return };
+        apply_structural_edit(
+            world,
+            &file,
+            None,
+            "loadable removal",
+            |_, data| match is_commands_ref(&self.node) {
+                true => data.remove_command_loadable(&self.loadable_name),
+                false => data.remove_scene_loadable(&self.node.path, &self.loadable_name),
+            },
+        );
+    }
+}
 
 //-------------------------------------------------------------------------------------------------------------------
 
-// TODO: add/remove/move scene node
-// - requires re-spawning widgets
+/// Command for moving a loadable among the other loadables in its scene node in the editor.
+///
+/// If `SceneRef::path` equals `"#commands"` then the loadable is moved in the file's `#commands` section.
+#[derive(Debug)]
+pub struct MoveLoadable
+{
+    pub node: SceneRef,
+    /// Shortname of the loadable.
+    pub loadable_name: String,
+    pub index: usize,
+}
+
+impl Command for MoveLoadable
+{
+    fn apply(self, world: &mut World)
+    {
+        let Some(file) = get_scene_file(&self.node, "loadable move") else { return };
+        apply_structural_edit(world, &file, None, "loadable move", |_, data| {
+            match is_commands_ref(&self.node) {
+                true => data.move_command_loadable(&self.loadable_name, self.index),
+                false => data.move_scene_loadable(&self.node.path, &self.loadable_name, self.index),
+            }
+        });
+    }
+}
 
 //-------------------------------------------------------------------------------------------------------------------
 
-// TODO: add/remove/move loadable
-// - requires re-spawning widgets
+#[cfg(test)]
+mod tests
+{
+    use bevy::reflect::serde::TypedReflectDeserializer;
+    use bevy::reflect::TypeRegistry;
+
+    use super::*;
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    enum TestVariant
+    {
+        #[default]
+        A,
+        B(u32),
+    }
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    struct OptionHolder
+    {
+        value: Option<u32>,
+        variant: Option<TestVariant>,
+    }
+
+    fn parse_loadable(raw: &str) -> CobLoadable
+    {
+        let mut data = Cob::parse(Span::new_extra(raw, CobLocationMetadata { file: "test.cob" })).unwrap();
+        data.get_command_loadable_mut("OptionHolder")
+            .unwrap()
+            .clone()
+    }
+
+    fn deserialize(registry: &TypeRegistry, loadable: &CobLoadable) -> Box<dyn PartialReflect>
+    {
+        let registration = registry
+            .get(std::any::TypeId::of::<OptionHolder>())
+            .unwrap();
+        TypedReflectDeserializer::new(registration, registry)
+            .deserialize(loadable)
+            .unwrap()
+    }
+
+    /// Changes the variant of an `OptionHolder` field the same way [`SetEnumVariant`] does.
+    fn set_variant(
+        registry: &TypeRegistry,
+        raw: &str,
+        field: &'static str,
+        value: Box<dyn PartialReflect>,
+    ) -> CobLoadable
+    {
+        let targeted = parse_loadable(raw);
+        let path = ReflectStructurePath { path: Arc::from(vec![ReflectStructurePoint::Struct(field)]) };
+        let rebuilt = path
+            .try_rebuild_value(deserialize(registry, &targeted).as_ref(), |target| {
+                set_enum_variant(target, value)
+            })
+            .unwrap();
+        let mut loadable = CobLoadable::extract_partial_reflect(rebuilt.as_ref(), registry).unwrap();
+        loadable.recover_fill(&targeted);
+        loadable
+    }
+
+    #[test]
+    fn set_option_variant()
+    {
+        let mut registry = TypeRegistry::default();
+        registry.register::<OptionHolder>();
+
+        // `Some` values are flattened and `None` is written as `none`, so they round trip through COB.
+        let cases: [(&str, Box<dyn PartialReflect>, &str, OptionHolder); 4] = [
+            (
+                "value",
+                Box::new(Some(5u32)),
+                "OptionHolder{value:5 variant:none}",
+                OptionHolder { value: Some(5), variant: None },
+            ),
+            (
+                "value",
+                Box::new(None::<u32>),
+                "OptionHolder{value:none variant:none}",
+                OptionHolder { value: None, variant: None },
+            ),
+            (
+                "variant",
+                Box::new(Some(TestVariant::A)),
+                "OptionHolder{value:none variant:A}",
+                OptionHolder { value: None, variant: Some(TestVariant::A) },
+            ),
+            (
+                "variant",
+                Box::new(Some(TestVariant::B(3))),
+                "OptionHolder{value:none variant:B(3)}",
+                OptionHolder { value: None, variant: Some(TestVariant::B(3)) },
+            ),
+        ];
+
+        let mut raw = String::from("#commands\nOptionHolder{value:none variant:none}\n");
+        for (field, value, expected_raw, expected) in cases {
+            let loadable = set_variant(&registry, &raw, field, value);
+            raw = format!("#commands\n{}\n", expected_raw);
+            assert_eq!(loadable, parse_loadable(&raw));
+
+            let reflected = deserialize(&registry, &loadable);
+            assert_eq!(OptionHolder::from_reflect(reflected.as_ref()), Some(expected));
+        }
+
+        // Values must have the same type.
+        let target = Some(1u32);
+        assert!(set_enum_variant(&target, Box::new(Some(1i32))).is_err());
+    }
+}
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -41,3 +41,23 @@ pub struct EditorFileSaved
 }
 
 //-------------------------------------------------------------------------------------------------------------------
+
+/// Reactive event broadcasted when a file changes on disk while it has unsaved changes in the editor.
+///
+/// The conflict can be resolved with [`ResolveFileConflict`].
+#[derive(Debug, Clone)]
+pub struct EditorFileConflict
+{
+    pub file: CobFile,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Reactive event broadcasted when a scene node is picked in the game window in inspect mode.
+#[derive(Debug, Clone)]
+pub struct EditorNodeInspected
+{
+    pub node: SceneRef,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::*;
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Patches to the same value within this window of the previous patch are merged into one undo step.
+const GROUP_WINDOW: Duration = Duration::from_millis(500);
+
+/// Max number of undo steps recorded per file.
+const MAX_HISTORY: usize = 100;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Identifies the value edited by a patch, used to group rapid patches into one undo step.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct EditGroup
+{
+    scene_ref: SceneRef,
+    loadable_name: &'static str,
+    structure_path: ReflectStructurePath,
+}
+
+impl EditGroup
+{
+    pub(super) fn new(editor_ref: &CobEditorRef) -> Self
+    {
+        Self {
+            scene_ref: editor_ref.scene_ref.clone(),
+            loadable_name: editor_ref.loadable_name,
+            structure_path: editor_ref.structure_path.clone(),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// An invertible edit to a file's data.
+///
+/// Edits only store the parts of a file they changed. Applying or reverting an edit fails without changing the
+/// file if the file doesn't contain the value the edit expects to replace.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum CobEdit
+{
+    /// Replaces the value of a command or scene node loadable.
+    Loadable
+    {
+        scene_ref: SceneRef,
+        loadable_name: &'static str,
+        before: CobLoadable,
+        after: CobLoadable,
+    },
+    /// Replaces a range of sections, and the fill at the end of the file.
+    Sections
+    {
+        start: usize,
+        before: Vec<CobSection>,
+        after: Vec<CobSection>,
+        end_fill_before: CobFill,
+        end_fill_after: CobFill,
+    },
+}
+
+impl CobEdit
+{
+    /// Makes an edit that replaces the loadable targeted by an editor reference.
+    pub(super) fn loadable(editor_ref: &CobEditorRef, before: CobLoadable, after: CobLoadable) -> Self
+    {
+        Self::Loadable {
+            scene_ref: editor_ref.scene_ref.clone(),
+            loadable_name: editor_ref.loadable_name,
+            before,
+            after,
+        }
+    }
+
+    /// Makes an edit that turns `before` into `after`.
+    ///
+    /// Only the sections between the first and last changed sections are stored. Returns `None` if nothing
+    /// changed.
+    pub(super) fn diff(before: &Cob, after: &Cob) -> Option<Self>
+    {
+        if before.sections == after.sections && before.end_fill == after.end_fill {
+            return None;
+        }
+
+        let prefix = before
+            .sections
+            .iter()
+            .zip(after.sections.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let suffix = before.sections[prefix..]
+            .iter()
+            .rev()
+            .zip(after.sections[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        Some(Self::Sections {
+            start: prefix,
+            before: before.sections[prefix..(before.sections.len() - suffix)].to_vec(),
+            after: after.sections[prefix..(after.sections.len() - suffix)].to_vec(),
+            end_fill_before: before.end_fill.clone(),
+            end_fill_after: after.end_fill.clone(),
+        })
+    }
+
+    /// Applies the edit to `data`.
+    pub(super) fn apply(&self, data: &mut Cob) -> Result<(), String>
+    {
+        self.replace(data, false)
+    }
+
+    /// Reverts the edit in `data`.
+    pub(super) fn revert(&self, data: &mut Cob) -> Result<(), String>
+    {
+        self.replace(data, true)
+    }
+
+    fn replace(&self, data: &mut Cob, invert: bool) -> Result<(), String>
+    {
+        match self {
+            Self::Loadable { scene_ref, loadable_name, before, after } => {
+                let (from, to) = match invert {
+                    true => (after, before),
+                    false => (before, after),
+                };
+                let target = match scene_ref.path.iter().next() == Some("#commands") {
+                    true => data.get_command_loadable_mut(loadable_name),
+                    false => data.get_scene_loadable_mut(&scene_ref.path, loadable_name),
+                };
+                let Some(target) = target else {
+                    return Err(format!("loadable {} at {:?} is missing", loadable_name, scene_ref));
+                };
+                if *target != *from {
+                    return Err(format!("loadable {} at {:?} has a different value", loadable_name, scene_ref));
+                }
+                *target = to.clone();
+            }
+            Self::Sections { start, before, after, end_fill_before, end_fill_after } => {
+                let (from, to, from_fill, to_fill) = match invert {
+                    true => (after, before, end_fill_after, end_fill_before),
+                    false => (before, after, end_fill_before, end_fill_after),
+                };
+                let range = *start..(*start + from.len());
+                if data.sections.get(range.clone()) != Some(from.as_slice()) || data.end_fill != *from_fill {
+                    return Err(String::from("the edited sections have different content"));
+                }
+                data.sections.splice(range, to.iter().cloned());
+                data.end_fill = to_fill.clone();
+            }
+        }
+        Ok(())
+    }
+
+    /// Combines this edit with an edit made right after it.
+    ///
+    /// Returns `false` if the edits can't be combined, which is only possible if they replace the same loadable.
+    fn merge(&mut self, next: &Self) -> bool
+    {
+        let (
+            Self::Loadable { scene_ref, loadable_name, after, .. },
+            Self::Loadable {
+                scene_ref: next_scene_ref,
+                loadable_name: next_loadable_name,
+                before: next_before,
+                after: next_after,
+            },
+        ) = (self, next)
+        else {
+            return false;
+        };
+        if scene_ref != next_scene_ref || loadable_name != next_loadable_name || after != next_before {
+            return false;
+        }
+        *after = next_after.clone();
+        true
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// An edit that can be undone and redone.
+struct HistoryEntry
+{
+    edit: CobEdit,
+    group: Option<EditGroup>,
+    /// Time of the last edit merged into this entry.
+    last_edit: Duration,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Default)]
+struct FileHistory
+{
+    undo: Vec<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Undo/redo history of edits made in the editor, tracked per file.
+#[derive(Default)]
+pub(super) struct EditorHistory
+{
+    files: HashMap<CobFile, FileHistory>,
+}
+
+impl EditorHistory
+{
+    /// Records an edit.
+    ///
+    /// Edits with the same group are merged if they happen within a short window, so dragging a slider only adds
+    /// one undo step. Recording an edit clears the file's redo steps.
+    pub(super) fn record(&mut self, file: CobFile, edit: CobEdit, group: Option<EditGroup>, now: Duration)
+    {
+        let history = self.files.entry(file).or_default();
+        history.redo.clear();
+
+        if let Some(last) = history.undo.last_mut() {
+            if group.is_some()
+                && last.group == group
+                && now.saturating_sub(last.last_edit) <= GROUP_WINDOW
+                && last.edit.merge(&edit)
+            {
+                last.last_edit = now;
+                return;
+            }
+        }
+
+        history
+            .undo
+            .push(HistoryEntry { edit, group, last_edit: now });
+        if history.undo.len() > MAX_HISTORY {
+            history.undo.remove(0);
+        }
+    }
+
+    /// Undoes the last edit of a file in `data`.
+    ///
+    /// Returns `false` if there is nothing to undo. Fails and clears the file's history if `data` doesn't contain
+    /// the changes made by the edit.
+    pub(super) fn undo(&mut self, file: &CobFile, data: &mut Cob) -> Result<bool, String>
+    {
+        let Some(history) = self.files.get_mut(file) else { return Ok(false) };
+        let Some(entry) = history.undo.pop() else { return Ok(false) };
+        if let Err(err) = entry.edit.revert(data) {
+            self.invalidate(file);
+            return Err(format!("the file was changed outside the editor's history; {err}"));
+        }
+
+        // Edits made after an undo start a new undo step.
+        if let Some(last) = history.undo.last_mut() {
+            last.group = None;
+        }
+
+        history.redo.push(entry);
+        Ok(true)
+    }
+
+    /// Redoes the last undone edit of a file in `data`.
+    ///
+    /// Returns `false` if there is nothing to redo. Fails and clears the file's history if `data` doesn't match
+    /// the data left by the undo.
+    pub(super) fn redo(&mut self, file: &CobFile, data: &mut Cob) -> Result<bool, String>
+    {
+        let Some(history) = self.files.get_mut(file) else { return Ok(false) };
+        let Some(mut entry) = history.redo.pop() else { return Ok(false) };
+        if let Err(err) = entry.edit.apply(data) {
+            self.invalidate(file);
+            return Err(format!("the file was changed outside the editor's history; {err}"));
+        }
+
+        // Redone edits are never merged with new edits.
+        entry.group = None;
+        history.undo.push(entry);
+        Ok(true)
+    }
+
+    /// Clears a file's history, e.g. because the file was reloaded with external changes.
+    pub(super) fn invalidate(&mut self, file: &CobFile)
+    {
+        self.files.remove(file);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests
+{
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn parse(raw: &str) -> Cob
+    {
+        Cob::parse(Span::new_extra(raw, CobLocationMetadata { file: "test.cob" })).unwrap()
+    }
+
+    fn command(data: &mut Cob) -> CobLoadable
+    {
+        data.get_command_loadable_mut("A").unwrap().clone()
+    }
+
+    /// Makes an edit that changes the `A` command to `value`, and applies it to `data`.
+    fn set_command(data: &mut Cob, value: &str) -> CobEdit
+    {
+        let before = command(data);
+        let after = command(&mut parse(&format!("#commands\nA({value})\n")));
+        let edit = CobEdit::Loadable {
+            scene_ref: SceneRef::new("test.cob", "#commands"),
+            loadable_name: "A",
+            before,
+            after,
+        };
+        edit.apply(data).unwrap();
+        edit
+    }
+
+    fn group() -> Option<EditGroup>
+    {
+        Some(EditGroup {
+            scene_ref: SceneRef::new("test.cob", "#commands"),
+            loadable_name: "A",
+            structure_path: ReflectStructurePath { path: Arc::from(vec![]) },
+        })
+    }
+
+    #[test]
+    fn undo_and_redo()
+    {
+        let file = CobFile::try_new("test.cob").unwrap();
+        let before = parse("#defs\n$a = 1\n\n#commands\nA(1)\n");
+        let after = parse("#defs\n$a = 1\n\n#scenes\n\"s\"\n\n#commands\nA(1)\n");
+        let edit = CobEdit::diff(&before, &after).unwrap();
+        let CobEdit::Sections { start, before: removed, after: added, .. } = &edit else { unreachable!() };
+        assert_eq!((*start, removed.len(), added.len()), (1, 0, 1));
+
+        let mut history = EditorHistory::default();
+        history.record(file.clone(), edit, None, Duration::ZERO);
+
+        let mut data = after.clone();
+        assert_eq!(history.undo(&file, &mut data), Ok(true));
+        assert_eq!(data, before);
+        assert_eq!(history.undo(&file, &mut data), Ok(false));
+
+        assert_eq!(history.redo(&file, &mut data), Ok(true));
+        assert_eq!(data, after);
+        assert_eq!(history.redo(&file, &mut data), Ok(false));
+
+        // New edits clear redo steps.
+        assert_eq!(history.undo(&file, &mut data), Ok(true));
+        let edit = set_command(&mut data, "2");
+        history.record(file.clone(), edit, None, Duration::ZERO);
+        assert_eq!(history.redo(&file, &mut data), Ok(false));
+    }
+
+    #[test]
+    fn undo_fails_if_file_changed()
+    {
+        let file = CobFile::try_new("test.cob").unwrap();
+        let mut data = parse("#commands\nA(1)\n");
+        let mut history = EditorHistory::default();
+        let edit = set_command(&mut data, "2");
+        history.record(file.clone(), edit, None, Duration::ZERO);
+
+        // The edited loadable was changed without recording it.
+        let mut changed = data.clone();
+        set_command(&mut changed, "3");
+        assert!(history.undo(&file, &mut changed).is_err());
+        assert_eq!(command(&mut changed), command(&mut parse("#commands\nA(3)\n")));
+
+        // History was cleared.
+        assert_eq!(history.undo(&file, &mut data), Ok(false));
+    }
+
+    #[test]
+    fn group_edits()
+    {
+        let file = CobFile::try_new("test.cob").unwrap();
+        let mut data = parse("#commands\nA(1)\n");
+        let mut history = EditorHistory::default();
+
+        // Rapid edits to the same value are one undo step.
+        let edit = set_command(&mut data, "2");
+        history.record(file.clone(), edit, group(), Duration::from_millis(0));
+        let edit = set_command(&mut data, "3");
+        history.record(file.clone(), edit, group(), Duration::from_millis(400));
+        let edit = set_command(&mut data, "4");
+        history.record(file.clone(), edit, group(), Duration::from_millis(800));
+
+        // Edits after the window, or without a group, are separate steps.
+        let edit = set_command(&mut data, "5");
+        history.record(file.clone(), edit, group(), Duration::from_millis(2000));
+        let edit = set_command(&mut data, "6");
+        history.record(file.clone(), edit, None, Duration::from_millis(2100));
+
+        for value in ["5", "4", "1"] {
+            assert_eq!(history.undo(&file, &mut data), Ok(true));
+            assert_eq!(command(&mut data), command(&mut parse(&format!("#commands\nA({value})\n"))));
+        }
+        assert_eq!(history.undo(&file, &mut data), Ok(false));
+
+        // Redone edits are not merged with new edits.
+        assert_eq!(history.redo(&file, &mut data), Ok(true));
+        let edit = set_command(&mut data, "7");
+        history.record(file.clone(), edit, group(), Duration::from_millis(2200));
+        assert_eq!(history.undo(&file, &mut data), Ok(true));
+        assert_eq!(command(&mut data), command(&mut parse("#commands\nA(4)\n")));
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
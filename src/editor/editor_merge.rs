@@ -0,0 +1,507 @@
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Picks the changed side of a three-way merge.
+///
+/// Returns `None` if both sides changed in different ways.
+fn merge_value<'a, T: PartialEq>(base: &'a T, mine: &'a T, theirs: &'a T) -> Option<&'a T>
+{
+    if mine == theirs || theirs == base {
+        return Some(mine);
+    }
+    if mine == base {
+        return Some(theirs);
+    }
+    None
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn section_name(section: &CobSection) -> &'static str
+{
+    match section {
+        CobSection::Manifest(_) => "#manifest",
+        CobSection::Import(_) => "#import",
+        CobSection::Defs(_) => "#defs",
+        CobSection::Theme(_) => "#theme",
+        CobSection::Commands(_) => "#commands",
+        CobSection::Scenes(_) => "#scenes",
+        CobSection::Conditional(_) => "#if",
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Keys scene nodes by name and their index among siblings with the same name.
+fn keyed_layers<'a>(layers: impl Iterator<Item = &'a CobSceneLayer>)
+    -> Vec<((&'a str, usize), &'a CobSceneLayer)>
+{
+    let mut keyed: Vec<((&str, usize), &CobSceneLayer)> = vec![];
+    for layer in layers {
+        let name = layer.name.as_str();
+        let index = keyed.iter().filter(|((n, _), _)| *n == name).count();
+        keyed.push(((name, index), layer));
+    }
+    keyed
+}
+
+fn child_layers(layer: &CobSceneLayer) -> Vec<((&str, usize), &CobSceneLayer)>
+{
+    keyed_layers(layer.entries.iter().filter_map(|entry| match entry {
+        CobSceneLayerEntry::Layer(layer) => Some(layer),
+        _ => None,
+    }))
+}
+
+fn find_layer<'a>(layers: &[((&str, usize), &'a CobSceneLayer)], key: (&str, usize)) -> Option<&'a CobSceneLayer>
+{
+    layers
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, layer)| *layer)
+}
+
+/// Gets a scene node's content excluding its children.
+fn own_content(layer: &CobSceneLayer) -> (Option<&CobSceneBase>, Vec<&CobSceneLayerEntry>)
+{
+    let entries = layer
+        .entries
+        .iter()
+        .filter(|entry| !matches!(entry, CobSceneLayerEntry::Layer(_)))
+        .collect();
+    (layer.base.as_ref(), entries)
+}
+
+fn node_path(parent: &str, name: &str) -> String
+{
+    match parent.is_empty() {
+        true => String::from(name),
+        false => format!("{parent}::{name}"),
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Merges lists of sibling scene nodes.
+///
+/// Nodes are matched by name. Nodes added on either side are kept. Nodes removed on one side are removed unless
+/// the other side changed them, which is a conflict.
+fn merge_layers(
+    path: &str,
+    base: &[((&str, usize), &CobSceneLayer)],
+    mine: &[((&str, usize), &CobSceneLayer)],
+    theirs: &[((&str, usize), &CobSceneLayer)],
+    conflicts: &mut Vec<String>,
+) -> Vec<CobSceneLayer>
+{
+    let mut merged: Vec<((&str, usize), CobSceneLayer)> = vec![];
+
+    for (key, mine_layer) in mine.iter() {
+        let base_layer = find_layer(base, *key);
+        match (base_layer, find_layer(theirs, *key)) {
+            (_, Some(theirs_layer)) => {
+                let layer = merge_layer(&node_path(path, key.0), base_layer, mine_layer, theirs_layer, conflicts);
+                merged.push((*key, layer));
+            }
+            // Removed by them.
+            (Some(base_layer), None) => {
+                if base_layer != *mine_layer {
+                    conflicts.push(node_path(path, key.0));
+                    merged.push((*key, (*mine_layer).clone()));
+                }
+            }
+            // Added by me.
+            (None, None) => merged.push((*key, (*mine_layer).clone())),
+        }
+    }
+
+    for (idx, (key, theirs_layer)) in theirs.iter().enumerate() {
+        if find_layer(mine, *key).is_some() {
+            continue;
+        }
+        match find_layer(base, *key) {
+            // Removed by me.
+            Some(base_layer) => {
+                if base_layer != *theirs_layer {
+                    conflicts.push(node_path(path, key.0));
+                }
+            }
+            // Added by them. Insert it after the closest preceding sibling.
+            None => {
+                let pos = theirs[..idx]
+                    .iter()
+                    .rev()
+                    .find_map(|(prev, _)| merged.iter().position(|(k, _)| k == prev))
+                    .map(|pos| pos + 1)
+                    .unwrap_or(0);
+                merged.insert(pos, (*key, (*theirs_layer).clone()));
+            }
+        }
+    }
+
+    merged.into_iter().map(|(_, layer)| layer).collect()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Merges a scene node.
+///
+/// A node's own loadables are merged as one unit, and its children are merged recursively.
+fn merge_layer(
+    path: &str,
+    base: Option<&CobSceneLayer>,
+    mine: &CobSceneLayer,
+    theirs: &CobSceneLayer,
+    conflicts: &mut Vec<String>,
+) -> CobSceneLayer
+{
+    let Some(base) = base else {
+        // Added on both sides.
+        if mine != theirs {
+            conflicts.push(String::from(path));
+        }
+        return mine.clone();
+    };
+    if let Some(layer) = merge_value(base, mine, theirs) {
+        return layer.clone();
+    }
+
+    // Merge the node's own content.
+    let (base_own, mine_own, theirs_own) = (own_content(base), own_content(mine), own_content(theirs));
+    let source = match merge_value(&base_own, &mine_own, &theirs_own) {
+        Some(own) if *own == theirs_own => theirs,
+        Some(_) => mine,
+        None => {
+            conflicts.push(String::from(path));
+            mine
+        }
+    };
+
+    // Merge children.
+    let children = merge_layers(
+        path,
+        &child_layers(base),
+        &child_layers(mine),
+        &child_layers(theirs),
+        conflicts,
+    );
+
+    let mut layer = source.clone();
+    layer
+        .entries
+        .retain(|entry| !matches!(entry, CobSceneLayerEntry::Layer(_)));
+    layer
+        .entries
+        .extend(children.into_iter().map(CobSceneLayerEntry::Layer));
+    layer
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Three-way merges unsaved changes to a file with changes made to the file outside the editor.
+///
+/// `base` is the file data both versions were derived from. Scene nodes are merged individually, and other
+/// sections are merged as a whole. If a node or section was changed in different ways on both sides, then `mine`
+/// is kept and the node path or section name is returned as a conflict.
+///
+/// Fails if sections were added or removed.
+pub(super) fn merge_cob(base: &Cob, mine: &Cob, theirs: &Cob) -> Result<(Cob, Vec<String>), String>
+{
+    let same_sections = |a: &Cob, b: &Cob| {
+        a.sections.len() == b.sections.len()
+            && a.sections
+                .iter()
+                .zip(b.sections.iter())
+                .all(|(a, b)| section_name(a) == section_name(b))
+    };
+    if !same_sections(base, mine) || !same_sections(base, theirs) {
+        return Err(String::from("sections were added or removed"));
+    }
+
+    let mut conflicts = vec![];
+    let mut merged = mine.clone();
+    for (idx, section) in merged.sections.iter_mut().enumerate() {
+        let (base_section, theirs_section) = (&base.sections[idx], &theirs.sections[idx]);
+        if let Some(resolved) = merge_value(base_section, section, theirs_section) {
+            *section = resolved.clone();
+            continue;
+        }
+
+        // Scene nodes can be merged individually.
+        let (CobSection::Scenes(base_scenes), CobSection::Scenes(theirs_scenes), CobSection::Scenes(mine_scenes)) =
+            (base_section, theirs_section, &mut *section)
+        else {
+            conflicts.push(String::from(section_name(base_section)));
+            continue;
+        };
+        let scenes = merge_layers(
+            "",
+            &keyed_layers(base_scenes.scenes.iter()),
+            &keyed_layers(mine_scenes.scenes.iter()),
+            &keyed_layers(theirs_scenes.scenes.iter()),
+            &mut conflicts,
+        );
+        mine_scenes.scenes = scenes;
+    }
+    if let Some(end_fill) = merge_value(&base.end_fill, &mine.end_fill, &theirs.end_fill) {
+        merged.end_fill = end_fill.clone();
+    }
+
+    Ok((merged, conflicts))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn parse(raw: &str) -> Cob
+    {
+        Cob::parse(Span::new_extra(raw, CobLocationMetadata { file: "test.cob" })).unwrap()
+    }
+
+    fn merge(base: &str, mine: &str, theirs: &str) -> Result<(Cob, Vec<String>), String>
+    {
+        merge_cob(&parse(base), &parse(mine), &parse(theirs))
+    }
+
+    #[test]
+    fn merge_changes_from_both_sides()
+    {
+        let base = r#"#commands
+X(1)
+
+#scenes
+"a"
+    A(1)
+"b"
+    B(1)
+"c"
+    C(1)
+"#;
+        // Modifies `a`, removes `b`, and adds `d`.
+        let mine = r#"#commands
+X(1)
+
+#scenes
+"a"
+    A(2)
+"c"
+    C(1)
+"d"
+    D(1)
+"#;
+        // Modifies the commands and `c`, and adds `e`.
+        let theirs = r#"#commands
+X(2)
+
+#scenes
+"a"
+    A(1)
+"e"
+    E(1)
+"b"
+    B(1)
+"c"
+    C(2)
+"#;
+        let expected = parse(
+            r#"#commands
+X(2)
+
+#scenes
+"a"
+    A(2)
+"e"
+    E(1)
+"c"
+    C(2)
+"d"
+    D(1)
+"#,
+        );
+
+        assert_eq!(merge(base, mine, theirs).unwrap(), (expected.clone(), vec![]));
+        assert_eq!(merge(base, theirs, mine).unwrap(), (expected, vec![]));
+    }
+
+    #[test]
+    fn merge_same_change()
+    {
+        let base = r#"#scenes
+"a"
+    A(1)
+"#;
+        let changed = r#"#scenes
+"a"
+    A(2)
+"b"
+    B(1)
+"#;
+
+        assert_eq!(merge(base, changed, changed).unwrap(), (parse(changed), vec![]));
+    }
+
+    #[test]
+    fn merge_conflicts()
+    {
+        let base = r#"#commands
+X(1)
+
+#scenes
+"a"
+    A(1)
+    "x"
+        X(1)
+"b"
+    B(1)
+"c"
+    C(1)
+"#;
+        let mine = r#"#commands
+X(2)
+
+#scenes
+"a"
+    A(2)
+    "x"
+        X(2)
+"b"
+    B(2)
+"d"
+    D(1)
+"#;
+        let theirs = r#"#commands
+X(3)
+
+#scenes
+"a"
+    A(1)
+    "x"
+        X(3)
+"c"
+    C(2)
+"d"
+    D(2)
+"#;
+
+        let (merged, conflicts) = merge(base, mine, theirs).unwrap();
+        // - The commands changed on both sides.
+        // - `a::x` changed on both sides, while only I changed `a` itself.
+        // - I changed `b` and they removed it.
+        // - Both sides added `d` with different content.
+        // - They changed `c` and I removed it.
+        assert_eq!(conflicts, vec!["#commands", "a::x", "b", "d", "c"]);
+        // My version is kept for every conflict.
+        assert_eq!(merged, parse(mine));
+    }
+
+    #[test]
+    fn merge_inserts_their_nodes_after_preceding_sibling()
+    {
+        let base = r#"#scenes
+"a"
+    A(1)
+    "x"
+        X(1)
+"b"
+    B(1)
+"#;
+        let mine = r#"#scenes
+"a"
+    A(1)
+    "x"
+        X(1)
+    "y"
+        Y(1)
+"b"
+    B(1)
+"m"
+    M(1)
+"#;
+        let theirs = r#"#scenes
+"t1"
+    T(1)
+"a"
+    A(1)
+    "w"
+        W(1)
+    "x"
+        X(1)
+"t2"
+    T(2)
+"t3"
+    T(3)
+"b"
+    B(1)
+"#;
+        let expected = parse(
+            r#"#scenes
+"t1"
+    T(1)
+"a"
+    A(1)
+    "w"
+        W(1)
+    "x"
+        X(1)
+    "y"
+        Y(1)
+"t2"
+    T(2)
+"t3"
+    T(3)
+"b"
+    B(1)
+"m"
+    M(1)
+"#,
+        );
+
+        assert_eq!(merge(base, mine, theirs).unwrap(), (expected, vec![]));
+    }
+
+    #[test]
+    fn merge_fails_if_sections_change()
+    {
+        let base = r#"#commands
+X(1)
+
+#scenes
+"a"
+    A(1)
+"#;
+        let added = r#"#defs
+$c = 1
+
+#commands
+X(1)
+
+#scenes
+"a"
+    A(1)
+"#;
+        let removed = r#"#scenes
+"a"
+    A(1)
+"#;
+        let reordered = r#"#scenes
+"a"
+    A(1)
+
+#commands
+X(1)
+"#;
+
+        assert!(merge(base, added, base).is_err());
+        assert!(merge(base, base, added).is_err());
+        assert!(merge(base, removed, base).is_err());
+        assert!(merge(base, base, removed).is_err());
+        assert!(merge(base, base, reordered).is_err());
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,163 @@
+use bevy::picking::focus::HoverMap;
+use bevy::picking::pointer::PointerId;
+use bevy::prelude::*;
+use bevy_cobweb::prelude::*;
+
+use super::*;
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+const OVERLAY_COLOR: Color = Color::srgb(1.0, 0.4, 0.8);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn spawn_inspect_overlay(mut c: Commands)
+{
+    c.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            display: Display::None,
+            ..default()
+        },
+        Outline::new(Val::Px(2.), Val::ZERO, OVERLAY_COLOR),
+        GlobalZIndex(i32::MAX),
+        PickingBehavior::IGNORE,
+        InspectOverlay,
+    ));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Finds the scene node entity hovered in the game window.
+///
+/// The closest hovered entity is used, or its nearest ancestor that was loaded from a scene node.
+fn find_hovered_node(
+    hover_map: &HoverMap,
+    scene_buffer: &SceneBuffer,
+    editor_cameras: &Query<(), With<EditorCamera>>,
+    parents: &Query<&Parent>,
+) -> Option<(Entity, Entity)>
+{
+    let (hit_entity, hit) = hover_map
+        .get(&PointerId::Mouse)?
+        .iter()
+        .filter(|(_, hit)| !editor_cameras.contains(hit.camera))
+        .min_by(|(_, a), (_, b)| a.depth.total_cmp(&b.depth))?;
+
+    let mut entity = *hit_entity;
+    loop {
+        if scene_buffer.get_scene_ref(entity).is_some() {
+            return Some((entity, hit.camera));
+        }
+        entity = parents.get(entity).ok()?.get();
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Highlights the hovered scene node in inspect mode, and inspects it when clicked.
+fn update_inspect_mode(
+    mut c: Commands,
+    mut inspection: ResMut<EditorInspection>,
+    hover_map: Res<HoverMap>,
+    scene_buffer: Res<SceneBuffer>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    editor_cameras: Query<(), With<EditorCamera>>,
+    parents: Query<&Parent>,
+    nodes: Query<(&ComputedNode, &GlobalTransform)>,
+    mut overlay: Query<(Entity, &mut Node, Option<&TargetCamera>), With<InspectOverlay>>,
+)
+{
+    let Ok((overlay_entity, mut overlay_node, overlay_camera)) = overlay.get_single_mut() else { return };
+
+    let hovered = match inspection.enabled {
+        true => find_hovered_node(&hover_map, &scene_buffer, &editor_cameras, &parents),
+        false => None,
+    };
+
+    // Update the overlay.
+    let Some((hovered_entity, camera)) = hovered else {
+        if overlay_node.display != Display::None {
+            overlay_node.display = Display::None;
+        }
+        return;
+    };
+    if let Ok((computed, transform)) = nodes.get(hovered_entity) {
+        // Nodes are positioned in physical pixels, but overlay values are in logical pixels.
+        let scale = computed.inverse_scale_factor();
+        let size = computed.size() * scale;
+        let top_left = transform.translation().truncate() * scale - size / 2.;
+        overlay_node.display = Display::Flex;
+        overlay_node.left = Val::Px(top_left.x);
+        overlay_node.top = Val::Px(top_left.y);
+        overlay_node.width = Val::Px(size.x);
+        overlay_node.height = Val::Px(size.y);
+        if overlay_camera.map(|target| target.0) != Some(camera) {
+            c.entity(overlay_entity).insert(TargetCamera(camera));
+        }
+    }
+
+    // Inspect the hovered node when clicked.
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(scene_ref) = scene_buffer.get_scene_ref(hovered_entity).cloned() else { return };
+    inspection.enabled = false;
+    inspection.inspected = Some(scene_ref.clone());
+    c.react().broadcast(EditorNodeInspected { node: scene_ref });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks the editor's inspect mode.
+///
+/// In inspect mode, scene nodes in the game window are highlighted when hovered. Clicking a node selects its file
+/// and scene node in the editor, and ends inspect mode.
+#[derive(Resource, Default, Debug)]
+pub(super) struct EditorInspection
+{
+    enabled: bool,
+    inspected: Option<SceneRef>,
+}
+
+impl EditorInspection
+{
+    pub(super) fn is_enabled(&self) -> bool
+    {
+        self.enabled
+    }
+
+    pub(super) fn toggle(&mut self)
+    {
+        self.enabled = !self.enabled;
+    }
+
+    /// The last scene node picked in inspect mode.
+    pub(super) fn inspected(&self) -> Option<&SceneRef>
+    {
+        self.inspected.as_ref()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Marker component for the outline shown over hovered nodes in inspect mode.
+#[derive(Component, Debug)]
+struct InspectOverlay;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(super) struct CobEditorInspectPlugin;
+
+impl Plugin for CobEditorInspectPlugin
+{
+    fn build(&self, app: &mut App)
+    {
+        app.init_resource::<EditorInspection>()
+            .add_systems(Startup, spawn_inspect_overlay)
+            .add_systems(Update, update_inspect_mode);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -7,9 +7,13 @@ mod death_signal;
 mod editor;
 mod editor_commands;
 mod editor_events;
+mod editor_history;
+mod editor_merge;
 //mod editor_stack;
 mod hash_registry;
+mod inspect;
 mod plugin;
+mod reflect_widgets;
 mod template;
 mod utils;
 mod widget_interop;
@@ -20,9 +24,13 @@ pub(self) use death_signal::*;
 pub(crate) use editor::*;
 pub use editor_commands::*;
 pub use editor_events::*;
+pub(self) use editor_history::*;
+pub(self) use editor_merge::*;
 //pub(self) use editor_stack::*;
 pub(crate) use hash_registry::*;
+pub(self) use inspect::*;
 pub(crate) use plugin::*;
+pub(self) use reflect_widgets::*;
 pub(self) use template::*;
 pub(self) use utils::*;
 pub use widget_interop::*;
@@ -13,6 +13,8 @@ impl Plugin for CobEditorPlugin
         app.add_plugins(CobWidgetRegistryPlugin)
             .add_plugins(CobHashRegistryPlugin)
             .add_plugins(CobEditorImplPlugin)
+            .add_plugins(CobReflectWidgetsPlugin)
+            .add_plugins(CobEditorInspectPlugin)
             .add_plugins(CobEditorBuildPlugin);
     }
 }
@@ -0,0 +1,1159 @@
+use std::any::TypeId;
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use bevy::reflect::{
+    Array, DynamicEnum, DynamicList, DynamicMap, DynamicStruct, DynamicTuple, DynamicTupleStruct, DynamicVariant,
+    Enum, EnumInfo, List, Map, ReflectRef, Struct, Tuple, TupleStruct, TypeInfo, TypeRegistry, VariantInfo,
+    VariantType,
+};
+use bevy_cobweb::prelude::*;
+
+use super::*;
+use crate::prelude::*;
+use crate::sickle::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Units shown by the [`Val`] widget, in the order they are cycled.
+const VAL_UNITS: [&str; 7] = ["auto", "px", "%", "vw", "vh", "vmin", "vmax"];
+
+type PrimitiveParser = fn(&str) -> Option<Box<dyn PartialReflect>>;
+
+/// A part of a destructured value, with a label and the structure point that targets it.
+type ValueEntry<'v> = (String, ReflectStructurePoint, &'v (dyn PartialReflect + 'static));
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn val_parts(val: Val) -> (f32, usize)
+{
+    match val {
+        Val::Auto => (0., 0),
+        Val::Px(number) => (number, 1),
+        Val::Percent(number) => (number, 2),
+        Val::Vw(number) => (number, 3),
+        Val::Vh(number) => (number, 4),
+        Val::VMin(number) => (number, 5),
+        Val::VMax(number) => (number, 6),
+    }
+}
+
+fn make_val(number: f32, unit: usize) -> Val
+{
+    match unit {
+        1 => Val::Px(number),
+        2 => Val::Percent(number),
+        3 => Val::Vw(number),
+        4 => Val::Vh(number),
+        5 => Val::VMin(number),
+        6 => Val::VMax(number),
+        _ => Val::Auto,
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Gets the current text of a primitive value and a parser for new values typed into an inline text input.
+fn primitive_input(value: &(dyn PartialReflect + 'static)) -> Option<(String, PrimitiveParser)>
+{
+    macro_rules! try_input {
+        ($($type:ty),*) => {$(
+            if let Some(value) = value.try_downcast_ref::<$type>() {
+                let parser: PrimitiveParser = |text| Some(Box::new(text.trim().parse::<$type>().ok()?));
+                return Some((value.to_string(), parser));
+            }
+        )*};
+    }
+
+    // Strings are not trimmed.
+    if let Some(value) = value.try_downcast_ref::<String>() {
+        let parser: PrimitiveParser = |text| Some(Box::new(String::from(text)));
+        return Some((value.clone(), parser));
+    }
+    try_input!(f32, f64, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, char);
+    None
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Makes a default value for a type.
+///
+/// Uses [`ReflectDefault`] if available, and otherwise builds the value from the defaults of its fields. Lists and
+/// maps are empty, and enums use their first variant that can be built.
+fn default_value(registry: &TypeRegistry, type_id: TypeId) -> Option<Box<dyn PartialReflect>>
+{
+    if let Some(reflect_default) = registry.get_type_data::<ReflectDefault>(type_id) {
+        return Some(reflect_default.default().into_partial_reflect());
+    }
+
+    let type_info = registry.get_type_info(type_id)?;
+    match type_info {
+        TypeInfo::Struct(info) => {
+            let mut value = DynamicStruct::default();
+            value.set_represented_type(Some(type_info));
+            for field in info.iter() {
+                value.insert_boxed(field.name(), default_value(registry, field.type_id())?);
+            }
+            Some(Box::new(value))
+        }
+        TypeInfo::TupleStruct(info) => {
+            let mut value = DynamicTupleStruct::default();
+            value.set_represented_type(Some(type_info));
+            for field in info.iter() {
+                value.insert_boxed(default_value(registry, field.type_id())?);
+            }
+            Some(Box::new(value))
+        }
+        TypeInfo::Tuple(info) => {
+            let mut value = DynamicTuple::default();
+            value.set_represented_type(Some(type_info));
+            for field in info.iter() {
+                value.insert_boxed(default_value(registry, field.type_id())?);
+            }
+            Some(Box::new(value))
+        }
+        TypeInfo::List(_) => {
+            let mut value = DynamicList::default();
+            value.set_represented_type(Some(type_info));
+            Some(Box::new(value))
+        }
+        TypeInfo::Map(_) => {
+            let mut value = DynamicMap::default();
+            value.set_represented_type(Some(type_info));
+            Some(Box::new(value))
+        }
+        TypeInfo::Enum(info) => info
+            .iter()
+            .find_map(|variant| default_variant(registry, type_info, variant)),
+        _ => None,
+    }
+}
+
+/// Makes a value of an enum variant with default fields.
+fn default_variant(
+    registry: &TypeRegistry,
+    type_info: &'static TypeInfo,
+    variant: &VariantInfo,
+) -> Option<Box<dyn PartialReflect>>
+{
+    let TypeInfo::Enum(info) = type_info else { return None };
+    let dyn_variant = match variant {
+        VariantInfo::Struct(variant) => {
+            let mut fields = DynamicStruct::default();
+            for field in variant.iter() {
+                fields.insert_boxed(field.name(), default_value(registry, field.type_id())?);
+            }
+            DynamicVariant::Struct(fields)
+        }
+        VariantInfo::Tuple(variant) => {
+            let mut fields = DynamicTuple::default();
+            for field in variant.iter() {
+                fields.insert_boxed(default_value(registry, field.type_id())?);
+            }
+            DynamicVariant::Tuple(fields)
+        }
+        VariantInfo::Unit(_) => DynamicVariant::Unit,
+    };
+
+    let mut value = DynamicEnum::new_with_index(info.index_of(variant.name())?, variant.name(), dyn_variant);
+    value.set_represented_type(Some(type_info));
+    Some(Box::new(value))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Switches which inline text input receives keyboard input.
+///
+/// The previously-focused input loses any text that wasn't submitted.
+fn set_text_focus(
+    c: &mut Commands,
+    focus: &mut EditorTextFocus,
+    inputs: &mut Query<&mut EditorTextInput>,
+    text_editor: &mut TextEditor,
+    next: Option<Entity>,
+)
+{
+    if focus.0 == next {
+        return;
+    }
+
+    if let Some(prev) = focus.0.take() {
+        if let Ok(mut input) = inputs.get_mut(prev) {
+            input.text = input.original.clone();
+            input.write(text_editor, false);
+            c.react().entity_event(prev, Deselect);
+        }
+    }
+
+    let Some(next) = next else { return };
+    let Ok(input) = inputs.get(next) else { return };
+    input.write(text_editor, true);
+    c.react().entity_event(next, Select);
+    focus.0 = Some(next);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Types into the focused inline text input.
+///
+/// Only keys pressed while the editor window is focused are used. `Enter` submits the text and `Escape` discards
+/// it.
+fn update_text_input(
+    mut c: Commands,
+    mut keys: EventReader<KeyboardInput>,
+    mut focus: ResMut<EditorTextFocus>,
+    mut inputs: Query<&mut EditorTextInput>,
+    mut text_editor: TextEditor,
+    pressed: Res<ButtonInput<KeyCode>>,
+    editor_window: Query<Entity, With<EditorWindow>>,
+)
+{
+    let Some(focused) = focus.0 else {
+        keys.clear();
+        return;
+    };
+    let Ok(mut input) = inputs.get_mut(focused) else {
+        // The input was despawned, e.g. because the editor view was rebuilt.
+        focus.0 = None;
+        keys.clear();
+        return;
+    };
+    let editor_window = editor_window.get_single().ok();
+
+    // Characters typed with `Ctrl` or `Cmd` are shortcuts.
+    let shortcut = pressed.any_pressed([
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::SuperLeft,
+        KeyCode::SuperRight,
+    ]);
+
+    let mut changed = false;
+    for key in keys.read() {
+        if key.state != ButtonState::Pressed || Some(key.window) != editor_window {
+            continue;
+        }
+
+        match &key.logical_key {
+            Key::Character(_) if shortcut => (),
+            Key::Character(chars) => {
+                input.text.extend(chars.chars().filter(|c| !c.is_control()));
+                changed = true;
+            }
+            Key::Space => {
+                input.text.push(' ');
+                changed = true;
+            }
+            Key::Backspace => {
+                changed |= input.text.pop().is_some();
+            }
+            Key::Enter | Key::Escape => {
+                match key.logical_key == Key::Enter {
+                    true => {
+                        input.original = input.text.clone();
+                        c.react()
+                            .entity_event(focused, EditorTextSubmit(input.text.clone()));
+                    }
+                    false => input.text = input.original.clone(),
+                }
+                input.write(&mut text_editor, false);
+                c.react().entity_event(focused, Deselect);
+                focus.0 = None;
+                return;
+            }
+            _ => (),
+        }
+    }
+
+    if changed {
+        input.write(&mut text_editor, true);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Spawns an inline text input.
+///
+/// Submitted text is sent to the input entity as an [`EditorTextSubmit`] entity event. Use `edit` to add a reactor
+/// for it.
+fn build_text_input<'a, C>(l: &mut LoadedScene<'a, '_, UiBuilder<'a, Entity>>, text: String, edit: C)
+where
+    C: for<'x> FnOnce(&mut LoadedScene<'x, '_, UiBuilder<'x, Entity>>),
+{
+    l.load_scene_and_edit(("editor.frame", "text_input"), |l| {
+        let input_entity = l.id();
+        let text_entity = l.get("text").id();
+
+        let initial_text = text.clone();
+        l.get("text")
+            .update(move |id: UpdateId, mut e: TextEditor| {
+                write_text!(e, *id, "{}", initial_text);
+            });
+        l.insert(EditorTextInput { original: text.clone(), text, text_entity });
+
+        // Focus the input when pressed.
+        l.on_pressed(
+            move |//
+                mut c: Commands,
+                mut focus: ResMut<EditorTextFocus>,
+                mut inputs: Query<&mut EditorTextInput>,
+                mut text_editor: TextEditor//
+            | {
+                set_text_focus(&mut c, &mut focus, &mut inputs, &mut text_editor, Some(input_entity));
+            },
+        );
+
+        (edit)(l);
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Adds a reactor to an inline text input that submits parsed text with [`SubmitPatch`].
+fn submit_text_on_event<'a>(
+    l: &mut LoadedScene<'a, '_, UiBuilder<'a, Entity>>,
+    editor_ref: CobEditorRef,
+    parser: PrimitiveParser,
+)
+{
+    l.on_event::<EditorTextSubmit>()
+        .r(move |event: EntityEvent<EditorTextSubmit>, mut c: Commands| {
+            let Some((_, submitted)) = event.try_read() else { return };
+            let Some(value) = (parser)(&submitted.0) else {
+                tracing::warn!("ignoring editor input {:?} for {} in {:?}; failed parsing the input",
+                    submitted.0, editor_ref.loadable_name, editor_ref.scene_ref);
+                return;
+            };
+            c.queue(SubmitPatch { editor_ref: editor_ref.clone(), value });
+        });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Makes a toggle that folds `content_entity`.
+fn setup_fold_toggle<'a>(l: &mut LoadedScene<'a, '_, UiBuilder<'a, Entity>>, content_entity: Entity)
+{
+    let toggle_entity = l.id();
+    let mut folded = false;
+    l.on_pressed(move |mut c: Commands, mut e: TextEditor| {
+        folded = !folded;
+        let (control, text) = match folded {
+            true => (DisplayControl::Hide, "[+]"),
+            false => (DisplayControl::Display, "[-]"),
+        };
+        c.entity(content_entity).apply(control);
+        write_text!(e, toggle_entity, "{}", text);
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Gets the fields of a struct.
+///
+/// Returns `None` if the struct has no type info.
+fn struct_entries(dyn_struct: &dyn Struct) -> Option<Vec<ValueEntry<'_>>>
+{
+    let Some(TypeInfo::Struct(info)) = dyn_struct.get_represented_type_info() else { return None };
+
+    // Field names in structure points need to be static, so we get them from the type info.
+    let fields = (0..dyn_struct.field_len())
+        .filter_map(|i| {
+            let name = info.field(dyn_struct.name_at(i)?)?.name();
+            Some((
+                String::from(name),
+                ReflectStructurePoint::Struct(name),
+                dyn_struct.field_at(i)?,
+            ))
+        })
+        .collect();
+    Some(fields)
+}
+
+/// Gets the elements of a list, labeled by index.
+fn list_entries(dyn_list: &dyn List) -> Vec<ValueEntry<'_>>
+{
+    dyn_list
+        .iter()
+        .enumerate()
+        .map(|(i, element)| (i.to_string(), ReflectStructurePoint::List(i), element))
+        .collect()
+}
+
+/// Gets the keys and values of a map, with structure points for the values.
+fn map_entries(
+    dyn_map: &dyn Map,
+) -> Vec<(
+    &(dyn PartialReflect + 'static),
+    ReflectStructurePoint,
+    &(dyn PartialReflect + 'static),
+)>
+{
+    (0..dyn_map.len())
+        .filter_map(|i| {
+            let (key, value) = dyn_map.get_at(i)?;
+            Some((key, ReflectStructurePoint::MapValue(i), value))
+        })
+        .collect()
+}
+
+/// Gets the inner value of an `Option` if it is `Some`.
+fn option_entry(dyn_enum: &dyn Enum) -> Option<(ReflectStructurePoint, &(dyn PartialReflect + 'static))>
+{
+    dyn_enum
+        .field_at(0)
+        .map(|inner| (ReflectStructurePoint::Enum("Some", 0), inner))
+}
+
+/// Gets the name of an enum's current variant and its fields. Tuple fields are labeled by index.
+///
+/// Returns `None` if the variant is not in the type info.
+fn enum_entries<'v>(dyn_enum: &'v dyn Enum, info: &'static EnumInfo)
+    -> Option<(&'static str, Vec<ValueEntry<'v>>)>
+{
+    // Variant names in structure points need to be static, so we get them from the type info.
+    let variant_name = info.variant(dyn_enum.variant_name())?.name();
+    let fields = (0..dyn_enum.field_len())
+        .filter_map(|i| {
+            let name = dyn_enum
+                .name_at(i)
+                .map(String::from)
+                .unwrap_or_else(|| i.to_string());
+            Some((
+                name,
+                ReflectStructurePoint::Enum(variant_name, i),
+                dyn_enum.field_at(i)?,
+            ))
+        })
+        .collect();
+    Some((variant_name, fields))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Builds a named row with a widget for a field.
+fn build_field<'a>(
+    l: &mut LoadedScene<'a, '_, UiBuilder<'a, Entity>>,
+    widgets: &CobWidgetRegistry,
+    name: String,
+    editor_ref: CobEditorRef,
+    value: &(dyn PartialReflect + 'static),
+)
+{
+    l.load_scene_and_edit(("editor.frame", "field"), |l| {
+        l.get("name")
+            .update(move |id: UpdateId, mut e: TextEditor| {
+                write_text!(e, *id, "{}:", name);
+            });
+        l.edit("content", |l| {
+            build_reflected_widget(l, widgets, editor_ref, value);
+        });
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Builds a foldable list of fields.
+fn build_fields<'a, 'v>(
+    l: &mut LoadedScene<'a, '_, UiBuilder<'a, Entity>>,
+    widgets: &CobWidgetRegistry,
+    editor_ref: &CobEditorRef,
+    fields: impl Iterator<Item = (String, ReflectStructurePoint, &'v (dyn PartialReflect + 'static))>,
+)
+{
+    l.load_scene_and_edit(("editor.frame", "struct"), |l| {
+        let fields_entity = l.get("fields").id();
+        l.edit("toggle", |l| setup_fold_toggle(l, fields_entity));
+        l.edit("fields", |l| {
+            for (name, point, value) in fields {
+                build_field(l, widgets, name, editor_ref.extend(point), value);
+            }
+        });
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn build_struct_widget<'a>(
+    l: &mut LoadedScene<'a, '_, UiBuilder<'a, Entity>>,
+    widgets: &CobWidgetRegistry,
+    editor_ref: CobEditorRef,
+    dyn_struct: &dyn Struct,
+)
+{
+    let Some(fields) = struct_entries(dyn_struct) else {
+        l.load_scene(("editor.frame", "destructure_unsupported"));
+        return;
+    };
+    if fields.is_empty() {
+        return;
+    }
+
+    build_fields(l, widgets, &editor_ref, fields.into_iter());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn build_tuple_struct_widget<'a>(
+    l: &mut LoadedScene<'a, '_, UiBuilder<'a, Entity>>,
+    widgets: &CobWidgetRegistry,
+    editor_ref: CobEditorRef,
+    dyn_tuplestruct: &dyn TupleStruct,
+)
+{
+    // Newtypes are flattened, the same as in COB files.
+    if dyn_tuplestruct.field_len() == 1 {
+        let field = dyn_tuplestruct.field(0).unwrap();
+        build_reflected_widget(
+            l,
+            widgets,
+            editor_ref.extend(ReflectStructurePoint::TupleStruct(0)),
+            field,
+        );
+        return;
+    }
+    if dyn_tuplestruct.field_len() == 0 {
+        return;
+    }
+
+    let fields = dyn_tuplestruct
+        .iter_fields()
+        .enumerate()
+        .map(|(i, field)| (i.to_string(), ReflectStructurePoint::TupleStruct(i), field));
+    build_fields(l, widgets, &editor_ref, fields);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn build_tuple_widget<'a>(
+    l: &mut LoadedScene<'a, '_, UiBuilder<'a, Entity>>,
+    widgets: &CobWidgetRegistry,
+    editor_ref: CobEditorRef,
+    dyn_tuple: &dyn Tuple,
+)
+{
+    let fields = dyn_tuple
+        .iter_fields()
+        .enumerate()
+        .map(|(i, field)| (i.to_string(), ReflectStructurePoint::Tuple(i), field));
+    build_fields(l, widgets, &editor_ref, fields);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn build_array_widget<'a>(
+    l: &mut LoadedScene<'a, '_, UiBuilder<'a, Entity>>,
+    widgets: &CobWidgetRegistry,
+    editor_ref: CobEditorRef,
+    dyn_array: &dyn Array,
+)
+{
+    let fields = dyn_array
+        .iter()
+        .enumerate()
+        .map(|(i, element)| (i.to_string(), ReflectStructurePoint::Array(i), element));
+    build_fields(l, widgets, &editor_ref, fields);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn build_list_widget<'a>(
+    l: &mut LoadedScene<'a, '_, UiBuilder<'a, Entity>>,
+    widgets: &CobWidgetRegistry,
+    editor_ref: CobEditorRef,
+    dyn_list: &dyn List,
+)
+{
+    let Some(TypeInfo::List(info)) = dyn_list.get_represented_type_info() else {
+        l.load_scene(("editor.frame", "destructure_unsupported"));
+        return;
+    };
+    let item_type = info.item_ty().id();
+
+    l.load_scene_and_edit(("editor.frame", "collection"), |l| {
+        l.edit("entries", |l| {
+            for (index, (label, point, element)) in list_entries(dyn_list).into_iter().enumerate() {
+                l.load_scene_and_edit(("editor.frame", "collection_entry"), |l| {
+                    let remove_ref = editor_ref.clone();
+                    l.get("remove").on_pressed(move |mut c: Commands| {
+                        c.queue(RemoveListEntry { editor_ref: remove_ref.clone(), index });
+                    });
+                    l.get("key").update(move |id: UpdateId, mut e: TextEditor| {
+                        write_text!(e, *id, "{}:", label);
+                    });
+                    l.edit("content", |l| {
+                        build_reflected_widget(l, widgets, editor_ref.extend(point), element);
+                    });
+                });
+            }
+        });
+
+        // New entries are added at the end.
+        let index = dyn_list.len();
+        l.get("add")
+            .on_pressed(move |mut c: Commands, registry: Res<AppTypeRegistry>| {
+                let Some(value) = default_value(&registry.read(), item_type) else {
+                    tracing::warn!("failed adding list entry to {} in {:?}; the entry type has no default value",
+                        editor_ref.loadable_name, editor_ref.scene_ref);
+                    return;
+                };
+                c.queue(InsertListEntry { editor_ref: editor_ref.clone(), index, value });
+            });
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn build_map_widget<'a>(
+    l: &mut LoadedScene<'a, '_, UiBuilder<'a, Entity>>,
+    widgets: &CobWidgetRegistry,
+    editor_ref: CobEditorRef,
+    dyn_map: &dyn Map,
+)
+{
+    let Some(TypeInfo::Map(info)) = dyn_map.get_represented_type_info() else {
+        l.load_scene(("editor.frame", "destructure_unsupported"));
+        return;
+    };
+    let key_type = info.key_ty().id();
+    let value_type = info.value_ty().id();
+
+    l.load_scene_and_edit(("editor.frame", "collection"), |l| {
+        l.edit("entries", |l| {
+            for (key, point, value) in map_entries(dyn_map) {
+                l.load_scene_and_edit(("editor.frame", "collection_entry"), |l| {
+                    let remove_ref = editor_ref.clone();
+                    let remove_key = key.clone_value();
+                    l.get("remove").on_pressed(move |mut c: Commands| {
+                        c.queue(RemoveMapEntry {
+                            editor_ref: remove_ref.clone(),
+                            key: remove_key.clone_value(),
+                        });
+                    });
+                    let key_text = format!("{:?}", key);
+                    l.get("key").update(move |id: UpdateId, mut e: TextEditor| {
+                        write_text!(e, *id, "{}:", key_text);
+                    });
+                    l.edit("content", |l| {
+                        build_reflected_widget(l, widgets, editor_ref.extend(point), value);
+                    });
+                });
+            }
+        });
+
+        // New entries use default keys, so they can only be added if the default key isn't in the map.
+        l.get("add")
+            .on_pressed(move |mut c: Commands, registry: Res<AppTypeRegistry>| {
+                let registry = registry.read();
+                let key = default_value(&registry, key_type);
+                let value = default_value(&registry, value_type);
+                let (Some(key), Some(value)) = (key, value) else {
+                    tracing::warn!("failed adding map entry to {} in {:?}; the key or value type has no default \
+                        value", editor_ref.loadable_name, editor_ref.scene_ref);
+                    return;
+                };
+                c.queue(InsertMapEntry { editor_ref: editor_ref.clone(), key, value });
+            });
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Builds a widget for an `Option`.
+///
+/// `None` can be replaced with a default `Some`, and `Some` can be removed.
+fn build_option_widget<'a>(
+    l: &mut LoadedScene<'a, '_, UiBuilder<'a, Entity>>,
+    widgets: &CobWidgetRegistry,
+    editor_ref: CobEditorRef,
+    dyn_enum: &dyn Enum,
+    type_info: &'static TypeInfo,
+)
+{
+    let TypeInfo::Enum(info) = type_info else { return };
+
+    match option_entry(dyn_enum) {
+        Some((point, inner)) => {
+            l.load_scene_and_edit(("editor.frame", "collection_entry"), |l| {
+                let remove_ref = editor_ref.clone();
+                l.get("remove")
+                    .on_pressed(move |mut c: Commands, registry: Res<AppTypeRegistry>| {
+                        let Some(none) = info
+                            .variant("None")
+                            .and_then(|variant| default_variant(&registry.read(), type_info, variant))
+                        else {
+                            return;
+                        };
+                        c.queue(SetEnumVariant { editor_ref: remove_ref.clone(), value: none });
+                    });
+                l.edit("content", |l| {
+                    build_reflected_widget(l, widgets, editor_ref.extend(point), inner);
+                });
+            });
+        }
+        None => {
+            l.load_scene_and_edit(("editor.frame", "option_none"), |l| {
+                l.get("add")
+                    .on_pressed(move |mut c: Commands, registry: Res<AppTypeRegistry>| {
+                        let Some(some) = info
+                            .variant("Some")
+                            .and_then(|variant| default_variant(&registry.read(), type_info, variant))
+                        else {
+                            tracing::warn!("failed adding value to {} in {:?}; the option's inner type has no \
+                                default value", editor_ref.loadable_name, editor_ref.scene_ref);
+                            return;
+                        };
+                        c.queue(SetEnumVariant { editor_ref: editor_ref.clone(), value: some });
+                    });
+            });
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Builds a variant dropdown and widgets for the current variant's fields.
+///
+/// Selecting a new variant replaces the value with the new variant's default fields.
+fn build_enum_widget<'a>(
+    l: &mut LoadedScene<'a, '_, UiBuilder<'a, Entity>>,
+    widgets: &CobWidgetRegistry,
+    editor_ref: CobEditorRef,
+    dyn_enum: &dyn Enum,
+    type_info: &'static TypeInfo,
+)
+{
+    let TypeInfo::Enum(info) = type_info else { return };
+    let Some((variant_name, fields)) = enum_entries(dyn_enum, info) else {
+        l.load_scene(("editor.frame", "destructure_unsupported"));
+        return;
+    };
+
+    l.load_scene_and_edit(("editor.frame", "enum"), |l| {
+        let options_entity = l.get("options").id();
+
+        // Variant dropdown.
+        l.get("variant::text")
+            .update(move |id: UpdateId, mut e: TextEditor| {
+                write_text!(e, *id, "{}", variant_name);
+            });
+        l.get("variant")
+            .on_pressed(move |mut c: Commands, nodes: Query<&Node>| {
+                let Ok(node) = nodes.get(options_entity) else { return };
+                let control = match node.display {
+                    Display::None => DisplayControl::Display,
+                    _ => DisplayControl::Hide,
+                };
+                c.entity(options_entity).apply(control);
+            });
+
+        l.edit("options", |l| {
+            for variant in info.iter() {
+                let name = variant.name();
+                l.load_scene_and_edit(("editor.frame", "enum_option"), |l| {
+                    l.get("text")
+                        .update(move |id: UpdateId, mut e: TextEditor| {
+                            write_text!(e, *id, "{}", name);
+                        });
+
+                    if name == variant_name {
+                        let option_entity = l.id();
+                        l.react().entity_event(option_entity, Select);
+                    }
+
+                    let editor_ref = editor_ref.clone();
+                    l.on_pressed(move |mut c: Commands, registry: Res<AppTypeRegistry>| {
+                        c.entity(options_entity).apply(DisplayControl::Hide);
+                        if name == variant_name {
+                            return;
+                        }
+                        let Some(value) = info
+                            .variant(name)
+                            .and_then(|variant| default_variant(&registry.read(), type_info, variant))
+                        else {
+                            tracing::warn!("failed changing {} in {:?} to variant {}; the variant's fields have no \
+                                default values", editor_ref.loadable_name, editor_ref.scene_ref, name);
+                            return;
+                        };
+                        c.queue(SetEnumVariant { editor_ref: editor_ref.clone(), value });
+                    });
+                });
+            }
+        });
+
+        // Variant fields.
+        l.edit("fields", |l| {
+            if matches!(dyn_enum.variant_type(), VariantType::Tuple) && fields.len() == 1 {
+                let (_, point, field) = &fields[0];
+                build_reflected_widget(l, widgets, editor_ref.extend(point.clone()), *field);
+                return;
+            }
+
+            for (name, point, field) in fields.iter() {
+                build_field(l, widgets, name.clone(), editor_ref.extend(point.clone()), *field);
+            }
+        });
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Builds a swatch and hex input for a [`Color`].
+fn build_color_widget<'a>(
+    l: &mut LoadedScene<'a, '_, UiBuilder<'a, Entity>>,
+    editor_ref: CobEditorRef,
+    color: Color,
+)
+{
+    l.load_scene_and_edit(("editor.frame", "color"), |l| {
+        let swatch_entity = l.get("swatch").id();
+        l.get("swatch").apply(BackgroundColor(color));
+
+        l.edit("input", |l| {
+            build_text_input(l, color.to_srgba().to_hex(), |l| {
+                l.on_event::<EditorTextSubmit>().r(
+                    move |event: EntityEvent<EditorTextSubmit>, mut c: Commands| {
+                        let Some((_, submitted)) = event.try_read() else { return };
+                        let Ok(srgba) = Srgba::hex(submitted.0.trim()) else {
+                            tracing::warn!("ignoring editor input {:?} for {} in {:?}; expected a hex color",
+                                submitted.0, editor_ref.loadable_name, editor_ref.scene_ref);
+                            return;
+                        };
+                        let color = Color::Srgba(srgba);
+                        c.entity(swatch_entity).apply(BackgroundColor(color));
+                        c.queue(SubmitPatch { editor_ref: editor_ref.clone(), value: Box::new(color) });
+                    },
+                );
+            });
+        });
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Builds a number input and unit selector for a [`Val`].
+fn build_val_widget<'a>(l: &mut LoadedScene<'a, '_, UiBuilder<'a, Entity>>, editor_ref: CobEditorRef, val: Val)
+{
+    l.load_scene_and_edit(("editor.frame", "val"), |l| {
+        let widget_entity = l.id();
+        let unit_entity = l.get("unit").id();
+        l.insert(EditedVal(val));
+
+        let (number, unit) = val_parts(val);
+        l.get("unit")
+            .update(move |id: UpdateId, mut e: TextEditor| {
+                write_text!(e, *id, "{}", VAL_UNITS[unit]);
+            });
+
+        // Pressing the unit cycles through units.
+        let unit_ref = editor_ref.clone();
+        l.get("unit").on_pressed(
+            move |mut c: Commands, mut vals: Query<&mut EditedVal>, mut e: TextEditor| {
+                let Ok(mut edited) = vals.get_mut(widget_entity) else { return };
+                let (number, unit) = val_parts(edited.0);
+                let unit = (unit + 1) % VAL_UNITS.len();
+                edited.0 = make_val(number, unit);
+                write_text!(e, unit_entity, "{}", VAL_UNITS[unit]);
+                c.queue(SubmitPatch { editor_ref: unit_ref.clone(), value: Box::new(edited.0) });
+            },
+        );
+
+        // Typing a number keeps the unit, or uses pixels if the value was `Auto`.
+        l.edit("input", |l| {
+            build_text_input(l, number.to_string(), |l| {
+                l.on_event::<EditorTextSubmit>().r(
+                    move |//
+                        event: EntityEvent<EditorTextSubmit>,
+                        mut c: Commands,
+                        mut vals: Query<&mut EditedVal>,
+                        mut e: TextEditor//
+                    | {
+                        let Some((_, submitted)) = event.try_read() else { return };
+                        let Ok(number) = submitted.0.trim().parse::<f32>() else {
+                            tracing::warn!("ignoring editor input {:?} for {} in {:?}; expected a number",
+                                submitted.0, editor_ref.loadable_name, editor_ref.scene_ref);
+                            return;
+                        };
+                        let Ok(mut edited) = vals.get_mut(widget_entity) else { return };
+                        let unit = val_parts(edited.0).1.max(1);
+                        edited.0 = make_val(number, unit);
+                        write_text!(e, unit_entity, "{}", VAL_UNITS[unit]);
+                        c.queue(SubmitPatch { editor_ref: editor_ref.clone(), value: Box::new(edited.0) });
+                    },
+                );
+            });
+        });
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Builds an inline input for primitive values, or a toggle for `bool`s.
+fn build_primitive_widget<'a>(
+    l: &mut LoadedScene<'a, '_, UiBuilder<'a, Entity>>,
+    editor_ref: CobEditorRef,
+    value: &(dyn PartialReflect + 'static),
+)
+{
+    if let Some(mut current) = value.try_downcast_ref::<bool>().copied() {
+        l.load_scene_and_edit(("editor.frame", "bool_toggle"), |l| {
+            let toggle_entity = l.id();
+            l.update(move |id: UpdateId, mut e: TextEditor| {
+                write_text!(e, *id, "{}", current);
+            });
+            l.on_pressed(move |mut c: Commands, mut e: TextEditor| {
+                current = !current;
+                write_text!(e, toggle_entity, "{}", current);
+                c.queue(SubmitPatch { editor_ref: editor_ref.clone(), value: Box::new(current) });
+            });
+        });
+        return;
+    }
+
+    let Some((text, parser)) = primitive_input(value) else {
+        l.load_scene(("editor.frame", "destructure_unsupported"));
+        return;
+    };
+    build_text_input(l, text, |l| submit_text_on_event(l, editor_ref, parser));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Builds a widget for a reflected value.
+///
+/// Uses a registered [`CobEditorWidget`] for the value's type if there is one. Otherwise the value is destructured
+/// with reflection, and widgets are built for its parts.
+pub(super) fn build_reflected_widget<'a>(
+    l: &mut LoadedScene<'a, '_, UiBuilder<'a, Entity>>,
+    widgets: &CobWidgetRegistry,
+    editor_ref: CobEditorRef,
+    value: &(dyn PartialReflect + 'static),
+)
+{
+    let Some(type_info) = value.get_represented_type_info() else {
+        l.load_scene(("editor.frame", "destructure_unsupported"));
+        return;
+    };
+
+    // Check for a registered widget.
+    if let Some(spawn_fn) = widgets.get(type_info.type_path()) {
+        let content_entity = l.id();
+        let (loader, builder) = l.inner();
+        if (spawn_fn)(builder.commands(), loader, content_entity, &editor_ref, value) {
+            return;
+        }
+    }
+
+    // Check for types with custom widgets.
+    if type_info.type_id() == TypeId::of::<Color>() {
+        if let Some(color) = Color::from_reflect(value) {
+            build_color_widget(l, editor_ref, color);
+            return;
+        }
+    }
+    if type_info.type_id() == TypeId::of::<Val>() {
+        if let Some(val) = Val::from_reflect(value) {
+            build_val_widget(l, editor_ref, val);
+            return;
+        }
+    }
+
+    // Destructure the value.
+    match value.reflect_ref() {
+        ReflectRef::Struct(dyn_struct) => build_struct_widget(l, widgets, editor_ref, dyn_struct),
+        ReflectRef::TupleStruct(dyn_tuplestruct) => {
+            build_tuple_struct_widget(l, widgets, editor_ref, dyn_tuplestruct)
+        }
+        ReflectRef::Tuple(dyn_tuple) => build_tuple_widget(l, widgets, editor_ref, dyn_tuple),
+        ReflectRef::List(dyn_list) => build_list_widget(l, widgets, editor_ref, dyn_list),
+        ReflectRef::Array(dyn_array) => build_array_widget(l, widgets, editor_ref, dyn_array),
+        ReflectRef::Map(dyn_map) => build_map_widget(l, widgets, editor_ref, dyn_map),
+        ReflectRef::Set(_) => {
+            // TODO: sets can only be edited by adding/removing entries
+            l.load_scene(("editor.frame", "sets_unsupported"));
+        }
+        ReflectRef::Enum(dyn_enum) => match type_info.type_path().starts_with("core::option::Option<") {
+            true => build_option_widget(l, widgets, editor_ref, dyn_enum, type_info),
+            false => build_enum_widget(l, widgets, editor_ref, dyn_enum, type_info),
+        },
+        ReflectRef::Opaque(_) => build_primitive_widget(l, editor_ref, value),
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Entity event sent to an inline text input when its text is submitted with `Enter`.
+struct EditorTextSubmit(String);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Tracks the inline text input that receives keyboard input.
+#[derive(Resource, Default)]
+struct EditorTextFocus(Option<Entity>);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Component for inline text inputs.
+#[derive(Component, Debug)]
+struct EditorTextInput
+{
+    /// The text being edited.
+    text: String,
+    /// The last submitted text.
+    original: String,
+    /// The entity that displays the text.
+    text_entity: Entity,
+}
+
+impl EditorTextInput
+{
+    /// Displays the text, with a cursor if the input is focused.
+    fn write(&self, text_editor: &mut TextEditor, focused: bool)
+    {
+        match focused {
+            true => write_text!(text_editor, self.text_entity, "{}|", self.text),
+            false => write_text!(text_editor, self.text_entity, "{}", self.text),
+        };
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Component that tracks the current value of a [`Val`] widget.
+#[derive(Component, Debug)]
+struct EditedVal(Val);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(super) struct CobReflectWidgetsPlugin;
+
+impl Plugin for CobReflectWidgetsPlugin
+{
+    fn build(&self, app: &mut App)
+    {
+        app.init_resource::<EditorTextFocus>()
+            .add_systems(Update, update_text_input);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests
+{
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use bevy::reflect::serde::TypedReflectDeserializer;
+    use serde::de::DeserializeSeed;
+
+    use super::*;
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    struct Inner
+    {
+        x: u32,
+    }
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    enum Shape
+    {
+        #[default]
+        Empty,
+        Rect(u32, u32),
+    }
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    struct Widgets
+    {
+        inner: Inner,
+        shape: Shape,
+        list: Vec<u32>,
+        map: HashMap<String, u32>,
+        option: Option<u32>,
+    }
+
+    fn path(points: &[ReflectStructurePoint]) -> ReflectStructurePath
+    {
+        ReflectStructurePath { path: Arc::from(points.to_vec()) }
+    }
+
+    /// Patches `value` into `target` at `points`.
+    fn patch(target: &mut Box<dyn PartialReflect>, points: &[ReflectStructurePoint], value: u32)
+    {
+        path(points)
+            .try_patch_value(target, Box::new(value))
+            .unwrap();
+    }
+
+    #[test]
+    fn widget_paths_resolve()
+    {
+        let mut registry = TypeRegistry::default();
+        registry.register::<Widgets>();
+
+        // Deserialize a loadable the same way the editor does, which produces dynamic values.
+        let raw = "#commands\nWidgets{inner:{x:1} shape:Rect(2 3) list:[4 5] map:{\"a\":6} option:7}\n";
+        let mut data = Cob::parse(Span::new_extra(raw, CobLocationMetadata { file: "test.cob" })).unwrap();
+        let loadable = data.get_command_loadable_mut("Widgets").unwrap().clone();
+        let registration = registry.get(TypeId::of::<Widgets>()).unwrap();
+        let mut target = TypedReflectDeserializer::new(registration, &registry)
+            .deserialize(&loadable)
+            .unwrap();
+
+        // Collect the paths the widgets would use for each field.
+        let ReflectRef::Struct(dyn_struct) = target.reflect_ref() else { unreachable!() };
+        let fields = struct_entries(dyn_struct).unwrap();
+        let names: Vec<&str> = fields.iter().map(|(name, ..)| name.as_str()).collect();
+        assert_eq!(names, ["inner", "shape", "list", "map", "option"]);
+
+        let field = |i: usize| fields[i].2.reflect_ref();
+        let ReflectRef::Struct(inner) = field(0) else { unreachable!() };
+        let inner_entries = struct_entries(inner).unwrap();
+        assert_eq!(inner_entries[0].0, "x");
+
+        let ReflectRef::Enum(shape) = field(1) else { unreachable!() };
+        let Some(TypeInfo::Enum(shape_info)) = shape.get_represented_type_info() else { unreachable!() };
+        let (variant_name, shape_entries) = enum_entries(shape, shape_info).unwrap();
+        assert_eq!(variant_name, "Rect");
+        assert_eq!(shape_entries[1].0, "1");
+
+        let ReflectRef::List(list) = field(2) else { unreachable!() };
+        let elements = list_entries(list);
+        assert_eq!(elements.len(), 2);
+        assert_eq!(elements[1].0, "1");
+
+        let ReflectRef::Map(map) = field(3) else { unreachable!() };
+        let values = map_entries(map);
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].0.try_downcast_ref::<String>().unwrap(), "a");
+
+        let ReflectRef::Enum(option) = field(4) else { unreachable!() };
+        let (option_point, _) = option_entry(option).unwrap();
+
+        let patches = [
+            ([fields[0].1.clone(), inner_entries[0].1.clone()], 10),
+            ([fields[1].1.clone(), shape_entries[1].1.clone()], 30),
+            ([fields[2].1.clone(), elements[1].1.clone()], 50),
+            ([fields[3].1.clone(), values[0].1.clone()], 60),
+            ([fields[4].1.clone(), option_point], 70),
+        ];
+
+        // Edits through the paths change the expected parts of the loadable.
+        for (points, value) in patches {
+            patch(&mut target, &points, value);
+        }
+        assert_eq!(
+            Widgets::from_reflect(target.as_ref()).unwrap(),
+            Widgets {
+                inner: Inner { x: 10 },
+                shape: Shape::Rect(2, 30),
+                list: vec![4, 50],
+                map: HashMap::from([(String::from("a"), 60)]),
+                option: Some(70),
+            }
+        );
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::load_embedded_scene_file;
+use crate::builtin::EDITOR_FRAME_COB;
 
 //-------------------------------------------------------------------------------------------------------------------
 
@@ -10,7 +10,7 @@ impl Plugin for CobEditorTemplatePlugin
 {
     fn build(&self, app: &mut App)
     {
-        load_embedded_scene_file!(app, "bevy_cobweb_ui", "src/editor/template", "frame.cob");
+        EDITOR_FRAME_COB.load(app);
     }
 }
 
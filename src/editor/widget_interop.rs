@@ -3,7 +3,10 @@
 use std::sync::Arc;
 
 use bevy::prelude::*;
-use bevy::reflect::{ApplyError, ReflectMut};
+use bevy::reflect::{
+    ApplyError, Array, DynamicArray, DynamicEnum, DynamicStruct, DynamicTuple, DynamicTupleStruct, DynamicVariant,
+    Enum, List, Map, ReflectMut, ReflectRef, Tuple, TypeInfo, VariantInfo, VariantType,
+};
 
 use super::*;
 use crate::prelude::*;
@@ -11,7 +14,7 @@ use crate::prelude::*;
 //-------------------------------------------------------------------------------------------------------------------
 
 /// A structure point is a specific item inside some container.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub(super) enum ReflectStructurePoint
 {
     /// Includes the field name.
@@ -88,6 +91,213 @@ impl ReflectStructurePoint
             }
         }
     }
+
+    /// Makes a dynamic copy of `target` with the current point replaced by the output of `edit`.
+    fn rebuild(
+        &self,
+        target: &(dyn PartialReflect + 'static),
+        edit: impl FnOnce(&(dyn PartialReflect + 'static)) -> Result<Box<dyn PartialReflect>, String>,
+    ) -> Result<Box<dyn PartialReflect>, String>
+    {
+        let not_found = || format!("{:?} not found", self);
+
+        match self {
+            Self::Struct(field_name) => {
+                let ReflectRef::Struct(dyn_struct) = target.reflect_ref() else { return Err(not_found()) };
+                let field = (edit)(dyn_struct.field(field_name).ok_or_else(not_found)?)?;
+                let mut rebuilt = dyn_struct.clone_dynamic();
+                rebuilt.insert_boxed(*field_name, field);
+                Ok(Box::new(rebuilt))
+            }
+            Self::TupleStruct(index) => {
+                let ReflectRef::TupleStruct(dyn_tuplestruct) = target.reflect_ref() else {
+                    return Err(not_found());
+                };
+                let mut field = Some((edit)(dyn_tuplestruct.field(*index).ok_or_else(not_found)?)?);
+                let mut rebuilt = DynamicTupleStruct::default();
+                rebuilt.set_represented_type(dyn_tuplestruct.get_represented_type_info());
+                for (i, value) in dyn_tuplestruct.iter_fields().enumerate() {
+                    let value = if i == *index {
+                        field.take().unwrap()
+                    } else {
+                        value.clone_value()
+                    };
+                    rebuilt.insert_boxed(value);
+                }
+                Ok(Box::new(rebuilt))
+            }
+            Self::Tuple(index) => {
+                let ReflectRef::Tuple(dyn_tuple) = target.reflect_ref() else { return Err(not_found()) };
+                let mut field = Some((edit)(dyn_tuple.field(*index).ok_or_else(not_found)?)?);
+                let mut rebuilt = DynamicTuple::default();
+                rebuilt.set_represented_type(dyn_tuple.get_represented_type_info());
+                for (i, value) in dyn_tuple.iter_fields().enumerate() {
+                    let value = if i == *index {
+                        field.take().unwrap()
+                    } else {
+                        value.clone_value()
+                    };
+                    rebuilt.insert_boxed(value);
+                }
+                Ok(Box::new(rebuilt))
+            }
+            Self::List(index) => {
+                let ReflectRef::List(dyn_list) = target.reflect_ref() else { return Err(not_found()) };
+                let element = (edit)(dyn_list.get(*index).ok_or_else(not_found)?)?;
+                let mut rebuilt = dyn_list.clone_dynamic();
+                rebuilt.remove(*index);
+                rebuilt.insert(*index, element);
+                Ok(Box::new(rebuilt))
+            }
+            Self::Array(index) => {
+                let ReflectRef::Array(dyn_array) = target.reflect_ref() else { return Err(not_found()) };
+                let mut element = Some((edit)(dyn_array.get(*index).ok_or_else(not_found)?)?);
+                let values: Vec<Box<dyn PartialReflect>> = dyn_array
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        if i == *index {
+                            element.take().unwrap()
+                        } else {
+                            value.clone_value()
+                        }
+                    })
+                    .collect();
+                let mut rebuilt = DynamicArray::new(values.into_boxed_slice());
+                rebuilt.set_represented_type(dyn_array.get_represented_type_info());
+                Ok(Box::new(rebuilt))
+            }
+            Self::MapValue(index) => {
+                let ReflectRef::Map(dyn_map) = target.reflect_ref() else { return Err(not_found()) };
+                let (key, value) = dyn_map.get_at(*index).ok_or_else(not_found)?;
+                let value = (edit)(value)?;
+                let mut rebuilt = dyn_map.clone_dynamic();
+                rebuilt.insert_boxed(key.clone_value(), value);
+                Ok(Box::new(rebuilt))
+            }
+            Self::Enum(variant_name, index) => {
+                let ReflectRef::Enum(dyn_enum) = target.reflect_ref() else { return Err(not_found()) };
+                if dyn_enum.variant_name() != *variant_name {
+                    return Err(not_found());
+                }
+                let field = (edit)(dyn_enum.field_at(*index).ok_or_else(not_found)?)?;
+                let mut fields = enum_fields(dyn_enum);
+                fields[*index].1 = field;
+                Ok(Box::new(rebuild_enum(dyn_enum, fields)))
+            }
+            Self::MapKey(_) | Self::Set(_) => Err(format!("{:?} can't be rebuilt", self)),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Copies the fields of an enum's current variant. Tuple variant fields have no names.
+fn enum_fields(dyn_enum: &dyn Enum) -> Vec<(Option<String>, Box<dyn PartialReflect>)>
+{
+    (0..dyn_enum.field_len())
+        .map(|i| {
+            let name = dyn_enum.name_at(i).map(String::from);
+            (name, dyn_enum.field_at(i).unwrap().clone_value())
+        })
+        .collect()
+}
+
+/// Makes a copy of an enum's current variant with new fields.
+fn rebuild_enum(dyn_enum: &dyn Enum, fields: Vec<(Option<String>, Box<dyn PartialReflect>)>) -> DynamicEnum
+{
+    let variant = match dyn_enum.variant_type() {
+        VariantType::Struct => {
+            let mut dyn_struct = DynamicStruct::default();
+            for (name, value) in fields {
+                dyn_struct.insert_boxed(name.unwrap_or_default(), value);
+            }
+            DynamicVariant::Struct(dyn_struct)
+        }
+        VariantType::Tuple => {
+            let mut dyn_tuple = DynamicTuple::default();
+            for (_, value) in fields {
+                dyn_tuple.insert_boxed(value);
+            }
+            DynamicVariant::Tuple(dyn_tuple)
+        }
+        VariantType::Unit => DynamicVariant::Unit,
+    };
+    let mut rebuilt =
+        DynamicEnum::new_with_index(dyn_enum.variant_index(), dyn_enum.variant_name().to_string(), variant);
+    rebuilt.set_represented_type(dyn_enum.get_represented_type_info());
+    rebuilt
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Edits the fields of a struct or struct-like enum variant.
+///
+/// Fields are sorted in declaration order after the edit. Fails if a field isn't declared by the type.
+pub(super) fn edit_struct_fields(
+    target: &(dyn PartialReflect + 'static),
+    edit: impl FnOnce(&mut Vec<(String, Box<dyn PartialReflect>)>) -> Result<(), String>,
+) -> Result<Box<dyn PartialReflect>, String>
+{
+    let type_info = target.get_represented_type_info();
+
+    match target.reflect_ref() {
+        ReflectRef::Struct(dyn_struct) => {
+            let Some(TypeInfo::Struct(info)) = type_info else {
+                return Err(String::from("struct type info is missing"));
+            };
+            let mut fields: Vec<_> = (0..dyn_struct.field_len())
+                .map(|i| {
+                    let name = dyn_struct.name_at(i).unwrap().to_string();
+                    (name, dyn_struct.field_at(i).unwrap().clone_value())
+                })
+                .collect();
+            (edit)(&mut fields)?;
+            let mut indexed = fields
+                .into_iter()
+                .map(|(name, value)| match info.index_of(&name) {
+                    Some(i) => Ok((i, name, value)),
+                    None => Err(format!("{} has no field {}", info.type_path(), name)),
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            indexed.sort_by_key(|(i, _, _)| *i);
+
+            let mut rebuilt = DynamicStruct::default();
+            rebuilt.set_represented_type(type_info);
+            for (_, name, value) in indexed {
+                rebuilt.insert_boxed(name, value);
+            }
+            Ok(Box::new(rebuilt))
+        }
+        ReflectRef::Enum(dyn_enum) if matches!(dyn_enum.variant_type(), VariantType::Struct) => {
+            let Some(TypeInfo::Enum(info)) = type_info else {
+                return Err(String::from("enum type info is missing"));
+            };
+            let Some(VariantInfo::Struct(variant_info)) = info.variant(dyn_enum.variant_name()) else {
+                return Err(format!("{} has no struct variant {}", info.type_path(), dyn_enum.variant_name()));
+            };
+            let mut fields: Vec<_> = enum_fields(dyn_enum)
+                .into_iter()
+                .map(|(name, value)| (name.unwrap_or_default(), value))
+                .collect();
+            (edit)(&mut fields)?;
+            let mut indexed = fields
+                .into_iter()
+                .map(|(name, value)| match variant_info.index_of(&name) {
+                    Some(i) => Ok((i, Some(name), value)),
+                    None => Err(format!("{}::{} has no field {}", info.type_path(), variant_info.name(), name)),
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            indexed.sort_by_key(|(i, _, _)| *i);
+
+            let fields = indexed
+                .into_iter()
+                .map(|(_, name, value)| (name, value))
+                .collect();
+            Ok(Box::new(rebuild_enum(dyn_enum, fields)))
+        }
+        _ => Err(String::from("expected a struct or struct-like enum variant")),
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -95,7 +305,7 @@ impl ReflectStructurePoint
 /// Records a 'path' into a reflected type where a [`CobEditorWidget`] is editing.
 ///
 /// Used to target-patch parts of a reflected type.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub(super) struct ReflectStructurePath
 {
     pub(super) path: Arc<[ReflectStructurePoint]>,
@@ -131,8 +341,44 @@ impl ReflectStructurePath
             target_part = next;
         }
 
-        target.try_apply(value.as_ref()).map_err(|e| Some(e))
+        target_part.try_apply(value.as_ref()).map_err(|e| Some(e))
     }
+
+    /// Makes a path to a value inside the value this path points to.
+    pub(super) fn extend(&self, point: ReflectStructurePoint) -> Self
+    {
+        Self {
+            path: self
+                .path
+                .iter()
+                .cloned()
+                .chain(std::iter::once(point))
+                .collect(),
+        }
+    }
+
+    /// Makes a dynamic copy of `target` with the value pointed to by `self.path` replaced by the output of `edit`.
+    ///
+    /// Unlike [`Self::try_patch_value`], the value is replaced instead of patched, so struct fields and collection
+    /// entries can be added or removed.
+    pub(super) fn try_rebuild_value(
+        &self,
+        target: &(dyn PartialReflect + 'static),
+        edit: impl FnOnce(&(dyn PartialReflect + 'static)) -> Result<Box<dyn PartialReflect>, String>,
+    ) -> Result<Box<dyn PartialReflect>, String>
+    {
+        rebuild_value(target, &self.path, edit)
+    }
+}
+
+fn rebuild_value(
+    target: &(dyn PartialReflect + 'static),
+    path: &[ReflectStructurePoint],
+    edit: impl FnOnce(&(dyn PartialReflect + 'static)) -> Result<Box<dyn PartialReflect>, String>,
+) -> Result<Box<dyn PartialReflect>, String>
+{
+    let Some((point, remaining)) = path.split_first() else { return (edit)(target) };
+    point.rebuild(target, |inner| rebuild_value(inner, remaining, edit))
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -162,6 +408,15 @@ pub struct CobEditorRef
 
 impl CobEditorRef
 {
+    /// Makes a reference to a value inside the value this reference points to.
+    pub(super) fn extend(&self, point: ReflectStructurePoint) -> Self
+    {
+        Self {
+            structure_path: self.structure_path.extend(point),
+            ..self.clone()
+        }
+    }
+
     /// The scene location this reference points to.
     pub fn scene_ref(&self) -> SceneRef
     {
@@ -191,6 +446,7 @@ impl CobEditorRef
 /// Note that if your value is *not* a loadable and contains `#[reflect(default)]` fields, widgets are currently
 /// unable to add or remove such fields. Non-loadable values passed via [`SubmitPatch`] are applied with
 /// [`PartialReflect::try_apply`], which only updates the intersecting fields between the original and the patch.
+/// Use [`InsertStructField`] and [`RemoveStructField`] to add or remove such fields.
 ///
 /// Loadables do not have this problem because submitted patches will directly replace the original loadable.
 pub trait CobEditorWidget
@@ -0,0 +1,52 @@
+use bevy::prelude::*;
+use smol_str::SmolStr;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Resource with the theme that constants overridden by `#theme` sections in COB files resolve to.
+///
+/// If no theme is active, or the active theme doesn't override a constant, then the constant's value from its
+/// `#defs` section is used.
+///
+/// Changing this re-resolves all commands and scene nodes that use themed constants, and updates the entities
+/// loaded from those scene nodes. This works with or without the `hot_reload` feature.
+///
+/// ```ignore
+/// app.world_mut().resource_mut::<ActiveTheme>().set("light");
+/// ```
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct ActiveTheme
+{
+    theme: Option<SmolStr>,
+}
+
+impl ActiveTheme
+{
+    /// Makes a resource with `theme` active.
+    pub fn new(theme: impl Into<SmolStr>) -> Self
+    {
+        Self { theme: Some(theme.into()) }
+    }
+
+    /// Sets the active theme.
+    pub fn set(&mut self, theme: impl Into<SmolStr>) -> &mut Self
+    {
+        self.theme = Some(theme.into());
+        self
+    }
+
+    /// Removes the active theme so constants use their default values.
+    pub fn clear(&mut self) -> &mut Self
+    {
+        self.theme = None;
+        self
+    }
+
+    /// Gets the active theme.
+    pub fn get(&self) -> Option<&str>
+    {
+        self.theme.as_deref()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
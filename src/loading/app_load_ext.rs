@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use bevy::asset::AssetApp;
 use bevy::prelude::*;
@@ -12,6 +12,7 @@ fn load_cobweb_assets(
     mut files: ResMut<LoadedCobAssetFiles>,
     mut cob_cache: ResMut<CobAssetCache>,
     mut commands_buffer: ResMut<CommandsBuffer>,
+    mut preloaded: ResMut<PreloadedAssets>,
     asset_server: Res<AssetServer>,
 )
 {
@@ -22,6 +23,24 @@ fn load_cobweb_assets(
         files.start_loading(file, &mut cob_cache, &asset_server);
     }
 
+    // Loads preloaded files and assets.
+    // - Files are registered in the commands buffer when their manifests are processed.
+    let mut seen: HashSet<CobFile> = presets.iter().cloned().collect();
+    for index in files.take_preload_indices() {
+        for file in index.files.iter() {
+            let Some(file) = CobFile::try_new(file) else {
+                tracing::warn!("failed preloading COB file {}; does not have '.cob' extension", file);
+                continue;
+            };
+            if !seen.insert(file.clone()) {
+                continue;
+            }
+            files.start_loading(file, &mut cob_cache, &asset_server);
+        }
+
+        preloaded.load(&index, &asset_server);
+    }
+
     // Initialize commands buffer.
     commands_buffer.set_root_file(presets);
 }
@@ -33,20 +52,23 @@ fn load_cobweb_assets(
 pub(crate) struct LoadedCobAssetFiles
 {
     preset_files: Vec<CobFile>,
+    preload_indices: Vec<CobIndex>,
     handles: HashMap<AssetId<CobAssetFile>, Handle<CobAssetFile>>,
+    /// Files that were loaded before a manifest containing them was processed.
+    deferred: Vec<CobAssetFile>,
 }
 
 impl LoadedCobAssetFiles
 {
     fn add_preset_file(&mut self, file: &str)
     {
-        match CobFile::try_new(file) {
+        match CobFile::try_new(file).or_else(|| CobFile::try_new_compiled(file)) {
             Some(file) => {
                 tracing::info!("registered COB file {}", file.as_str());
                 self.preset_files.push(file);
             }
             None => {
-                tracing::warn!("failed registering COB file {}; does not have '.cob' extension", file)
+                tracing::warn!("failed registering COB file {}; does not have '.cob' or '.cobc' extension", file)
             }
         }
     }
@@ -56,6 +78,11 @@ impl LoadedCobAssetFiles
         std::mem::take(&mut self.preset_files)
     }
 
+    fn take_preload_indices(&mut self) -> Vec<CobIndex>
+    {
+        std::mem::take(&mut self.preload_indices)
+    }
+
     pub(crate) fn start_loading(
         &mut self,
         file: CobFile,
@@ -81,6 +108,21 @@ impl LoadedCobAssetFiles
     {
         self.handles.remove(&id)
     }
+
+    pub(crate) fn defer_file(&mut self, asset: CobAssetFile)
+    {
+        self.deferred.push(asset);
+    }
+
+    pub(crate) fn take_deferred_files(&mut self) -> Vec<CobAssetFile>
+    {
+        std::mem::take(&mut self.deferred)
+    }
+
+    pub(crate) fn num_deferred_files(&self) -> usize
+    {
+        self.deferred.len()
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -89,7 +131,16 @@ impl LoadedCobAssetFiles
 pub trait LoadedCobAssetFilesAppExt
 {
     /// Registers a cobweb asset file to be loaded.
+    ///
+    /// Files compiled with [`CobCompiler`] (`.cobc` files) can be loaded instead of the text files they were
+    /// compiled from.
     fn load(&mut self, file: impl AsRef<str>) -> &mut Self;
+
+    /// Requests all files and assets in a [`CobIndex`] on startup.
+    ///
+    /// Root files still need to be registered with [`Self::load`]. Preloaded files are only processed once a
+    /// manifest that contains them is processed.
+    fn preload(&mut self, index: CobIndex) -> &mut Self;
 }
 
 impl LoadedCobAssetFilesAppExt for App
@@ -105,6 +156,19 @@ impl LoadedCobAssetFilesAppExt for App
             .add_preset_file(file.as_ref());
         self
     }
+
+    fn preload(&mut self, index: CobIndex) -> &mut Self
+    {
+        if !self.world().contains_resource::<LoadedCobAssetFiles>() {
+            self.init_resource::<LoadedCobAssetFiles>();
+        }
+
+        self.world_mut()
+            .resource_mut::<LoadedCobAssetFiles>()
+            .preload_indices
+            .push(index);
+        self
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -17,11 +17,16 @@ struct PreprocessedSceneFile
     /// Imports for detecting when a re-load is required.
     /// - Can include both manifest keys and file paths.
     imports: HashMap<ManifestKey, CobImportAlias>,
+    /// Files containing base scenes of scenes in this file.
+    scene_base_files: HashSet<ManifestKey>,
     /// Data cached for re-loading when dependencies are reloaded.
     data: Cob,
     /// File hash for editor use.
     #[cfg(feature = "editor")]
     hash: crate::editor::CobFileHash,
+    /// Whether the file had parse errors, for editor use.
+    #[cfg(feature = "editor")]
+    has_errors: bool,
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -33,20 +38,29 @@ struct ProcessedSceneFile
     constants_buff: ConstantsBuffer,
     /// Specs that can be imported into other files.
     specs: SpecsMap,
+    /// Scenes cached for use as base scenes by dependents.
+    scenes: BaseScenes,
     /// Imports for detecting when a re-load is required.
     #[cfg(feature = "hot_reload")]
     imports: HashMap<ManifestKey, CobImportAlias>,
+    /// Base scene files for detecting when a re-load is required.
+    #[cfg(feature = "hot_reload")]
+    scene_base_files: HashSet<ManifestKey>,
     /// Un-extracted data cached for re-loading when imports are reloaded.
     #[cfg(feature = "hot_reload")]
     data: Cob,
     /// File hash for editor use.
     #[cfg(feature = "editor")]
     hash: crate::editor::CobFileHash,
+    /// Whether the file had parse errors, for editor use.
+    #[cfg(feature = "editor")]
+    has_errors: bool,
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
-/// Resource that manages content extracted from cobweb asset files (`.cob` files).
+/// Resource that manages content extracted from cobweb asset files (`.cob` files) and compiled files (`.cobc`
+/// files).
 ///
 /// Can be used to load scenes with [`LoadSceneExt::load_scene`], or load individual scene nodes with
 /// [`CobLoadingEntityCommandsExt::load`].
@@ -82,11 +96,28 @@ pub(crate) struct CobAssetCache
     /// Records processed files.
     processed: HashMap<CobFile, ProcessedSceneFile>,
 
+    /// Tracks compiled files waiting to be processed.
+    compiled: Vec<(CobFile, CompiledCob)>,
+    /// Tracks compiled scenes that have not been extracted.
+    #[cfg(feature = "hot_reload")]
+    needs_compiled_scene_extraction: Vec<(CobFile, Vec<CompiledSceneNode>)>,
+
     /// Tracks files that have been processed but not scene-extracted.
     #[cfg(feature = "hot_reload")]
     needs_scene_extraction: HashMap<CobFile, Cob>,
 }
 
+impl PreprocessedSceneFile
+{
+    /// Iterates the manifest keys of files that must be processed before this file.
+    fn dependencies(&self) -> impl Iterator<Item = &ManifestKey>
+    {
+        self.imports.keys().chain(self.scene_base_files.iter())
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 impl CobAssetCache
 {
     pub(super) fn new(manifest_map: Arc<Mutex<ManifestMap>>) -> Self
@@ -114,10 +145,23 @@ impl CobAssetCache
         (self.pending.len(), self.total_expected_sheets)
     }
 
+    /// Gets the number of files that have not been loaded.
+    pub(super) fn num_pending_files(&self) -> usize
+    {
+        self.pending.len()
+    }
+
+    /// Returns `true` if any file has finished loading.
+    #[cfg(not(feature = "hot_reload"))]
+    pub(super) fn has_loaded_files(&self) -> bool
+    {
+        self.pending.len() < self.total_expected_sheets
+    }
+
     /// Gets the number of files waiting to be processed.
     pub(super) fn num_preprocessed_pending(&self) -> usize
     {
-        self.preprocessed.len()
+        self.preprocessed.len() + self.compiled.len()
     }
 
     /// Returns `(hash, data, is_processed)`.
@@ -223,6 +267,28 @@ impl CobAssetCache
         let _ = self.pending.remove(file);
     }
 
+    /// Replaces scene nodes that failed to parse with their versions from the last time the file was loaded.
+    #[cfg(feature = "hot_reload")]
+    pub(crate) fn restore_broken_scene_nodes(&self, data: &mut Cob, broken_nodes: &[ScenePath])
+    {
+        if broken_nodes.is_empty() {
+            return;
+        }
+
+        // Use the most recent version of the file, which may not be processed yet.
+        let prev = self
+            .preprocessed
+            .iter()
+            .rev()
+            .find(|p| p.file == data.file)
+            .map(|p| &p.data)
+            .or_else(|| self.processed.get(&data.file).map(|p| &p.data));
+        let Some(prev) = prev else { return };
+
+        tracing::info!("keeping last good version of scene nodes {:?} in {:?}", broken_nodes, data.file);
+        data.restore_scene_nodes(prev, broken_nodes);
+    }
+
     /// Inserts a preprocessed file for later processing.
     pub(crate) fn add_preprocessed_file(
         &mut self,
@@ -230,6 +296,7 @@ impl CobAssetCache
         imports: HashMap<ManifestKey, CobImportAlias>,
         data: Cob,
         #[cfg(feature = "editor")] hash: crate::editor::CobFileHash,
+        #[cfg(feature = "editor")] has_errors: bool,
     )
     {
         // Remove if already processed.
@@ -251,7 +318,8 @@ impl CobAssetCache
         // - Note: We don't need to check for circular dependencies here. It can be checked after processing files
         //   by seeing if there are any pending files remaining. Once all pending files are loaded, if a file fails
         //   to process that implies it has circular dependencies.
-        for import in imports.keys() {
+        let scene_base_files = collect_scene_base_files(&data);
+        for import in imports.keys().chain(scene_base_files.iter()) {
             // Try to convert to file. This may fail if the imported file is not initialized yet.
             let Some(import_file) = self.manifest_map().get(import) else { continue };
 
@@ -289,13 +357,94 @@ impl CobAssetCache
         let preprocessed = PreprocessedSceneFile {
             file,
             imports,
+            scene_base_files,
             data,
             #[cfg(feature = "editor")]
             hash,
+            #[cfg(feature = "editor")]
+            has_errors,
         };
         self.preprocessed.push(preprocessed);
     }
 
+    /// Inserts a compiled file for later processing.
+    pub(crate) fn add_compiled_file(&mut self, file: CobFile, data: CompiledCob)
+    {
+        self.initialize_file(&file);
+        self.compiled.push((file, data));
+    }
+
+    /// Extracts the files in a compiled file.
+    ///
+    /// Compiled files don't have dependencies, so they can be extracted immediately. The compiled file is added to
+    /// the commands buffer as the parent of its roots, so commands are ordered the same as if the roots were
+    /// loaded directly.
+    ///
+    /// Returns the number of files extracted.
+    fn process_compiled_file(
+        &mut self,
+        bundle: CobFile,
+        data: CompiledCob,
+        type_registry: &TypeRegistry,
+        diagnostics: &CobDiagnostics,
+        _c: &mut Commands,
+        commands_buffer: &mut CommandsBuffer,
+        scene_buffer: &mut SceneBuffer,
+        _scene_loader: &mut SceneLoader,
+    ) -> usize
+    {
+        tracing::info!("extracting compiled COB file {:?}", bundle.as_str());
+
+        commands_buffer.set_file_descendants(bundle.clone(), data.roots);
+        commands_buffer.set_file_commands(bundle, vec![]);
+
+        // Files are ordered so each file comes after the file whose manifest contains it.
+        let num_files = data.files.len();
+        for CompiledCobFile { file, manifest, commands, scenes } in data.files {
+            diagnostics.clear_file(file.as_str());
+
+            // Register manifest keys.
+            let mut descendants = vec![];
+            for (other_file, manifest_key) in manifest {
+                if other_file != file {
+                    descendants.push(other_file.clone());
+                }
+                self.register_manifest_key(other_file, Some(manifest_key));
+            }
+            commands_buffer.set_file_descendants(file.clone(), descendants);
+
+            extract_compiled_commands(
+                type_registry,
+                commands_buffer,
+                scene_buffer,
+                file.clone(),
+                commands,
+                diagnostics,
+            );
+
+            #[cfg(not(feature = "hot_reload"))]
+            {
+                // Extract scenes immediately.
+                extract_compiled_scenes(
+                    type_registry,
+                    _c,
+                    scene_buffer,
+                    _scene_loader,
+                    file,
+                    scenes,
+                    diagnostics,
+                );
+            }
+            #[cfg(feature = "hot_reload")]
+            {
+                // Defer scene extraction until it can be synchronized with loading entities.
+                self.needs_compiled_scene_extraction.push((file, scenes));
+            }
+        }
+
+        num_files
+    }
+
     /// Converts a preprocessed file to a processed file.
     ///
     /// Assumes all imports are available.
@@ -303,40 +452,19 @@ impl CobAssetCache
         &mut self,
         mut preprocessed: PreprocessedSceneFile,
         type_registry: &TypeRegistry,
+        flags: &CobFlags,
         loadables: &LoadableRegistry,
         _c: &mut Commands,
         commands_buffer: &mut CommandsBuffer,
-        _scene_buffer: &mut SceneBuffer,
+        scene_buffer: &mut SceneBuffer,
         _scene_loader: &mut SceneLoader,
     )
     {
-        // Initialize constants map from dependencies.
-        let mut constants_buff = ConstantsBuffer::default();
-        // specs collector
-        let mut specs = SpecsMap::default();
-
-        for (dependency, alias) in preprocessed.imports.iter() {
-            let Some(dependency) = self.manifest_map().get(&dependency) else {
-                tracing::error!("failed extracting import {:?} for {:?}; failed manifest key lookup (this is a bug)",
-                    dependency, preprocessed.file);
-                continue;
-            };
-            let Some(processed) = self.processed.get(&dependency) else {
-                tracing::error!("failed extracting import {:?} for {:?}; dependency is not processed (this is a bug)",
-                    dependency, preprocessed.file);
-                continue;
-            };
-
-            constants_buff.append(alias, &processed.constants_buff);
-            specs.import_specs(&dependency, &preprocessed.file, &processed.specs);
-        }
-
         // Prepare to process the file.
         let mut processed = ProcessedSceneFile::default();
 
         #[cfg(feature = "hot_reload")]
         {
-            processed.imports = preprocessed.imports;
             // Data must be cloned before extraction, because extraction will modify the value in-place in order
             // to process definitions. Definitions always need to be re-processed when re-extracting a file.
             processed.data = preprocessed.data.clone();
@@ -345,37 +473,82 @@ impl CobAssetCache
         #[cfg(feature = "editor")]
         {
             processed.hash = preprocessed.hash;
+            processed.has_errors = preprocessed.has_errors;
+        }
+
+        // Collect constants maps from dependencies.
+        // specs collector
+        let mut specs = SpecsMap::default();
+        let mut imports = Vec::with_capacity(preprocessed.imports.len());
+        {
+            let manifest_map = self.manifest_map.lock().unwrap();
+            for (dependency, alias) in preprocessed.imports.iter() {
+                let Some(dependency) = manifest_map.get(&dependency) else {
+                    tracing::error!("failed extracting import {:?} for {:?}; failed manifest key lookup \
+                        (this is a bug)", dependency, preprocessed.file);
+                    continue;
+                };
+                let Some(processed) = self.processed.get(&dependency) else {
+                    tracing::error!("failed extracting import {:?} for {:?}; dependency is not processed \
+                        (this is a bug)", dependency, preprocessed.file);
+                    continue;
+                };
+
+                imports.push((alias, &processed.constants_buff));
+                specs.import_specs(&dependency, &preprocessed.file, &processed.specs);
+            }
         }
 
         // Process the file.
         // - This updates the constants/specs maps with info extracted from the file.
-        extract_cob_importables(
-            preprocessed.file.clone(),
-            &mut preprocessed.data,
-            &mut constants_buff,
-            &mut specs,
-        );
+        let constants_buff =
+            extract_cob_defs(&preprocessed.file, &mut preprocessed.data, flags, imports, &mut specs);
+
+        #[cfg(feature = "hot_reload")]
+        {
+            processed.imports = preprocessed.imports;
+            processed.scene_base_files = preprocessed.scene_base_files;
+        }
 
         extract_cob_commands(
             type_registry,
             commands_buffer,
+            scene_buffer,
             preprocessed.file.clone(),
             &mut preprocessed.data,
+            flags,
             loadables,
             &constants_buff,
             &specs,
         );
 
+        // Apply base scenes to derived scenes.
+        {
+            let manifest_map = self.manifest_map.lock().unwrap();
+            processed.scenes = extract_scene_inheritance(
+                &preprocessed.file,
+                &mut preprocessed.data,
+                flags,
+                &constants_buff,
+                |key| {
+                    let base_file = manifest_map.get(key)?;
+                    let base = self.processed.get(&base_file)?;
+                    Some((&base.scenes, &base.constants_buff))
+                },
+            );
+        }
+
         #[cfg(not(feature = "hot_reload"))]
         {
             // Extract scenes immediately.
             extract_cob_scenes(
                 type_registry,
                 _c,
-                _scene_buffer,
+                scene_buffer,
                 _scene_loader,
                 preprocessed.file.clone(),
                 preprocessed.data,
+                flags,
                 loadables,
                 &constants_buff,
                 &specs,
@@ -410,7 +583,9 @@ impl CobAssetCache
                     .processed
                     .iter()
                     .filter_map(|(file, processed)| {
-                        if processed.imports.contains_key(&manifest_key) {
+                        if processed.imports.contains_key(&manifest_key)
+                            || processed.scene_base_files.contains(&manifest_key)
+                        {
                             return Some(file.clone());
                         }
                         None
@@ -427,6 +602,8 @@ impl CobAssetCache
                         processed.data,
                         #[cfg(feature = "editor")]
                         processed.hash,
+                        #[cfg(feature = "editor")]
+                        processed.has_errors,
                     );
                 }
             }
@@ -439,7 +616,9 @@ impl CobAssetCache
     pub(super) fn process_cobweb_asset_files(
         &mut self,
         type_registry: &TypeRegistry,
+        flags: &CobFlags,
         loadables: &LoadableRegistry,
+        diagnostics: &CobDiagnostics,
         c: &mut Commands,
         commands_buffer: &mut CommandsBuffer,
         scene_buffer: &mut SceneBuffer,
@@ -450,6 +629,19 @@ impl CobAssetCache
         let mut num_processed = 0;
         let mut preprocessed = Vec::new();
 
+        for (bundle, data) in std::mem::take(&mut self.compiled) {
+            num_processed += self.process_compiled_file(
+                bundle,
+                data,
+                type_registry,
+                diagnostics,
+                c,
+                commands_buffer,
+                scene_buffer,
+                scene_loader,
+            );
+        }
+
         while !self.preprocessed.is_empty() {
             let num_already_processed = num_processed;
             preprocessed.clear();
@@ -462,8 +654,7 @@ impl CobAssetCache
                 {
                     let manifest_map = self.manifest_map.lock().unwrap();
                     if preprocessed
-                        .imports
-                        .keys()
+                        .dependencies()
                         .any(|i| match manifest_map.get(i) {
                             Some(i) => !self.processed.contains_key(&i),
                             None => true,
@@ -482,6 +673,7 @@ impl CobAssetCache
                 self.process_cobweb_asset_file(
                     preprocessed,
                     type_registry,
+                    flags,
                     loadables,
                     c,
                     commands_buffer,
@@ -520,11 +712,75 @@ impl CobAssetCache
         num_processed > 0
     }
 
+    /// Re-queues all processed files so they will be extracted again.
+    ///
+    /// Used when [`CobFlags`] change, since `#if` blocks may select different sections.
+    #[cfg(feature = "hot_reload")]
+    pub(super) fn reprocess_all_files(&mut self, commands_buffer: &mut CommandsBuffer)
+    {
+        let files: Vec<CobFile> = self.processed.keys().cloned().collect();
+        for file in files {
+            commands_buffer.prep_commands_refresh(file.clone());
+            let processed = self.processed.remove(&file).unwrap();
+            self.add_preprocessed_file(
+                file,
+                processed.imports,
+                processed.data,
+                #[cfg(feature = "editor")]
+                processed.hash,
+                #[cfg(feature = "editor")]
+                processed.has_errors,
+            );
+        }
+    }
+
+    /// Re-queues a file after it was edited in the editor, so it will be re-processed like a hot-reloaded file.
+    ///
+    /// Returns `false` if the file is unknown or its hash doesn't match, which means a hot-reloaded version of the
+    /// file is being processed.
+    #[cfg(feature = "editor")]
+    pub(crate) fn reprocess_edited_file(
+        &mut self,
+        commands_buffer: &mut CommandsBuffer,
+        data: Cob,
+        hash: crate::editor::CobFileHash,
+    ) -> bool
+    {
+        // Files waiting to be processed only need new data.
+        if self.preprocessed_set.contains(&data.file) {
+            let Some(preprocessed) = self.preprocessed.iter_mut().find(|p| p.file == data.file) else {
+                return false;
+            };
+            if preprocessed.hash != hash {
+                return false;
+            }
+            preprocessed.data = data;
+            return true;
+        }
+
+        if !self
+            .processed
+            .get(&data.file)
+            .is_some_and(|processed| processed.hash == hash)
+        {
+            return false;
+        }
+
+        let file = data.file.clone();
+        commands_buffer.prep_commands_refresh(file.clone());
+        let processed = self.processed.remove(&file).unwrap();
+        // Only files without errors can be edited.
+        self.add_preprocessed_file(file, processed.imports, data, hash, false);
+        true
+    }
+
     #[cfg(feature = "hot_reload")]
     pub(crate) fn handle_pending_scene_extraction(
         &mut self,
         type_registry: &TypeRegistry,
+        flags: &CobFlags,
         loadables: &LoadableRegistry,
+        diagnostics: &CobDiagnostics,
         c: &mut Commands,
         scene_buffer: &mut SceneBuffer,
         scene_loader: &mut SceneLoader,
@@ -532,6 +788,10 @@ impl CobAssetCache
     )
     {
         // Note: We assume it doesn't matter what file order scenes are extracted in.
+        for (file, scenes) in self.needs_compiled_scene_extraction.drain(..) {
+            extract_compiled_scenes(type_registry, c, scene_buffer, scene_loader, file, scenes, diagnostics);
+        }
+
         for (file, data) in self.needs_scene_extraction.drain() {
             let Some(processed) = self.processed.get_mut(&file) else { continue };
 
@@ -542,6 +802,7 @@ impl CobAssetCache
                 scene_loader,
                 file,
                 data,
+                flags,
                 loadables,
                 &processed.constants_buff,
                 &processed.specs,
@@ -552,7 +813,7 @@ impl CobAssetCache
             // file's cached state.
             #[cfg(feature = "editor")]
             {
-                editor.add_processed(c, processed.hash, &processed.data);
+                editor.add_processed(c, processed.hash, processed.has_errors, &processed.data);
             }
         }
     }
@@ -21,11 +21,11 @@ fn get_current_time() -> Duration
 
 //-------------------------------------------------------------------------------------------------------------------
 
-struct CommandLoadCommand
+pub(super) struct CommandLoadCommand
 {
-    callback: fn(&mut World, ReflectedLoadable, SceneRef),
-    scene_ref: SceneRef,
-    loadable: ReflectedLoadable,
+    pub(super) callback: fn(&mut World, ReflectedLoadable, SceneRef),
+    pub(super) scene_ref: SceneRef,
+    pub(super) loadable: ReflectedLoadable,
 }
 
 impl Command for CommandLoadCommand
@@ -190,13 +190,17 @@ pub(crate) struct CommandsBuffer
     stack_cached: Vec<(usize, Arc<[CobFile]>)>,
     /// Cached for memory reuse.
     inverted_stack_cached: Vec<(usize, Arc<[CobFile]>, bool)>,
+
+    /// Records re-applied commands.
+    #[cfg(feature = "hot_reload")]
+    recorder: ReloadRecorder,
 }
 
 impl CommandsBuffer
 {
     /// Makes a new buffer with pseudo 'global' file at the root level,
     /// where manually-loaded files will be added as descendants.
-    pub(super) fn new() -> Self
+    pub(super) fn new(#[cfg(feature = "hot_reload")] recorder: ReloadRecorder) -> Self
     {
         let global = Self::global_file();
         let mut buffer = Self {
@@ -216,6 +220,8 @@ impl CommandsBuffer
             #[cfg(feature = "hot_reload")]
             stack_cached: vec![],
             inverted_stack_cached: vec![],
+            #[cfg(feature = "hot_reload")]
+            recorder,
         };
         buffer.hierarchy.insert(
             global,
@@ -274,6 +280,14 @@ impl CommandsBuffer
         self.set_file_commands(file, vec![]);
     }
 
+    /// Returns `true` if the file has been registered as a descendant of another file.
+    ///
+    /// Files from a [`CobIndex`] may finish loading before the files whose manifests contain them.
+    pub(crate) fn contains_file(&self, file: &CobFile) -> bool
+    {
+        self.hierarchy.contains_key(file)
+    }
+
     /// Tries to update the traversal point to the requested file.
     ///
     /// Will truncate `self.file_order` to the new traversal point.
@@ -944,6 +958,14 @@ impl CommandsBuffer
                         continue;
                     };
 
+                    #[cfg(feature = "hot_reload")]
+                    buff.recorder.record(|report| {
+                        report.commands.push(CobReappliedCommand {
+                            file: file.clone(),
+                            type_path: cached.command.loadable.type_path().into(),
+                        })
+                    });
+
                     c.queue(CommandLoadCommand {
                         callback,
                         scene_ref: dummy_scene_ref.clone(),
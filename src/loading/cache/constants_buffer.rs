@@ -4,8 +4,8 @@ use std::sync::Arc;
 use smallvec::SmallVec;
 use smol_str::SmolStr;
 
-use crate::loading::CobConstantValue;
-use crate::prelude::CobImportAlias;
+use crate::loading::{CobConstantValue, CobDataMacroDef, CobLoadableMacroDef, CobSceneMacroDef};
+use crate::prelude::{CobFile, CobImportAlias};
 
 //-------------------------------------------------------------------------------------------------------------------
 
@@ -32,26 +32,80 @@ fn path_to_string<T: AsRef<str>>(separator: &str, path: &[T]) -> SmolStr
 
 //-------------------------------------------------------------------------------------------------------------------
 
-// [ identifier : constant value ]
-type ConstantsMap = HashMap<SmolStr, CobConstantValue>;
+/// The kinds of definitions in `#defs` sections.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CobDefKind
+{
+    /// `$name = value`
+    Constant,
+    /// `name!(..) = value`
+    DataMacro,
+    /// `name!(..) = Loadable(..)`
+    LoadableMacro,
+    /// `+name(..) = \` followed by scene layer entries
+    SceneMacro,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Definitions extracted from a single file.
+#[derive(Default, Debug)]
+struct DefsMap
+{
+    /// The file the definitions were extracted from.
+    file: Option<CobFile>,
+    /// [ identifier : constant value ]
+    constants: HashMap<SmolStr, CobConstantValue>,
+    /// [ identifier : data macro definition ]
+    data_macros: HashMap<SmolStr, CobDataMacroDef>,
+    /// [ identifier : loadable macro definition ]
+    loadable_macros: HashMap<SmolStr, CobLoadableMacroDef>,
+    /// [ identifier : scene macro definition ]
+    scene_macros: HashMap<SmolStr, CobSceneMacroDef>,
+}
+
+impl DefsMap
+{
+    fn get_name(&self, kind: CobDefKind, name: &str) -> Option<&SmolStr>
+    {
+        match kind {
+            CobDefKind::Constant => self.constants.get_key_value(name).map(|(k, _)| k),
+            CobDefKind::DataMacro => self.data_macros.get_key_value(name).map(|(k, _)| k),
+            CobDefKind::LoadableMacro => self.loadable_macros.get_key_value(name).map(|(k, _)| k),
+            CobDefKind::SceneMacro => self.scene_macros.get_key_value(name).map(|(k, _)| k),
+        }
+    }
+
+    fn names(&self, kind: CobDefKind) -> Vec<&SmolStr>
+    {
+        match kind {
+            CobDefKind::Constant => self.constants.keys().collect(),
+            CobDefKind::DataMacro => self.data_macros.keys().collect(),
+            CobDefKind::LoadableMacro => self.loadable_macros.keys().collect(),
+            CobDefKind::SceneMacro => self.scene_macros.keys().collect(),
+        }
+    }
+}
 
 //-------------------------------------------------------------------------------------------------------------------
 
-/// Records a stack of constant maps.
+/// Records a stack of constant and macro maps.
 ///
-/// Used to efficiently merge constants when importing them into new files.
+/// Used to efficiently merge constants and macros when importing them into new files.
 #[derive(Default, Debug)]
 pub struct ConstantsBuffer
 {
-    stack: SmallVec<[(SmolStr, Arc<ConstantsMap>); 5]>,
-    new_file: ConstantsMap,
+    stack: SmallVec<[(SmolStr, Arc<DefsMap>); 5]>,
+    new_file: DefsMap,
+    /// `true` while resolving the body of a macro definition, where macro params are allowed.
+    in_macro_def: bool,
 }
 
 impl ConstantsBuffer
 {
-    pub(crate) fn start_new_file(&mut self)
+    pub(crate) fn start_new_file(&mut self, file: &CobFile)
     {
-        self.new_file = HashMap::default();
+        self.new_file = DefsMap { file: Some(file.clone()), ..Default::default() };
     }
 
     pub(crate) fn end_new_file(&mut self)
@@ -63,24 +117,114 @@ impl ConstantsBuffer
     /// Adds an entry to the new file being collected.
     pub(crate) fn insert(&mut self, name: SmolStr, value: CobConstantValue)
     {
-        self.new_file.insert(name, value);
+        self.new_file.constants.insert(name, value);
+    }
+
+    /// Adds a data macro to the new file being collected.
+    pub(crate) fn insert_data_macro(&mut self, name: SmolStr, def: CobDataMacroDef)
+    {
+        self.new_file.data_macros.insert(name, def);
+    }
+
+    /// Adds a loadable macro to the new file being collected.
+    pub(crate) fn insert_loadable_macro(&mut self, name: SmolStr, def: CobLoadableMacroDef)
+    {
+        self.new_file.loadable_macros.insert(name, def);
+    }
+
+    /// Adds a scene macro to the new file being collected.
+    pub(crate) fn insert_scene_macro(&mut self, name: SmolStr, def: CobSceneMacroDef)
+    {
+        self.new_file.scene_macros.insert(name, def);
+    }
+
+    /// Resolves the body of a macro definition.
+    ///
+    /// Macro params are only allowed while resolving a macro definition. Elsewhere they are an error.
+    pub(crate) fn resolve_macro_def<T>(&mut self, resolve: impl FnOnce(&Self) -> T) -> T
+    {
+        self.in_macro_def = true;
+        let result = (resolve)(self);
+        self.in_macro_def = false;
+        result
+    }
+
+    /// Returns `true` if a macro definition is being resolved.
+    pub(crate) fn in_macro_def(&self) -> bool
+    {
+        self.in_macro_def
     }
 
     /// Searches backward through the stack until a match is found.
-    pub fn get(&self, path: impl AsRef<str>) -> Option<&CobConstantValue>
+    fn search<'a, T>(&'a self, path: &str, get: impl Fn(&'a DefsMap, &str) -> Option<T>) -> Option<T>
     {
-        let path = path.as_ref();
-        self.new_file.get(path).or_else(|| {
+        (get)(&self.new_file, path).or_else(|| {
             self.stack.iter().rev().find_map(|(prefix, m)| {
                 let stripped = path.strip_prefix(prefix.as_str())?;
                 let cleaned = stripped
                     .strip_prefix(CONSTANT_SEPARATOR)
                     .unwrap_or(stripped);
-                m.get(cleaned)
+                (get)(&**m, cleaned)
             })
         })
     }
 
+    /// Finds the file that defines the constant or macro at `path`, and the definition's name in that file.
+    ///
+    /// Returns `None` if the definition can't be found, or if it was inserted without a file.
+    pub fn locate(&self, kind: CobDefKind, path: impl AsRef<str>) -> Option<(&CobFile, &str)>
+    {
+        self.search(path.as_ref(), |m, p| {
+            Some((m.file.as_ref()?, m.get_name(kind, p)?.as_str()))
+        })
+    }
+
+    /// Gets the paths of all constants or macros of a kind that can be accessed through the buffer, including
+    /// import aliases (e.g. `alias::name`).
+    ///
+    /// Paths are sorted and deduplicated.
+    pub fn paths(&self, kind: CobDefKind) -> Vec<SmolStr>
+    {
+        let mut paths: Vec<SmolStr> = self
+            .new_file
+            .names(kind)
+            .into_iter()
+            .cloned()
+            .chain(self.stack.iter().flat_map(|(prefix, m)| {
+                m.names(kind)
+                    .into_iter()
+                    .map(|name| path_to_string(CONSTANT_SEPARATOR, &[prefix.as_str(), name.as_str()]))
+            }))
+            .collect();
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+
+    /// Searches backward through the stack until a match is found.
+    pub fn get(&self, path: impl AsRef<str>) -> Option<&CobConstantValue>
+    {
+        self.search(path.as_ref(), |m, p| m.constants.get(p))
+    }
+
+    /// Searches backward through the stack until a matching data macro is found.
+    pub fn get_data_macro(&self, path: impl AsRef<str>) -> Option<&CobDataMacroDef>
+    {
+        self.search(path.as_ref(), |m, p| m.data_macros.get(p))
+    }
+
+    /// Searches backward through the stack until a matching loadable macro is found.
+    pub fn get_loadable_macro(&self, path: impl AsRef<str>) -> Option<&CobLoadableMacroDef>
+    {
+        self.search(path.as_ref(), |m, p| m.loadable_macros.get(p))
+    }
+
+    /// Searches backward through the stack until a matching scene macro is found.
+    pub fn get_scene_macro(&self, path: impl AsRef<str>) -> Option<&CobSceneMacroDef>
+    {
+        self.search(path.as_ref(), |m, p| m.scene_macros.get(p))
+    }
+
     pub(crate) fn append(&mut self, alias: &CobImportAlias, to_append: &Self)
     {
         let alias = alias.as_str();
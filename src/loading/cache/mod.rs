@@ -3,6 +3,8 @@ mod commands_buffer;
 mod constants_buffer;
 mod manifest_map;
 mod plugin;
+#[cfg(feature = "hot_reload")]
+mod reload_report;
 mod scene_buffer;
 
 pub(crate) use cob_asset_cache::*;
@@ -10,4 +12,6 @@ pub(crate) use commands_buffer::*;
 pub use constants_buffer::*;
 pub(crate) use manifest_map::*;
 pub(crate) use plugin::*;
+#[cfg(feature = "hot_reload")]
+pub use reload_report::*;
 pub use scene_buffer::*;
@@ -8,6 +8,57 @@ use crate::prelude::*;
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Preprocesses a loaded asset.
+///
+/// Files that aren't in the commands buffer yet are deferred until a manifest that contains them is processed.
+fn preprocess_cobweb_asset_file(
+    asset: CobAssetFile,
+    asset_server: &AssetServer,
+    cob_files: &mut LoadedCobAssetFiles,
+    cob_cache: &mut CobAssetCache,
+    commands_buffer: &mut CommandsBuffer,
+)
+{
+    if let CobAssetFile::File { data, .. } = &asset {
+        if !commands_buffer.contains_file(&data.file) {
+            cob_files.defer_file(asset);
+            return;
+        }
+    }
+
+    match asset {
+        CobAssetFile::Ignore => (),
+        CobAssetFile::File {
+            #[cfg(feature = "editor")]
+            hash,
+            #[cfg(feature = "editor")]
+            has_errors,
+            data,
+            #[cfg(feature = "hot_reload")]
+            broken_nodes,
+        } => {
+            preprocess_cob_file(
+                asset_server,
+                cob_files,
+                cob_cache,
+                commands_buffer,
+                data,
+                #[cfg(feature = "editor")]
+                hash,
+                #[cfg(feature = "editor")]
+                has_errors,
+                #[cfg(feature = "hot_reload")]
+                broken_nodes,
+            );
+        }
+        CobAssetFile::Compiled { file, data } => {
+            cob_cache.add_compiled_file(file, data);
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 fn preprocess_cobweb_asset_files(
     asset_server: Res<AssetServer>,
     mut events: EventReader<AssetEvent<CobAssetFile>>,
@@ -15,6 +66,7 @@ fn preprocess_cobweb_asset_files(
     mut assets: ResMut<Assets<CobAssetFile>>,
     mut cob_cache: ResMut<CobAssetCache>,
     mut commands_buffer: ResMut<CommandsBuffer>,
+    #[cfg(feature = "hot_reload")] recorder: Res<ReloadRecorder>,
 )
 {
     for event in events.read() {
@@ -36,24 +88,49 @@ fn preprocess_cobweb_asset_files(
             continue;
         };
 
-        match asset {
-            CobAssetFile::Ignore => continue,
-            CobAssetFile::File {
-                #[cfg(feature = "editor")]
-                hash,
-                data,
-            } => {
-                preprocess_cob_file(
-                    &asset_server,
-                    &mut cob_files,
-                    &mut cob_cache,
-                    &mut commands_buffer,
-                    data,
-                    #[cfg(feature = "editor")]
-                    hash,
-                );
+        #[cfg(feature = "hot_reload")]
+        {
+            if let (AssetEvent::Modified { .. }, CobAssetFile::File { data, .. }) = (event, &asset) {
+                recorder.record(|report| report.files.push(data.file.clone()));
             }
         }
+
+        preprocess_cobweb_asset_file(
+            asset,
+            &asset_server,
+            &mut cob_files,
+            &mut cob_cache,
+            &mut commands_buffer,
+        );
+    }
+
+    // Retry deferred files until none of them can be placed.
+    loop {
+        let deferred = cob_files.take_deferred_files();
+        let num_deferred = deferred.len();
+        for asset in deferred {
+            preprocess_cobweb_asset_file(
+                asset,
+                &asset_server,
+                &mut cob_files,
+                &mut cob_cache,
+                &mut commands_buffer,
+            );
+        }
+        if cob_files.num_deferred_files() == num_deferred {
+            break;
+        }
+    }
+
+    // If only deferred files are pending, then they aren't in any manifest (e.g. the CobIndex is out of date).
+    let num_deferred = cob_files.num_deferred_files();
+    if num_deferred > 0 && num_deferred == cob_cache.num_pending_files() {
+        for asset in cob_files.take_deferred_files() {
+            let CobAssetFile::File { data, .. } = asset else { continue };
+            tracing::warn!("discarding COB file {:?} that isn't in any manifest; if it was preloaded with a \
+                CobIndex, then the index may be out of date", data.file.as_str());
+            cob_cache.initialize_file(&data.file);
+        }
     }
 
     // Note: we don't try to handle asset load failures here because a file load failure is assumed to be
@@ -64,7 +141,9 @@ fn preprocess_cobweb_asset_files(
 
 fn process_cobweb_asset_files(
     types: Res<AppTypeRegistry>,
+    flags: Res<CobFlags>,
     loadables: Res<LoadableRegistry>,
+    diagnostics: Res<CobDiagnostics>,
     mut cob_cache: ResMut<CobAssetCache>,
     mut c: Commands,
     mut commands_buffer: ResMut<CommandsBuffer>,
@@ -76,7 +155,9 @@ fn process_cobweb_asset_files(
 
     if cob_cache.process_cobweb_asset_files(
         &type_registry,
+        &flags,
         &loadables,
+        &diagnostics,
         &mut c,
         &mut commands_buffer,
         &mut scene_buffer,
@@ -88,6 +169,77 @@ fn process_cobweb_asset_files(
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Re-extracts all files when [`CobFlags`] change, since `#if` blocks may select different sections.
+#[cfg(feature = "hot_reload")]
+fn reprocess_on_flags_changed(
+    flags: Res<CobFlags>,
+    mut cob_cache: ResMut<CobAssetCache>,
+    mut commands_buffer: ResMut<CommandsBuffer>,
+)
+{
+    if !flags.is_changed() || flags.is_added() {
+        return;
+    }
+
+    tracing::info!("re-extracting COB files after CobFlags changed");
+    cob_cache.reprocess_all_files(&mut commands_buffer);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Warns when [`CobFlags`] change after files started loading, since files are only extracted once without
+/// `hot_reload`.
+#[cfg(not(feature = "hot_reload"))]
+fn warn_on_flags_changed(flags: Res<CobFlags>, cob_cache: Res<CobAssetCache>)
+{
+    if !flags.is_changed() || flags.is_added() || !cob_cache.has_loaded_files() {
+        return;
+    }
+
+    tracing::warn!("CobFlags changed after COB files started loading; files that were already loaded won't be \
+        re-extracted, so set flags before loading files or enable the hot_reload feature");
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Re-resolves `rem` and `em` values in loaded scene nodes when the [`RootFontSize`] changes.
+fn update_root_font_size(
+    types: Res<AppTypeRegistry>,
+    root_font_size: Res<RootFontSize>,
+    loadables: Res<LoadableRegistry>,
+    mut c: Commands,
+    mut scene_buffer: ResMut<SceneBuffer>,
+)
+{
+    if !root_font_size.is_changed() {
+        return;
+    }
+
+    let type_registry = types.read();
+    scene_buffer.set_root_font_size(root_font_size.0, &type_registry, &loadables, &mut c);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Re-resolves themed constants in loaded commands and scene nodes when the [`ActiveTheme`] changes.
+fn update_active_theme(
+    types: Res<AppTypeRegistry>,
+    active_theme: Res<ActiveTheme>,
+    loadables: Res<LoadableRegistry>,
+    mut c: Commands,
+    mut scene_buffer: ResMut<SceneBuffer>,
+)
+{
+    if !active_theme.is_changed() {
+        return;
+    }
+
+    let type_registry = types.read();
+    scene_buffer.set_active_theme(active_theme.get(), &type_registry, &loadables, &mut c);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 fn apply_pending_commands(mut c: Commands, mut buffer: ResMut<CommandsBuffer>, loaders: Res<LoadableRegistry>)
 {
     buffer.apply_pending_commands(&mut c, &loaders);
@@ -121,7 +273,9 @@ fn apply_pending_node_updates_pre(
 #[cfg(feature = "hot_reload")]
 fn apply_pending_node_updates_extract(
     types: Res<AppTypeRegistry>,
+    flags: Res<CobFlags>,
     loadables: Res<LoadableRegistry>,
+    diagnostics: Res<CobDiagnostics>,
     mut cob_cache: ResMut<CobAssetCache>,
     mut c: Commands,
     commands_buffer: Res<CommandsBuffer>,
@@ -139,7 +293,9 @@ fn apply_pending_node_updates_extract(
     let type_registry = types.read();
     cob_cache.handle_pending_scene_extraction(
         &type_registry,
+        &flags,
         &loadables,
+        &diagnostics,
         &mut c,
         &mut scene_buffer,
         &mut scene_loader,
@@ -192,6 +348,22 @@ fn cleanup_despawned_loaded_entities(world: &mut World)
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// `HasLoadables` is only removed when the entity is despawned.
+#[cfg(not(feature = "hot_reload"))]
+fn cleanup_despawned_loaded_entities(
+    mut scene_buffer: ResMut<SceneBuffer>,
+    mut removed: RemovedComponents<HasLoadables>,
+)
+{
+    let dead_entities: Vec<Entity> = removed.read().collect();
+    if dead_entities.is_empty() {
+        return;
+    }
+    scene_buffer.remove_dynamic_entities(&dead_entities);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Reactive event broadcasted when the [`CobAssetCache`] has been updated with COB asset data.
 pub struct CobCacheUpdated;
 
@@ -211,14 +383,35 @@ impl Plugin for CobAssetCachePlugin
     fn build(&self, app: &mut App)
     {
         let manifest_map = Arc::new(Mutex::new(ManifestMap::default()));
+        #[cfg(feature = "hot_reload")]
+        let recorder = ReloadRecorder::default();
         app.insert_resource(CobAssetCache::new(manifest_map.clone()))
             .register_asset_tracker::<CobAssetCache>()
-            .insert_resource(CommandsBuffer::new())
-            .insert_resource(SceneBuffer::new(manifest_map))
+            .insert_resource(CommandsBuffer::new(
+                #[cfg(feature = "hot_reload")]
+                recorder.clone(),
+            ))
+            .insert_resource(SceneBuffer::new(
+                manifest_map,
+                #[cfg(feature = "hot_reload")]
+                recorder.clone(),
+            ))
+            .init_resource::<CobFlags>()
+            .init_resource::<RootFontSize>()
+            .init_resource::<ActiveTheme>()
+            // These run outside `FileProcessingSet` so they also run after loading without `hot_reload`.
+            .add_systems(
+                First,
+                (update_root_font_size, update_active_theme)
+                    .chain()
+                    .before(FileProcessingSet),
+            )
             .add_systems(
                 First,
                 (
                     preprocess_cobweb_asset_files,
+                    #[cfg(feature = "hot_reload")]
+                    reprocess_on_flags_changed,
                     process_cobweb_asset_files.run_if(|s: Res<CobAssetCache>| s.num_preprocessed_pending() > 0),
                     #[cfg(feature = "hot_reload")]
                     cleanup_despawned_loaded_entities,
@@ -229,6 +422,8 @@ impl Plugin for CobAssetCachePlugin
                     apply_pending_node_updates_extract,
                     #[cfg(feature = "hot_reload")]
                     apply_pending_node_updates_post,
+                    #[cfg(feature = "hot_reload")]
+                    emit_reload_report,
                 )
                     .chain()
                     .in_set(FileProcessingSet),
@@ -245,6 +440,7 @@ impl Plugin for CobAssetCachePlugin
                     apply_pending_node_updates_pre,
                     apply_pending_node_updates_extract,
                     apply_pending_node_updates_post,
+                    emit_reload_report,
                 )
                     .chain()
                     .before(bevy::ui::UiSystem::Prepare)
@@ -257,11 +453,15 @@ impl Plugin for CobAssetCachePlugin
             app.configure_sets(First, FileProcessingSet.run_if(in_state(LoadState::Loading)))
                 .add_systems(OnExit(LoadState::Loading), |mut c: Commands| {
                     c.remove_resource::<CommandsBuffer>();
-                });
+                })
+                .add_systems(First, warn_on_flags_changed.before(FileProcessingSet))
+                .add_systems(Last, cleanup_despawned_loaded_entities);
         }
 
         #[cfg(feature = "hot_reload")]
-        app.add_systems(Last, cleanup_despawned_loaded_entities);
+        app.insert_resource(recorder)
+            .init_resource::<CobReloadReporting>()
+            .add_systems(Last, cleanup_despawned_loaded_entities);
     }
 }
 
@@ -0,0 +1,229 @@
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use bevy::prelude::*;
+use bevy_cobweb::prelude::*;
+
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Broadcasts the changes recorded since the last report.
+///
+/// Recording starts in [`LoadState::Done`] so the initial load isn't reported.
+pub(super) fn emit_reload_report(
+    mut c: Commands,
+    load_state: Res<State<LoadState>>,
+    reporting: Res<CobReloadReporting>,
+    recorder: Res<ReloadRecorder>,
+    commands_buffer: Res<CommandsBuffer>,
+)
+{
+    recorder.set_active(reporting.enabled && *load_state.get() == LoadState::Done);
+
+    // Wait until all reloaded files have been applied.
+    if commands_buffer.is_blocked() {
+        return;
+    }
+
+    let Some(report) = recorder.take() else { return };
+    if reporting.log {
+        tracing::info!("{}", report);
+    }
+    c.react().broadcast(report);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Resource that controls [`CobReloadReport`]s.
+///
+/// Reports are disabled by default.
+#[derive(Resource, Debug, Default, Copy, Clone)]
+pub struct CobReloadReporting
+{
+    /// If `true`, then a [`CobReloadReport`] is broadcast whenever hot-reloaded changes are applied.
+    pub enabled: bool,
+    /// If `true`, then reports are also logged at `info` level.
+    pub log: bool,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A change to a loadable in a scene node.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CobLoadableChange
+{
+    /// The loadable was added to the node.
+    Added,
+    /// The loadable's value changed.
+    Updated,
+    /// The loadable moved to a different position in the node.
+    Moved,
+    /// The loadable was removed from the node.
+    Removed,
+}
+
+/// A loadable in a scene node that changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobReloadedLoadable
+{
+    pub node: SceneRef,
+    pub type_path: String,
+    pub change: CobLoadableChange,
+}
+
+/// A loadable that was reverted on an entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobRevertedLoadable
+{
+    pub entity: Entity,
+    pub type_path: String,
+}
+
+/// A command that was re-applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobReappliedCommand
+{
+    pub file: CobFile,
+    pub type_path: String,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Reactive event broadcasted with the changes made by a hot reload.
+///
+/// Only sent if enabled with [`CobReloadReporting`]. Changes are collected until every reloaded file has been
+/// applied, so one report can cover several files.
+///
+/// Loadables are compared with `reflect_partial_eq`, so a report is a good way to find out why a loadable keeps
+/// being reverted (for example if it contains `NaN`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CobReloadReport
+{
+    /// Files that were reloaded.
+    pub files: Vec<CobFile>,
+    /// Commands that were re-applied, in the order they were applied.
+    pub commands: Vec<CobReappliedCommand>,
+    /// Scene nodes added to existing scenes.
+    pub nodes_added: Vec<SceneRef>,
+    /// Scene nodes removed from existing scenes.
+    pub nodes_removed: Vec<SceneRef>,
+    /// Scene nodes that moved to a different position among their siblings.
+    pub nodes_moved: Vec<SceneRef>,
+    /// Loadables in scene nodes that were added, updated, moved, or removed.
+    pub loadables: Vec<CobReloadedLoadable>,
+    /// Loadables reverted on entities before the entities were updated.
+    pub reverted: Vec<CobRevertedLoadable>,
+    /// Entities that were spawned, despawned, or re-loaded.
+    pub entities: Vec<Entity>,
+}
+
+impl CobReloadReport
+{
+    /// Returns `true` if nothing changed.
+    pub fn is_empty(&self) -> bool
+    {
+        self.files.is_empty()
+            && self.commands.is_empty()
+            && self.nodes_added.is_empty()
+            && self.nodes_removed.is_empty()
+            && self.nodes_moved.is_empty()
+            && self.loadables.is_empty()
+            && self.reverted.is_empty()
+            && self.entities.is_empty()
+    }
+
+    pub(crate) fn add_entity(&mut self, entity: Entity)
+    {
+        if !self.entities.contains(&entity) {
+            self.entities.push(entity);
+        }
+    }
+}
+
+impl Display for CobReloadReport
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result
+    {
+        write!(f, "COB reload report")?;
+        if !self.files.is_empty() {
+            write!(f, "\nfiles:")?;
+            for file in self.files.iter() {
+                write!(f, " {}", file.as_str())?;
+            }
+        }
+        for command in self.commands.iter() {
+            write!(f, "\ncommand re-applied: {} in {}", command.type_path, command.file.as_str())?;
+        }
+        for node in self.nodes_added.iter() {
+            write!(f, "\nnode added: {:?}", node)?;
+        }
+        for node in self.nodes_removed.iter() {
+            write!(f, "\nnode removed: {:?}", node)?;
+        }
+        for node in self.nodes_moved.iter() {
+            write!(f, "\nnode moved: {:?}", node)?;
+        }
+        for loadable in self.loadables.iter() {
+            write!(f, "\nloadable {:?}: {} at {:?}", loadable.change, loadable.type_path, loadable.node)?;
+        }
+        for reverted in self.reverted.iter() {
+            write!(f, "\nloadable reverted: {} on {:?}", reverted.type_path, reverted.entity)?;
+        }
+        if !self.entities.is_empty() {
+            write!(f, "\nentities: {:?}", self.entities)?;
+        }
+        Ok(())
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Collects a [`CobReloadReport`].
+///
+/// Shared between the buffers that apply hot-reloaded changes. Nothing is recorded while inactive.
+#[derive(Resource, Debug, Default, Clone)]
+pub(crate) struct ReloadRecorder
+{
+    report: Arc<Mutex<Option<CobReloadReport>>>,
+}
+
+impl ReloadRecorder
+{
+    fn lock(&self) -> MutexGuard<Option<CobReloadReport>>
+    {
+        self.report
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn set_active(&self, active: bool)
+    {
+        let mut report = self.lock();
+        match (active, report.is_some()) {
+            (true, false) => *report = Some(CobReloadReport::default()),
+            (false, true) => *report = None,
+            _ => (),
+        }
+    }
+
+    /// Takes the current report if it isn't empty.
+    fn take(&self) -> Option<CobReloadReport>
+    {
+        let mut report = self.lock();
+        if report.as_ref()?.is_empty() {
+            return None;
+        }
+        report.replace(CobReloadReport::default())
+    }
+
+    /// Records changes if the recorder is active.
+    pub(crate) fn record(&self, recorder: impl FnOnce(&mut CobReloadReport))
+    {
+        if let Some(report) = self.lock().as_mut() {
+            (recorder)(report);
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -7,10 +7,14 @@ use std::sync::{Arc, Mutex, MutexGuard};
 
 use bevy::ecs::world::Command;
 use bevy::prelude::*;
+use bevy::reflect::serde::TypedReflectDeserializer;
+use bevy::reflect::TypeRegistry;
 #[cfg(feature = "hot_reload")]
 use bevy_cobweb::prelude::*;
 use smallvec::SmallVec;
+use smol_str::SmolStr;
 
+use super::*;
 use crate::prelude::*;
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -101,6 +105,30 @@ fn insert_node_loadable_entry(
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Gets the sizes that `rem` and `em` values resolve to in a scene node.
+///
+/// `em` values are relative to the node's `TextLineSize`, which may itself be written in `rem` or use themed
+/// constants.
+fn font_sizes(root_font_size: f32, theme: Option<&str>, text_size: Option<&CobLoadable>) -> CobFontSizes
+{
+    let root_sizes = CobFontSizes { rem: root_font_size, em: root_font_size };
+    let em = text_size.and_then(|text_size| {
+        let mut text_size = text_size.clone();
+        text_size.resolve_theme(theme).ok()?;
+        text_size.resolve_font_units(&root_sizes).ok()?;
+        let CobLoadableVariant::Tuple(tuple) = &text_size.variant else { return None };
+        match tuple.entries.first()? {
+            CobValue::Number(number) => number.number.as_f32_lossy(),
+            CobValue::Builtin(CobBuiltin::Val { val: Val::Px(px), .. }) => Some(*px),
+            _ => None,
+        }
+    });
+
+    CobFontSizes { rem: root_font_size, em: em.unwrap_or(root_font_size) }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 #[derive(PartialEq)]
 enum InsertNodeResult
 {
@@ -211,6 +239,39 @@ struct SubscriptionRef
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// A loadable with values that depend on the [`RootFontSize`] or [`ActiveTheme`].
+#[derive(Debug)]
+struct DynamicLoadable
+{
+    index: usize,
+    type_id: TypeId,
+    full_type_name: &'static str,
+    /// The loadable before themed constants and `rem` and `em` values were resolved.
+    loadable: CobLoadable,
+}
+
+/// Tracks the loadables with themed constants or `rem` or `em` values in a scene node.
+#[derive(Debug)]
+struct DynamicNode
+{
+    /// The node's `TextLineSize`, which `em` values are relative to.
+    text_size: Option<CobLoadable>,
+    loadables: Vec<DynamicLoadable>,
+}
+
+/// A command with themed constants.
+#[derive(Debug)]
+struct ThemedCommand
+{
+    file: CobFile,
+    type_id: TypeId,
+    full_type_name: &'static str,
+    /// The command before themed constants were resolved.
+    loadable: CobLoadable,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 #[derive(Resource, Debug, Default)]
 pub struct SceneBuffer
 {
@@ -233,13 +294,42 @@ pub struct SceneBuffer
     /// Records loadables that need to be reverted/updated.
     #[cfg(feature = "hot_reload")]
     refresh_ctx: RefreshCtx,
+    /// Records changes for [`CobReloadReport`]s.
+    #[cfg(feature = "hot_reload")]
+    recorder: ReloadRecorder,
+
+    /// The current [`RootFontSize`].
+    root_font_size: f32,
+    /// The current [`ActiveTheme`].
+    active_theme: Option<SmolStr>,
+    /// Tracks scene nodes with themed constants or `rem` or `em` values so they can be updated when the
+    /// [`RootFontSize`] or [`ActiveTheme`] changes.
+    dynamic_nodes: HashMap<SceneRef, DynamicNode>,
+    /// Tracks entities loaded from scene nodes in `dynamic_nodes`.
+    /// - With the `hot_reload` feature, `subscriptions` is used instead.
+    #[cfg(not(feature = "hot_reload"))]
+    dynamic_subscriptions: HashMap<SceneRef, SmallVec<[SubscriptionRef; 1]>>,
+    /// Tracks commands with themed constants so they can be re-applied when the [`ActiveTheme`] changes.
+    ///
+    /// These are stored here because the [`CommandsBuffer`] is removed after loading if the `hot_reload` feature
+    /// is not enabled.
+    themed_commands: Vec<ThemedCommand>,
 }
 
 impl SceneBuffer
 {
-    pub(super) fn new(manifest_map: Arc<Mutex<ManifestMap>>) -> Self
+    pub(super) fn new(
+        manifest_map: Arc<Mutex<ManifestMap>>,
+        #[cfg(feature = "hot_reload")] recorder: ReloadRecorder,
+    ) -> Self
     {
-        Self { manifest_map, ..default() }
+        Self {
+            manifest_map,
+            #[cfg(feature = "hot_reload")]
+            recorder,
+            root_font_size: RootFontSize::default().0,
+            ..default()
+        }
     }
 
     fn manifest_map(&mut self) -> MutexGuard<ManifestMap>
@@ -252,10 +342,68 @@ impl SceneBuffer
     /// We need to prepare scene nodes because they may be empty.
     pub(crate) fn prepare_scene_node(&mut self, scene_ref: SceneRef)
     {
+        // Loadables with dynamic values are tracked again when the node's loadables are inserted.
+        self.dynamic_nodes.remove(&scene_ref);
         self.loadables.entry(scene_ref).or_default();
     }
 
+    /// Gets the current [`ActiveTheme`].
+    pub(crate) fn active_theme(&self) -> Option<&str>
+    {
+        self.active_theme.as_deref()
+    }
+
+    /// Gets the sizes that `rem` and `em` values resolve to in a scene node with the given `TextLineSize`.
+    pub(crate) fn font_sizes(&self, text_size: Option<&CobLoadable>) -> CobFontSizes
+    {
+        font_sizes(self.root_font_size, self.active_theme(), text_size)
+    }
+
+    /// Tracks a loadable with themed constants or `rem` or `em` values so it can be updated when the
+    /// [`RootFontSize`] or [`ActiveTheme`] changes.
+    ///
+    /// The loadable should not have its themed constants and `rem` and `em` values resolved yet.
+    pub(crate) fn track_dynamic_loadable(
+        &mut self,
+        scene_ref: &SceneRef,
+        text_size: Option<&CobLoadable>,
+        index: usize,
+        type_id: TypeId,
+        full_type_name: &'static str,
+        loadable: CobLoadable,
+    )
+    {
+        self.dynamic_nodes
+            .entry(scene_ref.clone())
+            .or_insert_with(|| DynamicNode { text_size: text_size.cloned(), loadables: vec![] })
+            .loadables
+            .push(DynamicLoadable { index, type_id, full_type_name, loadable });
+    }
+
+    /// Removes tracked themed commands from a file that is about to have its commands extracted.
+    pub(crate) fn prepare_file_commands(&mut self, file: &CobFile)
+    {
+        self.themed_commands.retain(|command| command.file != *file);
+    }
+
+    /// Tracks a command with themed constants so it can be re-applied when the [`ActiveTheme`] changes.
+    ///
+    /// The command should not have its themed constants resolved yet.
+    pub(crate) fn track_themed_command(
+        &mut self,
+        file: &CobFile,
+        type_id: TypeId,
+        full_type_name: &'static str,
+        loadable: CobLoadable,
+    )
+    {
+        self.themed_commands
+            .push(ThemedCommand { file: file.clone(), type_id, full_type_name, loadable });
+    }
+
     /// Inserts a loadable at the specified path and index if its value will change.
+    ///
+    /// Returns `true` if the loadable was inserted.
     pub(crate) fn insert_loadable(
         &mut self,
         scene_ref: &SceneRef,
@@ -263,7 +411,7 @@ impl SceneBuffer
         loadable: ReflectedLoadable,
         type_id: TypeId,
         full_type_name: &str,
-    )
+    ) -> bool
     {
         let res = insert_node_loadable_entry(
             &mut self.loadables,
@@ -274,25 +422,169 @@ impl SceneBuffer
             full_type_name,
         );
         if res == InsertNodeResult::NoChange {
-            return;
+            return false;
         }
 
         // Identify entites that should update.
         #[cfg(feature = "hot_reload")]
         {
-            let Some(subscriptions) = self.subscriptions.get(scene_ref) else { return };
-            if subscriptions.is_empty() {
-                return;
-            }
+            self.recorder.record(|report| {
+                let change = match res {
+                    InsertNodeResult::Changed => CobLoadableChange::Updated,
+                    InsertNodeResult::Rearranged => CobLoadableChange::Moved,
+                    _ => CobLoadableChange::Added,
+                };
+                report.loadables.push(CobReloadedLoadable {
+                    node: scene_ref.clone(),
+                    type_path: full_type_name.into(),
+                    change,
+                });
+            });
+
+            let Some(subscriptions) = self.subscriptions.get(scene_ref) else { return true };
 
             for subscription in subscriptions {
                 if res == InsertNodeResult::Changed {
                     self.refresh_ctx.add_revert(*subscription, type_id);
+                    self.recorder.record(|report| {
+                        report.reverted.push(CobRevertedLoadable {
+                            entity: subscription.entity,
+                            type_path: full_type_name.into(),
+                        })
+                    });
                 }
                 self.refresh_ctx
                     .add_update(*subscription, scene_ref.clone());
             }
         }
+
+        true
+    }
+
+    /// Re-resolves `rem` and `em` values if the root font size changed.
+    ///
+    /// Entities that loaded the affected scene nodes are updated. With the `hot_reload` feature this happens when
+    /// pending node updates are applied.
+    pub(super) fn set_root_font_size(
+        &mut self,
+        root_font_size: f32,
+        type_registry: &TypeRegistry,
+        callbacks: &LoadableRegistry,
+        c: &mut Commands,
+    )
+    {
+        if self.root_font_size == root_font_size {
+            return;
+        }
+        self.root_font_size = root_font_size;
+        self.refresh_dynamic_nodes("root font size", type_registry, callbacks, c);
+    }
+
+    /// Re-resolves themed constants if the active theme changed.
+    ///
+    /// Commands with themed constants are re-applied, then entities that loaded the affected scene nodes are
+    /// updated. With the `hot_reload` feature entities are updated when pending node updates are applied.
+    pub(super) fn set_active_theme(
+        &mut self,
+        theme: Option<&str>,
+        type_registry: &TypeRegistry,
+        callbacks: &LoadableRegistry,
+        c: &mut Commands,
+    )
+    {
+        if self.active_theme() == theme {
+            return;
+        }
+        self.active_theme = theme.map(SmolStr::from);
+        self.refresh_themed_commands(type_registry, callbacks, c);
+        self.refresh_dynamic_nodes("active theme", type_registry, callbacks, c);
+    }
+
+    fn refresh_themed_commands(&self, type_registry: &TypeRegistry, callbacks: &LoadableRegistry, c: &mut Commands)
+    {
+        for command in self.themed_commands.iter() {
+            let mut loadable = command.loadable.clone();
+            if let Err(err) = loadable.resolve_theme(self.active_theme()) {
+                tracing::warn!("failed updating command {:?} in {:?} after active theme changed; {}",
+                    command.full_type_name, command.file, err.as_str());
+                continue;
+            }
+            let Some(registration) = type_registry.get(command.type_id) else { continue };
+            let deserializer = TypedReflectDeserializer::new(registration, type_registry);
+            let value = get_loadable_value(deserializer, &loadable);
+
+            let Some(callback) = callbacks.get_for_command(command.type_id) else { continue };
+            c.queue(CommandLoadCommand {
+                callback,
+                scene_ref: SceneRef {
+                    file: SceneFile::File(command.file.clone()),
+                    path: ScenePath::new("#commands"),
+                },
+                loadable: value,
+            });
+        }
+    }
+
+    /// Re-resolves the loadables of scene nodes with themed constants or `rem` or `em` values.
+    ///
+    /// `changed` is used in warnings.
+    fn refresh_dynamic_nodes(
+        &mut self,
+        changed: &str,
+        type_registry: &TypeRegistry,
+        _callbacks: &LoadableRegistry,
+        _c: &mut Commands,
+    )
+    {
+        let dynamic_nodes = std::mem::take(&mut self.dynamic_nodes);
+        for (scene_ref, node) in dynamic_nodes.iter() {
+            let sizes = self.font_sizes(node.text_size.as_ref());
+
+            for entry in node.loadables.iter() {
+                let mut loadable = entry.loadable.clone();
+                let res = loadable
+                    .resolve_theme(self.active_theme())
+                    .and_then(|_| loadable.resolve_font_units(&sizes));
+                if let Err(err) = res {
+                    tracing::warn!("failed updating loadable {:?} at {:?} after {} changed; {}",
+                        entry.full_type_name, scene_ref, changed, err.as_str());
+                    continue;
+                }
+                let Some(registration) = type_registry.get(entry.type_id) else { continue };
+                let deserializer = TypedReflectDeserializer::new(registration, type_registry);
+                let value = get_loadable_value(deserializer, &loadable);
+
+                if !self.insert_loadable(
+                    scene_ref,
+                    Some(entry.index),
+                    value.clone(),
+                    entry.type_id,
+                    entry.full_type_name,
+                ) {
+                    continue;
+                }
+
+                // Without hot reloading, entities need to be updated here.
+                #[cfg(not(feature = "hot_reload"))]
+                {
+                    let Some(callback) = _callbacks.get_for_node(entry.type_id) else { continue };
+                    let Some(subscriptions) = self.dynamic_subscriptions.get_mut(scene_ref) else { continue };
+
+                    // Clean up despawned entities.
+                    subscriptions.retain(|s| _c.get_entity(s.entity).is_some());
+
+                    for subscription in subscriptions.iter() {
+                        _c.queue(NodeLoadCommand {
+                            callback,
+                            entity: subscription.entity,
+                            scene_ref: scene_ref.clone(),
+                            loadable: value.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        self.dynamic_nodes = dynamic_nodes;
     }
 
     /// Cleans up any removed loadables if the loadable set became smaller after a hot reload.
@@ -313,8 +605,22 @@ impl SceneBuffer
             .into_iter()
             .flat_map(|l| l.drain(count..))
         {
+            let type_path = removed.loadable.type_path();
+            self.recorder.record(|report| {
+                report.loadables.push(CobReloadedLoadable {
+                    node: scene_ref.clone(),
+                    type_path: type_path.into(),
+                    change: CobLoadableChange::Removed,
+                });
+            });
+
             for subscription in subscriptions {
                 self.refresh_ctx.add_revert(*subscription, removed.type_id);
+                self.recorder.record(|report| {
+                    report
+                        .reverted
+                        .push(CobRevertedLoadable { entity: subscription.entity, type_path: type_path.into() })
+                });
                 self.refresh_ctx
                     .add_update(*subscription, scene_ref.clone());
             }
@@ -391,6 +697,15 @@ impl SceneBuffer
 
         // Add to subscriptions.
         let subscription = SubscriptionRef { entity, initializer };
+        #[cfg(not(feature = "hot_reload"))]
+        {
+            if self.dynamic_nodes.contains_key(&scene_ref) {
+                self.dynamic_subscriptions
+                    .entry(scene_ref.clone())
+                    .or_default()
+                    .push(subscription);
+            }
+        }
         #[cfg(feature = "hot_reload")]
         {
             self.subscriptions
@@ -444,6 +759,15 @@ impl SceneBuffer
         self.refresh_ctx.add_update(subscription, scene_ref.clone());
     }
 
+    /// Gets the scene node an entity is subscribed to.
+    #[cfg(feature = "hot_reload")]
+    pub fn get_scene_ref(&self, entity: Entity) -> Option<&SceneRef>
+    {
+        self.subscriptions_rev
+            .get(&entity)
+            .map(|(scene_ref, _)| scene_ref)
+    }
+
     /// Requests that the scene node an entity is subscribed to be reloaded on that entity.
     #[cfg(feature = "hot_reload")]
     pub fn request_reload(&mut self, entity: Entity)
@@ -471,10 +795,21 @@ impl SceneBuffer
         // Reload entities.
         let needs_updates = self.refresh_ctx.updates().collect::<Vec<_>>();
         for (entity, initializer, scene_ref) in needs_updates {
+            self.recorder.record(|report| report.add_entity(entity));
             self.load_entity(SubscriptionRef { entity, initializer }, scene_ref, callbacks, c);
         }
     }
 
+    /// Cleans up despawned entities that loaded scene nodes with themed constants or `rem` or `em` values.
+    #[cfg(not(feature = "hot_reload"))]
+    pub(super) fn remove_dynamic_entities(&mut self, dead_entities: &[Entity])
+    {
+        self.dynamic_subscriptions.retain(|_, subscribed| {
+            subscribed.retain(|s| !dead_entities.contains(&s.entity));
+            !subscribed.is_empty()
+        });
+    }
+
     /// Cleans up despawned entities.
     #[cfg(feature = "hot_reload")]
     pub(super) fn remove_entity(&mut self, scene_loader: &mut SceneLoader, dead_entity: Entity)
@@ -1,3 +1,5 @@
+use smol_str::SmolStr;
+
 use crate::prelude::*;
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -35,14 +37,235 @@ fn get_scene_loadable_from_layer<'a, 'b>(
 
 //-------------------------------------------------------------------------------------------------------------------
 
+fn collect_sections<'a>(sections: &'a [CobSection], flags: Option<&CobFlags>, collected: &mut Vec<&'a CobSection>)
+{
+    for section in sections.iter() {
+        let CobSection::Conditional(conditional) = section else {
+            collected.push(section);
+            continue;
+        };
+        match flags {
+            Some(flags) => collect_sections(conditional.active_sections(flags), Some(flags), collected),
+            None => {
+                collect_sections(&conditional.sections, None, collected);
+                if let Some(branch) = &conditional.else_branch {
+                    collect_sections(&branch.sections, None, collected);
+                }
+            }
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn collect_active_sections_mut<'a>(
+    sections: &'a mut [CobSection],
+    flags: &CobFlags,
+    collected: &mut Vec<&'a mut CobSection>,
+)
+{
+    for section in sections.iter_mut() {
+        match section {
+            CobSection::Conditional(conditional) => {
+                collect_active_sections_mut(conditional.active_sections_mut(flags), flags, collected);
+            }
+            _ => collected.push(section),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Makes fill that puts an item on a new line with `indent` spaces.
+fn line_fill(indent: usize) -> CobFill
+{
+    CobFill::new(format!("\n{}", " ".repeat(indent)))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Gets the indent of items in a scene node.
+///
+/// Falls back to four spaces past the node's own indent if the node has no loadables or child nodes.
+fn scene_layer_content_indent(layer: &CobSceneLayer) -> usize
+{
+    layer
+        .entries
+        .iter()
+        .find_map(|entry| match entry {
+            CobSceneLayerEntry::Loadable(loadable) => loadable.fill.ends_newline_then_num_spaces(),
+            CobSceneLayerEntry::Layer(child) => child.name_fill.ends_newline_then_num_spaces(),
+            _ => None,
+        })
+        .unwrap_or_else(|| layer.name_fill.ends_newline_then_num_spaces().unwrap_or(0) + 4)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn scene_entry_loadable(entry: &CobSceneLayerEntry) -> Option<&CobLoadable>
+{
+    match entry {
+        CobSceneLayerEntry::Loadable(loadable) => Some(loadable),
+        _ => None,
+    }
+}
+
+fn command_entry_loadable(entry: &CobCommandEntry) -> Option<&CobLoadable>
+{
+    match entry {
+        CobCommandEntry::Loadable(loadable) => Some(loadable),
+        _ => None,
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Finds the position of the loadable named `name`.
+// TODO: This allocates a string to do loadable name checks.
+fn find_loadable<T>(entries: &[T], name: &str, get: impl Fn(&T) -> Option<&CobLoadable>) -> Option<usize>
+{
+    let mut id_scratch = String::default();
+    entries.iter().position(|entry| {
+        let Some(loadable) = (get)(entry) else { return false };
+        id_scratch = loadable
+            .id
+            .to_canonical(Some(std::mem::take(&mut id_scratch)));
+        id_scratch == name
+    })
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Finds where to insert an entry so it lands before the `index`-th entry that matches `is_kind`.
+///
+/// If `index` is `None` or out of range, the entry goes after the last matching entry, or at `fallback` if no
+/// entries match.
+fn insert_position<T>(entries: &[T], index: Option<usize>, is_kind: impl Fn(&T) -> bool, fallback: usize)
+    -> usize
+{
+    let mut matching = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| (is_kind)(*entry))
+        .map(|(pos, _)| pos);
+    if let Some(pos) = index.and_then(|index| matching.clone().nth(index)) {
+        return pos;
+    }
+    matching.last().map(|pos| pos + 1).unwrap_or(fallback)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Moves the entry at `from` so it lands before the `index`-th other entry that matches `is_kind`.
+fn move_entry<T>(entries: &mut Vec<T>, from: usize, index: usize, is_kind: impl Fn(&T) -> bool)
+{
+    let entry = entries.remove(from);
+    let pos = insert_position(entries, Some(index), is_kind, entries.len());
+    entries.insert(pos, entry);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn layer_entry_name(entry: &CobSceneLayerEntry) -> Option<&str>
+{
+    match entry {
+        CobSceneLayerEntry::Layer(layer) if !layer.name.is_empty() => Some(layer.name.as_str()),
+        _ => None,
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Finds where to insert a scene node among `siblings` so it is next to the same nodes as in a previous version of
+/// the file.
+///
+/// `before` and `after` are the names of the node's siblings in the previous version.
+fn find_restore_position<'a>(
+    siblings: impl Iterator<Item = Option<&'a str>> + Clone,
+    before: &[&str],
+    after: &[&str],
+) -> Option<usize>
+{
+    let position = |name: &str| siblings.clone().position(|sibling| sibling == Some(name));
+    before
+        .iter()
+        .rev()
+        .find_map(|name| position(*name).map(|pos| pos + 1))
+        .or_else(|| after.iter().find_map(|name| position(*name)))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Gets the names of the named siblings before and after `name`.
+fn sibling_names<'a>(siblings: impl Iterator<Item = Option<&'a str>>, name: &str) -> (Vec<&'a str>, Vec<&'a str>)
+{
+    let names: Vec<&str> = siblings.flatten().collect();
+    let idx = names.iter().position(|n| *n == name).unwrap_or(names.len());
+    (names[..idx].to_vec(), names[(idx + 1).min(names.len())..].to_vec())
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Restores a scene node's own content from `prev` while keeping the node's current named children.
+///
+/// Anonymous children are restored with their parent, since they can't be addressed by path.
+fn restore_layer_content(layer: &mut CobSceneLayer, prev: &CobSceneLayer)
+{
+    let mut children: Vec<Option<CobSceneLayer>> = std::mem::take(&mut layer.entries)
+        .into_iter()
+        .filter_map(|entry| match entry {
+            CobSceneLayerEntry::Layer(child) if !child.name.is_empty() => Some(Some(child)),
+            _ => None,
+        })
+        .collect();
+    let names: Vec<SmolStr> = children
+        .iter()
+        .flatten()
+        .map(|child| child.name.0.clone())
+        .collect();
+
+    // Restore content and keep named children that still exist.
+    layer.base = prev.base.clone();
+    for entry in prev.entries.iter() {
+        let Some(name) = layer_entry_name(entry) else {
+            layer.entries.push(entry.clone());
+            continue;
+        };
+        let Some(child) = children.iter_mut().find(|child| {
+            child
+                .as_ref()
+                .is_some_and(|child| child.name.as_str() == name)
+        }) else {
+            continue;
+        };
+        layer
+            .entries
+            .push(CobSceneLayerEntry::Layer(child.take().unwrap()));
+    }
+
+    // Insert children that were added since `prev`.
+    for (idx, child) in children.into_iter().enumerate() {
+        let Some(child) = child else { continue };
+        let before: Vec<&str> = names[..idx].iter().map(|n| n.as_str()).collect();
+        let after: Vec<&str> = names[(idx + 1)..].iter().map(|n| n.as_str()).collect();
+        let pos = find_restore_position(layer.entries.iter().map(layer_entry_name), &before, &after)
+            .unwrap_or(layer.entries.len());
+        layer.entries.insert(pos, CobSceneLayerEntry::Layer(child));
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum CobSection
 {
     Manifest(CobManifest),
     Import(CobImport),
     Defs(CobDefs),
+    Theme(CobTheme),
     Commands(CobCommands),
     Scenes(CobScenes),
+    Conditional(CobConditional),
 }
 
 impl CobSection
@@ -53,8 +276,10 @@ impl CobSection
             Self::Manifest(section) => section.write_to(first_section, writer),
             Self::Import(section) => section.write_to(first_section, writer),
             Self::Defs(section) => section.write_to(first_section, writer),
+            Self::Theme(section) => section.write_to(first_section, writer),
             Self::Commands(section) => section.write_to(first_section, writer),
             Self::Scenes(section) => section.write_to(first_section, writer),
+            Self::Conditional(section) => section.write_to(first_section, writer),
         }
     }
 
@@ -73,6 +298,10 @@ impl CobSection
             (Some(section), fill, remaining) => return Ok((Some(Self::Defs(section)), fill, remaining)),
             (None, fill, _) => fill,
         };
+        let fill = match rc(content, move |c| CobTheme::try_parse(fill, c))? {
+            (Some(section), fill, remaining) => return Ok((Some(Self::Theme(section)), fill, remaining)),
+            (None, fill, _) => fill,
+        };
         let fill = match rc(content, move |c| CobCommands::try_parse(fill, c))? {
             (Some(section), fill, remaining) => return Ok((Some(Self::Commands(section)), fill, remaining)),
             (None, fill, _) => fill,
@@ -81,6 +310,10 @@ impl CobSection
             (Some(section), fill, remaining) => return Ok((Some(Self::Scenes(section)), fill, remaining)),
             (None, fill, _) => fill,
         };
+        let fill = match rc(content, move |c| CobConditional::try_parse(fill, c))? {
+            (Some(section), fill, remaining) => return Ok((Some(Self::Conditional(section)), fill, remaining)),
+            (None, fill, _) => fill,
+        };
 
         Ok((None, fill, content))
     }
@@ -88,6 +321,32 @@ impl CobSection
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Result of [`Cob::parse_recoverable`].
+#[derive(Debug, Clone)]
+pub struct CobRecovery
+{
+    /// The parts of the file that were parsed successfully.
+    pub cob: Cob,
+    /// Errors encountered while parsing, in the order they were found.
+    pub diagnostics: Vec<CobDiagnostic>,
+    /// Scene nodes that contained errors.
+    ///
+    /// These nodes are included in [`Self::cob`] without their invalid items, or are missing if the start of the
+    /// node was invalid. Anonymous nodes are reported via their nearest named ancestor.
+    pub broken_nodes: Vec<ScenePath>,
+}
+
+impl CobRecovery
+{
+    /// Returns `true` if no errors were found.
+    pub fn is_ok(&self) -> bool
+    {
+        self.diagnostics.is_empty()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct Cob
 {
@@ -116,12 +375,35 @@ impl Cob
         Ok(())
     }
 
+    /// Parses a COB file.
+    ///
+    /// Parsing stops at the first error. Use [`CobDiagnostic::from_span_error`] to get a readable description of
+    /// the error.
     pub fn parse(span: Span) -> Result<Self, SpanError>
+    {
+        clear_diagnostic();
+        Self::parse_inner(span)
+    }
+
+    /// Parses a COB file, recovering from errors where possible.
+    ///
+    /// After an error the parser skips to the next section, scene node, or loadable boundary, so all errors in the
+    /// file are collected and the valid parts of the file are still returned. Fails only if the file name is
+    /// invalid.
+    pub fn parse_recoverable(span: Span) -> Result<CobRecovery, SpanError>
+    {
+        clear_diagnostic();
+        start_recovery();
+        let result = Self::parse_inner(span);
+        let (diagnostics, broken_nodes) = end_recovery();
+
+        Ok(CobRecovery { cob: result?, diagnostics, broken_nodes })
+    }
+
+    fn parse_inner(span: Span) -> Result<Self, SpanError>
     {
         let Some(file) = CobFile::try_new(span.extra.file) else {
-            tracing::warn!("failed parsing COB file at {}; file name doesn't end with '.cob'",
-                get_location(span).as_str());
-            return Err(span_verify_error(span));
+            return Err(span_diagnostic(span, "expected file name ending in '.cob'"));
         };
 
         debug_assert_eq!(get_local_recursion_count(), 0);
@@ -130,20 +412,27 @@ impl Cob
         let (mut fill, mut remaining) = CobFill::parse(span);
 
         let end_fill = loop {
-            match rc(remaining, move |rm| CobSection::try_parse(fill, rm))? {
-                (Some(section), next_fill, after_section) => {
+            match rc(remaining, move |rm| CobSection::try_parse(fill, rm)) {
+                Ok((Some(section), next_fill, after_section)) => {
                     sections.push(section);
                     fill = next_fill;
                     remaining = after_section;
                 }
-                (None, end_fill, end_of_file) => {
+                Ok((None, end_fill, end_of_file)) => {
                     if end_of_file.len() != 0 {
-                        tracing::warn!("incomplete COB file parsing, error at {}", get_location(end_of_file).as_str());
-                        return Err(span_verify_error(end_of_file));
+                        let err = span_diagnostic(
+                            end_of_file,
+                            "expected a section (#manifest, #import, #defs, #theme, #commands, #scenes, or #if)",
+                        );
+                        (fill, remaining) = try_recover_section(err, end_of_file)?;
+                        continue;
                     }
 
                     break end_fill;
                 }
+                Err(err) => {
+                    (fill, remaining) = try_recover_section(err, remaining)?;
+                }
             }
         };
 
@@ -152,6 +441,149 @@ impl Cob
         Ok(Self { file, sections, end_fill })
     }
 
+    /// Gets the sections that are active for `flags`.
+    ///
+    /// Sections in `#if` blocks are included if their branch is selected by `flags`. The blocks themselves are not
+    /// included.
+    pub fn active_sections(&self, flags: &CobFlags) -> Vec<&CobSection>
+    {
+        let mut collected = vec![];
+        collect_sections(&self.sections, Some(flags), &mut collected);
+        collected
+    }
+
+    /// Gets the sections that are active for `flags`.
+    ///
+    /// See [`Self::active_sections`].
+    pub fn active_sections_mut(&mut self, flags: &CobFlags) -> Vec<&mut CobSection>
+    {
+        let mut collected = vec![];
+        collect_active_sections_mut(&mut self.sections, flags, &mut collected);
+        collected
+    }
+
+    /// Gets all sections, including the sections in every branch of `#if` blocks. The blocks themselves are not
+    /// included.
+    pub fn all_sections(&self) -> Vec<&CobSection>
+    {
+        let mut collected = vec![];
+        collect_sections(&self.sections, None, &mut collected);
+        collected
+    }
+
+    /// Gets the scene node at `path`.
+    pub fn get_scene_layer(&self, path: &ScenePath) -> Option<&CobSceneLayer>
+    {
+        let mut path_iter = path.iter();
+        let root_name = path_iter.next()?;
+
+        let mut layer = self.sections.iter().find_map(|s| {
+            let CobSection::Scenes(scenes) = s else { return None };
+            scenes.scenes.iter().find(|s| s.name.as_str() == root_name)
+        })?;
+
+        for name in path_iter {
+            layer = layer.entries.iter().find_map(|e| match e {
+                CobSceneLayerEntry::Layer(next) if next.name.as_str() == name => Some(next),
+                _ => None,
+            })?;
+        }
+
+        Some(layer)
+    }
+
+    /// Gets the scene node at `path`.
+    pub fn get_scene_layer_mut(&mut self, path: &ScenePath) -> Option<&mut CobSceneLayer>
+    {
+        let mut path_iter = path.iter();
+        let root_name = path_iter.next()?;
+
+        let mut layer = self.sections.iter_mut().find_map(|s| {
+            let CobSection::Scenes(scenes) = s else { return None };
+            scenes
+                .scenes
+                .iter_mut()
+                .find(|s| s.name.as_str() == root_name)
+        })?;
+
+        for name in path_iter {
+            layer = layer.entries.iter_mut().find_map(|e| match e {
+                CobSceneLayerEntry::Layer(next) if next.name.as_str() == name => Some(next),
+                _ => None,
+            })?;
+        }
+
+        Some(layer)
+    }
+
+    /// Restores scene nodes in this file from the same nodes in `other`.
+    ///
+    /// Used to keep the last good version of scene nodes that failed to parse. A node's loadables and anonymous
+    /// children are restored, and its named children are kept unless they are also in `nodes`. Nodes missing from
+    /// this file are copied from `other` next to the same siblings. Nodes missing from `other` are ignored.
+    pub fn restore_scene_nodes(&mut self, other: &Cob, nodes: &[ScenePath])
+    {
+        for path in nodes {
+            let Some(prev) = other.get_scene_layer(path) else { continue };
+            match self.get_scene_layer_mut(path) {
+                Some(layer) => restore_layer_content(layer, prev),
+                None => self.insert_scene_layer(other, path, prev),
+            }
+        }
+    }
+
+    /// Inserts a copy of a scene node from `other`, next to the siblings it has in `other`.
+    ///
+    /// Does nothing if the node's parent is missing from this file.
+    fn insert_scene_layer(&mut self, other: &Cob, path: &ScenePath, layer: &CobSceneLayer)
+    {
+        let Some(parent) = path.parent() else { return };
+        let name = layer.name.as_str();
+
+        // Scene roots are inserted into the section with their siblings.
+        if parent.len() == 0 {
+            let Some(prev_siblings) = other.sections.iter().find_map(|s| match s {
+                CobSection::Scenes(scenes) if scenes.scenes.iter().any(|s| s.name.as_str() == name) => {
+                    Some(&scenes.scenes)
+                }
+                _ => None,
+            }) else {
+                return;
+            };
+            let (before, after) = sibling_names(prev_siblings.iter().map(|s| Some(s.name.as_str())), name);
+
+            let mut sections: Vec<&mut Vec<CobSceneLayer>> = self
+                .sections
+                .iter_mut()
+                .filter_map(|s| match s {
+                    CobSection::Scenes(scenes) => Some(&mut scenes.scenes),
+                    _ => None,
+                })
+                .collect();
+            let position = sections.iter().enumerate().find_map(|(idx, siblings)| {
+                let siblings = siblings.iter().map(|s| Some(s.name.as_str()));
+                find_restore_position(siblings, &before, &after).map(|pos| (idx, pos))
+            });
+            match position {
+                Some((idx, pos)) => sections[idx].insert(pos, layer.clone()),
+                None => {
+                    let Some(siblings) = sections.first_mut() else { return };
+                    siblings.push(layer.clone());
+                }
+            }
+            return;
+        }
+
+        let Some(prev_parent) = other.get_scene_layer(&parent) else { return };
+        let Some(parent) = self.get_scene_layer_mut(&parent) else { return };
+        let (before, after) = sibling_names(prev_parent.entries.iter().map(layer_entry_name), name);
+        let pos = find_restore_position(parent.entries.iter().map(layer_entry_name), &before, &after)
+            .unwrap_or(parent.entries.len());
+        parent
+            .entries
+            .insert(pos, CobSceneLayerEntry::Layer(layer.clone()));
+    }
+
     // TODO: This allocates a string to do loadable name checks.
     pub fn get_command_loadable_mut(&mut self, target_name: &str) -> Option<&mut CobLoadable>
     {
@@ -195,6 +627,302 @@ impl Cob
 
         None
     }
+
+    /// Gets the first `#scenes` section, or adds one at the end of the file.
+    fn scenes_section_mut(&mut self) -> &mut CobScenes
+    {
+        let pos = match self
+            .sections
+            .iter()
+            .position(|s| matches!(s, CobSection::Scenes(_)))
+        {
+            Some(pos) => pos,
+            None => {
+                self.sections.push(CobSection::Scenes(CobScenes {
+                    start_fill: CobFill::default(),
+                    scenes: vec![],
+                }));
+                self.sections.len() - 1
+            }
+        };
+        let CobSection::Scenes(scenes) = &mut self.sections[pos] else { unreachable!() };
+        scenes
+    }
+
+    /// Gets the first `#commands` section, or adds one before the first `#scenes` section.
+    fn commands_section_mut(&mut self) -> &mut CobCommands
+    {
+        let pos = match self
+            .sections
+            .iter()
+            .position(|s| matches!(s, CobSection::Commands(_)))
+        {
+            Some(pos) => pos,
+            None => {
+                let pos = self
+                    .sections
+                    .iter()
+                    .position(|s| matches!(s, CobSection::Scenes(_)))
+                    .unwrap_or(self.sections.len());
+                self.sections
+                    .insert(pos, CobSection::Commands(CobCommands::default()));
+                pos
+            }
+        };
+        let CobSection::Commands(commands) = &mut self.sections[pos] else { unreachable!() };
+        commands
+    }
+
+    /// Adds an empty scene node at `path`.
+    ///
+    /// The node is inserted before the `index`-th node among its siblings, or after the last sibling if `index` is
+    /// `None` or out of range. New scene roots are added to the first `#scenes` section.
+    ///
+    /// Fails if the node already exists or its parent doesn't exist.
+    pub fn insert_scene_node(&mut self, path: &ScenePath, index: Option<usize>) -> Result<(), String>
+    {
+        let (Some(parent), Some(name)) = (path.parent(), path.iter().next_back()) else {
+            return Err(String::from("scene path is empty"));
+        };
+        if self.get_scene_layer(path).is_some() {
+            return Err(format!("scene node {:?} already exists", path));
+        }
+        let name = CobSceneNodeName(SmolStr::from(name));
+
+        // Add a scene root.
+        if parent.len() == 0 {
+            let scenes = self.scenes_section_mut();
+            let fill = if scenes.scenes.is_empty() {
+                "\n"
+            } else {
+                "\n\n"
+            };
+            let pos = index
+                .filter(|index| *index < scenes.scenes.len())
+                .unwrap_or(scenes.scenes.len());
+            scenes.scenes.insert(
+                pos,
+                CobSceneLayer {
+                    name_fill: CobFill::new(fill),
+                    name,
+                    base: None,
+                    entries: vec![],
+                },
+            );
+            return Ok(());
+        }
+
+        // Add a child node.
+        let Some(parent_layer) = self.get_scene_layer_mut(&parent) else {
+            return Err(format!("parent scene node {:?} doesn't exist", parent));
+        };
+        let name_fill = line_fill(scene_layer_content_indent(parent_layer));
+        let is_layer = |entry: &CobSceneLayerEntry| matches!(entry, CobSceneLayerEntry::Layer(_));
+        let pos = insert_position(&parent_layer.entries, index, is_layer, parent_layer.entries.len());
+        parent_layer.entries.insert(
+            pos,
+            CobSceneLayerEntry::Layer(CobSceneLayer { name_fill, name, base: None, entries: vec![] }),
+        );
+        Ok(())
+    }
+
+    /// Removes the scene node at `path`, including all its loadables and child nodes.
+    pub fn remove_scene_node(&mut self, path: &ScenePath) -> Result<(), String>
+    {
+        let (Some(parent), Some(name)) = (path.parent(), path.iter().next_back()) else {
+            return Err(String::from("scene path is empty"));
+        };
+
+        // Remove a scene root.
+        if parent.len() == 0 {
+            for section in self.sections.iter_mut() {
+                let CobSection::Scenes(scenes) = section else { continue };
+                let Some(pos) = scenes.scenes.iter().position(|s| s.name.as_str() == name) else { continue };
+                scenes.scenes.remove(pos);
+                return Ok(());
+            }
+            return Err(format!("scene node {:?} doesn't exist", path));
+        }
+
+        // Remove a child node.
+        let pos = self.get_scene_layer(&parent).and_then(|layer| {
+            layer.entries.iter().position(|entry| match entry {
+                CobSceneLayerEntry::Layer(child) => child.name.as_str() == name,
+                _ => false,
+            })
+        });
+        let (Some(pos), Some(parent_layer)) = (pos, self.get_scene_layer_mut(&parent)) else {
+            return Err(format!("scene node {:?} doesn't exist", path));
+        };
+        parent_layer.entries.remove(pos);
+        Ok(())
+    }
+
+    /// Moves the scene node at `path` so it lands before the `index`-th node among its other siblings.
+    ///
+    /// The node is moved after the last sibling if `index` is out of range.
+    pub fn move_scene_node(&mut self, path: &ScenePath, index: usize) -> Result<(), String>
+    {
+        let (Some(parent), Some(name)) = (path.parent(), path.iter().next_back()) else {
+            return Err(String::from("scene path is empty"));
+        };
+
+        // Move a scene root.
+        if parent.len() == 0 {
+            for section in self.sections.iter_mut() {
+                let CobSection::Scenes(scenes) = section else { continue };
+                let Some(pos) = scenes.scenes.iter().position(|s| s.name.as_str() == name) else { continue };
+                move_entry(&mut scenes.scenes, pos, index, |_| true);
+                return Ok(());
+            }
+            return Err(format!("scene node {:?} doesn't exist", path));
+        }
+
+        // Move a child node.
+        let Some(parent_layer) = self.get_scene_layer_mut(&parent) else {
+            return Err(format!("scene node {:?} doesn't exist", path));
+        };
+        let Some(pos) = parent_layer.entries.iter().position(|entry| match entry {
+            CobSceneLayerEntry::Layer(child) => child.name.as_str() == name,
+            _ => false,
+        }) else {
+            return Err(format!("scene node {:?} doesn't exist", path));
+        };
+        move_entry(&mut parent_layer.entries, pos, index, |entry| {
+            matches!(entry, CobSceneLayerEntry::Layer(_))
+        });
+        Ok(())
+    }
+
+    /// Adds a loadable to the scene node at `path`.
+    ///
+    /// The loadable is inserted before the `index`-th loadable in the node, or after the last loadable if `index`
+    /// is `None` or out of range. The loadable's fill is replaced so it lines up with the node's other items.
+    ///
+    /// Fails if the node already has a loadable with the same name.
+    pub fn insert_scene_loadable(
+        &mut self,
+        path: &ScenePath,
+        mut loadable: CobLoadable,
+        index: Option<usize>,
+    ) -> Result<(), String>
+    {
+        let Some(layer) = self.get_scene_layer_mut(path) else {
+            return Err(format!("scene node {:?} doesn't exist", path));
+        };
+        let name = loadable.id.to_canonical(None);
+        if find_loadable(&layer.entries, &name, scene_entry_loadable).is_some() {
+            return Err(format!("scene node {:?} already has loadable {}", path, name));
+        }
+
+        loadable.fill = line_fill(scene_layer_content_indent(layer));
+        let first_layer = layer
+            .entries
+            .iter()
+            .position(|entry| matches!(entry, CobSceneLayerEntry::Layer(_)))
+            .unwrap_or(layer.entries.len());
+        let pos = insert_position(
+            &layer.entries,
+            index,
+            |entry| scene_entry_loadable(entry).is_some(),
+            first_layer,
+        );
+        layer
+            .entries
+            .insert(pos, CobSceneLayerEntry::Loadable(loadable));
+        Ok(())
+    }
+
+    /// Removes the loadable named `name` from the scene node at `path`.
+    pub fn remove_scene_loadable(&mut self, path: &ScenePath, name: &str) -> Result<(), String>
+    {
+        let Some(layer) = self.get_scene_layer_mut(path) else {
+            return Err(format!("scene node {:?} doesn't exist", path));
+        };
+        let Some(pos) = find_loadable(&layer.entries, name, scene_entry_loadable) else {
+            return Err(format!("scene node {:?} doesn't have loadable {}", path, name));
+        };
+        layer.entries.remove(pos);
+        Ok(())
+    }
+
+    /// Moves the loadable named `name` in the scene node at `path` so it lands before the `index`-th other
+    /// loadable in the node.
+    ///
+    /// The loadable is moved after the last loadable if `index` is out of range.
+    pub fn move_scene_loadable(&mut self, path: &ScenePath, name: &str, index: usize) -> Result<(), String>
+    {
+        let Some(layer) = self.get_scene_layer_mut(path) else {
+            return Err(format!("scene node {:?} doesn't exist", path));
+        };
+        let Some(pos) = find_loadable(&layer.entries, name, scene_entry_loadable) else {
+            return Err(format!("scene node {:?} doesn't have loadable {}", path, name));
+        };
+        move_entry(&mut layer.entries, pos, index, |entry| {
+            scene_entry_loadable(entry).is_some()
+        });
+        Ok(())
+    }
+
+    /// Adds a loadable to the first `#commands` section.
+    ///
+    /// The loadable is inserted before the `index`-th command in the section, or after the last command if `index`
+    /// is `None` or out of range. A `#commands` section is added if the file doesn't have one.
+    ///
+    /// Fails if the file already has a command with the same name.
+    pub fn insert_command_loadable(
+        &mut self,
+        mut loadable: CobLoadable,
+        index: Option<usize>,
+    ) -> Result<(), String>
+    {
+        let name = loadable.id.to_canonical(None);
+        if self.get_command_loadable_mut(&name).is_some() {
+            return Err(format!("#commands already has loadable {}", name));
+        }
+
+        loadable.fill = line_fill(0);
+        let commands = self.commands_section_mut();
+        let pos = insert_position(
+            &commands.entries,
+            index,
+            |entry| command_entry_loadable(entry).is_some(),
+            commands.entries.len(),
+        );
+        commands
+            .entries
+            .insert(pos, CobCommandEntry::Loadable(loadable));
+        Ok(())
+    }
+
+    /// Removes the command loadable named `name`.
+    pub fn remove_command_loadable(&mut self, name: &str) -> Result<(), String>
+    {
+        for section in self.sections.iter_mut() {
+            let CobSection::Commands(commands) = section else { continue };
+            let Some(pos) = find_loadable(&commands.entries, name, command_entry_loadable) else { continue };
+            commands.entries.remove(pos);
+            return Ok(());
+        }
+        Err(format!("#commands doesn't have loadable {}", name))
+    }
+
+    /// Moves the command loadable named `name` so it lands before the `index`-th other command in its
+    /// `#commands` section.
+    ///
+    /// The loadable is moved after the last command if `index` is out of range.
+    pub fn move_command_loadable(&mut self, name: &str, index: usize) -> Result<(), String>
+    {
+        for section in self.sections.iter_mut() {
+            let CobSection::Commands(commands) = section else { continue };
+            let Some(pos) = find_loadable(&commands.entries, name, command_entry_loadable) else { continue };
+            move_entry(&mut commands.entries, pos, index, |entry| {
+                command_entry_loadable(entry).is_some()
+            });
+            return Ok(());
+        }
+        Err(format!("#commands doesn't have loadable {}", name))
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -13,8 +13,9 @@ use crate::prelude::*;
 
 /// Represents the path to a cobweb asset file in the `asset` directory.
 ///
-/// Cobweb asset files use the `.cob` extension. If your original path includes an asset source, then
-/// the asset source must be included in the name (e.g. `embedded://scene.cob` -> `scene.cob`).
+/// Cobweb asset files use the `.cob` extension, and compiled files use the `.cobc` extension. If your original
+/// path includes an asset source, then the asset source must be included in the name (e.g. `embedded://scene.cob`
+/// -> `scene.cob`).
 ///
 /// Example: `ui/home.cob` for a `home` cobweb asset in `assets/ui`.
 #[derive(Debug, Clone, Deref, Eq, PartialEq, Hash)]
@@ -34,6 +35,18 @@ impl CobFile
         Some(Self(Arc::from(file)))
     }
 
+    /// Tries to create a new reference to a compiled COB file (see [`CompiledCob`]).
+    ///
+    /// Fails if the file doesn't end with `.cobc`.
+    pub fn try_new_compiled(file: impl AsRef<str>) -> Option<Self>
+    {
+        let file = file.as_ref();
+        if !file.ends_with(".cobc") {
+            return None;
+        }
+        Some(Self(Arc::from(file)))
+    }
+
     pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
     {
         writer.write_bytes("\"".as_bytes())?;
@@ -48,14 +61,10 @@ impl CobFile
 
         // Validate
         if let Err(err) = AssetPath::try_parse(*path.fragment()) {
-            tracing::warn!("failed parsing COB file path at {}; path is invalid {:?}",
-                get_location(content).as_str(), err);
-            return Err(span_verify_error(content));
+            return Err(span_diagnostic(content, format!("expected valid file path; {:?}", err)));
         }
         if !path.ends_with(".cob") {
-            tracing::warn!("failed parsing COB file path at {}; file does not end with '.cob' extension",
-                get_location(content).as_str());
-            return Err(span_verify_error(content));
+            return Err(span_diagnostic(content, "expected file path ending in '.cob'"));
         }
 
         Ok((Self(Arc::from(*path.fragment())), remaining))
@@ -146,9 +146,7 @@ impl CobLoadableVariant
             match rc(remaining, |rm| CobEnum::try_parse(CobFill::default(), rm))? {
                 (Some(variant), next_fill, remaining) => return Ok((Self::Enum(variant), next_fill, remaining)),
                 _ => {
-                    tracing::warn!("failed parsing loadable enum at {}; no valid variant name",
-                        get_location(content));
-                    return Err(span_verify_error(content));
+                    return Err(span_diagnostic(content, "expected enum variant name after '::'"));
                 }
             }
         }
@@ -186,6 +184,39 @@ impl CobLoadableVariant
             Self::Enum(variant) => variant.resolve(constants),
         }
     }
+
+    pub fn apply_macro_params(&mut self, bindings: &CobMacroBindings) -> Result<(), String>
+    {
+        match self {
+            Self::Unit => Ok(()),
+            Self::Tuple(tuple) => tuple.apply_macro_params(bindings),
+            Self::Array(array) => array.apply_macro_params(bindings),
+            Self::Map(map) => map.apply_macro_params(bindings),
+            Self::Enum(variant) => variant.apply_macro_params(bindings),
+        }
+    }
+
+    pub fn resolve_font_units(&mut self, sizes: &CobFontSizes) -> Result<bool, String>
+    {
+        match self {
+            Self::Unit => Ok(false),
+            Self::Tuple(tuple) => tuple.resolve_font_units(sizes),
+            Self::Array(array) => array.resolve_font_units(sizes),
+            Self::Map(map) => map.resolve_font_units(sizes),
+            Self::Enum(variant) => variant.resolve_font_units(sizes),
+        }
+    }
+
+    pub fn resolve_theme(&mut self, theme: Option<&str>) -> Result<bool, String>
+    {
+        match self {
+            Self::Unit => Ok(false),
+            Self::Tuple(tuple) => tuple.resolve_theme(theme),
+            Self::Array(array) => array.resolve_theme(theme),
+            Self::Map(map) => map.resolve_theme(theme),
+            Self::Enum(variant) => variant.resolve_theme(theme),
+        }
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -229,6 +260,25 @@ impl CobLoadable
         self.variant.resolve(constants)
     }
 
+    /// Replaces macro params with values from a loadable macro call.
+    pub fn apply_macro_params(&mut self, bindings: &CobMacroBindings) -> Result<(), String>
+    {
+        self.variant.apply_macro_params(bindings)
+    }
+
+    /// Converts `rem` and `em` values to `Val::Px` values.
+    ///
+    /// Returns `true` if any `rem` or `em` values were found.
+    pub fn resolve_font_units(&mut self, sizes: &CobFontSizes) -> Result<bool, String>
+    {
+        self.variant.resolve_font_units(sizes)
+    }
+
+    pub fn resolve_theme(&mut self, theme: Option<&str>) -> Result<bool, String>
+    {
+        self.variant.resolve_theme(theme)
+    }
+
     pub fn extract<T: Serialize + 'static>(value: &T, registry: &TypeRegistry) -> CobResult<Self>
     {
         let type_info = registry
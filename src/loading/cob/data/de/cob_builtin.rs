@@ -12,8 +12,12 @@ where
     V: Visitor<'de>,
 {
     match builtin {
-        CobBuiltin::Color(CobHexColor { color, .. }) => visitor.visit_enum(ColorSrgbaAccess { color: *color }),
+        CobBuiltin::Color(CobColor { color, .. }) => visitor.visit_enum(ColorSrgbaAccess { color: *color }),
         CobBuiltin::Val { val, .. } => visitor.visit_enum(ValAccess { val: *val }),
+        CobBuiltin::FontRelative { unit, .. } => Err(serde::de::Error::custom(format!(
+            "`{}` values can only be used in scene nodes",
+            unit.as_str()
+        ))),
     }
 }
 
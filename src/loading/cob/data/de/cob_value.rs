@@ -1,4 +1,5 @@
-use serde::de::{Expected, Unexpected, Visitor};
+use bevy::ui::Val;
+use serde::de::{Expected, IntoDeserializer, Unexpected, Visitor};
 
 use super::{
     deserialize_builtin, visit_array_ref, visit_map_ref, visit_tuple_ref, visit_wrapped_value_ref,
@@ -16,6 +17,8 @@ macro_rules! deserialize_value_ref_number {
         {
             match self {
                 CobValue::Number(n) => n.deserialize_any(visitor),
+                // Pixel values can be used as numbers, e.g. so font sizes can be written in `rem`.
+                CobValue::Builtin(CobBuiltin::Val { val: Val::Px(px), .. }) => visitor.visit_f32(*px),
                 _ => Err(self.invalid_type(&visitor)),
             }
         }
@@ -46,6 +49,15 @@ impl<'de> serde::Deserializer<'de> for &'de CobValue
             CobValue::Constant(_) => Err(self.invalid_type(&visitor)),
             CobValue::DataMacro(_) => Err(self.invalid_type(&visitor)),
             CobValue::MacroParam(_) => Err(self.invalid_type(&visitor)),
+            CobValue::Expression(_) => Err(self.invalid_type(&visitor)),
+            CobValue::Function(_) => Err(self.invalid_type(&visitor)),
+            // Visited as a newtype struct so localization-aware types like `TextContent` can tell it apart from a
+            // plain string.
+            CobValue::Localized(l) => {
+                let request = l.request().map_err(CobError::Message)?;
+                visitor.visit_newtype_struct(IntoDeserializer::<CobError>::into_deserializer(request))
+            }
+            CobValue::Themed(_) => Err(self.invalid_type(&visitor)),
         }
     }
 
@@ -119,6 +131,7 @@ impl<'de> serde::Deserializer<'de> for &'de CobValue
     {
         match self {
             CobValue::String(s) => visitor.visit_borrowed_str(s.as_str()),
+            CobValue::Localized(l) => visitor.visit_string(l.request().map_err(CobError::Message)?),
             _ => Err(self.invalid_type(&visitor)),
         }
     }
@@ -288,6 +301,10 @@ impl CobValue
             CobValue::Constant(_) => Unexpected::Other("constant"),
             CobValue::DataMacro(_) => Unexpected::Other("data macro"),
             CobValue::MacroParam(_) => Unexpected::Other("macro param"),
+            CobValue::Expression(_) => Unexpected::Other("expression"),
+            CobValue::Function(_) => Unexpected::Other("function call"),
+            CobValue::Localized(_) => Unexpected::Other("localized text"),
+            CobValue::Themed(_) => Unexpected::Other("themed constant"),
         }
     }
 }
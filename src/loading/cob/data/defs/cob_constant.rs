@@ -150,6 +150,23 @@ impl CobConstantValue
             Self::ValueGroup(group) => group.resolve(constants),
         }
     }
+
+    pub fn apply_macro_params(&mut self, bindings: &CobMacroBindings) -> Result<(), String>
+    {
+        match self {
+            Self::Value(value) => {
+                if let Some(group) = value.apply_macro_params(bindings)? {
+                    *self = Self::ValueGroup(CobValueGroup {
+                        start_fill: CobFill::default(),
+                        entries: group,
+                        end_fill: CobFill::default(),
+                    });
+                }
+                Ok(())
+            }
+            Self::ValueGroup(group) => group.apply_macro_params(bindings),
+        }
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -233,8 +250,10 @@ impl CobConstantDef
         let (remaining, _) = char('=').parse(remaining)?;
         let (value_fill, remaining) = CobFill::parse(remaining);
         let (Some(value), end_fill, remaining) = CobConstantValue::try_parse(value_fill, remaining)? else {
-            tracing::warn!("constant definition is invalid at {}", get_location(content).as_str());
-            return Err(span_verify_error(content));
+            return Err(span_diagnostic(
+                content,
+                "expected value after '=' in constant definition",
+            ));
         };
 
         let def = Self { start_fill, name, pre_eq_fill, value };
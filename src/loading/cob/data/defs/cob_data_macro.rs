@@ -3,12 +3,30 @@
 
 // def must start at beginning of line
 
+use nom::bytes::complete::tag;
+use nom::character::complete::char;
+use nom::combinator::recognize;
+use nom::multi::many0_count;
+use nom::sequence::{terminated, tuple};
+use nom::Parser;
+use smol_str::SmolStr;
+
 use crate::prelude::*;
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// A data macro call, e.g. `card_bg!(#FF0000)` or `my_import::card_bg!(color: #FF0000)`.
+///
+/// Data macro calls can be used anywhere a [`CobValue`] is allowed. If the macro expands to a value group, then
+/// the group will be flattened into the parent structure (an array, tuple, or map).
 #[derive(Debug, Clone, PartialEq)]
-pub struct CobDataMacroCall;
+pub struct CobDataMacroCall
+{
+    pub start_fill: CobFill,
+    /// The macro's name, including `a::b::` import alias segments. Does not include the `!`.
+    pub path: SmolStr,
+    pub args: CobMacroArgs,
+}
 
 impl CobDataMacroCall
 {
@@ -17,25 +35,88 @@ impl CobDataMacroCall
         self.write_to_with_space(writer, "")
     }
 
-    pub fn write_to_with_space(&self, _writer: &mut impl RawSerializer, _space: &str)
-        -> Result<(), std::io::Error>
+    pub fn write_to_with_space(&self, writer: &mut impl RawSerializer, space: &str) -> Result<(), std::io::Error>
     {
+        self.start_fill.write_to_or_else(writer, space)?;
+        writer.write_bytes(self.path.as_bytes())?;
+        writer.write_bytes("!".as_bytes())?;
+        self.args.write_to(writer)?;
         Ok(())
     }
 
     pub fn try_parse(fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
     {
-        // TODO
-        Ok((None, fill, content))
+        let Ok((remaining, path)) = recognize(tuple((
+            // Extensions
+            many0_count(terminated(snake_identifier, tag("::"))),
+            // Macro name
+            snake_identifier,
+        )))
+        .parse(content) else {
+            return Ok((None, fill, content));
+        };
+        let Ok((remaining, _)) = char::<_, ()>('!').parse(remaining) else {
+            return Ok((None, fill, content));
+        };
+        let (Some(args), next_fill, remaining) = rc(remaining, |rm| CobMacroArgs::try_parse(rm))? else {
+            return Err(span_diagnostic(
+                content,
+                "expected '(' ... ')' after '!' in data macro call",
+            ));
+        };
+
+        let call = Self {
+            start_fill: fill,
+            path: SmolStr::from(*path.fragment()),
+            args,
+        };
+        Ok((Some(call), next_fill, remaining))
     }
 
-    pub fn recover_fill(&mut self, _other: &Self) {}
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        self.start_fill.recover(&other.start_fill);
+        self.args.recover_fill(&other.args);
+    }
+
+    /// Expands the macro call into the value or value group it points to.
+    ///
+    /// Arguments are resolved with `constants` before being inserted to the macro's body. The macro's body is
+    /// resolved when its definition is extracted (see [`CobDataMacroDef`]), so the expanded value is fully
+    /// resolved.
+    pub fn expand(&mut self, constants: &ConstantsBuffer) -> Result<CobConstantValue, String>
+    {
+        self.args.resolve(constants)?;
+        let Some(def) = constants.get_data_macro(self.path.as_str()) else {
+            return Err(format!("data macro lookup failed for {}!", self.path.as_str()));
+        };
+        let bindings = def
+            .params
+            .bind(&self.args)
+            .map_err(|err| format!("failed expanding data macro {}!; {err}", self.path.as_str()))?;
+        let mut value = def.value.clone();
+        value
+            .apply_macro_params(&bindings)
+            .map_err(|err| format!("failed expanding data macro {}!; {err}", self.path.as_str()))?;
+        Ok(value)
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// A data macro definition, e.g. `card_bg!(@color @alpha = 1.0) = Srgba{ ... }`.
+///
+/// Data macros can expand to a single value or a value group.
 #[derive(Debug, Clone, PartialEq)]
-pub struct CobDataMacroDef;
+pub struct CobDataMacroDef
+{
+    pub start_fill: CobFill,
+    pub name: SmolStr,
+    pub params: CobMacroParamDefs,
+    pub pre_eq_fill: CobFill,
+    /// The value is expected to handle its own fill.
+    pub value: CobConstantValue,
+}
 
 impl CobDataMacroDef
 {
@@ -44,19 +125,67 @@ impl CobDataMacroDef
         self.write_to_with_space(writer, "")
     }
 
-    pub fn write_to_with_space(&self, _writer: &mut impl RawSerializer, _space: &str)
-        -> Result<(), std::io::Error>
+    pub fn write_to_with_space(&self, writer: &mut impl RawSerializer, space: &str) -> Result<(), std::io::Error>
     {
+        self.start_fill.write_to_or_else(writer, space)?;
+        writer.write_bytes(self.name.as_bytes())?;
+        writer.write_bytes("!".as_bytes())?;
+        self.params.write_to(writer)?;
+        self.pre_eq_fill.write_to(writer)?;
+        writer.write_bytes("=".as_bytes())?;
+        self.value.write_to(writer)?;
         Ok(())
     }
 
-    pub fn try_parse(fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
+    pub fn try_parse(start_fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
     {
-        // TODO
-        Ok((None, fill, content))
+        let Ok((remaining, name)) = snake_identifier(content) else {
+            return Ok((None, start_fill, content));
+        };
+        let Ok((remaining, _)) = char::<_, ()>('!').parse(remaining) else {
+            return Ok((None, start_fill, content));
+        };
+        let (Some(params), pre_eq_fill, remaining) = rc(remaining, |rm| CobMacroParamDefs::try_parse(rm))? else {
+            return Err(span_diagnostic(
+                content,
+                "expected '(' ... ')' after '!' in data macro definition",
+            ));
+        };
+        let (remaining, _) = char('=').parse(remaining)?;
+        let (value_fill, remaining) = CobFill::parse(remaining);
+        let (Some(value), end_fill, remaining) = CobConstantValue::try_parse(value_fill, remaining)? else {
+            return Err(span_diagnostic(
+                content,
+                "expected value after '=' in data macro definition",
+            ));
+        };
+
+        let def = Self {
+            start_fill,
+            name: SmolStr::from(*name.fragment()),
+            params,
+            pre_eq_fill,
+            value,
+        };
+        Ok((Some(def), end_fill, remaining))
     }
 
-    pub fn recover_fill(&mut self, _other: &Self) {}
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        self.start_fill.recover(&other.start_fill);
+        self.params.recover_fill(&other.params);
+        self.pre_eq_fill.recover(&other.pre_eq_fill);
+        self.value.recover_fill(&other.value);
+    }
+
+    /// Resolves constants and macro calls in the macro's param defaults and body.
+    ///
+    /// Macro params in the body are left in place. They will be replaced when the macro is expanded.
+    pub fn resolve(&mut self, constants: &ConstantsBuffer) -> Result<(), String>
+    {
+        self.params.resolve(constants)?;
+        self.value.resolve(constants)
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
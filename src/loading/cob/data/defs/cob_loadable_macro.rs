@@ -4,12 +4,29 @@
 
 // def must start at beginning of line
 
+use nom::bytes::complete::tag;
+use nom::character::complete::char;
+use nom::combinator::recognize;
+use nom::multi::many0_count;
+use nom::sequence::{terminated, tuple};
+use nom::Parser;
+use smol_str::SmolStr;
+
 use crate::prelude::*;
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// A loadable macro call, e.g. `Button!(#FF0000)` or `my_import::Button!(color: #FF0000)`.
+///
+/// Loadable macro calls can be used in scene nodes and `#commands` sections. They expand to one or more loadables.
 #[derive(Debug, Clone, PartialEq)]
-pub struct CobLoadableMacroCall;
+pub struct CobLoadableMacroCall
+{
+    pub start_fill: CobFill,
+    /// The macro's name, including `a::b::` import alias segments. Does not include the `!`.
+    pub path: SmolStr,
+    pub args: CobMacroArgs,
+}
 
 impl CobLoadableMacroCall
 {
@@ -18,46 +35,441 @@ impl CobLoadableMacroCall
         self.write_to_with_space(writer, "")
     }
 
-    pub fn write_to_with_space(&self, _writer: &mut impl RawSerializer, _space: &str)
-        -> Result<(), std::io::Error>
+    pub fn write_to_with_space(&self, writer: &mut impl RawSerializer, space: &str) -> Result<(), std::io::Error>
+    {
+        self.start_fill.write_to_or_else(writer, space)?;
+        writer.write_bytes(self.path.as_bytes())?;
+        writer.write_bytes("!".as_bytes())?;
+        self.args.write_to(writer)?;
+        Ok(())
+    }
+
+    pub fn try_parse(fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
+    {
+        let Ok((remaining, path)) = recognize(tuple((
+            // Extensions
+            many0_count(terminated(snake_identifier, tag("::"))),
+            // Macro name
+            camel_identifier,
+        )))
+        .parse(content) else {
+            return Ok((None, fill, content));
+        };
+        let Ok((remaining, _)) = char::<_, ()>('!').parse(remaining) else {
+            return Ok((None, fill, content));
+        };
+        let (Some(args), next_fill, remaining) = rc(remaining, |rm| CobMacroArgs::try_parse(rm))? else {
+            return Err(span_diagnostic(
+                content,
+                "expected '(' ... ')' after '!' in loadable macro call",
+            ));
+        };
+
+        let call = Self {
+            start_fill: fill,
+            path: SmolStr::from(*path.fragment()),
+            args,
+        };
+        Ok((Some(call), next_fill, remaining))
+    }
+
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        self.start_fill.recover(&other.start_fill);
+        self.args.recover_fill(&other.args);
+    }
+
+    /// Expands the macro call into the loadables it points to.
+    ///
+    /// Arguments are resolved with `constants` before being inserted to the macro's body. The macro's body is
+    /// resolved when its definition is extracted (see [`CobLoadableMacroDef`]), so the expanded loadables are
+    /// fully resolved.
+    pub fn expand(&mut self, constants: &ConstantsBuffer) -> Result<Vec<CobLoadable>, String>
+    {
+        self.args.resolve(constants)?;
+        let Some(def) = constants.get_loadable_macro(self.path.as_str()) else {
+            return Err(format!("loadable macro lookup failed for {}!", self.path.as_str()));
+        };
+        let bindings = def
+            .params
+            .bind(&self.args)
+            .map_err(|err| format!("failed expanding loadable macro {}!; {err}", self.path.as_str()))?;
+        let mut loadables = def.value.loadables()?;
+        for loadable in loadables.iter_mut() {
+            loadable
+                .apply_macro_params(&bindings)
+                .map_err(|err| format!("failed expanding loadable macro {}!; {err}", self.path.as_str()))?;
+        }
+        Ok(loadables)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CobLoadableGroupEntry
+{
+    LoadableMacroCall(CobLoadableMacroCall),
+    Loadable(CobLoadable),
+}
+
+impl CobLoadableGroupEntry
+{
+    pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
     {
+        match self {
+            Self::LoadableMacroCall(call) => {
+                call.write_to(writer)?;
+            }
+            Self::Loadable(loadable) => {
+                loadable.write_to(writer)?;
+            }
+        }
         Ok(())
     }
 
     pub fn try_parse(fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
     {
-        // TODO
+        // Parse loadable macro calls before loadables to avoid conflicts.
+        let fill = match rc(content, move |c| CobLoadableMacroCall::try_parse(fill, c))? {
+            (Some(item), fill, remaining) => return Ok((Some(Self::LoadableMacroCall(item)), fill, remaining)),
+            (None, fill, _) => fill,
+        };
+        let fill = match rc(content, move |c| CobLoadable::try_parse(fill, c))? {
+            (Some(item), fill, remaining) => return Ok((Some(Self::Loadable(item)), fill, remaining)),
+            (None, fill, _) => fill,
+        };
+
         Ok((None, fill, content))
     }
 
-    pub fn recover_fill(&mut self, _other: &Self) {}
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        match (self, other) {
+            (Self::LoadableMacroCall(call), Self::LoadableMacroCall(other_call)) => {
+                call.recover_fill(other_call);
+            }
+            (Self::Loadable(loadable), Self::Loadable(other_loadable)) => {
+                loadable.recover_fill(other_loadable);
+            }
+            _ => (),
+        }
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// A group of loadables, e.g. `\ FlexNode{...} BackgroundColor(...) \`.
+///
+/// Only allowed in loadable macro definitions.
 #[derive(Debug, Clone, PartialEq)]
-pub struct CobLoadableMacroDef;
+pub struct CobLoadableGroup
+{
+    /// Fill before opening `\`.
+    pub start_fill: CobFill,
+    pub entries: Vec<CobLoadableGroupEntry>,
+    /// Fill before ending `\`.
+    pub end_fill: CobFill,
+}
 
-impl CobLoadableMacroDef
+impl CobLoadableGroup
 {
     pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
     {
         self.write_to_with_space(writer, "")
     }
 
-    pub fn write_to_with_space(&self, _writer: &mut impl RawSerializer, _space: &str)
-        -> Result<(), std::io::Error>
+    pub fn write_to_with_space(&self, writer: &mut impl RawSerializer, space: &str) -> Result<(), std::io::Error>
+    {
+        self.start_fill.write_to_or_else(writer, space)?;
+        writer.write_bytes("\\".as_bytes())?;
+        for entry in self.entries.iter() {
+            entry.write_to(writer)?;
+        }
+        self.end_fill.write_to(writer)?;
+        writer.write_bytes("\\".as_bytes())?;
+        Ok(())
+    }
+
+    pub fn try_parse(start_fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
+    {
+        let Ok((remaining, _)) = char::<_, ()>('\\').parse(content) else {
+            return Ok((None, start_fill, content));
+        };
+
+        let (mut item_fill, mut remaining) = CobFill::parse(remaining);
+        let mut entries = vec![];
+
+        let end_fill = loop {
+            let fill_len = item_fill.len();
+            match rc(remaining, move |rm| CobLoadableGroupEntry::try_parse(item_fill, rm))? {
+                (Some(entry), next_fill, after_entry) => {
+                    if entries.len() > 0 {
+                        if fill_len == 0 {
+                            return Err(span_diagnostic(
+                                content,
+                                format!("expected whitespace before loadable group entry #{}", entries.len() + 1),
+                            ));
+                        }
+                    }
+                    entries.push(entry);
+                    item_fill = next_fill;
+                    remaining = after_entry;
+                }
+                (None, end_fill, after_end) => {
+                    remaining = after_end;
+                    break end_fill;
+                }
+            }
+        };
+
+        let (remaining, _) = char('\\').parse(remaining)?;
+        let (post_fill, remaining) = CobFill::parse(remaining);
+        Ok((Some(Self { start_fill, entries, end_fill }), post_fill, remaining))
+    }
+
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        self.start_fill.recover(&other.start_fill);
+        for (entry, other_entry) in self.entries.iter_mut().zip(other.entries.iter()) {
+            entry.recover_fill(other_entry);
+        }
+        self.end_fill.recover(&other.end_fill);
+    }
+
+    /// Resolves constants and macro calls in the group's loadables.
+    ///
+    /// Loadable macro calls are expanded and flattened into the group.
+    pub fn resolve(&mut self, constants: &ConstantsBuffer) -> Result<(), String>
     {
+        let mut idx = 0;
+        while idx < self.entries.len() {
+            let loadables = match &mut self.entries[idx] {
+                CobLoadableGroupEntry::LoadableMacroCall(call) => call.expand(constants)?,
+                CobLoadableGroupEntry::Loadable(loadable) => {
+                    loadable.resolve(constants)?;
+                    idx += 1;
+                    continue;
+                }
+            };
+
+            // Remove the old entry.
+            self.entries.remove(idx);
+
+            // Flatten the expanded loadables into the group.
+            for loadable in loadables {
+                self.entries
+                    .insert(idx, CobLoadableGroupEntry::Loadable(loadable));
+                idx += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// The body of a loadable macro definition.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CobLoadableMacroValue
+{
+    LoadableMacroCall(CobLoadableMacroCall),
+    Loadable(CobLoadable),
+    /// Used for collections of loadables that will all be inserted when the macro is called.
+    Group(CobLoadableGroup),
+}
+
+impl CobLoadableMacroValue
+{
+    pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
+    {
+        self.write_to_with_space(writer, "")
+    }
+
+    pub fn write_to_with_space(&self, writer: &mut impl RawSerializer, space: &str) -> Result<(), std::io::Error>
+    {
+        match self {
+            Self::LoadableMacroCall(call) => {
+                call.write_to_with_space(writer, space)?;
+            }
+            Self::Loadable(loadable) => {
+                // Loadables always have fill.
+                loadable.write_to(writer)?;
+            }
+            Self::Group(group) => {
+                group.write_to_with_space(writer, space)?;
+            }
+        }
         Ok(())
     }
 
     pub fn try_parse(fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
     {
-        // TODO
+        let fill = match rc(content, move |c| CobLoadableMacroCall::try_parse(fill, c))? {
+            (Some(item), fill, remaining) => return Ok((Some(Self::LoadableMacroCall(item)), fill, remaining)),
+            (None, fill, _) => fill,
+        };
+        let fill = match rc(content, move |c| CobLoadable::try_parse(fill, c))? {
+            (Some(item), fill, remaining) => return Ok((Some(Self::Loadable(item)), fill, remaining)),
+            (None, fill, _) => fill,
+        };
+        let fill = match rc(content, move |c| CobLoadableGroup::try_parse(fill, c))? {
+            (Some(item), fill, remaining) => return Ok((Some(Self::Group(item)), fill, remaining)),
+            (None, fill, _) => fill,
+        };
+
         Ok((None, fill, content))
     }
 
-    pub fn recover_fill(&mut self, _other: &Self) {}
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        match (self, other) {
+            (Self::LoadableMacroCall(call), Self::LoadableMacroCall(other_call)) => {
+                call.recover_fill(other_call);
+            }
+            (Self::Loadable(loadable), Self::Loadable(other_loadable)) => {
+                loadable.recover_fill(other_loadable);
+            }
+            (Self::Group(group), Self::Group(other_group)) => {
+                group.recover_fill(other_group);
+            }
+            _ => (),
+        }
+    }
+
+    /// Resolves constants and macro calls in the value.
+    ///
+    /// A top-level loadable macro call is expanded into a loadable group.
+    pub fn resolve(&mut self, constants: &ConstantsBuffer) -> Result<(), String>
+    {
+        match self {
+            Self::LoadableMacroCall(call) => {
+                let start_fill = call.start_fill.clone();
+                let loadables = call.expand(constants)?;
+                *self = Self::Group(CobLoadableGroup {
+                    start_fill,
+                    entries: loadables
+                        .into_iter()
+                        .map(CobLoadableGroupEntry::Loadable)
+                        .collect(),
+                    end_fill: CobFill::default(),
+                });
+                Ok(())
+            }
+            Self::Loadable(loadable) => loadable.resolve(constants),
+            Self::Group(group) => group.resolve(constants),
+        }
+    }
+
+    /// Gets a copy of the loadables in the value.
+    ///
+    /// Errors if the value contains unexpanded loadable macro calls.
+    pub fn loadables(&self) -> Result<Vec<CobLoadable>, String>
+    {
+        match self {
+            Self::LoadableMacroCall(call) => {
+                Err(format!("loadable macro call {}! is unexpanded", call.path.as_str()))
+            }
+            Self::Loadable(loadable) => Ok(vec![loadable.clone()]),
+            Self::Group(group) => group
+                .entries
+                .iter()
+                .map(|entry| match entry {
+                    CobLoadableGroupEntry::LoadableMacroCall(call) => {
+                        Err(format!("loadable macro call {}! is unexpanded", call.path.as_str()))
+                    }
+                    CobLoadableGroupEntry::Loadable(loadable) => Ok(loadable.clone()),
+                })
+                .collect(),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A loadable macro definition, e.g. `Button!(@color @radius = 4px) = \ FlexNode{...} BackgroundColor(@color) \`.
+///
+/// Loadable macros can expand to a single loadable or a loadable group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobLoadableMacroDef
+{
+    pub start_fill: CobFill,
+    pub name: SmolStr,
+    pub params: CobMacroParamDefs,
+    pub pre_eq_fill: CobFill,
+    /// The value is expected to handle its own fill.
+    pub value: CobLoadableMacroValue,
+}
+
+impl CobLoadableMacroDef
+{
+    pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
+    {
+        self.write_to_with_space(writer, "")
+    }
+
+    pub fn write_to_with_space(&self, writer: &mut impl RawSerializer, space: &str) -> Result<(), std::io::Error>
+    {
+        self.start_fill.write_to_or_else(writer, space)?;
+        writer.write_bytes(self.name.as_bytes())?;
+        writer.write_bytes("!".as_bytes())?;
+        self.params.write_to(writer)?;
+        self.pre_eq_fill.write_to(writer)?;
+        writer.write_bytes("=".as_bytes())?;
+        self.value.write_to(writer)?;
+        Ok(())
+    }
+
+    pub fn try_parse(start_fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
+    {
+        let Ok((remaining, name)) = camel_identifier(content) else {
+            return Ok((None, start_fill, content));
+        };
+        let Ok((remaining, _)) = char::<_, ()>('!').parse(remaining) else {
+            return Ok((None, start_fill, content));
+        };
+        let (Some(params), pre_eq_fill, remaining) = rc(remaining, |rm| CobMacroParamDefs::try_parse(rm))? else {
+            return Err(span_diagnostic(
+                content,
+                "expected '(' ... ')' after '!' in loadable macro definition",
+            ));
+        };
+        let (remaining, _) = char('=').parse(remaining)?;
+        let (value_fill, remaining) = CobFill::parse(remaining);
+        let (Some(value), end_fill, remaining) = CobLoadableMacroValue::try_parse(value_fill, remaining)? else {
+            return Err(span_diagnostic(
+                content,
+                "expected loadable or loadable group \\ ... \\ after '=' in loadable macro definition",
+            ));
+        };
+
+        let def = Self {
+            start_fill,
+            name: SmolStr::from(*name.fragment()),
+            params,
+            pre_eq_fill,
+            value,
+        };
+        Ok((Some(def), end_fill, remaining))
+    }
+
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        self.start_fill.recover(&other.start_fill);
+        self.params.recover_fill(&other.params);
+        self.pre_eq_fill.recover(&other.pre_eq_fill);
+        self.value.recover_fill(&other.value);
+    }
+
+    /// Resolves constants and macro calls in the macro's param defaults and body.
+    ///
+    /// Macro params in the body are left in place. They will be replaced when the macro is expanded.
+    pub fn resolve(&mut self, constants: &ConstantsBuffer) -> Result<(), String>
+    {
+        self.params.resolve(constants)?;
+        self.value.resolve(constants)
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -8,14 +8,32 @@
 // - Catch-all into flatten group
 // - type params for generics: use ^param notation without whitespace, cannot be assigned (non-optional)
 
-use nom::IResult;
+use std::collections::HashMap;
+
+use nom::bytes::complete::tag;
+use nom::character::complete::char;
+use nom::combinator::opt;
+use nom::{IResult, Parser};
+use smol_str::SmolStr;
 
 use crate::prelude::*;
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// A macro parameter inside a macro definition's body.
+///
+/// - `@name`: Required param.
+/// - `@name?`: Optional param. If no value is available for the param, then the entry containing the param will be
+///   removed (e.g. an array entry or a map key-value pair).
+/// - `..@name`: Catch-all param. Will be replaced with all unused arguments of a macro call.
 #[derive(Debug, Clone, PartialEq)]
-pub struct CobMacroParam;
+pub struct CobMacroParam
+{
+    pub start_fill: CobFill,
+    pub catch_all: bool,
+    pub name: SmolStr,
+    pub optional: bool,
+}
 
 impl CobMacroParam
 {
@@ -24,43 +42,104 @@ impl CobMacroParam
         self.write_to_with_space(writer, "")
     }
 
-    pub fn write_to_with_space(&self, _writer: &mut impl RawSerializer, _space: &str)
-        -> Result<(), std::io::Error>
+    pub fn write_to_with_space(&self, writer: &mut impl RawSerializer, space: &str) -> Result<(), std::io::Error>
     {
+        self.start_fill.write_to_or_else(writer, space)?;
+        if self.catch_all {
+            writer.write_bytes("..".as_bytes())?;
+        }
+        writer.write_bytes("@".as_bytes())?;
+        writer.write_bytes(self.name.as_bytes())?;
+        if self.optional {
+            writer.write_bytes("?".as_bytes())?;
+        }
         Ok(())
     }
 
+    /// Nomlike means the ok value is `(remaining, result)`.
+    ///
+    /// Does not parse fill before or after the param.
     pub fn parse_nomlike(content: Span) -> IResult<Span, Self>
     {
-        // TODO
-        Err(span_verify_error(content))
+        let (remaining, catch_all) = opt(tag("..")).parse(content)?;
+        let (remaining, _) = char('@').parse(remaining)?;
+        let (remaining, name) = snake_identifier(remaining)?;
+        let (remaining, optional) = opt(char('?')).parse(remaining)?;
+
+        if catch_all.is_some() && optional.is_some() {
+            return Err(span_diagnostic(
+                content,
+                "expected non-optional catch-all param; catch-all params cannot be optional",
+            ));
+        }
+
+        Ok((
+            remaining,
+            Self {
+                start_fill: CobFill::default(),
+                catch_all: catch_all.is_some(),
+                name: SmolStr::from(*name.fragment()),
+                optional: optional.is_some(),
+            },
+        ))
     }
 
     pub fn try_parse(fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
     {
-        // TODO
-        Ok((None, fill, content))
+        let Ok((remaining, mut param)) = Self::parse_nomlike(content) else {
+            return Ok((None, fill, content));
+        };
+        param.start_fill = fill;
+        let (next_fill, remaining) = CobFill::parse(remaining);
+        Ok((Some(param), next_fill, remaining))
     }
 
     pub fn is_required(&self) -> bool
     {
-        // TODO
-        false
+        !self.optional && !self.catch_all
     }
 
     pub fn is_catch_all(&self) -> bool
     {
-        // TODO
-        false
+        self.catch_all
     }
 
-    pub fn recover_fill(&mut self, _other: &Self) {}
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        self.start_fill.recover(&other.start_fill);
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// A parameter in a macro definition's parameter list.
 #[derive(Debug, Clone, PartialEq)]
-pub struct CobMacroParamDef;
+pub enum CobMacroParamDef
+{
+    /// `@name`
+    Unassigned
+    {
+        start_fill: CobFill, name: SmolStr
+    },
+    /// `@name = value`
+    ///
+    /// The value is used if the macro call doesn't set the param.
+    Assigned
+    {
+        start_fill: CobFill,
+        name: SmolStr,
+        pre_eq_fill: CobFill,
+        /// The value is expected to handle its own fill.
+        value: CobValue,
+    },
+    /// `..@name`
+    ///
+    /// Collects all arguments that don't match other params into a value group.
+    CatchAll
+    {
+        start_fill: CobFill, name: SmolStr
+    },
+}
 
 impl CobMacroParamDef
 {
@@ -69,13 +148,425 @@ impl CobMacroParamDef
         self.write_to_with_space(writer, "")
     }
 
-    pub fn write_to_with_space(&self, _writer: &mut impl RawSerializer, _space: &str)
-        -> Result<(), std::io::Error>
+    pub fn write_to_with_space(&self, writer: &mut impl RawSerializer, space: &str) -> Result<(), std::io::Error>
+    {
+        match self {
+            Self::Unassigned { start_fill, name } => {
+                start_fill.write_to_or_else(writer, space)?;
+                writer.write_bytes("@".as_bytes())?;
+                writer.write_bytes(name.as_bytes())?;
+            }
+            Self::Assigned { start_fill, name, pre_eq_fill, value } => {
+                start_fill.write_to_or_else(writer, space)?;
+                writer.write_bytes("@".as_bytes())?;
+                writer.write_bytes(name.as_bytes())?;
+                pre_eq_fill.write_to(writer)?;
+                writer.write_bytes("=".as_bytes())?;
+                value.write_to(writer)?;
+            }
+            Self::CatchAll { start_fill, name } => {
+                start_fill.write_to_or_else(writer, space)?;
+                writer.write_bytes("..@".as_bytes())?;
+                writer.write_bytes(name.as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn try_parse(start_fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
+    {
+        let Ok((remaining, param)) = CobMacroParam::parse_nomlike(content) else {
+            return Ok((None, start_fill, content));
+        };
+        if param.optional {
+            return Err(span_diagnostic(
+                content,
+                "expected non-optional param definition; param definitions cannot be optional",
+            ));
+        }
+        let (post_fill, remaining) = CobFill::parse(remaining);
+
+        if param.catch_all {
+            return Ok((
+                Some(Self::CatchAll { start_fill, name: param.name }),
+                post_fill,
+                remaining,
+            ));
+        }
+
+        let Ok((remaining, _)) = char::<_, ()>('=').parse(remaining) else {
+            return Ok((
+                Some(Self::Unassigned { start_fill, name: param.name }),
+                post_fill,
+                remaining,
+            ));
+        };
+        let (value_fill, remaining) = CobFill::parse(remaining);
+        let (Some(value), next_fill, remaining) = rc(remaining, move |rm| CobValue::try_parse(value_fill, rm))?
+        else {
+            return Err(span_diagnostic(
+                content,
+                "expected value after '=' in macro param definition",
+            ));
+        };
+
+        Ok((
+            Some(Self::Assigned { start_fill, name: param.name, pre_eq_fill: post_fill, value }),
+            next_fill,
+            remaining,
+        ))
+    }
+
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        match (self, other) {
+            (Self::Unassigned { start_fill, .. }, Self::Unassigned { start_fill: other_fill, .. }) => {
+                start_fill.recover(other_fill);
+            }
+            (
+                Self::Assigned { start_fill, pre_eq_fill, value, .. },
+                Self::Assigned {
+                    start_fill: other_start_fill,
+                    pre_eq_fill: other_pre_eq_fill,
+                    value: other_value,
+                    ..
+                },
+            ) => {
+                start_fill.recover(other_start_fill);
+                pre_eq_fill.recover(other_pre_eq_fill);
+                value.recover_fill(other_value);
+            }
+            (Self::CatchAll { start_fill, .. }, Self::CatchAll { start_fill: other_fill, .. }) => {
+                start_fill.recover(other_fill);
+            }
+            _ => (),
+        }
+    }
+
+    pub fn name(&self) -> &str
+    {
+        match self {
+            Self::Unassigned { name, .. } | Self::Assigned { name, .. } | Self::CatchAll { name, .. } => {
+                name.as_str()
+            }
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// The parameter list of a macro definition, e.g. `(@a @b = 10 ..@rest)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobMacroParamDefs
+{
+    pub entries: Vec<CobMacroParamDef>,
+    /// Fill before ending `)`.
+    pub end_fill: CobFill,
+}
+
+impl CobMacroParamDefs
+{
+    pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
+    {
+        writer.write_bytes("(".as_bytes())?;
+        for (idx, entry) in self.entries.iter().enumerate() {
+            if idx == 0 {
+                entry.write_to(writer)?;
+            } else {
+                entry.write_to_with_space(writer, " ")?;
+            }
+        }
+        self.end_fill.write_to(writer)?;
+        writer.write_bytes(")".as_bytes())?;
+        Ok(())
+    }
+
+    /// Parses the parameter list. The opening `(` must be the first character of `content`.
+    pub fn try_parse(content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
+    {
+        let Ok((remaining, _)) = char::<_, ()>('(').parse(content) else {
+            return Ok((None, CobFill::default(), content));
+        };
+
+        let (mut item_fill, mut remaining) = CobFill::parse(remaining);
+        let mut entries: Vec<CobMacroParamDef> = vec![];
+
+        let end_fill = loop {
+            let fill_len = item_fill.len();
+            match rc(remaining, move |rm| CobMacroParamDef::try_parse(item_fill, rm))? {
+                (Some(entry), next_fill, after_entry) => {
+                    if entries.len() > 0 && fill_len == 0 {
+                        return Err(span_diagnostic(
+                            content,
+                            format!("expected whitespace before macro param #{}", entries.len() + 1),
+                        ));
+                    }
+                    if entries.iter().any(|e| e.name() == entry.name()) {
+                        return Err(span_diagnostic(
+                            content,
+                            format!("expected unique macro param names; @{} is defined twice", entry.name()),
+                        ));
+                    }
+                    if matches!(entry, CobMacroParamDef::CatchAll { .. })
+                        && entries
+                            .iter()
+                            .any(|e| matches!(e, CobMacroParamDef::CatchAll { .. }))
+                    {
+                        return Err(span_diagnostic(
+                            content,
+                            "expected at most one catch-all param in macro params",
+                        ));
+                    }
+                    entries.push(entry);
+                    item_fill = next_fill;
+                    remaining = after_entry;
+                }
+                (None, end_fill, after_end) => {
+                    remaining = after_end;
+                    break end_fill;
+                }
+            }
+        };
+
+        let (remaining, _) = char(')').parse(remaining)?;
+        let (post_fill, remaining) = CobFill::parse(remaining);
+        Ok((Some(Self { entries, end_fill }), post_fill, remaining))
+    }
+
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        for (entry, other_entry) in self.entries.iter_mut().zip(other.entries.iter()) {
+            entry.recover_fill(other_entry);
+        }
+        self.end_fill.recover(&other.end_fill);
+    }
+
+    /// Resolves default values of assigned params.
+    pub fn resolve(&mut self, constants: &ConstantsBuffer) -> Result<(), String>
     {
+        for entry in self.entries.iter_mut() {
+            let CobMacroParamDef::Assigned { name, value, .. } = entry else { continue };
+            if value.resolve(constants)?.is_some() {
+                return Err(
+                    format!("default value of macro param @{} points to a value group but only plain \
+                    values are allowed", name.as_str()),
+                );
+            }
+        }
         Ok(())
     }
 
-    pub fn recover_fill(&mut self, _other: &Self) {}
+    /// Binds macro call arguments to these params.
+    ///
+    /// Positional arguments are assigned in order to params that weren't already assigned by name. Named
+    /// arguments (`name: value`) are assigned to the param with the same name. Arguments that don't match a param
+    /// are collected by the catch-all param, if there is one.
+    pub fn bind(&self, args: &CobMacroArgs) -> Result<CobMacroBindings, String>
+    {
+        let mut bindings = CobMacroBindings::default();
+        let mut positional = vec![];
+
+        for entry in self.entries.iter() {
+            match entry {
+                CobMacroParamDef::Unassigned { name, .. } => {
+                    bindings.params.insert(name.clone(), None);
+                    positional.push(name);
+                }
+                CobMacroParamDef::Assigned { name, value, .. } => {
+                    bindings.params.insert(name.clone(), Some(value.clone()));
+                    positional.push(name);
+                }
+                CobMacroParamDef::CatchAll { name, .. } => {
+                    bindings.catch_all = Some((name.clone(), vec![]));
+                }
+            }
+        }
+
+        let num_positional = positional.len();
+        let mut next_positional = positional.into_iter();
+        let mut assigned: Vec<SmolStr> = vec![];
+
+        for arg in args.entries.iter() {
+            match arg {
+                CobValueGroupEntry::Value(value) => {
+                    if let CobValue::MacroParam(CobMacroParam { catch_all: true, name, .. }) = value {
+                        return Err(format!("catch-all macro param ..@{} cannot be passed to a macro call",
+                            name.as_str()));
+                    }
+                    if let Some(name) = next_positional.find(|name| !assigned.contains(*name)) {
+                        bindings.params.insert(name.clone(), Some(value.clone()));
+                        assigned.push(name.clone());
+                    } else if let Some((_, catch_all)) = &mut bindings.catch_all {
+                        catch_all.push(arg.clone());
+                    } else {
+                        return Err(format!("too many arguments, expected at most {num_positional}"));
+                    }
+                }
+                CobValueGroupEntry::KeyValue(kv) => {
+                    let param_name = match &kv.key {
+                        CobMapKey::FieldName { name, .. } if bindings.params.contains_key(name) => Some(name),
+                        _ => None,
+                    };
+                    if let Some(name) = param_name {
+                        if assigned.contains(name) {
+                            return Err(format!("macro param @{} is assigned more than once", name.as_str()));
+                        }
+                        bindings.params.insert(name.clone(), Some(kv.value.clone()));
+                        assigned.push(name.clone());
+                    } else if let Some((_, catch_all)) = &mut bindings.catch_all {
+                        catch_all.push(arg.clone());
+                    } else {
+                        let key = match &kv.key {
+                            CobMapKey::FieldName { name, .. } => name.as_str(),
+                            CobMapKey::Value(_) => "{value key}",
+                        };
+                        return Err(format!("named argument {key} doesn't match any macro params"));
+                    }
+                }
+            }
+        }
+
+        Ok(bindings)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// The argument list of a macro call, e.g. `(10 b: 11)`.
+///
+/// Arguments can be plain values (positional arguments) or key-value pairs (named arguments).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobMacroArgs
+{
+    pub entries: Vec<CobValueGroupEntry>,
+    /// Fill before ending `)`.
+    pub end_fill: CobFill,
+}
+
+impl CobMacroArgs
+{
+    pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
+    {
+        writer.write_bytes("(".as_bytes())?;
+        for entry in self.entries.iter() {
+            entry.write_to(writer)?;
+        }
+        self.end_fill.write_to(writer)?;
+        writer.write_bytes(")".as_bytes())?;
+        Ok(())
+    }
+
+    /// Parses the argument list. The opening `(` must be the first character of `content`.
+    pub fn try_parse(content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
+    {
+        let Ok((remaining, _)) = char::<_, ()>('(').parse(content) else {
+            return Ok((None, CobFill::default(), content));
+        };
+
+        let (mut item_fill, mut remaining) = CobFill::parse(remaining);
+        let mut entries = vec![];
+
+        let end_fill = loop {
+            let fill_len = item_fill.len();
+            match rc(remaining, move |rm| CobValueGroupEntry::try_parse(item_fill, rm))? {
+                (Some(entry), next_fill, after_entry) => {
+                    if entries.len() > 0 && fill_len == 0 {
+                        return Err(span_diagnostic(
+                            content,
+                            format!("expected whitespace before macro arg #{}", entries.len() + 1),
+                        ));
+                    }
+                    entries.push(entry);
+                    item_fill = next_fill;
+                    remaining = after_entry;
+                }
+                (None, end_fill, after_end) => {
+                    remaining = after_end;
+                    break end_fill;
+                }
+            }
+        };
+
+        let (remaining, _) = char(')').parse(remaining)?;
+        let (post_fill, remaining) = CobFill::parse(remaining);
+        Ok((Some(Self { entries, end_fill }), post_fill, remaining))
+    }
+
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        for (entry, other_entry) in self.entries.iter_mut().zip(other.entries.iter()) {
+            entry.recover_fill(other_entry);
+        }
+        self.end_fill.recover(&other.end_fill);
+    }
+
+    pub fn resolve(&mut self, constants: &ConstantsBuffer) -> Result<(), String>
+    {
+        let mut idx = 0;
+        while idx < self.entries.len() {
+            // If resolving the entry returns a group of values, they need to be flattened into the args.
+            let Some(group) = self.entries[idx].resolve(constants)? else {
+                idx += 1;
+                continue;
+            };
+
+            // Remove the old entry.
+            self.entries.remove(idx);
+
+            // Flatten the group into the args.
+            for val in group.iter() {
+                self.entries.insert(idx, val.clone());
+                idx += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Returned by [`CobMacroBindings::get`].
+pub enum CobMacroParamValue<'a>
+{
+    Value(&'a CobValue),
+    /// Values that should be flattened into the param's parent structure. Will be empty if an optional param has
+    /// no value.
+    Group(&'a [CobValueGroupEntry]),
+}
+
+/// Values assigned to macro params when expanding a macro call.
+///
+/// See [`CobMacroParamDefs::bind`].
+#[derive(Default, Debug)]
+pub struct CobMacroBindings
+{
+    /// [ param name : value ]
+    ///
+    /// Params without a value are `None`.
+    params: HashMap<SmolStr, Option<CobValue>>,
+    catch_all: Option<(SmolStr, Vec<CobValueGroupEntry>)>,
+}
+
+impl CobMacroBindings
+{
+    /// Gets the value that should replace a macro param.
+    pub fn get(&self, param: &CobMacroParam) -> Result<CobMacroParamValue<'_>, String>
+    {
+        if param.catch_all {
+            return match &self.catch_all {
+                Some((name, entries)) if *name == param.name => Ok(CobMacroParamValue::Group(entries)),
+                _ => Err(format!("..@{} is not a catch-all param of the macro", param.name.as_str())),
+            };
+        }
+
+        match self.params.get(&param.name) {
+            Some(Some(value)) => Ok(CobMacroParamValue::Value(value)),
+            Some(None) if param.optional => Ok(CobMacroParamValue::Group(&[])),
+            Some(None) => Err(format!("missing argument for required macro param @{}", param.name.as_str())),
+            None => Err(format!("@{} is not a param of the macro", param.name.as_str())),
+        }
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
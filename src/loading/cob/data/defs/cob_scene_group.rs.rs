@@ -45,9 +45,10 @@ impl CobSceneGroup
                 (Some(entry), next_fill, after_entry) => {
                     if entries.len() > 0 {
                         if fill_len == 0 {
-                            tracing::warn!("failed parsing scene group at {}; entry #{} is not preceded by fill/whitespace",
-                                get_location(content), entries.len() + 1);
-                            return Err(span_verify_error(content));
+                            return Err(span_diagnostic(
+                                content,
+                                format!("expected whitespace before scene group entry #{}", entries.len() + 1),
+                            ));
                         }
                     }
                     entries.push(entry);
@@ -3,112 +3,628 @@
 // - Flatten group only
 // - Scene layer
 // CobSceneMacroParam
+// - `..'name'` inserts the content of the matching param def
+// - `..*` infers the name from the enclosing scene node or param def
 // CobSceneMacroParamDef
-
-// How to capture special catch-all syntax '..*' ?
+// - `'name'` block nested below a scene macro call
 
 // def must start at beginning of line
 
+use nom::bytes::complete::tag;
+use nom::character::complete::char;
+use nom::combinator::recognize;
+use nom::multi::many0_count;
+use nom::sequence::{terminated, tuple};
+use nom::Parser;
+use smol_str::SmolStr;
+
 use crate::prelude::*;
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Merges duplicate entries in a scene layer.
+///
+/// Loadables that appear more than once are replaced by the last instance, and loadables followed by a matching
+/// [`CobLoadableRemoval`] are removed. Named scene nodes that appear more than once are merged into the first
+/// instance.
+pub(crate) fn merge_scene_layer_entries(entries: &mut Vec<CobSceneLayerEntry>)
+{
+    let mut scratch = String::default();
+    let mut idx = 0;
+    while idx < entries.len() {
+        match &entries[idx] {
+            CobSceneLayerEntry::Loadable(loadable) => {
+                scratch = loadable.id.to_canonical(Some(scratch));
+                let is_overridden = entries[(idx + 1)..].iter().any(|entry| match entry {
+                    CobSceneLayerEntry::Loadable(other) => other.id.to_canonical(None) == scratch,
+                    CobSceneLayerEntry::LoadableRemoval(other) => other.id.to_canonical(None) == scratch,
+                    _ => false,
+                });
+                if is_overridden {
+                    entries.remove(idx);
+                    continue;
+                }
+            }
+            CobSceneLayerEntry::Layer(layer) if !layer.name.is_empty() => {
+                let name = layer.name.clone();
+                let mut merged = false;
+                let mut other_idx = idx + 1;
+                while other_idx < entries.len() {
+                    if !matches!(&entries[other_idx], CobSceneLayerEntry::Layer(other) if other.name == name) {
+                        other_idx += 1;
+                        continue;
+                    }
+                    let CobSceneLayerEntry::Layer(other) = entries.remove(other_idx) else { unreachable!() };
+                    let CobSceneLayerEntry::Layer(layer) = &mut entries[idx] else { unreachable!() };
+                    layer.entries.extend(other.entries);
+                    merged = true;
+                }
+                if merged {
+                    let CobSceneLayerEntry::Layer(layer) = &mut entries[idx] else { unreachable!() };
+                    merge_scene_layer_entries(&mut layer.entries);
+                }
+            }
+            _ => (),
+        }
+        idx += 1;
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Resolves constants and macro calls in the body of a scene macro definition or a base scene.
+///
+/// Inferred scene macro params (`..*`) are given the name of the enclosing scene node or param def.
+pub(crate) fn resolve_scene_entries(
+    entries: &mut Vec<CobSceneLayerEntry>,
+    enclosing_name: Option<&str>,
+    constants: &ConstantsBuffer,
+) -> Result<(), String>
+{
+    let mut expanded = false;
+    let mut idx = 0;
+    while idx < entries.len() {
+        let new_entries: Vec<CobSceneLayerEntry> = match &mut entries[idx] {
+            CobSceneLayerEntry::Loadable(loadable) => {
+                loadable.resolve(constants)?;
+                idx += 1;
+                continue;
+            }
+            CobSceneLayerEntry::LoadableRemoval(_) => {
+                idx += 1;
+                continue;
+            }
+            CobSceneLayerEntry::LoadableMacroCall(call) => call
+                .expand(constants)?
+                .into_iter()
+                .map(CobSceneLayerEntry::Loadable)
+                .collect(),
+            CobSceneLayerEntry::SceneMacroCall(call) => {
+                for param_def in call.param_defs.iter_mut() {
+                    resolve_scene_entries(&mut param_def.entries, Some(param_def.name.as_str()), constants)?;
+                }
+                call.expand(constants)?
+            }
+            CobSceneLayerEntry::Layer(layer) => {
+                let name = (!layer.name.is_empty()).then_some(layer.name.as_str());
+                resolve_scene_entries(&mut layer.entries, name, constants)?;
+                idx += 1;
+                continue;
+            }
+            CobSceneLayerEntry::SceneMacroParam(param) => {
+                if param.name.is_none() {
+                    let Some(enclosing_name) = enclosing_name else {
+                        return Err(
+                            "failed inferring name of scene macro param ..*; the param must be inside a \
+                            named scene node or a scene macro param def"
+                                .into(),
+                        );
+                    };
+                    param.name = Some(SmolStr::from(enclosing_name));
+                }
+                idx += 1;
+                continue;
+            }
+        };
+
+        // Replace the macro call with its expanded entries.
+        let num_new = new_entries.len();
+        entries.splice(idx..=idx, new_entries);
+        idx += num_new;
+        expanded = true;
+    }
+
+    if expanded {
+        merge_scene_layer_entries(entries);
+    }
+
+    Ok(())
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Replaces data macro params in scene entries with values from a macro call.
+fn apply_macro_params_to_scene_entries(
+    entries: &mut Vec<CobSceneLayerEntry>,
+    bindings: &CobMacroBindings,
+) -> Result<(), String>
+{
+    for entry in entries.iter_mut() {
+        match entry {
+            CobSceneLayerEntry::Loadable(loadable) => loadable.apply_macro_params(bindings)?,
+            CobSceneLayerEntry::Layer(layer) => apply_macro_params_to_scene_entries(&mut layer.entries, bindings)?,
+            CobSceneLayerEntry::SceneMacroParam(_) | CobSceneLayerEntry::LoadableRemoval(_) => (),
+            CobSceneLayerEntry::LoadableMacroCall(call) => {
+                return Err(format!("loadable macro call {}! is unexpanded", call.path.as_str()));
+            }
+            CobSceneLayerEntry::SceneMacroCall(call) => {
+                return Err(format!("scene macro call +{} is unexpanded", call.path.as_str()));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Replaces scene macro params with the content of matching param defs from a macro call.
+///
+/// Params without a matching param def are removed. `used` records which param defs were inserted.
+fn insert_scene_macro_params(
+    entries: &mut Vec<CobSceneLayerEntry>,
+    param_defs: &[CobSceneMacroParamDef],
+    used: &mut [bool],
+)
+{
+    let mut inserted = false;
+    let mut idx = 0;
+    while idx < entries.len() {
+        match &mut entries[idx] {
+            CobSceneLayerEntry::SceneMacroParam(param) => {
+                let pos = param
+                    .name
+                    .as_ref()
+                    .and_then(|name| param_defs.iter().position(|def| def.name == *name));
+                inserted = true;
+
+                let Some(pos) = pos else {
+                    entries.remove(idx);
+                    continue;
+                };
+
+                // Entries are skipped after insertion so params forwarded from an outer macro are left alone.
+                used[pos] = true;
+                let new_entries = param_defs[pos].entries.iter().cloned();
+                let num_new = new_entries.len();
+                entries.splice(idx..=idx, new_entries);
+                idx += num_new;
+                continue;
+            }
+            CobSceneLayerEntry::Layer(layer) => insert_scene_macro_params(&mut layer.entries, param_defs, used),
+            _ => (),
+        }
+        idx += 1;
+    }
+
+    if inserted {
+        merge_scene_layer_entries(entries);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A scene macro param, e.g. `..'content'` or `..*`.
+///
+/// Scene macro params mark where content from a macro call's [`CobSceneMacroParamDef`] should be inserted. They
+/// are only allowed in the body of a scene macro definition.
+///
+/// The name of a `..*` param is inferred from the enclosing scene node or param def when the macro definition is
+/// extracted.
 #[derive(Debug, Clone, PartialEq)]
-pub struct CobSceneMacroCall;
+pub struct CobSceneMacroParam
+{
+    pub start_fill: CobFill,
+    /// The param's name. Will be `None` for `..*`.
+    pub name: Option<SmolStr>,
+}
 
-impl CobSceneMacroCall
+impl CobSceneMacroParam
 {
     pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
     {
         self.write_to_with_space(writer, "")
     }
 
-    pub fn write_to_with_space(&self, _writer: &mut impl RawSerializer, _space: &str)
-        -> Result<(), std::io::Error>
+    pub fn write_to_with_space(&self, writer: &mut impl RawSerializer, space: &str) -> Result<(), std::io::Error>
     {
+        self.start_fill.write_to_or_else(writer, space)?;
+        writer.write_bytes("..".as_bytes())?;
+        match &self.name {
+            Some(name) => {
+                writer.write_bytes("'".as_bytes())?;
+                writer.write_bytes(name.as_bytes())?;
+                writer.write_bytes("'".as_bytes())?;
+            }
+            None => {
+                writer.write_bytes("*".as_bytes())?;
+            }
+        }
         Ok(())
     }
 
     pub fn try_parse(fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
     {
-        Ok((None, fill, content))
+        let Ok((remaining, _)) = tag::<_, _, ()>("..").parse(content) else {
+            return Ok((None, fill, content));
+        };
+
+        let (name, remaining) = if let Ok((remaining, _)) = char::<_, ()>('*').parse(remaining) {
+            (None, remaining)
+        } else if let Ok((remaining, (_, name, _))) =
+            tuple((char('\''), anything_identifier, char('\''))).parse(remaining)
+        {
+            (Some(SmolStr::from(*name.fragment())), remaining)
+        } else {
+            return Err(span_diagnostic(
+                content,
+                "expected ..* or ..'name' for scene macro param",
+            ));
+        };
+
+        let (next_fill, remaining) = CobFill::parse(remaining);
+        Ok((Some(Self { start_fill: fill, name }), next_fill, remaining))
     }
 
-    pub fn recover_fill(&mut self, _other: &Self) {}
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        self.start_fill.recover(&other.start_fill);
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Content for a scene macro param, nested below a scene macro call.
+///
+/// Example:
+/*
+```rust
++button(#FF0000)
+    'text'
+        TextLine{ text: "Hello" }
+```
+*/
+///
+/// The content is inserted wherever the macro's body contains a matching [`CobSceneMacroParam`].
 #[derive(Debug, Clone, PartialEq)]
-pub struct CobSceneMacroDef;
+pub struct CobSceneMacroParamDef
+{
+    /// Fill before the param name.
+    ///
+    /// Whitespace between the name and most recent newline is used to control scene layer depth.
+    pub name_fill: CobFill,
+    pub name: SmolStr,
+    pub entries: Vec<CobSceneLayerEntry>,
+}
 
-impl CobSceneMacroDef
+impl CobSceneMacroParamDef
 {
     pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
     {
-        self.write_to_with_space(writer, "")
+        self.write_to_with_space(writer, "\n")
     }
 
-    pub fn write_to_with_space(&self, _writer: &mut impl RawSerializer, _space: &str)
-        -> Result<(), std::io::Error>
+    pub fn write_to_with_space(&self, writer: &mut impl RawSerializer, space: &str) -> Result<(), std::io::Error>
     {
+        self.name_fill.write_to_or_else(writer, space)?;
+        writer.write_bytes("'".as_bytes())?;
+        writer.write_bytes(self.name.as_bytes())?;
+        writer.write_bytes("'".as_bytes())?;
+        for entry in self.entries.iter() {
+            entry.write_to(writer)?;
+        }
         Ok(())
     }
 
-    pub fn try_parse(fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
+    pub fn try_parse(name_fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
     {
-        // TODO
-        Ok((None, fill, content))
+        let Ok((remaining, _)) = char::<_, ()>('\'').parse(content) else {
+            return Ok((None, name_fill, content));
+        };
+        let Ok((remaining, name)) = terminated(anything_identifier, char('\'')).parse(remaining) else {
+            return Err(span_diagnostic(content, "expected 'name' for scene macro param def"));
+        };
+
+        // Extract param indent
+        let Some(param_indent) = name_fill.ends_newline_then_num_spaces() else {
+            return Err(span_diagnostic(
+                content,
+                "expected scene macro param def to be on a separate line from the previous item",
+            ));
+        };
+
+        // Get content indent from first item_fill.
+        let name = SmolStr::from(*name.fragment());
+        let (item_fill, remaining) = CobFill::parse(remaining);
+        let Some(content_indent) = item_fill.ends_newline_then_num_spaces() else {
+            if remaining.fragment().len() == 0 {
+                // End-of-file
+                return Ok((Some(Self { name_fill, name, entries: vec![] }), item_fill, remaining));
+            }
+            return Err(span_diagnostic(
+                remaining,
+                "expected first item after a scene macro param def name to be on a separate line",
+            ));
+        };
+
+        // Collect entries.
+        let (entries, end_fill, remaining) =
+            CobSceneLayerEntry::parse_entries(param_indent, content_indent, item_fill, remaining)?;
+
+        Ok((Some(Self { name_fill, name, entries }), end_fill, remaining))
     }
 
-    pub fn recover_fill(&mut self, _other: &Self) {}
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        self.name_fill.recover(&other.name_fill);
+        for (entry, other) in self.entries.iter_mut().zip(other.entries.iter()) {
+            entry.recover_fill(other);
+        }
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// A scene macro call, e.g. `+button(#FF0000)` or `+my_import::button(color: #FF0000)`.
+///
+/// Scene macro calls can be used in scene layers. The macro's body will be inserted to the scene layer where the
+/// macro is called. Content for the macro's scene params can be nested below the call (see
+/// [`CobSceneMacroParamDef`]).
 #[derive(Debug, Clone, PartialEq)]
-pub struct CobSceneMacroParam;
+pub struct CobSceneMacroCall
+{
+    pub start_fill: CobFill,
+    /// The macro's name, including `a::b::` import alias segments. Does not include the `+`.
+    pub path: SmolStr,
+    pub args: CobMacroArgs,
+    pub param_defs: Vec<CobSceneMacroParamDef>,
+}
 
-impl CobSceneMacroParam
+impl CobSceneMacroCall
 {
     pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
     {
         self.write_to_with_space(writer, "")
     }
 
-    pub fn write_to_with_space(&self, _writer: &mut impl RawSerializer, _space: &str)
-        -> Result<(), std::io::Error>
+    pub fn write_to_with_space(&self, writer: &mut impl RawSerializer, space: &str) -> Result<(), std::io::Error>
     {
+        self.start_fill.write_to_or_else(writer, space)?;
+        writer.write_bytes("+".as_bytes())?;
+        writer.write_bytes(self.path.as_bytes())?;
+        self.args.write_to(writer)?;
+        for param_def in self.param_defs.iter() {
+            param_def.write_to(writer)?;
+        }
         Ok(())
     }
 
     pub fn try_parse(fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
     {
-        Ok((None, fill, content))
+        let Ok((remaining, _)) = char::<_, ()>('+').parse(content) else {
+            return Ok((None, fill, content));
+        };
+        let Ok((remaining, path)) = recognize(tuple((
+            // Extensions
+            many0_count(terminated(snake_identifier, tag("::"))),
+            // Macro name
+            snake_identifier,
+        )))
+        .parse(remaining) else {
+            return Err(span_diagnostic(
+                content,
+                "expected snake-case macro name after '+' in scene macro call",
+            ));
+        };
+        let (Some(args), mut next_fill, mut remaining) = rc(remaining, |rm| CobMacroArgs::try_parse(rm))? else {
+            return Err(span_diagnostic(content, "expected '(' ... ')' after scene macro name"));
+        };
+
+        // Collect param defs nested below the call.
+        let call_indent = fill.ends_newline_then_num_spaces();
+        let mut param_defs = vec![];
+        while let (Some(call_indent), Some(indent)) = (call_indent, next_fill.ends_newline_then_num_spaces()) {
+            if indent <= call_indent {
+                break;
+            }
+            let (Some(param_def), item_fill, after_item) =
+                rc(remaining, move |rm| CobSceneMacroParamDef::try_parse(next_fill, rm))?
+            else {
+                return Err(span_diagnostic(
+                    remaining,
+                    "expected scene macro param def (e.g. 'name') nested below scene macro call",
+                ));
+            };
+            param_defs.push(param_def);
+            next_fill = item_fill;
+            remaining = after_item;
+        }
+
+        let call = Self {
+            start_fill: fill,
+            path: SmolStr::from(*path.fragment()),
+            args,
+            param_defs,
+        };
+        Ok((Some(call), next_fill, remaining))
     }
 
-    pub fn recover_fill(&mut self, _other: &Self) {}
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        self.start_fill.recover(&other.start_fill);
+        self.args.recover_fill(&other.args);
+        for (param_def, other_param_def) in self.param_defs.iter_mut().zip(other.param_defs.iter()) {
+            param_def.recover_fill(other_param_def);
+        }
+    }
+
+    /// Expands the macro call into the scene layer entries it points to.
+    ///
+    /// Arguments are resolved with `constants` before being inserted to the macro's body. Content from param
+    /// defs is inserted as-is, and needs to be resolved by the caller.
+    pub fn expand(&mut self, constants: &ConstantsBuffer) -> Result<Vec<CobSceneLayerEntry>, String>
+    {
+        self.args.resolve(constants)?;
+        let Some(def) = constants.get_scene_macro(self.path.as_str()) else {
+            return Err(format!("scene macro lookup failed for +{}", self.path.as_str()));
+        };
+        let bindings = def
+            .params
+            .bind(&self.args)
+            .map_err(|err| format!("failed expanding scene macro +{}; {err}", self.path.as_str()))?;
+        let mut entries = def.entries.clone();
+        apply_macro_params_to_scene_entries(&mut entries, &bindings)
+            .map_err(|err| format!("failed expanding scene macro +{}; {err}", self.path.as_str()))?;
+
+        let mut used = vec![false; self.param_defs.len()];
+        insert_scene_macro_params(&mut entries, &self.param_defs, &mut used);
+        if let Some((unused, _)) = self
+            .param_defs
+            .iter()
+            .zip(used.iter())
+            .find(|(_, used)| !**used)
+        {
+            return Err(
+                format!("failed expanding scene macro +{}; the macro has no scene param '{}'",
+                self.path.as_str(), unused.name.as_str()),
+            );
+        }
+
+        Ok(entries)
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// A scene macro definition, e.g. `+button(@color) = \ ... \`.
+///
+/// The body of a scene macro is a scene layer that will be inserted wherever the macro is called. It can contain
+/// loadables, scene nodes, macro calls, and [`CobSceneMacroParam`]s.
 #[derive(Debug, Clone, PartialEq)]
-pub struct CobSceneMacroParamDef;
+pub struct CobSceneMacroDef
+{
+    pub start_fill: CobFill,
+    pub name: SmolStr,
+    pub params: CobMacroParamDefs,
+    pub pre_eq_fill: CobFill,
+    /// Fill before the opening `\`.
+    pub value_fill: CobFill,
+    pub entries: Vec<CobSceneLayerEntry>,
+    /// Fill before the closing `\`.
+    pub end_fill: CobFill,
+}
 
-impl CobSceneMacroParamDef
+impl CobSceneMacroDef
 {
     pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
     {
         self.write_to_with_space(writer, "")
     }
 
-    pub fn write_to_with_space(&self, _writer: &mut impl RawSerializer, _space: &str)
-        -> Result<(), std::io::Error>
+    pub fn write_to_with_space(&self, writer: &mut impl RawSerializer, space: &str) -> Result<(), std::io::Error>
     {
+        self.start_fill.write_to_or_else(writer, space)?;
+        writer.write_bytes("+".as_bytes())?;
+        writer.write_bytes(self.name.as_bytes())?;
+        self.params.write_to(writer)?;
+        self.pre_eq_fill.write_to(writer)?;
+        writer.write_bytes("=".as_bytes())?;
+        self.value_fill.write_to_or_else(writer, " ")?;
+        writer.write_bytes("\\".as_bytes())?;
+        for entry in self.entries.iter() {
+            entry.write_to(writer)?;
+        }
+        self.end_fill.write_to_or_else(writer, "\n")?;
+        writer.write_bytes("\\".as_bytes())?;
         Ok(())
     }
 
-    pub fn recover_fill(&mut self, _other: &Self) {}
+    pub fn try_parse(start_fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
+    {
+        let Ok((remaining, _)) = char::<_, ()>('+').parse(content) else {
+            return Ok((None, start_fill, content));
+        };
+        let Ok((remaining, name)) = snake_identifier(remaining) else {
+            return Err(span_diagnostic(
+                content,
+                "expected snake-case macro name after '+' in scene macro definition",
+            ));
+        };
+        let (Some(params), pre_eq_fill, remaining) = rc(remaining, |rm| CobMacroParamDefs::try_parse(rm))? else {
+            return Err(span_diagnostic(content, "expected '(' ... ')' after scene macro name"));
+        };
+        let (remaining, _) = char('=').parse(remaining)?;
+        let (value_fill, remaining) = CobFill::parse(remaining);
+        let Ok((remaining, _)) = char::<_, ()>('\\').parse(remaining) else {
+            return Err(span_diagnostic(
+                content,
+                "expected scene group \\ ... \\ after '=' in scene macro definition",
+            ));
+        };
+
+        // Get content indent from first item_fill.
+        let def_indent = start_fill.ends_newline_then_num_spaces().unwrap_or(0);
+        let (item_fill, remaining) = CobFill::parse(remaining);
+        let Some(content_indent) = item_fill.ends_newline_then_num_spaces() else {
+            return Err(span_diagnostic(
+                remaining,
+                "expected first item after the opening '\\' of a scene macro definition to be on a separate line",
+            ));
+        };
+
+        // Collect entries.
+        let (entries, end_fill, remaining) =
+            CobSceneLayerEntry::parse_entries(def_indent, content_indent, item_fill, remaining)?;
+        let Ok((remaining, _)) = char::<_, ()>('\\').parse(remaining) else {
+            return Err(span_diagnostic(
+                remaining,
+                "expected closing '\\' on a separate line at the end of scene macro definition",
+            ));
+        };
+        let (post_fill, remaining) = CobFill::parse(remaining);
+
+        let def = Self {
+            start_fill,
+            name: SmolStr::from(*name.fragment()),
+            params,
+            pre_eq_fill,
+            value_fill,
+            entries,
+            end_fill,
+        };
+        Ok((Some(def), post_fill, remaining))
+    }
+
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        self.start_fill.recover(&other.start_fill);
+        self.params.recover_fill(&other.params);
+        self.pre_eq_fill.recover(&other.pre_eq_fill);
+        self.value_fill.recover(&other.value_fill);
+        for (entry, other) in self.entries.iter_mut().zip(other.entries.iter()) {
+            entry.recover_fill(other);
+        }
+        self.end_fill.recover(&other.end_fill);
+    }
+
+    /// Resolves constants and macro calls in the macro's param defaults and body.
+    ///
+    /// Macro params in the body are left in place. They will be replaced when the macro is expanded.
+    pub fn resolve(&mut self, constants: &ConstantsBuffer) -> Result<(), String>
+    {
+        self.params.resolve(constants)?;
+        resolve_scene_entries(&mut self.entries, None, constants)
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use nom::character::complete::char;
 use nom::Parser;
 
@@ -38,9 +40,10 @@ impl CobValueGroupEntry
                 return Ok((Some(Self::Value(value)), next_fill, remaining));
             }
             (CobMapKVParseResult::KeyNoValue(CobMapKey::FieldName { name, .. }), _, _) => {
-                tracing::warn!("failed parsing value group entry at {}; found field name without value: {}",
-                    get_location(content).as_str(), name.as_str());
-                return Err(span_verify_error(content));
+                return Err(span_diagnostic(
+                    content,
+                    format!("expected ':' after field name `{}` in value group", name.as_str()),
+                ));
             }
             (CobMapKVParseResult::Failure, fill, _) => fill,
         };
@@ -64,13 +67,33 @@ impl CobValueGroupEntry
     pub fn resolve<'a>(
         &mut self,
         constants: &'a ConstantsBuffer,
-    ) -> Result<Option<&'a [CobValueGroupEntry]>, String>
+    ) -> Result<Option<Cow<'a, [CobValueGroupEntry]>>, String>
     {
         match self {
             Self::KeyValue(kv) => kv.resolve(constants).map(|()| None),
             Self::Value(value) => value.resolve(constants),
         }
     }
+
+    /// Replaces macro params with values from a macro call.
+    ///
+    /// Returns a group of entries if the entry should be replaced (or removed if the group is empty).
+    pub fn apply_macro_params(
+        &mut self,
+        bindings: &CobMacroBindings,
+    ) -> Result<Option<Vec<CobValueGroupEntry>>, String>
+    {
+        match self {
+            Self::KeyValue(kv) => {
+                if kv.apply_macro_params(bindings)? {
+                    Ok(None)
+                } else {
+                    Ok(Some(vec![]))
+                }
+            }
+            Self::Value(value) => value.apply_macro_params(bindings),
+        }
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -119,9 +142,10 @@ impl CobValueGroup
                 (Some(entry), next_fill, after_entry) => {
                     if entries.len() > 0 {
                         if fill_len == 0 {
-                            tracing::warn!("failed parsing value group at {}; entry #{} is not preceded by fill/whitespace",
-                                get_location(content), entries.len() + 1);
-                            return Err(span_verify_error(content));
+                            return Err(span_diagnostic(
+                                content,
+                                format!("expected whitespace before value group entry #{}", entries.len() + 1),
+                            ));
                         }
                     }
                     entries.push(entry);
@@ -171,6 +195,29 @@ impl CobValueGroup
 
         Ok(())
     }
+
+    pub fn apply_macro_params(&mut self, bindings: &CobMacroBindings) -> Result<(), String>
+    {
+        let mut idx = 0;
+        while idx < self.entries.len() {
+            // If the entry is replaced with a group of values, they need to be flattened into this group.
+            let Some(group) = self.entries[idx].apply_macro_params(bindings)? else {
+                idx += 1;
+                continue;
+            };
+
+            // Remove the old entry.
+            self.entries.remove(idx);
+
+            // Flatten the group into the outer group.
+            for val in group {
+                self.entries.insert(idx, val);
+                idx += 1;
+            }
+        }
+
+        Ok(())
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -143,11 +143,23 @@ impl serde::Serializer for CobValueSerializer
     }
 
     #[inline]
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> CobResult<CobValue>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> CobResult<CobValue>
     where
         T: ?Sized + Serialize,
     {
-        value.serialize(self)
+        let value = value.serialize(self)?;
+
+        // Localized text is written as `loc()`.
+        if name == CobLocalized::NEWTYPE_NAME {
+            let CobValue::String(request) = value else {
+                return Err(CobError::Message(
+                    format!("expected localization request string; found {:?}", value),
+                ));
+            };
+            return Ok(CobValue::Localized(CobLocalized::from_request(request.as_str())));
+        }
+
+        Ok(value)
     }
 
     fn serialize_newtype_variant<T>(
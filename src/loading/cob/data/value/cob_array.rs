@@ -51,9 +51,10 @@ impl CobArray
                 (Some(entry), next_fill, after_entry) => {
                     if entries.len() > 0 {
                         if fill_len == 0 {
-                            tracing::warn!("failed parsing array at {}; entry #{} is not preceded by fill/whitespace",
-                                get_location(content), entries.len() + 1);
-                            return Err(span_verify_error(content));
+                            return Err(span_diagnostic(
+                                content,
+                                format!("expected whitespace before array entry #{}", entries.len() + 1),
+                            ));
                         }
                     }
                     entries.push(entry);
@@ -104,6 +105,11 @@ impl CobArray
                                 an array, the group contains a key-value pair which is incompatible with arrays",
                                 constant.path.as_str())
                             }
+                            CobValue::DataMacro(call) => {
+                                format!("failed flattening value group from data macro {}! into \
+                                an array, the group contains a key-value pair which is incompatible with arrays",
+                                call.path.as_str())
+                            }
                             _ => format!("failed flattening {{source unknown}} value group into \
                                 an array, the group contains a key-value pair which is incompatible with arrays"),
                         };
@@ -119,6 +125,56 @@ impl CobArray
 
         Ok(())
     }
+
+    pub fn apply_macro_params(&mut self, bindings: &CobMacroBindings) -> Result<(), String>
+    {
+        let mut idx = 0;
+        while idx < self.entries.len() {
+            // If the entry is replaced with a group of values, they need to be flattened into this array.
+            let Some(group) = self.entries[idx].apply_macro_params(bindings)? else {
+                idx += 1;
+                continue;
+            };
+
+            // Remove the old entry.
+            self.entries.remove(idx);
+
+            // Flatten the group into the array.
+            for val in group {
+                match val {
+                    CobValueGroupEntry::KeyValue(_) => {
+                        return Err("failed flattening macro param into an array, the param contains a \
+                            key-value pair which is incompatible with arrays"
+                            .into());
+                    }
+                    CobValueGroupEntry::Value(val) => {
+                        self.entries.insert(idx, val);
+                        idx += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn resolve_font_units(&mut self, sizes: &CobFontSizes) -> Result<bool, String>
+    {
+        let mut found = false;
+        for entry in self.entries.iter_mut() {
+            found |= entry.resolve_font_units(sizes)?;
+        }
+        Ok(found)
+    }
+
+    pub fn resolve_theme(&mut self, theme: Option<&str>) -> Result<bool, String>
+    {
+        let mut found = false;
+        for entry in self.entries.iter_mut() {
+            found |= entry.resolve_theme(theme)?;
+        }
+        Ok(found)
+    }
 }
 
 impl From<Vec<CobValue>> for CobArray
@@ -4,20 +4,23 @@ use nom::character::complete::char;
 use nom::combinator::{value, verify};
 use nom::error::ErrorKind;
 use nom::{AsChar, IResult, InputLength, InputTake, InputTakeAtPosition, Parser};
+use smol_str::SmolStr;
 
 use crate::prelude::*;
 
 //-------------------------------------------------------------------------------------------------------------------
 
 /// Converts a color field number to a pair of hex digits if there is no precision loss.
-fn to_hex_int(num: f64) -> Option<u8>
+fn to_hex_int(num: f32) -> Option<u8>
 {
-    let converted = (num * 255.0f64) as u8;
-    let left = num;
-    let right = (converted as f64) / (255.0f64);
-    let diff = (left - right) as f32;
+    let converted = (num * 255.).round();
+    if !(0.0..=255.0).contains(&converted) {
+        return None;
+    }
+    let converted = converted as u8;
 
-    if diff.is_subnormal() || diff == 0.0 {
+    // Must match how hex digits are parsed.
+    if (converted as f32) / 255. == num {
         Some(converted)
     } else {
         None
@@ -69,14 +72,147 @@ fn parse_hex_u32(input: Span) -> IResult<Span, u32>
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Argument of a color function like `rgb(255 0 0)`.
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum CobColorArg
+{
+    Number(f32),
+    /// A percentage, e.g. `50%` is `Percent(50.)`.
+    Percent(f32),
+}
+
+impl CobColorArg
+{
+    /// Numbers are divided by `max`, percentages are divided by 100.
+    fn fraction(self, max: f32) -> f32
+    {
+        match self {
+            Self::Number(number) => number / max,
+            Self::Percent(percent) => percent / 100.,
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) const COLOR_FUNCTIONS: [&str; 4] = ["rgb", "rgba", "hsl", "hsla"];
+
+/// Makes a color from the arguments of a color function.
+///
+/// - `rgb(red green blue [alpha])`: Channels are numbers from `0` to `255` or percentages.
+/// - `hsl(hue saturation lightness [alpha])`: Hue is in degrees. Saturation and lightness are numbers from `0` to
+///   `1` or percentages.
+///
+/// Alphas are numbers from `0` to `1` or percentages. `rgba` and `hsla` are aliases of `rgb` and `hsl`.
+pub(crate) fn color_from_function(name: &str, args: &[CobColorArg]) -> Result<Srgba, String>
+{
+    if args.len() < 3 || args.len() > 4 {
+        return Err(format!("{name}() expects 3 to 4 arguments; found {}", args.len()));
+    }
+    let alpha = args.get(3).map(|alpha| alpha.fraction(1.)).unwrap_or(1.);
+
+    match name {
+        "rgb" | "rgba" => Ok(Srgba::new(
+            args[0].fraction(255.),
+            args[1].fraction(255.),
+            args[2].fraction(255.),
+            alpha,
+        )),
+        "hsl" | "hsla" => {
+            let CobColorArg::Number(hue) = args[0] else {
+                return Err(format!("expected a number for the hue in {name}(); found a percentage"));
+            };
+            Ok(Srgba::from(Hsla::new(
+                hue,
+                args[1].fraction(1.),
+                args[2].fraction(1.),
+                alpha,
+            )))
+        }
+        _ => Err(format!("unknown color function {name}()")),
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Writes `#RRGGBB`, or `#RRGGBBAA` if alpha is not 1. If `short` is set, writes `#RGB` or `#RGBA` when possible.
+fn write_hex(color: Srgba, short: bool, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
+{
+    let mut channels = vec![color.red, color.green, color.blue];
+    if color.alpha != 1.0 {
+        channels.push(color.alpha);
+    }
+    let channels: Vec<u8> = channels
+        .into_iter()
+        .map(|channel| (channel * 255.).round() as u8)
+        .collect();
+
+    writer.write_bytes("#".as_bytes())?;
+    if short && channels.iter().all(|channel| channel % 17 == 0) {
+        for channel in channels {
+            write!(writer, "{:X}", channel / 17)?;
+        }
+    } else {
+        for channel in channels {
+            write_num_as_hex(channel, writer)?;
+        }
+    }
+    Ok(())
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Converts a parsed argument of a color function literal.
+///
+/// Returns `None` if the argument isn't a number or percentage.
+fn literal_color_arg(value: &CobValue) -> Option<CobColorArg>
+{
+    match value {
+        CobValue::Number(number) => Some(CobColorArg::Number(number.number.as_f64()? as f32)),
+        CobValue::Builtin(CobBuiltin::Val { val: Val::Percent(percent), .. }) => {
+            Some(CobColorArg::Percent(*percent))
+        }
+        _ => None,
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// How a [`CobColor`] is written.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub enum CobColorSyntax
+{
+    /// `#RRGGBB`, or `#RRGGBBAA` if alpha is not 1.
+    #[default]
+    Hex,
+    /// `#RGB`, or `#RGBA` if alpha is not 1. Written as [`Self::Hex`] if the color can't be shortened.
+    ShortHex,
+    /// A CSS color name like `aliceblue`.
+    Named(SmolStr),
+    /// `rgb`, `rgba`, `hsl`, or `hsla` with number and percentage arguments, e.g. `rgb(255 0 0)`.
+    Function
+    {
+        name: SmolStr, args: CobTuple
+    },
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A color literal.
+///
+/// Colors can be written as hex (`#RGB`, `#RGBA`, `#RRGGBB`, `#RRGGBBAA`), CSS color names (`aliceblue`), or color
+/// functions with literal arguments (`rgb(255 0 0)`, `rgba(255 0 0 50%)`, `hsl(120 50% 50%)`, `hsla(..)`). Color
+/// functions with non-literal arguments like `rgb($r 0 0)` are parsed as [`CobFunctionCall`] instead.
 #[derive(Debug, Clone, PartialEq)]
-pub struct CobHexColor
+pub struct CobColor
 {
     pub fill: CobFill,
     pub color: Srgba,
+    /// The syntax the color was parsed from, used when writing the color.
+    pub syntax: CobColorSyntax,
 }
 
-impl CobHexColor
+impl CobColor
 {
     pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
     {
@@ -86,63 +222,198 @@ impl CobHexColor
     pub fn write_to_with_space(&self, writer: &mut impl RawSerializer, space: &str) -> Result<(), std::io::Error>
     {
         self.fill.write_to_or_else(writer, space)?;
-        writer.write_bytes("#".as_bytes()).unwrap();
-        if self.color.alpha != 1.0 {
-            write_num_as_hex((self.color.alpha * 255.) as u8, writer)?;
+        match &self.syntax {
+            CobColorSyntax::Hex => write_hex(self.color, false, writer)?,
+            CobColorSyntax::ShortHex => write_hex(self.color, true, writer)?,
+            CobColorSyntax::Named(name) => writer.write_bytes(name.as_bytes())?,
+            CobColorSyntax::Function { name, args } => {
+                writer.write_bytes(name.as_bytes())?;
+                args.write_to(writer)?;
+            }
         }
-        write_num_as_hex((self.color.red * 255.) as u8, writer)?;
-        write_num_as_hex((self.color.green * 255.) as u8, writer)?;
-        write_num_as_hex((self.color.blue * 255.) as u8, writer)?;
         Ok(())
     }
 
     pub fn try_parse(fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
     {
-        let Ok((remaining, _)) = char::<_, ()>('#').parse(content) else { return Ok((None, fill, content)) };
+        if let Ok((remaining, _)) = char::<_, ()>('#').parse(content) {
+            return Self::parse_hex(fill, content, remaining);
+        }
+
+        let Ok((after_name, name)) = snake_identifier(content) else { return Ok((None, fill, content)) };
+        let name = *name.fragment();
+
+        // Color function literal
+        if COLOR_FUNCTIONS.contains(&name) && char::<_, ()>('(').parse(after_name).is_ok() {
+            let (Some(args), next_fill, remaining) =
+                rc(after_name, move |rm| CobTuple::try_parse(CobFill::default(), rm))?
+            else {
+                return Ok((None, fill, content));
+            };
+            let Some(literal_args) = args
+                .entries
+                .iter()
+                .map(literal_color_arg)
+                .collect::<Option<Vec<_>>>()
+            else {
+                // Not a literal, e.g. `rgb($r 0 0)`.
+                return Ok((None, fill, content));
+            };
+            let color = color_from_function(name, &literal_args).map_err(|err| span_diagnostic(content, err))?;
+            let syntax = CobColorSyntax::Function { name: SmolStr::from(name), args };
+            return Ok((Some(Self { fill, color, syntax }), next_fill, remaining));
+        }
+
+        // Named color
+        let Some(color) = css_color(name) else { return Ok((None, fill, content)) };
+        // Struct fields, macro calls, and function calls can look like color names.
+        if after_name.fragment().starts_with(['!', '(', ':']) {
+            return Ok((None, fill, content));
+        }
+        let (next_fill, remaining) = CobFill::parse(after_name);
+        if remaining.fragment().starts_with(':') {
+            return Ok((None, fill, content));
+        }
+        let syntax = CobColorSyntax::Named(SmolStr::from(name));
+        Ok((Some(Self { fill, color, syntax }), next_fill, remaining))
+    }
+
+    fn parse_hex<'a>(
+        fill: CobFill,
+        content: Span<'a>,
+        remaining: Span<'a>,
+    ) -> Result<(Option<Self>, CobFill, Span<'a>), SpanError<'a>>
+    {
         let start_len = remaining.input_len();
         let (remaining, digits) = parse_hex_u32(remaining)?;
         let end_len = remaining.input_len();
 
         let len = start_len.saturating_sub(end_len);
-        if len != 8 && len != 6 {
-            tracing::warn!("failed parsing hex color at {}; hex length is {} but expected 6 or 8",
-                get_location(content), len);
-            return Err(span_verify_error(content));
-        }
+        let (syntax, num_channels, bits) = match len {
+            3 | 4 => (CobColorSyntax::ShortHex, len, 4),
+            6 | 8 => (CobColorSyntax::Hex, len / 2, 8),
+            _ => {
+                return Err(span_diagnostic(
+                    content,
+                    format!("expected 3, 4, 6, or 8 hex digits in color; found {}", len),
+                ));
+            }
+        };
 
-        let mut color = Srgba::default();
-        if len == 8 {
-            color.alpha = (((digits >> 24) as u8) as f32) / 255.;
+        // Channels are in RGBA order. Short hex digits are repeated, e.g. `F` is `FF`.
+        let mut channels = [1.0f32; 4];
+        for (idx, channel) in channels.iter_mut().take(num_channels).enumerate() {
+            let shift = bits * (num_channels - 1 - idx);
+            let mut value = (digits >> shift) & ((1 << bits) - 1);
+            if bits == 4 {
+                value *= 17;
+            }
+            *channel = (value as u8 as f32) / 255.;
         }
-        color.red = (((digits >> 16) as u8) as f32) / 255.;
-        color.green = (((digits >> 8) as u8) as f32) / 255.;
-        color.blue = ((digits as u8) as f32) / 255.;
+        let color = Srgba::new(channels[0], channels[1], channels[2], channels[3]);
 
         let (next_fill, remaining) = CobFill::parse(remaining);
-        Ok((Some(Self { fill, color }), next_fill, remaining))
+        Ok((Some(Self { fill, color, syntax }), next_fill, remaining))
     }
 
     pub fn recover_fill(&mut self, other: &Self)
     {
         self.fill.recover(&other.fill);
+        if let (CobColorSyntax::Function { args, .. }, CobColorSyntax::Function { args: other_args, .. }) =
+            (&mut self.syntax, &other.syntax)
+        {
+            args.recover_fill(other_args);
+        }
     }
 }
 
-impl TryFrom<Srgba> for CobHexColor
+impl TryFrom<Srgba> for CobColor
 {
     type Error = ();
 
     /// Only succeeds if all fields can be converted to hex without precision loss.
     fn try_from(value: Srgba) -> Result<Self, ()>
     {
-        if to_hex_int(value.red as f64).is_none()
-            || to_hex_int(value.green as f64).is_none()
-            || to_hex_int(value.blue as f64).is_none()
-            || to_hex_int(value.alpha as f64).is_none()
+        if to_hex_int(value.red).is_none()
+            || to_hex_int(value.green).is_none()
+            || to_hex_int(value.blue).is_none()
+            || to_hex_int(value.alpha).is_none()
         {
             return Err(());
         }
-        Ok(Self { fill: CobFill::default(), color: value })
+        Ok(Self {
+            fill: CobFill::default(),
+            color: value,
+            syntax: CobColorSyntax::Hex,
+        })
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Splits a number followed by `rem` or `em` from the start of `content`.
+///
+/// This is checked before parsing numbers, since the number parser reads the `e` in `1em` as an exponent.
+fn split_font_unit(content: Span) -> Option<(Span, CobFontUnit, Span)>
+{
+    let len = content
+        .fragment()
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))?;
+    if len == 0 {
+        return None;
+    }
+    let (remaining, number) = content.take_split(len);
+
+    if let Ok((remaining, _)) = tag::<_, _, ()>("rem").parse(remaining) {
+        Some((number, CobFontUnit::Rem, remaining))
+    } else if let Ok((remaining, _)) = tag::<_, _, ()>("em").parse(remaining) {
+        Some((number, CobFontUnit::Em, remaining))
+    } else {
+        None
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Unit of a [`CobBuiltin::FontRelative`] value.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CobFontUnit
+{
+    /// Relative to the [`RootFontSize`].
+    Rem,
+    /// Relative to the scene node's `TextLineSize`, or the [`RootFontSize`] if the node doesn't have one.
+    Em,
+}
+
+impl CobFontUnit
+{
+    pub fn as_str(&self) -> &'static str
+    {
+        match self {
+            Self::Rem => "rem",
+            Self::Em => "em",
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Font sizes that [`CobFontUnit`] values are resolved against.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CobFontSizes
+{
+    pub rem: f32,
+    pub em: f32,
+}
+
+impl CobFontSizes
+{
+    fn get(&self, unit: CobFontUnit) -> f32
+    {
+        match unit {
+            CobFontUnit::Rem => self.rem,
+            CobFontUnit::Em => self.em,
+        }
     }
 }
 
@@ -151,7 +422,7 @@ impl TryFrom<Srgba> for CobHexColor
 #[derive(Debug, Clone, PartialEq)]
 pub enum CobBuiltin
 {
-    Color(CobHexColor),
+    Color(CobColor),
     Val
     {
         fill: CobFill,
@@ -159,6 +430,16 @@ pub enum CobBuiltin
         number: Option<CobNumberValue>,
         val: Val,
     },
+    /// A `rem` or `em` value like `1.5rem`.
+    ///
+    /// These are replaced by `Val::Px` values when scene nodes are loaded, and re-resolved when the
+    /// [`RootFontSize`] changes. They can't be used in `#commands` sections.
+    FontRelative
+    {
+        fill: CobFill,
+        number: CobNumberValue,
+        unit: CobFontUnit,
+    },
 }
 
 impl CobBuiltin
@@ -203,16 +484,19 @@ impl CobBuiltin
                     }
                 }
             }
+            Self::FontRelative { fill, number, unit } => {
+                fill.write_to_or_else(writer, space)?;
+                number.write_to(writer)?;
+                writer.write_bytes(unit.as_str().as_bytes())?;
+            }
         }
         Ok(())
     }
 
     pub fn try_parse(fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
     {
-        // NOTE: recursion not tested here (not vulnerable)
-
-        // Hex color
-        let fill = match CobHexColor::try_parse(fill, content)? {
+        // Color
+        let fill = match CobColor::try_parse(fill, content)? {
             (Some(color), next_fill, remaining) => return Ok((Some(Self::Color(color)), next_fill, remaining)),
             (None, fill, _) => fill,
         };
@@ -225,6 +509,16 @@ impl CobBuiltin
             return Ok((Some(Self::Val { fill, number: None, val }), next_fill, remaining));
         }
 
+        // rem/em
+        if let Some((number_span, unit, remaining)) = split_font_unit(content) {
+            if let Ok((number, rest)) = CobNumberValue::parse(number_span) {
+                if rest.fragment().is_empty() {
+                    let (next_fill, remaining) = CobFill::parse(remaining);
+                    return Ok((Some(Self::FontRelative { fill, number, unit }), next_fill, remaining));
+                }
+            }
+        }
+
         // Val::X(f32)
         let Ok((number, remaining)) = CobNumberValue::parse(content) else { return Ok((None, fill, content)) };
         let get_num = || -> Result<f32, SpanError> {
@@ -302,7 +596,7 @@ impl CobBuiltin
                 }
             }
 
-            return Ok(CobHexColor::try_from(color).map(|c| Self::Color(c)).ok());
+            return Ok(CobColor::try_from(color).map(|c| Self::Color(c)).ok());
         }
 
         if typename == "Val" {
@@ -339,9 +633,27 @@ impl CobBuiltin
             (Self::Val { fill, .. }, Self::Val { fill: other_fill, .. }) => {
                 fill.recover(&other_fill);
             }
+            (Self::FontRelative { fill, .. }, Self::FontRelative { fill: other_fill, .. }) => {
+                fill.recover(&other_fill);
+            }
             _ => (),
         }
     }
+
+    /// Converts `rem` and `em` values to `Val::Px` values.
+    ///
+    /// Returns `true` if the value was converted.
+    pub fn resolve_font_units(&mut self, sizes: &CobFontSizes) -> bool
+    {
+        let Self::FontRelative { fill, number, unit } = self else { return false };
+        let px = number.as_f32_lossy().unwrap_or_default() * sizes.get(*unit);
+        *self = Self::Val {
+            fill: std::mem::take(fill),
+            number: Some(CobNumberValue::Float32(px)),
+            val: Val::Px(px),
+        };
+        true
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
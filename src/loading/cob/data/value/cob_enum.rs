@@ -104,6 +104,36 @@ impl CobEnumVariant
             Self::Map(map) => map.resolve(constants),
         }
     }
+
+    pub fn apply_macro_params(&mut self, bindings: &CobMacroBindings) -> Result<(), String>
+    {
+        match self {
+            Self::Unit => Ok(()),
+            Self::Array(arr) => arr.apply_macro_params(bindings),
+            Self::Tuple(tup) => tup.apply_macro_params(bindings),
+            Self::Map(map) => map.apply_macro_params(bindings),
+        }
+    }
+
+    pub fn resolve_font_units(&mut self, sizes: &CobFontSizes) -> Result<bool, String>
+    {
+        match self {
+            Self::Unit => Ok(false),
+            Self::Array(arr) => arr.resolve_font_units(sizes),
+            Self::Tuple(tup) => tup.resolve_font_units(sizes),
+            Self::Map(map) => map.resolve_font_units(sizes),
+        }
+    }
+
+    pub fn resolve_theme(&mut self, theme: Option<&str>) -> Result<bool, String>
+    {
+        match self {
+            Self::Unit => Ok(false),
+            Self::Array(arr) => arr.resolve_theme(theme),
+            Self::Tuple(tup) => tup.resolve_theme(theme),
+            Self::Map(map) => map.resolve_theme(theme),
+        }
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -151,6 +181,21 @@ impl CobEnum
         self.variant.resolve(constants)
     }
 
+    pub fn apply_macro_params(&mut self, bindings: &CobMacroBindings) -> Result<(), String>
+    {
+        self.variant.apply_macro_params(bindings)
+    }
+
+    pub fn resolve_font_units(&mut self, sizes: &CobFontSizes) -> Result<bool, String>
+    {
+        self.variant.resolve_font_units(sizes)
+    }
+
+    pub fn resolve_theme(&mut self, theme: Option<&str>) -> Result<bool, String>
+    {
+        self.variant.resolve_theme(theme)
+    }
+
     pub fn unit(variant: &str) -> Self
     {
         Self {
@@ -0,0 +1,658 @@
+use std::cmp::Ordering;
+
+use bevy::prelude::*;
+use nom::character::complete::{char, one_of};
+use nom::Parser;
+use smol_str::SmolStr;
+
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+const FUNCTIONS: [&str; 12] = [
+    "min",
+    "max",
+    "clamp",
+    "lighten",
+    "darken",
+    "mix",
+    "with_alpha",
+    "rgb",
+    "rgba",
+    "hsl",
+    "hsla",
+    "oklch",
+];
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// An evaluated expression operand.
+#[derive(Debug, Copy, Clone)]
+enum Operand
+{
+    Number
+    {
+        value: f64,
+        /// Results stay integers if all operands are integers.
+        is_int: bool,
+    },
+    Val(Val),
+    Color(Srgba),
+}
+
+impl Operand
+{
+    fn describe(&self) -> &'static str
+    {
+        match self {
+            Self::Number { .. } => "number",
+            Self::Val(_) => "Val",
+            Self::Color(_) => "color",
+        }
+    }
+
+    /// Converts a resolved value to an operand.
+    ///
+    /// Returns `Ok(None)` if the value contains macro params, which means it can't be evaluated yet.
+    fn from_value(value: &CobValue) -> Result<Option<Self>, String>
+    {
+        match value {
+            CobValue::Number(number) => {
+                let Some(float) = number.number.as_f64() else {
+                    return Err(format!("number {:?} is too large for expressions", number.number));
+                };
+                let is_int = matches!(number.number, CobNumberValue::Uint(_) | CobNumberValue::Int(_));
+                Ok(Some(Self::Number { value: float, is_int }))
+            }
+            CobValue::Builtin(CobBuiltin::Val { val: Val::Auto, .. }) => {
+                Err(String::from("`auto` can't be used in expressions"))
+            }
+            CobValue::Builtin(CobBuiltin::Val { val, .. }) => Ok(Some(Self::Val(*val))),
+            CobValue::Builtin(CobBuiltin::Color(color)) => Ok(Some(Self::Color(color.color))),
+            // Single-entry tuples are used to group sub-expressions, e.g. `($a + 1) * 2`.
+            CobValue::Tuple(tuple) if tuple.entries.len() == 1 => Self::from_value(&tuple.entries[0]),
+            CobValue::Expression(expression) => match expression.evaluate()? {
+                Some(value) => Self::from_value(&value),
+                None => Ok(None),
+            },
+            CobValue::Function(call) => match call.evaluate()? {
+                Some(value) => Self::from_value(&value),
+                None => Ok(None),
+            },
+            // `rem` and `em` values are resolved when scene nodes are loaded.
+            CobValue::Builtin(CobBuiltin::FontRelative { .. }) => Ok(None),
+            CobValue::MacroParam(_) | CobValue::Constant(_) | CobValue::DataMacro(_) => Ok(None),
+            // Themed constants are resolved when the active theme is known.
+            CobValue::Themed(_) => Ok(None),
+            _ => Err(String::from("expected a number, Val, or color in expression")),
+        }
+    }
+
+    fn into_value(self, fill: CobFill) -> CobValue
+    {
+        match self {
+            Self::Number { value, is_int } => {
+                let number = if is_int && value.is_finite() && value.fract() == 0.0 {
+                    if value >= 0.0 {
+                        CobNumberValue::Uint(value as u128)
+                    } else {
+                        CobNumberValue::Int(value as i128)
+                    }
+                } else {
+                    CobNumberValue::Float64(value)
+                };
+                CobValue::Number(CobNumber { fill, number })
+            }
+            Self::Val(val) => CobValue::Builtin(CobBuiltin::Val {
+                fill,
+                number: val_number(val).map(CobNumberValue::Float32),
+                val,
+            }),
+            Self::Color(color) => {
+                CobValue::Builtin(CobBuiltin::Color(CobColor { fill, color, syntax: CobColorSyntax::Hex }))
+            }
+        }
+    }
+
+    fn number(&self, what: &str) -> Result<f64, String>
+    {
+        match self {
+            Self::Number { value, .. } => Ok(*value),
+            _ => Err(format!("expected a number for {what}; found {}", self.describe())),
+        }
+    }
+
+    /// Numbers are used as-is, percentages are divided by 100.
+    fn fraction(&self, what: &str) -> Result<f32, String>
+    {
+        match self {
+            Self::Number { value, .. } => Ok(*value as f32),
+            Self::Val(Val::Percent(percent)) => Ok(*percent / 100.),
+            _ => Err(format!("expected a number or percentage for {what}; found {}", self.describe())),
+        }
+    }
+
+    fn color(&self, what: &str) -> Result<Srgba, String>
+    {
+        match self {
+            Self::Color(color) => Ok(*color),
+            _ => Err(format!("expected a color for {what}; found {}", self.describe())),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn val_number(val: Val) -> Option<f32>
+{
+    match val {
+        Val::Auto => None,
+        Val::Px(n) | Val::Percent(n) | Val::Vw(n) | Val::Vh(n) | Val::VMin(n) | Val::VMax(n) => Some(n),
+    }
+}
+
+fn map_val(val: Val, f: impl FnOnce(f32) -> f32) -> Val
+{
+    match val {
+        Val::Auto => Val::Auto,
+        Val::Px(n) => Val::Px(f(n)),
+        Val::Percent(n) => Val::Percent(f(n)),
+        Val::Vw(n) => Val::Vw(f(n)),
+        Val::Vh(n) => Val::Vh(f(n)),
+        Val::VMin(n) => Val::VMin(f(n)),
+        Val::VMax(n) => Val::VMax(f(n)),
+    }
+}
+
+fn same_unit(a: Val, b: Val) -> bool
+{
+    std::mem::discriminant(&a) == std::mem::discriminant(&b)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn apply_operator(left: Operand, op: CobOperator, right: Operand) -> Result<Operand, String>
+{
+    let result = match (left, op, right) {
+        (Operand::Number { value: a, is_int: a_int }, op, Operand::Number { value: b, is_int: b_int }) => {
+            let value = match op {
+                CobOperator::Add => a + b,
+                CobOperator::Subtract => a - b,
+                CobOperator::Multiply => a * b,
+                CobOperator::Divide => {
+                    if b == 0.0 {
+                        return Err(String::from("division by zero in expression"));
+                    }
+                    a / b
+                }
+            };
+            Operand::Number { value, is_int: a_int && b_int }
+        }
+        (Operand::Val(val), CobOperator::Multiply, Operand::Number { value, .. })
+        | (Operand::Number { value, .. }, CobOperator::Multiply, Operand::Val(val)) => {
+            Operand::Val(map_val(val, |n| n * value as f32))
+        }
+        (Operand::Val(val), CobOperator::Divide, Operand::Number { value, .. }) => {
+            if value == 0.0 {
+                return Err(String::from("division by zero in expression"));
+            }
+            Operand::Val(map_val(val, |n| n / value as f32))
+        }
+        (Operand::Val(a), CobOperator::Add | CobOperator::Subtract, Operand::Val(b)) => {
+            if !same_unit(a, b) {
+                return Err(format!("can't combine {a:?} and {b:?} in an expression; units must match"));
+            }
+            let b = val_number(b).unwrap_or_default();
+            match op {
+                CobOperator::Add => Operand::Val(map_val(a, |n| n + b)),
+                _ => Operand::Val(map_val(a, |n| n - b)),
+            }
+        }
+        (left, op, right) => {
+            return Err(format!(
+                "can't apply `{}` to {} and {}",
+                op.as_str(),
+                left.describe(),
+                right.describe()
+            ));
+        }
+    };
+    Ok(result)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn compare(a: &Operand, b: &Operand) -> Result<Ordering, String>
+{
+    let (a, b) = match (a, b) {
+        (Operand::Number { value: a, .. }, Operand::Number { value: b, .. }) => (*a, *b),
+        (Operand::Val(a), Operand::Val(b)) if same_unit(*a, *b) => (
+            val_number(*a).unwrap_or_default() as f64,
+            val_number(*b).unwrap_or_default() as f64,
+        ),
+        _ => {
+            return Err(format!(
+                "can't compare {} and {}; expected numbers or Vals with the same unit",
+                a.describe(),
+                b.describe()
+            ))
+        }
+    };
+    Ok(a.total_cmp(&b))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn evaluate_function(name: &str, args: &[Operand]) -> Result<Operand, String>
+{
+    let expect_args = |min: usize, max: usize| -> Result<(), String> {
+        if args.len() < min || args.len() > max {
+            let expected = if min == max {
+                format!("{min}")
+            } else {
+                format!("{min} to {max}")
+            };
+            return Err(format!("{name}() expects {expected} arguments; found {}", args.len()));
+        }
+        Ok(())
+    };
+
+    let result = match name {
+        "min" | "max" => {
+            expect_args(1, usize::MAX)?;
+            let mut result = args[0];
+            for arg in args[1..].iter() {
+                let ordering = compare(arg, &result)?;
+                if (name == "min" && ordering.is_lt()) || (name == "max" && ordering.is_gt()) {
+                    result = *arg;
+                }
+            }
+            result
+        }
+        "clamp" => {
+            expect_args(3, 3)?;
+            let (value, min, max) = (args[0], args[1], args[2]);
+            if compare(&value, &min)?.is_lt() {
+                min
+            } else if compare(&value, &max)?.is_gt() {
+                max
+            } else {
+                value
+            }
+        }
+        "lighten" | "darken" => {
+            expect_args(2, 2)?;
+            let color = Oklcha::from(Color::from(args[0].color("the first argument")?));
+            let amount = args[1].fraction("the amount")?;
+            let color = if name == "lighten" {
+                color.lighter(amount)
+            } else {
+                color.darker(amount)
+            };
+            Operand::Color(Srgba::from(Color::from(color)))
+        }
+        "mix" => {
+            expect_args(3, 3)?;
+            let a = Oklaba::from(Color::from(args[0].color("the first argument")?));
+            let b = Oklaba::from(Color::from(args[1].color("the second argument")?));
+            let factor = args[2].fraction("the mix factor")?;
+            Operand::Color(Srgba::from(Color::from(a.mix(&b, factor))))
+        }
+        "with_alpha" => {
+            expect_args(2, 2)?;
+            let color = args[0].color("the first argument")?;
+            Operand::Color(color.with_alpha(args[1].fraction("the alpha")?))
+        }
+        "rgb" | "rgba" | "hsl" | "hsla" => {
+            let args = args
+                .iter()
+                .map(|arg| match arg {
+                    Operand::Number { value, .. } => Ok(CobColorArg::Number(*value as f32)),
+                    Operand::Val(Val::Percent(percent)) => Ok(CobColorArg::Percent(*percent)),
+                    _ => Err(format!("expected a number or percentage in {name}(); found {}", arg.describe())),
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            Operand::Color(color_from_function(name, &args)?)
+        }
+        "oklch" => {
+            expect_args(3, 4)?;
+            let lightness = args[0].fraction("the lightness")?;
+            let chroma = args[1].number("the chroma")? as f32;
+            let hue = args[2].number("the hue")? as f32;
+            let alpha = args.get(3).map(|a| a.fraction("the alpha")).transpose()?;
+            let color = Oklcha::new(lightness, chroma, hue, alpha.unwrap_or(1.));
+            Operand::Color(Srgba::from(Color::from(color)))
+        }
+        _ => return Err(format!("unknown function {name}()")),
+    };
+    Ok(result)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Arithmetic operator in a [`CobExpression`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CobOperator
+{
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl CobOperator
+{
+    pub fn as_str(&self) -> &'static str
+    {
+        match self {
+            Self::Add => "+",
+            Self::Subtract => "-",
+            Self::Multiply => "*",
+            Self::Divide => "/",
+        }
+    }
+
+    fn parse(content: Span) -> Option<(Self, Span)>
+    {
+        let (remaining, op) = one_of::<_, _, ()>("+-*/").parse(content).ok()?;
+        let op = match op {
+            '+' => Self::Add,
+            '-' => Self::Subtract,
+            '*' => Self::Multiply,
+            _ => Self::Divide,
+        };
+        Some((op, remaining))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// An operator and the value to its right in a [`CobExpression`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobExpressionTerm
+{
+    /// Fill before the operator.
+    pub op_fill: CobFill,
+    pub op: CobOperator,
+    pub value: CobValue,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// An arithmetic expression, e.g. `$gap * 2`.
+///
+/// Operands can be numbers, `Val`s, and colors (in function calls), or constants and macro params that resolve
+/// to them. `*` and `/` bind tighter than `+` and `-`, and single-entry tuples can be used for grouping, e.g.
+/// `($gap + 2px) * 2`. Operators must be surrounded by whitespace so `$a -1` is not confused with `$a - 1`.
+///
+/// Expressions are evaluated when they are resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobExpression
+{
+    pub first: Box<CobValue>,
+    pub terms: Vec<CobExpressionTerm>,
+}
+
+impl CobExpression
+{
+    pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
+    {
+        self.write_to_with_space(writer, "")
+    }
+
+    pub fn write_to_with_space(&self, writer: &mut impl RawSerializer, space: &str) -> Result<(), std::io::Error>
+    {
+        self.first.write_to_with_space(writer, space)?;
+        for term in self.terms.iter() {
+            term.op_fill.write_to_or_else(writer, " ")?;
+            writer.write_bytes(term.op.as_str().as_bytes())?;
+            term.value.write_to_with_space(writer, " ")?;
+        }
+        Ok(())
+    }
+
+    /// Tries to extend `first` into an expression if it's followed by an operator.
+    ///
+    /// Returns `first` unchanged if there is no operator.
+    pub fn try_extend(
+        first: CobValue,
+        fill: CobFill,
+        content: Span,
+    ) -> Result<(CobValue, CobFill, Span), SpanError>
+    {
+        let mut terms = vec![];
+        let mut fill = fill;
+        let mut remaining = content;
+
+        loop {
+            // Operators must be surrounded by whitespace.
+            if fill.len() == 0 {
+                break;
+            }
+            let Some((op, after_op)) = CobOperator::parse(remaining) else { break };
+            let (value_fill, after_fill) = CobFill::parse(after_op);
+            if value_fill.len() == 0 {
+                break;
+            }
+
+            let (Some(value), next_fill, after_value) = CobValue::try_parse_operand(value_fill, after_fill)?
+            else {
+                return Err(span_diagnostic(
+                    after_fill,
+                    format!("expected a value after `{}` in expression", op.as_str()),
+                ));
+            };
+            terms.push(CobExpressionTerm { op_fill: fill, op, value });
+            fill = next_fill;
+            remaining = after_value;
+        }
+
+        if terms.is_empty() {
+            return Ok((first, fill, remaining));
+        }
+        Ok((
+            CobValue::Expression(Self { first: Box::new(first), terms }),
+            fill,
+            remaining,
+        ))
+    }
+
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        self.first.recover_fill(&other.first);
+        for (term, other_term) in self.terms.iter_mut().zip(other.terms.iter()) {
+            term.op_fill.recover(&other_term.op_fill);
+            term.value.recover_fill(&other_term.value);
+        }
+    }
+
+    fn values_mut(&mut self) -> impl Iterator<Item = &mut CobValue>
+    {
+        std::iter::once(&mut *self.first).chain(self.terms.iter_mut().map(|term| &mut term.value))
+    }
+
+    /// Resolves constants and data macros in the expression's operands.
+    pub fn resolve(&mut self, constants: &ConstantsBuffer) -> Result<(), String>
+    {
+        for value in self.values_mut() {
+            if value.resolve(constants)?.is_some() {
+                return Err(String::from("value groups can't be used in expressions"));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn apply_macro_params(&mut self, bindings: &CobMacroBindings) -> Result<(), String>
+    {
+        for value in self.values_mut() {
+            if value.apply_macro_params(bindings)?.is_some() {
+                return Err(String::from("macro params in expressions must be single values"));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn resolve_font_units(&mut self, sizes: &CobFontSizes) -> Result<bool, String>
+    {
+        let mut found = false;
+        for value in self.values_mut() {
+            found |= value.resolve_font_units(sizes)?;
+        }
+        Ok(found)
+    }
+
+    pub fn resolve_theme(&mut self, theme: Option<&str>) -> Result<bool, String>
+    {
+        let mut found = false;
+        for value in self.values_mut() {
+            found |= value.resolve_theme(theme)?;
+        }
+        Ok(found)
+    }
+
+    /// Evaluates the expression.
+    ///
+    /// Returns `Ok(None)` if the expression still contains constants, data macros, macro params, themed constants,
+    /// or `rem`/`em` values.
+    pub fn evaluate(&self) -> Result<Option<CobValue>, String>
+    {
+        let Some(first) = Operand::from_value(&self.first)? else { return Ok(None) };
+
+        // Apply `*` and `/` first, then `+` and `-` from left to right.
+        let mut sums = vec![(CobOperator::Add, first)];
+        for term in self.terms.iter() {
+            let Some(operand) = Operand::from_value(&term.value)? else { return Ok(None) };
+            match term.op {
+                CobOperator::Multiply | CobOperator::Divide => {
+                    let (op, left) = sums.pop().unwrap();
+                    sums.push((op, apply_operator(left, term.op, operand)?));
+                }
+                CobOperator::Add | CobOperator::Subtract => sums.push((term.op, operand)),
+            }
+        }
+
+        let mut sums = sums.into_iter();
+        let (_, mut result) = sums.next().unwrap();
+        for (op, operand) in sums {
+            result = apply_operator(result, op, operand)?;
+        }
+
+        Ok(Some(result.into_value(value_fill(&self.first))))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Gets the fill before a value.
+fn value_fill(value: &CobValue) -> CobFill
+{
+    match value {
+        CobValue::Enum(value) => value.fill.clone(),
+        CobValue::Builtin(CobBuiltin::Color(color)) => color.fill.clone(),
+        CobValue::Builtin(CobBuiltin::Val { fill, .. }) => fill.clone(),
+        CobValue::Builtin(CobBuiltin::FontRelative { fill, .. }) => fill.clone(),
+        CobValue::Array(array) => array.start_fill.clone(),
+        CobValue::Tuple(tuple) => tuple.start_fill.clone(),
+        CobValue::Map(map) => map.start_fill.clone(),
+        CobValue::Number(number) => number.fill.clone(),
+        CobValue::Bool(value) => value.fill.clone(),
+        CobValue::None(value) => value.fill.clone(),
+        CobValue::String(string) => string.fill.clone(),
+        CobValue::Constant(constant) => constant.start_fill.clone(),
+        CobValue::DataMacro(call) => call.start_fill.clone(),
+        CobValue::MacroParam(param) => param.start_fill.clone(),
+        CobValue::Expression(expression) => value_fill(&expression.first),
+        CobValue::Function(call) => call.fill.clone(),
+        CobValue::Localized(localized) => localized.fill.clone(),
+        CobValue::Themed(themed) => themed.fill.clone(),
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A call to a built-in function, e.g. `min($a 10px)` or `lighten(#FF0000 20%)`.
+///
+/// Available functions:
+/// - `min(a b ..)`, `max(a b ..)`, `clamp(value min max)`: Numbers or `Val`s with the same unit.
+/// - `lighten(color amount)`, `darken(color amount)`: Changes a color's lightness in the Oklch color space.
+/// - `mix(a b factor)`: Mixes two colors in the Oklab color space. A factor of `0` gives `a`.
+/// - `with_alpha(color alpha)`: Replaces a color's alpha.
+/// - `rgb(..)`, `rgba(..)`, `hsl(..)`, `hsla(..)`: The same as the [`CobColor`] literals, for arguments that
+///   aren't literals like `rgb($r 0 0)`.
+/// - `oklch(lightness chroma hue)`, `oklch(l c h alpha)`: Makes a color. Hue is in degrees.
+///
+/// Amounts, factors, alphas, saturations, and lightnesses can be numbers from `0` to `1` or percentages.
+///
+/// Function calls are evaluated when they are resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobFunctionCall
+{
+    pub fill: CobFill,
+    pub name: SmolStr,
+    pub args: CobTuple,
+}
+
+impl CobFunctionCall
+{
+    pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
+    {
+        self.write_to_with_space(writer, "")
+    }
+
+    pub fn write_to_with_space(&self, writer: &mut impl RawSerializer, space: &str) -> Result<(), std::io::Error>
+    {
+        self.fill.write_to_or_else(writer, space)?;
+        writer.write_bytes(self.name.as_bytes())?;
+        self.args.write_to(writer)?;
+        Ok(())
+    }
+
+    pub fn try_parse(fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
+    {
+        let Ok((remaining, name)) = snake_identifier(content) else { return Ok((None, fill, content)) };
+        if char::<_, ()>('(').parse(remaining).is_err() {
+            return Ok((None, fill, content));
+        }
+        if !FUNCTIONS.contains(name.fragment()) {
+            return Err(span_diagnostic(
+                content,
+                format!("unknown function {}(); expected one of {}", name.fragment(), FUNCTIONS.join(", ")),
+            ));
+        }
+
+        let (Some(args), next_fill, remaining) =
+            rc(remaining, move |rm| CobTuple::try_parse(CobFill::default(), rm))?
+        else {
+            return Err(span_diagnostic(remaining, "expected function arguments"));
+        };
+
+        Ok((
+            Some(Self { fill, name: SmolStr::from(*name.fragment()), args }),
+            next_fill,
+            remaining,
+        ))
+    }
+
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        self.fill.recover(&other.fill);
+        self.args.recover_fill(&other.args);
+    }
+
+    /// Evaluates the function.
+    ///
+    /// Returns `Ok(None)` if the arguments still contain constants, data macros, macro params, themed constants,
+    /// or `rem`/`em` values.
+    pub fn evaluate(&self) -> Result<Option<CobValue>, String>
+    {
+        let mut args = Vec::with_capacity(self.args.entries.len());
+        for arg in self.args.entries.iter() {
+            let Some(operand) = Operand::from_value(arg)? else { return Ok(None) };
+            args.push(operand);
+        }
+        let result = evaluate_function(self.name.as_str(), &args)?;
+        Ok(Some(result.into_value(self.fill.clone())))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,227 @@
+use nom::bytes::complete::tag;
+use nom::character::complete::char;
+use nom::Parser;
+
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Converts a `loc()` argument to the text used in a localization request.
+fn arg_text(name: &str, value: &CobValue) -> Result<String, String>
+{
+    match value {
+        CobValue::String(string) => Ok(String::from(string.as_str())),
+        CobValue::Bool(value) => Ok(value.value.to_string()),
+        CobValue::Number(number) => {
+            let mut bytes = vec![];
+            number
+                .number
+                .write_to(&mut DefaultRawSerializer::new(&mut bytes))
+                .map_err(|err| err.to_string())?;
+            Ok(String::from_utf8_lossy(&bytes).into_owned())
+        }
+        _ => Err(format!("loc() argument `{}` must be a string, number, or bool", name)),
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Converts text from a localization request back to a `loc()` argument value.
+fn arg_value(text: &str) -> CobValue
+{
+    if let Ok(number) = text.parse::<u128>() {
+        return CobValue::Number(CobNumber::from(number));
+    }
+    if let Ok(number) = text.parse::<i128>() {
+        return CobValue::Number(CobNumber::from(number));
+    }
+    if let Some(number) = text.parse::<f64>().ok().filter(|number| number.is_finite()) {
+        return CobValue::Number(CobNumber::from(number));
+    }
+    match text {
+        "true" => CobValue::Bool(CobBool::from(true)),
+        "false" => CobValue::Bool(CobBool::from(false)),
+        _ => CobValue::String(CobString::from(text)),
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Localized text like `loc("menu-play" count: $n)`.
+///
+/// Deserializes to a [`TextContent::Localized`] with a [`fluent_content::Request`] template like
+/// `"menu-play?count=3"`, or to the template string if deserialized as a `String`. Argument values must resolve
+/// to strings, numbers, or bools.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobLocalized
+{
+    pub fill: CobFill,
+    /// The fluent message ID.
+    pub id: CobString,
+    /// Message arguments. Keys are always [`CobMapKey::FieldName`].
+    pub args: Vec<CobMapKeyValue>,
+    /// Fill before ending `)`.
+    pub end_fill: CobFill,
+}
+
+impl CobLocalized
+{
+    /// Name used when serializing [`TextContent::Localized`] as a newtype struct.
+    ///
+    /// [`CobValueSerializer`] uses it to write the text as `loc()`.
+    pub const NEWTYPE_NAME: &'static str = "CobLocalized";
+
+    pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
+    {
+        self.write_to_with_space(writer, "")
+    }
+
+    pub fn write_to_with_space(&self, writer: &mut impl RawSerializer, space: &str) -> Result<(), std::io::Error>
+    {
+        self.fill.write_to_or_else(writer, space)?;
+        writer.write_bytes("loc(".as_bytes())?;
+        self.id.write_to(writer)?;
+        for arg in self.args.iter() {
+            arg.write_to_with_space(writer, " ")?;
+        }
+        self.end_fill.write_to(writer)?;
+        writer.write_bytes(")".as_bytes())?;
+        Ok(())
+    }
+
+    pub fn try_parse(fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
+    {
+        let Ok((remaining, _)) = tag::<_, _, ()>("loc(").parse(content) else { return Ok((None, fill, content)) };
+
+        let (id_fill, remaining) = CobFill::parse(remaining);
+        let (Some(id), mut item_fill, mut remaining) = rc(remaining, move |rm| CobString::try_parse(id_fill, rm))?
+        else {
+            return Err(span_diagnostic(
+                remaining,
+                "expected message ID string at start of loc()",
+            ));
+        };
+
+        let mut args = vec![];
+        let end_fill = loop {
+            let fill_len = item_fill.len();
+            match rc(remaining, move |rm| CobMapKeyValue::try_parse(item_fill, rm))? {
+                (CobMapKVParseResult::Success(arg), next_fill, after_arg) => {
+                    if !matches!(arg.key, CobMapKey::FieldName { .. }) {
+                        return Err(span_diagnostic(remaining, "expected argument name in loc()"));
+                    }
+                    if fill_len == 0 {
+                        return Err(span_diagnostic(
+                            remaining,
+                            format!("expected whitespace before loc() argument #{}", args.len() + 1),
+                        ));
+                    }
+                    args.push(arg);
+                    item_fill = next_fill;
+                    remaining = after_arg;
+                }
+                (CobMapKVParseResult::KeyNoValue(_), _, _) => {
+                    return Err(span_diagnostic(remaining, "expected `name: value` argument in loc()"));
+                }
+                (CobMapKVParseResult::Failure, end_fill, _) => break end_fill,
+            }
+        };
+
+        let (remaining, _) = char(')').parse(remaining)?;
+        let (post_fill, remaining) = CobFill::parse(remaining);
+        Ok((Some(Self { fill, id, args, end_fill }), post_fill, remaining))
+    }
+
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        self.fill.recover(&other.fill);
+        self.id.recover_fill(&other.id);
+        for (arg, other_arg) in self.args.iter_mut().zip(other.args.iter()) {
+            arg.recover_fill(other_arg);
+        }
+        self.end_fill.recover(&other.end_fill);
+    }
+
+    pub fn resolve(&mut self, constants: &ConstantsBuffer) -> Result<(), String>
+    {
+        for arg in self.args.iter_mut() {
+            arg.resolve(constants)?;
+        }
+        Ok(())
+    }
+
+    pub fn apply_macro_params(&mut self, bindings: &CobMacroBindings) -> Result<(), String>
+    {
+        let mut idx = 0;
+        while idx < self.args.len() {
+            // Arguments are removed if they are optional macro params without values.
+            if self.args[idx].apply_macro_params(bindings)? {
+                idx += 1;
+            } else {
+                self.args.remove(idx);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn resolve_font_units(&mut self, sizes: &CobFontSizes) -> Result<bool, String>
+    {
+        let mut found = false;
+        for arg in self.args.iter_mut() {
+            found |= arg.value.resolve_font_units(sizes)?;
+        }
+        Ok(found)
+    }
+
+    pub fn resolve_theme(&mut self, theme: Option<&str>) -> Result<bool, String>
+    {
+        let mut found = false;
+        for arg in self.args.iter_mut() {
+            found |= arg.value.resolve_theme(theme)?;
+        }
+        Ok(found)
+    }
+
+    /// Makes a [`fluent_content::Request`] template like `"menu-play?count=3"`.
+    ///
+    /// Fails if an argument isn't a string, number, or bool.
+    pub fn request(&self) -> Result<String, String>
+    {
+        let mut request = String::from(self.id.as_str());
+        for (idx, arg) in self.args.iter().enumerate() {
+            let CobMapKey::FieldName { name, .. } = &arg.key else {
+                return Err(String::from("loc() argument names must be field names"));
+            };
+            request.push(if idx == 0 { '?' } else { '&' });
+            request.push_str(name.as_str());
+            request.push('=');
+            request.push_str(&arg_text(name.as_str(), &arg.value)?);
+        }
+        Ok(request)
+    }
+
+    /// Makes a value from a [`fluent_content::Request`] template.
+    ///
+    /// Argument values that look like numbers or bools are converted back to numbers or bools.
+    pub fn from_request(request: &str) -> Self
+    {
+        let (id, args) = request.split_once('?').unwrap_or((request, ""));
+        let args = args
+            .split('&')
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| {
+                let (name, value) = arg.split_once('=').unwrap_or((arg, ""));
+                CobMapKeyValue::struct_field(name, arg_value(value))
+            })
+            .collect();
+
+        Self {
+            fill: CobFill::default(),
+            id: CobString::from(id),
+            args,
+            end_fill: CobFill::default(),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use nom::character::complete::char;
 use nom::Parser;
 use smol_str::SmolStr;
@@ -81,6 +83,10 @@ impl CobMapKey
                             format!("constant ${} in a map entry's key points to value group \
                             but only plain values are allowed", constant.path.as_str())
                         }
+                        CobValue::DataMacro(call) => {
+                            format!("data macro {}! in a map entry's key expands to a value group \
+                            but only plain values are allowed", call.path.as_str())
+                        }
                         _ => format!("{{unknown source}} in a map entry's key points to value group \
                             but only plain values are allowed"),
                     };
@@ -151,8 +157,7 @@ impl CobMapKeyValue
         };
         let (value_fill, remaining) = CobFill::parse(remaining);
         let (Some(value), next_fill, remaining) = rc(remaining, |rm| CobValue::try_parse(value_fill, rm))? else {
-            tracing::warn!("failed parsing value for map entry at {}; no valid value found", get_location(remaining));
-            return Err(span_verify_error(content));
+            return Err(span_diagnostic(content, "expected value after ':' in map entry"));
         };
         Ok((
             CobMapKVParseResult::Success(Self { key, semicolon_fill, value }),
@@ -177,6 +182,10 @@ impl CobMapKeyValue
                     format!("constant ${} in a map entry's value points to value group \
                     but only plain values are allowed", constant.path.as_str())
                 }
+                CobValue::DataMacro(call) => {
+                    format!("data macro {}! in a map entry's value expands to a value group \
+                    but only plain values are allowed", call.path.as_str())
+                }
                 _ => format!("{{unknown source}} in a map entry's value points to value group \
                     but only plain values are allowed"),
             };
@@ -186,6 +195,37 @@ impl CobMapKeyValue
         Ok(())
     }
 
+    /// Replaces macro params with values from a macro call.
+    ///
+    /// Returns `false` if the key-value pair should be removed because an optional macro param has no value.
+    pub fn apply_macro_params(&mut self, bindings: &CobMacroBindings) -> Result<bool, String>
+    {
+        if let CobMapKey::Value(key) = &mut self.key {
+            if let Some(group) = key.apply_macro_params(bindings)? {
+                if group.is_empty() {
+                    return Ok(false);
+                }
+                return Err(
+                    "macro param in a map entry's key points to a value group but only plain values \
+                    are allowed"
+                        .into(),
+                );
+            }
+        }
+        if let Some(group) = self.value.apply_macro_params(bindings)? {
+            if group.is_empty() {
+                return Ok(false);
+            }
+            return Err(
+                "macro param in a map entry's value points to a value group but only plain values \
+                are allowed"
+                    .into(),
+            );
+        }
+
+        Ok(true)
+    }
+
     pub fn struct_field(key: &str, value: CobValue) -> Self
     {
         Self {
@@ -220,6 +260,8 @@ pub enum CobMapEntry
 {
     KeyValue(CobMapKeyValue),
     Constant(CobConstant),
+    /// Data macros in maps must expand to value groups.
+    DataMacro(CobDataMacroCall),
     /// Only catch-all params are allowed.
     MacroParam(CobMacroParam),
 }
@@ -240,6 +282,9 @@ impl CobMapEntry
             Self::Constant(constant) => {
                 constant.write_to_with_space(writer, space)?;
             }
+            Self::DataMacro(call) => {
+                call.write_to_with_space(writer, space)?;
+            }
             Self::MacroParam(param) => {
                 param.write_to_with_space(writer, space)?;
             }
@@ -260,29 +305,33 @@ impl CobMapEntry
                         next_fill,
                         remaining,
                     )),
+                    CobMapKey::Value(CobValue::DataMacro(call)) => {
+                        Ok((CobMapEntryResult::Success(Self::DataMacro(call)), next_fill, remaining))
+                    }
+                    CobMapKey::Value(CobValue::MacroParam(param)) => {
+                        if !param.is_catch_all() {
+                            return Err(span_diagnostic(
+                                content,
+                                "expected catch-all macro param (e.g. `..@name`) in map",
+                            ));
+                        }
+                        Ok((
+                            CobMapEntryResult::Success(Self::MacroParam(param)),
+                            next_fill,
+                            remaining,
+                        ))
+                    }
                     CobMapKey::Value(non_constant_val) => {
                         Ok((CobMapEntryResult::UnusedValue(non_constant_val), next_fill, remaining))
                     }
-                    CobMapKey::FieldName { fill, .. } => Ok((CobMapEntryResult::Failure, fill, content)),
+                    CobMapKey::FieldName { name, .. } => Err(span_diagnostic(
+                        remaining,
+                        format!("expected `:` after struct field `{}`", name.as_str()),
+                    )),
                 };
             }
             (CobMapKVParseResult::Failure, next_fill, _) => next_fill,
         };
-        let fill = match rc(content, move |c| CobMacroParam::try_parse(fill, c))? {
-            (Some(param), next_fill, remaining) => {
-                if !param.is_catch_all() {
-                    tracing::warn!("failed parsing map entry at {}; found macro param that isn't a 'catch all'",
-                        get_location(content));
-                    return Err(span_verify_error(content));
-                }
-                return Ok((
-                    CobMapEntryResult::Success(Self::MacroParam(param)),
-                    next_fill,
-                    remaining,
-                ));
-            }
-            (None, next_fill, _) => next_fill,
-        };
 
         Ok((CobMapEntryResult::Failure, fill, content))
     }
@@ -296,6 +345,9 @@ impl CobMapEntry
             (Self::Constant(constant), Self::Constant(other_constant)) => {
                 constant.recover_fill(other_constant);
             }
+            (Self::DataMacro(call), Self::DataMacro(other_call)) => {
+                call.recover_fill(other_call);
+            }
             (Self::MacroParam(param), Self::MacroParam(other_param)) => {
                 param.recover_fill(other_param);
             }
@@ -306,7 +358,7 @@ impl CobMapEntry
     pub fn resolve<'a>(
         &mut self,
         constants: &'a ConstantsBuffer,
-    ) -> Result<Option<&'a [CobValueGroupEntry]>, String>
+    ) -> Result<Option<Cow<'a, [CobValueGroupEntry]>>, String>
     {
         match self {
             Self::KeyValue(kv) => kv.resolve(constants)?,
@@ -322,14 +374,57 @@ impl CobMapEntry
                         );
                     }
                     CobConstantValue::ValueGroup(group) => {
-                        return Ok(Some(&group.entries));
+                        return Ok(Some(Cow::Borrowed(&group.entries)));
                     }
                 }
             }
+            Self::DataMacro(call) => match call.expand(constants)? {
+                CobConstantValue::Value(_) => {
+                    return Err(
+                        format!("data macro {}! expands to a value but is found in a map where only \
+                        value groups of key-value pairs are allowed", call.path.as_str()),
+                    );
+                }
+                CobConstantValue::ValueGroup(group) => {
+                    return Ok(Some(Cow::Owned(group.entries)));
+                }
+            },
+            // Macro params are left in place so macro definitions can be resolved before they are called.
             Self::MacroParam(param) => {
-                // TODO: need to warn if encountered a param while not resolving a macro call
-                return Err(format!("encountered macro parameter {param:?} in map"));
+                if !constants.in_macro_def() {
+                    return Err(
+                        format!("encountered macro parameter {param:?} in map outside a macro definition"),
+                    );
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Replaces macro params with values from a macro call.
+    ///
+    /// Returns a group of entries if the entry should be replaced (or removed if the group is empty).
+    pub fn apply_macro_params(
+        &mut self,
+        bindings: &CobMacroBindings,
+    ) -> Result<Option<Vec<CobValueGroupEntry>>, String>
+    {
+        match self {
+            Self::KeyValue(kv) => {
+                if !kv.apply_macro_params(bindings)? {
+                    return Ok(Some(vec![]));
+                }
             }
+            Self::Constant(_) | Self::DataMacro(_) => (),
+            Self::MacroParam(param) => match bindings.get(param)? {
+                CobMacroParamValue::Value(_) => {
+                    return Err(
+                        format!("macro param ..@{} in a map points to a value but only value groups of \
+                        key-value pairs are allowed", param.name.as_str()),
+                    );
+                }
+                CobMacroParamValue::Group(group) => return Ok(Some(group.to_vec())),
+            },
         }
         Ok(None)
     }
@@ -399,9 +494,10 @@ impl CobMap
                 (CobMapEntryResult::Success(entry), next_fill, after_entry) => {
                     if entries.len() > 0 {
                         if fill_len == 0 {
-                            tracing::warn!("failed parsing map at {}; entry #{} is not preceded by fill/whitespace",
-                                get_location(content).as_str(), entries.len() + 1);
-                            return Err(span_verify_error(content));
+                            return Err(span_diagnostic(
+                                content,
+                                format!("expected whitespace before map entry #{}", entries.len() + 1),
+                            ));
                         }
                     }
                     entries.push(entry);
@@ -409,9 +505,10 @@ impl CobMap
                     remaining = after_entry;
                 }
                 (CobMapEntryResult::UnusedValue(_), _, _) => {
-                    tracing::warn!("failed parsing map at {}; entry #{} is a value-like key without a value",
-                        get_location(content).as_str(), entries.len() + 1);
-                    return Err(span_verify_error(content));
+                    return Err(span_diagnostic(
+                        content,
+                        format!("expected ':' and a value after map entry #{}", entries.len() + 1),
+                    ));
                 }
                 (CobMapEntryResult::Failure, end_fill, after_end) => {
                     remaining = after_end;
@@ -420,7 +517,9 @@ impl CobMap
             }
         };
 
-        let (remaining, _) = char('}').parse(remaining)?;
+        let Ok((remaining, _)) = char::<_, ()>('}').parse(remaining) else {
+            return Err(span_diagnostic(remaining, "expected map entry or closing `}`"));
+        };
         let (post_fill, remaining) = CobFill::parse(remaining);
         Ok((Some(Self { start_fill, entries, end_fill }), post_fill, remaining))
     }
@@ -461,6 +560,11 @@ impl CobMap
                                 a map, the group contains a plain value which is incompatible with maps",
                                 constant.path.as_str())
                             }
+                            CobMapEntry::DataMacro(call) => {
+                                format!("failed flattening value group from data macro {}! into \
+                                a map, the group contains a plain value which is incompatible with maps",
+                                call.path.as_str())
+                            }
                             _ => format!("failed flattening {{source unknown}} value group into \
                                 a map, the group contains a plain value which is incompatible with maps"),
                         };
@@ -472,6 +576,60 @@ impl CobMap
 
         Ok(())
     }
+
+    pub fn apply_macro_params(&mut self, bindings: &CobMacroBindings) -> Result<(), String>
+    {
+        let mut idx = 0;
+        while idx < self.entries.len() {
+            // If the entry is replaced with a group of values, they need to be flattened into this map.
+            let Some(group) = self.entries[idx].apply_macro_params(bindings)? else {
+                idx += 1;
+                continue;
+            };
+
+            // Remove the old entry.
+            self.entries.remove(idx);
+
+            // Flatten the group into the map.
+            for val in group {
+                match val {
+                    CobValueGroupEntry::KeyValue(kv) => {
+                        self.entries.insert(idx, CobMapEntry::KeyValue(kv));
+                        idx += 1;
+                    }
+                    CobValueGroupEntry::Value(_) => {
+                        return Err(
+                            "failed flattening macro param into a map, the param contains a plain value \
+                            which is incompatible with maps"
+                                .into(),
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn resolve_font_units(&mut self, sizes: &CobFontSizes) -> Result<bool, String>
+    {
+        let mut found = false;
+        for entry in self.entries.iter_mut() {
+            let CobMapEntry::KeyValue(kv) = entry else { continue };
+            found |= kv.value.resolve_font_units(sizes)?;
+        }
+        Ok(found)
+    }
+
+    pub fn resolve_theme(&mut self, theme: Option<&str>) -> Result<bool, String>
+    {
+        let mut found = false;
+        for entry in self.entries.iter_mut() {
+            let CobMapEntry::KeyValue(kv) = entry else { continue };
+            found |= kv.value.resolve_theme(theme)?;
+        }
+        Ok(found)
+    }
 }
 
 impl From<Vec<CobMapEntry>> for CobMap
@@ -61,9 +61,10 @@ impl CobNumberValue
             0 => true,
             1 => false,
             _ => {
-                tracing::warn!("failed parsing number at {}; encountered multiple '-' in a row",
-                    get_location(content));
-                return Err(span_verify_error(content));
+                return Err(span_diagnostic(
+                    content,
+                    "expected number after '-'; found multiple '-' in a row",
+                ));
             }
         };
 
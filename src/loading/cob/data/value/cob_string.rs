@@ -101,10 +101,11 @@ fn parse_fragment(input: Span) -> IResult<Span, StringFragment>
         map(parse_escaped_char, StringFragment::EscapedChar),
         map(parse_new_section, StringFragment::EscapedSpaces),
         map_res(char('\\'), |_| -> Result<StringFragment, SpanError> {
-            tracing::warn!("failed parsing string at {}; invalid escape sequence \
+            Err(span_diagnostic(
+                input,
+                "expected valid escape sequence \
                 (supported: \\n, \\r, \\t, \\b, \\f, \\\\, \\\", \\u{{<unicode hex>}}, \\<newline><spaces>)",
-                get_location(input).as_str());
-            Err(span_verify_error(input))
+            ))
         }),
     ))
     .parse(input)
@@ -0,0 +1,53 @@
+use smol_str::SmolStr;
+
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A constant with values that depend on the [`ActiveTheme`].
+///
+/// This is made when extracting a constant that is overridden by `#theme` sections, so it never appears in parsed
+/// files. It's replaced by the active theme's value when commands and scene nodes are loaded, and written as the
+/// constant it was made from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobThemedValue
+{
+    pub fill: CobFill,
+    /// The constant that was overridden.
+    pub name: CobConstantName,
+    /// Used if the active theme doesn't override the constant.
+    pub default: Box<CobValue>,
+    /// [ theme name : value ]
+    pub themes: Vec<(SmolStr, CobValue)>,
+}
+
+impl CobThemedValue
+{
+    pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
+    {
+        self.write_to_with_space(writer, "")
+    }
+
+    pub fn write_to_with_space(&self, writer: &mut impl RawSerializer, space: &str) -> Result<(), std::io::Error>
+    {
+        self.fill.write_to_or_else(writer, space)?;
+        self.name.write_to(writer)?;
+        Ok(())
+    }
+
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        self.fill.recover(&other.fill);
+    }
+
+    /// Gets the value for a theme.
+    pub fn get(&self, theme: Option<&str>) -> &CobValue
+    {
+        theme
+            .and_then(|theme| self.themes.iter().find(|(name, _)| name == theme))
+            .map(|(_, value)| value)
+            .unwrap_or(&self.default)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use bevy::reflect::serde::TypedReflectSerializer;
 use bevy::reflect::{PartialReflect, Reflect, TypeRegistry};
 use serde::Serialize;
@@ -23,6 +25,14 @@ pub enum CobValue
     DataMacro(CobDataMacroCall),
     /// Only valid inside a macro definition.
     MacroParam(CobMacroParam),
+    /// Arithmetic expression like `$gap * 2`. Replaced by its result when resolved.
+    Expression(CobExpression),
+    /// Built-in function call like `lighten($color 20%)`. Replaced by its result when resolved.
+    Function(CobFunctionCall),
+    /// Localized text like `loc("menu-play" count: $n)`.
+    Localized(CobLocalized),
+    /// Constant overridden by `#theme` sections. Replaced by the [`ActiveTheme`]'s value when loaded.
+    Themed(CobThemedValue),
 }
 
 impl CobValue
@@ -71,11 +81,36 @@ impl CobValue
             Self::MacroParam(val) => {
                 val.write_to_with_space(writer, space)?;
             }
+            Self::Expression(val) => {
+                val.write_to_with_space(writer, space)?;
+            }
+            Self::Function(val) => {
+                val.write_to_with_space(writer, space)?;
+            }
+            Self::Localized(val) => {
+                val.write_to_with_space(writer, space)?;
+            }
+            Self::Themed(val) => {
+                val.write_to_with_space(writer, space)?;
+            }
         }
         Ok(())
     }
 
     pub fn try_parse(fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
+    {
+        let (Some(value), fill, remaining) = Self::try_parse_operand(fill, content)? else {
+            return Ok((None, fill, content));
+        };
+        let (value, fill, remaining) = CobExpression::try_extend(value, fill, remaining)?;
+        Ok((Some(value), fill, remaining))
+    }
+
+    /// Parses a value without checking if it's followed by an expression operator.
+    pub(crate) fn try_parse_operand(
+        fill: CobFill,
+        content: Span,
+    ) -> Result<(Option<Self>, CobFill, Span), SpanError>
     {
         let fill = match rc(content, move |c| CobEnum::try_parse(fill, c))? {
             (Some(value), fill, remaining) => return Ok((Some(Self::Enum(value)), fill, remaining)),
@@ -121,6 +156,15 @@ impl CobValue
             (Some(value), fill, remaining) => return Ok((Some(Self::DataMacro(value)), fill, remaining)),
             (None, fill, _) => fill,
         };
+        // Must come before function calls, which reject unknown function names.
+        let fill = match rc(content, move |c| CobLocalized::try_parse(fill, c))? {
+            (Some(value), fill, remaining) => return Ok((Some(Self::Localized(value)), fill, remaining)),
+            (None, fill, _) => fill,
+        };
+        let fill = match rc(content, move |c| CobFunctionCall::try_parse(fill, c))? {
+            (Some(value), fill, remaining) => return Ok((Some(Self::Function(value)), fill, remaining)),
+            (None, fill, _) => fill,
+        };
         let fill = match rc(content, move |c| CobMacroParam::try_parse(fill, c))? {
             (Some(value), fill, remaining) => return Ok((Some(Self::MacroParam(value)), fill, remaining)),
             (None, fill, _) => fill,
@@ -168,6 +212,18 @@ impl CobValue
             (Self::MacroParam(val), Self::MacroParam(other_val)) => {
                 val.recover_fill(other_val);
             }
+            (Self::Expression(val), Self::Expression(other_val)) => {
+                val.recover_fill(other_val);
+            }
+            (Self::Function(val), Self::Function(other_val)) => {
+                val.recover_fill(other_val);
+            }
+            (Self::Localized(val), Self::Localized(other_val)) => {
+                val.recover_fill(other_val);
+            }
+            (Self::Themed(val), Self::Themed(other_val)) => {
+                val.recover_fill(other_val);
+            }
             _ => (),
         }
     }
@@ -175,7 +231,7 @@ impl CobValue
     pub fn resolve<'a>(
         &mut self,
         constants: &'a ConstantsBuffer,
-    ) -> Result<Option<&'a [CobValueGroupEntry]>, String>
+    ) -> Result<Option<Cow<'a, [CobValueGroupEntry]>>, String>
     {
         match self {
             Self::Enum(val) => val.resolve(constants)?,
@@ -189,23 +245,152 @@ impl CobValue
                 match const_val {
                     CobConstantValue::Value(val) => *self = val.clone(),
                     CobConstantValue::ValueGroup(group) => {
-                        return Ok(Some(&group.entries));
+                        return Ok(Some(Cow::Borrowed(&group.entries)));
                     }
                 }
             }
-            Self::DataMacro(_) => {
-                // TODO
-            }
+            Self::DataMacro(call) => match call.expand(constants)? {
+                CobConstantValue::Value(val) => *self = val,
+                CobConstantValue::ValueGroup(group) => {
+                    return Ok(Some(Cow::Owned(group.entries)));
+                }
+            },
+            // Macro params are left in place so macro definitions can be resolved before they are called.
             Self::MacroParam(param) => {
-                // TODO: need to warn if encountered a param while not resolving a macro call
-                return Err(format!("encountered macro parameter {param:?}"));
+                if !constants.in_macro_def() {
+                    return Err(format!("encountered macro parameter {param:?} outside a macro definition"));
+                }
+            }
+            // Expressions are evaluated once their operands are resolved. They are left in place if they contain
+            // macro params.
+            Self::Expression(expression) => {
+                expression.resolve(constants)?;
+                if let Some(val) = expression.evaluate()? {
+                    *self = val;
+                }
+            }
+            Self::Function(call) => {
+                call.args.resolve(constants)?;
+                if let Some(val) = call.evaluate()? {
+                    *self = val;
+                }
             }
+            Self::Localized(val) => val.resolve(constants)?,
             _ => (),
         }
 
         Ok(None)
     }
 
+    /// Replaces macro params with values from a macro call.
+    ///
+    /// Returns a group of values if the value is a param that should be flattened into its parent structure. The
+    /// group will be empty if the param is optional and has no value.
+    pub fn apply_macro_params(
+        &mut self,
+        bindings: &CobMacroBindings,
+    ) -> Result<Option<Vec<CobValueGroupEntry>>, String>
+    {
+        match self {
+            Self::Enum(val) => val.apply_macro_params(bindings)?,
+            Self::Array(val) => val.apply_macro_params(bindings)?,
+            Self::Tuple(val) => val.apply_macro_params(bindings)?,
+            Self::Map(val) => val.apply_macro_params(bindings)?,
+            Self::MacroParam(param) => match bindings.get(param)? {
+                CobMacroParamValue::Value(val) => *self = val.clone(),
+                CobMacroParamValue::Group(group) => return Ok(Some(group.to_vec())),
+            },
+            Self::Expression(expression) => {
+                expression.apply_macro_params(bindings)?;
+                if let Some(val) = expression.evaluate()? {
+                    *self = val;
+                }
+            }
+            Self::Function(call) => {
+                call.args.apply_macro_params(bindings)?;
+                if let Some(val) = call.evaluate()? {
+                    *self = val;
+                }
+            }
+            Self::Localized(val) => val.apply_macro_params(bindings)?,
+            _ => (),
+        }
+
+        Ok(None)
+    }
+
+    /// Converts `rem` and `em` values to `Val::Px` values, and evaluates expressions and function calls that use
+    /// them.
+    ///
+    /// Returns `true` if any `rem` or `em` values were found.
+    pub fn resolve_font_units(&mut self, sizes: &CobFontSizes) -> Result<bool, String>
+    {
+        let found = match self {
+            Self::Enum(val) => val.resolve_font_units(sizes)?,
+            Self::Builtin(val) => val.resolve_font_units(sizes),
+            Self::Array(val) => val.resolve_font_units(sizes)?,
+            Self::Tuple(val) => val.resolve_font_units(sizes)?,
+            Self::Map(val) => val.resolve_font_units(sizes)?,
+            Self::Expression(expression) => {
+                let found = expression.resolve_font_units(sizes)?;
+                if let Some(val) = expression.evaluate()? {
+                    *self = val;
+                }
+                found
+            }
+            Self::Function(call) => {
+                let found = call.args.resolve_font_units(sizes)?;
+                if let Some(val) = call.evaluate()? {
+                    *self = val;
+                }
+                found
+            }
+            Self::Localized(val) => val.resolve_font_units(sizes)?,
+            _ => false,
+        };
+
+        Ok(found)
+    }
+
+    /// Replaces themed constants with their values for `theme`, and evaluates expressions and function calls
+    /// that use them.
+    ///
+    /// Returns `true` if any themed constants were found.
+    pub fn resolve_theme(&mut self, theme: Option<&str>) -> Result<bool, String>
+    {
+        let found = match self {
+            Self::Enum(val) => val.resolve_theme(theme)?,
+            Self::Array(val) => val.resolve_theme(theme)?,
+            Self::Tuple(val) => val.resolve_theme(theme)?,
+            Self::Map(val) => val.resolve_theme(theme)?,
+            Self::Expression(expression) => {
+                let found = expression.resolve_theme(theme)?;
+                if let Some(val) = expression.evaluate()? {
+                    *self = val;
+                }
+                found
+            }
+            Self::Function(call) => {
+                let found = call.args.resolve_theme(theme)?;
+                if let Some(val) = call.evaluate()? {
+                    *self = val;
+                }
+                found
+            }
+            Self::Localized(val) => val.resolve_theme(theme)?,
+            Self::Themed(themed) => {
+                // Theme values may use other themed constants.
+                let mut val = themed.get(theme).clone();
+                val.resolve_theme(theme)?;
+                *self = val;
+                true
+            }
+            _ => false,
+        };
+
+        Ok(found)
+    }
+
     pub fn extract<T: ?Sized + Serialize>(value: &T) -> CobResult<Self>
     {
         value.serialize(CobValueSerializer)
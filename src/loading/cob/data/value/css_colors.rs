@@ -0,0 +1,175 @@
+use bevy::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Named colors from the CSS4 specification, sorted by name.
+///
+/// Values match the [`css`](bevy::color::palettes::css) palette.
+const CSS_COLORS: [(&str, Srgba); 148] = [
+    ("aliceblue", Srgba::rgb(0.941, 0.973, 1.0)),
+    ("antiquewhite", Srgba::rgb(0.98, 0.922, 0.843)),
+    ("aqua", Srgba::rgb(0.0, 1.0, 1.0)),
+    ("aquamarine", Srgba::rgb(0.498, 1.0, 0.831)),
+    ("azure", Srgba::rgb(0.941, 1.0, 1.0)),
+    ("beige", Srgba::rgb(0.961, 0.961, 0.863)),
+    ("bisque", Srgba::rgb(1.0, 0.894, 0.769)),
+    ("black", Srgba::rgb(0.0, 0.0, 0.0)),
+    ("blanchedalmond", Srgba::rgb(1.0, 0.922, 0.804)),
+    ("blue", Srgba::rgb(0.0, 0.0, 1.0)),
+    ("blueviolet", Srgba::rgb(0.541, 0.169, 0.886)),
+    ("brown", Srgba::rgb(0.647, 0.165, 0.165)),
+    ("burlywood", Srgba::rgb(0.871, 0.722, 0.529)),
+    ("cadetblue", Srgba::rgb(0.373, 0.62, 0.627)),
+    ("chartreuse", Srgba::rgb(0.498, 1.0, 0.0)),
+    ("chocolate", Srgba::rgb(0.824, 0.412, 0.118)),
+    ("coral", Srgba::rgb(1.0, 0.498, 0.314)),
+    ("cornflowerblue", Srgba::rgb(0.392, 0.584, 0.929)),
+    ("cornsilk", Srgba::rgb(1.0, 0.973, 0.863)),
+    ("crimson", Srgba::rgb(0.863, 0.078, 0.235)),
+    ("cyan", Srgba::rgb(0.0, 1.0, 1.0)),
+    ("darkblue", Srgba::rgb(0.0, 0.0, 0.545)),
+    ("darkcyan", Srgba::rgb(0.0, 0.545, 0.545)),
+    ("darkgoldenrod", Srgba::rgb(0.722, 0.525, 0.043)),
+    ("darkgray", Srgba::rgb(0.663, 0.663, 0.663)),
+    ("darkgreen", Srgba::rgb(0.0, 0.392, 0.0)),
+    ("darkgrey", Srgba::rgb(0.663, 0.663, 0.663)),
+    ("darkkhaki", Srgba::rgb(0.741, 0.718, 0.42)),
+    ("darkmagenta", Srgba::rgb(0.545, 0.0, 0.545)),
+    ("darkolivegreen", Srgba::rgb(0.333, 0.42, 0.184)),
+    ("darkorange", Srgba::rgb(1.0, 0.549, 0.0)),
+    ("darkorchid", Srgba::rgb(0.6, 0.196, 0.8)),
+    ("darkred", Srgba::rgb(0.545, 0.0, 0.0)),
+    ("darksalmon", Srgba::rgb(0.914, 0.588, 0.478)),
+    ("darkseagreen", Srgba::rgb(0.561, 0.737, 0.561)),
+    ("darkslateblue", Srgba::rgb(0.282, 0.239, 0.545)),
+    ("darkslategray", Srgba::rgb(0.184, 0.31, 0.31)),
+    ("darkslategrey", Srgba::rgb(0.184, 0.31, 0.31)),
+    ("darkturquoise", Srgba::rgb(0.0, 0.808, 0.82)),
+    ("darkviolet", Srgba::rgb(0.58, 0.0, 0.827)),
+    ("deeppink", Srgba::rgb(1.0, 0.078, 0.576)),
+    ("deepskyblue", Srgba::rgb(0.0, 0.749, 1.0)),
+    ("dimgray", Srgba::rgb(0.412, 0.412, 0.412)),
+    ("dimgrey", Srgba::rgb(0.412, 0.412, 0.412)),
+    ("dodgerblue", Srgba::rgb(0.118, 0.565, 1.0)),
+    ("firebrick", Srgba::rgb(0.698, 0.133, 0.133)),
+    ("floralwhite", Srgba::rgb(1.0, 0.98, 0.941)),
+    ("forestgreen", Srgba::rgb(0.133, 0.545, 0.133)),
+    ("fuchsia", Srgba::rgb(1.0, 0.0, 1.0)),
+    ("gainsboro", Srgba::rgb(0.863, 0.863, 0.863)),
+    ("ghostwhite", Srgba::rgb(0.973, 0.973, 1.0)),
+    ("gold", Srgba::rgb(1.0, 0.843, 0.0)),
+    ("goldenrod", Srgba::rgb(0.855, 0.647, 0.125)),
+    ("gray", Srgba::rgb(0.5019608, 0.5019608, 0.5019608)),
+    ("green", Srgba::rgb(0.0, 0.5019608, 0.0)),
+    ("greenyellow", Srgba::rgb(0.678, 1.0, 0.184)),
+    ("grey", Srgba::rgb(0.502, 0.502, 0.502)),
+    ("honeydew", Srgba::rgb(0.941, 1.0, 0.941)),
+    ("hotpink", Srgba::rgb(1.0, 0.412, 0.706)),
+    ("indianred", Srgba::rgb(0.804, 0.361, 0.361)),
+    ("indigo", Srgba::rgb(0.294, 0.0, 0.51)),
+    ("ivory", Srgba::rgb(1.0, 1.0, 0.941)),
+    ("khaki", Srgba::rgb(0.941, 0.902, 0.549)),
+    ("lavender", Srgba::rgb(0.902, 0.902, 0.98)),
+    ("lavenderblush", Srgba::rgb(1.0, 0.941, 0.961)),
+    ("lawngreen", Srgba::rgb(0.486, 0.988, 0.0)),
+    ("lemonchiffon", Srgba::rgb(1.0, 0.98, 0.804)),
+    ("lightblue", Srgba::rgb(0.678, 0.847, 0.902)),
+    ("lightcoral", Srgba::rgb(0.941, 0.502, 0.502)),
+    ("lightcyan", Srgba::rgb(0.878, 1.0, 1.0)),
+    ("lightgoldenrodyellow", Srgba::rgb(0.98, 0.98, 0.824)),
+    ("lightgray", Srgba::rgb(0.827, 0.827, 0.827)),
+    ("lightgreen", Srgba::rgb(0.565, 0.933, 0.565)),
+    ("lightgrey", Srgba::rgb(0.827, 0.827, 0.827)),
+    ("lightpink", Srgba::rgb(1.0, 0.714, 0.757)),
+    ("lightsalmon", Srgba::rgb(1.0, 0.627, 0.478)),
+    ("lightseagreen", Srgba::rgb(0.125, 0.698, 0.667)),
+    ("lightskyblue", Srgba::rgb(0.529, 0.808, 0.98)),
+    ("lightslategray", Srgba::rgb(0.467, 0.533, 0.6)),
+    ("lightslategrey", Srgba::rgb(0.467, 0.533, 0.6)),
+    ("lightsteelblue", Srgba::rgb(0.69, 0.769, 0.871)),
+    ("lightyellow", Srgba::rgb(1.0, 1.0, 0.878)),
+    ("lime", Srgba::rgb(0.0, 1.0, 0.0)),
+    ("limegreen", Srgba::rgb(0.196, 0.804, 0.196)),
+    ("linen", Srgba::rgb(0.98, 0.941, 0.902)),
+    ("magenta", Srgba::rgb(1.0, 0.0, 1.0)),
+    ("maroon", Srgba::rgb(0.5019608, 0.0, 0.0)),
+    ("mediumaquamarine", Srgba::rgb(0.4, 0.804, 0.667)),
+    ("mediumblue", Srgba::rgb(0.0, 0.0, 0.804)),
+    ("mediumorchid", Srgba::rgb(0.729, 0.333, 0.827)),
+    ("mediumpurple", Srgba::rgb(0.576, 0.439, 0.859)),
+    ("mediumseagreen", Srgba::rgb(0.235, 0.702, 0.443)),
+    ("mediumslateblue", Srgba::rgb(0.482, 0.408, 0.933)),
+    ("mediumspringgreen", Srgba::rgb(0.0, 0.98, 0.604)),
+    ("mediumturquoise", Srgba::rgb(0.282, 0.82, 0.8)),
+    ("mediumvioletred", Srgba::rgb(0.78, 0.082, 0.522)),
+    ("midnightblue", Srgba::rgb(0.098, 0.098, 0.439)),
+    ("mintcream", Srgba::rgb(0.961, 1.0, 0.98)),
+    ("mistyrose", Srgba::rgb(1.0, 0.894, 0.882)),
+    ("moccasin", Srgba::rgb(1.0, 0.894, 0.71)),
+    ("navajowhite", Srgba::rgb(1.0, 0.871, 0.678)),
+    ("navy", Srgba::rgb(0.0, 0.0, 0.5019608)),
+    ("oldlace", Srgba::rgb(0.992, 0.961, 0.902)),
+    ("olive", Srgba::rgb(0.5019608, 0.5019608, 0.0)),
+    ("olivedrab", Srgba::rgb(0.42, 0.557, 0.137)),
+    ("orange", Srgba::rgb(1.0, 0.647, 0.0)),
+    ("orangered", Srgba::rgb(1.0, 0.271, 0.0)),
+    ("orchid", Srgba::rgb(0.855, 0.439, 0.839)),
+    ("palegoldenrod", Srgba::rgb(0.933, 0.91, 0.667)),
+    ("palegreen", Srgba::rgb(0.596, 0.984, 0.596)),
+    ("paleturquoise", Srgba::rgb(0.686, 0.933, 0.933)),
+    ("palevioletred", Srgba::rgb(0.859, 0.439, 0.576)),
+    ("papayawhip", Srgba::rgb(1.0, 0.937, 0.835)),
+    ("peachpuff", Srgba::rgb(1.0, 0.855, 0.725)),
+    ("peru", Srgba::rgb(0.804, 0.522, 0.247)),
+    ("pink", Srgba::rgb(1.0, 0.753, 0.796)),
+    ("plum", Srgba::rgb(0.867, 0.627, 0.867)),
+    ("powderblue", Srgba::rgb(0.69, 0.878, 0.902)),
+    ("purple", Srgba::rgb(0.5019608, 0.0, 0.5019608)),
+    ("rebeccapurple", Srgba::rgb(0.4, 0.2, 0.6)),
+    ("red", Srgba::rgb(1.0, 0.0, 0.0)),
+    ("rosybrown", Srgba::rgb(0.737, 0.561, 0.561)),
+    ("royalblue", Srgba::rgb(0.255, 0.412, 0.882)),
+    ("saddlebrown", Srgba::rgb(0.545, 0.271, 0.075)),
+    ("salmon", Srgba::rgb(0.98, 0.502, 0.447)),
+    ("sandybrown", Srgba::rgb(0.957, 0.643, 0.376)),
+    ("seagreen", Srgba::rgb(0.18, 0.545, 0.341)),
+    ("seashell", Srgba::rgb(1.0, 0.961, 0.933)),
+    ("sienna", Srgba::rgb(0.627, 0.322, 0.176)),
+    ("silver", Srgba::rgb(0.7529412, 0.7529412, 0.7529412)),
+    ("skyblue", Srgba::rgb(0.529, 0.808, 0.922)),
+    ("slateblue", Srgba::rgb(0.416, 0.353, 0.804)),
+    ("slategray", Srgba::rgb(0.439, 0.502, 0.565)),
+    ("slategrey", Srgba::rgb(0.439, 0.502, 0.565)),
+    ("snow", Srgba::rgb(1.0, 0.98, 0.98)),
+    ("springgreen", Srgba::rgb(0.0, 1.0, 0.498)),
+    ("steelblue", Srgba::rgb(0.275, 0.51, 0.706)),
+    ("tan", Srgba::rgb(0.824, 0.706, 0.549)),
+    ("teal", Srgba::rgb(0.0, 0.5019608, 0.5019608)),
+    ("thistle", Srgba::rgb(0.847, 0.749, 0.847)),
+    ("tomato", Srgba::rgb(1.0, 0.388, 0.278)),
+    ("turquoise", Srgba::rgb(0.251, 0.878, 0.816)),
+    ("violet", Srgba::rgb(0.933, 0.51, 0.933)),
+    ("wheat", Srgba::rgb(0.961, 0.871, 0.702)),
+    ("white", Srgba::rgb(1.0, 1.0, 1.0)),
+    ("whitesmoke", Srgba::rgb(0.961, 0.961, 0.961)),
+    ("yellow", Srgba::rgb(1.0, 1.0, 0.0)),
+    ("yellowgreen", Srgba::rgb(0.604, 0.804, 0.196)),
+];
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Looks up a CSS color name like `aliceblue`.
+///
+/// `transparent` is also supported.
+pub(crate) fn css_color(name: &str) -> Option<Srgba>
+{
+    if name == "transparent" {
+        return Some(Srgba::NONE);
+    }
+    CSS_COLORS
+        .binary_search_by_key(&name, |(color_name, _)| *color_name)
+        .ok()
+        .map(|idx| CSS_COLORS[idx].1)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
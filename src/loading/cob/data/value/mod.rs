@@ -2,20 +2,28 @@ mod cob_array;
 mod cob_bool;
 mod cob_builtin;
 mod cob_enum;
+mod cob_expression;
+mod cob_localized;
 mod cob_map;
 mod cob_none;
 mod cob_number;
 mod cob_string;
+mod cob_themed;
 mod cob_tuple;
 mod cob_value;
+mod css_colors;
 
 pub use cob_array::*;
 pub use cob_bool::*;
 pub use cob_builtin::*;
 pub use cob_enum::*;
+pub use cob_expression::*;
+pub use cob_localized::*;
 pub use cob_map::*;
 pub use cob_none::*;
 pub use cob_number::*;
 pub use cob_string::*;
+pub use cob_themed::*;
 pub use cob_tuple::*;
 pub use cob_value::*;
+pub(crate) use css_colors::*;
@@ -0,0 +1,900 @@
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Options for [`Cob::format`].
+#[derive(Debug, Clone)]
+pub struct CobFormatStyle
+{
+    /// Number of spaces per indentation level.
+    ///
+    /// Defaults to `4`.
+    pub indent_width: usize,
+    /// Maps that would extend past this column are wrapped with one entry per line.
+    ///
+    /// Maps containing comments are always wrapped. Defaults to `115`.
+    pub max_width: usize,
+    /// Sorts `#import` entries by key.
+    ///
+    /// Only enable this if the imported files don't define constants or macros with the same names, since the one
+    /// imported last is used. `#manifest` entries are never sorted because they set the order that commands are
+    /// applied. Defaults to `false`.
+    pub sort_imports: bool,
+    /// Aligns the `=` of consecutive constant definitions in `#defs` sections. A blank line ends a group of
+    /// aligned definitions.
+    ///
+    /// Defaults to `true`.
+    pub align_defs: bool,
+}
+
+impl Default for CobFormatStyle
+{
+    fn default() -> Self
+    {
+        Self {
+            indent_width: 4,
+            max_width: 115,
+            sort_imports: false,
+            align_defs: true,
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+enum FillToken<'a>
+{
+    Newline,
+    Comment(&'a str),
+}
+
+/// Splits fill into newlines and comments. Other fill characters are discarded.
+fn fill_tokens(fill: &str) -> Vec<FillToken>
+{
+    let mut tokens = vec![];
+    let mut rest = fill;
+
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with("//") {
+            let end = rest.find('\n').unwrap_or(rest.len());
+            tokens.push(FillToken::Comment(rest[..end].trim_end()));
+            rest = &rest[end..];
+        } else if rest.starts_with("/*") {
+            let end = rest[2..]
+                .find("*/")
+                .map(|end| end + 4)
+                .unwrap_or(rest.len());
+            tokens.push(FillToken::Comment(&rest[..end]));
+            rest = &rest[end..];
+        } else {
+            if c == '\n' {
+                tokens.push(FillToken::Newline);
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    tokens
+}
+
+/// Converts a line comment to a block comment so it can be followed by more content on the same line.
+fn to_block_comment(comment: &str) -> String
+{
+    match comment.strip_prefix("//") {
+        Some(content) => format!("/*{} */", content.replace("*/", "* /")),
+        None => comment.into(),
+    }
+}
+
+fn fill_len(fill: &CobFill) -> usize
+{
+    fill.string.chars().count()
+}
+
+fn rendered_len(write: impl FnOnce(&mut DefaultRawSerializer<'_>) -> Result<(), std::io::Error>) -> usize
+{
+    let mut bytes = vec![];
+    write(&mut DefaultRawSerializer::new(&mut bytes)).expect("writing to a buffer should not fail");
+    String::from_utf8_lossy(&bytes).chars().count()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn value_fill(value: &mut CobValue) -> &mut CobFill
+{
+    match value {
+        CobValue::Enum(value) => &mut value.fill,
+        CobValue::Builtin(CobBuiltin::Color(color)) => &mut color.fill,
+        CobValue::Builtin(CobBuiltin::Val { fill, .. }) => fill,
+        CobValue::Builtin(CobBuiltin::FontRelative { fill, .. }) => fill,
+        CobValue::Array(array) => &mut array.start_fill,
+        CobValue::Tuple(tuple) => &mut tuple.start_fill,
+        CobValue::Map(map) => &mut map.start_fill,
+        CobValue::Number(number) => &mut number.fill,
+        CobValue::Bool(value) => &mut value.fill,
+        CobValue::None(value) => &mut value.fill,
+        CobValue::String(string) => &mut string.fill,
+        CobValue::Constant(constant) => &mut constant.start_fill,
+        CobValue::DataMacro(call) => &mut call.start_fill,
+        CobValue::MacroParam(param) => &mut param.start_fill,
+        CobValue::Expression(expression) => value_fill(&mut expression.first),
+        CobValue::Function(call) => &mut call.fill,
+        CobValue::Localized(localized) => &mut localized.fill,
+        CobValue::Themed(themed) => &mut themed.fill,
+    }
+}
+
+fn key_fill(key: &mut CobMapKey) -> &mut CobFill
+{
+    match key {
+        CobMapKey::Value(value) => value_fill(value),
+        CobMapKey::FieldName { fill, .. } => fill,
+    }
+}
+
+fn map_entry_fill(entry: &mut CobMapEntry) -> &mut CobFill
+{
+    match entry {
+        CobMapEntry::KeyValue(key_value) => key_fill(&mut key_value.key),
+        CobMapEntry::Constant(constant) => &mut constant.start_fill,
+        CobMapEntry::DataMacro(call) => &mut call.start_fill,
+        CobMapEntry::MacroParam(param) => &mut param.start_fill,
+    }
+}
+
+fn group_entry_fill(entry: &mut CobValueGroupEntry) -> &mut CobFill
+{
+    match entry {
+        CobValueGroupEntry::KeyValue(key_value) => key_fill(&mut key_value.key),
+        CobValueGroupEntry::Value(value) => value_fill(value),
+    }
+}
+
+fn separator(idx: usize) -> &'static str
+{
+    if idx == 0 {
+        ""
+    } else {
+        " "
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Controls blank lines in fill before an item on a new line.
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum BlankLine
+{
+    /// Keep up to one blank line if there were blank lines in the original fill.
+    Keep,
+    /// Remove all blank lines.
+    Remove,
+    /// Always insert one blank line after the previous item.
+    Force,
+}
+
+struct CobFormatter<'a>
+{
+    style: &'a CobFormatStyle,
+    /// Set when comments are found while formatting fill inline.
+    found_comment: bool,
+}
+
+impl<'a> CobFormatter<'a>
+{
+    fn indent_len(&self, depth: usize) -> usize
+    {
+        depth * self.style.indent_width
+    }
+
+    /// Makes fill for an item that starts on a new line at `depth`.
+    ///
+    /// Comments on the same line as the previous item stay there, and other comments are placed on their own lines
+    /// at the item's indentation.
+    fn line_fill(&self, fill: &CobFill, depth: usize, after_item: bool, blank: BlankLine) -> CobFill
+    {
+        self.line_fill_with_comments_at(fill, depth, depth, after_item, blank)
+    }
+
+    /// Same as [`Self::line_fill`], but comments on their own lines are indented to `comment_depth`.
+    fn line_fill_with_comments_at(
+        &self,
+        fill: &CobFill,
+        comment_depth: usize,
+        depth: usize,
+        after_item: bool,
+        blank: BlankLine,
+    ) -> CobFill
+    {
+        let comment_indent = " ".repeat(self.indent_len(comment_depth));
+        let indent = " ".repeat(self.indent_len(depth));
+        let push_break = |out: &mut String, newlines: usize, first: bool| {
+            if first && !after_item {
+                return;
+            }
+            out.push('\n');
+            if (first && blank == BlankLine::Force) || (blank != BlankLine::Remove && newlines >= 2) {
+                out.push('\n');
+            }
+        };
+
+        let mut out = String::new();
+        let mut newlines = 0;
+        let mut first = true;
+        for token in fill_tokens(&fill.string) {
+            match token {
+                FillToken::Newline => newlines += 1,
+                FillToken::Comment(comment) if after_item && first && newlines == 0 => {
+                    out.push(' ');
+                    out.push_str(comment);
+                }
+                FillToken::Comment(comment) => {
+                    push_break(&mut out, newlines, first);
+                    out.push_str(&comment_indent);
+                    out.push_str(comment);
+                    newlines = 0;
+                    first = false;
+                }
+            }
+        }
+        push_break(&mut out, newlines, first);
+        out.push_str(&indent);
+
+        CobFill::new(out)
+    }
+
+    fn set_line(&self, fill: &mut CobFill, depth: usize, blank: BlankLine)
+    {
+        *fill = self.line_fill(fill, depth, true, blank);
+    }
+
+    /// Sets fill before the closer of a multi-line group at `depth`. Comments stay indented with the group's
+    /// entries.
+    fn set_closing_line(&self, fill: &mut CobFill, depth: usize)
+    {
+        *fill = self.line_fill_with_comments_at(fill, depth + 1, depth, true, BlankLine::Remove);
+    }
+
+    /// Makes fill for an item in the middle of a line. `space` is used if the fill has no comments.
+    fn inline_fill(&mut self, fill: &CobFill, space: &str) -> CobFill
+    {
+        let mut out = String::from(space);
+        for token in fill_tokens(&fill.string) {
+            let FillToken::Comment(comment) = token else { continue };
+            self.found_comment = true;
+            out.push_str(&to_block_comment(comment));
+            out.push(' ');
+        }
+        CobFill::new(out)
+    }
+
+    fn set_inline(&mut self, fill: &mut CobFill, space: &str)
+    {
+        *fill = self.inline_fill(fill, space);
+    }
+
+    //---------------------------------------------------------------------------------------------------------------
+
+    fn inline_value(&mut self, value: &mut CobValue)
+    {
+        match value {
+            CobValue::Enum(value) => self.inline_enum_variant(&mut value.variant),
+            CobValue::Array(array) => self.inline_seq(&mut array.entries, &mut array.end_fill),
+            CobValue::Tuple(tuple) => self.inline_seq(&mut tuple.entries, &mut tuple.end_fill),
+            CobValue::Map(map) => self.inline_map(map),
+            CobValue::DataMacro(call) => self.inline_args(&mut call.args),
+            CobValue::Expression(expression) => {
+                self.inline_value(&mut expression.first);
+                for term in expression.terms.iter_mut() {
+                    self.set_inline(&mut term.op_fill, " ");
+                    self.set_inline(value_fill(&mut term.value), " ");
+                    self.inline_value(&mut term.value);
+                }
+            }
+            CobValue::Function(call) => {
+                self.set_inline(&mut call.args.start_fill, "");
+                self.inline_seq(&mut call.args.entries, &mut call.args.end_fill);
+            }
+            CobValue::Localized(localized) => {
+                self.set_inline(&mut localized.id.fill, "");
+                for arg in localized.args.iter_mut() {
+                    self.set_inline(key_fill(&mut arg.key), " ");
+                    self.inline_key_value(arg);
+                }
+                self.set_inline(&mut localized.end_fill, "");
+            }
+            CobValue::Builtin(CobBuiltin::Color(CobColor {
+                syntax: CobColorSyntax::Function { args, .. },
+                ..
+            })) => {
+                self.set_inline(&mut args.start_fill, "");
+                self.inline_seq(&mut args.entries, &mut args.end_fill);
+            }
+            _ => (),
+        }
+    }
+
+    fn inline_enum_variant(&mut self, variant: &mut CobEnumVariant)
+    {
+        match variant {
+            CobEnumVariant::Unit => (),
+            CobEnumVariant::Tuple(tuple) => {
+                self.set_inline(&mut tuple.start_fill, "");
+                self.inline_seq(&mut tuple.entries, &mut tuple.end_fill);
+            }
+            CobEnumVariant::Array(array) => {
+                self.set_inline(&mut array.start_fill, "");
+                self.inline_seq(&mut array.entries, &mut array.end_fill);
+            }
+            CobEnumVariant::Map(map) => {
+                self.set_inline(&mut map.start_fill, "");
+                self.inline_map(map);
+            }
+        }
+    }
+
+    fn inline_seq(&mut self, entries: &mut [CobValue], end_fill: &mut CobFill)
+    {
+        for (idx, entry) in entries.iter_mut().enumerate() {
+            self.set_inline(value_fill(entry), separator(idx));
+            self.inline_value(entry);
+        }
+        self.set_inline(end_fill, "");
+    }
+
+    fn inline_map(&mut self, map: &mut CobMap)
+    {
+        for (idx, entry) in map.entries.iter_mut().enumerate() {
+            self.set_inline(map_entry_fill(entry), separator(idx));
+            match entry {
+                CobMapEntry::KeyValue(key_value) => self.inline_key_value(key_value),
+                CobMapEntry::DataMacro(call) => self.inline_args(&mut call.args),
+                CobMapEntry::Constant(_) | CobMapEntry::MacroParam(_) => (),
+            }
+        }
+        self.set_inline(&mut map.end_fill, "");
+    }
+
+    fn inline_key_value(&mut self, key_value: &mut CobMapKeyValue)
+    {
+        if let CobMapKey::Value(key) = &mut key_value.key {
+            self.inline_value(key);
+        }
+        self.set_inline(&mut key_value.semicolon_fill, "");
+        self.set_inline(value_fill(&mut key_value.value), "");
+        self.inline_value(&mut key_value.value);
+    }
+
+    fn inline_args(&mut self, args: &mut CobMacroArgs)
+    {
+        for (idx, entry) in args.entries.iter_mut().enumerate() {
+            self.set_inline(group_entry_fill(entry), separator(idx));
+            match entry {
+                CobValueGroupEntry::KeyValue(key_value) => self.inline_key_value(key_value),
+                CobValueGroupEntry::Value(value) => self.inline_value(value),
+            }
+        }
+        self.set_inline(&mut args.end_fill, "");
+    }
+
+    fn inline_param_defs(&mut self, params: &mut CobMacroParamDefs)
+    {
+        for (idx, param) in params.entries.iter_mut().enumerate() {
+            match param {
+                CobMacroParamDef::Unassigned { start_fill, .. }
+                | CobMacroParamDef::CatchAll { start_fill, .. } => {
+                    self.set_inline(start_fill, separator(idx));
+                }
+                CobMacroParamDef::Assigned { start_fill, pre_eq_fill, value, .. } => {
+                    self.set_inline(start_fill, separator(idx));
+                    self.set_inline(pre_eq_fill, " ");
+                    self.set_inline(value_fill(value), " ");
+                    self.inline_value(value);
+                }
+            }
+        }
+        self.set_inline(&mut params.end_fill, "");
+    }
+
+    fn inline_generics(&mut self, generics: &mut CobGenerics)
+    {
+        for (idx, value) in generics.values.iter_mut().enumerate() {
+            self.inline_generic_value(value, if idx == 0 { "" } else { ", " });
+        }
+        self.set_inline(&mut generics.close_fill, "");
+    }
+
+    fn inline_generic_value(&mut self, value: &mut CobGenericValue, space: &str)
+    {
+        match value {
+            CobGenericValue::Item(CobGenericItem::Struct { fill, generics, .. }) => {
+                self.set_inline(fill, space);
+                if let Some(generics) = generics {
+                    self.inline_generics(generics);
+                }
+            }
+            CobGenericValue::Item(CobGenericItem::Tuple { fill, values, close_fill }) => {
+                self.set_inline(fill, space);
+                for (idx, value) in values.iter_mut().enumerate() {
+                    self.inline_generic_value(value, if idx == 0 { "" } else { ", " });
+                }
+                self.set_inline(close_fill, "");
+            }
+            CobGenericValue::Item(CobGenericItem::RustPrimitive(primitive)) => {
+                self.set_inline(&mut primitive.fill, space);
+            }
+            CobGenericValue::MacroParam(param) => self.set_inline(&mut param.start_fill, space),
+        }
+    }
+
+    //---------------------------------------------------------------------------------------------------------------
+
+    /// Formats a value whose leading fill has already been set. `column` is the column after the leading fill.
+    ///
+    /// Returns the column at the end of the value.
+    fn format_value(&mut self, value: &mut CobValue, depth: usize, column: usize) -> usize
+    {
+        match value {
+            CobValue::Map(map) => self.format_map(map, depth, column),
+            CobValue::Enum(value) => {
+                let column = column + value.id.0.chars().count();
+                self.format_enum_variant(&mut value.variant, depth, column)
+            }
+            CobValue::Array(array) => self.format_seq(&mut array.entries, &mut array.end_fill, depth, column),
+            CobValue::Tuple(tuple) => self.format_seq(&mut tuple.entries, &mut tuple.end_fill, depth, column),
+            _ => {
+                self.inline_value(value);
+                let len = rendered_len(|writer| value.write_to(writer));
+                column + len - fill_len(value_fill(value))
+            }
+        }
+    }
+
+    fn format_enum_variant(&mut self, variant: &mut CobEnumVariant, depth: usize, column: usize) -> usize
+    {
+        match variant {
+            CobEnumVariant::Unit => column,
+            CobEnumVariant::Tuple(tuple) => {
+                self.set_inline(&mut tuple.start_fill, "");
+                let column = column + fill_len(&tuple.start_fill);
+                self.format_seq(&mut tuple.entries, &mut tuple.end_fill, depth, column)
+            }
+            CobEnumVariant::Array(array) => {
+                self.set_inline(&mut array.start_fill, "");
+                let column = column + fill_len(&array.start_fill);
+                self.format_seq(&mut array.entries, &mut array.end_fill, depth, column)
+            }
+            CobEnumVariant::Map(map) => {
+                self.set_inline(&mut map.start_fill, "");
+                let column = column + fill_len(&map.start_fill);
+                self.format_map(map, depth, column)
+            }
+        }
+    }
+
+    /// Formats the entries of a tuple or array. Sequences are never wrapped, but maps inside them can be.
+    fn format_seq(
+        &mut self,
+        entries: &mut [CobValue],
+        end_fill: &mut CobFill,
+        depth: usize,
+        column: usize,
+    ) -> usize
+    {
+        // Opening bracket.
+        let mut column = column + 1;
+        for (idx, entry) in entries.iter_mut().enumerate() {
+            self.set_inline(value_fill(entry), separator(idx));
+            column += fill_len(value_fill(entry));
+            column = self.format_value(entry, depth, column);
+        }
+        self.set_inline(end_fill, "");
+        column + fill_len(end_fill) + 1
+    }
+
+    /// Formats a map on one line if it fits and has no comments, otherwise puts each entry on its own line.
+    fn format_map(&mut self, map: &mut CobMap, depth: usize, column: usize) -> usize
+    {
+        let mut inline = map.clone();
+        self.found_comment = false;
+        self.inline_map(&mut inline);
+        let end = column + rendered_len(|writer| inline.write_to(writer)) - fill_len(&inline.start_fill);
+
+        if !self.found_comment && end <= self.style.max_width {
+            *map = inline;
+            return end;
+        }
+
+        for entry in map.entries.iter_mut() {
+            self.set_line(map_entry_fill(entry), depth + 1, BlankLine::Keep);
+            let column = self.indent_len(depth + 1);
+            match entry {
+                CobMapEntry::KeyValue(key_value) => {
+                    self.format_key_value(key_value, depth + 1, column);
+                }
+                CobMapEntry::DataMacro(call) => self.inline_args(&mut call.args),
+                CobMapEntry::Constant(_) | CobMapEntry::MacroParam(_) => (),
+            }
+        }
+        self.set_closing_line(&mut map.end_fill, depth);
+
+        // Closing brace.
+        self.indent_len(depth) + 1
+    }
+
+    /// Formats a key-value pair whose key fill has already been set. `column` is the column after the key fill.
+    fn format_key_value(&mut self, key_value: &mut CobMapKeyValue, depth: usize, column: usize) -> usize
+    {
+        let column = match &mut key_value.key {
+            CobMapKey::FieldName { name, .. } => column + name.chars().count(),
+            CobMapKey::Value(key) => {
+                self.inline_value(key);
+                column + rendered_len(|writer| key.write_to(writer)) - fill_len(value_fill(key))
+            }
+        };
+        self.set_inline(&mut key_value.semicolon_fill, "");
+        self.set_inline(value_fill(&mut key_value.value), "");
+
+        // Colon.
+        let column = column + fill_len(&key_value.semicolon_fill) + 1 + fill_len(value_fill(&mut key_value.value));
+        self.format_value(&mut key_value.value, depth, column)
+    }
+
+    /// Formats a loadable whose fill has already been set. `column` is the column after the fill.
+    fn format_loadable(&mut self, loadable: &mut CobLoadable, depth: usize, column: usize)
+    {
+        let mut column = column + loadable.id.name.chars().count();
+        if let Some(generics) = &mut loadable.id.generics {
+            self.inline_generics(generics);
+            column += rendered_len(|writer| generics.write_to(writer));
+        }
+
+        match &mut loadable.variant {
+            CobLoadableVariant::Unit => (),
+            CobLoadableVariant::Tuple(tuple) => {
+                self.set_inline(&mut tuple.start_fill, "");
+                self.format_seq(&mut tuple.entries, &mut tuple.end_fill, depth, column);
+            }
+            CobLoadableVariant::Array(array) => {
+                self.set_inline(&mut array.start_fill, "");
+                self.format_seq(&mut array.entries, &mut array.end_fill, depth, column);
+            }
+            CobLoadableVariant::Map(map) => {
+                self.set_inline(&mut map.start_fill, "");
+                self.format_map(map, depth, column);
+            }
+            CobLoadableVariant::Enum(value) => {
+                self.set_inline(&mut value.fill, "");
+                // `::`
+                let column = column + 2 + value.id.0.chars().count();
+                self.format_enum_variant(&mut value.variant, depth, column);
+            }
+        }
+    }
+
+    fn format_loadable_macro_call(&mut self, call: &mut CobLoadableMacroCall, depth: usize)
+    {
+        self.set_line(&mut call.start_fill, depth, BlankLine::Keep);
+        self.inline_args(&mut call.args);
+    }
+
+    //---------------------------------------------------------------------------------------------------------------
+
+    fn format_layer_entries(&mut self, entries: &mut [CobSceneLayerEntry], depth: usize)
+    {
+        for entry in entries.iter_mut() {
+            match entry {
+                CobSceneLayerEntry::Loadable(loadable) => {
+                    self.set_line(&mut loadable.fill, depth, BlankLine::Keep);
+                    self.format_loadable(loadable, depth, self.indent_len(depth));
+                }
+                CobSceneLayerEntry::LoadableMacroCall(call) => self.format_loadable_macro_call(call, depth),
+                CobSceneLayerEntry::SceneMacroCall(call) => {
+                    self.set_line(&mut call.start_fill, depth, BlankLine::Keep);
+                    self.inline_args(&mut call.args);
+                    for param_def in call.param_defs.iter_mut() {
+                        self.set_line(&mut param_def.name_fill, depth + 1, BlankLine::Keep);
+                        self.format_layer_entries(&mut param_def.entries, depth + 2);
+                    }
+                }
+                CobSceneLayerEntry::Layer(layer) => self.format_layer(layer, depth),
+                CobSceneLayerEntry::SceneMacroParam(param) => {
+                    self.set_line(&mut param.start_fill, depth, BlankLine::Keep);
+                }
+                CobSceneLayerEntry::LoadableRemoval(removal) => {
+                    self.set_line(&mut removal.start_fill, depth, BlankLine::Keep);
+                }
+            }
+        }
+    }
+
+    fn format_layer(&mut self, layer: &mut CobSceneLayer, depth: usize)
+    {
+        self.set_line(&mut layer.name_fill, depth, BlankLine::Keep);
+        if let Some(base) = &mut layer.base {
+            self.set_inline(&mut base.colon_fill, " ");
+            self.set_inline(&mut base.base_fill, " ");
+        }
+        self.format_layer_entries(&mut layer.entries, depth + 1);
+    }
+
+    //---------------------------------------------------------------------------------------------------------------
+
+    /// Formats a definition's value. `column` is the column after the `=`.
+    fn format_constant_value(&mut self, value: &mut CobConstantValue, column: usize)
+    {
+        match value {
+            CobConstantValue::Value(value) => {
+                self.set_inline(value_fill(value), " ");
+                let column = column + fill_len(value_fill(value));
+                self.format_value(value, 0, column);
+            }
+            CobConstantValue::ValueGroup(group) => {
+                self.set_inline(&mut group.start_fill, " ");
+                for entry in group.entries.iter_mut() {
+                    self.set_line(group_entry_fill(entry), 1, BlankLine::Keep);
+                    let column = self.indent_len(1);
+                    match entry {
+                        CobValueGroupEntry::KeyValue(key_value) => {
+                            self.format_key_value(key_value, 1, column);
+                        }
+                        CobValueGroupEntry::Value(value) => {
+                            self.format_value(value, 1, column);
+                        }
+                    }
+                }
+                self.set_closing_line(&mut group.end_fill, 0);
+            }
+        }
+    }
+
+    fn format_defs(&mut self, defs: &mut CobDefs)
+    {
+        for entry in defs.entries.iter_mut() {
+            let start_fill = match entry {
+                CobDefEntry::Constant(def) => &mut def.start_fill,
+                CobDefEntry::DataMacro(def) => &mut def.start_fill,
+                CobDefEntry::LoadableMacro(def) => &mut def.start_fill,
+                CobDefEntry::SceneMacro(def) => &mut def.start_fill,
+            };
+            self.set_line(start_fill, 0, BlankLine::Keep);
+        }
+
+        // Pad constant names so the `=` of consecutive constants line up.
+        let mut name_widths = vec![0; defs.entries.len()];
+        if self.style.align_defs {
+            let mut group: Vec<usize> = vec![];
+            for idx in 0..=defs.entries.len() {
+                let constant = match defs.entries.get(idx) {
+                    Some(CobDefEntry::Constant(def)) => Some(def),
+                    _ => None,
+                };
+                let ends_group = match constant {
+                    Some(def) => def.start_fill.string.contains("\n\n"),
+                    None => true,
+                };
+                if ends_group {
+                    let width = group.iter().map(|idx| name_widths[*idx]).max().unwrap_or(0);
+                    for idx in group.drain(..) {
+                        name_widths[idx] = width;
+                    }
+                }
+                if let Some(def) = constant {
+                    name_widths[idx] = def.name.name.chars().count();
+                    group.push(idx);
+                }
+            }
+        }
+
+        for (entry, name_width) in defs.entries.iter_mut().zip(name_widths) {
+            match entry {
+                CobDefEntry::Constant(def) => {
+                    let name_len = def.name.name.chars().count();
+                    let padding = " ".repeat(name_width.saturating_sub(name_len) + 1);
+                    self.set_inline(&mut def.pre_eq_fill, &padding);
+                    // `$name =`
+                    let column = 1 + name_len + fill_len(&def.pre_eq_fill) + 1;
+                    self.format_constant_value(&mut def.value, column);
+                }
+                CobDefEntry::DataMacro(def) => {
+                    self.inline_param_defs(&mut def.params);
+                    self.set_inline(&mut def.pre_eq_fill, " ");
+                    let params_len = rendered_len(|writer| def.params.write_to(writer));
+                    let column = def.name.chars().count() + 1 + params_len + fill_len(&def.pre_eq_fill) + 1;
+                    self.format_constant_value(&mut def.value, column);
+                }
+                CobDefEntry::LoadableMacro(def) => {
+                    self.inline_param_defs(&mut def.params);
+                    self.set_inline(&mut def.pre_eq_fill, " ");
+                    let params_len = rendered_len(|writer| def.params.write_to(writer));
+                    let column = def.name.chars().count() + 1 + params_len + fill_len(&def.pre_eq_fill) + 1;
+                    self.format_loadable_macro_value(&mut def.value, column);
+                }
+                CobDefEntry::SceneMacro(def) => {
+                    self.inline_param_defs(&mut def.params);
+                    self.set_inline(&mut def.pre_eq_fill, " ");
+                    self.set_inline(&mut def.value_fill, " ");
+                    self.format_layer_entries(&mut def.entries, 1);
+                    self.set_closing_line(&mut def.end_fill, 0);
+                }
+            }
+        }
+    }
+
+    fn format_theme(&mut self, theme: &mut CobTheme)
+    {
+        self.set_inline(&mut theme.name_fill, "");
+        self.set_inline(&mut theme.name_end_fill, "");
+
+        // Theme entries are formatted like the constants in a `#defs` section.
+        let mut defs = CobDefs {
+            start_fill: CobFill::default(),
+            entries: theme.entries.drain(..).map(CobDefEntry::Constant).collect(),
+        };
+        self.format_defs(&mut defs);
+        theme.entries = defs
+            .entries
+            .into_iter()
+            .filter_map(|entry| match entry {
+                CobDefEntry::Constant(def) => Some(def),
+                _ => None,
+            })
+            .collect();
+    }
+
+    /// Formats a loadable macro's value. `column` is the column after the `=`.
+    fn format_loadable_macro_value(&mut self, value: &mut CobLoadableMacroValue, column: usize)
+    {
+        match value {
+            CobLoadableMacroValue::LoadableMacroCall(call) => {
+                self.set_inline(&mut call.start_fill, " ");
+                self.inline_args(&mut call.args);
+            }
+            CobLoadableMacroValue::Loadable(loadable) => {
+                self.set_inline(&mut loadable.fill, " ");
+                let column = column + fill_len(&loadable.fill);
+                self.format_loadable(loadable, 0, column);
+            }
+            CobLoadableMacroValue::Group(group) => {
+                self.set_inline(&mut group.start_fill, " ");
+                for entry in group.entries.iter_mut() {
+                    match entry {
+                        CobLoadableGroupEntry::LoadableMacroCall(call) => self.format_loadable_macro_call(call, 1),
+                        CobLoadableGroupEntry::Loadable(loadable) => {
+                            self.set_line(&mut loadable.fill, 1, BlankLine::Keep);
+                            self.format_loadable(loadable, 1, self.indent_len(1));
+                        }
+                    }
+                }
+                self.set_closing_line(&mut group.end_fill, 0);
+            }
+        }
+    }
+
+    //---------------------------------------------------------------------------------------------------------------
+
+    fn format_condition(&mut self, condition: &mut CobCondition)
+    {
+        self.set_inline(&mut condition.start_fill, "");
+        if let Some(comparison) = &mut condition.comparison {
+            self.set_inline(&mut comparison.op_fill, " ");
+            self.set_inline(&mut comparison.value.fill, " ");
+        }
+        self.set_inline(&mut condition.end_fill, "");
+    }
+
+    /// Formats the sections in a branch of an `#if` block. The first section starts directly after the `#if` or
+    /// `#else` line.
+    fn format_branch(&mut self, sections: &mut [CobSection])
+    {
+        for (idx, section) in sections.iter_mut().enumerate() {
+            let blank = if idx == 0 {
+                BlankLine::Remove
+            } else {
+                BlankLine::Force
+            };
+            self.format_section(section, true, blank);
+        }
+    }
+
+    /// Formats a section. `after_item` should be `false` for the first section in the file.
+    fn format_section(&mut self, section: &mut CobSection, after_item: bool, blank: BlankLine)
+    {
+        let start_fill = match section {
+            CobSection::Manifest(section) => &mut section.start_fill,
+            CobSection::Import(section) => &mut section.start_fill,
+            CobSection::Defs(section) => &mut section.start_fill,
+            CobSection::Theme(section) => &mut section.start_fill,
+            CobSection::Commands(section) => &mut section.start_fill,
+            CobSection::Scenes(section) => &mut section.start_fill,
+            CobSection::Conditional(section) => &mut section.start_fill,
+        };
+        *start_fill = self.line_fill(start_fill, 0, after_item, blank);
+
+        match section {
+            CobSection::Manifest(manifest) => {
+                for entry in manifest.entries.iter_mut() {
+                    self.set_line(&mut entry.entry_fill, 0, BlankLine::Keep);
+                    self.set_inline(&mut entry.as_fill, " ");
+                    self.set_inline(&mut entry.key_fill, " ");
+                }
+            }
+            CobSection::Import(import) => {
+                // Blank lines are removed from sorted entries since they would no longer separate the same
+                // entries.
+                let entry_blank = if self.style.sort_imports {
+                    import.entries.sort_by(|a, b| a.key.0.cmp(&b.key.0));
+                    BlankLine::Remove
+                } else {
+                    BlankLine::Keep
+                };
+                for entry in import.entries.iter_mut() {
+                    self.set_line(&mut entry.entry_fill, 0, entry_blank);
+                    self.set_inline(&mut entry.as_fill, " ");
+                    self.set_inline(&mut entry.alias_fill, " ");
+                }
+            }
+            CobSection::Defs(defs) => self.format_defs(defs),
+            CobSection::Theme(theme) => self.format_theme(theme),
+            CobSection::Commands(commands) => {
+                for entry in commands.entries.iter_mut() {
+                    match entry {
+                        CobCommandEntry::Loadable(loadable) => {
+                            self.set_line(&mut loadable.fill, 0, BlankLine::Keep);
+                            self.format_loadable(loadable, 0, 0);
+                        }
+                        CobCommandEntry::LoadableMacroCall(call) => self.format_loadable_macro_call(call, 0),
+                    }
+                }
+            }
+            CobSection::Scenes(scenes) => {
+                for layer in scenes.scenes.iter_mut() {
+                    self.format_layer(layer, 0);
+                }
+            }
+            CobSection::Conditional(conditional) => {
+                self.format_condition(&mut conditional.condition);
+                self.format_branch(&mut conditional.sections);
+                if let Some(branch) = &mut conditional.else_branch {
+                    self.set_line(&mut branch.start_fill, 0, BlankLine::Force);
+                    self.format_branch(&mut branch.sections);
+                }
+                self.set_line(&mut conditional.end_fill, 0, BlankLine::Force);
+            }
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+impl Cob
+{
+    /// Rewrites all fill in the file to a canonical layout. Comments are preserved.
+    ///
+    /// - Scene nodes, loadables, and multi-line groups are indented by [`CobFormatStyle::indent_width`].
+    /// - Each loadable is placed on its own line.
+    /// - Sections are separated by one blank line, and other blank lines are collapsed to at most one.
+    /// - Values are written with single spaces between entries and no space after `:`.
+    /// - Maps that would extend past [`CobFormatStyle::max_width`] are wrapped with one entry per line.
+    /// - `#import` entries are sorted, and consecutive `#defs` constants are aligned, depending on the style.
+    ///   `#manifest` entries are never reordered.
+    ///
+    /// Use [`Cob::write_to`] to serialize the formatted file.
+    pub fn format(&mut self, style: &CobFormatStyle)
+    {
+        let mut formatter = CobFormatter { style, found_comment: false };
+        for (idx, section) in self.sections.iter_mut().enumerate() {
+            let (after_item, blank) = if idx == 0 {
+                (false, BlankLine::Keep)
+            } else {
+                (true, BlankLine::Force)
+            };
+            formatter.format_section(section, after_item, blank);
+        }
+        let after_item = !self.sections.is_empty();
+        let end_fill = formatter.line_fill(&self.end_fill, 0, after_item, BlankLine::Keep);
+        self.end_fill = CobFill::new(format!("{}\n", end_fill.string.trim_end()));
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -1,11 +1,13 @@
 mod cob;
 mod data;
+mod format;
 mod parsing;
 mod raw_serializer;
 mod sections;
 
 pub use cob::*;
 pub use data::*;
+pub use format::*;
 pub use parsing::*;
 pub use raw_serializer::*;
 pub use sections::*;
@@ -0,0 +1,255 @@
+use std::cell::RefCell;
+use std::fmt::Display;
+
+use nom::error::ErrorKind;
+
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+thread_local! {
+    /// The most recent diagnostic recorded while parsing on this thread.
+    static LAST_DIAGNOSTIC: RefCell<Option<CobDiagnostic>> = RefCell::new(None);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Clears the diagnostic recorded by [`span_diagnostic`].
+pub(crate) fn clear_diagnostic()
+{
+    LAST_DIAGNOSTIC.set(None);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Makes a [`SpanError`] for a verification error while parsing, and records a [`CobDiagnostic`] describing it.
+///
+/// The message should say what was expected, e.g. "expected `:` after struct field `width`". The diagnostic can be
+/// retrieved with [`CobDiagnostic::from_span_error`].
+pub(crate) fn span_diagnostic(content: Span, message: impl Into<String>) -> SpanError
+{
+    let diagnostic = CobDiagnostic::new(content, message);
+    // Recovered errors are logged by the caller of `Cob::parse_recoverable`.
+    if !is_recovering() {
+        tracing::warn!("{} at {}", diagnostic.message.as_str(), get_location(content).as_str());
+    }
+    LAST_DIAGNOSTIC.set(Some(diagnostic));
+    span_verify_error(content)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn describe_error_kind(code: ErrorKind) -> String
+{
+    match code {
+        ErrorKind::Char => "unexpected character".into(),
+        ErrorKind::Tag => "unexpected token".into(),
+        ErrorKind::Float | ErrorKind::Digit => "invalid number".into(),
+        ErrorKind::Eof => "unexpected end of file".into(),
+        _ => format!("unexpected input ({:?})", code),
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn edit_distance(a: &str, b: &str) -> usize
+{
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut current);
+    }
+
+    prev[b.len()]
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Finds the candidate that is most similar to `name`.
+///
+/// Used for "did you mean" hints. Returns `None` if no candidate is similar enough to be a likely misspelling.
+pub fn find_similar_name<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str>
+{
+    let max_distance = (name.chars().count() / 3).max(1);
+    let lowercase = name.to_lowercase();
+
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| {
+            // Differences in capitalization are the most likely misspelling.
+            let distance = match candidate.to_lowercase() == lowercase {
+                true => 0,
+                false => edit_distance(name, candidate),
+            };
+            (distance, candidate)
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Location in a COB file's source text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobSourceLocation
+{
+    /// Line number, starting at 1.
+    pub line: u32,
+    /// Column number in UTF-8 characters, starting at 1.
+    pub column: usize,
+    /// Byte offset from the beginning of the file.
+    pub offset: usize,
+    /// The full source line containing the location.
+    pub source_line: String,
+}
+
+impl CobSourceLocation
+{
+    pub fn new(span: Span) -> Self
+    {
+        let beginning = String::from_utf8_lossy(span.get_line_beginning());
+        let end = span
+            .fragment()
+            .split('\n')
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches('\r');
+
+        Self {
+            line: span.location_line(),
+            column: span.get_utf8_column(),
+            offset: span.location_offset(),
+            source_line: format!("{}{}", beginning, end),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A human-readable problem found in a COB file.
+///
+/// Parse errors include a [`CobSourceLocation`]. Problems found after parsing (e.g. unknown loadable names)
+/// instead include the path to the scene node where they were found.
+///
+/// Diagnostics are printed like compiler errors, with a snippet of the source line and a caret pointing at the
+/// problem. Loaded diagnostics can be inspected with the [`CobDiagnostics`] resource.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobDiagnostic
+{
+    /// The file where the diagnostic was found.
+    pub file: String,
+    /// The source location of the diagnostic. Only available for parse errors.
+    pub location: Option<CobSourceLocation>,
+    /// The scene node where the diagnostic was found. Will be `#commands` for diagnostics in command sections.
+    pub scene_path: Option<ScenePath>,
+    /// Describes the problem, e.g. "expected `:` after struct field `width`".
+    pub message: String,
+    /// Suggested fix, e.g. "did you mean `FlexNode`?".
+    pub hint: Option<String>,
+}
+
+impl CobDiagnostic
+{
+    /// Makes a diagnostic pointing at the beginning of `span`.
+    pub fn new(span: Span, message: impl Into<String>) -> Self
+    {
+        Self {
+            file: String::from(span.extra.file),
+            location: Some(CobSourceLocation::new(span)),
+            scene_path: None,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    /// Makes a diagnostic for a scene node.
+    pub fn new_for_scene(file: impl Into<String>, scene_path: ScenePath, message: impl Into<String>) -> Self
+    {
+        Self {
+            file: file.into(),
+            location: None,
+            scene_path: Some(scene_path),
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    /// Adds a hint to the diagnostic.
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self
+    {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// Converts a [`Cob::parse`] error to a diagnostic.
+    ///
+    /// If a parser recorded what it expected at the error location, then that message will be used. Otherwise the
+    /// message is derived from the raw `nom` error code.
+    ///
+    /// Returns `None` for [`nom::Err::Incomplete`] errors, which don't have a location.
+    pub fn from_span_error(error: &SpanError) -> Option<Self>
+    {
+        let (nom::Err::Error(err) | nom::Err::Failure(err)) = error else { return None };
+        let recorded = LAST_DIAGNOSTIC.take();
+
+        match recorded {
+            Some(diagnostic)
+                if diagnostic.file == err.input.extra.file
+                    && diagnostic.location.as_ref().map(|l| l.offset) == Some(err.input.location_offset()) =>
+            {
+                Some(diagnostic)
+            }
+            _ => Some(Self::new(err.input, describe_error_kind(err.code))),
+        }
+    }
+}
+
+impl Display for CobDiagnostic
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
+    {
+        writeln!(f, "error: {}", self.message)?;
+
+        match &self.location {
+            Some(location) => {
+                let line_num = location.line.to_string();
+                let gutter = " ".repeat(line_num.len());
+                let caret_offset: String = location
+                    .source_line
+                    .chars()
+                    .take(location.column.saturating_sub(1))
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect();
+
+                writeln!(f, "{}--> {}:{}:{}", gutter, self.file, location.line, location.column)?;
+                writeln!(f, "{} |", gutter)?;
+                writeln!(f, "{} | {}", line_num, location.source_line)?;
+                write!(f, "{} | {}^", gutter, caret_offset)?;
+            }
+            None => {
+                write!(f, " --> {}", self.file)?;
+            }
+        }
+
+        if let Some(scene_path) = &self.scene_path {
+            let path = scene_path.iter().collect::<Vec<_>>().join("::");
+            write!(f, "\n  = in scene node: {}", path)?;
+        }
+        if let Some(hint) = &self.hint {
+            write!(f, "\n  = help: {}", hint)?;
+        }
+
+        Ok(())
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,230 @@
+use std::cell::RefCell;
+
+use nom::Slice;
+use smol_str::SmolStr;
+
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Default)]
+struct RecoveryState
+{
+    diagnostics: Vec<CobDiagnostic>,
+    /// Path to the scene node currently being parsed. Only set while parsing a `#scenes` section.
+    scene_path: Option<Vec<SmolStr>>,
+    broken_nodes: Vec<ScenePath>,
+}
+
+thread_local! {
+    /// Set while parsing with [`Cob::parse_recoverable`].
+    static RECOVERY: RefCell<Option<RecoveryState>> = RefCell::new(None);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) fn start_recovery()
+{
+    RECOVERY.set(Some(RecoveryState::default()));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Returns `(diagnostics, broken nodes)` collected since [`start_recovery`].
+pub(crate) fn end_recovery() -> (Vec<CobDiagnostic>, Vec<ScenePath>)
+{
+    let state = RECOVERY.take().unwrap_or_default();
+    (state.diagnostics, state.broken_nodes)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Returns `true` if parsing with [`Cob::parse_recoverable`].
+///
+/// Errors are reported by the caller of `parse_recoverable` in that case.
+pub(crate) fn is_recovering() -> bool
+{
+    RECOVERY.with_borrow(|state| state.is_some())
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Starts tracking scene node paths for recovered errors.
+pub(crate) fn recovery_enter_scenes()
+{
+    RECOVERY.with_borrow_mut(|state| {
+        let Some(state) = state else { return };
+        state.scene_path = Some(vec![]);
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) fn recovery_exit_scenes()
+{
+    RECOVERY.with_borrow_mut(|state| {
+        let Some(state) = state else { return };
+        state.scene_path = None;
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) fn recovery_push_scene_node(name: &str)
+{
+    RECOVERY.with_borrow_mut(|state| {
+        let Some(path) = state.as_mut().and_then(|s| s.scene_path.as_mut()) else { return };
+        path.push(SmolStr::from(name));
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) fn recovery_pop_scene_node()
+{
+    RECOVERY.with_borrow_mut(|state| {
+        let Some(path) = state.as_mut().and_then(|s| s.scene_path.as_mut()) else { return };
+        path.pop();
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Returns `true` if the line is fill that can't be used as a recovery point.
+fn is_fill_line(line: &str) -> bool
+{
+    line.is_empty() || line.starts_with("//") || line.starts_with("/*")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Gets the name of the scene node that starts at `content`, if `content` starts with a named scene node.
+fn scene_node_name(content: Span) -> Option<&str>
+{
+    let fragment = *content.fragment();
+    let name = fragment.strip_prefix('"')?;
+    let name = &name[..name.find('"')?];
+    (!name.is_empty() && !name.contains('\n')).then_some(name)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Returns `true` if the line starts with a closing bracket, which means it's the end of a multi-line item.
+fn is_closer_line(line: &str) -> bool
+{
+    line.starts_with('}') || line.starts_with(')') || line.starts_with(']')
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Skips to the first line after the start of `content` where `is_boundary(indent, line)` returns `true`.
+///
+/// The returned fill includes the newline before the boundary line, so the boundary line's indent can be read
+/// from the fill.
+fn skip_to_line(content: Span, is_boundary: impl Fn(usize, &str) -> bool) -> (CobFill, Span)
+{
+    let fragment = *content.fragment();
+    let mut newline = fragment.find('\n').unwrap_or(fragment.len());
+
+    while newline < fragment.len() {
+        let line_start = newline + 1;
+        let line_end = fragment[line_start..]
+            .find('\n')
+            .map(|end| line_start + end)
+            .unwrap_or(fragment.len());
+        let line = &fragment[line_start..line_end];
+        let trimmed = line.trim_start_matches(' ');
+        let indent = line.len() - trimmed.len();
+        let trimmed = trimmed.trim_end();
+
+        if !is_fill_line(trimmed) && is_boundary(indent, trimmed) {
+            return CobFill::parse(content.slice(newline..));
+        }
+
+        newline = line_end;
+    }
+
+    CobFill::parse(content.slice(fragment.len()..))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Records `error` if parsing with [`Cob::parse_recoverable`], then skips from the beginning of the item that
+/// failed to the next item boundary.
+///
+/// Returns the error if not recovering.
+fn try_recover<'a>(
+    error: SpanError<'a>,
+    item_start: Span<'a>,
+    is_boundary: impl Fn(usize, &str) -> bool,
+) -> Result<(CobFill, Span<'a>), SpanError<'a>>
+{
+    let recovered = RECOVERY.with_borrow_mut(|state| {
+        let Some(state) = state else { return false };
+        let Some(diagnostic) = CobDiagnostic::from_span_error(&error) else { return false };
+
+        // Track the nearest scene node that can be addressed by path. Anonymous nodes are renamed during
+        // extraction, so we use their parent instead.
+        // - If the item that failed is a scene node, then that node is broken instead of its parent. This includes
+        //   scene roots.
+        if let Some(path) = &state.scene_path {
+            let named = path.iter().take_while(|segment| !segment.is_empty());
+            let mut node = named.fold(ScenePath::empty(), |node, segment| node.extend(segment));
+            if node.len() == path.len() {
+                if let Some(failed) = scene_node_name(item_start).and_then(|name| node.extend_single(name)) {
+                    node = failed;
+                }
+            }
+            if node.len() > 0 && !state.broken_nodes.contains(&node) {
+                state.broken_nodes.push(node);
+            }
+        }
+
+        state.diagnostics.push(diagnostic);
+        true
+    });
+
+    if !recovered {
+        return Err(error);
+    }
+
+    Ok(skip_to_line(item_start, is_boundary))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Recovers from an error in a section by skipping to the next section.
+pub(crate) fn try_recover_section<'a>(
+    error: SpanError<'a>,
+    item_start: Span<'a>,
+) -> Result<(CobFill, Span<'a>), SpanError<'a>>
+{
+    try_recover(error, item_start, |indent, line| indent == 0 && line.starts_with('#'))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Recovers from an error in a top-level section item by skipping to the next un-indented item.
+pub(crate) fn try_recover_section_item<'a>(
+    error: SpanError<'a>,
+    item_start: Span<'a>,
+) -> Result<(CobFill, Span<'a>), SpanError<'a>>
+{
+    try_recover(error, item_start, |indent, line| indent == 0 && !is_closer_line(line))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Recovers from an error in a scene layer item by skipping to the next item with indent `<= max_indent`.
+pub(crate) fn try_recover_layer_item<'a>(
+    error: SpanError<'a>,
+    item_start: Span<'a>,
+    max_indent: usize,
+) -> Result<(CobFill, Span<'a>), SpanError<'a>>
+{
+    try_recover(error, item_start, |indent, line| {
+        indent <= max_indent && !is_closer_line(line)
+    })
+}
+
+//-------------------------------------------------------------------------------------------------------------------
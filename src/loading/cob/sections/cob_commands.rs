@@ -33,11 +33,21 @@ impl CobCommandEntry
         let starts_newline = fill.ends_with_newline();
         let check_newline = || -> Result<(), SpanError> {
             if !starts_newline {
-                tracing::warn!("command entry doesn't start on a new line at {}", get_location(content).as_str());
-                return Err(span_verify_error(content));
+                return Err(span_diagnostic(
+                    content,
+                    "expected command entry to start on a new line",
+                ));
             }
             Ok(())
         };
+        // Parse loadable macro calls before loadables to avoid conflicts.
+        let fill = match rc(content, move |c| CobLoadableMacroCall::try_parse(fill, c))? {
+            (Some(call), next_fill, remaining) => {
+                (check_newline)()?;
+                return Ok((Some(Self::LoadableMacroCall(call)), next_fill, remaining));
+            }
+            (None, fill, _) => fill,
+        };
         let fill = match rc(content, move |c| CobLoadable::try_parse(fill, c))? {
             (Some(loadable), next_fill, remaining) => {
                 (check_newline)()?;
@@ -49,13 +59,6 @@ impl CobCommandEntry
             }
             (None, fill, _) => fill,
         };
-        let fill = match rc(content, move |c| CobLoadableMacroCall::try_parse(fill, c))? {
-            (Some(call), next_fill, remaining) => {
-                (check_newline)()?;
-                return Ok((Some(Self::LoadableMacroCall(call)), next_fill, remaining));
-            }
-            (None, fill, _) => fill,
-        };
 
         Ok((None, fill, content))
     }
@@ -90,25 +93,29 @@ impl CobCommands
         };
 
         if start_fill.len() != 0 && !start_fill.ends_with_newline() {
-            tracing::warn!("failed parsing commands section at {} that doesn't start on newline",
-                get_location(content).as_str());
-            return Err(span_verify_error(content));
+            return Err(span_diagnostic(
+                content,
+                "expected #commands section to start on a new line",
+            ));
         }
 
         let (mut item_fill, mut remaining) = CobFill::parse(remaining);
         let mut entries = vec![];
 
         let end_fill = loop {
-            match rc(remaining, move |rm| CobCommandEntry::try_parse(item_fill, rm))? {
-                (Some(entry), next_fill, after_entry) => {
+            match rc(remaining, move |rm| CobCommandEntry::try_parse(item_fill, rm)) {
+                Ok((Some(entry), next_fill, after_entry)) => {
                     entries.push(entry);
                     item_fill = next_fill;
                     remaining = after_entry;
                 }
-                (None, end_fill, after_end) => {
+                Ok((None, end_fill, after_end)) => {
                     remaining = after_end;
                     break end_fill;
                 }
+                Err(err) => {
+                    (item_fill, remaining) = try_recover_section_item(err, remaining)?;
+                }
             }
         };
 
@@ -0,0 +1,337 @@
+use nom::bytes::complete::tag;
+use nom::Parser;
+use smol_str::SmolStr;
+
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn section_keyword(section: &CobSection) -> &'static str
+{
+    match section {
+        CobSection::Manifest(_) => "#manifest",
+        CobSection::Import(_) => "#import",
+        CobSection::Defs(_) => "#defs",
+        CobSection::Theme(_) => "#theme",
+        CobSection::Commands(_) => "#commands",
+        CobSection::Scenes(_) => "#scenes",
+        CobSection::Conditional(_) => "#if",
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Parses sections until a section can't be parsed.
+///
+/// Returns the sections, the fill before the first unparsed line, and the unparsed content.
+fn parse_branch(fill: CobFill, content: Span) -> Result<(Vec<CobSection>, CobFill, Span), SpanError>
+{
+    let mut sections = vec![];
+    let mut fill = fill;
+    let mut remaining = content;
+
+    loop {
+        match rc(remaining, move |rm| CobSection::try_parse(fill, rm)) {
+            Ok((Some(section), next_fill, after_section)) => {
+                if matches!(section, CobSection::Manifest(_) | CobSection::Import(_)) {
+                    let err = span_diagnostic(
+                        remaining,
+                        format!("{} sections can't be used in #if blocks", section_keyword(&section)),
+                    );
+                    (fill, remaining) = try_recover_section(err, remaining)?;
+                    continue;
+                }
+                sections.push(section);
+                fill = next_fill;
+                remaining = after_section;
+            }
+            Ok((None, end_fill, end)) => return Ok((sections, end_fill, end)),
+            Err(err) => {
+                (fill, remaining) = try_recover_section(err, remaining)?;
+            }
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Comparison operator in a [`CobCondition`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CobConditionOp
+{
+    /// `=`
+    Equals,
+    /// `!=`
+    NotEquals,
+}
+
+impl CobConditionOp
+{
+    pub fn as_str(&self) -> &'static str
+    {
+        match self {
+            Self::Equals => "=",
+            Self::NotEquals => "!=",
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// The `= "value"` part of a [`CobCondition`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobConditionComparison
+{
+    /// Fill between the flag and the operator.
+    pub op_fill: CobFill,
+    pub op: CobConditionOp,
+    pub value: CobString,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// The condition of an `#if` block.
+///
+/// Conditions are evaluated against the flags in [`CobFlags`]:
+/// - `flag`: The flag is set.
+/// - `!flag`: The flag is not set.
+/// - `flag = "value"`: The flag is set to `value`.
+/// - `flag != "value"`: The flag is not set to `value`. Unset flags don't equal any value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobCondition
+{
+    /// Fill between `(` and the flag.
+    pub start_fill: CobFill,
+    pub negated: bool,
+    pub flag: SmolStr,
+    pub comparison: Option<CobConditionComparison>,
+    /// Fill before `)`.
+    pub end_fill: CobFill,
+}
+
+impl CobCondition
+{
+    /// Checks if the condition holds for `flags`.
+    pub fn evaluate(&self, flags: &CobFlags) -> bool
+    {
+        let value = flags.get(&self.flag);
+        match &self.comparison {
+            None => value.is_some() != self.negated,
+            Some(comparison) => {
+                let equals = value == Some(comparison.value.as_str());
+                match comparison.op {
+                    CobConditionOp::Equals => equals,
+                    CobConditionOp::NotEquals => !equals,
+                }
+            }
+        }
+    }
+
+    pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
+    {
+        writer.write_bytes("(".as_bytes())?;
+        self.start_fill.write_to(writer)?;
+        if self.negated {
+            writer.write_bytes("!".as_bytes())?;
+        }
+        writer.write_bytes(self.flag.as_bytes())?;
+        if let Some(comparison) = &self.comparison {
+            comparison.op_fill.write_to(writer)?;
+            writer.write_bytes(comparison.op.as_str().as_bytes())?;
+            comparison.value.write_to(writer)?;
+        }
+        self.end_fill.write_to(writer)?;
+        writer.write_bytes(")".as_bytes())?;
+        Ok(())
+    }
+
+    /// Parses a condition, including its parentheses.
+    pub fn parse(content: Span) -> Result<(Self, Span), SpanError>
+    {
+        let Ok((remaining, _)) = tag::<_, _, ()>("(").parse(content) else {
+            return Err(span_diagnostic(content, "expected '(' after #if"));
+        };
+        let (start_fill, remaining) = CobFill::parse(remaining);
+
+        let (remaining, negated) = match tag::<_, _, ()>("!").parse(remaining) {
+            Ok((remaining, _)) => (remaining, true),
+            Err(_) => (remaining, false),
+        };
+        let Ok((remaining, flag)) = snake_identifier(remaining) else {
+            return Err(span_diagnostic(
+                remaining,
+                "expected a snake-case flag name in #if condition",
+            ));
+        };
+        let flag = SmolStr::from(*flag.fragment());
+        let (fill, remaining) = CobFill::parse(remaining);
+
+        let op = if let Ok((after_op, _)) = tag::<_, _, ()>("!=").parse(remaining) {
+            Some((CobConditionOp::NotEquals, after_op))
+        } else if let Ok((after_op, _)) = tag::<_, _, ()>("=").parse(remaining) {
+            Some((CobConditionOp::Equals, after_op))
+        } else {
+            None
+        };
+
+        let (comparison, end_fill, remaining) = match op {
+            Some((op, after_op)) => {
+                if negated {
+                    return Err(span_diagnostic(
+                        remaining,
+                        "negated #if conditions can't be compared to a value",
+                    ));
+                }
+                let (value_fill, after_fill) = CobFill::parse(after_op);
+                let (Some(value), end_fill, remaining) = CobString::try_parse(value_fill, after_fill)? else {
+                    return Err(span_diagnostic(after_fill, "expected a string value in #if condition"));
+                };
+                (
+                    Some(CobConditionComparison { op_fill: fill, op, value }),
+                    end_fill,
+                    remaining,
+                )
+            }
+            None => (None, fill, remaining),
+        };
+
+        let Ok((remaining, _)) = tag::<_, _, ()>(")").parse(remaining) else {
+            return Err(span_diagnostic(remaining, "expected ')' to close #if condition"));
+        };
+
+        Ok((Self { start_fill, negated, flag, comparison, end_fill }, remaining))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// The `#else` branch of an `#if` block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobConditionalElse
+{
+    pub start_fill: CobFill,
+    pub sections: Vec<CobSection>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A block of sections that are only used if a condition holds.
+///
+/// ```text
+/// #if(platform = "wasm")
+/// #commands
+/// Fullscreen(false)
+///
+/// #else
+/// #commands
+/// Fullscreen(true)
+///
+/// #endif
+/// ```
+///
+/// Blocks can contain `#defs`, `#theme`, `#commands`, `#scenes`, and nested `#if` blocks. To make individual
+/// entries or scene nodes conditional, put them in their own section inside the block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobConditional
+{
+    pub start_fill: CobFill,
+    pub condition: CobCondition,
+    pub sections: Vec<CobSection>,
+    pub else_branch: Option<CobConditionalElse>,
+    /// Fill before `#endif`.
+    pub end_fill: CobFill,
+}
+
+impl CobConditional
+{
+    /// Gets the sections of the branch selected by `flags`.
+    pub fn active_sections(&self, flags: &CobFlags) -> &[CobSection]
+    {
+        if self.condition.evaluate(flags) {
+            &self.sections
+        } else {
+            self.else_branch
+                .as_ref()
+                .map(|branch| branch.sections.as_slice())
+                .unwrap_or(&[])
+        }
+    }
+
+    /// Gets the sections of the branch selected by `flags`.
+    pub fn active_sections_mut(&mut self, flags: &CobFlags) -> &mut [CobSection]
+    {
+        if self.condition.evaluate(flags) {
+            &mut self.sections
+        } else {
+            self.else_branch
+                .as_mut()
+                .map(|branch| branch.sections.as_mut_slice())
+                .unwrap_or(&mut [])
+        }
+    }
+
+    pub fn write_to(&self, first_section: bool, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
+    {
+        let space = if first_section { "" } else { "\n\n" };
+        self.start_fill.write_to_or_else(writer, space)?;
+        writer.write_bytes("#if".as_bytes())?;
+        self.condition.write_to(writer)?;
+        for section in self.sections.iter() {
+            section.write_to(false, writer)?;
+        }
+        if let Some(branch) = &self.else_branch {
+            branch.start_fill.write_to_or_else(writer, "\n\n")?;
+            writer.write_bytes("#else".as_bytes())?;
+            for section in branch.sections.iter() {
+                section.write_to(false, writer)?;
+            }
+        }
+        self.end_fill.write_to_or_else(writer, "\n\n")?;
+        writer.write_bytes("#endif".as_bytes())?;
+        Ok(())
+    }
+
+    pub fn try_parse(start_fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
+    {
+        let Ok((remaining, _)) = tag::<_, _, ()>("#if").parse(content) else {
+            return Ok((None, start_fill, content));
+        };
+
+        if start_fill.len() != 0 && !start_fill.ends_with_newline() {
+            return Err(span_diagnostic(content, "expected #if block to start on a new line"));
+        }
+
+        let (condition, remaining) = CobCondition::parse(remaining)?;
+        let (fill, remaining) = CobFill::parse(remaining);
+        let (sections, mut fill, mut remaining) = parse_branch(fill, remaining)?;
+
+        let mut else_branch = None;
+        if let Ok((after_else, _)) = tag::<_, _, ()>("#else").parse(remaining) {
+            if !fill.ends_with_newline() {
+                return Err(span_diagnostic(remaining, "expected #else to start on a new line"));
+            }
+            let (else_fill, after_else) = CobFill::parse(after_else);
+            let (else_sections, next_fill, next_remaining) = parse_branch(else_fill, after_else)?;
+            else_branch = Some(CobConditionalElse { start_fill: fill, sections: else_sections });
+            fill = next_fill;
+            remaining = next_remaining;
+        }
+
+        let Ok((after_endif, _)) = tag::<_, _, ()>("#endif").parse(remaining) else {
+            let message = match else_branch {
+                Some(_) => "expected #endif to close #if",
+                None => "expected #else or #endif to close #if",
+            };
+            return Err(span_diagnostic(remaining, message));
+        };
+        if !fill.ends_with_newline() {
+            return Err(span_diagnostic(remaining, "expected #endif to start on a new line"));
+        }
+
+        let (next_fill, remaining) = CobFill::parse(after_endif);
+        let conditional = Self { start_fill, condition, sections, else_branch, end_fill: fill };
+        Ok((Some(conditional), next_fill, remaining))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
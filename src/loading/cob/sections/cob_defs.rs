@@ -40,8 +40,7 @@ impl CobDefEntry
         let starts_newline = fill.ends_with_newline();
         let check_newline = || -> Result<(), SpanError> {
             if !starts_newline {
-                tracing::warn!("def entry doesn't start on a new line at {}", get_location(content).as_str());
-                return Err(span_verify_error(content));
+                return Err(span_diagnostic(content, "expected def entry to start on a new line"));
             }
             Ok(())
         };
@@ -108,25 +107,29 @@ impl CobDefs
         };
 
         if start_fill.len() != 0 && !start_fill.ends_with_newline() {
-            tracing::warn!("failed parsing defs section at {} that doesn't start on newline",
-                get_location(content).as_str());
-            return Err(span_verify_error(content));
+            return Err(span_diagnostic(
+                content,
+                "expected #defs section to start on a new line",
+            ));
         }
 
         let (mut item_fill, mut remaining) = CobFill::parse(remaining);
         let mut entries = vec![];
 
         let end_fill = loop {
-            match rc(remaining, move |rm| CobDefEntry::try_parse(item_fill, rm))? {
-                (Some(entry), next_fill, after_entry) => {
+            match rc(remaining, move |rm| CobDefEntry::try_parse(item_fill, rm)) {
+                Ok((Some(entry), next_fill, after_entry)) => {
                     entries.push(entry);
                     item_fill = next_fill;
                     remaining = after_entry;
                 }
-                (None, end_fill, after_end) => {
+                Ok((None, end_fill, after_end)) => {
                     remaining = after_end;
                     break end_fill;
                 }
+                Err(err) => {
+                    (item_fill, remaining) = try_recover_section_item(err, remaining)?;
+                }
             }
         };
 
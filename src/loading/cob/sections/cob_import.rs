@@ -101,19 +101,16 @@ impl CobImportEntry
             return Ok((None, entry_fill, content));
         };
         if !entry_fill.ends_with_newline() {
-            tracing::warn!("import entry doesn't start on a new line at {}", get_location(content).as_str());
-            return Err(span_verify_error(content));
+            return Err(span_diagnostic(content, "expected import entry to start on a new line"));
         }
         let (as_fill, remaining) = CobFill::parse(remaining);
         if as_fill.len() == 0 {
-            tracing::warn!("no fill/whitespace before import 'as' at {}", get_location(remaining).as_str());
-            return Err(span_verify_error(remaining));
+            return Err(span_diagnostic(remaining, "expected whitespace before import 'as'"));
         }
         let (remaining, _) = tag("as").parse(remaining)?;
         let (alias_fill, remaining) = CobFill::parse(remaining);
         if alias_fill.len() == 0 {
-            tracing::warn!("no fill/whitespace after import 'as' at {}", get_location(remaining).as_str());
-            return Err(span_verify_error(remaining));
+            return Err(span_diagnostic(remaining, "expected whitespace after import 'as'"));
         }
         let (alias, remaining) = CobImportAlias::parse(remaining)?;
         let (next_fill, remaining) = CobFill::parse(remaining);
@@ -178,9 +175,10 @@ impl CobImport
         };
 
         if start_fill.len() != 0 && !start_fill.ends_with_newline() {
-            tracing::warn!("failed parsing import section at {} that doesn't start on newline",
-                get_location(content).as_str());
-            return Err(span_verify_error(content));
+            return Err(span_diagnostic(
+                content,
+                "expected #import section to start on a new line",
+            ));
         }
 
         let (mut item_fill, mut remaining) = CobFill::parse(remaining);
@@ -135,19 +135,19 @@ impl CobManifestEntry
             return Ok((None, entry_fill, content));
         };
         if !entry_fill.ends_with_newline() {
-            tracing::warn!("manifest entry doesn't start on a new line at {}", get_location(content).as_str());
-            return Err(span_verify_error(content));
+            return Err(span_diagnostic(
+                content,
+                "expected manifest entry to start on a new line",
+            ));
         }
         let (as_fill, remaining) = CobFill::parse(remaining);
         if as_fill.len() == 0 {
-            tracing::warn!("no fill/whitespace before manifest 'as' at {}", get_location(remaining).as_str());
-            return Err(span_verify_error(remaining));
+            return Err(span_diagnostic(remaining, "expected whitespace before manifest 'as'"));
         }
         let (remaining, _) = tag("as").parse(remaining)?;
         let (key_fill, remaining) = CobFill::parse(remaining);
         if key_fill.len() == 0 {
-            tracing::warn!("no fill/whitespace after manifest 'as' at {}", get_location(remaining).as_str());
-            return Err(span_verify_error(remaining));
+            return Err(span_diagnostic(remaining, "expected whitespace after manifest 'as'"));
         }
         let (key, remaining) = ManifestKey::parse(remaining)?;
         let (next_fill, remaining) = CobFill::parse(remaining);
@@ -202,9 +202,10 @@ impl CobManifest
         };
 
         if start_fill.len() != 0 && !start_fill.ends_with_newline() {
-            tracing::warn!("failed parsing manifest section at {} that doesn't start on newline",
-                get_location(content).as_str());
-            return Err(span_verify_error(content));
+            return Err(span_diagnostic(
+                content,
+                "expected #manifest section to start on a new line",
+            ));
         }
 
         let (mut item_fill, mut remaining) = CobFill::parse(remaining);
@@ -33,9 +33,10 @@ impl CobSceneNodeName
             tag("\""),
         )
         .parse(remaining) else {
-            tracing::warn!("failed parsing scene node name at {}; name is not snake-case (e.g. a_b_c)",
-                get_location(content).as_str());
-            return Err(span_verify_error(content));
+            return Err(span_diagnostic(
+                content,
+                "expected snake-case scene node name (e.g. a_b_c)",
+            ));
         };
 
         Ok((Some(Self(SmolStr::from(name))), remaining))
@@ -44,6 +45,50 @@ impl CobSceneNodeName
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Removes a loadable inherited from a base scene, e.g. `-BackgroundColor`.
+///
+/// See [`CobSceneBase`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobLoadableRemoval
+{
+    pub start_fill: CobFill,
+    pub id: CobLoadableIdentifier,
+}
+
+impl CobLoadableRemoval
+{
+    pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
+    {
+        self.start_fill.write_to(writer)?;
+        writer.write_bytes("-".as_bytes())?;
+        self.id.write_to(writer)?;
+        Ok(())
+    }
+
+    pub fn try_parse(fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
+    {
+        let Ok((remaining, _)) = char::<_, ()>('-').parse(content) else {
+            return Ok((None, fill, content));
+        };
+        let Ok((id, remaining)) = CobLoadableIdentifier::parse(remaining) else {
+            return Err(span_diagnostic(
+                content,
+                "expected loadable name after '-' in loadable removal",
+            ));
+        };
+        let (next_fill, remaining) = CobFill::parse(remaining);
+        Ok((Some(Self { start_fill: fill, id }), next_fill, remaining))
+    }
+
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        self.start_fill.recover(&other.start_fill);
+        self.id.recover_fill(&other.id);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Full loadable.
 #[derive(Debug, Clone, PartialEq)]
 pub enum CobSceneLayerEntry
@@ -54,6 +99,8 @@ pub enum CobSceneLayerEntry
     Layer(CobSceneLayer),
     /// This is the `..'node_name'` and `..*` syntax.
     SceneMacroParam(CobSceneMacroParam),
+    /// This is the `-LoadableName` syntax.
+    LoadableRemoval(CobLoadableRemoval),
 }
 
 impl CobSceneLayerEntry
@@ -76,6 +123,9 @@ impl CobSceneLayerEntry
             Self::SceneMacroParam(entry) => {
                 entry.write_to(writer)?;
             }
+            Self::LoadableRemoval(entry) => {
+                entry.write_to(writer)?;
+            }
         }
         Ok(())
     }
@@ -93,9 +143,7 @@ impl CobSceneLayerEntry
                 // End-of-file
                 return Ok((None, fill, content));
             }
-            tracing::warn!("failed parsing scene at {}; encountered something that isn't on a separate line",
-                get_location(content));
-            return Err(span_verify_error(content));
+            return Err(span_diagnostic(content, "expected scene item to be on a separate line"));
         };
 
         // The next item isn't on the active layer.
@@ -124,6 +172,12 @@ impl CobSceneLayerEntry
             (None, fill, _) => fill,
         };
         let fill = match rc(content, move |c| CobSceneLayer::try_parse(fill, c))? {
+            (Some(item), _, _) if item.base.is_some() => {
+                return Err(span_diagnostic(
+                    content,
+                    "only scene roots can derive from a base scene",
+                ));
+            }
             (Some(item), fill, remaining) => return Ok((Some(Self::Layer(item)), fill, remaining)),
             (None, fill, _) => fill,
         };
@@ -131,10 +185,48 @@ impl CobSceneLayerEntry
             (Some(item), fill, remaining) => return Ok((Some(Self::SceneMacroParam(item)), fill, remaining)),
             (None, fill, _) => fill,
         };
+        let fill = match rc(content, move |c| CobLoadableRemoval::try_parse(fill, c))? {
+            (Some(item), fill, remaining) => return Ok((Some(Self::LoadableRemoval(item)), fill, remaining)),
+            (None, fill, _) => fill,
+        };
 
         Ok((None, fill, content))
     }
 
+    /// Parses the entries of a scene layer.
+    ///
+    /// Entries are collected until an item is found with indent `<= layer_indent`.
+    pub(crate) fn parse_entries(
+        layer_indent: usize,
+        content_indent: usize,
+        mut item_fill: CobFill,
+        mut remaining: Span,
+    ) -> Result<(Vec<Self>, CobFill, Span), SpanError>
+    {
+        let mut entries = vec![];
+        let end_fill = loop {
+            // Note: this will properly handle the case where content_indent <= layer_indent.
+            match rc(remaining, move |rm| {
+                Self::try_parse(layer_indent, content_indent, item_fill, rm)
+            }) {
+                Ok((Some(entry), next_fill, after_entry)) => {
+                    entries.push(entry);
+                    item_fill = next_fill;
+                    remaining = after_entry;
+                }
+                Ok((None, end_fill, after_end)) => {
+                    remaining = after_end;
+                    break end_fill;
+                }
+                Err(err) => {
+                    (item_fill, remaining) = try_recover_layer_item(err, remaining, content_indent)?;
+                }
+            }
+        };
+
+        Ok((entries, end_fill, remaining))
+    }
+
     pub fn recover_fill(&mut self, other: &Self)
     {
         match (self, other) {
@@ -153,6 +245,9 @@ impl CobSceneLayerEntry
             (Self::SceneMacroParam(entry), Self::SceneMacroParam(other_entry)) => {
                 entry.recover_fill(other_entry);
             }
+            (Self::LoadableRemoval(entry), Self::LoadableRemoval(other_entry)) => {
+                entry.recover_fill(other_entry);
+            }
             _ => (),
         }
     }
@@ -160,6 +255,78 @@ impl CobSceneLayerEntry
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// The base scene of a derived scene, e.g. `: base::"menu_frame"` in `"settings_menu" : base::"menu_frame"`.
+///
+/// A derived scene starts as a copy of its base scene. Loadables in the derived scene override loadables in the
+/// base scene, [`CobLoadableRemoval`]s remove them, and scene nodes with the same name as nodes in the base scene
+/// are patched in the same way. Other scene nodes are appended.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobSceneBase
+{
+    /// Fill before the `:`.
+    pub colon_fill: CobFill,
+    /// Fill after the `:`.
+    pub base_fill: CobFill,
+    /// Manifest key of the file that contains the base scene.
+    ///
+    /// Will be `None` if the base scene is in the same file as the derived scene.
+    pub file: Option<ManifestKey>,
+    pub name: CobSceneNodeName,
+}
+
+impl CobSceneBase
+{
+    pub fn write_to(&self, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
+    {
+        self.colon_fill.write_to_or_else(writer, " ")?;
+        writer.write_bytes(":".as_bytes())?;
+        self.base_fill.write_to_or_else(writer, " ")?;
+        if let Some(file) = &self.file {
+            file.write_to(writer)?;
+            writer.write_bytes("::".as_bytes())?;
+        }
+        self.name.write_to(writer)?;
+        Ok(())
+    }
+
+    pub fn try_parse(content: Span) -> Result<(Option<Self>, Span), SpanError>
+    {
+        let (colon_fill, remaining) = CobFill::parse(content);
+        if colon_fill.ends_newline_then_num_spaces().is_some() {
+            return Ok((None, content));
+        }
+        let Ok((remaining, _)) = char::<_, ()>(':').parse(remaining) else { return Ok((None, content)) };
+        let (base_fill, remaining) = CobFill::parse(remaining);
+        if base_fill.ends_newline_then_num_spaces().is_some() {
+            return Err(span_diagnostic(
+                remaining,
+                "expected base scene on the same line as the derived scene",
+            ));
+        }
+
+        let (file, remaining) = match terminated(ManifestKey::parse, tag::<_, _, ()>("::")).parse(remaining) {
+            Ok((remaining, file)) => (Some(file), remaining),
+            Err(_) => (None, remaining),
+        };
+        let (Some(name), remaining) = CobSceneNodeName::try_parse(remaining)? else {
+            return Err(span_diagnostic(
+                remaining,
+                "expected base scene name (e.g. \"name\" or manifest_key::\"name\")",
+            ));
+        };
+
+        Ok((Some(Self { colon_fill, base_fill, file, name }), remaining))
+    }
+
+    pub fn recover_fill(&mut self, other: &Self)
+    {
+        self.colon_fill.recover(&other.colon_fill);
+        self.base_fill.recover(&other.base_fill);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct CobSceneLayer
 {
@@ -168,6 +335,8 @@ pub struct CobSceneLayer
     /// Whitespace between the name and most recent newline is used to control scene layer depth.
     pub name_fill: CobFill,
     pub name: CobSceneNodeName,
+    /// The scene this scene derives from. Only scene roots can have a base scene.
+    pub base: Option<CobSceneBase>,
     pub entries: Vec<CobSceneLayerEntry>,
 }
 
@@ -177,6 +346,9 @@ impl CobSceneLayer
     {
         self.name_fill.write_to_or_else(writer, "\n")?;
         self.name.write_to(writer)?;
+        if let Some(base) = &self.base {
+            base.write_to(writer)?;
+        }
         for entry in self.entries.iter() {
             entry.write_to(writer)?;
         }
@@ -192,48 +364,46 @@ impl CobSceneLayer
 
         // Extract layer indent
         let Some(layer_indent) = name_fill.ends_newline_then_num_spaces() else {
-            tracing::warn!("failed parsing scene at {}; node name is not on a separate line from the previous item",
-                get_location(remaining));
-            return Err(span_verify_error(content));
+            return Err(span_diagnostic(
+                content,
+                "expected scene node name to be on a separate line from the previous item",
+            ));
         };
 
+        let (base, remaining) = CobSceneBase::try_parse(remaining)?;
+
         // Get content indent from first item_fill.
-        let (mut item_fill, mut remaining) = CobFill::parse(remaining);
+        let (item_fill, remaining) = CobFill::parse(remaining);
         let Some(content_indent) = item_fill.ends_newline_then_num_spaces() else {
             if remaining.fragment().len() == 0 {
                 // End-of-file
-                return Ok((Some(Self { name_fill, name, entries: vec![] }), item_fill, remaining));
+                return Ok((
+                    Some(Self { name_fill, name, base, entries: vec![] }),
+                    item_fill,
+                    remaining,
+                ));
             }
-            tracing::warn!("failed parsing scene at {}; first item after a node name isn't on a separate line",
-                get_location(remaining));
-            return Err(span_verify_error(remaining));
+            return Err(span_diagnostic(
+                remaining,
+                "expected first item after a scene node name to be on a separate line",
+            ));
         };
 
         // Collect entries.
-        let mut entries = vec![];
-        let end_fill = loop {
-            // Note: this will properly handle the case where content_indent <= layer_indent.
-            match rc(remaining, move |rm| {
-                CobSceneLayerEntry::try_parse(layer_indent, content_indent, item_fill, rm)
-            })? {
-                (Some(entry), next_fill, after_entry) => {
-                    entries.push(entry);
-                    item_fill = next_fill;
-                    remaining = after_entry;
-                }
-                (None, end_fill, after_end) => {
-                    remaining = after_end;
-                    break end_fill;
-                }
-            }
-        };
+        recovery_push_scene_node(name.as_str());
+        let result = CobSceneLayerEntry::parse_entries(layer_indent, content_indent, item_fill, remaining);
+        recovery_pop_scene_node();
+        let (entries, end_fill, remaining) = result?;
 
-        Ok((Some(Self { name_fill, name, entries }), end_fill, remaining))
+        Ok((Some(Self { name_fill, name, base, entries }), end_fill, remaining))
     }
 
     pub fn recover_fill(&mut self, other: &Self)
     {
         self.name_fill.recover(&other.name_fill);
+        if let (Some(base), Some(other_base)) = (&mut self.base, &other.base) {
+            base.recover_fill(other_base);
+        }
         for (entry, other) in self.entries.iter_mut().zip(other.entries.iter()) {
             entry.recover_fill(other);
         }
@@ -269,36 +439,54 @@ impl CobScenes
         };
 
         if start_fill.len() != 0 && !start_fill.ends_with_newline() {
-            tracing::warn!("failed parsing scenes section at {} that doesn't start on newline",
-                get_location(remaining).as_str());
-            return Err(span_verify_error(remaining));
+            return Err(span_diagnostic(
+                remaining,
+                "expected #scenes section to start on a new line",
+            ));
         }
 
-        let (mut item_fill, mut remaining) = CobFill::parse(remaining);
+        let (item_fill, remaining) = CobFill::parse(remaining);
         let mut scenes = vec![];
 
+        recovery_enter_scenes();
+        let result = Self::parse_scenes(item_fill, remaining, &mut scenes);
+        recovery_exit_scenes();
+        let (end_fill, remaining) = result?;
+
+        let scenes = CobScenes { start_fill, scenes };
+        Ok((Some(scenes), end_fill, remaining))
+    }
+
+    fn parse_scenes<'a>(
+        mut item_fill: CobFill,
+        mut remaining: Span<'a>,
+        scenes: &mut Vec<CobSceneLayer>,
+    ) -> Result<(CobFill, Span<'a>), SpanError<'a>>
+    {
         let end_fill = loop {
             let item_depth = item_fill.ends_newline_then_num_spaces();
-            match rc(remaining, move |rm| CobSceneLayer::try_parse(item_fill, rm))? {
-                (Some(entry), next_fill, after_entry) => {
+            match rc(remaining, move |rm| CobSceneLayer::try_parse(item_fill, rm)) {
+                Ok((Some(entry), next_fill, after_entry)) => {
                     if item_depth != Some(0) {
-                        tracing::warn!("failed parsing scene at {}; scene is assessed to be on base layer \
-                            but doesn't start with a newline", get_location(remaining).as_str());
-                        return Err(span_verify_error(remaining));
+                        let err = span_diagnostic(remaining, "expected scene to start on a new line");
+                        (item_fill, remaining) = try_recover_section_item(err, remaining)?;
+                        continue;
                     }
                     scenes.push(entry);
                     item_fill = next_fill;
                     remaining = after_entry;
                 }
-                (None, end_fill, after_end) => {
+                Ok((None, end_fill, after_end)) => {
                     remaining = after_end;
                     break end_fill;
                 }
+                Err(err) => {
+                    (item_fill, remaining) = try_recover_section_item(err, remaining)?;
+                }
             }
         };
 
-        let scenes = CobScenes { start_fill, scenes };
-        Ok((Some(scenes), end_fill, remaining))
+        Ok((end_fill, remaining))
     }
 }
 
@@ -0,0 +1,111 @@
+use nom::bytes::complete::tag;
+use nom::Parser;
+use smol_str::SmolStr;
+
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A set of overrides for constants defined in `#defs` sections of the same file.
+///
+/// When a theme is selected with [`ActiveTheme`], constants it overrides use the theme's values instead of their
+/// default values.
+///
+/// ```text
+/// #defs
+/// $bg = #101010
+/// $text = #F0F0F0
+///
+/// #theme(light)
+/// $bg = #F0F0F0
+/// $text = #101010
+/// ```
+///
+/// Multiple `#theme` sections with the same name are combined.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CobTheme
+{
+    pub start_fill: CobFill,
+    /// Fill between `(` and the theme name.
+    pub name_fill: CobFill,
+    pub name: SmolStr,
+    /// Fill between the theme name and `)`.
+    pub name_end_fill: CobFill,
+    pub entries: Vec<CobConstantDef>,
+}
+
+impl CobTheme
+{
+    pub fn write_to(&self, first_section: bool, writer: &mut impl RawSerializer) -> Result<(), std::io::Error>
+    {
+        let space = if first_section { "" } else { "\n\n" };
+        self.start_fill.write_to_or_else(writer, space)?;
+        writer.write_bytes("#theme(".as_bytes())?;
+        self.name_fill.write_to(writer)?;
+        writer.write_bytes(self.name.as_bytes())?;
+        self.name_end_fill.write_to(writer)?;
+        writer.write_bytes(")".as_bytes())?;
+        for entry in self.entries.iter() {
+            entry.write_to(writer)?;
+        }
+        Ok(())
+    }
+
+    pub fn try_parse(start_fill: CobFill, content: Span) -> Result<(Option<Self>, CobFill, Span), SpanError>
+    {
+        let Ok((remaining, _)) = tag::<_, _, ()>("#theme").parse(content) else {
+            return Ok((None, start_fill, content));
+        };
+
+        if start_fill.len() != 0 && !start_fill.ends_with_newline() {
+            return Err(span_diagnostic(
+                content,
+                "expected #theme section to start on a new line",
+            ));
+        }
+
+        let Ok((remaining, _)) = tag::<_, _, ()>("(").parse(remaining) else {
+            return Err(span_diagnostic(remaining, "expected '(' after #theme"));
+        };
+        let (name_fill, remaining) = CobFill::parse(remaining);
+        let Ok((remaining, name)) = snake_identifier(remaining) else {
+            return Err(span_diagnostic(remaining, "expected a snake-case theme name in #theme"));
+        };
+        let name = SmolStr::from(*name.fragment());
+        let (name_end_fill, remaining) = CobFill::parse(remaining);
+        let Ok((remaining, _)) = tag::<_, _, ()>(")").parse(remaining) else {
+            return Err(span_diagnostic(remaining, "expected ')' after #theme name"));
+        };
+
+        let (mut item_fill, mut remaining) = CobFill::parse(remaining);
+        let mut entries = vec![];
+
+        let end_fill = loop {
+            let entry_content = remaining;
+            match rc(remaining, move |rm| CobDefEntry::try_parse(item_fill, rm)) {
+                Ok((Some(CobDefEntry::Constant(entry)), next_fill, after_entry)) => {
+                    entries.push(entry);
+                    item_fill = next_fill;
+                    remaining = after_entry;
+                }
+                Ok((Some(_), _, _)) => {
+                    let err =
+                        span_diagnostic(entry_content, "only constants can be overridden in #theme sections");
+                    (item_fill, remaining) = try_recover_section_item(err, remaining)?;
+                }
+                Ok((None, end_fill, after_end)) => {
+                    remaining = after_end;
+                    break end_fill;
+                }
+                Err(err) => {
+                    (item_fill, remaining) = try_recover_section_item(err, remaining)?;
+                }
+            }
+        };
+
+        let theme = CobTheme { start_fill, name_fill, name, name_end_fill, entries };
+        Ok((Some(theme), end_fill, remaining))
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
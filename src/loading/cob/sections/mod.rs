@@ -1,11 +1,15 @@
 mod cob_commands;
+mod cob_conditional;
 mod cob_defs;
 mod cob_import;
 mod cob_manifest;
 mod cob_scenes;
+mod cob_theme;
 
 pub use cob_commands::*;
+pub use cob_conditional::*;
 pub use cob_defs::*;
 pub use cob_import::*;
 pub use cob_manifest::*;
 pub use cob_scenes::*;
+pub use cob_theme::*;
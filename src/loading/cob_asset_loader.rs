@@ -11,6 +11,9 @@ use crate::prelude::*;
 
 struct CobAssetLoader
 {
+    type_registry: AppTypeRegistry,
+    loadables: LoadableRegistry,
+    diagnostics: CobDiagnostics,
     #[cfg(feature = "editor")]
     registry: CobHashRegistry,
 }
@@ -51,29 +54,52 @@ impl AssetLoader for CobAssetLoader
         }
 
         // Parse the raw file data.
-        let data = match Cob::parse(Span::new_extra(&string, CobLocationMetadata { file: file.as_str() })) {
-            Ok(data) => data,
-            Err(nom::Err::Error(err)) | Err(nom::Err::Failure(err)) => {
-                let nom::error::Error { input, code } = err;
-                return Err(CobAssetLoaderError::CobParsing(
-                    format!("error at {}: {:?}", get_location(input).as_str(), code),
-                ));
-            }
-            Err(nom::Err::Incomplete(err)) => {
-                return Err(CobAssetLoaderError::CobParsing(
-                    format!("insufficient data in {}: {:?}", file.as_str(), err),
-                ));
-            }
-        };
+        // - Errors are recovered from so the valid parts of the file can be applied.
+        self.diagnostics.clear_file(&file);
+        let recovery =
+            match Cob::parse_recoverable(Span::new_extra(&string, CobLocationMetadata { file: file.as_str() })) {
+                Ok(recovery) => recovery,
+                Err(err @ nom::Err::Error(_)) | Err(err @ nom::Err::Failure(_)) => {
+                    let diagnostic =
+                        CobDiagnostic::from_span_error(&err).expect("only incomplete errors are skipped");
+                    self.diagnostics.add(diagnostic.clone());
+                    return Err(CobAssetLoaderError::Diagnostic(diagnostic));
+                }
+                Err(nom::Err::Incomplete(err)) => {
+                    return Err(CobAssetLoaderError::CobParsing(
+                        format!("insufficient data in {}: {:?}", file.as_str(), err),
+                    ));
+                }
+            };
+
+        #[cfg(feature = "editor")]
+        let has_errors = !recovery.is_ok();
+        for diagnostic in recovery.diagnostics {
+            tracing::error!("{}", diagnostic);
+            self.diagnostics.add(diagnostic);
+        }
+        let data = recovery.cob;
+
+        // Check for semantic problems up-front so they are all reported at once.
+        for diagnostic in validate(&data, &self.type_registry.read(), &self.loadables) {
+            tracing::warn!("{}", diagnostic);
+            self.diagnostics.add(diagnostic);
+        }
+        #[cfg(feature = "hot_reload")]
+        let broken_nodes = recovery.broken_nodes;
 
         #[cfg(not(feature = "editor"))]
         {
-            return Ok(CobAssetFile::File { data });
+            return Ok(CobAssetFile::File {
+                data,
+                #[cfg(feature = "hot_reload")]
+                broken_nodes,
+            });
         }
 
         #[cfg(feature = "editor")]
         {
-            return Ok(CobAssetFile::File { hash, data });
+            return Ok(CobAssetFile::File { hash, has_errors, data, broken_nodes });
         }
     }
 
@@ -85,6 +111,53 @@ impl AssetLoader for CobAssetLoader
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Loads `.cobc` files written by [`CobCompiler`].
+struct CompiledCobAssetLoader;
+
+impl AssetLoader for CompiledCobAssetLoader
+{
+    type Asset = CobAssetFile;
+    type Settings = ();
+    type Error = CobAssetLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error>
+    {
+        // Get file name including source.
+        let file = load_context.asset_path().path().to_string_lossy();
+        let file = match load_context.asset_path().source() {
+            AssetSourceId::Default => String::from(&*file),
+            AssetSourceId::Name(name) => format!("{}://{}", *name, &*file),
+        };
+        let Some(file) = CobFile::try_new_compiled(&file) else {
+            return Err(CobAssetLoaderError::Compiled(
+                format!("expected file name ending in '.cobc': {}", file),
+            ));
+        };
+
+        // Read the file.
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).await?;
+
+        // Decode the compiled data.
+        let data = CompiledCob::from_bytes(&bytes)
+            .map_err(|err| CobAssetLoaderError::Compiled(format!("{} in {}", err, file.as_str())))?;
+
+        Ok(CobAssetFile::Compiled { file, data })
+    }
+
+    fn extensions(&self) -> &[&str]
+    {
+        &[".cobc"]
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Possible errors that can be produced by the internal `CobAssetLoader`.
 #[non_exhaustive]
 #[derive(Debug, Error)]
@@ -96,6 +169,12 @@ pub enum CobAssetLoaderError
     /// A COB Error.
     #[error("Could not parse the CobAssetFile data: {0}")]
     CobParsing(String),
+    /// A COB parsing error with source location information.
+    #[error("Could not parse the CobAssetFile data:\n{0}")]
+    Diagnostic(CobDiagnostic),
+    /// A compiled COB file could not be decoded.
+    #[error("Could not decode the compiled CobAssetFile data: {0}")]
+    Compiled(String),
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -112,7 +191,19 @@ pub(crate) enum CobAssetFile
     {
         #[cfg(feature = "editor")]
         hash: CobFileHash,
+        /// Whether the file had parse errors. Files with errors can't be edited in the editor, since saving them
+        /// would discard the invalid parts of the file.
+        #[cfg(feature = "editor")]
+        has_errors: bool,
         data: Cob,
+        /// Scene nodes that failed to parse. The last good version of these nodes will be kept.
+        #[cfg(feature = "hot_reload")]
+        broken_nodes: Vec<ScenePath>,
+    },
+    /// A compiled file.
+    Compiled
+    {
+        file: CobFile, data: CompiledCob
     },
 }
 
@@ -124,9 +215,21 @@ impl Plugin for CobAssetLoaderPlugin
 {
     fn build(&self, app: &mut App)
     {
+        let type_registry = app.world().resource::<AppTypeRegistry>().clone();
+        let loadables = app
+            .world_mut()
+            .get_resource_or_init::<LoadableRegistry>()
+            .share_names();
+        let diagnostics = app
+            .world_mut()
+            .get_resource_or_init::<CobDiagnostics>()
+            .clone();
+
+        app.register_asset_loader(CompiledCobAssetLoader);
+
         #[cfg(not(feature = "editor"))]
         {
-            app.register_asset_loader(CobAssetLoader {});
+            app.register_asset_loader(CobAssetLoader { type_registry, loadables, diagnostics });
         }
 
         #[cfg(feature = "editor")]
@@ -135,7 +238,7 @@ impl Plugin for CobAssetLoaderPlugin
                 .world_mut()
                 .get_resource_or_init::<CobHashRegistry>()
                 .clone();
-            app.register_asset_loader(CobAssetLoader { registry });
+            app.register_asset_loader(CobAssetLoader { type_registry, loadables, diagnostics, registry });
         }
     }
 }
@@ -0,0 +1,76 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use bevy::prelude::*;
+use bevy::utils::hashbrown::HashMap;
+
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Resource that collects [`CobDiagnostic`]s for loaded COB files.
+///
+/// Diagnostics for a file are cleared whenever the file is reloaded, so this always reflects the problems in the
+/// latest version of each file. Editor tools can use this to show errors next to the source that caused them.
+#[derive(Resource, Clone)]
+pub struct CobDiagnostics
+{
+    /// [ file name : diagnostics ]
+    diagnostics: Arc<Mutex<HashMap<String, Vec<CobDiagnostic>>>>,
+}
+
+impl CobDiagnostics
+{
+    fn lock(&self) -> MutexGuard<HashMap<String, Vec<CobDiagnostic>>>
+    {
+        self.diagnostics
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Gets the diagnostics recorded for a file.
+    pub fn get(&self, file: &str) -> Vec<CobDiagnostic>
+    {
+        self.lock().get(file).cloned().unwrap_or_default()
+    }
+
+    /// Gets all recorded diagnostics.
+    pub fn all(&self) -> Vec<CobDiagnostic>
+    {
+        self.lock().values().flatten().cloned().collect()
+    }
+
+    /// Returns `true` if no files have diagnostics.
+    pub fn is_empty(&self) -> bool
+    {
+        self.lock()
+            .values()
+            .all(|diagnostics| diagnostics.is_empty())
+    }
+
+    pub(crate) fn clear_file(&self, file: &str)
+    {
+        self.lock().remove(file);
+    }
+
+    pub(crate) fn add(&self, diagnostic: CobDiagnostic)
+    {
+        let mut diagnostics = self.lock();
+        let entry = diagnostics.entry(diagnostic.file.clone()).or_default();
+
+        // Scenes may be re-extracted without reloading the file, so we ignore duplicates.
+        if entry.contains(&diagnostic) {
+            return;
+        }
+        entry.push(diagnostic);
+    }
+}
+
+impl Default for CobDiagnostics
+{
+    fn default() -> Self
+    {
+        Self { diagnostics: Arc::new(Mutex::new(HashMap::default())) }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use smol_str::SmolStr;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Resource with the flags that `#if` blocks in COB files are evaluated against.
+///
+/// The `platform` flag is set by default: `"wasm"` on wasm targets, otherwise the value of
+/// [`std::env::consts::OS`] (e.g. `"linux"`, `"windows"`, `"macos"`).
+///
+/// With the `hot_reload` feature, COB files are re-extracted when this resource changes. Otherwise flags must be
+/// set before COB files are loaded.
+///
+/// ```ignore
+/// app.world_mut().resource_mut::<CobFlags>().set("device", "steam_deck");
+/// ```
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct CobFlags
+{
+    flags: HashMap<SmolStr, SmolStr>,
+}
+
+impl CobFlags
+{
+    /// Makes a flag set without any flags.
+    pub fn empty() -> Self
+    {
+        Self { flags: HashMap::default() }
+    }
+
+    /// Sets a flag's value.
+    pub fn set(&mut self, flag: impl Into<SmolStr>, value: impl Into<SmolStr>) -> &mut Self
+    {
+        self.flags.insert(flag.into(), value.into());
+        self
+    }
+
+    /// Sets a flag without a value, for use in conditions like `#if(my_flag)`.
+    pub fn enable(&mut self, flag: impl Into<SmolStr>) -> &mut Self
+    {
+        self.set(flag, "")
+    }
+
+    /// Unsets a flag.
+    pub fn remove(&mut self, flag: impl AsRef<str>) -> &mut Self
+    {
+        self.flags.remove(flag.as_ref());
+        self
+    }
+
+    /// Gets a flag's value if it's set.
+    pub fn get(&self, flag: impl AsRef<str>) -> Option<&str>
+    {
+        self.flags.get(flag.as_ref()).map(|value| value.as_str())
+    }
+
+    /// Checks if a flag is set.
+    pub fn is_set(&self, flag: impl AsRef<str>) -> bool
+    {
+        self.flags.contains_key(flag.as_ref())
+    }
+}
+
+impl Default for CobFlags
+{
+    fn default() -> Self
+    {
+        let platform = if cfg!(target_family = "wasm") {
+            "wasm"
+        } else {
+            std::env::consts::OS
+        };
+        let mut flags = Self::empty();
+        flags.set("platform", platform);
+        flags
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
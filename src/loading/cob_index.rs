@@ -0,0 +1,365 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use bevy::asset::{LoadState as AssetLoadState, UntypedAssetId};
+use bevy::ecs::world::Command;
+use bevy::prelude::*;
+use bevy::reflect::serde::TypedReflectDeserializer;
+use bevy::reflect::{PartialReflect, TypeRegistry};
+use serde::de::DeserializeSeed;
+use serde::{Deserialize, Serialize};
+use smol_str::SmolStr;
+
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn check_preloaded_assets(
+    asset_server: Res<AssetServer>,
+    images: Res<ImageMap>,
+    fonts: Res<FontMap>,
+    audio: Res<AudioMap>,
+    mut preloaded: ResMut<PreloadedAssets>,
+)
+{
+    preloaded.update(&asset_server, &images, &fonts, &audio);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Gets the value of a compiled command if it has type `T`.
+fn get_command<T: FromReflect + TypePath>(type_registry: &TypeRegistry, command: &CompiledLoadable) -> Option<T>
+{
+    if command.type_path != T::type_path() {
+        return None;
+    }
+    let registration = type_registry.get_with_type_path(T::type_path())?;
+    let value = TypedReflectDeserializer::new(registration, type_registry)
+        .deserialize(&command.loadable)
+        .ok()?;
+    T::from_reflect(value.as_partial_reflect())
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn push_unique(paths: &mut Vec<String>, path: impl Into<String>)
+{
+    let path = path.into();
+    if !paths.contains(&path) {
+        paths.push(path);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A precomputed list of the COB files and assets an app loads on startup.
+///
+/// Normally COB files are discovered one manifest at a time, and assets are only requested once the
+/// [`LoadImages`], [`LoadFonts`], and [`LoadAudio`] commands that reference them are applied. Preloading an index
+/// with [`LoadedCobAssetFilesAppExt::preload`] requests everything in parallel on startup, and lets
+/// [`LoadProgress`] report accurate totals from the first frame.
+///
+/// An index can be made with [`Self::from_compiled`] or exported to a JSON file with [`ExportCobIndex`].
+/// Indexes only need to be up to date for performance. If an indexed file isn't in any manifest it will be
+/// discarded, and files missing from the index will be loaded as usual.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CobIndex
+{
+    /// COB files in manifest order.
+    pub files: Vec<String>,
+    /// Paths from [`LoadImages`] commands.
+    pub images: Vec<String>,
+    /// Paths of fonts in families requested by [`LoadFonts`] commands.
+    ///
+    /// Only fonts registered with [`RegisterFontFamilies`] in the same COB files are included.
+    pub fonts: Vec<String>,
+    /// Paths from [`LoadAudio`] commands.
+    pub audio: Vec<String>,
+}
+
+impl CobIndex
+{
+    /// Makes an index for the files in a [`CompiledCob`].
+    ///
+    /// Commands that can't be read with the type registry are ignored.
+    pub fn from_compiled(compiled: &CompiledCob, type_registry: &TypeRegistry) -> Self
+    {
+        let mut index = Self::default();
+        let mut font_families: HashMap<SmolStr, Vec<String>> = HashMap::default();
+        let mut requested_families = vec![];
+
+        for file in compiled.files.iter() {
+            push_unique(&mut index.files, file.file.as_str());
+
+            for command in file.commands.iter() {
+                if let Some(LoadImages(images)) = get_command(type_registry, command) {
+                    images
+                        .into_iter()
+                        .for_each(|path| push_unique(&mut index.images, path));
+                } else if let Some(LoadAudio(audio)) = get_command(type_registry, command) {
+                    audio
+                        .into_iter()
+                        .for_each(|path| push_unique(&mut index.audio, path));
+                } else if let Some(LoadFonts(families)) = get_command(type_registry, command) {
+                    requested_families.extend(families);
+                } else if let Some(RegisterFontFamilies(families)) = get_command(type_registry, command) {
+                    for RegisterFontFamily { family, fonts } in families {
+                        font_families
+                            .entry(family.0)
+                            .or_default()
+                            .extend(fonts.into_iter().map(|font| font.path));
+                    }
+                }
+            }
+        }
+
+        for family in requested_families.iter() {
+            let Some(fonts) = font_families.get(family) else { continue };
+            fonts
+                .iter()
+                .for_each(|path| push_unique(&mut index.fonts, path.as_str()));
+        }
+
+        index
+    }
+
+    /// Reads an index from JSON.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error>
+    {
+        serde_json::from_str(json)
+    }
+
+    /// Writes the index as JSON.
+    pub fn to_json(&self) -> String
+    {
+        serde_json::to_string_pretty(self).expect("COB indexes should always serialize")
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command that compiles COB files with a [`CobCompiler`] and writes their [`CobIndex`] to a JSON file.
+///
+/// ```ignore
+/// app.add_systems(Startup, |mut c: Commands| {
+///     c.queue(ExportCobIndex::new(CobCompiler::new("assets", ["main.cob"]), "assets/main.cob_index.json"));
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExportCobIndex
+{
+    pub compiler: CobCompiler,
+    pub path: PathBuf,
+}
+
+impl ExportCobIndex
+{
+    pub fn new(compiler: CobCompiler, path: impl Into<PathBuf>) -> Self
+    {
+        Self { compiler, path: path.into() }
+    }
+}
+
+impl Command for ExportCobIndex
+{
+    fn apply(self, world: &mut World)
+    {
+        let index = {
+            let type_registry = world.resource::<AppTypeRegistry>().read();
+            self.compiler
+                .compile(
+                    &type_registry,
+                    world.resource::<LoadableRegistry>(),
+                    world.resource::<CobFlags>(),
+                )
+                .map(|compiled| CobIndex::from_compiled(&compiled, &type_registry))
+        };
+
+        let index = match index {
+            Ok(index) => index,
+            Err(err) => {
+                tracing::error!("failed indexing COB files for {}: {}", self.path.display(), err);
+                return;
+            }
+        };
+
+        match std::fs::write(&self.path, index.to_json()) {
+            Ok(()) => tracing::info!("exported COB index to {}", self.path.display()),
+            Err(err) => tracing::error!("failed exporting COB index to {}: {}", self.path.display(), err),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Drops handles for assets that an asset map holds, so they aren't counted twice.
+fn release_tracked(
+    handles: &mut Vec<(String, UntypedHandle)>,
+    pending: &mut HashSet<UntypedAssetId>,
+    is_tracked: impl Fn(&str) -> bool,
+)
+{
+    handles.retain(|(path, handle)| {
+        if !is_tracked(path) {
+            return true;
+        }
+        pending.remove(&handle.id());
+        false
+    });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Keeps assets requested by a [`CobIndex`] alive and tracks their loading progress.
+///
+/// Once a [`LoadImages`], [`LoadFonts`], or [`LoadAudio`] command adds an asset to its asset map, the asset is
+/// released so [`LoadProgress`] totals stay the same.
+#[derive(Resource, Default)]
+pub(crate) struct PreloadedAssets
+{
+    /// [ path : handle ]
+    images: Vec<(String, UntypedHandle)>,
+    /// [ path : handle ]
+    fonts: Vec<(String, UntypedHandle)>,
+    /// [ path : handle ]
+    audio: Vec<(String, UntypedHandle)>,
+    pending: HashSet<UntypedAssetId>,
+}
+
+impl PreloadedAssets
+{
+    /// Requests the assets in an index.
+    pub(crate) fn load(&mut self, index: &CobIndex, asset_server: &AssetServer)
+    {
+        for path in index.images.iter() {
+            let handle = asset_server.load::<Image>(path).untyped();
+            self.pending.insert(handle.id());
+            self.images.push((path.clone(), handle));
+        }
+        for path in index.fonts.iter() {
+            let handle = asset_server.load::<Font>(path).untyped();
+            self.pending.insert(handle.id());
+            self.fonts.push((path.clone(), handle));
+        }
+        for path in index.audio.iter() {
+            let handle = asset_server.load::<AudioSource>(path).untyped();
+            self.pending.insert(handle.id());
+            self.audio.push((path.clone(), handle));
+        }
+    }
+
+    /// Releases assets held by the asset maps, then removes finished assets from the pending set.
+    fn update(&mut self, asset_server: &AssetServer, images: &ImageMap, fonts: &FontMap, audio: &AudioMap)
+    {
+        release_tracked(&mut self.images, &mut self.pending, |path| images.contains_path(path));
+        release_tracked(&mut self.fonts, &mut self.pending, |path| fonts.contains_path(path));
+        release_tracked(&mut self.audio, &mut self.pending, |path| audio.contains_path(path));
+
+        self.pending.retain(|id| {
+            !matches!(asset_server.load_state(*id), AssetLoadState::Loaded | AssetLoadState::Failed(_))
+        });
+    }
+}
+
+impl AssetLoadProgress for PreloadedAssets
+{
+    fn pending_assets(&self) -> usize
+    {
+        self.pending.len()
+    }
+
+    fn total_assets(&self) -> usize
+    {
+        self.images.len() + self.fonts.len() + self.audio.len()
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) struct CobIndexPlugin;
+
+impl Plugin for CobIndexPlugin
+{
+    fn build(&self, app: &mut App)
+    {
+        app.init_resource::<PreloadedAssets>()
+            .register_asset_tracker::<PreloadedAssets>()
+            .add_systems(PreUpdate, check_preloaded_assets.in_set(LoadProgressSet::Prepare));
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    /// Gets the total number of assets tracked by the preloaded assets and the asset maps.
+    fn total_assets(world: &World) -> usize
+    {
+        world.resource::<PreloadedAssets>().total_assets()
+            + world.resource::<ImageMap>().total_assets()
+            + world.resource::<FontMap>().total_assets()
+            + world.resource::<AudioMap>().total_assets()
+    }
+
+    #[test]
+    fn preloaded_total_stays_constant()
+    {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_asset::<Font>()
+            .init_asset::<AudioSource>()
+            .init_resource::<ImageMap>()
+            .init_resource::<FontMap>()
+            .init_resource::<AudioMap>()
+            .init_resource::<PreloadedAssets>()
+            .add_systems(PreUpdate, check_preloaded_assets);
+
+        let index = CobIndex {
+            files: vec![String::from("main.cob")],
+            images: vec![String::from("a.png"), String::from("b.png")],
+            fonts: vec![],
+            audio: vec![String::from("c.ogg")],
+        };
+        app.world_mut()
+            .resource_scope(|world, mut preloaded: Mut<PreloadedAssets>| {
+                preloaded.load(&index, world.resource::<AssetServer>());
+            });
+        app.update();
+        assert_eq!(total_assets(app.world()), 3);
+
+        // Assets added to the asset maps by commands are released by the preloader.
+        app.world_mut()
+            .resource_scope(|world, mut images: Mut<ImageMap>| {
+                images.insert("a.png", world.resource::<AssetServer>());
+            });
+        app.update();
+        assert_eq!(app.world().resource::<PreloadedAssets>().total_assets(), 2);
+        assert_eq!(total_assets(app.world()), 3);
+
+        app.world_mut()
+            .resource_scope(|world, mut images: Mut<ImageMap>| {
+                images.insert("b.png", world.resource::<AssetServer>());
+            });
+        app.world_mut()
+            .resource_scope(|world, mut audio: Mut<AudioMap>| {
+                audio.insert("c.ogg", world.resource::<AssetServer>());
+            });
+        app.update();
+        assert_eq!(app.world().resource::<PreloadedAssets>().total_assets(), 0);
+        assert_eq!(total_assets(app.world()), 3);
+
+        // Unindexed assets still count.
+        app.world_mut()
+            .resource_scope(|world, mut images: Mut<ImageMap>| {
+                images.insert("d.png", world.resource::<AssetServer>());
+            });
+        app.update();
+        assert_eq!(total_assets(app.world()), 4);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
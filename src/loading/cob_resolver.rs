@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use crate::builtin::BUILTIN_COB_FILES;
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Debug)]
+struct ResolverFile
+{
+    data: Cob,
+    manifest: Vec<(CobFile, ManifestKey)>,
+    imports: HashMap<ManifestKey, CobImportAlias>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Resolves the constants and macros that are visible in COB files without running an app.
+///
+/// Files are resolved the same way they are when an app loads them:
+/// - Manifest keys are collected from the `#manifest` sections of all files, including the builtin files enabled
+///   by this crate's features (e.g. `builtin.colors.basic`).
+/// - Imported files are resolved before the files that import them, and their defs are accessed through import
+///   aliases.
+/// - `#theme` sections override constants in the same file.
+/// - `#if` blocks are evaluated against the [`CobFlags`] passed to [`Self::new`].
+///
+/// Useful for tools like the COB language server, which need to know what a name refers to.
+///
+/// ```ignore
+/// let mut resolver = CobResolver::new(CobFlags::default());
+/// resolver.insert(cob);
+/// let constants = resolver.resolve(&file)?;
+/// let (def_file, def_name) = constants.locate(CobDefKind::Constant, "colors::primary").unwrap();
+/// ```
+#[derive(Debug)]
+pub struct CobResolver
+{
+    flags: CobFlags,
+    files: HashMap<CobFile, ResolverFile>,
+    /// Cached results of [`Self::resolve`]. Cleared when files or flags change.
+    resolved: HashMap<CobFile, ConstantsBuffer>,
+}
+
+impl CobResolver
+{
+    /// Makes a new resolver that contains the builtin files.
+    pub fn new(flags: CobFlags) -> Self
+    {
+        let mut resolver = Self {
+            flags,
+            files: HashMap::default(),
+            resolved: HashMap::default(),
+        };
+        for builtin in BUILTIN_COB_FILES.iter() {
+            let file = builtin.asset_path();
+            match Cob::parse(Span::new_extra(builtin.content, CobLocationMetadata { file: &file })) {
+                Ok(data) => resolver.insert(data),
+                Err(_) => tracing::error!("failed parsing builtin COB file {:?}; this is a bug", file),
+            }
+        }
+        resolver
+    }
+
+    /// Gets the flags that `#if` blocks are evaluated against.
+    pub fn flags(&self) -> &CobFlags
+    {
+        &self.flags
+    }
+
+    /// Sets the flags that `#if` blocks are evaluated against.
+    pub fn set_flags(&mut self, flags: CobFlags)
+    {
+        self.flags = flags;
+        self.resolved.clear();
+    }
+
+    /// Adds a file, replacing the previous version of the file.
+    pub fn insert(&mut self, data: Cob)
+    {
+        let (manifest, imports) = extract_manifest_and_imports(&data);
+        self.files
+            .insert(data.file.clone(), ResolverFile { data, manifest, imports });
+        self.resolved.clear();
+    }
+
+    /// Removes a file.
+    pub fn remove(&mut self, file: &CobFile)
+    {
+        if self.files.remove(file).is_some() {
+            self.resolved.clear();
+        }
+    }
+
+    /// Gets the file a manifest key points to.
+    pub fn manifest_file(&self, key: &ManifestKey) -> Option<&CobFile>
+    {
+        self.files
+            .values()
+            .flat_map(|file| file.manifest.iter())
+            .find(|(_, other)| other == key)
+            .map(|(file, _)| file)
+    }
+
+    /// Resolves the constants and macros that are visible in a file.
+    ///
+    /// Fails if the file is unknown, if it imports an unknown manifest key, or if its imports form a cycle. Apps
+    /// discard files with import errors, so nothing is visible in them.
+    pub fn resolve(&mut self, file: &CobFile) -> Result<&ConstantsBuffer, String>
+    {
+        self.resolve_inner(file, &mut vec![])?;
+        self.resolved
+            .get(file)
+            .ok_or_else(|| format!("failed resolving {:?}", file.as_str()))
+    }
+
+    fn resolve_inner(&mut self, file: &CobFile, in_progress: &mut Vec<CobFile>) -> Result<(), String>
+    {
+        if self.resolved.contains_key(file) {
+            return Ok(());
+        }
+        if in_progress.contains(file) {
+            let cycle: Vec<&str> = in_progress.iter().map(|f| f.as_str()).collect();
+            return Err(format!("dependency cycle {:?} -> {:?}", cycle, file.as_str()));
+        }
+        let Some(resolver_file) = self.files.get(file) else {
+            return Err(format!("unknown file {:?}", file.as_str()));
+        };
+
+        // Resolve imports first.
+        let imports: Vec<(CobFile, CobImportAlias)> = resolver_file
+            .imports
+            .iter()
+            .map(|(key, alias)| {
+                let dependency = self.manifest_file(key).ok_or_else(|| {
+                    format!("{:?} imports unknown manifest key {:?}", file.as_str(), key.as_str())
+                })?;
+                Ok((dependency.clone(), alias.clone()))
+            })
+            .collect::<Result<_, String>>()?;
+
+        in_progress.push(file.clone());
+        for (dependency, _) in imports.iter() {
+            self.resolve_inner(dependency, in_progress)?;
+        }
+        in_progress.pop();
+
+        // Extraction modifies the data, so it's cloned to allow resolving again after flags change.
+        let mut data = self.files.get(file).unwrap().data.clone();
+        let constants = extract_cob_defs(
+            file,
+            &mut data,
+            &self.flags,
+            imports
+                .iter()
+                .filter_map(|(dependency, alias)| Some((alias, self.resolved.get(dependency)?))),
+            &mut SpecsMap::default(),
+        );
+        self.resolved.insert(file.clone(), constants);
+
+        Ok(())
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,626 @@
+use std::collections::HashSet;
+
+use bevy::reflect::serde::TypedReflectDeserializer;
+use bevy::reflect::{NamedField, TypeInfo, TypeRegistry, VariantInfo};
+use serde::de::DeserializeSeed;
+use smol_str::SmolStr;
+
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Kinds of definitions that can be referenced by path.
+#[derive(Copy, Clone)]
+enum DefKind
+{
+    Constant,
+    DataMacro,
+    LoadableMacro,
+    SceneMacro,
+}
+
+impl DefKind
+{
+    fn describe(self, name: &str) -> String
+    {
+        match self {
+            Self::Constant => format!("constant `${}`", name),
+            Self::DataMacro => format!("data macro `{}!`", name),
+            Self::LoadableMacro => format!("loadable macro `{}!`", name),
+            Self::SceneMacro => format!("scene macro `+{}`", name),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Names of definitions and imports in a file.
+#[derive(Default)]
+struct FileDefs
+{
+    constants: HashSet<SmolStr>,
+    data_macros: HashSet<SmolStr>,
+    loadable_macros: HashSet<SmolStr>,
+    scene_macros: HashSet<SmolStr>,
+    aliases: HashSet<SmolStr>,
+    /// Set if there is an `as _` import, which means unprefixed paths may come from other files.
+    has_unaliased_import: bool,
+}
+
+impl FileDefs
+{
+    fn new(cob: &Cob) -> Self
+    {
+        let mut defs = Self::default();
+        for section in cob.all_sections() {
+            match section {
+                CobSection::Import(import) => {
+                    for entry in import.entries.iter() {
+                        match &entry.alias {
+                            CobImportAlias::None => defs.has_unaliased_import = true,
+                            CobImportAlias::Alias(alias) => {
+                                defs.aliases.insert(alias.clone());
+                            }
+                        }
+                    }
+                }
+                CobSection::Defs(section) => {
+                    for entry in section.entries.iter() {
+                        match entry {
+                            CobDefEntry::Constant(def) => defs.constants.insert(def.name.name.clone()),
+                            CobDefEntry::DataMacro(def) => defs.data_macros.insert(def.name.clone()),
+                            CobDefEntry::LoadableMacro(def) => defs.loadable_macros.insert(def.name.clone()),
+                            CobDefEntry::SceneMacro(def) => defs.scene_macros.insert(def.name.clone()),
+                        };
+                    }
+                }
+                _ => (),
+            }
+        }
+        defs
+    }
+
+    fn get(&self, kind: DefKind) -> &HashSet<SmolStr>
+    {
+        match kind {
+            DefKind::Constant => &self.constants,
+            DefKind::DataMacro => &self.data_macros,
+            DefKind::LoadableMacro => &self.loadable_macros,
+            DefKind::SceneMacro => &self.scene_macros,
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Follows newtype structs and `Option`s until `matches` returns `true`.
+fn unwrap_type_info(
+    mut info: Option<&'static TypeInfo>,
+    matches: impl Fn(&'static TypeInfo) -> bool,
+) -> Option<&'static TypeInfo>
+{
+    while let Some(current) = info {
+        if matches(current) {
+            return Some(current);
+        }
+        info = match current {
+            TypeInfo::TupleStruct(tuple) if tuple.field_len() == 1 => tuple.field_at(0)?.type_info(),
+            TypeInfo::Enum(enum_info) if is_option(current) => match enum_info.variant("Some")? {
+                VariantInfo::Tuple(variant) => variant.field_at(0)?.type_info(),
+                _ => None,
+            },
+            _ => None,
+        };
+    }
+    None
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn is_option(info: &TypeInfo) -> bool
+{
+    let table = info.type_path_table();
+    table.module_path() == Some("core::option") && table.ident() == Some("Option")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+struct Validator<'a>
+{
+    file: &'a str,
+    type_registry: &'a TypeRegistry,
+    loadables: &'a LoadableRegistry,
+    defs: FileDefs,
+    diagnostics: Vec<CobDiagnostic>,
+    /// Set to `false` when walking over content that must be resolved before it can be deserialized.
+    resolved: bool,
+}
+
+impl<'a> Validator<'a>
+{
+    fn report(&mut self, path: &ScenePath, message: String, hint: Option<String>)
+    {
+        let mut diagnostic = CobDiagnostic::new_for_scene(self.file, path.clone(), message);
+        diagnostic.hint = hint;
+        self.diagnostics.push(diagnostic);
+    }
+
+    fn check_def_path(&mut self, path: &ScenePath, def_path: &str, kind: DefKind)
+    {
+        // Paths may resolve to definitions in files imported without an alias, which we can't see here.
+        if self.defs.has_unaliased_import {
+            return;
+        }
+
+        match def_path.split_once("::") {
+            Some((alias, _)) => {
+                if self.defs.aliases.contains(alias) {
+                    return;
+                }
+                let hint = find_similar_name(alias, self.defs.aliases.iter().map(|a| a.as_str()))
+                    .map(|similar| format!("did you mean `{}`?", similar));
+                let message = format!("unknown import alias `{}` in {}", alias, kind.describe(def_path));
+                self.report(path, message, hint);
+            }
+            None => {
+                let defs = self.defs.get(kind);
+                if defs.contains(def_path) {
+                    return;
+                }
+                let hint = find_similar_name(def_path, defs.iter().map(|d| d.as_str()))
+                    .map(|similar| format!("did you mean `{}`?", similar));
+                let message = format!("{} is not defined in this file", kind.describe(def_path));
+                self.report(path, message, hint);
+            }
+        }
+    }
+
+    fn check_value(&mut self, path: &ScenePath, value: &CobValue, info: Option<&'static TypeInfo>)
+    {
+        match value {
+            CobValue::Enum(value) => self.check_enum(path, value, info),
+            CobValue::Map(map) => self.check_struct_map(path, map, info),
+            CobValue::Array(array) => self.check_values(path, &array.entries),
+            CobValue::Tuple(tuple) => self.check_values(path, &tuple.entries),
+            CobValue::Constant(constant) => {
+                self.resolved = false;
+                self.check_def_path(path, constant.path.as_str(), DefKind::Constant);
+            }
+            CobValue::DataMacro(call) => {
+                self.resolved = false;
+                self.check_def_path(path, call.path.as_str(), DefKind::DataMacro);
+                self.check_group(path, &call.args.entries);
+            }
+            CobValue::MacroParam(_) | CobValue::Themed(_) => self.resolved = false,
+            CobValue::Expression(expression) => {
+                self.resolved = false;
+                self.check_value(path, &expression.first, None);
+                for term in expression.terms.iter() {
+                    self.check_value(path, &term.value, None);
+                }
+                if let Err(err) = expression.evaluate() {
+                    self.report(path, err, None);
+                }
+            }
+            CobValue::Function(call) => {
+                self.resolved = false;
+                self.check_values(path, &call.args.entries);
+                if let Err(err) = call.evaluate() {
+                    self.report(path, err, None);
+                }
+            }
+            CobValue::Localized(localized) => {
+                for arg in localized.args.iter() {
+                    self.check_value(path, &arg.value, None);
+                }
+            }
+            CobValue::Builtin(_)
+            | CobValue::Number(_)
+            | CobValue::Bool(_)
+            | CobValue::None(_)
+            | CobValue::String(_) => (),
+        }
+    }
+
+    fn check_values(&mut self, path: &ScenePath, values: &[CobValue])
+    {
+        for value in values.iter() {
+            self.check_value(path, value, None);
+        }
+    }
+
+    fn check_group(&mut self, path: &ScenePath, entries: &[CobValueGroupEntry])
+    {
+        for entry in entries.iter() {
+            match entry {
+                CobValueGroupEntry::KeyValue(kv) => {
+                    if let CobMapKey::Value(key) = &kv.key {
+                        self.check_value(path, key, None);
+                    }
+                    self.check_value(path, &kv.value, None);
+                }
+                CobValueGroupEntry::Value(value) => self.check_value(path, value, None),
+            }
+        }
+    }
+
+    /// Checks map entries against struct fields if the struct type is known.
+    fn check_map(&mut self, path: &ScenePath, map: &CobMap, owner: &str, fields: Option<&[&'static NamedField]>)
+    {
+        for entry in map.entries.iter() {
+            match entry {
+                CobMapEntry::KeyValue(kv) => {
+                    let field_info = match (&kv.key, fields) {
+                        (CobMapKey::FieldName { name, .. }, Some(fields)) => {
+                            match fields.iter().find(|field| field.name() == name.as_str()) {
+                                Some(field) => field.type_info(),
+                                None => {
+                                    let hint = find_similar_name(name, fields.iter().map(|f| f.name()))
+                                        .map(|similar| format!("did you mean `{}`?", similar));
+                                    let message = format!("`{}` has no field named `{}`", owner, name);
+                                    self.report(path, message, hint);
+                                    None
+                                }
+                            }
+                        }
+                        (CobMapKey::Value(key), _) => {
+                            self.check_value(path, key, None);
+                            None
+                        }
+                        _ => None,
+                    };
+                    self.check_value(path, &kv.value, field_info);
+                }
+                CobMapEntry::Constant(constant) => {
+                    self.resolved = false;
+                    self.check_def_path(path, constant.path.as_str(), DefKind::Constant);
+                }
+                CobMapEntry::DataMacro(call) => {
+                    self.resolved = false;
+                    self.check_def_path(path, call.path.as_str(), DefKind::DataMacro);
+                    self.check_group(path, &call.args.entries);
+                }
+                CobMapEntry::MacroParam(_) => self.resolved = false,
+            }
+        }
+    }
+
+    /// Checks a map that should deserialize to a struct, possibly wrapped in newtypes.
+    fn check_struct_map(&mut self, path: &ScenePath, map: &CobMap, info: Option<&'static TypeInfo>)
+    {
+        match unwrap_type_info(info, |i| matches!(i, TypeInfo::Struct(_))) {
+            Some(TypeInfo::Struct(struct_info)) => {
+                let fields: Vec<_> = struct_info.iter().collect();
+                self.check_map(path, map, struct_info.type_path_table().short_path(), Some(&fields));
+            }
+            _ => self.check_map(path, map, "", None),
+        }
+    }
+
+    /// Checks that an enum variant exists if the enum type is known.
+    fn check_enum(&mut self, path: &ScenePath, value: &CobEnum, info: Option<&'static TypeInfo>)
+    {
+        let variant_name = value.id.0.as_str();
+        let info = unwrap_type_info(info, |i| match i {
+            TypeInfo::Enum(enum_info) => enum_info.contains_variant(variant_name) || !is_option(i),
+            _ => false,
+        });
+        let enum_name = info
+            .map(|info| info.type_path_table().short_path())
+            .unwrap_or_default();
+        let variant_info = match info {
+            Some(TypeInfo::Enum(enum_info)) => match enum_info.variant(variant_name) {
+                Some(variant_info) => Some(variant_info),
+                None => {
+                    let hint = find_similar_name(variant_name, enum_info.variant_names().iter().copied())
+                        .map(|similar| format!("did you mean `{}`?", similar));
+                    let message = format!("`{}` has no variant named `{}`", enum_name, variant_name);
+                    self.report(path, message, hint);
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        match (&value.variant, variant_info) {
+            (CobEnumVariant::Unit, _) => (),
+            (CobEnumVariant::Tuple(tuple), Some(VariantInfo::Tuple(variant_info))) => {
+                for (idx, entry) in tuple.entries.iter().enumerate() {
+                    let field_info = variant_info.field_at(idx).and_then(|f| f.type_info());
+                    self.check_value(path, entry, field_info);
+                }
+            }
+            (CobEnumVariant::Tuple(tuple), _) => self.check_values(path, &tuple.entries),
+            (CobEnumVariant::Array(array), _) => self.check_values(path, &array.entries),
+            (CobEnumVariant::Map(map), Some(VariantInfo::Struct(variant_info))) => {
+                let owner = format!("{}::{}", enum_name, variant_name);
+                let fields: Vec<_> = variant_info.iter().collect();
+                self.check_map(path, map, &owner, Some(&fields));
+            }
+            (CobEnumVariant::Map(map), _) => self.check_map(path, map, "", None),
+        }
+    }
+
+    fn check_loadable(&mut self, path: &ScenePath, loadable: &CobLoadable, in_def: bool)
+    {
+        let name = loadable.id.to_canonical(None);
+        let registration = match self.loadables.get_type_id(&name) {
+            Some(type_id) => self.type_registry.get(type_id),
+            None => {
+                // Extraction skips unregistered loadables without reporting them, so this is the only report.
+                let hint = self
+                    .loadables
+                    .find_similar(&name)
+                    .map(|similar| format!("did you mean `{}`?", similar));
+                let message = format!("no loadable named `{}` was registered in the app", name);
+                self.report(path, message, hint);
+                None
+            }
+        };
+        let info = registration.map(|r| r.type_info());
+
+        self.resolved = true;
+        let num_diagnostics = self.diagnostics.len();
+        match &loadable.variant {
+            CobLoadableVariant::Unit => (),
+            CobLoadableVariant::Tuple(tuple) => match info {
+                Some(TypeInfo::TupleStruct(tuple_info)) => {
+                    for (idx, entry) in tuple.entries.iter().enumerate() {
+                        let field_info = tuple_info.field_at(idx).and_then(|f| f.type_info());
+                        self.check_value(path, entry, field_info);
+                    }
+                }
+                _ => self.check_values(path, &tuple.entries),
+            },
+            CobLoadableVariant::Array(array) => self.check_values(path, &array.entries),
+            CobLoadableVariant::Map(map) => self.check_struct_map(path, map, info),
+            CobLoadableVariant::Enum(value) => self.check_enum(path, value, info),
+        }
+
+        // Try to deserialize fully-resolved loadables to catch mismatched value types.
+        let Some(registration) = registration else { return };
+        if in_def || !self.resolved || self.diagnostics.len() != num_diagnostics {
+            return;
+        }
+        let deserializer = TypedReflectDeserializer::new(registration, self.type_registry);
+        if let Err(err) = deserializer.deserialize(loadable) {
+            self.report(path, format!("failed deserializing loadable `{}`: {}", name, err), None);
+        }
+    }
+
+    fn check_loadable_macro_call(&mut self, path: &ScenePath, call: &CobLoadableMacroCall)
+    {
+        self.check_def_path(path, call.path.as_str(), DefKind::LoadableMacro);
+        self.check_group(path, &call.args.entries);
+    }
+
+    fn check_layer<'b>(
+        &mut self,
+        parent: &ScenePath,
+        layer: &'b CobSceneLayer,
+        seen_names: &mut Vec<&'b str>,
+        in_def: bool,
+    )
+    {
+        // Anonymous nodes are renamed during extraction so they can't conflict.
+        let name = layer.name.as_str();
+        if name.is_empty() {
+            self.check_layer_entries(parent, &layer.entries, in_def);
+            return;
+        }
+
+        let path = parent.extend(name);
+        if seen_names.contains(&name) {
+            let message = format!("duplicate scene node `{}`; sibling nodes must have unique names", name);
+            self.report(&path, message, None);
+        }
+        seen_names.push(name);
+        self.check_layer_entries(&path, &layer.entries, in_def);
+    }
+
+    fn check_layer_entries(&mut self, path: &ScenePath, entries: &[CobSceneLayerEntry], in_def: bool)
+    {
+        let mut seen_names = vec![];
+
+        for entry in entries.iter() {
+            match entry {
+                CobSceneLayerEntry::Loadable(loadable) => self.check_loadable(path, loadable, in_def),
+                CobSceneLayerEntry::LoadableMacroCall(call) => self.check_loadable_macro_call(path, call),
+                CobSceneLayerEntry::SceneMacroCall(call) => {
+                    self.check_def_path(path, call.path.as_str(), DefKind::SceneMacro);
+                    self.check_group(path, &call.args.entries);
+                    for param in call.param_defs.iter() {
+                        self.check_layer_entries(path, &param.entries, in_def);
+                    }
+                }
+                CobSceneLayerEntry::Layer(layer) => self.check_layer(path, layer, &mut seen_names, in_def),
+                CobSceneLayerEntry::SceneMacroParam(_) | CobSceneLayerEntry::LoadableRemoval(_) => (),
+            }
+        }
+    }
+
+    /// Reports loadable removals in scenes without a base scene, where there is nothing for them to remove.
+    ///
+    /// Removals after a macro call or a loadable with the same name in the same node are allowed, and nodes that
+    /// may receive children from a scene macro call are skipped.
+    fn check_removals_without_base(&mut self, path: &ScenePath, entries: &[CobSceneLayerEntry])
+    {
+        let mut written = vec![];
+        let mut after_macro_call = false;
+
+        for entry in entries.iter() {
+            match entry {
+                CobSceneLayerEntry::Loadable(loadable) => written.push(loadable.id.to_canonical(None)),
+                CobSceneLayerEntry::LoadableMacroCall(_) | CobSceneLayerEntry::SceneMacroCall(_) => {
+                    after_macro_call = true;
+                }
+                CobSceneLayerEntry::LoadableRemoval(removal) => {
+                    let name = removal.id.to_canonical(None);
+                    if after_macro_call || written.contains(&name) {
+                        continue;
+                    }
+                    self.report(
+                        path,
+                        format!("removing `{}` has no effect because the scene has no base scene", name),
+                        Some(String::from(
+                            "add a base scene with `\"scene\" : \"base\"`, or remove the `-`",
+                        )),
+                    );
+                }
+                CobSceneLayerEntry::Layer(_) | CobSceneLayerEntry::SceneMacroParam(_) => (),
+            }
+        }
+
+        if entries
+            .iter()
+            .any(|entry| matches!(entry, CobSceneLayerEntry::SceneMacroCall(_)))
+        {
+            return;
+        }
+        for entry in entries.iter() {
+            let CobSceneLayerEntry::Layer(layer) = entry else { continue };
+            let child_path = match layer.name.is_empty() {
+                true => path.clone(),
+                false => path.extend(layer.name.as_str()),
+            };
+            self.check_removals_without_base(&child_path, &layer.entries);
+        }
+    }
+
+    fn check_defs(&mut self, defs: &CobDefs)
+    {
+        let path = ScenePath::new("#defs");
+        for entry in defs.entries.iter() {
+            match entry {
+                CobDefEntry::Constant(def) => self.check_constant_value(&path, &def.value),
+                CobDefEntry::DataMacro(def) => {
+                    self.check_param_defs(&path, &def.params);
+                    self.check_constant_value(&path, &def.value);
+                }
+                CobDefEntry::LoadableMacro(def) => {
+                    self.check_param_defs(&path, &def.params);
+                    match &def.value {
+                        CobLoadableMacroValue::LoadableMacroCall(call) => {
+                            self.check_loadable_macro_call(&path, call)
+                        }
+                        CobLoadableMacroValue::Loadable(loadable) => self.check_loadable(&path, loadable, true),
+                        CobLoadableMacroValue::Group(group) => {
+                            for entry in group.entries.iter() {
+                                match entry {
+                                    CobLoadableGroupEntry::LoadableMacroCall(call) => {
+                                        self.check_loadable_macro_call(&path, call)
+                                    }
+                                    CobLoadableGroupEntry::Loadable(loadable) => {
+                                        self.check_loadable(&path, loadable, true)
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                CobDefEntry::SceneMacro(def) => {
+                    self.check_param_defs(&path, &def.params);
+                    self.check_layer_entries(&path.extend(def.name.as_str()), &def.entries, true);
+                }
+            }
+        }
+    }
+
+    fn check_theme(&mut self, theme: &CobTheme)
+    {
+        let path = ScenePath::new("#theme");
+        for def in theme.entries.iter() {
+            // Themes can only override constants defined in the same file.
+            if !self.defs.constants.contains(&def.name.name) {
+                let hint = find_similar_name(def.name.as_str(), self.defs.constants.iter().map(|c| c.as_str()))
+                    .map(|similar| format!("did you mean `${}`?", similar));
+                let message = format!(
+                    "theme {:?} overrides constant `${}` that isn't defined in this file",
+                    theme.name.as_str(),
+                    def.name.as_str()
+                );
+                self.report(&path, message, hint);
+            }
+            self.check_constant_value(&path, &def.value);
+        }
+    }
+
+    fn check_constant_value(&mut self, path: &ScenePath, value: &CobConstantValue)
+    {
+        match value {
+            CobConstantValue::Value(value) => self.check_value(path, value, None),
+            CobConstantValue::ValueGroup(group) => self.check_group(path, &group.entries),
+        }
+    }
+
+    fn check_param_defs(&mut self, path: &ScenePath, params: &CobMacroParamDefs)
+    {
+        for param in params.entries.iter() {
+            if let CobMacroParamDef::Assigned { value, .. } = param {
+                self.check_value(path, value, None);
+            }
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Checks a parsed COB file for semantic problems that would otherwise only be found during extraction.
+///
+/// Reports:
+/// - Loadables that aren't registered in the app.
+/// - Struct fields and enum variants that don't exist on their type, and loadables that fail to deserialize.
+/// - Constants, macros, and import aliases that can't be resolved. Paths are not checked if the file has an `as _`
+///   import, since they may refer to definitions in the imported file.
+/// - Duplicate sibling scene node names.
+/// - `#theme` overrides of constants that aren't defined in the file.
+///
+/// Macro calls are not expanded, so problems inside expanded macro content are only reported at the macro
+/// definition.
+///
+/// This runs automatically when COB files are loaded, and the diagnostics are added to [`CobDiagnostics`].
+pub fn validate(cob: &Cob, type_registry: &TypeRegistry, loadables: &LoadableRegistry) -> Vec<CobDiagnostic>
+{
+    let mut validator = Validator {
+        file: cob.file.as_str(),
+        type_registry,
+        loadables,
+        defs: FileDefs::new(cob),
+        diagnostics: vec![],
+        resolved: true,
+    };
+
+    // All branches of `#if` blocks are checked, since flags may change at runtime.
+    for section in cob.all_sections() {
+        match section {
+            CobSection::Manifest(_) | CobSection::Import(_) | CobSection::Conditional(_) => (),
+            CobSection::Defs(defs) => validator.check_defs(defs),
+            CobSection::Theme(theme) => validator.check_theme(theme),
+            CobSection::Commands(commands) => {
+                let path = ScenePath::new("#commands");
+                for entry in commands.entries.iter() {
+                    match entry {
+                        CobCommandEntry::Loadable(loadable) => validator.check_loadable(&path, loadable, false),
+                        CobCommandEntry::LoadableMacroCall(call) => {
+                            validator.check_loadable_macro_call(&path, call)
+                        }
+                    }
+                }
+            }
+            CobSection::Scenes(scenes) => {
+                let mut seen_names = vec![];
+                for layer in scenes.scenes.iter() {
+                    validator.check_layer(&ScenePath::empty(), layer, &mut seen_names, false);
+                    if layer.base.is_none() {
+                        let path = ScenePath::empty().extend(layer.name.as_str());
+                        validator.check_removals_without_base(&path, &layer.entries);
+                    }
+                }
+            }
+        }
+    }
+
+    validator.diagnostics
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,667 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use smol_str::SmolStr;
+
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Identifies compiled COB data.
+const MAGIC: &[u8; 4] = b"COBC";
+/// Incremented when the binary format changes.
+const VERSION: u32 = 1;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Writes the binary format of [`CompiledCob`].
+///
+/// Type paths are written to a table at the start of the data so each path is only stored once.
+#[derive(Default)]
+pub(super) struct CobBinaryWriter
+{
+    body: Vec<u8>,
+    type_paths: Vec<SmolStr>,
+    type_path_indices: HashMap<SmolStr, u32>,
+}
+
+impl CobBinaryWriter
+{
+    pub(super) fn finish(self) -> Vec<u8>
+    {
+        let mut header = CobBinaryWriter::default();
+        header.body.extend_from_slice(MAGIC);
+        header.write_u32(VERSION);
+        header.write_len(self.type_paths.len());
+        for type_path in self.type_paths.iter() {
+            header.write_str(type_path);
+        }
+
+        let mut bytes = header.body;
+        bytes.extend_from_slice(&self.body);
+        bytes
+    }
+
+    fn write_u8(&mut self, value: u8)
+    {
+        self.body.push(value);
+    }
+
+    fn write_u32(&mut self, value: u32)
+    {
+        self.body.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn write_f32(&mut self, value: f32)
+    {
+        self.body.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(super) fn write_len(&mut self, len: usize)
+    {
+        self.write_u32(len as u32);
+    }
+
+    pub(super) fn write_str(&mut self, value: &str)
+    {
+        self.write_len(value.len());
+        self.body.extend_from_slice(value.as_bytes());
+    }
+
+    fn write_type_path(&mut self, type_path: &SmolStr)
+    {
+        let index = match self.type_path_indices.get(type_path) {
+            Some(index) => *index,
+            None => {
+                let index = self.type_paths.len() as u32;
+                self.type_paths.push(type_path.clone());
+                self.type_path_indices.insert(type_path.clone(), index);
+                index
+            }
+        };
+        self.write_u32(index);
+    }
+
+    pub(super) fn write_file(&mut self, file: &CompiledCobFile) -> Result<(), String>
+    {
+        self.write_str(file.file.as_str());
+        self.write_len(file.manifest.len());
+        for (manifest_file, key) in file.manifest.iter() {
+            self.write_str(manifest_file.as_str());
+            self.write_str(key.as_str());
+        }
+        self.write_len(file.commands.len());
+        for command in file.commands.iter() {
+            self.write_loadable(command)?;
+        }
+        self.write_len(file.scenes.len());
+        for scene in file.scenes.iter() {
+            self.write_scene_node(scene)?;
+        }
+        Ok(())
+    }
+
+    fn write_scene_node(&mut self, node: &CompiledSceneNode) -> Result<(), String>
+    {
+        self.write_str(&node.name);
+        self.write_len(node.loadables.len());
+        for loadable in node.loadables.iter() {
+            self.write_loadable(loadable)?;
+        }
+        match node.text_size {
+            Some(index) => {
+                self.write_u8(1);
+                self.write_len(index);
+            }
+            None => self.write_u8(0),
+        }
+        self.write_len(node.children.len());
+        for child in node.children.iter() {
+            self.write_scene_node(child)?;
+        }
+        Ok(())
+    }
+
+    fn write_loadable(&mut self, loadable: &CompiledLoadable) -> Result<(), String>
+    {
+        self.write_type_path(&loadable.type_path);
+        self.write_str(&loadable.loadable.id.to_canonical(None));
+        match &loadable.loadable.variant {
+            CobLoadableVariant::Unit => self.write_u8(0),
+            CobLoadableVariant::Tuple(tuple) => {
+                self.write_u8(1);
+                self.write_values(&tuple.entries)?;
+            }
+            CobLoadableVariant::Array(array) => {
+                self.write_u8(2);
+                self.write_values(&array.entries)?;
+            }
+            CobLoadableVariant::Map(map) => {
+                self.write_u8(3);
+                self.write_map(map)?;
+            }
+            CobLoadableVariant::Enum(value) => {
+                self.write_u8(4);
+                self.write_enum(value)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_values(&mut self, values: &[CobValue]) -> Result<(), String>
+    {
+        self.write_len(values.len());
+        for value in values.iter() {
+            self.write_value(value)?;
+        }
+        Ok(())
+    }
+
+    fn write_map(&mut self, map: &CobMap) -> Result<(), String>
+    {
+        self.write_len(map.entries.len());
+        for entry in map.entries.iter() {
+            let CobMapEntry::KeyValue(entry) = entry else {
+                return Err(format!("map entry {:?} was not resolved", entry));
+            };
+            match &entry.key {
+                CobMapKey::Value(key) => {
+                    self.write_u8(0);
+                    self.write_value(key)?;
+                }
+                CobMapKey::FieldName { name, .. } => {
+                    self.write_u8(1);
+                    self.write_str(name);
+                }
+            }
+            self.write_value(&entry.value)?;
+        }
+        Ok(())
+    }
+
+    fn write_enum(&mut self, value: &CobEnum) -> Result<(), String>
+    {
+        self.write_str(&value.id.0);
+        match &value.variant {
+            CobEnumVariant::Unit => self.write_u8(0),
+            CobEnumVariant::Tuple(tuple) => {
+                self.write_u8(1);
+                self.write_values(&tuple.entries)?;
+            }
+            CobEnumVariant::Array(array) => {
+                self.write_u8(2);
+                self.write_values(&array.entries)?;
+            }
+            CobEnumVariant::Map(map) => {
+                self.write_u8(3);
+                self.write_map(map)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_number(&mut self, number: &CobNumberValue)
+    {
+        match *number {
+            CobNumberValue::Uint(value) => {
+                self.write_u8(0);
+                self.body.extend_from_slice(&value.to_le_bytes());
+            }
+            CobNumberValue::Int(value) => {
+                self.write_u8(1);
+                self.body.extend_from_slice(&value.to_le_bytes());
+            }
+            CobNumberValue::Float64(value) => {
+                self.write_u8(2);
+                self.body.extend_from_slice(&value.to_le_bytes());
+            }
+            CobNumberValue::Float32(value) => {
+                self.write_u8(3);
+                self.write_f32(value);
+            }
+        }
+    }
+
+    fn write_val(&mut self, val: &Val)
+    {
+        let (tag, value) = match *val {
+            Val::Auto => (0, 0.),
+            Val::Px(value) => (1, value),
+            Val::Percent(value) => (2, value),
+            Val::Vw(value) => (3, value),
+            Val::Vh(value) => (4, value),
+            Val::VMin(value) => (5, value),
+            Val::VMax(value) => (6, value),
+        };
+        self.write_u8(tag);
+        self.write_f32(value);
+    }
+
+    fn write_builtin(&mut self, builtin: &CobBuiltin)
+    {
+        match builtin {
+            CobBuiltin::Color(color) => {
+                self.write_u8(0);
+                let Srgba { red, green, blue, alpha } = color.color;
+                for channel in [red, green, blue, alpha] {
+                    self.write_f32(channel);
+                }
+            }
+            CobBuiltin::Val { number, val, .. } => {
+                self.write_u8(1);
+                match number {
+                    Some(number) => {
+                        self.write_u8(1);
+                        self.write_number(number);
+                    }
+                    None => self.write_u8(0),
+                }
+                self.write_val(val);
+            }
+            CobBuiltin::FontRelative { number, unit, .. } => {
+                self.write_u8(2);
+                self.write_number(number);
+                self.write_u8(match unit {
+                    CobFontUnit::Rem => 0,
+                    CobFontUnit::Em => 1,
+                });
+            }
+        }
+    }
+
+    fn write_value(&mut self, value: &CobValue) -> Result<(), String>
+    {
+        match value {
+            CobValue::Enum(value) => {
+                self.write_u8(0);
+                self.write_enum(value)?;
+            }
+            CobValue::Builtin(value) => {
+                self.write_u8(1);
+                self.write_builtin(value);
+            }
+            CobValue::Array(value) => {
+                self.write_u8(2);
+                self.write_values(&value.entries)?;
+            }
+            CobValue::Tuple(value) => {
+                self.write_u8(3);
+                self.write_values(&value.entries)?;
+            }
+            CobValue::Map(value) => {
+                self.write_u8(4);
+                self.write_map(value)?;
+            }
+            CobValue::Number(value) => {
+                self.write_u8(5);
+                self.write_number(&value.number);
+            }
+            CobValue::Bool(value) => {
+                self.write_u8(6);
+                self.write_u8(value.value as u8);
+            }
+            CobValue::None(_) => {
+                self.write_u8(7);
+            }
+            CobValue::String(value) => {
+                self.write_u8(8);
+                self.write_str(value.as_str());
+            }
+            CobValue::Expression(value) => {
+                self.write_u8(9);
+                self.write_value(&value.first)?;
+                self.write_len(value.terms.len());
+                for term in value.terms.iter() {
+                    self.write_u8(match term.op {
+                        CobOperator::Add => 0,
+                        CobOperator::Subtract => 1,
+                        CobOperator::Multiply => 2,
+                        CobOperator::Divide => 3,
+                    });
+                    self.write_value(&term.value)?;
+                }
+            }
+            CobValue::Function(value) => {
+                self.write_u8(10);
+                self.write_str(&value.name);
+                self.write_values(&value.args.entries)?;
+            }
+            CobValue::Themed(value) => {
+                self.write_u8(11);
+                self.write_str(&value.name.name);
+                self.write_value(&value.default)?;
+                self.write_len(value.themes.len());
+                for (theme, value) in value.themes.iter() {
+                    self.write_str(theme);
+                    self.write_value(value)?;
+                }
+            }
+            CobValue::Localized(value) => {
+                self.write_u8(12);
+                self.write_str(value.id.as_str());
+                self.write_len(value.args.len());
+                for arg in value.args.iter() {
+                    let CobMapKey::FieldName { name, .. } = &arg.key else {
+                        return Err(format!("loc() argument {:?} is not a field name", arg.key));
+                    };
+                    self.write_str(name);
+                    self.write_value(&arg.value)?;
+                }
+            }
+            CobValue::Constant(_) | CobValue::DataMacro(_) | CobValue::MacroParam(_) => {
+                return Err(format!("value {:?} was not resolved", value));
+            }
+        }
+        Ok(())
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Reads the binary format of [`CompiledCob`].
+pub(super) struct CobBinaryReader<'a>
+{
+    bytes: &'a [u8],
+    type_paths: Vec<SmolStr>,
+}
+
+impl<'a> CobBinaryReader<'a>
+{
+    /// Reads the header and type path table.
+    pub(super) fn new(bytes: &'a [u8]) -> Result<Self, String>
+    {
+        let Some(bytes) = bytes.strip_prefix(MAGIC) else {
+            return Err("data is not compiled COB".into());
+        };
+        let mut reader = Self { bytes, type_paths: vec![] };
+        let version = reader.read_u32()?;
+        if version != VERSION {
+            return Err(
+                format!("unsupported compiled COB version {} (expected {}); the data must be compiled \
+                again", version, VERSION),
+            );
+        }
+        for _ in 0..reader.read_len()? {
+            let type_path = reader.read_str()?;
+            reader.type_paths.push(type_path);
+        }
+        Ok(reader)
+    }
+
+    /// Checks that all data was read.
+    pub(super) fn finish(self) -> Result<(), String>
+    {
+        if !self.bytes.is_empty() {
+            return Err(format!("found {} unexpected trailing bytes", self.bytes.len()));
+        }
+        Ok(())
+    }
+
+    fn read_bytes<const N: usize>(&mut self) -> Result<[u8; N], String>
+    {
+        if self.bytes.len() < N {
+            return Err("unexpected end of data".into());
+        }
+        let (bytes, remaining) = self.bytes.split_at(N);
+        self.bytes = remaining;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn read_u8(&mut self) -> Result<u8, String>
+    {
+        Ok(self.read_bytes::<1>()?[0])
+    }
+
+    fn read_u32(&mut self) -> Result<u32, String>
+    {
+        Ok(u32::from_le_bytes(self.read_bytes()?))
+    }
+
+    fn read_f32(&mut self) -> Result<f32, String>
+    {
+        Ok(f32::from_le_bytes(self.read_bytes()?))
+    }
+
+    pub(super) fn read_len(&mut self) -> Result<usize, String>
+    {
+        Ok(self.read_u32()? as usize)
+    }
+
+    fn read_str(&mut self) -> Result<SmolStr, String>
+    {
+        let len = self.read_len()?;
+        if self.bytes.len() < len {
+            return Err("unexpected end of data".into());
+        }
+        let (bytes, remaining) = self.bytes.split_at(len);
+        self.bytes = remaining;
+        let value = std::str::from_utf8(bytes).map_err(|err| format!("invalid string: {}", err))?;
+        Ok(SmolStr::from(value))
+    }
+
+    fn read_vec<T>(&mut self, mut read: impl FnMut(&mut Self) -> Result<T, String>) -> Result<Vec<T>, String>
+    {
+        let len = self.read_len()?;
+        let mut values = Vec::with_capacity(len.min(self.bytes.len()));
+        for _ in 0..len {
+            values.push(read(self)?);
+        }
+        Ok(values)
+    }
+
+    pub(super) fn read_file_path(&mut self) -> Result<CobFile, String>
+    {
+        let file = self.read_str()?;
+        CobFile::try_new(&file).ok_or_else(|| format!("invalid file {:?}", file.as_str()))
+    }
+
+    pub(super) fn read_file(&mut self) -> Result<CompiledCobFile, String>
+    {
+        let file = self.read_file_path()?;
+        let manifest = self.read_vec(|reader| {
+            let manifest_file = reader.read_file_path()?;
+            let key = reader.read_str()?;
+            Ok((manifest_file, ManifestKey::new(key)))
+        })?;
+        let commands = self.read_vec(Self::read_loadable)?;
+        let scenes = self.read_vec(Self::read_scene_node)?;
+        Ok(CompiledCobFile { file, manifest, commands, scenes })
+    }
+
+    fn read_scene_node(&mut self) -> Result<CompiledSceneNode, String>
+    {
+        let name = self.read_str()?;
+        let loadables = self.read_vec(Self::read_loadable)?;
+        let text_size = match self.read_u8()? {
+            0 => None,
+            1 => Some(self.read_len()?),
+            tag => return Err(format!("invalid text size tag {}", tag)),
+        };
+        let children = self.read_vec(Self::read_scene_node)?;
+        Ok(CompiledSceneNode { name, loadables, text_size, children })
+    }
+
+    fn read_loadable(&mut self) -> Result<CompiledLoadable, String>
+    {
+        let index = self.read_len()?;
+        let Some(type_path) = self.type_paths.get(index).cloned() else {
+            return Err(format!("invalid type path index {}", index));
+        };
+        let name = self.read_str()?;
+        let variant = match self.read_u8()? {
+            0 => CobLoadableVariant::Unit,
+            1 => CobLoadableVariant::Tuple(CobTuple::from(self.read_values()?)),
+            2 => CobLoadableVariant::Array(CobArray::from(self.read_values()?)),
+            3 => CobLoadableVariant::Map(self.read_map()?),
+            4 => CobLoadableVariant::Enum(self.read_enum()?),
+            tag => return Err(format!("invalid loadable tag {}", tag)),
+        };
+        let loadable = CobLoadable {
+            fill: CobFill::default(),
+            id: CobLoadableIdentifier { name, generics: None },
+            variant,
+        };
+        Ok(CompiledLoadable { type_path, loadable })
+    }
+
+    fn read_values(&mut self) -> Result<Vec<CobValue>, String>
+    {
+        self.read_vec(Self::read_value)
+    }
+
+    fn read_map(&mut self) -> Result<CobMap, String>
+    {
+        let entries = self.read_vec(|reader| {
+            let entry = match reader.read_u8()? {
+                0 => {
+                    let key = reader.read_value()?;
+                    CobMapEntry::map_entry(key, reader.read_value()?)
+                }
+                1 => {
+                    let name = reader.read_str()?;
+                    CobMapEntry::struct_field(&name, reader.read_value()?)
+                }
+                tag => return Err(format!("invalid map key tag {}", tag)),
+            };
+            Ok(entry)
+        })?;
+        Ok(CobMap::from(entries))
+    }
+
+    fn read_enum(&mut self) -> Result<CobEnum, String>
+    {
+        let id = self.read_str()?;
+        let variant = match self.read_u8()? {
+            0 => CobEnumVariant::Unit,
+            1 => CobEnumVariant::Tuple(CobTuple::from(self.read_values()?)),
+            2 => CobEnumVariant::Array(CobArray::from(self.read_values()?)),
+            3 => CobEnumVariant::Map(self.read_map()?),
+            tag => return Err(format!("invalid enum variant tag {}", tag)),
+        };
+        Ok(CobEnum {
+            fill: CobFill::default(),
+            id: CobEnumVariantIdentifier(id),
+            variant,
+        })
+    }
+
+    fn read_number(&mut self) -> Result<CobNumberValue, String>
+    {
+        let number = match self.read_u8()? {
+            0 => CobNumberValue::Uint(u128::from_le_bytes(self.read_bytes()?)),
+            1 => CobNumberValue::Int(i128::from_le_bytes(self.read_bytes()?)),
+            2 => CobNumberValue::Float64(f64::from_le_bytes(self.read_bytes()?)),
+            3 => CobNumberValue::Float32(self.read_f32()?),
+            tag => return Err(format!("invalid number tag {}", tag)),
+        };
+        Ok(number)
+    }
+
+    fn read_val(&mut self) -> Result<Val, String>
+    {
+        let tag = self.read_u8()?;
+        let value = self.read_f32()?;
+        let val = match tag {
+            0 => Val::Auto,
+            1 => Val::Px(value),
+            2 => Val::Percent(value),
+            3 => Val::Vw(value),
+            4 => Val::Vh(value),
+            5 => Val::VMin(value),
+            6 => Val::VMax(value),
+            tag => return Err(format!("invalid Val tag {}", tag)),
+        };
+        Ok(val)
+    }
+
+    fn read_builtin(&mut self) -> Result<CobBuiltin, String>
+    {
+        let builtin = match self.read_u8()? {
+            0 => {
+                let [red, green, blue, alpha] =
+                    [self.read_f32()?, self.read_f32()?, self.read_f32()?, self.read_f32()?];
+                CobBuiltin::Color(CobColor {
+                    fill: CobFill::default(),
+                    color: Srgba::new(red, green, blue, alpha),
+                    syntax: CobColorSyntax::default(),
+                })
+            }
+            1 => {
+                let number = match self.read_u8()? {
+                    0 => None,
+                    1 => Some(self.read_number()?),
+                    tag => return Err(format!("invalid Val number tag {}", tag)),
+                };
+                CobBuiltin::Val { fill: CobFill::default(), number, val: self.read_val()? }
+            }
+            2 => {
+                let number = self.read_number()?;
+                let unit = match self.read_u8()? {
+                    0 => CobFontUnit::Rem,
+                    1 => CobFontUnit::Em,
+                    tag => return Err(format!("invalid font unit tag {}", tag)),
+                };
+                CobBuiltin::FontRelative { fill: CobFill::default(), number, unit }
+            }
+            tag => return Err(format!("invalid builtin tag {}", tag)),
+        };
+        Ok(builtin)
+    }
+
+    fn read_value(&mut self) -> Result<CobValue, String>
+    {
+        let value = match self.read_u8()? {
+            0 => CobValue::Enum(self.read_enum()?),
+            1 => CobValue::Builtin(self.read_builtin()?),
+            2 => CobValue::Array(CobArray::from(self.read_values()?)),
+            3 => CobValue::Tuple(CobTuple::from(self.read_values()?)),
+            4 => CobValue::Map(self.read_map()?),
+            5 => CobValue::Number(CobNumber { fill: CobFill::default(), number: self.read_number()? }),
+            6 => CobValue::Bool(CobBool::from(self.read_u8()? != 0)),
+            7 => CobValue::None(CobNone::default()),
+            8 => CobValue::String(CobString::from(String::from(self.read_str()?))),
+            9 => {
+                let first = Box::new(self.read_value()?);
+                let terms = self.read_vec(|reader| {
+                    let op = match reader.read_u8()? {
+                        0 => CobOperator::Add,
+                        1 => CobOperator::Subtract,
+                        2 => CobOperator::Multiply,
+                        3 => CobOperator::Divide,
+                        tag => return Err(format!("invalid operator tag {}", tag)),
+                    };
+                    Ok(CobExpressionTerm { op_fill: CobFill::default(), op, value: reader.read_value()? })
+                })?;
+                CobValue::Expression(CobExpression { first, terms })
+            }
+            10 => {
+                let name = self.read_str()?;
+                let args = CobTuple::from(self.read_values()?);
+                CobValue::Function(CobFunctionCall { fill: CobFill::default(), name, args })
+            }
+            11 => {
+                let name = CobConstantName { name: self.read_str()? };
+                let default = Box::new(self.read_value()?);
+                let themes = self.read_vec(|reader| Ok((reader.read_str()?, reader.read_value()?)))?;
+                CobValue::Themed(CobThemedValue { fill: CobFill::default(), name, default, themes })
+            }
+            12 => {
+                let id = CobString::from(String::from(self.read_str()?));
+                let args = self.read_vec(|reader| {
+                    let name = reader.read_str()?;
+                    Ok(CobMapKeyValue::struct_field(&name, reader.read_value()?))
+                })?;
+                CobValue::Localized(CobLocalized {
+                    fill: CobFill::default(),
+                    id,
+                    args,
+                    end_fill: CobFill::default(),
+                })
+            }
+            tag => return Err(format!("invalid value tag {}", tag)),
+        };
+        Ok(value)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,449 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy::reflect::TypeRegistry;
+use thiserror::Error;
+
+use crate::builtin::BUILTIN_COB_FILES;
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A parsed file waiting to be compiled.
+struct ParsedFile
+{
+    data: Cob,
+    manifest: Vec<(CobFile, ManifestKey)>,
+    imports: HashMap<ManifestKey, CobImportAlias>,
+    scene_base_files: HashSet<ManifestKey>,
+    /// Only files reachable from the compiler's roots are written to the output.
+    emit: bool,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Data cached for use by dependents.
+struct CompiledDependency
+{
+    constants_buff: ConstantsBuffer,
+    scenes: BaseScenes,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Possible errors that can be produced by the [`CobCompiler`].
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum CobCompileError
+{
+    /// A file could not be read.
+    #[error("Could not read {file}: {err}")]
+    Io
+    {
+        file: String, err: std::io::Error
+    },
+    /// Problems in the COB files.
+    #[error("Could not compile COB files; found {} problem(s):\n{}", .0.len(), display_diagnostics(.0))]
+    Diagnostics(Vec<CobDiagnostic>),
+    /// An import or base scene could not be resolved.
+    #[error("Could not resolve COB dependency: {0}")]
+    Dependency(String),
+    /// The output could not be encoded.
+    #[error("Could not encode compiled COB data: {0}")]
+    Encoding(String),
+}
+
+fn display_diagnostics(diagnostics: &[CobDiagnostic]) -> String
+{
+    diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Compiles a tree of COB files to a [`CompiledCob`] for release builds.
+///
+/// Starting from a set of root files, all files reachable through `#manifest` sections are compiled. Defs and
+/// macros are resolved, imports are flattened, base scenes are applied, and loadables are bound to their full type
+/// paths, so loading the result skips parsing and extraction. Problems that would normally be logged while loading
+/// a file are returned as [`CobCompileError::Diagnostics`] instead.
+///
+/// `#if` blocks are evaluated against the [`CobFlags`] passed to [`Self::compile`], so you need one compiled file
+/// per set of flags (e.g. per platform).
+///
+/// ```ignore
+/// let compiler = CobCompiler::new("assets", ["main.cob"]);
+/// app.add_systems(Startup, move |mut c: Commands| {
+///     c.queue(ExportCompiledCob::new(compiler.clone(), "assets/main.cobc"));
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct CobCompiler
+{
+    assets_dir: PathBuf,
+    roots: Vec<CobFile>,
+    sources: HashMap<CobFile, String>,
+}
+
+impl CobCompiler
+{
+    /// Makes a new compiler.
+    ///
+    /// Files are read from `assets_dir` (usually your `assets` directory) using their asset paths. Roots that
+    /// aren't `.cob` files are ignored.
+    pub fn new(assets_dir: impl Into<PathBuf>, roots: impl IntoIterator<Item = impl AsRef<str>>) -> Self
+    {
+        let roots = roots
+            .into_iter()
+            .filter_map(|root| {
+                let root = root.as_ref();
+                let file = CobFile::try_new(root);
+                if file.is_none() {
+                    tracing::warn!("ignoring COB compiler root {}; does not have '.cob' extension", root);
+                }
+                file
+            })
+            .collect();
+
+        let mut compiler = Self {
+            assets_dir: assets_dir.into(),
+            roots,
+            sources: HashMap::default(),
+        };
+        // Builtin files are compile-time dependencies only. The builtin plugins still load them as text files at
+        // runtime.
+        for builtin in BUILTIN_COB_FILES.iter() {
+            compiler.add_source(builtin.asset_path(), builtin.content);
+        }
+        compiler
+    }
+
+    /// Adds a file that should be compiled from memory instead of being read from the assets directory.
+    ///
+    /// Sources that aren't reachable from the roots can still be imported by files that are, but they won't be
+    /// included in the output.
+    pub fn add_source(&mut self, file: impl AsRef<str>, content: impl Into<String>) -> &mut Self
+    {
+        match CobFile::try_new(file.as_ref()) {
+            Some(file) => {
+                self.sources.insert(file, content.into());
+            }
+            None => {
+                tracing::warn!("ignoring COB compiler source {}; does not have '.cob' extension", file.as_ref());
+            }
+        }
+        self
+    }
+
+    fn read_file(&self, file: &CobFile) -> Result<String, CobCompileError>
+    {
+        if let Some(content) = self.sources.get(file) {
+            return Ok(content.clone());
+        }
+
+        std::fs::read_to_string(self.assets_dir.join(file.as_str()))
+            .map_err(|err| CobCompileError::Io { file: String::from(file.as_str()), err })
+    }
+
+    fn parse_file(
+        &self,
+        file: &CobFile,
+        emit: bool,
+        diagnostics: &CobDiagnostics,
+    ) -> Result<Option<ParsedFile>, CobCompileError>
+    {
+        let content = self.read_file(file)?;
+        let data = match Cob::parse(Span::new_extra(&content, CobLocationMetadata { file: file.as_str() })) {
+            Ok(data) => data,
+            Err(err) => {
+                match CobDiagnostic::from_span_error(&err) {
+                    Some(diagnostic) => diagnostics.add(diagnostic),
+                    None => diagnostics.add(CobDiagnostic::new_for_scene(
+                        file.as_str(),
+                        ScenePath::new("#file"),
+                        "insufficient data",
+                    )),
+                }
+                return Ok(None);
+            }
+        };
+
+        let (manifest, imports) = extract_manifest_and_imports(&data);
+        let scene_base_files = collect_scene_base_files(&data);
+        Ok(Some(ParsedFile { data, manifest, imports, scene_base_files, emit }))
+    }
+
+    /// Parses `start` and all files reachable from it through manifests.
+    ///
+    /// Returns the newly-parsed files in manifest order.
+    fn parse_tree(
+        &self,
+        start: impl IntoIterator<Item = CobFile>,
+        emit: bool,
+        parsed: &mut HashMap<CobFile, ParsedFile>,
+        diagnostics: &CobDiagnostics,
+    ) -> Result<Vec<CobFile>, CobCompileError>
+    {
+        let mut order = vec![];
+        let mut queue: VecDeque<CobFile> = start.into_iter().collect();
+
+        while let Some(file) = queue.pop_front() {
+            if parsed.contains_key(&file) || order.contains(&file) {
+                continue;
+            }
+            order.push(file.clone());
+            let Some(parsed_file) = self.parse_file(&file, emit, diagnostics)? else { continue };
+            queue.extend(parsed_file.manifest.iter().map(|(other, _)| other.clone()));
+            parsed.insert(file, parsed_file);
+        }
+
+        Ok(order)
+    }
+
+    /// Compiles the roots and everything reachable from them through manifests.
+    pub fn compile(
+        &self,
+        type_registry: &TypeRegistry,
+        loadables: &LoadableRegistry,
+        flags: &CobFlags,
+    ) -> Result<CompiledCob, CobCompileError>
+    {
+        let diagnostics = CobDiagnostics::default();
+
+        // Parse all files.
+        let mut parsed = HashMap::default();
+        let emitted = self.parse_tree(self.roots.iter().cloned(), true, &mut parsed, &diagnostics)?;
+        let mut sources: Vec<CobFile> = self.sources.keys().cloned().collect();
+        sources.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        self.parse_tree(sources, false, &mut parsed, &diagnostics)?;
+
+        // Map manifest keys to files.
+        let mut manifest_map: HashMap<ManifestKey, CobFile> = HashMap::default();
+        for parsed_file in parsed.values() {
+            for (file, key) in parsed_file.manifest.iter() {
+                if let Some(prev) = manifest_map.insert(key.clone(), file.clone()) {
+                    if prev != *file {
+                        return Err(CobCompileError::Dependency(format!(
+                            "manifest key {:?} is used for both {:?} and {:?}",
+                            key.as_str(),
+                            prev.as_str(),
+                            file.as_str()
+                        )));
+                    }
+                }
+            }
+        }
+
+        // Extract files after their dependencies.
+        let mut state = CompilerState {
+            type_registry,
+            loadables,
+            flags,
+            diagnostics: &diagnostics,
+            manifest_map: &manifest_map,
+            parsed: &parsed,
+            processed: HashMap::default(),
+            in_progress: vec![],
+            compiled: HashMap::default(),
+        };
+        for file in emitted.iter() {
+            state.process(file)?;
+        }
+        let mut compiled = state.compiled;
+
+        let diagnostics = diagnostics.all();
+        if !diagnostics.is_empty() {
+            return Err(CobCompileError::Diagnostics(diagnostics));
+        }
+
+        let files = emitted
+            .into_iter()
+            .filter_map(|file| {
+                let (commands, scenes) = compiled.remove(&file)?;
+                let manifest = parsed.remove(&file)?.manifest;
+                Some(CompiledCobFile { file, manifest, commands, scenes })
+            })
+            .collect();
+
+        Ok(CompiledCob { roots: self.roots.clone(), files })
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+struct CompilerState<'a>
+{
+    type_registry: &'a TypeRegistry,
+    loadables: &'a LoadableRegistry,
+    flags: &'a CobFlags,
+    diagnostics: &'a CobDiagnostics,
+    manifest_map: &'a HashMap<ManifestKey, CobFile>,
+    parsed: &'a HashMap<CobFile, ParsedFile>,
+    processed: HashMap<CobFile, CompiledDependency>,
+    /// Stack of files being processed, for detecting dependency cycles.
+    in_progress: Vec<CobFile>,
+    /// [ file : (commands, scenes) ]
+    compiled: HashMap<CobFile, (Vec<CompiledLoadable>, Vec<CompiledSceneNode>)>,
+}
+
+impl CompilerState<'_>
+{
+    fn get_dependency(&self, file: &CobFile, key: &ManifestKey) -> Result<CobFile, CobCompileError>
+    {
+        self.manifest_map.get(key).cloned().ok_or_else(|| {
+            CobCompileError::Dependency(format!(
+                "{:?} depends on unknown manifest key {:?}; note that builtin colors/widgets/etc. require the \
+                corresponding feature to be enabled",
+                file.as_str(),
+                key.as_str()
+            ))
+        })
+    }
+
+    fn process(&mut self, file: &CobFile) -> Result<(), CobCompileError>
+    {
+        if self.processed.contains_key(file) {
+            return Ok(());
+        }
+        if self.in_progress.contains(file) {
+            let cycle: Vec<&str> = self.in_progress.iter().map(|f| f.as_str()).collect();
+            return Err(CobCompileError::Dependency(format!(
+                "dependency cycle {:?} -> {:?}",
+                cycle,
+                file.as_str()
+            )));
+        }
+        // Files that failed to parse were already reported.
+        let Some(parsed_file) = self.parsed.get(file) else { return Ok(()) };
+
+        // Process dependencies first.
+        let imports: Vec<(CobFile, CobImportAlias)> = parsed_file
+            .imports
+            .iter()
+            .map(|(key, alias)| Ok((self.get_dependency(file, key)?, alias.clone())))
+            .collect::<Result<_, CobCompileError>>()?;
+        let base_files: Vec<CobFile> = parsed_file
+            .scene_base_files
+            .iter()
+            .map(|key| self.get_dependency(file, key))
+            .collect::<Result<_, CobCompileError>>()?;
+        let emit = parsed_file.emit;
+
+        self.in_progress.push(file.clone());
+        for dependency in imports.iter().map(|(dep, _)| dep).chain(base_files.iter()) {
+            self.process(dependency)?;
+        }
+        self.in_progress.pop();
+
+        // Extract the file's defs on top of the defs of its dependencies.
+        let mut specs = SpecsMap::default();
+        let mut data = self.parsed.get(file).unwrap().data.clone();
+        let constants_buff = extract_cob_defs(
+            file,
+            &mut data,
+            self.flags,
+            imports.iter().filter_map(|(dependency, alias)| {
+                let processed = self.processed.get(dependency)?;
+                Some((alias, &processed.constants_buff))
+            }),
+            &mut specs,
+        );
+
+        let commands = match emit {
+            true => compile_cob_commands(
+                self.type_registry,
+                file,
+                &mut data,
+                self.flags,
+                self.loadables,
+                self.diagnostics,
+                &constants_buff,
+            ),
+            false => vec![],
+        };
+
+        let scenes = extract_scene_inheritance(file, &mut data, self.flags, &constants_buff, |key| {
+            let base = self.processed.get(self.manifest_map.get(key)?)?;
+            Some((&base.scenes, &base.constants_buff))
+        });
+
+        if emit {
+            let scenes = compile_cob_scenes(
+                self.type_registry,
+                file,
+                &mut data,
+                self.flags,
+                self.loadables,
+                self.diagnostics,
+                &constants_buff,
+            );
+            self.compiled.insert(file.clone(), (commands, scenes));
+        }
+
+        self.processed
+            .insert(file.clone(), CompiledDependency { constants_buff, scenes });
+
+        Ok(())
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command that compiles COB files with a [`CobCompiler`] and writes the result to a `.cobc` file.
+///
+/// Uses the app's type registry, [`LoadableRegistry`], and [`CobFlags`], so it should run after all loadables
+/// are registered.
+///
+/// ```ignore
+/// app.add_systems(Startup, |mut c: Commands| {
+///     c.queue(ExportCompiledCob::new(CobCompiler::new("assets", ["main.cob"]), "assets/main.cobc"));
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExportCompiledCob
+{
+    pub compiler: CobCompiler,
+    pub path: PathBuf,
+}
+
+impl ExportCompiledCob
+{
+    pub fn new(compiler: CobCompiler, path: impl Into<PathBuf>) -> Self
+    {
+        Self { compiler, path: path.into() }
+    }
+}
+
+impl Command for ExportCompiledCob
+{
+    fn apply(self, world: &mut World)
+    {
+        let compiled = {
+            let type_registry = world.resource::<AppTypeRegistry>().read();
+            self.compiler.compile(
+                &type_registry,
+                world.resource::<LoadableRegistry>(),
+                world.resource::<CobFlags>(),
+            )
+        };
+
+        let bytes = match compiled.and_then(|compiled| compiled.to_bytes()) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                tracing::error!("failed compiling COB files for {}: {}", self.path.display(), err);
+                return;
+            }
+        };
+
+        match std::fs::write(&self.path, bytes) {
+            Ok(()) => tracing::info!("exported compiled COB files to {}", self.path.display()),
+            Err(err) => tracing::error!("failed exporting compiled COB files to {}: {}", self.path.display(), err),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,103 @@
+use smol_str::SmolStr;
+
+use super::*;
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A loadable whose defs and macros were resolved by the [`CobCompiler`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledLoadable
+{
+    /// The full type path of the loadable, used to look up its type registration without going through the
+    /// short names in the [`LoadableRegistry`].
+    pub type_path: SmolStr,
+    /// The loadable's value.
+    ///
+    /// May contain [`CobValue::Themed`] values and `rem`/`em` values, which are resolved when the loadable is
+    /// loaded.
+    pub loadable: CobLoadable,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A scene node whose macros and base scenes were resolved by the [`CobCompiler`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledSceneNode
+{
+    /// The node's name. Anonymous nodes are given the same `_{index}` names they get when extracting text files.
+    pub name: SmolStr,
+    pub loadables: Vec<CompiledLoadable>,
+    /// Index of the node's `TextLineSize` loadable, which `em` values in the node are relative to.
+    pub text_size: Option<usize>,
+    pub children: Vec<CompiledSceneNode>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A COB file in a [`CompiledCob`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompiledCobFile
+{
+    /// The file the content was compiled from.
+    pub file: CobFile,
+    /// Entries in the file's `#manifest` sections.
+    pub manifest: Vec<(CobFile, ManifestKey)>,
+    /// Loadables in the file's `#commands` sections.
+    pub commands: Vec<CompiledLoadable>,
+    /// Scenes in the file's `#scenes` sections.
+    pub scenes: Vec<CompiledSceneNode>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// A tree of COB files compiled by the [`CobCompiler`].
+///
+/// Files are stored in manifest order, so every file comes after the file whose manifest contains it.
+///
+/// Use [`Self::to_bytes`] to get the binary format that is loaded from `.cobc` files.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompiledCob
+{
+    /// The files the compiler started from. Commands in these files are applied in this order.
+    pub roots: Vec<CobFile>,
+    pub files: Vec<CompiledCobFile>,
+}
+
+impl CompiledCob
+{
+    /// Writes the binary format.
+    ///
+    /// Fails if a value could not be fully resolved by the compiler.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, CobCompileError>
+    {
+        let mut writer = CobBinaryWriter::default();
+        writer.write_len(self.roots.len());
+        for root in self.roots.iter() {
+            writer.write_str(root.as_str());
+        }
+        writer.write_len(self.files.len());
+        for file in self.files.iter() {
+            writer
+                .write_file(file)
+                .map_err(|err| CobCompileError::Encoding(format!("{} in {:?}", err, file.file.as_str())))?;
+        }
+        Ok(writer.finish())
+    }
+
+    /// Reads the binary format written by [`Self::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String>
+    {
+        let mut reader = CobBinaryReader::new(bytes)?;
+        let roots = (0..reader.read_len()?)
+            .map(|_| reader.read_file_path())
+            .collect::<Result<Vec<_>, _>>()?;
+        let files = (0..reader.read_len()?)
+            .map(|_| reader.read_file())
+            .collect::<Result<Vec<_>, _>>()?;
+        reader.finish()?;
+        Ok(Self { roots, files })
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,7 @@
+mod cob_binary;
+mod cob_compiler;
+mod compiled_cob;
+
+pub(self) use cob_binary::*;
+pub use cob_compiler::*;
+pub use compiled_cob::*;
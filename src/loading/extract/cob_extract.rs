@@ -8,6 +8,29 @@ use crate::prelude::*;
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Extracts the manifest and import sections of a file.
+///
+/// Returns `(manifest entries, imports)`.
+pub(crate) fn extract_manifest_and_imports(
+    data: &Cob,
+) -> (Vec<(CobFile, ManifestKey)>, HashMap<ManifestKey, CobImportAlias>)
+{
+    let mut manifest = vec![];
+    let mut imports: HashMap<ManifestKey, CobImportAlias> = HashMap::default();
+
+    for section in data.sections.iter() {
+        match section {
+            CobSection::Manifest(section) => extract_manifest_section(&data.file, section, &mut manifest),
+            CobSection::Import(section) => extract_import_section(section, &mut imports),
+            _ => (),
+        }
+    }
+
+    (manifest, imports)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Preprocesses a cobweb asset file and adds it to [`CobAssetCache`] for processing.
 ///
 /// Only the manifest and imports sections of the file are extracted here.
@@ -16,23 +39,20 @@ pub(crate) fn preprocess_cob_file(
     cob_files: &mut LoadedCobAssetFiles,
     cob_cache: &mut CobAssetCache,
     commands_buffer: &mut CommandsBuffer,
-    data: Cob,
+    mut data: Cob,
     #[cfg(feature = "editor")] hash: crate::editor::CobFileHash,
+    #[cfg(feature = "editor")] has_errors: bool,
+    #[cfg(feature = "hot_reload")] broken_nodes: Vec<ScenePath>,
 )
 {
     cob_cache.initialize_file(&data.file);
 
-    // Extract manifest and import sections.
-    let mut manifest = vec![];
-    let mut imports: HashMap<ManifestKey, CobImportAlias> = HashMap::default();
+    // Keep the last good version of scene nodes that failed to parse.
+    #[cfg(feature = "hot_reload")]
+    cob_cache.restore_broken_scene_nodes(&mut data, &broken_nodes);
 
-    for section in data.sections.iter() {
-        match section {
-            CobSection::Manifest(section) => extract_manifest_section(&data.file, section, &mut manifest),
-            CobSection::Import(section) => extract_import_section(section, &mut imports),
-            _ => (),
-        }
-    }
+    // Extract manifest and import sections.
+    let (manifest, imports) = extract_manifest_and_imports(&data);
 
     // Register manifest keys.
     let mut descendants = vec![];
@@ -62,17 +82,24 @@ pub(crate) fn preprocess_cob_file(
         data,
         #[cfg(feature = "editor")]
         hash,
+        #[cfg(feature = "editor")]
+        has_errors,
     );
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
-/// Extracts importable values (defs sections).
+/// Extracts importable values (defs and theme sections).
 ///
 /// This is semi-destructive, because definitions will be removed and inserted to appropriate maps/buffers.
+///
+/// Constants overridden by `#theme` sections are extracted as [`CobThemedValue`]s.
+///
+/// Sections in `#if` blocks are only extracted if their branch is selected by `flags`.
 pub(crate) fn extract_cob_importables(
     file: CobFile,
     data: &mut Cob,
+    flags: &CobFlags,
     constants_buffer: &mut ConstantsBuffer,
     // tracks specs
     _specs: &mut SpecsMap,
@@ -80,26 +107,72 @@ pub(crate) fn extract_cob_importables(
 {
     tracing::info!("extracting COB file {:?}", file.as_str());
 
-    constants_buffer.start_new_file();
+    constants_buffer.start_new_file(&file);
 
-    for section in data.sections.iter_mut() {
+    // Themes are collected first so they can override constants from any #defs section in the file.
+    let mut theme_overrides = ThemeOverrides::default();
+    for section in data.active_sections_mut(flags) {
         match section {
-            CobSection::Defs(section) => extract_defs_section(&file, section, constants_buffer),
+            CobSection::Theme(section) => extract_theme_section(section, &mut theme_overrides),
             _ => (),
         }
     }
 
+    for section in data.active_sections_mut(flags) {
+        match section {
+            CobSection::Defs(section) => {
+                extract_defs_section(&file, section, &mut theme_overrides, constants_buffer)
+            }
+            _ => (),
+        }
+    }
+
+    for (constant, overrides) in theme_overrides.iter() {
+        for (theme, _) in overrides.iter() {
+            tracing::warn!("ignoring #theme({}) override of constant {:?} in {:?}; themes can only override \
+                constants defined in the same file", theme, constant.as_str(), file);
+        }
+    }
+
     constants_buffer.end_new_file();
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Makes the [`ConstantsBuffer`] of a file from the buffers of the files it imports, then extracts the file's
+/// importables into it (see [`extract_cob_importables`]).
+///
+/// This is how defs are resolved everywhere files are extracted, so the [`CobAssetCache`], the [`CobCompiler`],
+/// and the [`CobResolver`] agree on what is visible in a file.
+pub(crate) fn extract_cob_defs<'a>(
+    file: &CobFile,
+    data: &mut Cob,
+    flags: &CobFlags,
+    imports: impl IntoIterator<Item = (&'a CobImportAlias, &'a ConstantsBuffer)>,
+    specs: &mut SpecsMap,
+) -> ConstantsBuffer
+{
+    let mut constants_buffer = ConstantsBuffer::default();
+    for (alias, imported) in imports {
+        constants_buffer.append(alias, imported);
+    }
+    extract_cob_importables(file.clone(), data, flags, &mut constants_buffer, specs);
+    constants_buffer
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Extracts commands from a `Cob`. Commands are updated in-place when resolving defs.
+///
+/// Commands with themed constants are tracked in the [`SceneBuffer`] so they can be re-applied when the
+/// [`ActiveTheme`] changes.
 pub(crate) fn extract_cob_commands(
     type_registry: &TypeRegistry,
     commands_buffer: &mut CommandsBuffer,
+    scene_buffer: &mut SceneBuffer,
     file: CobFile,
     data: &mut Cob,
+    flags: &CobFlags,
     loadables: &LoadableRegistry,
     constants_buffer: &ConstantsBuffer,
     // tracks specs
@@ -107,12 +180,14 @@ pub(crate) fn extract_cob_commands(
 )
 {
     let mut commands = vec![];
+    scene_buffer.prepare_file_commands(&file);
 
-    for section in data.sections.iter_mut() {
+    for section in data.active_sections_mut(flags) {
         match section {
             CobSection::Commands(section) => extract_commands_section(
                 type_registry,
                 &mut commands,
+                scene_buffer,
                 &file,
                 section,
                 loadables,
@@ -135,13 +210,14 @@ pub(crate) fn extract_cob_scenes(
     scene_loader: &mut SceneLoader,
     file: CobFile,
     mut data: Cob,
+    flags: &CobFlags,
     loadables: &LoadableRegistry,
     constants_buffer: &ConstantsBuffer,
     // tracks specs
     _specs: &SpecsMap,
 )
 {
-    for section in data.sections.iter_mut() {
+    for section in data.active_sections_mut(flags) {
         match section {
             CobSection::Scenes(section) => extract_scenes(
                 type_registry,
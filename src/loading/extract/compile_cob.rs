@@ -0,0 +1,268 @@
+use bevy::reflect::{TypePath, TypeRegistry};
+use smol_str::SmolStr;
+
+use super::*;
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Resolves a loadable's defs and binds it to its type path.
+///
+/// Problems are reported as diagnostics so the compiler can fail instead of producing a partial file.
+fn compile_loadable(
+    id_scratch: String,
+    seen_shortnames: &mut Vec<&'static str>,
+    type_registry: &TypeRegistry,
+    compiled: &mut Vec<CompiledLoadable>,
+    file: &CobFile,
+    current_path: &ScenePath,
+    loadable: &mut CobLoadable,
+    loadables: &LoadableRegistry,
+    diagnostics: &CobDiagnostics,
+    constants_buffer: &ConstantsBuffer,
+) -> String
+{
+    // Get the loadable's longname.
+    let id_scratch = loadable.id.to_canonical(Some(id_scratch));
+    let Some((short_name, long_name, _, _)) = get_loadable_meta(
+        type_registry,
+        file,
+        current_path,
+        id_scratch.as_str(),
+        loadables,
+        Some(diagnostics),
+    ) else {
+        return id_scratch;
+    };
+
+    // Check for duplicate.
+    if seen_shortnames.iter().any(|other| *other == short_name) {
+        diagnostics.add(
+            CobDiagnostic::new_for_scene(
+                file.as_str(),
+                current_path.clone(),
+                format!("duplicate loadable `{}`", short_name),
+            )
+            .with_hint(format!("use Multi<{}> instead", short_name)),
+        );
+        return id_scratch;
+    }
+
+    // Resolve defs.
+    if let Err(err) = loadable.resolve(constants_buffer) {
+        diagnostics.add(CobDiagnostic::new_for_scene(
+            file.as_str(),
+            current_path.clone(),
+            format!("failed resolving defs in loadable `{}`; {}", short_name, err),
+        ));
+        return id_scratch;
+    }
+
+    seen_shortnames.push(short_name);
+    compiled.push(CompiledLoadable {
+        type_path: SmolStr::new_static(long_name),
+        loadable: loadable.clone(),
+    });
+
+    id_scratch
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Compiles the `#commands` sections of a file.
+///
+/// The file's importables should already be extracted into `constants_buffer`.
+pub(crate) fn compile_cob_commands(
+    type_registry: &TypeRegistry,
+    file: &CobFile,
+    data: &mut Cob,
+    flags: &CobFlags,
+    loadables: &LoadableRegistry,
+    diagnostics: &CobDiagnostics,
+    constants_buffer: &ConstantsBuffer,
+) -> Vec<CompiledLoadable>
+{
+    let mock_path = ScenePath::new("#commands");
+    let mut compiled = vec![];
+    let mut id_scratch = String::default();
+
+    for section in data.active_sections_mut(flags) {
+        let CobSection::Commands(section) = section else { continue };
+
+        // Duplicates are only checked within each section, the same as when extracting text files.
+        let mut seen_shortnames = vec![];
+
+        for entry in section.entries.iter_mut() {
+            let mut expanded = match entry {
+                CobCommandEntry::Loadable(loadable) => vec![loadable.clone()],
+                CobCommandEntry::LoadableMacroCall(call) => match call.expand(constants_buffer) {
+                    Ok(expanded) => expanded,
+                    Err(err) => {
+                        diagnostics.add(CobDiagnostic::new_for_scene(
+                            file.as_str(),
+                            mock_path.clone(),
+                            format!("failed expanding loadable macro call `{}`; {}", call.path.as_str(), err),
+                        ));
+                        continue;
+                    }
+                },
+            };
+
+            for loadable in expanded.iter_mut() {
+                id_scratch = compile_loadable(
+                    id_scratch,
+                    &mut seen_shortnames,
+                    type_registry,
+                    &mut compiled,
+                    file,
+                    &mock_path,
+                    loadable,
+                    loadables,
+                    diagnostics,
+                    constants_buffer,
+                );
+            }
+        }
+    }
+
+    compiled
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn compile_scene_layer(
+    mut id_scratch: String,
+    seen_shortnames: &mut Vec<&'static str>,
+    type_registry: &TypeRegistry,
+    file: &CobFile,
+    current_path: &ScenePath,
+    name: SmolStr,
+    cob_layer: &mut CobSceneLayer,
+    loadables: &LoadableRegistry,
+    diagnostics: &CobDiagnostics,
+    constants_buffer: &ConstantsBuffer,
+) -> (CompiledSceneNode, String)
+{
+    // Expand macros so the layer only contains loadables and scene nodes.
+    expand_scene_layer_macros(file, current_path, cob_layer, constants_buffer);
+
+    let mut node = CompiledSceneNode { name, loadables: vec![], text_size: None, children: vec![] };
+
+    // Add loadables.
+    seen_shortnames.clear();
+    for entry in cob_layer.entries.iter_mut() {
+        let CobSceneLayerEntry::Loadable(loadable) = entry else { continue };
+        id_scratch = compile_loadable(
+            id_scratch,
+            seen_shortnames,
+            type_registry,
+            &mut node.loadables,
+            file,
+            current_path,
+            loadable,
+            loadables,
+            diagnostics,
+            constants_buffer,
+        );
+    }
+    node.text_size = node
+        .loadables
+        .iter()
+        .position(|loadable| loadable.type_path == <TextLineSize as TypePath>::type_path());
+
+    // Add layers.
+    let mut anonymous_count = 0;
+    for entry in cob_layer.entries.iter_mut() {
+        let CobSceneLayerEntry::Layer(next_cob_layer) = entry else { continue };
+
+        // If node is anonymous, give it a unique name.
+        let layer_name = if next_cob_layer.name.as_str() == "" {
+            let name = SmolStr::from(format!("_{}", anonymous_count));
+            anonymous_count += 1;
+            name
+        } else {
+            next_cob_layer.name.0.clone()
+        };
+
+        let Some(node_path) = current_path.extend_single(layer_name.as_str()) else {
+            diagnostics.add(CobDiagnostic::new_for_scene(
+                file.as_str(),
+                current_path.clone(),
+                format!("scene node ID {:?} is a multi-segment path", layer_name.as_str()),
+            ));
+            continue;
+        };
+
+        let (child, next_scratch) = compile_scene_layer(
+            id_scratch,
+            seen_shortnames,
+            type_registry,
+            file,
+            &node_path,
+            layer_name,
+            next_cob_layer,
+            loadables,
+            diagnostics,
+            constants_buffer,
+        );
+        id_scratch = next_scratch;
+        node.children.push(child);
+    }
+
+    (node, id_scratch)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Compiles the `#scenes` sections of a file.
+///
+/// Base scenes should already be applied with [`extract_scene_inheritance`].
+pub(crate) fn compile_cob_scenes(
+    type_registry: &TypeRegistry,
+    file: &CobFile,
+    data: &mut Cob,
+    flags: &CobFlags,
+    loadables: &LoadableRegistry,
+    diagnostics: &CobDiagnostics,
+    constants_buffer: &ConstantsBuffer,
+) -> Vec<CompiledSceneNode>
+{
+    let mut compiled = vec![];
+    let mut id_scratch = String::default();
+    let mut seen_shortnames = vec![];
+
+    for section in data.active_sections_mut(flags) {
+        let CobSection::Scenes(section) = section else { continue };
+
+        for cob_layer in section.scenes.iter_mut() {
+            let Some(path) = ScenePath::parse_single(&*cob_layer.name) else {
+                diagnostics.add(CobDiagnostic::new_for_scene(
+                    file.as_str(),
+                    ScenePath::new("#scenes"),
+                    format!("scene root ID {:?} is a multi-segment path", &*cob_layer.name),
+                ));
+                continue;
+            };
+
+            let name = cob_layer.name.0.clone();
+            let (scene, next_scratch) = compile_scene_layer(
+                id_scratch,
+                &mut seen_shortnames,
+                type_registry,
+                file,
+                &path,
+                name,
+                cob_layer,
+                loadables,
+                diagnostics,
+                constants_buffer,
+            );
+            id_scratch = next_scratch;
+            compiled.push(scene);
+        }
+    }
+
+    compiled
+}
+
+//-------------------------------------------------------------------------------------------------------------------
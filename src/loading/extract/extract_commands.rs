@@ -1,3 +1,6 @@
+use std::any::TypeId;
+
+use bevy::reflect::serde::TypedReflectDeserializer;
 use bevy::reflect::TypeRegistry;
 
 use super::*;
@@ -5,9 +8,106 @@ use crate::prelude::*;
 
 //-------------------------------------------------------------------------------------------------------------------
 
+fn handle_command(
+    shortname: String,
+    seen_shortnames: &mut Vec<&'static str>,
+    type_registry: &TypeRegistry,
+    commands: &mut Vec<(&'static str, ErasedLoadable)>,
+    scene_buffer: &mut SceneBuffer,
+    file: &CobFile,
+    mock_path: &ScenePath,
+    loadable: &mut CobLoadable,
+    loadables: &LoadableRegistry,
+    constants_buffer: &ConstantsBuffer,
+) -> String
+{
+    // Get the shortname.
+    let shortname = loadable.id.to_canonical(Some(shortname));
+
+    // Get the loadable's longname.
+    let Some((short_name, long_name, type_id, deserializer)) = get_loadable_meta(
+        type_registry,
+        file,
+        mock_path,
+        shortname.as_str(),
+        loadables,
+        // Unregistered loadables are reported by `validate` when the file is loaded.
+        None,
+    ) else {
+        return shortname;
+    };
+
+    // Check for duplicate.
+    if seen_shortnames.iter().any(|other| *other == short_name) {
+        tracing::warn!("ignoring duplicate command {} in {:?}; use Multi<{}> instead",
+            short_name, file, short_name);
+        return shortname;
+    }
+
+    seen_shortnames.push(short_name);
+
+    // Resolve defs.
+    if let Err(err) = loadable.resolve(constants_buffer) {
+        tracing::warn!("failed extracting command {:?} in {:?}; error resolving defs: {:?}",
+            short_name, file, err.as_str());
+        return shortname;
+    }
+
+    push_command(
+        commands,
+        scene_buffer,
+        file,
+        short_name,
+        long_name,
+        type_id,
+        deserializer,
+        loadable,
+    );
+
+    shortname
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Resolves a command's themed constants and saves its value.
+///
+/// The command's defs should already be resolved.
+pub(super) fn push_command(
+    commands: &mut Vec<(&'static str, ErasedLoadable)>,
+    scene_buffer: &mut SceneBuffer,
+    file: &CobFile,
+    short_name: &'static str,
+    long_name: &'static str,
+    type_id: TypeId,
+    deserializer: TypedReflectDeserializer,
+    loadable: &mut CobLoadable,
+)
+{
+    // Resolve themed constants.
+    let unresolved = loadable.clone();
+    match loadable.resolve_theme(scene_buffer.active_theme()) {
+        Ok(true) => scene_buffer.track_themed_command(file, type_id, long_name, unresolved),
+        Ok(false) => (),
+        Err(err) => {
+            tracing::warn!("failed extracting command {:?} in {:?}; error resolving theme: {:?}",
+                short_name, file, err.as_str());
+            return;
+        }
+    }
+
+    // Get the commands's value.
+    let command_value = get_loadable_value(deserializer, loadable);
+
+    // Save the command.
+    commands.push((long_name, ErasedLoadable { type_id, loadable: command_value }));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 pub(super) fn extract_commands_section(
     type_registry: &TypeRegistry,
     commands: &mut Vec<(&'static str, ErasedLoadable)>,
+    scene_buffer: &mut SceneBuffer,
     file: &CobFile,
     section: &mut CobCommands,
     loadables: &LoadableRegistry,
@@ -25,40 +125,44 @@ pub(super) fn extract_commands_section(
     for entry in section.entries.iter_mut() {
         match entry {
             CobCommandEntry::Loadable(loadable) => {
-                // Get the shortname.
-                shortname = loadable.id.to_canonical(Some(shortname));
-
-                // Get the loadable's longname.
-                let Some((short_name, long_name, type_id, deserializer)) =
-                    get_loadable_meta(type_registry, file, &mock_path, shortname.as_str(), loadables)
-                else {
-                    continue;
+                shortname = handle_command(
+                    shortname,
+                    &mut seen_shortnames,
+                    type_registry,
+                    commands,
+                    scene_buffer,
+                    file,
+                    &mock_path,
+                    loadable,
+                    loadables,
+                    constants_buffer,
+                );
+            }
+            CobCommandEntry::LoadableMacroCall(call) => {
+                // Expand the macro and insert its loadables as if they were written out in the section.
+                let mut expanded = match call.expand(constants_buffer) {
+                    Ok(expanded) => expanded,
+                    Err(err) => {
+                        tracing::warn!("failed extracting loadable macro call {:?} in command section of {:?}; {}",
+                            call.path.as_str(), file, err.as_str());
+                        continue;
+                    }
                 };
 
-                // Check for duplicate.
-                if seen_shortnames.iter().any(|other| *other == short_name) {
-                    tracing::warn!("ignoring duplicate command {} in {:?}; use Multi<{}> instead",
-                        short_name, file, short_name);
-                    continue;
+                for loadable in expanded.iter_mut() {
+                    shortname = handle_command(
+                        shortname,
+                        &mut seen_shortnames,
+                        type_registry,
+                        commands,
+                        scene_buffer,
+                        file,
+                        &mock_path,
+                        loadable,
+                        loadables,
+                        constants_buffer,
+                    );
                 }
-
-                seen_shortnames.push(short_name);
-
-                // Resolve defs.
-                if let Err(err) = loadable.resolve(constants_buffer) {
-                    tracing::warn!("failed extracting command {:?} in {:?}; error resolving defs: {:?}",
-                        short_name, file, err.as_str());
-                    continue;
-                }
-
-                // Get the commands's value.
-                let command_value = get_loadable_value(deserializer, loadable);
-
-                // Save the command.
-                commands.push((long_name, ErasedLoadable { type_id, loadable: command_value }));
-            }
-            CobCommandEntry::LoadableMacroCall(_) => {
-                tracing::error!("ignoring unresolved loadable macro in COB file command section {:?}", file);
             }
         }
     }
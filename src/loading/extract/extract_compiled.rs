@@ -0,0 +1,174 @@
+use bevy::prelude::Commands;
+use bevy::reflect::TypeRegistry;
+
+use super::*;
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Extracts the commands of a compiled file.
+///
+/// Commands with themed constants are tracked in the [`SceneBuffer`] the same as commands from text files.
+pub(crate) fn extract_compiled_commands(
+    type_registry: &TypeRegistry,
+    commands_buffer: &mut CommandsBuffer,
+    scene_buffer: &mut SceneBuffer,
+    file: CobFile,
+    compiled: Vec<CompiledLoadable>,
+    diagnostics: &CobDiagnostics,
+)
+{
+    let mock_path = ScenePath::new("#commands");
+    let mut commands = vec![];
+    scene_buffer.prepare_file_commands(&file);
+
+    for CompiledLoadable { type_path, mut loadable } in compiled {
+        let Some((short_name, long_name, type_id, deserializer)) =
+            get_bound_loadable_meta(type_registry, &file, &mock_path, type_path.as_str(), diagnostics)
+        else {
+            continue;
+        };
+
+        push_command(
+            &mut commands,
+            scene_buffer,
+            &file,
+            short_name,
+            long_name,
+            type_id,
+            deserializer,
+            &mut loadable,
+        );
+    }
+
+    commands_buffer.set_file_commands(file, commands);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn extract_compiled_scene_layer(
+    type_registry: &TypeRegistry,
+    c: &mut Commands,
+    scene_buffer: &mut SceneBuffer,
+    scene_loader: &mut SceneLoader,
+    scene_layer: &mut SceneLayer,
+    scene: &SceneRef,
+    current_path: &ScenePath,
+    node: CompiledSceneNode,
+    diagnostics: &CobDiagnostics,
+)
+{
+    let file = scene
+        .file
+        .file()
+        .expect("all SceneFile should contain CobFile in scene extraction");
+
+    // Prep the node.
+    let scene_location = SceneRef { file: scene.file.clone(), path: current_path.clone() };
+    scene_buffer.prepare_scene_node(scene_location.clone());
+
+    // Begin layer update.
+    scene_layer.start_update(node.loadables.len() + node.children.len());
+
+    // Add loadables.
+    // - Loadables were deduplicated by the compiler, so their indices are stable.
+    let text_size = node
+        .text_size
+        .and_then(|index| node.loadables.get(index))
+        .map(|loadable| loadable.loadable.clone());
+    let mut loadable_index = 0;
+
+    for CompiledLoadable { type_path, mut loadable } in node.loadables {
+        let Some((short_name, long_name, type_id, deserializer)) =
+            get_bound_loadable_meta(type_registry, file, current_path, type_path.as_str(), diagnostics)
+        else {
+            continue;
+        };
+
+        if insert_scene_loadable(
+            scene_buffer,
+            file,
+            current_path,
+            loadable_index,
+            short_name,
+            long_name,
+            type_id,
+            deserializer,
+            &mut loadable,
+            text_size.as_ref(),
+        ) {
+            loadable_index += 1;
+        }
+    }
+
+    #[cfg(feature = "hot_reload")]
+    scene_buffer.end_loadable_insertion(&scene_location, loadable_index);
+
+    // Add layers.
+    for child in node.children {
+        let Some(node_path) = current_path.extend_single(child.name.as_str()) else {
+            tracing::error!("failed extracting compiled scene node {:?} at {:?} in {:?}, node ID is a \
+                multi-segment path (the compiled file may be corrupted)", child.name.as_str(), current_path, file);
+            continue;
+        };
+
+        // Save this node in the scene.
+        let child_layer = insert_scene_node(c, scene_loader, scene_layer, scene, current_path, &node_path);
+
+        extract_compiled_scene_layer(
+            type_registry,
+            c,
+            scene_buffer,
+            scene_loader,
+            child_layer,
+            scene,
+            &node_path,
+            child,
+            diagnostics,
+        );
+    }
+
+    end_scene_layer_update(c, scene_loader, scene_layer, scene);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Extracts the scenes of a compiled file.
+pub(crate) fn extract_compiled_scenes(
+    type_registry: &TypeRegistry,
+    c: &mut Commands,
+    scene_buffer: &mut SceneBuffer,
+    scene_loader: &mut SceneLoader,
+    file: CobFile,
+    compiled: Vec<CompiledSceneNode>,
+    diagnostics: &CobDiagnostics,
+)
+{
+    let mut scene_registry = scene_loader.take_scene_registry();
+
+    for node in compiled {
+        let Some(path) = ScenePath::parse_single(node.name.as_str()) else {
+            tracing::error!("failed extracting compiled scene {:?} in {:?}, scene root ID is a multi-segment path \
+                (the compiled file may be corrupted)", node.name.as_str(), file);
+            continue;
+        };
+        let scene_ref = SceneRef { file: SceneFile::File(file.clone()), path };
+        let scene_layer = scene_registry.get_or_insert(scene_ref.clone());
+
+        extract_compiled_scene_layer(
+            type_registry,
+            c,
+            scene_buffer,
+            scene_loader,
+            scene_layer,
+            &scene_ref,
+            &scene_ref.path,
+            node,
+            diagnostics,
+        );
+    }
+
+    scene_loader.return_scene_registry(scene_registry);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
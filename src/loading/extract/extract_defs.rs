@@ -1,8 +1,65 @@
+use std::collections::HashMap;
+
+use smol_str::SmolStr;
+
 use crate::prelude::*;
 
 //-------------------------------------------------------------------------------------------------------------------
 
-fn extract_constant_entry(file: &CobFile, mut entry: CobConstantDef, constants_buffer: &mut ConstantsBuffer)
+/// Constant overrides collected from the `#theme` sections of a file.
+///
+/// [ constant name : [ (theme name, override) ] ]
+pub(super) type ThemeOverrides = HashMap<SmolStr, Vec<(SmolStr, CobConstantDef)>>;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Replaces a constant's value with a [`CobThemedValue`].
+///
+/// Overrides are resolved with the same constants as the constant's default value.
+fn apply_theme_overrides(
+    file: &CobFile,
+    entry: &mut CobConstantDef,
+    overrides: Vec<(SmolStr, CobConstantDef)>,
+    constants_buffer: &ConstantsBuffer,
+)
+{
+    let CobConstantValue::Value(default) = &entry.value else {
+        tracing::warn!("ignoring #theme overrides of constant {:?} in {:?}; value groups can't be themed",
+            entry.name.as_str(), file);
+        return;
+    };
+
+    let mut themes = Vec::with_capacity(overrides.len());
+    for (theme, mut def) in overrides {
+        if let Err(err) = def.value.resolve(constants_buffer) {
+            tracing::warn!("failed extracting #theme({}) override of constant {:?} in {:?}; error resolving \
+                internal defs: {:?}", theme, entry.name.as_str(), file, err.as_str());
+            continue;
+        }
+        let CobConstantValue::Value(value) = def.value else {
+            tracing::warn!("ignoring #theme({}) override of constant {:?} in {:?}; value groups can't be themed",
+                theme, entry.name.as_str(), file);
+            continue;
+        };
+        themes.push((theme, value));
+    }
+
+    entry.value = CobConstantValue::Value(CobValue::Themed(CobThemedValue {
+        fill: CobFill::default(),
+        name: entry.name.clone(),
+        default: Box::new(default.clone()),
+        themes,
+    }));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn extract_constant_entry(
+    file: &CobFile,
+    mut entry: CobConstantDef,
+    theme_overrides: &mut ThemeOverrides,
+    constants_buffer: &mut ConstantsBuffer,
+)
 {
     // Resolve the def's internal value.
     if let Err(err) = entry.value.resolve(constants_buffer) {
@@ -11,32 +68,93 @@ fn extract_constant_entry(file: &CobFile, mut entry: CobConstantDef, constants_b
         return;
     }
 
+    // Apply overrides from #theme sections.
+    if let Some(overrides) = theme_overrides.remove(entry.name.as_str()) {
+        apply_theme_overrides(file, &mut entry, overrides, constants_buffer);
+    }
+
     // Save the constant definition in the constants buffer.
     constants_buffer.insert(entry.name.name, entry.value);
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
-fn extract_data_macro_entry(_file: &CobFile, _entry: CobDataMacroDef, _constants_buffer: &ConstantsBuffer) {}
+fn extract_data_macro_entry(file: &CobFile, mut entry: CobDataMacroDef, constants_buffer: &mut ConstantsBuffer)
+{
+    // Resolve the def's internal values. Macro params are left in place until the macro is called.
+    if let Err(err) = constants_buffer.resolve_macro_def(|constants| entry.resolve(constants)) {
+        tracing::warn!("failed extracting data macro entry {:?} in {:?}; error resolving internal defs: {:?}",
+            entry.name.as_str(), file, err.as_str());
+        return;
+    }
+
+    // Save the macro definition in the constants buffer.
+    constants_buffer.insert_data_macro(entry.name.clone(), entry);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn extract_loadable_macro_entry(
+    file: &CobFile,
+    mut entry: CobLoadableMacroDef,
+    constants_buffer: &mut ConstantsBuffer,
+)
+{
+    // Resolve the def's internal values. Macro params are left in place until the macro is called.
+    if let Err(err) = constants_buffer.resolve_macro_def(|constants| entry.resolve(constants)) {
+        tracing::warn!("failed extracting loadable macro entry {:?} in {:?}; error resolving internal defs: {:?}",
+            entry.name.as_str(), file, err.as_str());
+        return;
+    }
+
+    // Save the macro definition in the constants buffer.
+    constants_buffer.insert_loadable_macro(entry.name.clone(), entry);
+}
 
 //-------------------------------------------------------------------------------------------------------------------
 
-fn extract_loadable_macro_entry(_file: &CobFile, _entry: CobLoadableMacroDef, _constants_buffer: &ConstantsBuffer)
+fn extract_scene_macro_entry(file: &CobFile, mut entry: CobSceneMacroDef, constants_buffer: &mut ConstantsBuffer)
 {
+    // Resolve the def's internal values. Macro params are left in place until the macro is called.
+    if let Err(err) = constants_buffer.resolve_macro_def(|constants| entry.resolve(constants)) {
+        tracing::warn!("failed extracting scene macro entry {:?} in {:?}; error resolving internal defs: {:?}",
+            entry.name.as_str(), file, err.as_str());
+        return;
+    }
+
+    // Save the macro definition in the constants buffer.
+    constants_buffer.insert_scene_macro(entry.name.clone(), entry);
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
-fn extract_scene_macro_entry(_file: &CobFile, _entry: CobSceneMacroDef, _constants_buffer: &ConstantsBuffer) {}
+/// Removes all constant overrides and caches them in `theme_overrides`.
+///
+/// Later overrides of a constant in the same theme replace earlier ones.
+pub(super) fn extract_theme_section(section: &mut CobTheme, theme_overrides: &mut ThemeOverrides)
+{
+    for entry in section.entries.drain(..) {
+        let overrides = theme_overrides.entry(entry.name.name.clone()).or_default();
+        overrides.retain(|(theme, _)| *theme != section.name);
+        overrides.push((section.name.clone(), entry));
+    }
+}
 
 //-------------------------------------------------------------------------------------------------------------------
 
 /// Removes all definitions and caches them in appropriate buffers/maps.
-pub(super) fn extract_defs_section(file: &CobFile, section: &mut CobDefs, constants_buffer: &mut ConstantsBuffer)
+///
+/// Constants in `theme_overrides` are extracted as [`CobThemedValue`]s.
+pub(super) fn extract_defs_section(
+    file: &CobFile,
+    section: &mut CobDefs,
+    theme_overrides: &mut ThemeOverrides,
+    constants_buffer: &mut ConstantsBuffer,
+)
 {
     for entry in section.entries.drain(..) {
         match entry {
-            CobDefEntry::Constant(entry) => extract_constant_entry(file, entry, constants_buffer),
+            CobDefEntry::Constant(entry) => extract_constant_entry(file, entry, theme_overrides, constants_buffer),
             CobDefEntry::DataMacro(entry) => extract_data_macro_entry(file, entry, constants_buffer),
             CobDefEntry::LoadableMacro(entry) => extract_loadable_macro_entry(file, entry, constants_buffer),
             CobDefEntry::SceneMacro(entry) => extract_scene_macro_entry(file, entry, constants_buffer),
@@ -0,0 +1,117 @@
+use std::collections::{HashMap, HashSet};
+
+use smol_str::SmolStr;
+
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Scene roots from a file, cached so other scenes can derive from them.
+///
+/// Scenes are stored before defs are resolved, since they must be resolved with the constants of the file that
+/// contains them.
+#[derive(Default, Debug)]
+pub(crate) struct BaseScenes
+{
+    scenes: HashMap<SmolStr, CobSceneLayer>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Gets a copy of a base scene with its defs and macro calls resolved.
+fn get_base_scene<'a>(
+    base: &CobSceneBase,
+    file_scenes: &BaseScenes,
+    constants_buffer: &ConstantsBuffer,
+    base_files: &impl Fn(&ManifestKey) -> Option<(&'a BaseScenes, &'a ConstantsBuffer)>,
+) -> Result<CobSceneLayer, String>
+{
+    let (base_scenes, base_constants) = match &base.file {
+        Some(key) => {
+            base_files(key).ok_or_else(|| format!("file with manifest key {:?} is not loaded", key.as_str()))?
+        }
+        None => (file_scenes, constants_buffer),
+    };
+
+    let Some(base_layer) = base_scenes.scenes.get(base.name.as_str()) else {
+        return match &base.file {
+            Some(key) => Err(format!("there is no scene {:?} in {:?}", base.name.as_str(), key.as_str())),
+            None => Err(format!(
+                "there is no scene {:?} in the file; base scenes in the same file must be defined before the \
+                scenes that derive from them",
+                base.name.as_str()
+            )),
+        };
+    };
+
+    let mut base_layer = base_layer.clone();
+    resolve_scene_entries(&mut base_layer.entries, Some(base.name.as_str()), base_constants)?;
+    Ok(base_layer)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Collects the manifest keys of files that contain base scenes for scenes in `data`.
+///
+/// Includes scenes in every branch of `#if` blocks.
+pub(crate) fn collect_scene_base_files(data: &Cob) -> HashSet<ManifestKey>
+{
+    let mut base_files = HashSet::default();
+
+    for section in data.all_sections() {
+        let CobSection::Scenes(section) = section else { continue };
+        for cob_layer in section.scenes.iter() {
+            let Some(CobSceneBase { file: Some(key), .. }) = &cob_layer.base else { continue };
+            base_files.insert(key.clone());
+        }
+    }
+
+    base_files
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Replaces the content of derived scenes with their base scenes patched by the derived scene's entries.
+///
+/// Entries from the derived scene are appended to the base scene, then duplicates are merged so the derived
+/// scene's loadables and scene nodes override the base scene's (see [`merge_scene_layer_entries`]).
+///
+/// `base_files` looks up the scenes and constants of processed files. Returns the scenes in this file.
+pub(crate) fn extract_scene_inheritance<'a>(
+    file: &CobFile,
+    data: &mut Cob,
+    flags: &CobFlags,
+    constants_buffer: &ConstantsBuffer,
+    base_files: impl Fn(&ManifestKey) -> Option<(&'a BaseScenes, &'a ConstantsBuffer)>,
+) -> BaseScenes
+{
+    let mut file_scenes = BaseScenes::default();
+
+    for section in data.active_sections_mut(flags) {
+        let CobSection::Scenes(section) = section else { continue };
+        for cob_layer in section.scenes.iter_mut() {
+            if let Some(base) = &cob_layer.base {
+                match get_base_scene(base, &file_scenes, constants_buffer, &base_files) {
+                    Ok(base_layer) => {
+                        let mut entries = base_layer.entries;
+                        entries.append(&mut cob_layer.entries);
+                        merge_scene_layer_entries(&mut entries);
+                        cob_layer.entries = entries;
+                    }
+                    Err(err) => {
+                        tracing::warn!("failed applying base scene to scene {:?} in {:?}; {}",
+                            cob_layer.name.as_str(), file, err.as_str());
+                    }
+                }
+            }
+
+            file_scenes
+                .scenes
+                .insert(cob_layer.name.0.clone(), cob_layer.clone());
+        }
+    }
+
+    file_scenes
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -1,4 +1,7 @@
+use std::any::TypeId;
+
 use bevy::prelude::Commands;
+use bevy::reflect::serde::TypedReflectDeserializer;
 use bevy::reflect::TypeRegistry;
 
 use super::*;
@@ -6,6 +9,28 @@ use crate::prelude::*;
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Finds the [`TextLineSize`] loadable in a scene layer, which `em` values in the layer are relative to.
+///
+/// Loadables are matched by their registered type. Macros should be expanded first.
+pub(super) fn find_text_line_size(
+    cob_layer: &CobSceneLayer,
+    loadables: &LoadableRegistry,
+    constants_buffer: &ConstantsBuffer,
+) -> Option<CobLoadable>
+{
+    cob_layer.entries.iter().find_map(|entry| {
+        let CobSceneLayerEntry::Loadable(loadable) = entry else { return None };
+        if loadables.get_type_id(loadable.id.to_canonical(None)) != Some(TypeId::of::<TextLineSize>()) {
+            return None;
+        }
+        let mut loadable = loadable.clone();
+        loadable.resolve(constants_buffer).ok()?;
+        Some(loadable)
+    })
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 fn handle_loadable(
     id_scratch: String,
     seen_shortnames: &mut Vec<&'static str>,
@@ -16,13 +41,20 @@ fn handle_loadable(
     loadable: &mut CobLoadable,
     loadables: &LoadableRegistry,
     constants_buffer: &ConstantsBuffer,
+    text_size: Option<&CobLoadable>,
 ) -> String
 {
     // Get the loadable's longname.
     let id_scratch = loadable.id.to_canonical(Some(id_scratch));
-    let Some((short_name, long_name, type_id, deserializer)) =
-        get_loadable_meta(type_registry, file, current_path, id_scratch.as_str(), loadables)
-    else {
+    let Some((short_name, long_name, type_id, deserializer)) = get_loadable_meta(
+        type_registry,
+        file,
+        current_path,
+        id_scratch.as_str(),
+        loadables,
+        // Unregistered loadables are reported by `validate` when the file is loaded.
+        None,
+    ) else {
         return id_scratch;
     };
 
@@ -40,29 +72,201 @@ fn handle_loadable(
         return id_scratch;
     }
 
-    // Get the loadable's value.
-    let loadable_value = get_loadable_value(deserializer, loadable);
-
     // Save this loadable.
     let loadable_index = seen_shortnames.len();
-    seen_shortnames.push(short_name);
-
-    scene_buffer.insert_loadable(
-        &SceneRef {
-            file: SceneFile::File(file.clone()),
-            path: current_path.clone(),
-        },
-        Some(loadable_index),
-        loadable_value,
-        type_id,
+    if insert_scene_loadable(
+        scene_buffer,
+        file,
+        current_path,
+        loadable_index,
+        short_name,
         long_name,
-    );
+        type_id,
+        deserializer,
+        loadable,
+        text_size,
+    ) {
+        seen_shortnames.push(short_name);
+    }
 
     id_scratch
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Resolves a scene loadable's themed constants and `rem`/`em` values, then inserts it into the scene buffer.
+///
+/// The loadable's defs should already be resolved. Returns `false` if the loadable could not be inserted.
+pub(super) fn insert_scene_loadable(
+    scene_buffer: &mut SceneBuffer,
+    file: &CobFile,
+    current_path: &ScenePath,
+    loadable_index: usize,
+    short_name: &'static str,
+    long_name: &'static str,
+    type_id: TypeId,
+    deserializer: TypedReflectDeserializer,
+    loadable: &mut CobLoadable,
+    text_size: Option<&CobLoadable>,
+) -> bool
+{
+    // Resolve themed constants.
+    let unresolved = loadable.clone();
+    let has_themes = match loadable.resolve_theme(scene_buffer.active_theme()) {
+        Ok(has_themes) => has_themes,
+        Err(err) => {
+            tracing::warn!("failed extracting loadable {:?} at {:?} in {:?}; error resolving theme: {:?}",
+                short_name, current_path, file, err.as_str());
+            return false;
+        }
+    };
+
+    // Resolve rem/em values.
+    let has_font_units = match loadable.resolve_font_units(&scene_buffer.font_sizes(text_size)) {
+        Ok(has_font_units) => has_font_units,
+        Err(err) => {
+            tracing::warn!("failed extracting loadable {:?} at {:?} in {:?}; error resolving rem/em values: {:?}",
+                short_name, current_path, file, err.as_str());
+            return false;
+        }
+    };
+
+    // Get the loadable's value.
+    let loadable_value = get_loadable_value(deserializer, loadable);
+
+    // Save this loadable.
+    let scene_ref = SceneRef {
+        file: SceneFile::File(file.clone()),
+        path: current_path.clone(),
+    };
+    if has_themes || has_font_units {
+        scene_buffer.track_dynamic_loadable(&scene_ref, text_size, loadable_index, type_id, long_name, unresolved);
+    }
+    scene_buffer.insert_loadable(&scene_ref, Some(loadable_index), loadable_value, type_id, long_name);
+
+    true
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Expands macro calls in a scene layer into ordinary loadables and scene nodes.
+///
+/// If any macros are expanded, then duplicate loadables and scene nodes in the layer are merged so later entries
+/// override earlier ones.
+///
+/// If a macro's definition changes, then this file will be re-extracted and the SceneBuffer/SceneLoader will
+/// refresh any nodes that changed.
+pub(super) fn expand_scene_layer_macros(
+    file: &CobFile,
+    current_path: &ScenePath,
+    cob_layer: &mut CobSceneLayer,
+    constants_buffer: &ConstantsBuffer,
+)
+{
+    let mut expanded = false;
+    let mut idx = 0;
+    while idx < cob_layer.entries.len() {
+        let new_entries: Vec<CobSceneLayerEntry> = match &mut cob_layer.entries[idx] {
+            CobSceneLayerEntry::Loadable(_)
+            | CobSceneLayerEntry::Layer(_)
+            | CobSceneLayerEntry::LoadableRemoval(_) => {
+                idx += 1;
+                continue;
+            }
+            CobSceneLayerEntry::LoadableMacroCall(call) => match call.expand(constants_buffer) {
+                Ok(loadables) => loadables
+                    .into_iter()
+                    .map(CobSceneLayerEntry::Loadable)
+                    .collect(),
+                Err(err) => {
+                    tracing::warn!("failed expanding loadable macro call {:?} at {:?} in {:?}; {}",
+                        call.path.as_str(), current_path, file, err.as_str());
+                    vec![]
+                }
+            },
+            CobSceneLayerEntry::SceneMacroCall(call) => match call.expand(constants_buffer) {
+                Ok(entries) => entries,
+                Err(err) => {
+                    tracing::warn!("failed expanding scene macro call {:?} at {:?} in {:?}; {}",
+                        call.path.as_str(), current_path, file, err.as_str());
+                    vec![]
+                }
+            },
+            CobSceneLayerEntry::SceneMacroParam(_) => {
+                tracing::warn!("ignoring scene macro param at {:?} in {:?}; scene macro params are only allowed \
+                    in scene macro definitions", current_path, file);
+                vec![]
+            }
+        };
+
+        // Note: we don't advance past the new entries, since content inserted from scene macro params may
+        // contain more macro calls.
+        cob_layer.entries.splice(idx..=idx, new_entries);
+        expanded = true;
+    }
+
+    if expanded {
+        merge_scene_layer_entries(&mut cob_layer.entries);
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Inserts a scene node into its parent's layer and notifies the scene loader if the scene's structure changed.
+///
+/// Returns the node's layer.
+pub(super) fn insert_scene_node<'a>(
+    _c: &mut Commands,
+    _scene_loader: &mut SceneLoader,
+    scene_layer: &'a mut SceneLayer,
+    _scene: &SceneRef,
+    _parent_path: &ScenePath,
+    node_path: &ScenePath,
+) -> &'a mut SceneLayer
+{
+    match scene_layer.insert(node_path) {
+        #[cfg(feature = "hot_reload")]
+        SceneLayerInsertionResult::NoChange(child_layer) => child_layer,
+        #[cfg(feature = "hot_reload")]
+        SceneLayerInsertionResult::Updated(index, child_layer) => {
+            _scene_loader.handle_rearranged_scene_node(_c, _scene, _parent_path, node_path, index);
+            child_layer
+        }
+        SceneLayerInsertionResult::Added(_index, child_layer) => {
+            #[cfg(feature = "hot_reload")]
+            {
+                _scene_loader.handle_inserted_scene_node(_c, _scene, _parent_path, node_path, _index);
+            }
+            child_layer
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Ends a scene layer update and cleans up scene nodes that were removed from the layer.
+pub(super) fn end_scene_layer_update(
+    _c: &mut Commands,
+    _scene_loader: &mut SceneLoader,
+    scene_layer: &mut SceneLayer,
+    scene: &SceneRef,
+)
+{
+    for SceneLayerData { id, .. } in scene_layer.end_update() {
+        #[cfg(feature = "hot_reload")]
+        {
+            _scene_loader.cleanup_deleted_scene_node(_c, scene, &id);
+        }
+        #[cfg(not(feature = "hot_reload"))]
+        {
+            tracing::error!("scene node {:?} unexpectedly removed from {:?} while parsing scene (this is a bug)",
+                id, scene);
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 fn handle_scene_node(
     mut id_scratch: String,
     seen_shortnames: &mut Vec<&'static str>,
@@ -96,22 +300,7 @@ fn handle_scene_node(
     };
 
     // Save this node in the scene.
-    let child_layer = match scene_layer.insert(&node_path) {
-        #[cfg(feature = "hot_reload")]
-        SceneLayerInsertionResult::NoChange(child_layer) => child_layer,
-        #[cfg(feature = "hot_reload")]
-        SceneLayerInsertionResult::Updated(index, child_layer) => {
-            scene_loader.handle_rearranged_scene_node(c, scene, parent_path, &node_path, index);
-            child_layer
-        }
-        SceneLayerInsertionResult::Added(_index, child_layer) => {
-            #[cfg(feature = "hot_reload")]
-            {
-                scene_loader.handle_inserted_scene_node(c, scene, parent_path, &node_path, _index);
-            }
-            child_layer
-        }
-    };
+    let child_layer = insert_scene_node(c, scene_loader, scene_layer, scene, parent_path, &node_path);
 
     // Parse the child layer of this node.
     extract_scene_layer(
@@ -147,6 +336,14 @@ fn extract_scene_layer(
     constants_buffer: &ConstantsBuffer,
 ) -> String
 {
+    let file = scene
+        .file
+        .file()
+        .expect("all SceneFile should contain CobFile in scene extraction");
+
+    // Expand macros so the layer only contains loadables and scene nodes.
+    expand_scene_layer_macros(file, current_path, cob_layer, constants_buffer);
+
     // Prep the node.
     let scene_location = SceneRef { file: scene.file.clone(), path: current_path.clone() };
     scene_buffer.prepare_scene_node(scene_location.clone());
@@ -156,6 +353,7 @@ fn extract_scene_layer(
 
     // Add loadables.
     seen_shortnames.clear();
+    let text_size = find_text_line_size(cob_layer, loadables, constants_buffer);
 
     for entry in cob_layer.entries.iter_mut() {
         match entry {
@@ -165,30 +363,22 @@ fn extract_scene_layer(
                     seen_shortnames,
                     type_registry,
                     scene_buffer,
-                    scene
-                        .file
-                        .file()
-                        .expect("all SceneFile should contain CobFile in scene extraction"),
+                    file,
                     current_path,
                     loadable,
                     loadables,
                     constants_buffer,
+                    text_size.as_ref(),
                 );
             }
             // Do this one after we are done using the `seen_shortnames` buffer.
             CobSceneLayerEntry::Layer(_) => (),
-            CobSceneLayerEntry::LoadableMacroCall(_) => {
-                // TODO: resolve
-                tracing::warn!("ignoring loadable macro call in scene node {:?} in {:?}", current_path, scene.file);
-            }
-            CobSceneLayerEntry::SceneMacroCall(_) => {
-                // TODO: resolve
-                tracing::warn!("ignoring scene macro call in scene node {:?} in {:?}", current_path, scene.file);
-            }
-            CobSceneLayerEntry::SceneMacroParam(_) => {
-                // TODO: these are not allowed to appear here, they should already be resolved
-                tracing::warn!("ignoring scene macro param in scene node {:?} in {:?}", current_path, scene.file);
-            }
+            // Macros were expanded above.
+            CobSceneLayerEntry::LoadableMacroCall(_)
+            | CobSceneLayerEntry::SceneMacroCall(_)
+            | CobSceneLayerEntry::SceneMacroParam(_) => (),
+            // Removals were applied when merging the layer with its base scene.
+            CobSceneLayerEntry::LoadableRemoval(_) => (),
         }
     }
 
@@ -220,25 +410,14 @@ fn extract_scene_layer(
         }
     }
 
-    // End layer update and handle removed nodes.
-    for SceneLayerData { id, .. } in scene_layer.end_update() {
-        #[cfg(feature = "hot_reload")]
-        {
-            scene_loader.cleanup_deleted_scene_node(c, scene, &id);
-        }
-        #[cfg(not(feature = "hot_reload"))]
-        {
-            tracing::error!("scene node {:?} unexpectedly removed from {:?} while parsing scene (this is a bug)",
-                id, scene);
-        }
-    }
+    end_scene_layer_update(c, scene_loader, scene_layer, scene);
 
     id_scratch
 }
 
 //-------------------------------------------------------------------------------------------------------------------
 
-// TODO: disallow duplicate node names, excluding anonymous nodes
+/// Duplicate node names are reported by [`validate`] when the file is loaded.
 pub(super) fn extract_scenes(
     type_registry: &TypeRegistry,
     c: &mut Commands,
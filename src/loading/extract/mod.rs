@@ -1,17 +1,23 @@
 mod cob_extract;
+mod compile_cob;
 mod extract_commands;
+mod extract_compiled;
 mod extract_defs;
 mod extract_import;
 mod extract_manifest;
+mod extract_scene_inheritance;
 mod extract_scenes;
 mod reflected_loadable;
 mod utils;
 
 pub(crate) use cob_extract::*;
+pub(crate) use compile_cob::*;
 pub(self) use extract_commands::*;
+pub(crate) use extract_compiled::*;
 pub(self) use extract_defs::*;
 pub(self) use extract_import::*;
 pub(self) use extract_manifest::*;
+pub(crate) use extract_scene_inheritance::*;
 pub(self) use extract_scenes::*;
 pub(crate) use reflected_loadable::*;
-pub(self) use utils::*;
+pub(crate) use utils::*;
@@ -26,6 +26,19 @@ pub(crate) enum ReflectedLoadable
 
 impl ReflectedLoadable
 {
+    /// Gets the type path of the loadable's value, if it was deserialized.
+    #[cfg(feature = "hot_reload")]
+    pub(crate) fn type_path(&self) -> &str
+    {
+        match self {
+            Self::Value(loadable) => loadable
+                .get_represented_type_info()
+                .map(|info| info.type_path())
+                .unwrap_or_else(|| loadable.reflect_type_path()),
+            Self::DeserializationFailed(_) => "(failed deserializing)",
+        }
+    }
+
     pub(crate) fn equals(&self, other: &ReflectedLoadable) -> Option<bool>
     {
         let (Self::Value(this), Self::Value(other)) = (self, other) else {
@@ -9,21 +9,37 @@ use crate::prelude::*;
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Looks up a loadable by its short name.
+///
+/// Unregistered loadables are reported to `diagnostics` if it is provided.
 pub(super) fn get_loadable_meta<'a>(
     type_registry: &'a TypeRegistry,
     file: &CobFile,
     current_path: &ScenePath,
     short_name: &str,
     loadables: &LoadableRegistry,
+    diagnostics: Option<&CobDiagnostics>,
 ) -> Option<(&'static str, &'static str, TypeId, TypedReflectDeserializer<'a>)>
 {
     // Look up the registration.
     let registration = match loadables.get_type_id(short_name) {
         Some(type_id) => type_registry.get(type_id),
         None => {
+            let Some(diagnostics) = diagnostics else { return None };
+            let hint = loadables
+                .find_similar(short_name)
+                .map(|similar| format!("did you mean `{}`?", similar));
             tracing::warn!("failed getting type id for loadable {} at {:?} in {:?}; no loadable with this name was \
-                registered in the app",
-                short_name, current_path, file);
+                registered in the app{}",
+                short_name, current_path, file, hint.as_ref().map(|h| format!("; {}", h)).unwrap_or_default());
+
+            let mut diagnostic = CobDiagnostic::new_for_scene(
+                file.as_str(),
+                current_path.clone(),
+                format!("no loadable named `{}` was registered in the app", short_name),
+            );
+            diagnostic.hint = hint;
+            diagnostics.add(diagnostic);
             return None;
         }
     };
@@ -47,7 +63,37 @@ pub(super) fn get_loadable_meta<'a>(
 
 //-------------------------------------------------------------------------------------------------------------------
 
-pub(super) fn get_loadable_value(deserializer: TypedReflectDeserializer, value: &CobLoadable)
+/// Looks up a loadable by the type path it was bound to by the [`CobCompiler`].
+pub(super) fn get_bound_loadable_meta<'a>(
+    type_registry: &'a TypeRegistry,
+    file: &CobFile,
+    current_path: &ScenePath,
+    type_path: &str,
+    diagnostics: &CobDiagnostics,
+) -> Option<(&'static str, &'static str, TypeId, TypedReflectDeserializer<'a>)>
+{
+    let Some(registration) = type_registry.get_with_type_path(type_path) else {
+        tracing::warn!("failed getting type registration for compiled loadable {} at {:?} in {:?}; type was not \
+            registered in the app (the compiled file may be out of date)",
+            type_path, current_path, file);
+        diagnostics.add(CobDiagnostic::new_for_scene(
+            file.as_str(),
+            current_path.clone(),
+            format!("compiled loadable `{}` is not registered in the app", type_path),
+        ));
+        return None;
+    };
+
+    let short_name = registration.type_info().type_path_table().short_path();
+    let long_name = registration.type_info().type_path_table().path();
+    let deserializer = TypedReflectDeserializer::new(registration, type_registry);
+
+    Some((short_name, long_name, registration.type_info().type_id(), deserializer))
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+pub(crate) fn get_loadable_value(deserializer: TypedReflectDeserializer, value: &CobLoadable)
     -> ReflectedLoadable
 {
     match deserializer.deserialize(value) {
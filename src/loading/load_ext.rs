@@ -1,10 +1,11 @@
 use std::any::TypeId;
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard};
 
 use bevy::ecs::system::EntityCommands;
 use bevy::ecs::world::Command;
 use bevy::prelude::*;
-use bevy::reflect::GetTypeRegistration;
+use bevy::reflect::{GetTypeRegistration, PartialReflect};
 use bevy_cobweb::prelude::*;
 
 use crate::prelude::*;
@@ -22,7 +23,17 @@ fn validate_loadable_name(shortname: &'static str) -> bool
 
 //-------------------------------------------------------------------------------------------------------------------
 
-fn register_loadable_type<T: Loadable>(app: &mut App) -> Option<(&mut LoadableRegistry, TypeId)>
+fn make_default_loadable<T: Loadable>() -> Box<dyn PartialReflect>
+{
+    Box::new(T::default())
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn register_loadable_type<T: Loadable>(
+    app: &mut App,
+    kind: &'static str,
+) -> Option<(&mut LoadableRegistry, TypeId)>
 {
     // Look up canonical short name.
     let type_id = TypeId::of::<T>();
@@ -44,12 +55,21 @@ fn register_loadable_type<T: Loadable>(app: &mut App) -> Option<(&mut LoadableRe
         .world_mut()
         .get_resource_or_insert_with::<LoadableRegistry>(|| Default::default());
 
-    if let Some(prev) = loadables.loadables.insert(shortname, type_id) {
+    let prev = loadables
+        .loadables
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(shortname, type_id);
+    if let Some(prev) = prev {
         if prev != type_id {
             tracing::warn!("overwriting command loadable registration; new type id: {:?}, old type id: {:?}",
                 type_id, prev);
         }
     }
+    loadables.registrations.insert(
+        type_id,
+        LoadableRegistration { kind, make_default: make_default_loadable::<T> },
+    );
 
     Some((loadables.into_inner(), type_id))
 }
@@ -59,7 +79,7 @@ fn register_loadable_type<T: Loadable>(app: &mut App) -> Option<(&mut LoadableRe
 fn register_command_loadable<T: Command + Loadable>(app: &mut App)
 {
     // Register type.
-    let Some((loadables, type_id)) = register_loadable_type::<T>(app) else { return };
+    let Some((loadables, type_id)) = register_loadable_type::<T>(app, "command") else { return };
 
     // Add callback entry.
     let entry = loadables.command_callbacks.entry(type_id);
@@ -80,7 +100,7 @@ fn register_node_loadable<T: Loadable + 'static>(
 )
 {
     // Register type.
-    let Some((loadables, type_id)) = register_loadable_type::<T>(app) else { return };
+    let Some((loadables, type_id)) = register_loadable_type::<T>(app, register_type) else { return };
 
     // Applier callback.
     let entry = loadables.node_callbacks.entry(type_id);
@@ -233,11 +253,29 @@ pub(crate) fn load_queued_from_ref(
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// How a loadable type was registered.
+#[derive(Copy, Clone)]
+pub(crate) struct LoadableRegistration
+{
+    /// `component`, `bundle`, `reactive`, `instruction`, or `command`.
+    pub(crate) kind: &'static str,
+    pub(crate) make_default: fn() -> Box<dyn PartialReflect>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Resource that tracks loadable types registered with [`CobLoadableRegistrationAppExt`].
+///
+/// Used to [`validate`] COB files.
 #[derive(Resource, Default)]
-pub(crate) struct LoadableRegistry
+pub struct LoadableRegistry
 {
     /// [ short name : type id ]
-    loadables: HashMap<&'static str, TypeId>,
+    ///
+    /// Shared with the asset loader so files can be validated when they are loaded.
+    loadables: Arc<RwLock<HashMap<&'static str, TypeId>>>,
+    /// Used to [`export_loadable_schema`].
+    registrations: HashMap<TypeId, LoadableRegistration>,
 
     command_callbacks: HashMap<TypeId, fn(&mut World, ReflectedLoadable, SceneRef)>,
     node_callbacks: HashMap<TypeId, fn(&mut World, Entity, ReflectedLoadable, SceneRef)>,
@@ -266,9 +304,42 @@ impl LoadableRegistry
         self.revert_callbacks.get(&type_id).cloned()
     }
 
+    /// Makes a registry that shares loadable names with this registry but has no callbacks.
+    pub(crate) fn share_names(&self) -> Self
+    {
+        Self { loadables: self.loadables.clone(), ..Default::default() }
+    }
+
+    fn names(&self) -> RwLockReadGuard<HashMap<&'static str, TypeId>>
+    {
+        self.loadables
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
     pub(crate) fn get_type_id(&self, id: impl AsRef<str>) -> Option<TypeId>
     {
-        self.loadables.get(id.as_ref()).copied()
+        self.names().get(id.as_ref()).copied()
+    }
+
+    /// Gets all registered loadables with their registrations, sorted by name.
+    ///
+    /// Registrations are not available in registries made with [`Self::share_names`].
+    pub(crate) fn registered(&self) -> Vec<(&'static str, TypeId, Option<LoadableRegistration>)>
+    {
+        let mut registered: Vec<_> = self
+            .names()
+            .iter()
+            .map(|(name, type_id)| (*name, *type_id, self.registrations.get(type_id).copied()))
+            .collect();
+        registered.sort_by_key(|(name, ..)| *name);
+        registered
+    }
+
+    /// Finds the registered loadable name most similar to `id`, for "did you mean" hints.
+    pub(crate) fn find_similar(&self, id: impl AsRef<str>) -> Option<&'static str>
+    {
+        find_similar_name(id.as_ref(), self.names().keys().copied())
     }
 }
 
@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use bevy::ecs::world::Command;
+use bevy::prelude::*;
+use bevy::reflect::serde::TypedReflectSerializer;
+use bevy::reflect::{NamedField, PartialReflect, ReflectRef, TypeInfo, TypeRegistry, UnnamedField, VariantInfo};
+use serde::Serialize;
+
+use crate::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Serialize)]
+struct FieldSchema
+{
+    /// Tuple fields are named by index.
+    name: String,
+    #[serde(rename = "type")]
+    type_path: &'static str,
+    /// The field's value in the loadable's `Default` implementation, written as COB.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<String>,
+}
+
+#[derive(Serialize)]
+struct VariantSchema
+{
+    name: &'static str,
+    fields: Vec<FieldSchema>,
+}
+
+#[derive(Serialize)]
+struct LoadableSchema
+{
+    kind: &'static str,
+    type_path: &'static str,
+    fields: Vec<FieldSchema>,
+    variants: Vec<VariantSchema>,
+}
+
+#[derive(Serialize)]
+struct CobSchema
+{
+    loadables: BTreeMap<&'static str, LoadableSchema>,
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Writes a reflected value as COB.
+fn write_default(value: Option<&dyn PartialReflect>, registry: &TypeRegistry) -> Option<String>
+{
+    let value = TypedReflectSerializer::new(value?, registry)
+        .serialize(CobValueSerializer)
+        .ok()?;
+    let mut buff = Vec::<u8>::default();
+    value
+        .write_to(&mut DefaultRawSerializer::new(&mut buff))
+        .ok()?;
+    String::from_utf8(buff).ok()
+}
+
+fn named_fields<'a>(
+    fields: impl Iterator<Item = &'a NamedField>,
+    default: Option<&dyn PartialReflect>,
+    registry: &TypeRegistry,
+) -> Vec<FieldSchema>
+{
+    let default = default.and_then(|value| match value.reflect_ref() {
+        ReflectRef::Struct(value) => Some(value),
+        _ => None,
+    });
+    fields
+        .map(|field| FieldSchema {
+            name: String::from(field.name()),
+            type_path: field.type_path(),
+            default: write_default(default.and_then(|value| value.field(field.name())), registry),
+        })
+        .collect()
+}
+
+fn unnamed_fields<'a>(
+    fields: impl Iterator<Item = &'a UnnamedField>,
+    default: Option<&dyn PartialReflect>,
+    registry: &TypeRegistry,
+) -> Vec<FieldSchema>
+{
+    let default = default.and_then(|value| match value.reflect_ref() {
+        ReflectRef::TupleStruct(value) => Some(value),
+        _ => None,
+    });
+    fields
+        .map(|field| FieldSchema {
+            name: field.index().to_string(),
+            type_path: field.type_path(),
+            default: write_default(default.and_then(|value| value.field(field.index())), registry),
+        })
+        .collect()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Exports the shapes of all registered loadables as JSON.
+///
+/// For each loadable name the schema contains:
+/// - `kind`: How the loadable was registered (`component`, `bundle`, `reactive`, `instruction`, or `command`).
+/// - `type_path`: The full type path.
+/// - `fields`: The `name`, `type` path, and `default` value of each struct field. Tuple fields are named by index.
+///   Defaults are taken from the loadable's `Default` implementation and written as COB.
+/// - `variants`: The `name` and `fields` of each enum variant.
+///
+/// External tools like the `cob-lsp` language server can use the schema to check COB files without running the
+/// app. See [`ExportLoadableSchema`] for writing the schema to a file.
+pub fn export_loadable_schema(type_registry: &TypeRegistry, loadables: &LoadableRegistry) -> String
+{
+    let mut schema = CobSchema { loadables: BTreeMap::default() };
+
+    for (name, type_id, registration) in loadables.registered() {
+        let Some(type_info) = type_registry.get_type_info(type_id) else { continue };
+        let default = registration.map(|registration| (registration.make_default)());
+        let default = default.as_deref();
+
+        let (fields, variants) = match type_info {
+            TypeInfo::Struct(info) => (named_fields(info.iter(), default, type_registry), vec![]),
+            TypeInfo::TupleStruct(info) => (unnamed_fields(info.iter(), default, type_registry), vec![]),
+            TypeInfo::Enum(info) => {
+                let variants = info
+                    .iter()
+                    .map(|variant| VariantSchema {
+                        name: variant.name(),
+                        fields: match variant {
+                            VariantInfo::Struct(info) => named_fields(info.iter(), None, type_registry),
+                            VariantInfo::Tuple(info) => unnamed_fields(info.iter(), None, type_registry),
+                            VariantInfo::Unit(_) => vec![],
+                        },
+                    })
+                    .collect();
+                (vec![], variants)
+            }
+            _ => (vec![], vec![]),
+        };
+
+        let kind = registration
+            .map(|registration| registration.kind)
+            .unwrap_or_default();
+        let type_path = type_info.type_path();
+        schema
+            .loadables
+            .insert(name, LoadableSchema { kind, type_path, fields, variants });
+    }
+
+    serde_json::to_string_pretty(&schema).expect("loadable schemas should always serialize")
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Command that writes [`export_loadable_schema`] to a file.
+///
+/// ```ignore
+/// app.add_systems(Startup, |mut c: Commands| c.queue(ExportLoadableSchema::new("cob_schema.json")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExportLoadableSchema
+{
+    pub path: PathBuf,
+}
+
+impl ExportLoadableSchema
+{
+    pub fn new(path: impl Into<PathBuf>) -> Self
+    {
+        Self { path: path.into() }
+    }
+}
+
+impl Command for ExportLoadableSchema
+{
+    fn apply(self, world: &mut World)
+    {
+        let schema = {
+            let type_registry = world.resource::<AppTypeRegistry>().read();
+            export_loadable_schema(&type_registry, world.resource::<LoadableRegistry>())
+        };
+
+        match std::fs::write(&self.path, schema) {
+            Ok(()) => tracing::info!("exported loadable schema to {}", self.path.display()),
+            Err(err) => tracing::error!("failed exporting loadable schema to {}: {}", self.path.display(), err),
+        }
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
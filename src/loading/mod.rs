@@ -3,27 +3,45 @@
 #[allow(unused_imports)]
 use crate as bevy_cobweb_ui;
 
+mod active_theme;
 mod app_load_ext;
 mod cache;
 pub mod cob;
 mod cob_asset_loader;
+mod cob_diagnostics;
+mod cob_flags;
+mod cob_index;
+mod cob_resolver;
+mod cob_validate;
+mod compiled;
 mod extract;
 mod load_ext;
 mod load_progress;
 mod loadable;
+mod loadable_schema;
 mod plugin;
 mod references;
+mod root_font_size;
 mod scene;
 
+pub use active_theme::*;
 pub use app_load_ext::*;
 pub use cache::*;
 pub use cob::Cob;
 pub(crate) use cob::*;
 pub(crate) use cob_asset_loader::*;
+pub use cob_diagnostics::*;
+pub use cob_flags::*;
+pub use cob_index::*;
+pub use cob_resolver::*;
+pub use cob_validate::*;
+pub use compiled::*;
 pub(crate) use extract::*;
 pub use load_ext::*;
 pub use load_progress::*;
 pub use loadable::*;
+pub use loadable_schema::*;
 pub(crate) use plugin::*;
 pub use references::*;
+pub use root_font_size::*;
 pub use scene::*;
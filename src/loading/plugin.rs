@@ -11,6 +11,7 @@ impl Plugin for LoadingPlugin
     fn build(&self, app: &mut App)
     {
         app.add_plugins(LoadProgressPlugin)
+            .add_plugins(CobIndexPlugin)
             .add_plugins(LoadExtPlugin)
             .add_plugins(CobAssetLoaderPlugin)
             .add_plugins(AppLoadExtPlugin)
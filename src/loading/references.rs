@@ -213,6 +213,15 @@ impl ScenePath
         self.path.len()
     }
 
+    /// Gets the path of this path's parent node.
+    ///
+    /// Returns `None` if the path is empty.
+    pub fn parent(&self) -> Option<Self>
+    {
+        let (_, parent) = self.path.split_last()?;
+        Some(Self { path: Arc::from(parent) })
+    }
+
     /// Iterates the path's segments.
     pub fn iter(&self) -> impl Iterator<Item = &str> + Clone + DoubleEndedIterator
     {
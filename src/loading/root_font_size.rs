@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Resource with the font size that `rem` values in COB files are relative to.
+///
+/// `em` values are relative to their scene node's `TextLineSize`, or to this size if the node doesn't have one.
+///
+/// Changing this re-resolves all `rem` and `em` values in loaded scene nodes, and updates the entities they were
+/// loaded into. This can be used to scale text and spacing together, e.g. for accessibility settings.
+///
+/// Defaults to `16.0`.
+///
+/// ```ignore
+/// app.world_mut().resource_mut::<RootFontSize>().0 = 20.;
+/// ```
+#[derive(Resource, Debug, Copy, Clone, PartialEq)]
+pub struct RootFontSize(pub f32);
+
+impl Default for RootFontSize
+{
+    fn default() -> Self
+    {
+        Self(16.)
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
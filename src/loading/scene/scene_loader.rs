@@ -338,14 +338,25 @@ pub struct SceneLoader
     /// Used to update scene structures (add/remove/rearrange entities) in response to hot reloaded changes.
     #[cfg(feature = "hot_reload")]
     scene_instances: HashMap<SceneRef, SmallVec<[SceneInstance; 1]>>,
+    /// Records scene structure changes for [`CobReloadReport`]s.
+    #[cfg(feature = "hot_reload")]
+    recorder: ReloadRecorder,
 }
 
 impl SceneLoader
 {
     /// Makes a new scene loader from a shared manifest map.
-    pub(crate) fn new(manifest_map: Arc<Mutex<ManifestMap>>) -> Self
+    pub(crate) fn new(
+        manifest_map: Arc<Mutex<ManifestMap>>,
+        #[cfg(feature = "hot_reload")] recorder: ReloadRecorder,
+    ) -> Self
     {
-        Self { manifest_map, ..default() }
+        Self {
+            manifest_map,
+            #[cfg(feature = "hot_reload")]
+            recorder,
+            ..default()
+        }
     }
 
     fn manifest_map(&self) -> MutexGuard<ManifestMap>
@@ -384,6 +395,11 @@ impl SceneLoader
     {
         // Look up scene.
         let Some(scene_instances) = self.scene_instances.get_mut(scene) else { return };
+        self.recorder.record(|report| {
+            report
+                .nodes_added
+                .push(SceneRef { file: scene.file.clone(), path: inserted.clone() })
+        });
 
         // Update each instance.
         for scene_instance in scene_instances.iter_mut() {
@@ -431,6 +447,8 @@ impl SceneLoader
 
             // Save the entity.
             scene_instance.insert(inserted.clone(), node_entity);
+            self.recorder
+                .record(|report| report.add_entity(node_entity));
         }
     }
 
@@ -451,6 +469,11 @@ impl SceneLoader
     {
         // Look up scene.
         let Some(scene_instances) = self.scene_instances.get(scene) else { return };
+        self.recorder.record(|report| {
+            report
+                .nodes_moved
+                .push(SceneRef { file: scene.file.clone(), path: moved.clone() })
+        });
 
         // Update each instance.
         for scene_instance in scene_instances.iter() {
@@ -495,6 +518,11 @@ impl SceneLoader
     {
         // Look up scene.
         let Some(scene_instances) = self.scene_instances.get_mut(scene) else { return };
+        self.recorder.record(|report| {
+            report
+                .nodes_removed
+                .push(SceneRef { file: scene.file.clone(), path: deleted.clone() })
+        });
 
         // Update each instance.
         for scene_instance in scene_instances.iter_mut() {
@@ -513,6 +541,8 @@ impl SceneLoader
             };
 
             ec.despawn_recursive();
+            self.recorder
+                .record(|report| report.add_entity(node_entity));
         }
     }
 
@@ -708,7 +738,11 @@ impl Plugin for SceneLoaderPlugin
     fn build(&self, app: &mut App)
     {
         let manifest_map = app.world().resource::<CobAssetCache>().manifest_map_clone();
-        app.insert_resource(SceneLoader::new(manifest_map));
+        app.insert_resource(SceneLoader::new(
+            manifest_map,
+            #[cfg(feature = "hot_reload")]
+            app.world().resource::<ReloadRecorder>().clone(),
+        ));
     }
 }
 
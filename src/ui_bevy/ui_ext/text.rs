@@ -1,8 +1,12 @@
+use std::fmt::Formatter;
+
 use bevy::prelude::*;
 use bevy::text::{ComputedTextBlock, LineBreak};
 use bevy::ui::widget::TextNodeFlags;
 use bevy::ui::ContentSize;
 use bevy_cobweb::prelude::*;
+use serde::de::Visitor;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::prelude::*;
 
@@ -13,7 +17,7 @@ const TEXT_LINE_DEFAULT_TEXT: &str = "[[text line]]";
 //-------------------------------------------------------------------------------------------------------------------
 
 fn insert_text_line(
-    In((entity, mut line)): In<(Entity, TextLine)>,
+    In((entity, line)): In<(Entity, TextLine)>,
     mut commands: Commands,
     localizer: Res<TextLocalizer>,
     font_map: Res<FontMap>,
@@ -34,23 +38,153 @@ fn insert_text_line(
     // - We need to manually localize inserted text in case the text line is hot reloaded into an entity that
     //   already has Text (i.e. because auto-localization won't occur).
     // TODO: future localization rework should make this no longer necessary
-    if line.text.as_str() != TEXT_LINE_DEFAULT_TEXT {
+    let is_localized = line.text.is_localized();
+    let has_localized = localized.contains(entity);
+    let mut text = line.text.into_string();
+    if text.as_str() != TEXT_LINE_DEFAULT_TEXT {
         if let Ok(mut localized) = localized.get_mut(entity) {
-            localized.set_localization(line.text.as_str());
+            localized.set_localization(text.as_str());
             //todo: what happens if line.font is None? it should use bevy's default font
             localized.localization_mut().set_font_backup(font.clone());
-            localized.localize(&localizer, &font_map, &mut line.text, &mut font);
+            localized.localize(&localizer, &font_map, &mut text, &mut font);
         }
     }
 
     // Add text to entity.
     let Some(mut ec) = commands.get_entity(entity) else { return };
     ec.try_insert((
-        Text(line.text),
+        Text(text),
         TextLayout { justify: line.justify, linebreak: line.linebreak },
         TextFont { font, font_size: line.size, ..default() },
         TextColor(color),
     ));
+
+    // Localized text is auto-localized when `LocalizedText` is inserted.
+    if is_localized && !has_localized {
+        ec.try_insert(LocalizedText::default());
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Text for a [`TextLine`].
+///
+/// Plain strings deserialize to [`Self::Text`], and `loc()` values in COB files deserialize to
+/// [`Self::Localized`] (e.g. `TextLine{ text: loc("menu-play" count: $n) }`).
+#[derive(Reflect, Debug, Clone, PartialEq, Eq)]
+#[reflect(opaque, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TextContent
+{
+    /// Text that is used as-is.
+    ///
+    /// It will still be localized if the entity has [`LocalizedText`].
+    Text(String),
+    /// A localization template like `"menu-play?count=3"`. See [`fluent_content::Request`] for the syntax.
+    ///
+    /// [`LocalizedText`] will be inserted on the entity so the text is localized, and re-localized when
+    /// [`RelocalizeApp`] is broadcast.
+    Localized(String),
+}
+
+impl TextContent
+{
+    /// Makes localized text from a localization template.
+    pub fn localized(template: impl Into<String>) -> Self
+    {
+        Self::Localized(template.into())
+    }
+
+    /// Returns `true` if this is [`Self::Localized`].
+    pub fn is_localized(&self) -> bool
+    {
+        matches!(self, Self::Localized(_))
+    }
+
+    /// Gets the text or localization template.
+    pub fn as_str(&self) -> &str
+    {
+        match self {
+            Self::Text(text) | Self::Localized(text) => text.as_str(),
+        }
+    }
+
+    /// Converts to the text or localization template.
+    pub fn into_string(self) -> String
+    {
+        match self {
+            Self::Text(text) | Self::Localized(text) => text,
+        }
+    }
+}
+
+impl Default for TextContent
+{
+    fn default() -> Self
+    {
+        Self::Text(String::default())
+    }
+}
+
+impl From<&str> for TextContent
+{
+    fn from(text: &str) -> Self
+    {
+        Self::Text(text.into())
+    }
+}
+
+impl From<String> for TextContent
+{
+    fn from(text: String) -> Self
+    {
+        Self::Text(text)
+    }
+}
+
+impl Serialize for TextContent
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    {
+        match self {
+            Self::Text(text) => serializer.serialize_str(text),
+            Self::Localized(template) => serializer.serialize_newtype_struct(CobLocalized::NEWTYPE_NAME, template),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TextContent
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    {
+        deserializer.deserialize_any(TextContentVisitor)
+    }
+}
+
+struct TextContentVisitor;
+
+impl<'de> Visitor<'de> for TextContentVisitor
+{
+    type Value = TextContent;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result
+    {
+        formatter.write_str("a string or localized text")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, text: &str) -> Result<Self::Value, E>
+    {
+        Ok(TextContent::Text(text.into()))
+    }
+
+    fn visit_string<E: serde::de::Error>(self, text: String) -> Result<Self::Value, E>
+    {
+        Ok(TextContent::Text(text))
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    {
+        Ok(TextContent::Localized(String::deserialize(deserializer)?))
+    }
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -62,9 +196,9 @@ fn insert_text_line(
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextLine
 {
-    /// The starting text string.
+    /// The starting text string, or localized text.
     #[reflect(default = "TextLine::default_text")]
-    pub text: String,
+    pub text: TextContent,
     /// The font handle.
     ///
     /// Defaults to the built-in "Fira Sans Medium" font.
@@ -89,7 +223,7 @@ pub struct TextLine
 
 impl TextLine
 {
-    pub fn from_text(text: impl Into<String>) -> Self
+    pub fn from_text(text: impl Into<TextContent>) -> Self
     {
         Self { text: text.into(), ..default() }
     }
@@ -100,7 +234,7 @@ impl TextLine
         self
     }
 
-    fn default_text() -> String
+    fn default_text() -> TextContent
     {
         TEXT_LINE_DEFAULT_TEXT.into()
     }
@@ -284,7 +418,8 @@ impl Plugin for UiTextExtPlugin
 {
     fn build(&self, app: &mut App)
     {
-        app.register_instruction_type::<TextLine>()
+        app.register_type::<TextContent>()
+            .register_instruction_type::<TextLine>()
             .register_themed::<TextLineSize>()
             .register_animatable::<TextLineColor>();
     }
@@ -0,0 +1,86 @@
+use bevy::color::palettes::css;
+use bevy::prelude::*;
+use bevy_cobweb_ui::prelude::cob::*;
+
+use super::helpers::{test_cob, test_cob_fail, test_constant_values};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn color(value: &CobValue) -> &CobColor
+{
+    let CobValue::Builtin(CobBuiltin::Color(color)) = value else { unreachable!() };
+    color
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn color_literals()
+{
+    let res = test_cob(
+        b"#defs
+$a = #F00
+$b = #F008
+$c = #FF0000
+$d = #FF000080
+$e = aliceblue
+$f = rgb(255, 0, 0)
+$g = rgba(100% 0 0 50%)
+$h = hsla(120 100% 50% 0.5)
+",
+    );
+    let values = test_constant_values(&res);
+    let colors: Vec<_> = values.iter().map(|value| color(value)).collect();
+
+    assert_eq!(colors[0].color, Srgba::RED);
+    assert_eq!(colors[0].syntax, CobColorSyntax::ShortHex);
+    assert_eq!(colors[1].color, Srgba::new(1., 0., 0., 136. / 255.));
+    assert_eq!(colors[2].color, Srgba::RED);
+    assert_eq!(colors[2].syntax, CobColorSyntax::Hex);
+    assert_eq!(colors[3].color, Srgba::new(1., 0., 0., 128. / 255.));
+    assert_eq!(colors[4].color, css::ALICE_BLUE);
+    assert_eq!(colors[5].color, Srgba::RED);
+    assert_eq!(colors[6].color, Srgba::new(1., 0., 0., 0.5));
+    let green = colors[7].color;
+    assert!(green.red.abs() < 0.001 && (green.green - 1.).abs() < 0.001 && green.blue.abs() < 0.001);
+    assert_eq!(green.alpha, 0.5);
+
+    // Color names used as struct fields, and color functions with non-literal arguments.
+    let res = test_cob(
+        b"#defs
+$a = Srgba{ red:1 green :0 blue:0 alpha:1 }
+$b = rgb($r 0 0)
+$c = [red tan]
+",
+    );
+    let values = test_constant_values(&res);
+    assert!(matches!(values[0], CobValue::Enum(_)));
+    assert!(matches!(values[1], CobValue::Function(_)));
+    let CobValue::Array(array) = &values[2] else { unreachable!() };
+    assert_eq!(color(&array.entries[1]).color, css::TAN);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn color_literal_errors()
+{
+    // Wrong number of hex digits
+    test_cob_fail(
+        b"#defs
+$a = #FFFFF
+",
+        b"#FFFFF
+",
+    );
+    // Wrong number of arguments
+    test_cob_fail(
+        b"#defs
+$a = rgb(255 0)
+",
+        b"rgb(255 0)
+",
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,170 @@
+use bevy::prelude::*;
+use bevy_cobweb_ui::prelude::cob::*;
+use bevy_cobweb_ui::prelude::{CobCompileError, CobCompiler, CobFlags, CompiledCob, LoadableRegistry};
+use serde::Deserialize;
+
+use super::helpers::{prepare_test_app, PlainStruct, SimpleStruct};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn compile(app: &App, compiler: &CobCompiler, flags: &CobFlags) -> Result<CompiledCob, CobCompileError>
+{
+    let type_registry = app.world().resource::<AppTypeRegistry>().read();
+    compiler.compile(&type_registry, app.world().resource::<LoadableRegistry>(), flags)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn compile_sources()
+{
+    let app = prepare_test_app();
+    let mut compiler = CobCompiler::new("assets", ["main.cob"]);
+    compiler
+        .add_source(
+            "main.cob",
+            "#manifest
+self as main
+\"ui/menu.cob\" as ui.menu
+
+#import
+consts as c
+
+#defs
+$b = 2
+
+#commands
+SimpleStruct{a:$c::a b:$b}
+",
+        )
+        .add_source(
+            "ui/menu.cob",
+            "#import
+consts as c
+
+#scenes
+\"base\"
+    SimpleStruct{a:1 b:1}
+    \"\"
+        PlainStruct{boolean:true}
+
+\"menu\" : \"base\"
+    SimpleStruct{a:$c::a b:$c::a * 2}
+    \"header\"
+",
+        )
+        .add_source(
+            "consts.cob",
+            "#manifest
+self as consts
+
+#defs
+$a = 10
+",
+        );
+
+    let compiled = compile(&app, &compiler, &CobFlags::default()).unwrap();
+
+    // Only files reachable from the roots are included, in manifest order.
+    let files: Vec<&str> = compiled.files.iter().map(|f| f.file.as_str()).collect();
+    assert_eq!(files, vec!["main.cob", "ui/menu.cob"]);
+    assert_eq!(compiled.roots[0].as_str(), "main.cob");
+    assert_eq!(compiled.files[0].manifest.len(), 2);
+
+    // Defs and imports are resolved.
+    let command = &compiled.files[0].commands[0];
+    assert!(command.type_path.ends_with("::SimpleStruct"));
+    assert_eq!(SimpleStruct::deserialize(&command.loadable).unwrap(), SimpleStruct { a: 10, b: 2 });
+
+    // Base scenes are applied and anonymous nodes are named.
+    let menu = &compiled.files[1].scenes[1];
+    assert_eq!(menu.name, "menu");
+    assert_eq!(menu.loadables.len(), 1);
+    assert_eq!(
+        SimpleStruct::deserialize(&menu.loadables[0].loadable).unwrap(),
+        SimpleStruct { a: 10, b: 20 }
+    );
+    let children: Vec<&str> = menu.children.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(children, vec!["_0", "header"]);
+    assert_eq!(
+        PlainStruct::deserialize(&menu.children[0].loadables[0].loadable).unwrap(),
+        PlainStruct { boolean: true }
+    );
+
+    // Binary round trip.
+    let bytes = compiled.to_bytes().unwrap();
+    let decoded = CompiledCob::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded.to_bytes().unwrap(), bytes);
+    assert_eq!(decoded.files[1].scenes[1].children[1].name, "header");
+    assert_eq!(
+        SimpleStruct::deserialize(&decoded.files[0].commands[0].loadable).unwrap(),
+        SimpleStruct { a: 10, b: 2 }
+    );
+
+    // Corrupted data is rejected.
+    assert!(CompiledCob::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(CompiledCob::from_bytes(b"COB").is_err());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn compile_conditional()
+{
+    let app = prepare_test_app();
+    let mut compiler = CobCompiler::new("assets", ["main.cob"]);
+    compiler.add_source(
+        "main.cob",
+        "#if(demo)
+#commands
+SimpleStruct{a:1 b:1}
+#else
+#commands
+SimpleStruct{a:2 b:2}
+#endif
+",
+    );
+
+    let mut flags = CobFlags::default();
+    let compiled = compile(&app, &compiler, &flags).unwrap();
+    assert_eq!(
+        SimpleStruct::deserialize(&compiled.files[0].commands[0].loadable).unwrap(),
+        SimpleStruct { a: 2, b: 2 }
+    );
+
+    flags.enable("demo");
+    let compiled = compile(&app, &compiler, &flags).unwrap();
+    assert_eq!(
+        SimpleStruct::deserialize(&compiled.files[0].commands[0].loadable).unwrap(),
+        SimpleStruct { a: 1, b: 1 }
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn compile_errors()
+{
+    let app = prepare_test_app();
+    let flags = CobFlags::default();
+
+    // Unknown loadables and unresolved defs are reported together.
+    let mut compiler = CobCompiler::new("assets", ["main.cob"]);
+    compiler.add_source(
+        "main.cob",
+        "#commands\nSimpleStrct{a:1 b:1}\n\n#scenes\n\"a\"\n    SimpleStruct{a:$x}\n",
+    );
+    let Err(CobCompileError::Diagnostics(diagnostics)) = compile(&app, &compiler, &flags) else { unreachable!() };
+    assert_eq!(diagnostics.len(), 2);
+
+    // Unknown imports.
+    let mut compiler = CobCompiler::new("assets", ["main.cob"]);
+    compiler.add_source("main.cob", "#import\nmissing as m\n");
+    assert!(matches!(compile(&app, &compiler, &flags), Err(CobCompileError::Dependency(_))));
+
+    // Missing files.
+    let compiler = CobCompiler::new("assets", ["missing.cob"]);
+    assert!(matches!(compile(&app, &compiler, &flags), Err(CobCompileError::Io { .. })));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
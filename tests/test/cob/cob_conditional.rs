@@ -0,0 +1,171 @@
+use bevy_cobweb_ui::prelude::cob::*;
+use bevy_cobweb_ui::prelude::*;
+
+use super::helpers::{test_cob, test_cob_fail};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn condition(cob: &Cob) -> &CobConditional
+{
+    let CobSection::Conditional(conditional) = &cob.sections[0] else { unreachable!() };
+    conditional
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn conditional_section()
+{
+    let res = test_cob(
+        b"#if(platform = \"wasm\")
+#endif
+",
+    );
+    let conditional = condition(&res);
+    assert_eq!(conditional.condition.flag, "platform");
+    assert_eq!(conditional.sections.len(), 0);
+    assert!(conditional.else_branch.is_none());
+
+    let res = test_cob(
+        b"#if(platform = \"wasm\")
+#commands
+A
+#else
+#commands
+B
+
+#scenes
+\"a\"
+#endif
+",
+    );
+    let conditional = condition(&res);
+    assert_eq!(conditional.sections.len(), 1);
+    assert_eq!(conditional.else_branch.as_ref().unwrap().sections.len(), 2);
+
+    // Nested blocks and fill.
+    test_cob(
+        b"
+#defs
+$a = 1
+
+#if( !demo /*c*/ )
+// comment
+#if(device!=\"steam_deck\")
+#scenes
+\"a\"
+#endif
+#else
+#endif
+
+#commands
+A
+",
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn conditional_evaluation()
+{
+    let mut flags = CobFlags::empty();
+    flags.set("platform", "linux").enable("demo");
+
+    let res = test_cob(
+        b"#commands
+A
+
+#if(platform = \"wasm\")
+#commands
+B
+#else
+#commands
+C
+#if(demo)
+#commands
+D
+#endif
+#endif
+
+#if(!demo)
+#commands
+E
+#endif
+
+#if(device != \"steam_deck\")
+#commands
+F
+#endif
+",
+    );
+
+    let active: Vec<_> = res
+        .active_sections(&flags)
+        .into_iter()
+        .map(|section| {
+            let CobSection::Commands(commands) = section else { unreachable!() };
+            let CobCommandEntry::Loadable(loadable) = &commands.entries[0] else { unreachable!() };
+            loadable.id.to_canonical(None)
+        })
+        .collect();
+    assert_eq!(active, ["A", "C", "D", "F"]);
+    assert_eq!(res.all_sections().len(), 6);
+
+    flags.set("platform", "wasm").remove("demo");
+    assert_eq!(res.active_sections(&flags).len(), 4);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn conditional_errors()
+{
+    // Missing #endif
+    test_cob_fail(
+        b"#if(demo)
+#commands
+A
+",
+        b"",
+    );
+    // Manifest in a block
+    test_cob_fail(
+        b"#if(demo)
+#manifest
+self as a
+#endif
+",
+        b"#manifest
+self as a
+#endif
+",
+    );
+    // Negated comparison
+    test_cob_fail(
+        b"#if(!platform = \"wasm\")
+#endif
+",
+        b"= \"wasm\")
+#endif
+",
+    );
+    // Unquoted value
+    test_cob_fail(
+        b"#if(platform = wasm)
+#endif
+",
+        b"wasm)
+#endif
+",
+    );
+    // #endif not on a new line
+    test_cob_fail(
+        b"#if(demo) #endif
+",
+        b"#endif
+",
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
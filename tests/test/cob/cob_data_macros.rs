@@ -0,0 +1,319 @@
+use bevy_cobweb_ui::prelude::cob::*;
+use bevy_cobweb_ui::prelude::{CobFlags, CobResolver, ConstantsBuffer};
+use serde::Deserialize;
+
+use super::helpers::{test_cob, test_cob_fail, test_constant, SimpleStruct};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+const LIB_COB: &str = "#defs
+$base = 7
+point!(@a @b = $base) = {a:@a b:@b}
+";
+
+/// Makes a resolver with `main.cob`, which imports `lib.cob` as `lib`.
+fn test_resolver(main_defs: &str) -> CobResolver
+{
+    let main = format!("#manifest\n\"lib.cob\" as lib\n\n#import\nlib as lib\n\n#defs\n{main_defs}");
+    let mut resolver = CobResolver::new(CobFlags::default());
+    for (file, raw) in [("lib.cob", LIB_COB), ("main.cob", main.as_str())] {
+        resolver.insert(Cob::parse(Span::new_extra(raw, CobLocationMetadata { file })).unwrap());
+    }
+    resolver
+}
+
+fn main_constants(resolver: &mut CobResolver) -> &ConstantsBuffer
+{
+    resolver
+        .resolve(&CobFile::try_new("main.cob").unwrap())
+        .unwrap()
+}
+
+/// Expands a data macro call with the defs visible in `main.cob`.
+fn expand(constants: &ConstantsBuffer, raw: &str) -> Result<CobConstantValue, String>
+{
+    let CobValue::DataMacro(mut call) = test_constant(raw) else { unreachable!() };
+    call.expand(constants)
+}
+
+fn expand_struct(constants: &ConstantsBuffer, raw: &str) -> SimpleStruct
+{
+    let CobConstantValue::Value(value) = expand(constants, raw).unwrap() else { unreachable!() };
+    SimpleStruct::deserialize(&value).unwrap()
+}
+
+/// Resolves a value that contains data macro calls with the defs visible in `main.cob`.
+fn resolve_array(constants: &ConstantsBuffer, raw: &str) -> Vec<u32>
+{
+    let mut value = test_constant(raw);
+    assert!(value.resolve(constants).unwrap().is_none());
+    Vec::<u32>::deserialize(&value).unwrap()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn defs_section_data_macros()
+{
+    /*
+    let subscriber = tracing_subscriber::FmtSubscriber::builder()
+        .with_max_level(tracing::Level::TRACE)
+        .finish();
+    tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+    */
+
+    let res = test_cob(
+        b"#defs
+a!() = 10
+",
+    );
+    let CobSection::Defs(defs) = &res.sections[0] else { unreachable!() };
+    assert_eq!(defs.entries.len(), 1);
+    let CobDefEntry::DataMacro(def) = &defs.entries[0] else { unreachable!() };
+    assert_eq!(def.name.as_str(), "a");
+    assert_eq!(def.params.entries.len(), 0);
+    let CobConstantValue::Value(CobValue::Number(number)) = &def.value else { unreachable!() };
+    assert_eq!(number.number.as_u128().unwrap(), 10);
+
+    let res = test_cob(
+        b"
+#defs
+a!(@x) = @x
+b!( @x @y=10 ..@z ) = X{ a:@x b:@y? ..@z }
+c!(@x) = \\ 10 @x a::b!(@x) \\
+d!(@x = $a) = [@x? 1]
+",
+    );
+    let CobSection::Defs(defs) = &res.sections[0] else { unreachable!() };
+    assert_eq!(defs.entries.len(), 4);
+
+    let CobDefEntry::DataMacro(def) = &defs.entries[0] else { unreachable!() };
+    assert_eq!(def.name.as_str(), "a");
+    let CobMacroParamDef::Unassigned { name, .. } = &def.params.entries[0] else { unreachable!() };
+    assert_eq!(name.as_str(), "x");
+    let CobConstantValue::Value(CobValue::MacroParam(param)) = &def.value else { unreachable!() };
+    assert_eq!(param.name.as_str(), "x");
+    assert!(param.is_required());
+
+    let CobDefEntry::DataMacro(def) = &defs.entries[1] else { unreachable!() };
+    assert_eq!(def.name.as_str(), "b");
+    assert_eq!(def.params.entries.len(), 3);
+    let CobMacroParamDef::Assigned { name, value: CobValue::Number(number), .. } = &def.params.entries[1] else {
+        unreachable!()
+    };
+    assert_eq!(name.as_str(), "y");
+    assert_eq!(number.number.as_u128().unwrap(), 10);
+    let CobMacroParamDef::CatchAll { name, .. } = &def.params.entries[2] else { unreachable!() };
+    assert_eq!(name.as_str(), "z");
+    let CobConstantValue::Value(CobValue::Enum(CobEnum { variant: CobEnumVariant::Map(map), .. })) = &def.value
+    else {
+        unreachable!()
+    };
+    assert_eq!(map.entries.len(), 3);
+    let CobMapEntry::KeyValue(kv) = &map.entries[1] else { unreachable!() };
+    let CobValue::MacroParam(param) = &kv.value else { unreachable!() };
+    assert_eq!(param.name.as_str(), "y");
+    assert!(param.optional);
+    let CobMapEntry::MacroParam(param) = &map.entries[2] else { unreachable!() };
+    assert_eq!(param.name.as_str(), "z");
+    assert!(param.is_catch_all());
+
+    let CobDefEntry::DataMacro(def) = &defs.entries[2] else { unreachable!() };
+    let CobConstantValue::ValueGroup(group) = &def.value else { unreachable!() };
+    assert_eq!(group.entries.len(), 3);
+    let CobValueGroupEntry::Value(CobValue::DataMacro(call)) = &group.entries[2] else { unreachable!() };
+    assert_eq!(call.path.as_str(), "a::b");
+    assert_eq!(call.args.entries.len(), 1);
+
+    let CobDefEntry::DataMacro(def) = &defs.entries[3] else { unreachable!() };
+    let CobMacroParamDef::Assigned { value: CobValue::Constant(constant), .. } = &def.params.entries[0] else {
+        unreachable!()
+    };
+    assert_eq!(constant.path.as_str(), "a");
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn data_macro_calls()
+{
+    let res = test_cob(
+        b"#commands
+A(a!())
+B[ a!(1 2 x: 3) ]
+C{ a:a::b::c!(  X{ a:1 } ) b!() }
+",
+    );
+    let CobSection::Commands(commands) = &res.sections[0] else { unreachable!() };
+    assert_eq!(commands.entries.len(), 3);
+
+    let CobCommandEntry::Loadable(loadable) = &commands.entries[1] else { unreachable!() };
+    let CobLoadableVariant::Array(array) = &loadable.variant else { unreachable!() };
+    let CobValue::DataMacro(call) = &array.entries[0] else { unreachable!() };
+    assert_eq!(call.path.as_str(), "a");
+    assert_eq!(call.args.entries.len(), 3);
+    assert!(matches!(call.args.entries[0], CobValueGroupEntry::Value(CobValue::Number(_))));
+    let CobValueGroupEntry::KeyValue(kv) = &call.args.entries[2] else { unreachable!() };
+    assert!(matches!(&kv.key, CobMapKey::FieldName { name, .. } if name.as_str() == "x"));
+
+    let CobCommandEntry::Loadable(loadable) = &commands.entries[2] else { unreachable!() };
+    let CobLoadableVariant::Map(map) = &loadable.variant else { unreachable!() };
+    assert_eq!(map.entries.len(), 2);
+    let CobMapEntry::KeyValue(kv) = &map.entries[0] else { unreachable!() };
+    let CobValue::DataMacro(call) = &kv.value else { unreachable!() };
+    assert_eq!(call.path.as_str(), "a::b::c");
+    let CobMapEntry::DataMacro(call) = &map.entries[1] else { unreachable!() };
+    assert_eq!(call.path.as_str(), "b");
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn data_macro_errors()
+{
+    // Def not starting on newline
+    test_cob_fail(
+        b"#defs
+ a!() = 10",
+        b"a!() = 10",
+    );
+    // Def without params
+    test_cob_fail(
+        b"#defs
+a! = 10
+",
+        b"a! = 10\n",
+    );
+    // Optional param in def
+    test_cob_fail(
+        b"#defs
+a!(@x?) = 10
+",
+        b"@x?) = 10\n",
+    );
+    // Duplicate params
+    test_cob_fail(
+        b"#defs
+a!(@x @x) = 10
+",
+        b"(@x @x) = 10\n",
+    );
+    // Multiple catch-all params
+    test_cob_fail(
+        b"#defs
+a!(..@x ..@y) = 10
+",
+        b"(..@x ..@y) = 10\n",
+    );
+    // Non-catch-all param in map
+    test_cob_fail(
+        b"#defs
+a!(@x) = X{ @x }
+",
+        b"@x }\n",
+    );
+    // Call without args
+    test_cob_fail(
+        b"#commands
+A(a!)
+",
+        b"a!)\n",
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn stray_macro_params()
+{
+    let mut res = test_cob(
+        b"#commands
+A(@x)
+B{ ..@x }
+",
+    );
+    let CobSection::Commands(commands) = &mut res.sections[0] else { unreachable!() };
+
+    // Macro params are only allowed in macro definitions.
+    for entry in commands.entries.iter_mut() {
+        let CobCommandEntry::Loadable(loadable) = entry else { unreachable!() };
+        assert!(loadable.resolve(&ConstantsBuffer::default()).is_err());
+    }
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn data_macro_expansion()
+{
+    let mut resolver = test_resolver(
+        "pair!(@a @b) = {a:@a b:@b}
+opt!(@a @b = 5) = {a:@a b:@b}
+maybe!(@a @b) = {a:@a b:@b?}
+rest!(@first ..@rest) = [@first ..@rest]
+fields!(..@fields) = {a:1 ..@fields}
+nums!(@x @y) = \\ @x @y \\
+inner!(@v) = {a:@v b:@v}
+outer!(@x) = inner!(@x)
+two!() = 2
+",
+    );
+    let constants = main_constants(&mut resolver);
+
+    // Positional and named arguments.
+    assert_eq!(expand_struct(constants, "pair!(1 2)"), SimpleStruct { a: 1, b: 2 });
+    assert_eq!(expand_struct(constants, "pair!(b: 2 a: 1)"), SimpleStruct { a: 1, b: 2 });
+    assert_eq!(expand_struct(constants, "pair!(1 b: 2)"), SimpleStruct { a: 1, b: 2 });
+    // Positional arguments skip params that were already named.
+    assert_eq!(expand_struct(constants, "pair!(a: 1 2)"), SimpleStruct { a: 1, b: 2 });
+    assert_eq!(expand_struct(constants, "pair!(b: 2 1)"), SimpleStruct { a: 1, b: 2 });
+    assert!(expand(constants, "pair!(1 a: 2)").is_err());
+    assert!(expand(constants, "pair!(1)").is_err());
+    assert!(expand(constants, "pair!(1 2 3)").is_err());
+    assert!(expand(constants, "pair!(1 c: 2)").is_err());
+
+    // Default and optional params.
+    assert_eq!(expand_struct(constants, "opt!(1)"), SimpleStruct { a: 1, b: 5 });
+    assert_eq!(expand_struct(constants, "opt!(1 2)"), SimpleStruct { a: 1, b: 2 });
+    assert_eq!(expand_struct(constants, "maybe!(1 2)"), SimpleStruct { a: 1, b: 2 });
+    let CobConstantValue::Value(CobValue::Map(map)) = expand(constants, "maybe!(1)").unwrap() else {
+        unreachable!()
+    };
+    assert_eq!(map.entries.len(), 1);
+
+    // Catch-all params are flattened into the macro body.
+    assert_eq!(resolve_array(constants, "rest!(1 2 3)"), [1, 2, 3]);
+    assert_eq!(resolve_array(constants, "rest!(1)"), [1]);
+    assert_eq!(expand_struct(constants, "fields!(b: 2)"), SimpleStruct { a: 1, b: 2 });
+
+    // Value groups are flattened into the structure containing the call.
+    let CobConstantValue::ValueGroup(group) = expand(constants, "nums!(1 2)").unwrap() else { unreachable!() };
+    assert_eq!(group.entries.len(), 2);
+    assert_eq!(resolve_array(constants, "[0 nums!(1 2) 3]"), [0, 1, 2, 3]);
+
+    // Nested macro calls, in macro bodies and in arguments.
+    assert_eq!(expand_struct(constants, "outer!(3)"), SimpleStruct { a: 3, b: 3 });
+    assert_eq!(expand_struct(constants, "pair!(1 two!())"), SimpleStruct { a: 1, b: 2 });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn imported_data_macro_expansion()
+{
+    let mut resolver = test_resolver(
+        "wrap!(@x) = lib::point!(@x 2)
+",
+    );
+    let constants = main_constants(&mut resolver);
+
+    // Macros are looked up through import aliases, and their defaults are resolved in the file that defines them.
+    assert_eq!(expand_struct(constants, "lib::point!(1)"), SimpleStruct { a: 1, b: 7 });
+    assert_eq!(expand_struct(constants, "lib::point!(b: 2 1)"), SimpleStruct { a: 1, b: 2 });
+    assert_eq!(expand_struct(constants, "wrap!(1)"), SimpleStruct { a: 1, b: 2 });
+
+    // Imported macros are not visible without the alias.
+    assert!(expand(constants, "point!(1)").is_err());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
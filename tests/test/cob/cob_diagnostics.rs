@@ -0,0 +1,70 @@
+use bevy_cobweb_ui::prelude::cob::*;
+
+use super::helpers::test_span;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn parse_diagnostic(raw: &str) -> CobDiagnostic
+{
+    let Err(error) = Cob::parse(test_span(raw)) else { unreachable!() };
+    CobDiagnostic::from_span_error(&error).unwrap()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn parse_diagnostics()
+{
+    // Missing `:` in struct field
+    let diagnostic = parse_diagnostic(
+        "#commands
+A{ width 10px }
+",
+    );
+    assert_eq!(diagnostic.file, "test.cob");
+    assert_eq!(diagnostic.message, "expected `:` after struct field `width`");
+    let location = diagnostic.location.as_ref().unwrap();
+    assert_eq!(location.line, 2);
+    assert_eq!(location.column, 10);
+    assert_eq!(location.source_line, "A{ width 10px }");
+    assert_eq!(
+        diagnostic.to_string(),
+        "error: expected `:` after struct field `width`
+ --> test.cob:2:10
+  |
+2 | A{ width 10px }
+  |          ^"
+    );
+
+    // Unclosed map
+    let diagnostic = parse_diagnostic(
+        "#commands
+A{ width: 10px
+",
+    );
+    assert_eq!(diagnostic.message, "expected map entry or closing `}`");
+    assert_eq!(diagnostic.location.unwrap().line, 3);
+
+    // Diagnostics are not carried over between files
+    let diagnostic = parse_diagnostic(
+        "#commands
+A
+1",
+    );
+    assert_ne!(diagnostic.message, "expected map entry or closing `}`");
+    assert_eq!(diagnostic.location.unwrap().source_line, "1");
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn similar_names()
+{
+    let candidates = ["FlexNode", "TextLine", "BackgroundColor"];
+    assert_eq!(find_similar_name("FlexNod", candidates), Some("FlexNode"));
+    assert_eq!(find_similar_name("textline", candidates), Some("TextLine"));
+    assert_eq!(find_similar_name("BackgroundColour", candidates), Some("BackgroundColor"));
+    assert_eq!(find_similar_name("Transform", candidates), None);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,111 @@
+use bevy_cobweb_ui::prelude::cob::*;
+use bevy_cobweb_ui::prelude::*;
+
+use super::helpers::{test_cob, test_span};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn loadable(raw: &str) -> CobLoadable
+{
+    let (Some(loadable), _, _) = CobLoadable::try_parse(CobFill::default(), test_span(raw)).unwrap() else {
+        unreachable!()
+    };
+    loadable
+}
+
+fn write(cob: &Cob) -> String
+{
+    let mut buff = Vec::<u8>::default();
+    cob.write_to(&mut DefaultRawSerializer::new(&mut buff))
+        .unwrap();
+    String::from_utf8(buff).unwrap()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn scene_editing()
+{
+    let mut cob = test_cob(
+        b"#scenes
+\"menu\"
+    A
+    \"header\"
+        B
+    \"footer\"
+",
+    );
+    let menu = ScenePath::new("menu");
+
+    cob.insert_scene_loadable(&menu, loadable("C"), None)
+        .unwrap();
+    cob.insert_scene_node(&menu.extend("body"), Some(1))
+        .unwrap();
+    cob.insert_scene_loadable(&menu.extend("body"), loadable("D"), None)
+        .unwrap();
+    cob.move_scene_loadable(&menu, "A", 1).unwrap();
+    cob.move_scene_node(&menu.extend("footer"), 0).unwrap();
+    cob.remove_scene_node(&menu.extend("header")).unwrap();
+    cob.insert_scene_node(&ScenePath::new("settings"), None)
+        .unwrap();
+
+    let written = write(&cob);
+    assert_eq!(
+        written,
+        "#scenes
+\"menu\"
+    C
+    A
+    \"footer\"
+    \"body\"
+        D
+
+\"settings\"
+"
+    );
+    test_cob(written.as_bytes());
+
+    // Invalid edits fail without changing anything.
+    assert!(cob.insert_scene_node(&menu.extend("body"), None).is_err());
+    assert!(cob.insert_scene_node(&menu.extend("missing::node"), None).is_err());
+    assert!(cob.insert_scene_loadable(&menu, loadable("C"), None).is_err());
+    assert!(cob.remove_scene_loadable(&menu, "B").is_err());
+    assert!(cob.move_scene_node(&menu.extend("header"), 0).is_err());
+    assert_eq!(write(&cob), written);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn command_editing()
+{
+    let mut cob = test_cob(
+        b"#scenes
+\"a\"
+",
+    );
+
+    // A #commands section is added before the first #scenes section.
+    cob.insert_command_loadable(loadable("A"), None).unwrap();
+    cob.insert_command_loadable(loadable("B(1)"), Some(0))
+        .unwrap();
+    cob.insert_command_loadable(loadable("C"), None).unwrap();
+    cob.move_command_loadable("C", 1).unwrap();
+    cob.remove_command_loadable("A").unwrap();
+    assert!(cob.insert_command_loadable(loadable("B(2)"), None).is_err());
+
+    let written = write(&cob);
+    assert_eq!(
+        written,
+        "#commands
+B(1)
+C
+
+#scenes
+\"a\"
+"
+    );
+    test_cob(written.as_bytes());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+use bevy_cobweb_ui::prelude::cob::*;
+use bevy_cobweb_ui::prelude::*;
+
+use super::helpers::{test_cob, test_cob_fail, test_constant, test_constant_values};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn evaluate(value: &str) -> Result<CobValue, String>
+{
+    let mut value = test_constant(value);
+    value.resolve(&ConstantsBuffer::default())?;
+    Ok(value)
+}
+
+fn evaluate_number(value: &str) -> CobNumberValue
+{
+    let CobValue::Number(number) = evaluate(value).unwrap() else { unreachable!() };
+    number.number
+}
+
+fn evaluate_val(value: &str) -> Val
+{
+    let CobValue::Builtin(CobBuiltin::Val { val, .. }) = evaluate(value).unwrap() else { unreachable!() };
+    val
+}
+
+fn evaluate_color(value: &str) -> Srgba
+{
+    let CobValue::Builtin(CobBuiltin::Color(color)) = evaluate(value).unwrap() else { unreachable!() };
+    color.color
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn expression_parsing()
+{
+    let res = test_cob(
+        b"#defs
+$a = $b * 2 + 1px
+",
+    );
+    let CobValue::Expression(expression) = test_constant_values(&res).remove(0) else { unreachable!() };
+    assert!(matches!(*expression.first, CobValue::Constant(_)));
+    assert_eq!(expression.terms.len(), 2);
+    assert_eq!(expression.terms[0].op, CobOperator::Multiply);
+    assert_eq!(expression.terms[1].op, CobOperator::Add);
+
+    let res = test_cob(
+        b"#defs
+$a = lighten(#FF0000 20%)
+",
+    );
+    let CobValue::Function(call) = test_constant_values(&res).remove(0) else { unreachable!() };
+    assert_eq!(call.name, "lighten");
+    assert_eq!(call.args.entries.len(), 2);
+
+    // Operators need whitespace on both sides, otherwise they're part of the next value.
+    let res = test_cob(
+        b"#defs
+$a = (1 -2)
+",
+    );
+    let CobValue::Tuple(tuple) = test_constant_values(&res).remove(0) else { unreachable!() };
+    assert_eq!(tuple.entries.len(), 2);
+
+    // Fill, comments, and nesting.
+    test_cob(
+        b"#defs
+$a = (@x + 2px) /* half */ / 2
+$b = min( $a, clamp(@y * 2 0px 10px) )
+$c = mix(hsl(120 50% 50%) oklch(0.5 0.1 30 0.5) 0.25)
+
+#scenes
+\"a\"
+    FlexNode{ width:$a * 2 margin:UiRect{ left:max(1px 2px) - 1px } }
+    BackgroundColor(with_alpha(darken($c 10%) 80%))
+",
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn expression_evaluation()
+{
+    assert_eq!(evaluate_number("2 + 3 * 4"), CobNumberValue::Uint(14));
+    assert_eq!(evaluate_number("2 - 3"), CobNumberValue::Int(-1));
+    assert_eq!(evaluate_number("7 / 2"), CobNumberValue::Float64(3.5));
+    assert_eq!(evaluate_number("(1 + 2) * 3"), CobNumberValue::Uint(9));
+    assert_eq!(evaluate_number("1.5 * 2"), CobNumberValue::Float64(3.0));
+    assert_eq!(evaluate_number("clamp(15 0 10)"), CobNumberValue::Uint(10));
+
+    assert_eq!(evaluate_val("10px * 2 + 4px"), Val::Px(24.));
+    assert_eq!(evaluate_val("2 * 50% / 4"), Val::Percent(25.));
+    assert_eq!(evaluate_val("min(4px 2px 3px)"), Val::Px(2.));
+    assert_eq!(evaluate_val("max(1vw 2vw) - 1vw"), Val::Vw(1.));
+
+    assert_eq!(evaluate_color("with_alpha(#FF0000 50%)"), Srgba::new(1., 0., 0., 0.5));
+    let red = evaluate_color("hsl(0 100% 50%)");
+    assert!((red.red - 1.).abs() < 0.001 && red.green.abs() < 0.001 && red.blue.abs() < 0.001);
+    let lighter = evaluate_color("lighten(#808080 0.1)");
+    let darker = evaluate_color("darken(#808080 0.1)");
+    assert!(lighter.red > 0.51 && darker.red < 0.49);
+    let mixed = evaluate_color("mix(#000000 #FFFFFF 0)");
+    assert!(mixed.red.abs() < 0.001);
+
+    // Macro params are evaluated when the macro is called, so the expression is kept.
+    let mut value = test_constant("@x * 2");
+    value.resolve(&ConstantsBuffer::default()).unwrap();
+    assert!(matches!(value, CobValue::Expression(_)));
+
+    assert!(evaluate("1px + 2%").unwrap_err().contains("units must match"));
+    assert!(evaluate("1 / 0").unwrap_err().contains("division by zero"));
+    assert!(evaluate("auto * 2").unwrap_err().contains("auto"));
+    assert!(evaluate("\"a\" + 1").is_err());
+    assert!(evaluate("clamp(1 2)").unwrap_err().contains("expects 3 arguments"));
+    assert!(evaluate("lighten(1 0.1)").unwrap_err().contains("expected a color"));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn expression_errors()
+{
+    // Missing right operand
+    test_cob_fail(
+        b"#defs
+$a = 1 +
+",
+        b"",
+    );
+    // Unknown function
+    test_cob_fail(
+        b"#defs
+$a = foo(1)
+",
+        b"foo(1)
+",
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use bevy_cobweb_ui::prelude::cob::*;
+use bevy_cobweb_ui::prelude::*;
+
+use super::helpers::{test_cob, test_constant, test_constant_values};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn resolve(value: &str, sizes: CobFontSizes) -> (CobValue, bool)
+{
+    let mut value = test_constant(value);
+    value.resolve(&ConstantsBuffer::default()).unwrap();
+    let found = value.resolve_font_units(&sizes).unwrap();
+    (value, found)
+}
+
+fn resolve_val(value: &str, sizes: CobFontSizes) -> Val
+{
+    let (CobValue::Builtin(CobBuiltin::Val { val, .. }), true) = resolve(value, sizes) else { unreachable!() };
+    val
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn font_unit_parsing()
+{
+    let res = test_cob(
+        b"#defs
+$a = 1rem
+$b = 1.5em
+$c = -0.5rem
+$d = 1e2
+",
+    );
+    let values = test_constant_values(&res);
+    let CobValue::Builtin(CobBuiltin::FontRelative { unit, .. }) = &values[0] else { unreachable!() };
+    assert_eq!(*unit, CobFontUnit::Rem);
+    let CobValue::Builtin(CobBuiltin::FontRelative { number, unit, .. }) = &values[1] else { unreachable!() };
+    assert_eq!(*unit, CobFontUnit::Em);
+    assert_eq!(number.as_f32_lossy(), Some(1.5));
+    assert!(matches!(values[2], CobValue::Builtin(CobBuiltin::FontRelative { .. })));
+    // Exponents are still parsed as numbers.
+    assert!(matches!(values[3], CobValue::Number(_)));
+
+    // Fill, expressions, and scene nodes.
+    test_cob(
+        b"#defs
+$gap = 1rem * 2 + 4px
+
+#scenes
+\"a\"
+    TextLineSize(1.25rem)
+    FlexNode{ width:10em margin:UiRect{ left:$gap /*c*/ top:max(0.5em 2px) } }
+",
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn font_unit_resolution()
+{
+    let sizes = CobFontSizes { rem: 16., em: 20. };
+    assert_eq!(resolve_val("1rem", sizes), Val::Px(16.));
+    assert_eq!(resolve_val("1.5em", sizes), Val::Px(30.));
+    assert_eq!(resolve_val("1rem * 2 + 4px", sizes), Val::Px(36.));
+    assert_eq!(resolve_val("max(1em 1rem)", sizes), Val::Px(20.));
+
+    // Expressions with rem/em values are evaluated when font units are resolved.
+    let mut value = test_constant("1rem + 1px");
+    value.resolve(&ConstantsBuffer::default()).unwrap();
+    assert!(matches!(value, CobValue::Expression(_)));
+
+    // Values without rem/em are unchanged.
+    let (value, found) = resolve("10px", sizes);
+    assert!(!found);
+    assert!(matches!(value, CobValue::Builtin(CobBuiltin::Val { val: Val::Px(10.), .. })));
+
+    // Units in expressions must match after resolving.
+    let mut value = test_constant("1rem + 50%");
+    value.resolve(&ConstantsBuffer::default()).unwrap();
+    assert!(value.resolve_font_units(&sizes).is_err());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
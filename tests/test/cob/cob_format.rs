@@ -0,0 +1,225 @@
+use bevy_cobweb_ui::prelude::cob::*;
+
+use super::helpers::test_cob;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn format_raw(raw: &str, style: &CobFormatStyle) -> String
+{
+    let mut cob = test_cob(raw.as_bytes());
+    cob.format(style);
+
+    let mut buff = Vec::<u8>::default();
+    cob.write_to(&mut DefaultRawSerializer::new(&mut buff))
+        .unwrap();
+    String::from_utf8(buff).unwrap()
+}
+
+fn test_format(raw: &str, style: &CobFormatStyle, expected: &str)
+{
+    assert_eq!(format_raw(raw, style), expected);
+
+    // Formatting is idempotent.
+    assert_eq!(format_raw(expected, style), expected);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn format_scenes()
+{
+    test_format(
+        r#"// Header
+
+#scenes
+"a"  // root
+  Node{width:10   height:5}
+  Color( 1,2 , 3 )
+
+
+  // Child
+  "b"
+     Text{text:"hi"}
+"#,
+        &CobFormatStyle::default(),
+        r#"// Header
+
+#scenes
+"a" // root
+    Node{width:10 height:5}
+    Color(1 2 3)
+
+    // Child
+    "b"
+        Text{text:"hi"}
+"#,
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn format_sorted_imports_and_aligned_defs()
+{
+    let raw = r#"#manifest
+"b.cob" as b
+self as a
+#import
+z as z
+a as _
+
+#defs
+$long_name = 1
+$x   =   2
+
+$y=3
+"#;
+
+    test_format(
+        raw,
+        &CobFormatStyle { sort_imports: true, ..Default::default() },
+        r#"#manifest
+"b.cob" as b
+self as a
+
+#import
+a as _
+z as z
+
+#defs
+$long_name = 1
+$x         = 2
+
+$y = 3
+"#,
+    );
+
+    test_format(
+        raw,
+        &CobFormatStyle { align_defs: false, ..Default::default() },
+        r#"#manifest
+"b.cob" as b
+self as a
+
+#import
+z as z
+a as _
+
+#defs
+$long_name = 1
+$x = 2
+
+$y = 3
+"#,
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn format_wrapped_maps()
+{
+    test_format(
+        r#"#commands
+Node{width:100 height:100 margin:{top:5 bottom:5}}
+Short{ a:1 }
+Commented{
+a:1 // first
+    b:2
+}
+"#,
+        &CobFormatStyle { max_width: 30, ..Default::default() },
+        r#"#commands
+Node{
+    width:100
+    height:100
+    margin:{top:5 bottom:5}
+}
+Short{a:1}
+Commented{
+    a:1 // first
+    b:2
+}
+"#,
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn format_conditionals()
+{
+    test_format(
+        r#"#commands
+A
+#if( platform="wasm" )
+
+#commands
+B
+#else
+#scenes
+"a"
+#endif
+"#,
+        &CobFormatStyle::default(),
+        r#"#commands
+A
+
+#if(platform = "wasm")
+#commands
+B
+
+#else
+#scenes
+"a"
+
+#endif
+"#,
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn format_keeps_manifest_and_import_order()
+{
+    // Manifest order sets the order that commands are applied, and import order decides which of two imported
+    // definitions with the same name is used.
+    test_format(
+        r#"#manifest
+"z.cob" as z
+
+"b.cob" as b
+self as a
+#import
+z as z
+a as _
+"#,
+        &CobFormatStyle::default(),
+        r#"#manifest
+"z.cob" as z
+
+"b.cob" as b
+self as a
+
+#import
+z as z
+a as _
+"#,
+    );
+
+    // Manifest entries are not sorted even when sorting imports.
+    test_format(
+        r#"#manifest
+"z.cob" as z
+"b.cob" as b
+"#,
+        &CobFormatStyle { sort_imports: true, ..Default::default() },
+        r#"#manifest
+"z.cob" as z
+"b.cob" as b
+"#,
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+use bevy_cobweb_ui::prelude::{
+    CobCompiler, CobFlags, CobIndex, CobLoadableRegistrationAppExt, LoadAudio, LoadImages, LoadableRegistry,
+};
+
+use super::helpers::prepare_test_app;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn index_compiled_files()
+{
+    let mut app = prepare_test_app();
+    app.register_command_type::<LoadImages>()
+        .register_command_type::<LoadAudio>();
+
+    let mut compiler = CobCompiler::new("assets", ["main.cob"]);
+    compiler
+        .add_source(
+            "main.cob",
+            "#manifest
+self as main
+\"ui/menu.cob\" as ui.menu
+\"ui/hud.cob\" as ui.hud
+
+#commands
+LoadImages[\"images/a.png\" \"images/b.png\"]
+SimpleStruct{a:1 b:1}
+",
+        )
+        .add_source(
+            "ui/menu.cob",
+            "#commands
+LoadImages[\"images/b.png\" \"images/c.png\"]
+LoadAudio[\"audio/click.ogg\"]
+",
+        )
+        .add_source("ui/hud.cob", "#defs\n$x = 1\n");
+
+    let compiled = {
+        let type_registry = app.world().resource::<AppTypeRegistry>().read();
+        compiler
+            .compile(
+                &type_registry,
+                app.world().resource::<LoadableRegistry>(),
+                &CobFlags::default(),
+            )
+            .unwrap()
+    };
+    let index = CobIndex::from_compiled(&compiled, &app.world().resource::<AppTypeRegistry>().read());
+
+    // Files are in manifest order and asset paths are deduplicated.
+    assert_eq!(index.files, vec!["main.cob", "ui/menu.cob", "ui/hud.cob"]);
+    assert_eq!(index.images, vec!["images/a.png", "images/b.png", "images/c.png"]);
+    assert_eq!(index.audio, vec!["audio/click.ogg"]);
+    assert!(index.fonts.is_empty());
+
+    // JSON round trip.
+    assert_eq!(CobIndex::from_json(&index.to_json()).unwrap(), index);
+    assert!(CobIndex::from_json("{\"files\": 1}").is_err());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
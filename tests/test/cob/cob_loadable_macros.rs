@@ -0,0 +1,219 @@
+use bevy_cobweb_ui::prelude::cob::*;
+use serde::Deserialize;
+
+use super::helpers::{
+    test_cob, test_cob_fail, test_compile, test_compiled_names, PlainStruct, SimpleStruct, SimpleTupleStruct,
+};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn defs_section_loadable_macros()
+{
+    let res = test_cob(
+        b"#defs
+A!() = B
+",
+    );
+    let CobSection::Defs(defs) = &res.sections[0] else { unreachable!() };
+    assert_eq!(defs.entries.len(), 1);
+    let CobDefEntry::LoadableMacro(def) = &defs.entries[0] else { unreachable!() };
+    assert_eq!(def.name.as_str(), "A");
+    assert_eq!(def.params.entries.len(), 0);
+    let CobLoadableMacroValue::Loadable(loadable) = &def.value else { unreachable!() };
+    assert_eq!(loadable.id.to_canonical(None), "B");
+
+    let res = test_cob(
+        b"
+#defs
+A!(@x) = B(@x)
+C!(@x @y=10 ..@z) = \\
+    D{ a:@x b:@y? ..@z }
+    E<F>
+    a::G!(@x)
+\\
+H!() = \\ A!(1) B \\
+I!() = A!(1)
+",
+    );
+    let CobSection::Defs(defs) = &res.sections[0] else { unreachable!() };
+    assert_eq!(defs.entries.len(), 4);
+
+    let CobDefEntry::LoadableMacro(def) = &defs.entries[0] else { unreachable!() };
+    let CobLoadableMacroValue::Loadable(loadable) = &def.value else { unreachable!() };
+    let CobLoadableVariant::Tuple(tuple) = &loadable.variant else { unreachable!() };
+    let CobValue::MacroParam(param) = &tuple.entries[0] else { unreachable!() };
+    assert_eq!(param.name.as_str(), "x");
+
+    let CobDefEntry::LoadableMacro(def) = &defs.entries[1] else { unreachable!() };
+    assert_eq!(def.name.as_str(), "C");
+    assert_eq!(def.params.entries.len(), 3);
+    let CobLoadableMacroValue::Group(group) = &def.value else { unreachable!() };
+    assert_eq!(group.entries.len(), 3);
+    let CobLoadableGroupEntry::Loadable(loadable) = &group.entries[1] else { unreachable!() };
+    assert_eq!(loadable.id.to_canonical(None), "E<F>");
+    let CobLoadableGroupEntry::LoadableMacroCall(call) = &group.entries[2] else { unreachable!() };
+    assert_eq!(call.path.as_str(), "a::G");
+    assert_eq!(call.args.entries.len(), 1);
+
+    let CobDefEntry::LoadableMacro(def) = &defs.entries[2] else { unreachable!() };
+    let CobLoadableMacroValue::Group(group) = &def.value else { unreachable!() };
+    assert_eq!(group.entries.len(), 2);
+    let CobLoadableGroupEntry::LoadableMacroCall(call) = &group.entries[0] else { unreachable!() };
+    assert_eq!(call.path.as_str(), "A");
+
+    let CobDefEntry::LoadableMacro(def) = &defs.entries[3] else { unreachable!() };
+    let CobLoadableMacroValue::LoadableMacroCall(call) = &def.value else { unreachable!() };
+    assert_eq!(call.path.as_str(), "A");
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn loadable_macro_calls()
+{
+    let res = test_cob(
+        b"#commands
+A!()
+B
+a::b::C!(1 x: 2)
+",
+    );
+    let CobSection::Commands(commands) = &res.sections[0] else { unreachable!() };
+    assert_eq!(commands.entries.len(), 3);
+    let CobCommandEntry::LoadableMacroCall(call) = &commands.entries[0] else { unreachable!() };
+    assert_eq!(call.path.as_str(), "A");
+    assert_eq!(call.args.entries.len(), 0);
+    let CobCommandEntry::Loadable(loadable) = &commands.entries[1] else { unreachable!() };
+    assert_eq!(loadable.id.to_canonical(None), "B");
+    let CobCommandEntry::LoadableMacroCall(call) = &commands.entries[2] else { unreachable!() };
+    assert_eq!(call.path.as_str(), "a::b::C");
+    assert_eq!(call.args.entries.len(), 2);
+
+    let res = test_cob(
+        b"#scenes
+\"a\"
+    A!(#FF0000)
+    B
+    \"b\"
+        ui::C!( x: 10px )
+",
+    );
+    let CobSection::Scenes(scenes) = &res.sections[0] else { unreachable!() };
+    assert_eq!(scenes.scenes[0].entries.len(), 3);
+    let CobSceneLayerEntry::LoadableMacroCall(call) = &scenes.scenes[0].entries[0] else { unreachable!() };
+    assert_eq!(call.path.as_str(), "A");
+    let CobSceneLayerEntry::Loadable(loadable) = &scenes.scenes[0].entries[1] else { unreachable!() };
+    assert_eq!(loadable.id.to_canonical(None), "B");
+    let CobSceneLayerEntry::Layer(layer) = &scenes.scenes[0].entries[2] else { unreachable!() };
+    let CobSceneLayerEntry::LoadableMacroCall(call) = &layer.entries[0] else { unreachable!() };
+    assert_eq!(call.path.as_str(), "ui::C");
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn loadable_macro_errors()
+{
+    // Def not starting on newline
+    test_cob_fail(
+        b"#defs
+ A!() = B",
+        b"A!() = B",
+    );
+    // Def without params
+    test_cob_fail(
+        b"#defs
+A! = B
+",
+        b"A! = B\n",
+    );
+    // Def body is not a loadable
+    test_cob_fail(
+        b"#defs
+A!() = 10
+",
+        b"A!() = 10\n",
+    );
+    // Call without args
+    test_cob_fail(
+        b"#commands
+A!
+",
+        b"A!\n",
+    );
+    // Call not starting on newline
+    test_cob_fail(
+        b"#commands
+A!() B!()
+",
+        b"B!()\n",
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn loadable_macro_extraction()
+{
+    let file = test_compile(
+        "#import
+lib as lib
+
+#defs
+Pair!(@a @b = 2) = \\
+    SimpleStruct{a:@a b:@b}
+    PlainStruct{boolean:true}
+\\
+Wrapped!(@a) = \\
+    Pair!(@a 3)
+    UnitStruct
+\\
+
+#commands
+Pair!(1)
+lib::Twice!(5)
+
+#commands
+SimpleStruct{a:7 b:7}
+
+#scenes
+\"scene\"
+    Pair!(1)
+    SimpleStruct{a:9 b:9}
+    \"child\"
+        Wrapped!(4)
+        PlainStruct{boolean:false}
+",
+        &[(
+            "lib.cob",
+            "#manifest\nself as lib\n\n#defs\nTwice!(@x) = SimpleTupleStruct(@x @x)\n",
+        )],
+    );
+
+    // Commands: macros expand in place, including imported macros. Later commands override earlier ones when
+    // applied.
+    let commands = &file.commands;
+    assert_eq!(
+        test_compiled_names(commands),
+        ["SimpleStruct", "PlainStruct", "SimpleTupleStruct", "SimpleStruct"]
+    );
+    assert_eq!(SimpleStruct::deserialize(&commands[0].loadable).unwrap(), SimpleStruct { a: 1, b: 2 });
+    assert_eq!(SimpleTupleStruct::deserialize(&commands[2].loadable).unwrap(), SimpleTupleStruct(5, 5));
+    assert_eq!(SimpleStruct::deserialize(&commands[3].loadable).unwrap(), SimpleStruct { a: 7, b: 7 });
+
+    // Scene nodes: loadables written after a macro call override the loadables it provides.
+    let scene = &file.scenes[0];
+    assert_eq!(test_compiled_names(&scene.loadables), ["PlainStruct", "SimpleStruct"]);
+    assert_eq!(PlainStruct::deserialize(&scene.loadables[0].loadable).unwrap(), PlainStruct { boolean: true });
+    assert_eq!(SimpleStruct::deserialize(&scene.loadables[1].loadable).unwrap(), SimpleStruct { a: 9, b: 9 });
+
+    // Nested macro calls are expanded with the outer macro's args.
+    let child = &scene.children[0];
+    assert_eq!(child.name, "child");
+    assert_eq!(test_compiled_names(&child.loadables), ["SimpleStruct", "UnitStruct", "PlainStruct"]);
+    assert_eq!(SimpleStruct::deserialize(&child.loadables[0].loadable).unwrap(), SimpleStruct { a: 4, b: 3 });
+    assert_eq!(PlainStruct::deserialize(&child.loadables[2].loadable).unwrap(), PlainStruct { boolean: false });
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+use bevy::reflect::serde::TypedReflectDeserializer;
+use bevy_cobweb_ui::prelude::cob::*;
+use bevy_cobweb_ui::prelude::*;
+use serde::de::DeserializeSeed;
+use serde::Deserialize;
+
+use super::helpers::{test_cob, test_constant, test_span};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn resolve(value: &str) -> CobValue
+{
+    let mut value = test_constant(value);
+    value.resolve(&ConstantsBuffer::default()).unwrap();
+    value
+}
+
+fn write(value: &CobValue) -> String
+{
+    let mut buff = Vec::<u8>::default();
+    value
+        .write_to(&mut DefaultRawSerializer::new(&mut buff))
+        .unwrap();
+    String::from_utf8(buff).unwrap()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn localized_parsing()
+{
+    test_cob(
+        b"#scenes
+\"menu\"
+    TextLine{text:loc(\"menu-play\")}
+    \"\"
+        TextLine{text:loc( \"menu-play\" count:$n /* players */ name: $m )}
+",
+    );
+
+    let CobValue::Localized(localized) = resolve("loc(\"menu-play\" count:3 name:\"Ann\" ready:true)") else {
+        unreachable!()
+    };
+    assert_eq!(localized.id.as_str(), "menu-play");
+    assert_eq!(localized.args.len(), 3);
+    assert_eq!(localized.request().unwrap(), "menu-play?count=3&name=Ann&ready=true");
+
+    // Arguments must be strings, numbers, or bools.
+    let CobValue::Localized(localized) = resolve("loc(\"menu-play\" count:[1])") else { unreachable!() };
+    assert!(localized.request().is_err());
+
+    // A message ID and named arguments are required.
+    assert!(Cob::parse(test_span("#defs\n$a = loc(count:3)\n")).is_err());
+    assert!(Cob::parse(test_span("#defs\n$a = loc(\"menu-play\" 3)\n")).is_err());
+    assert!(Cob::parse(test_span("#defs\n$a = loc(\"menu-play\"count:3)\n")).is_err());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn localized_deserialization()
+{
+    let value = resolve("loc(\"menu-play\" count:3)");
+    assert_eq!(
+        TextContent::deserialize(&value).unwrap(),
+        TextContent::localized("menu-play?count=3")
+    );
+    assert_eq!(String::deserialize(&value).unwrap(), "menu-play?count=3");
+
+    // Plain strings stay plain.
+    let plain = resolve("\"menu-play\"");
+    assert_eq!(TextContent::deserialize(&plain).unwrap(), TextContent::from("menu-play"));
+
+    // Reflection uses the same deserializer.
+    let mut app = App::new();
+    app.register_type::<TextContent>();
+    let type_registry = app.world().resource::<AppTypeRegistry>().read();
+    let registration = type_registry
+        .get(std::any::TypeId::of::<TextContent>())
+        .unwrap();
+    let reflected = TypedReflectDeserializer::new(registration, &type_registry)
+        .deserialize(&value)
+        .unwrap();
+    assert_eq!(
+        TextContent::from_reflect(reflected.as_partial_reflect()).unwrap(),
+        TextContent::localized("menu-play?count=3")
+    );
+
+    // Serializing writes `loc()` again.
+    let extracted = CobValue::extract(&TextContent::localized("menu-play?count=3&name=Ann")).unwrap();
+    assert_eq!(write(&extracted), "loc(\"menu-play\" count:3 name:\"Ann\")");
+    let extracted = CobValue::extract(&TextContent::from("menu-play")).unwrap();
+    assert_eq!(write(&extracted), "\"menu-play\"");
+}
+
+//-------------------------------------------------------------------------------------------------------------------
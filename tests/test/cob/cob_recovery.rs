@@ -0,0 +1,218 @@
+use bevy_cobweb_ui::prelude::cob::*;
+use bevy_cobweb_ui::prelude::ScenePath;
+
+use super::helpers::{test_cob, test_span};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn recover_all_errors()
+{
+    let raw = "#commands
+A{ width 10px }
+B
+
+#scenes
+\"a\"
+    C{ x: }
+    D
+    \"b\"
+        E(
+\"c\"
+    F
+";
+    assert!(Cob::parse(test_span(raw)).is_err());
+
+    let recovery = Cob::parse_recoverable(test_span(raw)).unwrap();
+    assert!(!recovery.is_ok());
+    assert_eq!(recovery.diagnostics.len(), 3);
+    assert_eq!(recovery.diagnostics[0].message, "expected `:` after struct field `width`");
+    assert_eq!(recovery.diagnostics[1].location.as_ref().unwrap().line, 7);
+    assert_eq!(recovery.broken_nodes, vec![ScenePath::new("a"), ScenePath::new("a::b")]);
+
+    let cob = recovery.cob;
+    let CobSection::Commands(commands) = &cob.sections[0] else { unreachable!() };
+    assert_eq!(commands.entries.len(), 1);
+    let CobCommandEntry::Loadable(loadable) = &commands.entries[0] else { unreachable!() };
+    assert_eq!(loadable.id.to_canonical(None), "B");
+
+    let CobSection::Scenes(scenes) = &cob.sections[1] else { unreachable!() };
+    assert_eq!(scenes.scenes.len(), 2);
+    assert_eq!(scenes.scenes[0].entries.len(), 2);
+    let CobSceneLayerEntry::Loadable(loadable) = &scenes.scenes[0].entries[0] else { unreachable!() };
+    assert_eq!(loadable.id.to_canonical(None), "D");
+    let CobSceneLayerEntry::Layer(layer) = &scenes.scenes[0].entries[1] else { unreachable!() };
+    assert_eq!(layer.entries.len(), 0);
+    assert_eq!(scenes.scenes[1].name.as_str(), "c");
+    assert_eq!(scenes.scenes[1].entries.len(), 1);
+
+    // Invalid section content is skipped until the next section.
+    let recovery = Cob::parse_recoverable(test_span(
+        "#scenes
+\"a\"
+    A
+1 2 3
+#commands
+B
+",
+    ))
+    .unwrap();
+    assert_eq!(recovery.diagnostics.len(), 1);
+    assert_eq!(recovery.cob.sections.len(), 2);
+    let CobSection::Commands(commands) = &recovery.cob.sections[1] else { unreachable!() };
+    assert_eq!(commands.entries.len(), 1);
+
+    // Valid files don't produce diagnostics.
+    let recovery = Cob::parse_recoverable(test_span("#scenes\n\"a\"\n    A\n")).unwrap();
+    assert!(recovery.is_ok());
+    assert!(recovery.broken_nodes.is_empty());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn restore_broken_nodes()
+{
+    let prev = test_cob(
+        b"#scenes
+\"a\"
+    A
+    \"b\"
+        B
+",
+    );
+    let recovery = Cob::parse_recoverable(test_span(
+        "#scenes
+\"a\"
+    A
+    \"b\"
+        B{ x }
+",
+    ))
+    .unwrap();
+    assert_eq!(recovery.broken_nodes, vec![ScenePath::new("a::b")]);
+
+    let mut cob = recovery.cob;
+    let path = ScenePath::new("a::b");
+    assert_eq!(cob.get_scene_layer(&path).unwrap().entries.len(), 0);
+    cob.restore_scene_nodes(&prev, &recovery.broken_nodes);
+    assert_eq!(cob.get_scene_layer(&path), prev.get_scene_layer(&path));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn restore_broken_scene_roots()
+{
+    let prev = test_cob(
+        b"#scenes
+\"a\"
+    A
+\"b\"
+    B
+\"c\"
+    C
+",
+    );
+    let recovery = Cob::parse_recoverable(test_span(
+        "#scenes
+\"a\"
+    A2
+\"b\" : 1
+    B
+\"c\"
+    C
+",
+    ))
+    .unwrap();
+    assert_eq!(recovery.broken_nodes, vec![ScenePath::new("b")]);
+
+    let mut cob = recovery.cob;
+    assert!(cob.get_scene_layer(&ScenePath::new("b")).is_none());
+    cob.restore_scene_nodes(&prev, &recovery.broken_nodes);
+
+    let CobSection::Scenes(scenes) = &cob.sections[0] else { unreachable!() };
+    let names: Vec<&str> = scenes.scenes.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["a", "b", "c"]);
+    let CobSceneLayerEntry::Loadable(loadable) = &scenes.scenes[0].entries[0] else { unreachable!() };
+    assert_eq!(loadable.id.to_canonical(None), "A2");
+    let path = ScenePath::new("b");
+    assert_eq!(cob.get_scene_layer(&path), prev.get_scene_layer(&path));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn restore_only_broken_children()
+{
+    let prev = test_cob(
+        b"#scenes
+\"a\"
+    A
+    \"b\"
+        B
+    \"c\"
+        C
+",
+    );
+
+    // Only the node with the invalid start is restored.
+    let recovery = Cob::parse_recoverable(test_span(
+        "#scenes
+\"a\"
+    A
+    \"b\" : \"x\"
+        B2
+    \"c\"
+        C2
+    \"d\"
+        D
+",
+    ))
+    .unwrap();
+    assert_eq!(recovery.broken_nodes, vec![ScenePath::new("a::b")]);
+
+    let mut cob = recovery.cob;
+    cob.restore_scene_nodes(&prev, &recovery.broken_nodes);
+    let a = cob.get_scene_layer(&ScenePath::new("a")).unwrap();
+    let children: Vec<&str> = a
+        .entries
+        .iter()
+        .filter_map(|entry| match entry {
+            CobSceneLayerEntry::Layer(layer) => Some(layer.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(children, vec!["b", "c", "d"]);
+    let b = ScenePath::new("a::b");
+    assert_eq!(cob.get_scene_layer(&b), prev.get_scene_layer(&b));
+    let c = cob.get_scene_layer(&ScenePath::new("a::c")).unwrap();
+    let CobSceneLayerEntry::Loadable(loadable) = &c.entries[0] else { unreachable!() };
+    assert_eq!(loadable.id.to_canonical(None), "C2");
+
+    // Restoring a parent keeps changes to its children.
+    let recovery = Cob::parse_recoverable(test_span(
+        "#scenes
+\"a\"
+    A{ x }
+    \"b\"
+        B2
+    \"c\"
+        C
+",
+    ))
+    .unwrap();
+    assert_eq!(recovery.broken_nodes, vec![ScenePath::new("a")]);
+
+    let mut cob = recovery.cob;
+    cob.restore_scene_nodes(&prev, &recovery.broken_nodes);
+    let a = cob.get_scene_layer(&ScenePath::new("a")).unwrap();
+    assert_eq!(a.entries.len(), 3);
+    let CobSceneLayerEntry::Loadable(loadable) = &a.entries[0] else { unreachable!() };
+    assert_eq!(loadable.id.to_canonical(None), "A");
+    let b = cob.get_scene_layer(&ScenePath::new("a::b")).unwrap();
+    let CobSceneLayerEntry::Loadable(loadable) = &b.entries[0] else { unreachable!() };
+    assert_eq!(loadable.id.to_canonical(None), "B2");
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,152 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::reflect::TypePath;
+use bevy::render::settings::WgpuSettings;
+use bevy::render::RenderPlugin;
+use bevy::window::ExitCondition;
+use bevy::winit::WinitPlugin;
+use bevy_cobweb::prelude::*;
+use bevy_cobweb_ui::prelude::cob::CobFile;
+use bevy_cobweb_ui::prelude::*;
+
+use super::helpers::{PlainStruct, SerdeTypesPlugin, SimpleStruct};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[derive(Resource, Default)]
+struct Reports(Vec<CobReloadReport>);
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Makes a headless app that loads `main.cob` from `assets`.
+fn reload_test_app(assets: &Path) -> App
+{
+    let mut app = App::new();
+    app.add_plugins(
+        DefaultPlugins
+            .build()
+            .disable::<WinitPlugin>()
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                ..default()
+            })
+            .set(RenderPlugin {
+                render_creation: WgpuSettings { backends: None, ..default() }.into(),
+                ..default()
+            })
+            .set(AssetPlugin {
+                file_path: assets.to_string_lossy().into(),
+                // Files are reloaded manually.
+                watch_for_changes_override: Some(false),
+                ..default()
+            }),
+    )
+    .add_plugins(CobwebUiPlugin)
+    .add_plugins(SerdeTypesPlugin)
+    .load("main.cob")
+    .insert_resource(CobReloadReporting { enabled: true, log: false })
+    .init_resource::<Reports>()
+    .add_reactor(
+        broadcast::<CobReloadReport>(),
+        |event: BroadcastEvent<CobReloadReport>, mut reports: ResMut<Reports>| {
+            let Some(report) = event.try_read() else { return };
+            reports.0.push(report.clone());
+        },
+    );
+    app.finish();
+    app.cleanup();
+    app
+}
+
+/// Updates the app until `done` returns `true`.
+fn update_until(app: &mut App, done: impl Fn(&World) -> bool)
+{
+    for _ in 0..500 {
+        app.update();
+        if done(app.world()) {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    panic!("timed out waiting for the app to load");
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn reload_report_lists_changes()
+{
+    let assets: PathBuf = std::env::temp_dir().join(format!("cob_reload_report_{}", std::process::id()));
+    std::fs::create_dir_all(&assets).unwrap();
+    std::fs::write(
+        assets.join("main.cob"),
+        "#scenes
+\"scene\"
+    PlainStruct{boolean:false}
+",
+    )
+    .unwrap();
+
+    let mut app = reload_test_app(&assets);
+    update_until(&mut app, |w| *w.resource::<State<LoadState>>().get() == LoadState::Done);
+
+    // Load the scene node onto an entity.
+    let entity = app.world_mut().spawn_empty().id();
+    app.world_mut()
+        .commands()
+        .entity(entity)
+        .load(SceneRef::from(("main.cob", "scene")));
+    app.update();
+    assert!(app.world().resource::<Reports>().0.is_empty());
+
+    // Change a loadable and add another one.
+    std::fs::write(
+        assets.join("main.cob"),
+        "#scenes
+\"scene\"
+    PlainStruct{boolean:true}
+    SimpleStruct{a:1 b:2}
+",
+    )
+    .unwrap();
+    app.world().resource::<AssetServer>().reload("main.cob");
+    update_until(&mut app, |w| !w.resource::<Reports>().0.is_empty());
+
+    let reports = &app.world().resource::<Reports>().0;
+    assert_eq!(reports.len(), 1);
+    let report = &reports[0];
+    let node = SceneRef::from(("main.cob", "scene"));
+
+    assert_eq!(report.files, vec![CobFile::try_new("main.cob").unwrap()]);
+    assert!(report.commands.is_empty());
+    assert!(report.nodes_added.is_empty());
+    assert!(report.nodes_removed.is_empty());
+    assert_eq!(
+        report.loadables,
+        vec![
+            CobReloadedLoadable {
+                node: node.clone(),
+                type_path: PlainStruct::type_path().into(),
+                change: CobLoadableChange::Updated,
+            },
+            CobReloadedLoadable {
+                node: node.clone(),
+                type_path: SimpleStruct::type_path().into(),
+                change: CobLoadableChange::Added,
+            },
+        ]
+    );
+    // The changed loadable is reverted before the entity is updated.
+    assert_eq!(
+        report.reverted,
+        vec![CobRevertedLoadable { entity, type_path: PlainStruct::type_path().into() }]
+    );
+    assert_eq!(report.entities, vec![entity]);
+
+    std::fs::remove_dir_all(&assets).unwrap();
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,188 @@
+use bevy_cobweb_ui::prelude::cob::*;
+use serde::Deserialize;
+
+use super::helpers::{
+    test_cob, test_cob_fail, test_compile, test_compiled_names, PlainStruct, SimpleStruct, SimpleTupleStruct,
+};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn scene_bases()
+{
+    let res = test_cob(
+        b"#scenes
+\"a\"
+    A
+    \"x\"
+        B
+\"b\" : \"a\"
+    -A
+    \"x\"
+        C
+        -B<D>
+\"c\":ui.menus::\"frame\"
+\"d\" /* base */ : /* key */ menus::\"\"
+",
+    );
+    let CobSection::Scenes(scenes) = &res.sections[0] else { unreachable!() };
+    assert_eq!(scenes.scenes.len(), 4);
+    assert!(scenes.scenes[0].base.is_none());
+
+    let base = scenes.scenes[1].base.as_ref().unwrap();
+    assert!(base.file.is_none());
+    assert_eq!(base.name.as_str(), "a");
+    assert_eq!(scenes.scenes[1].entries.len(), 2);
+    let CobSceneLayerEntry::LoadableRemoval(removal) = &scenes.scenes[1].entries[0] else { unreachable!() };
+    assert_eq!(removal.id.to_canonical(None), "A");
+    let CobSceneLayerEntry::Layer(layer) = &scenes.scenes[1].entries[1] else { unreachable!() };
+    let CobSceneLayerEntry::LoadableRemoval(removal) = &layer.entries[1] else { unreachable!() };
+    assert_eq!(removal.id.to_canonical(None), "B<D>");
+
+    let base = scenes.scenes[2].base.as_ref().unwrap();
+    assert_eq!(base.file.as_ref().unwrap().as_str(), "ui.menus");
+    assert_eq!(base.name.as_str(), "frame");
+    assert_eq!(scenes.scenes[2].entries.len(), 0);
+
+    let base = scenes.scenes[3].base.as_ref().unwrap();
+    assert_eq!(base.file.as_ref().unwrap().as_str(), "menus");
+    assert_eq!(base.name.as_str(), "");
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn scene_base_errors()
+{
+    // Only scene roots can have a base
+    test_cob_fail(
+        b"#scenes
+\"a\"
+    \"b\" : \"c\"
+        A
+",
+        b"\"b\" : \"c\"
+        A
+",
+    );
+    // Base on the next line
+    test_cob_fail(
+        b"#scenes
+\"a\" :
+    A
+",
+        b"A\n",
+    );
+    // Base without a scene name
+    test_cob_fail(
+        b"#scenes
+\"a\" : menus
+",
+        b"menus\n",
+    );
+    // Removal without a loadable
+    test_cob_fail(
+        b"#scenes
+\"a\" : \"b\"
+    -
+",
+        b"-\n",
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn scene_inheritance_extraction()
+{
+    let file = test_compile(
+        "#defs
+$val = 3
+
+#scenes
+\"base\"
+    SimpleStruct{a:1 b:$val}
+    PlainStruct{boolean:true}
+    \"child\"
+        UnitStruct
+\"copy\" : \"base\"
+\"derived\" : \"base\"
+    -PlainStruct
+    SimpleStruct{a:2 b:2}
+    \"child\"
+        SimpleTupleStruct(1 2)
+    \"extra\"
+        UnitStruct
+\"remote\" : lib::\"frame\"
+    \"body\"
+        PlainStruct{boolean:false}
+",
+        &[(
+            "lib.cob",
+            "#manifest
+self as lib
+
+#defs
+$width = 10
+
+#scenes
+\"frame\"
+    SimpleStruct{a:$width b:0}
+    \"body\"
+        UnitStruct
+",
+        )],
+    );
+    let names: Vec<&str> = file
+        .scenes
+        .iter()
+        .map(|scene| scene.name.as_str())
+        .collect();
+    assert_eq!(names, ["base", "copy", "derived", "remote"]);
+
+    // Base scenes are not changed by the scenes that derive from them.
+    let base = &file.scenes[0];
+    assert_eq!(test_compiled_names(&base.loadables), ["SimpleStruct", "PlainStruct"]);
+    assert_eq!(SimpleStruct::deserialize(&base.loadables[0].loadable).unwrap(), SimpleStruct { a: 1, b: 3 });
+    assert_eq!(base.children.len(), 1);
+    assert_eq!(test_compiled_names(&base.children[0].loadables), ["UnitStruct"]);
+
+    // A scene with no entries is a copy of its base.
+    let copy = &file.scenes[1];
+    assert_eq!(test_compiled_names(&copy.loadables), ["SimpleStruct", "PlainStruct"]);
+    assert_eq!(SimpleStruct::deserialize(&copy.loadables[0].loadable).unwrap(), SimpleStruct { a: 1, b: 3 });
+    assert_eq!(copy.children.len(), 1);
+    assert_eq!(copy.children[0].name, "child");
+    assert_eq!(test_compiled_names(&copy.children[0].loadables), ["UnitStruct"]);
+
+    // Derived loadables override base loadables, `-Loadable` removes base loadables, children with the same name
+    // are merged, and new children are appended.
+    let derived = &file.scenes[2];
+    assert_eq!(test_compiled_names(&derived.loadables), ["SimpleStruct"]);
+    assert_eq!(SimpleStruct::deserialize(&derived.loadables[0].loadable).unwrap(), SimpleStruct { a: 2, b: 2 });
+    let children: Vec<&str> = derived
+        .children
+        .iter()
+        .map(|child| child.name.as_str())
+        .collect();
+    assert_eq!(children, ["child", "extra"]);
+    assert_eq!(test_compiled_names(&derived.children[0].loadables), ["UnitStruct", "SimpleTupleStruct"]);
+    assert_eq!(
+        SimpleTupleStruct::deserialize(&derived.children[0].loadables[1].loadable).unwrap(),
+        SimpleTupleStruct(1, 2)
+    );
+    assert_eq!(test_compiled_names(&derived.children[1].loadables), ["UnitStruct"]);
+
+    // Bases in other files are resolved with the constants of their own file.
+    let remote = &file.scenes[3];
+    assert_eq!(test_compiled_names(&remote.loadables), ["SimpleStruct"]);
+    assert_eq!(SimpleStruct::deserialize(&remote.loadables[0].loadable).unwrap(), SimpleStruct { a: 10, b: 0 });
+    assert_eq!(remote.children.len(), 1);
+    assert_eq!(test_compiled_names(&remote.children[0].loadables), ["UnitStruct", "PlainStruct"]);
+    assert_eq!(
+        PlainStruct::deserialize(&remote.children[0].loadables[1].loadable).unwrap(),
+        PlainStruct { boolean: false }
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
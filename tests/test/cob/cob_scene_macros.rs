@@ -0,0 +1,269 @@
+use bevy_cobweb_ui::prelude::cob::*;
+use bevy_cobweb_ui::prelude::CompiledSceneNode;
+use serde::Deserialize;
+
+use super::helpers::{
+    test_cob, test_cob_fail, test_compile, test_compiled_names, PlainStruct, SimpleStruct, SimpleTupleStruct,
+};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn defs_section_scene_macros()
+{
+    let res = test_cob(
+        b"#defs
++a() = \\
+    A
+\\
+",
+    );
+    let CobSection::Defs(defs) = &res.sections[0] else { unreachable!() };
+    assert_eq!(defs.entries.len(), 1);
+    let CobDefEntry::SceneMacro(def) = &defs.entries[0] else { unreachable!() };
+    assert_eq!(def.name.as_str(), "a");
+    assert_eq!(def.params.entries.len(), 0);
+    assert_eq!(def.entries.len(), 1);
+    let CobSceneLayerEntry::Loadable(loadable) = &def.entries[0] else { unreachable!() };
+    assert_eq!(loadable.id.to_canonical(None), "A");
+
+    let res = test_cob(
+        b"
+#defs
++button(@color @size=10px) = \\
+    BackgroundColor(@color)
+    \"text\"
+        TextLine{ size: @size }
+        ..*
+    ..'content'
+    A!(1)
+    +a::b(1)
+        'x'
+            B
+            \"c\"
+\\
++empty() = \\
+    ..'content'
+\\
+",
+    );
+    let CobSection::Defs(defs) = &res.sections[0] else { unreachable!() };
+    assert_eq!(defs.entries.len(), 2);
+
+    let CobDefEntry::SceneMacro(def) = &defs.entries[0] else { unreachable!() };
+    assert_eq!(def.name.as_str(), "button");
+    assert_eq!(def.params.entries.len(), 2);
+    assert_eq!(def.entries.len(), 5);
+    let CobSceneLayerEntry::Layer(layer) = &def.entries[1] else { unreachable!() };
+    assert_eq!(layer.name.as_str(), "text");
+    assert_eq!(layer.entries.len(), 2);
+    let CobSceneLayerEntry::SceneMacroParam(param) = &layer.entries[1] else { unreachable!() };
+    assert!(param.name.is_none());
+    let CobSceneLayerEntry::SceneMacroParam(param) = &def.entries[2] else { unreachable!() };
+    assert_eq!(param.name.as_ref().unwrap().as_str(), "content");
+    let CobSceneLayerEntry::LoadableMacroCall(call) = &def.entries[3] else { unreachable!() };
+    assert_eq!(call.path.as_str(), "A");
+    let CobSceneLayerEntry::SceneMacroCall(call) = &def.entries[4] else { unreachable!() };
+    assert_eq!(call.path.as_str(), "a::b");
+    assert_eq!(call.args.entries.len(), 1);
+    assert_eq!(call.param_defs.len(), 1);
+    assert_eq!(call.param_defs[0].name.as_str(), "x");
+    assert_eq!(call.param_defs[0].entries.len(), 2);
+
+    let CobDefEntry::SceneMacro(def) = &defs.entries[1] else { unreachable!() };
+    assert_eq!(def.name.as_str(), "empty");
+    assert_eq!(def.entries.len(), 1);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn scene_macro_calls()
+{
+    let res = test_cob(
+        b"#scenes
+\"a\"
+    +button(#FF0000)
+    B
+    \"b\"
+        +ui::button(#FF0000 size: 20px)
+            'text'
+                TextLine{ text: \"Hello\" }
+            'content'
+                \"child\"
+                    C
+        D
+",
+    );
+    let CobSection::Scenes(scenes) = &res.sections[0] else { unreachable!() };
+    assert_eq!(scenes.scenes[0].entries.len(), 3);
+    let CobSceneLayerEntry::SceneMacroCall(call) = &scenes.scenes[0].entries[0] else { unreachable!() };
+    assert_eq!(call.path.as_str(), "button");
+    assert_eq!(call.param_defs.len(), 0);
+    let CobSceneLayerEntry::Loadable(loadable) = &scenes.scenes[0].entries[1] else { unreachable!() };
+    assert_eq!(loadable.id.to_canonical(None), "B");
+
+    let CobSceneLayerEntry::Layer(layer) = &scenes.scenes[0].entries[2] else { unreachable!() };
+    assert_eq!(layer.entries.len(), 2);
+    let CobSceneLayerEntry::SceneMacroCall(call) = &layer.entries[0] else { unreachable!() };
+    assert_eq!(call.path.as_str(), "ui::button");
+    assert_eq!(call.args.entries.len(), 2);
+    assert_eq!(call.param_defs.len(), 2);
+    assert_eq!(call.param_defs[0].name.as_str(), "text");
+    assert_eq!(call.param_defs[0].entries.len(), 1);
+    assert_eq!(call.param_defs[1].name.as_str(), "content");
+    let CobSceneLayerEntry::Layer(child) = &call.param_defs[1].entries[0] else { unreachable!() };
+    assert_eq!(child.name.as_str(), "child");
+    assert_eq!(child.entries.len(), 1);
+    let CobSceneLayerEntry::Loadable(loadable) = &layer.entries[1] else { unreachable!() };
+    assert_eq!(loadable.id.to_canonical(None), "D");
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn scene_macro_errors()
+{
+    // Def not starting on newline
+    test_cob_fail(
+        b"#defs
+ +a() = \\
+    A
+\\
+",
+        b"+a() = \\\n    A\n\\\n",
+    );
+    // Def body is not a scene group
+    test_cob_fail(
+        b"#defs
++a() = A
+",
+        b"+a() = A\n",
+    );
+    // Def body not terminated
+    test_cob_fail(
+        b"#defs
++a() = \\
+    A
+",
+        b"",
+    );
+    // Call without args
+    test_cob_fail(
+        b"#scenes
+\"a\"
+    +button
+",
+        b"+button\n",
+    );
+    // Non-param-def nested below a call
+    test_cob_fail(
+        b"#scenes
+\"a\"
+    +button()
+        A
+",
+        b"A\n",
+    );
+    // Invalid scene macro param
+    test_cob_fail(
+        b"#defs
++a() = \\
+    ..content
+\\
+",
+        b"..content\n\\\n",
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn child_names(node: &CompiledSceneNode) -> Vec<&str>
+{
+    node.children
+        .iter()
+        .map(|child| child.name.as_str())
+        .collect()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn scene_macro_extraction()
+{
+    let file = test_compile(
+        "#import
+lib as lib
+
+#defs
++wrapper(@a @b) = \\
+    +lib::card(@a @b)
+\\
+
+#scenes
+\"scene\"
+    +lib::card(1)
+        'header'
+            SimpleTupleStruct(1 2)
+            PlainStruct{boolean:false}
+        'body'
+            \"content\"
+                UnitStruct
+    \"footer\"
+        SimpleStruct{a:5 b:5}
+
+\"plain\"
+    +wrapper(b: 3 4)
+",
+        &[(
+            "lib.cob",
+            "#manifest
+self as lib
+
+#defs
++card(@a @b = 2) = \\
+    SimpleStruct{a:@a b:@b}
+    \"header\"
+        PlainStruct{boolean:true}
+        ..*
+    ..'body'
+    \"footer\"
+        UnitStruct
+\\
+",
+        )],
+    );
+    let scene = &file.scenes[0];
+
+    // The macro body is inserted into the node that calls it.
+    assert_eq!(test_compiled_names(&scene.loadables), ["SimpleStruct"]);
+    assert_eq!(SimpleStruct::deserialize(&scene.loadables[0].loadable).unwrap(), SimpleStruct { a: 1, b: 2 });
+    assert_eq!(child_names(scene), ["header", "content", "footer"]);
+
+    // `..*` is named after its enclosing node, and content inserted there overrides the macro's loadables.
+    let header = &scene.children[0];
+    assert_eq!(test_compiled_names(&header.loadables), ["SimpleTupleStruct", "PlainStruct"]);
+    assert_eq!(SimpleTupleStruct::deserialize(&header.loadables[0].loadable).unwrap(), SimpleTupleStruct(1, 2));
+    assert_eq!(PlainStruct::deserialize(&header.loadables[1].loadable).unwrap(), PlainStruct { boolean: false });
+
+    // Content for named insertion points is inserted where the macro's body places it.
+    let content = &scene.children[1];
+    assert_eq!(test_compiled_names(&content.loadables), ["UnitStruct"]);
+
+    // Nodes written next to the call are merged into the macro's nodes.
+    let footer = &scene.children[2];
+    assert_eq!(test_compiled_names(&footer.loadables), ["UnitStruct", "SimpleStruct"]);
+    assert_eq!(SimpleStruct::deserialize(&footer.loadables[1].loadable).unwrap(), SimpleStruct { a: 5, b: 5 });
+
+    // Calls nested in other macros, without content for the insertion points.
+    let plain = &file.scenes[1];
+    assert_eq!(SimpleStruct::deserialize(&plain.loadables[0].loadable).unwrap(), SimpleStruct { a: 4, b: 3 });
+    assert_eq!(child_names(plain), ["header", "footer"]);
+    assert_eq!(test_compiled_names(&plain.children[0].loadables), ["PlainStruct"]);
+    assert_eq!(
+        PlainStruct::deserialize(&plain.children[0].loadables[0].loadable).unwrap(),
+        PlainStruct { boolean: true }
+    );
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -0,0 +1,140 @@
+use bevy::prelude::*;
+use bevy_cobweb_ui::prelude::cob::*;
+use bevy_cobweb_ui::prelude::{validate, LoadableRegistry};
+
+use super::helpers::{prepare_test_app, test_cob, test_cob_fail, test_constant};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn themed(name: &str, default: &str, themes: &[(&str, &str)]) -> CobValue
+{
+    CobValue::Themed(CobThemedValue {
+        fill: CobFill::default(),
+        name: CobConstantName { name: name.into() },
+        default: Box::new(test_constant(default)),
+        themes: themes
+            .iter()
+            .map(|(theme, value)| ((*theme).into(), test_constant(value)))
+            .collect(),
+    })
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn theme_sections()
+{
+    let res = test_cob(
+        b"#defs
+$bg = #000000
+$gap = 4px
+
+#theme(light)
+$bg = #FFFFFF
+$gap = 2px
+
+#theme( high_contrast /* c */ )
+// comment
+$bg = #FF00FF
+",
+    );
+    assert_eq!(res.sections.len(), 3);
+    let CobSection::Theme(theme) = &res.sections[1] else { unreachable!() };
+    assert_eq!(theme.name, "light");
+    assert_eq!(theme.entries.len(), 2);
+    assert_eq!(theme.entries[1].name.as_str(), "gap");
+    let CobSection::Theme(theme) = &res.sections[2] else { unreachable!() };
+    assert_eq!(theme.name, "high_contrast");
+    assert_eq!(theme.entries.len(), 1);
+
+    // Themes in conditional blocks.
+    test_cob(
+        b"#if(platform = \"wasm\")
+#theme(light)
+$bg = #FFFFFF
+#endif
+",
+    );
+
+    // Empty theme.
+    test_cob(b"#theme(dark)\n");
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn theme_section_errors()
+{
+    // Missing name
+    test_cob_fail(b"#theme\n$a = 1\n", b"\n$a = 1\n");
+    test_cob_fail(b"#theme()\n$a = 1\n", b")\n$a = 1\n");
+    // Name must be snake-case
+    test_cob_fail(b"#theme(Dark)\n$a = 1\n", b"Dark)\n$a = 1\n");
+    // Macros can't be themed
+    test_cob_fail(b"#theme(dark)\nmac!() = 1\n", b"mac!() = 1\n");
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn theme_resolution()
+{
+    let mut value = themed("bg", "#000000", &[("light", "#FFFFFF")]);
+    assert!(value.resolve_theme(Some("light")).unwrap());
+    assert_eq!(value, test_constant("#FFFFFF"));
+
+    // Unknown themes and no theme use the default value.
+    let mut value = themed("bg", "#000000", &[("light", "#FFFFFF")]);
+    assert!(value.resolve_theme(Some("sepia")).unwrap());
+    assert_eq!(value, test_constant("#000000"));
+    let mut value = themed("bg", "#000000", &[("light", "#FFFFFF")]);
+    assert!(value.resolve_theme(None).unwrap());
+    assert_eq!(value, test_constant("#000000"));
+
+    // Values without themed constants are unchanged.
+    let mut value = test_constant("{a:1 b:[2 3]}");
+    assert!(!value.resolve_theme(Some("light")).unwrap());
+    assert_eq!(value, test_constant("{a:1 b:[2 3]}"));
+
+    // Expressions and function calls are evaluated once the theme is known.
+    let CobValue::Function(mut call) = test_constant("max(1px 2px)") else { unreachable!() };
+    call.args.entries[1] = themed("gap", "2px", &[("compact", "0px")]);
+    let mut value = CobValue::Function(call);
+    assert_eq!(value.resolve_theme(Some("compact")), Ok(true));
+    let CobValue::Builtin(CobBuiltin::Val { val, .. }) = value else { unreachable!() };
+    assert_eq!(val, Val::Px(1.));
+
+    // Theme values can use other themed constants.
+    let mut value = themed("panel", "#000000", &[]);
+    let CobValue::Themed(panel) = &mut value else { unreachable!() };
+    *panel.default = themed("bg", "#000000", &[("light", "#FFFFFF")]);
+    assert!(value.resolve_theme(Some("light")).unwrap());
+    assert_eq!(value, test_constant("#FFFFFF"));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn theme_validation()
+{
+    let app = prepare_test_app();
+    let cob = test_cob(
+        b"#defs
+$bg = #000000
+
+#theme(light)
+$bg = #FFFFFF
+$bgg = #FFFFFF
+",
+    );
+    let type_registry = app.world().resource::<AppTypeRegistry>().read();
+    let diagnostics = validate(&cob, &type_registry, app.world().resource::<LoadableRegistry>());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "theme \"light\" overrides constant `$bgg` that isn't defined in this file"
+    );
+    assert_eq!(diagnostics[0].hint.as_deref(), Some("did you mean `$bg`?"));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
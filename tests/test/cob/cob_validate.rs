@@ -0,0 +1,215 @@
+use bevy::prelude::*;
+use bevy_cobweb_ui::prelude::cob::*;
+use bevy_cobweb_ui::prelude::{validate, LoadableRegistry, ScenePath};
+
+use super::helpers::{prepare_test_app, test_cob};
+
+//-------------------------------------------------------------------------------------------------------------------
+
+fn validate_raw(app: &App, raw: &[u8]) -> Vec<CobDiagnostic>
+{
+    let cob = test_cob(raw);
+    let type_registry = app.world().resource::<AppTypeRegistry>().read();
+    validate(&cob, &type_registry, app.world().resource::<LoadableRegistry>())
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn validate_loadables()
+{
+    let app = prepare_test_app();
+
+    // Valid
+    let diagnostics = validate_raw(
+        &app,
+        b"#commands
+PlainStruct{boolean:true}
+SimpleStruct{a:1 b:2}
+NewtypeEnum::X
+",
+    );
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+
+    // Unregistered loadable
+    let diagnostics = validate_raw(
+        &app,
+        b"#scenes
+\"a\"
+    PlainStrut{boolean:true}
+",
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "no loadable named `PlainStrut` was registered in the app");
+    assert_eq!(diagnostics[0].hint.as_deref(), Some("did you mean `PlainStruct`?"));
+    assert_eq!(diagnostics[0].scene_path, Some(ScenePath::new("a")));
+
+    // Unknown field
+    let diagnostics = validate_raw(
+        &app,
+        b"#commands
+SimpleStruct{a:1 c:2}
+",
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "`SimpleStruct` has no field named `c`");
+    assert_eq!(diagnostics[0].scene_path, Some(ScenePath::new("#commands")));
+
+    // Unknown enum variant
+    let diagnostics = validate_raw(
+        &app,
+        b"#commands
+NewtypeEnum::Y
+",
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "`NewtypeEnum` has no variant named `Y`");
+
+    // Wrong field type
+    let diagnostics = validate_raw(
+        &app,
+        b"#commands
+PlainStruct{boolean:10}
+",
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].message.starts_with("failed deserializing loadable `PlainStruct`"));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn validate_paths()
+{
+    let app = prepare_test_app();
+
+    // Valid
+    let diagnostics = validate_raw(
+        &app,
+        b"#import
+a as alias
+
+#defs
+$val = true
+
+#commands
+PlainStruct{boolean:$val}
+PlainStruct{boolean:$alias::val}
+",
+    );
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+
+    // Unresolved constant and alias
+    let diagnostics = validate_raw(
+        &app,
+        b"#import
+a as alias
+
+#defs
+$val = true
+
+#scenes
+\"a\"
+    PlainStruct{boolean:$vall}
+    \"b\"
+        PlainStruct{boolean:$alais::val}
+",
+    );
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].message, "constant `$vall` is not defined in this file");
+    assert_eq!(diagnostics[0].hint.as_deref(), Some("did you mean `val`?"));
+    assert_eq!(diagnostics[1].message, "unknown import alias `alais` in constant `$alais::val`");
+    assert_eq!(diagnostics[1].scene_path, Some(ScenePath::new("a::b")));
+
+    // Paths are not checked with unaliased imports.
+    let diagnostics = validate_raw(
+        &app,
+        b"#import
+a as _
+
+#commands
+PlainStruct{boolean:$val}
+",
+    );
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn validate_duplicate_nodes()
+{
+    let app = prepare_test_app();
+
+    let diagnostics = validate_raw(
+        &app,
+        b"#scenes
+\"a\"
+    \"b\"
+    \"\"
+    \"\"
+    \"b\"
+\"a\"
+",
+    );
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].message, "duplicate scene node `b`; sibling nodes must have unique names");
+    assert_eq!(diagnostics[0].scene_path, Some(ScenePath::new("a::b")));
+    assert_eq!(diagnostics[1].scene_path, Some(ScenePath::new("a")));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn validate_removals_without_base()
+{
+    let app = prepare_test_app();
+
+    // Removals in derived scenes, after macro calls, or after the same loadable are allowed.
+    let diagnostics = validate_raw(
+        &app,
+        b"#defs
++m() = \\
+    UnitStruct
+    \"e\"
+        UnitStruct
+\\
+
+#scenes
+\"a\"
+    PlainStruct{boolean:true}
+    -PlainStruct
+\"b\" : \"a\"
+    -PlainStruct
+    \"c\"
+        -UnitStruct
+\"d\"
+    +m()
+    -UnitStruct
+    \"e\"
+        -UnitStruct
+",
+    );
+    assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+
+    // Removals with nothing to remove.
+    let diagnostics = validate_raw(
+        &app,
+        b"#scenes
+\"a\"
+    -PlainStruct
+    PlainStruct{boolean:true}
+    \"b\"
+        -UnitStruct
+",
+    );
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(
+        diagnostics[0].message,
+        "removing `PlainStruct` has no effect because the scene has no base scene"
+    );
+    assert_eq!(diagnostics[0].scene_path, Some(ScenePath::new("a")));
+    assert_eq!(diagnostics[1].scene_path, Some(ScenePath::new("a::b")));
+}
+
+//-------------------------------------------------------------------------------------------------------------------
@@ -7,7 +7,7 @@ use bevy_cobweb_ui::prelude::*;
 use serde::de::DeserializeSeed;
 use serde::{Deserialize, Serialize};
 
-use crate::cob::helpers::test_span;
+use crate::cob::helpers::{prepare_test_app, test_span};
 
 //-------------------------------------------------------------------------------------------------------------------
 
@@ -256,6 +256,69 @@ pub fn test_cob(raw: &[u8]) -> Cob
 
 //-------------------------------------------------------------------------------------------------------------------
 
+/// Gets the values of the constants defined in a file's first section, which must be `#defs`.
+pub fn test_constant_values(cob: &Cob) -> Vec<CobValue>
+{
+    let CobSection::Defs(defs) = &cob.sections[0] else { unreachable!() };
+    defs.entries
+        .iter()
+        .map(|entry| {
+            let CobDefEntry::Constant(def) = entry else { unreachable!() };
+            let CobConstantValue::Value(value) = &def.value else { unreachable!() };
+            value.clone()
+        })
+        .collect()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Parses a raw COB value as a constant definition. The value is not resolved.
+pub fn test_constant(raw: &str) -> CobValue
+{
+    let cob = test_cob(format!("#defs\n$a = {raw}\n").as_bytes());
+    test_constant_values(&cob).remove(0)
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Compiles `main.cob` along with `sources` it can import, and gets the compiled `main.cob`.
+///
+/// Expects compilation to succeed.
+pub fn test_compile(main: &str, sources: &[(&str, &str)]) -> CompiledCobFile
+{
+    let app = prepare_test_app();
+    let mut compiler = CobCompiler::new("assets", ["main.cob"]);
+    compiler.add_source("main.cob", main);
+    for (file, content) in sources.iter() {
+        compiler.add_source(file, *content);
+    }
+
+    let type_registry = app.world().resource::<AppTypeRegistry>().read();
+    let loadables = app.world().resource::<LoadableRegistry>();
+    let compiled = match compiler.compile(&type_registry, loadables, &CobFlags::default()) {
+        Ok(compiled) => compiled,
+        Err(err) => panic!("{main}, ERR={err:?}"),
+    };
+    compiled
+        .files
+        .into_iter()
+        .find(|compiled| compiled.file.as_str() == "main.cob")
+        .unwrap()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
+/// Gets the short type names of compiled loadables, e.g. `SimpleStruct`.
+pub fn test_compiled_names(loadables: &[CompiledLoadable]) -> Vec<&str>
+{
+    loadables
+        .iter()
+        .map(|loadable| loadable.type_path.rsplit("::").next().unwrap())
+        .collect()
+}
+
+//-------------------------------------------------------------------------------------------------------------------
+
 /// Expects parsing a COB byte sequence to fail, with `remaining` bytes unparsed.
 pub fn test_cob_fail(raw: &[u8], remaining: &[u8])
 {
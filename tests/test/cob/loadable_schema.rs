@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+use bevy_cobweb_ui::prelude::{export_loadable_schema, LoadableRegistry};
+use serde_json::{json, Value};
+
+use super::helpers::prepare_test_app;
+
+//-------------------------------------------------------------------------------------------------------------------
+
+#[test]
+fn export_schema()
+{
+    let app = prepare_test_app();
+    let type_registry = app.world().resource::<AppTypeRegistry>().read();
+    let schema = export_loadable_schema(&type_registry, app.world().resource::<LoadableRegistry>());
+    let schema: Value = serde_json::from_str(&schema).unwrap();
+    let loadables = &schema["loadables"];
+
+    // Struct
+    let simple = &loadables["SimpleStruct"];
+    assert_eq!(simple["kind"], "instruction");
+    assert!(simple["type_path"].as_str().unwrap().ends_with("::SimpleStruct"));
+    assert_eq!(
+        simple["fields"],
+        json!([
+            { "name": "a", "type": "u32", "default": "0" },
+            { "name": "b", "type": "u32", "default": "0" }
+        ])
+    );
+    assert_eq!(loadables["PlainStruct"]["fields"][0]["default"], "false");
+
+    // Enum
+    let variants = &loadables["NewtypeEnum"]["variants"];
+    assert_eq!(variants[0]["name"], "Tuple");
+    assert_eq!(variants[0]["fields"], json!([{ "name": "0", "type": "()" }]));
+    assert_eq!(variants[1], json!({ "name": "X", "fields": [] }));
+
+    // Generic
+    assert!(loadables["SingleGeneric<u32>"].is_object());
+}
+
+//-------------------------------------------------------------------------------------------------------------------
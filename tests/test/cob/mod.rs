@@ -1,11 +1,31 @@
 pub mod helpers;
 
+mod cob_colors;
 mod cob_commands;
+mod cob_compiled;
+mod cob_conditional;
 mod cob_constants;
+mod cob_data_macros;
+mod cob_diagnostics;
+mod cob_editing;
+mod cob_expressions;
 mod cob_fill;
+mod cob_font_units;
+mod cob_format;
 mod cob_import;
+mod cob_index;
+mod cob_loadable_macros;
+mod cob_localized;
 mod cob_manifest;
+mod cob_recovery;
+#[cfg(feature = "hot_reload")]
+mod cob_reload_report;
+mod cob_scene_inheritance;
+mod cob_scene_macros;
 mod cob_scenes;
+mod cob_themes;
+mod cob_validate;
+mod loadable_schema;
 mod serde;
 
 //mod reflection_bug;  // Uses serde_json which is no longer a dependency.
@@ -95,6 +95,9 @@ fn numbers()
     // Lossy conversion: scientific notation
     test_equivalence_lossy(w, "FloatStruct(1e5)", "FloatStruct(100000)", FloatStruct(1e5f64));
     test_equivalence_lossy(w, "FloatStruct(-1e5)", "FloatStruct(-100000)", FloatStruct(-1e5f64));
+
+    // Lossy conversion: pixel values (e.g. resolved `rem` values)
+    test_equivalence_lossy(w, "FloatStruct(2px)", "FloatStruct(2)", FloatStruct(2.0f64));
 }
 
 //-------------------------------------------------------------------------------------------------------------------
@@ -499,6 +502,40 @@ fn builtins()
         "BuiltinColor(#FF0000)",
         BuiltinColor(Color::Srgba(Srgba::RED)),
     );
+
+    // 8-digit hex colors are RRGGBBAA
+    test_equivalence(
+        a.world(),
+        "BuiltinColor(#FF000080)",
+        "#FF000080",
+        BuiltinColor(Color::Srgba(Srgba::new(1., 0., 0., 128. / 255.))),
+    );
+
+    // Lossy conversion: other color syntax will be written as hex
+    test_equivalence_lossy(
+        a.world(),
+        "BuiltinColor(#F008)",
+        "BuiltinColor(#FF000088)",
+        BuiltinColor(Color::Srgba(Srgba::new(1., 0., 0., 136. / 255.))),
+    );
+    test_equivalence_lossy(
+        a.world(),
+        "BuiltinColor(gray)",
+        "BuiltinColor(#808080)",
+        BuiltinColor(Color::Srgba(bevy::color::palettes::basic::GRAY)),
+    );
+    test_equivalence_lossy(
+        a.world(),
+        "BuiltinColor(transparent)",
+        "BuiltinColor(#00000000)",
+        BuiltinColor(Color::Srgba(Srgba::NONE)),
+    );
+    test_equivalence_lossy(
+        a.world(),
+        "BuiltinColor(rgba(255, 0, 0, 0))",
+        "BuiltinColor(#FF000000)",
+        BuiltinColor(Color::Srgba(Srgba::new(1., 0., 0., 0.))),
+    );
 }
 
 //-------------------------------------------------------------------------------------------------------------------